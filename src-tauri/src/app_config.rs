@@ -48,6 +48,14 @@ pub struct AppConfig {
     #[serde(default)]
     pub quick_search: QuickSearchSettings,
 
+    // 参与联合搜索的其他工作区（当前工作区不在此列表中）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registered_workspaces: Vec<RegisteredWorkspace>,
+
+    // 只读的 Git 片段库（克隆到应用数据目录）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub library_sources: Vec<LibrarySource>,
+
     // 插件启用状态。官方功能默认需要安装本地插件包，核心功能除外。
     #[serde(default = "default_plugin_states")]
     pub plugins: PluginStates,
@@ -169,6 +177,46 @@ pub struct QuickSearchSettings {
     pub preview_visible: bool,
 }
 
+// 注册的工作区（联合搜索）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredWorkspace {
+    pub id: String,
+    // 搜索结果上显示的徽标名称
+    pub name: String,
+    pub path: String,
+    // 是否参与联合搜索
+    #[serde(default = "default_workspace_searchable")]
+    pub searchable: bool,
+}
+
+fn default_workspace_searchable() -> bool {
+    true
+}
+
+// 只读片段库（Git 仓库）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LibrarySource {
+    pub id: String,
+    pub name: String,
+    // 远程地址，支持 https、ssh 和本地 file://
+    pub remote_url: String,
+    // 跟踪的分支；为空时使用远程默认分支
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    // 自动拉取间隔（分钟），0 表示只手动拉取
+    #[serde(default = "default_library_pull_interval")]
+    pub pull_interval_minutes: u64,
+    // 是否参与联合搜索
+    #[serde(default = "default_workspace_searchable")]
+    pub searchable: bool,
+}
+
+fn default_library_pull_interval() -> u64 {
+    60
+}
+
 const HOST_PLUGIN_IDS: &[&str] = &[
     "translation",
     "screenshot",
@@ -246,6 +294,8 @@ impl Default for AppConfig {
             git: GitSettings::default(),
             editor: EditorSettings::default(),
             quick_search: QuickSearchSettings::default(),
+            registered_workspaces: Vec::new(),
+            library_sources: Vec::new(),
             plugins: default_plugin_states(),
             plugin_install_dir: None,
             // 兼容字段默认为 None
@@ -482,6 +532,50 @@ impl AppConfigManager {
         self.config.quick_search.preview_visible = visible;
     }
 
+    /// 获取注册的工作区
+    pub fn get_registered_workspaces(&self) -> &[RegisteredWorkspace] {
+        &self.config.registered_workspaces
+    }
+
+    /// 新增或更新注册的工作区（按 id 匹配）
+    pub fn upsert_registered_workspace(&mut self, workspace: RegisteredWorkspace) {
+        let workspaces = &mut self.config.registered_workspaces;
+        match workspaces.iter_mut().find(|w| w.id == workspace.id) {
+            Some(existing) => *existing = workspace,
+            None => workspaces.push(workspace),
+        }
+    }
+
+    /// 移除注册的工作区，返回是否存在
+    pub fn remove_registered_workspace(&mut self, id: &str) -> bool {
+        let workspaces = &mut self.config.registered_workspaces;
+        let before = workspaces.len();
+        workspaces.retain(|w| w.id != id);
+        workspaces.len() != before
+    }
+
+    /// 获取只读片段库
+    pub fn get_library_sources(&self) -> &[LibrarySource] {
+        &self.config.library_sources
+    }
+
+    /// 新增或更新只读片段库（按 id 匹配）
+    pub fn upsert_library_source(&mut self, source: LibrarySource) {
+        let sources = &mut self.config.library_sources;
+        match sources.iter_mut().find(|s| s.id == source.id) {
+            Some(existing) => *existing = source,
+            None => sources.push(source),
+        }
+    }
+
+    /// 移除只读片段库，返回是否存在
+    pub fn remove_library_source(&mut self, id: &str) -> bool {
+        let sources = &mut self.config.library_sources;
+        let before = sources.len();
+        sources.retain(|s| s.id != id);
+        sources.len() != before
+    }
+
     /// 更新主题
     pub fn update_theme(&mut self, theme: String) {
         self.config.theme = theme;
@@ -531,6 +625,9 @@ impl AppConfigManager {
             }
             "translation" => {
                 self.config.translation_engine = None;
+                self.config
+                    .extra
+                    .remove(crate::plugins::translation_providers::PROVIDERS_CONFIG_KEY);
                 self.config.offline_model_activated = None;
                 self.config.translate_hotkey = None;
                 self.config.selection_translate_hotkey = None;
@@ -898,7 +995,7 @@ pub fn apply_enabled_plugin_runtime_change(app_handle: &AppHandle, plugin_id: &s
 }
 
 pub fn ensure_enabled_plugin_storage(app_handle: &AppHandle) {
-    for plugin_id in ["local-launcher", "desktop-files", "search-engines", "todo"] {
+    for plugin_id in crate::db::PLUGINS_WITH_STORAGE.iter().copied() {
         if is_plugin_enabled(app_handle, plugin_id) {
             if let Err(e) = crate::db::ensure_plugin_storage(plugin_id) {
                 warn!("[Plugin] ensure storage for {} failed: {}", plugin_id, e);
@@ -947,22 +1044,31 @@ fn clear_workspace_plugin_config(app_handle: &AppHandle, plugin_id: &str) -> Res
 }
 
 fn clear_database_plugin_config(plugin_id: &str) -> Result<(), String> {
+    if plugin_id != "git-sync" && !plugin_has_database_storage(plugin_id) {
+        return Ok(());
+    }
+
+    let conn = crate::db::DbConnectionManager::get().map_err(|e| e.to_string())?;
+    clear_plugin_database(&conn, plugin_id)
+}
+
+fn clear_plugin_database(conn: &rusqlite::Connection, plugin_id: &str) -> Result<(), String> {
     if plugin_id == "git-sync" {
-        let conn = crate::db::DbConnectionManager::get().map_err(|e| e.to_string())?;
         conn.execute("DELETE FROM user_settings WHERE id = 1", [])
             .map_err(|e| e.to_string())?;
     }
 
-    if matches!(
-        plugin_id,
-        "local-launcher" | "desktop-files" | "search-engines" | "todo"
-    ) {
-        crate::db::clear_plugin_storage(plugin_id).map_err(|e| e.to_string())?;
+    if plugin_has_database_storage(plugin_id) {
+        crate::db::drop_plugin_tables(conn, plugin_id).map_err(|e| e.to_string())?;
     }
 
     Ok(())
 }
 
+fn plugin_has_database_storage(plugin_id: &str) -> bool {
+    crate::db::PLUGINS_WITH_STORAGE.contains(&plugin_id)
+}
+
 fn clear_app_plugin_config(app_handle: &AppHandle, plugin_id: &str) -> Result<(), String> {
     if let Some(config_state) = app_handle.try_state::<Arc<RwLock<AppConfigManager>>>() {
         let mut manager = config_state
@@ -1144,7 +1250,11 @@ pub fn update_git_settings_command(
     settings.remote_url = crate::git_common::remove_token_from_url(&settings.remote_url);
     settings.token.clear();
 
-    if settings.enabled && !has_required_git_sync_settings(&settings) {
+    // WebDAV、文件夹后端不需要 Git 身份和远程仓库
+    if settings.enabled
+        && crate::sync_backends::uses_git_backend(&app_handle)
+        && !has_required_git_sync_settings(&settings)
+    {
         return Err("启用 Git 同步前，请先在个人中心完成用户名、邮箱和远程仓库配置".to_string());
     }
 
@@ -1346,7 +1456,7 @@ fn normalized_existing_path(path: &Path) -> PathBuf {
     path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
 }
 
-fn same_path(left: &Path, right: &Path) -> bool {
+pub(crate) fn same_path(left: &Path, right: &Path) -> bool {
     path_to_display_string(&normalized_existing_path(left))
         .eq_ignore_ascii_case(&path_to_display_string(&normalized_existing_path(right)))
 }
@@ -3457,7 +3567,8 @@ pub fn set_plugin_enabled(
 #[cfg(test)]
 mod tests {
     use super::{
-        plugin_download_total_bytes, plugin_install_progress_percent, AppConfig, AppConfigManager,
+        plugin_download_total_bytes, plugin_has_database_storage, plugin_install_progress_percent,
+        AppConfig, AppConfigManager,
     };
     use std::path::PathBuf;
 
@@ -3485,6 +3596,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clearing_plugin_data_drops_local_ai_tables() {
+        assert!(plugin_has_database_storage("local-ai"));
+        assert!(plugin_has_database_storage("translation"));
+        assert!(!plugin_has_database_storage("git-sync"));
+
+        let path =
+            std::env::temp_dir().join(format!("plugin-clear-test-{}.db", uuid::Uuid::new_v4()));
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        crate::db::create_plugin_tables(&conn, "local-ai").unwrap();
+        crate::db::create_plugin_tables(&conn, "translation").unwrap();
+        let tables = |conn: &rusqlite::Connection| -> Vec<String> {
+            let mut stmt = conn
+                .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+                .unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        let local_ai_tables = [
+            "ai_chat_conversations",
+            "ai_chat_messages",
+            "ai_tag_suggestions",
+            "note_embeddings",
+        ];
+        let before = tables(&conn);
+        for table in local_ai_tables {
+            assert!(before.iter().any(|t| t == table), "缺少表 {}", table);
+        }
+
+        clear_plugin_database(&conn, "local-ai").unwrap();
+
+        // 全文索引表依赖 SQLite 是否编译了 FTS5，只检查清理后不存在
+        let after = tables(&conn);
+        for table in local_ai_tables.iter().chain(&["ai_chat_messages_fts"]) {
+            assert!(!after.iter().any(|t| t == table), "表 {} 未删除", table);
+        }
+        // 其他插件的表不受影响
+        assert!(after.iter().any(|t| t == "translation_memory"));
+
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn clearing_local_ai_config_removes_saved_chat_histories() {
         let mut config = AppConfig::default();
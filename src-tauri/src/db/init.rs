@@ -252,19 +252,59 @@ fn create_desktop_files_tables(conn: &rusqlite::Connection) -> Result<(), rusqli
     Ok(())
 }
 
+/// 打开插件存储所在的数据库连接（表在插件启用时由 ensure_plugin_storage 创建）
+pub fn open_plugin_store() -> Result<rusqlite::Connection, String> {
+    DbConnectionManager::get().map_err(|e| format!("打开数据库失败: {}", e))
+}
+
 pub fn ensure_plugin_storage(plugin_id: &str) -> Result<(), rusqlite::Error> {
     let conn = DbConnectionManager::get()?;
+    create_plugin_tables(&conn, plugin_id)
+}
+
+/// 在指定连接上创建插件拥有的数据表
+pub fn create_plugin_tables(
+    conn: &rusqlite::Connection,
+    plugin_id: &str,
+) -> Result<(), rusqlite::Error> {
     match plugin_id {
-        "local-launcher" => create_local_launcher_tables(&conn),
-        "desktop-files" => create_desktop_files_tables(&conn),
-        "search-engines" => create_search_engines_table(&conn),
-        "todo" => create_alarm_cards_table(&conn),
+        "local-launcher" => create_local_launcher_tables(conn),
+        "desktop-files" => create_desktop_files_tables(conn),
+        "search-engines" => create_search_engines_table(conn),
+        "todo" => create_alarm_cards_table(conn),
+        "local-ai" => {
+            crate::plugins::local_ai_embeddings::create_embedding_tables(conn)?;
+            crate::plugins::local_ai_tagging::create_tagging_tables(conn)?;
+            crate::plugins::local_ai_history::create_history_tables(conn)
+        }
+        "translation" => {
+            crate::plugins::translation_memory::create_memory_tables(conn)?;
+            crate::plugins::translation_notes::create_note_job_tables(conn)
+        }
         _ => Ok(()),
     }
 }
 
+/// 在数据库中有独立表的插件（与 clear_plugin_storage 的分支一一对应）
+pub const PLUGINS_WITH_STORAGE: &[&str] = &[
+    "local-launcher",
+    "desktop-files",
+    "search-engines",
+    "todo",
+    "local-ai",
+    "translation",
+];
+
 pub fn clear_plugin_storage(plugin_id: &str) -> Result<(), rusqlite::Error> {
     let conn = DbConnectionManager::get()?;
+    drop_plugin_tables(&conn, plugin_id)
+}
+
+/// 在指定连接上删除插件拥有的数据表，并使相关内存缓存失效
+pub fn drop_plugin_tables(
+    conn: &rusqlite::Connection,
+    plugin_id: &str,
+) -> Result<(), rusqlite::Error> {
     match plugin_id {
         "local-launcher" => {
             conn.execute("DROP TABLE IF EXISTS apps", [])?;
@@ -290,6 +330,19 @@ pub fn clear_plugin_storage(plugin_id: &str) -> Result<(), rusqlite::Error> {
         "todo" => {
             conn.execute("DROP TABLE IF EXISTS alarm_cards", [])?;
         }
+        "local-ai" => {
+            conn.execute("DROP TABLE IF EXISTS note_embeddings", [])?;
+            conn.execute("DROP TABLE IF EXISTS ai_tag_suggestions", [])?;
+            conn.execute("DROP TABLE IF EXISTS ai_chat_messages_fts", [])?;
+            conn.execute("DROP TABLE IF EXISTS ai_chat_messages", [])?;
+            conn.execute("DROP TABLE IF EXISTS ai_chat_conversations", [])?;
+        }
+        "translation" => {
+            conn.execute("DROP TABLE IF EXISTS translation_memory", [])?;
+            conn.execute("DROP TABLE IF EXISTS translation_glossary", [])?;
+            conn.execute("DROP TABLE IF EXISTS translation_note_jobs", [])?;
+            conn.execute("DROP TABLE IF EXISTS translation_note_chunks", [])?;
+        }
         _ => {}
     }
 
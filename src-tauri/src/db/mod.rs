@@ -18,7 +18,8 @@ pub use connection::DbConnectionManager;
 // 重新导出初始化函数
 pub use init::init_db;
 pub use init::{
-    clear_plugin_storage, ensure_plugin_storage, index_needs_refresh, mark_index_success,
+    clear_plugin_storage, create_plugin_tables, drop_plugin_tables, ensure_plugin_storage,
+    index_needs_refresh, mark_index_success, open_plugin_store, PLUGINS_WITH_STORAGE,
 };
 pub use reset::reset_rebuildable_indexes;
 
@@ -0,0 +1,681 @@
+// 笔记冲突的三方语义合并
+// frontmatter 按字段合并，正文按行做 diff3，只有两端改动重叠的段落留给用户处理
+
+use crate::markdown::{format_frontmatter_block, try_parse_front_matter, FrontMatter};
+use crate::sync_data::git_stage_file;
+use chrono::DateTime;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// 超过该规模的改动区间不再求最长公共子序列，整体视为改动
+const MAX_LCS_CELLS: usize = 4_000_000;
+const OURS_MARKER: &str = "<<<<<<< 本地";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>> 远程";
+
+/// 单篇笔记的合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteMergeReport {
+    pub file_path: String,
+    // 自动合并的 frontmatter 字段
+    pub merged_fields: Vec<String>,
+    // 两端改成了不同的值，暂时保留本地值
+    pub conflict_fields: Vec<String>,
+    // 正文中自动合并的改动段数
+    pub auto_merged_hunks: usize,
+    // 正文中两端改动重叠的段数，已用冲突标记包围
+    pub conflict_hunks: usize,
+    pub merged_content: String,
+}
+
+impl NoteMergeReport {
+    pub fn is_clean(&self) -> bool {
+        self.conflict_fields.is_empty() && self.conflict_hunks == 0
+    }
+}
+
+struct LineMerge {
+    text: String,
+    auto_merged_hunks: usize,
+    conflict_hunks: usize,
+}
+
+fn is_note_path(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".md")
+}
+
+/// 用 Git 冲突阶段（1 基线、2 本地、3 远端）合并一篇冲突笔记；非笔记或缺少某一端时返回 None
+pub fn merge_conflicted_note(
+    workspace_root: &Path,
+    file_path: &str,
+) -> Result<Option<NoteMergeReport>, String> {
+    if !is_note_path(file_path) {
+        return Ok(None);
+    }
+    let ours = stage_text(workspace_root, 2, file_path)?;
+    let theirs = stage_text(workspace_root, 3, file_path)?;
+    let (Some(ours), Some(theirs)) = (ours, theirs) else {
+        return Ok(None);
+    };
+    let base = stage_text(workspace_root, 1, file_path)?;
+
+    merge_note_contents(file_path, base.as_deref(), &ours, &theirs).map(Some)
+}
+
+// 非 UTF-8 内容不参与语义合并
+fn stage_text(workspace_root: &Path, stage: u8, file_path: &str) -> Result<Option<String>, String> {
+    Ok(git_stage_file(workspace_root, stage, file_path)?
+        .and_then(|bytes| String::from_utf8(bytes).ok()))
+}
+
+/// 自动合并冲突笔记：无重叠改动的笔记写回并标记为已解决，其余保持冲突状态
+pub fn auto_merge_note_conflicts(
+    workspace_root: &Path,
+    conflict_files: &[String],
+) -> Result<Vec<NoteMergeReport>, String> {
+    let mut reports = Vec::new();
+    let mut resolved_paths = Vec::new();
+
+    for path in conflict_files {
+        let report = match merge_conflicted_note(workspace_root, path) {
+            Ok(Some(report)) => report,
+            Ok(None) => continue,
+            Err(error) => {
+                warn!("⚠️ [Git] 笔记冲突合并失败 {}: {}", path, error);
+                continue;
+            }
+        };
+
+        if report.is_clean() {
+            std::fs::write(workspace_root.join(path), &report.merged_content)
+                .map_err(|e| format!("写入合并后的笔记失败: {}", e))?;
+            info!(
+                "🧩 [Git] 已自动合并笔记冲突: {}，字段 {:?}，正文 {} 处",
+                path, report.merged_fields, report.auto_merged_hunks
+            );
+            resolved_paths.push(path.clone());
+        } else {
+            info!(
+                "📋 [Git] 笔记仍有冲突需手动处理: {}，冲突字段 {:?}，冲突段落 {} 处",
+                path, report.conflict_fields, report.conflict_hunks
+            );
+        }
+        reports.push(report);
+    }
+
+    if !resolved_paths.is_empty() {
+        let output = crate::git_common::git_command()
+            .arg("add")
+            .arg("--")
+            .args(&resolved_paths)
+            .current_dir(workspace_root)
+            .output()
+            .map_err(|e| format!("标记自动合并的笔记失败: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "标记自动合并的笔记失败: {}",
+                crate::git_common::get_git_stderr(&output)
+            ));
+        }
+    }
+
+    Ok(reports)
+}
+
+/// 三方合并笔记内容；任一端没有可解析的 frontmatter 时按整篇文本逐行合并
+pub fn merge_note_contents(
+    file_path: &str,
+    base: Option<&str>,
+    ours: &str,
+    theirs: &str,
+) -> Result<NoteMergeReport, String> {
+    let mut merged_fields = Vec::new();
+    let mut conflict_fields = Vec::new();
+
+    let (ours_fm, ours_body) = try_parse_front_matter(ours);
+    let (theirs_fm, theirs_body) = try_parse_front_matter(theirs);
+    let (merged_content, body) = match (ours_fm, theirs_fm) {
+        (Some(ours_fm), Some(theirs_fm)) => {
+            let (base_fm, base_body) = match base.map(try_parse_front_matter) {
+                Some((fm, body)) => (fm, body),
+                None => (None, String::new()),
+            };
+            let front_matter = merge_front_matter(
+                base_fm.as_ref(),
+                &ours_fm,
+                &theirs_fm,
+                &mut merged_fields,
+                &mut conflict_fields,
+            );
+            let body = merge_lines(&base_body, &ours_body, &theirs_body);
+            let block = format_frontmatter_block(&front_matter)?;
+            let content = if body.text.trim().is_empty() {
+                block.trim_end().to_string()
+            } else {
+                format!("{}\n\n{}", block.trim_end(), body.text)
+            };
+            (content, body)
+        }
+        _ => {
+            let body = merge_lines(base.unwrap_or_default(), ours, theirs);
+            (body.text.clone(), body)
+        }
+    };
+
+    Ok(NoteMergeReport {
+        file_path: file_path.to_string(),
+        merged_fields,
+        conflict_fields,
+        auto_merged_hunks: body.auto_merged_hunks,
+        conflict_hunks: body.conflict_hunks,
+        merged_content,
+    })
+}
+
+// ============= frontmatter =============
+
+fn merge_front_matter(
+    base: Option<&FrontMatter>,
+    ours: &FrontMatter,
+    theirs: &FrontMatter,
+    merged_fields: &mut Vec<String>,
+    conflict_fields: &mut Vec<String>,
+) -> FrontMatter {
+    let mut merged = ours.clone();
+
+    // 标签取并集，但一端删除、另一端未动的标签仍按删除处理
+    let base_tags = base.map(|fm| fm.tags.as_slice()).unwrap_or_default();
+    let mut tags: Vec<String> = ours
+        .tags
+        .iter()
+        .filter(|tag| theirs.tags.contains(tag) || !base_tags.contains(tag))
+        .cloned()
+        .collect();
+    for tag in &theirs.tags {
+        if !tags.contains(tag) && (ours.tags.contains(tag) || !base_tags.contains(tag)) {
+            tags.push(tag.clone());
+        }
+    }
+    if ours.tags != theirs.tags {
+        merged_fields.push("tags".to_string());
+    }
+    merged.tags = tags;
+
+    // 任一端收藏即收藏
+    merged.favorite = ours.favorite || theirs.favorite;
+    if ours.favorite != theirs.favorite {
+        merged_fields.push("favorite".to_string());
+    }
+
+    if ours.modified != theirs.modified {
+        merged.modified = later_timestamp(&ours.modified, &theirs.modified).to_string();
+        merged_fields.push("modified".to_string());
+    }
+    if ours.created != theirs.created {
+        merged.created = if later_timestamp(&ours.created, &theirs.created) == ours.created {
+            theirs.created.clone()
+        } else {
+            ours.created.clone()
+        };
+        merged_fields.push("created".to_string());
+    }
+
+    macro_rules! merge_field {
+        ($field:ident, $name:expr) => {
+            merged.$field = merge_value(
+                $name,
+                base.map(|fm| &fm.$field),
+                &ours.$field,
+                &theirs.$field,
+                merged_fields,
+                conflict_fields,
+            );
+        };
+    }
+    merge_field!(id, "id");
+    merge_field!(title, "title");
+    merge_field!(fragment_type, "type");
+    merge_field!(language, "language");
+    merge_field!(framework, "framework");
+    merge_field!(kind, "kind");
+    merge_field!(summary, "summary");
+    merge_field!(forked_from, "forked_from");
+    merge_field!(chat_id, "chat_id");
+    merge_field!(translated_from, "translated_from");
+
+    merged
+}
+
+// 一端未改动时采用另一端，两端改成不同值时保留本地并记为冲突
+fn merge_value<T: PartialEq + Clone>(
+    name: &str,
+    base: Option<&T>,
+    ours: &T,
+    theirs: &T,
+    merged_fields: &mut Vec<String>,
+    conflict_fields: &mut Vec<String>,
+) -> T {
+    if ours == theirs {
+        return ours.clone();
+    }
+    if base == Some(ours) {
+        merged_fields.push(name.to_string());
+        return theirs.clone();
+    }
+    if base == Some(theirs) {
+        merged_fields.push(name.to_string());
+        return ours.clone();
+    }
+    conflict_fields.push(name.to_string());
+    ours.clone()
+}
+
+fn later_timestamp<'a>(a: &'a str, b: &'a str) -> &'a str {
+    match (
+        DateTime::parse_from_rfc3339(a),
+        DateTime::parse_from_rfc3339(b),
+    ) {
+        (Ok(left), Ok(right)) => {
+            if right > left {
+                b
+            } else {
+                a
+            }
+        }
+        _ => a.max(b),
+    }
+}
+
+// ============= 正文 diff3 =============
+
+fn merge_lines(base: &str, ours: &str, theirs: &str) -> LineMerge {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let to_ours = lcs_matches(&base, &ours);
+    let to_theirs = lcs_matches(&base, &theirs);
+
+    let mut merge = LineMerge {
+        text: String::new(),
+        auto_merged_hunks: 0,
+        conflict_hunks: 0,
+    };
+    let (mut i, mut a, mut b) = (0, 0, 0);
+    loop {
+        // 三端一致的行直接保留
+        while i < base.len() && to_ours[i] == Some(a) && to_theirs[i] == Some(b) {
+            merge.text.push_str(base[i]);
+            i += 1;
+            a += 1;
+            b += 1;
+        }
+
+        // 下一处三端重新对齐的基线行，之间为一段改动
+        let next = (i..base.len()).find_map(|k| Some((k, to_ours[k]?, to_theirs[k]?)));
+        let (k, ak, bk) = next.unwrap_or((base.len(), ours.len(), theirs.len()));
+        if (k, ak, bk) == (i, a, b) {
+            break;
+        }
+
+        let base_hunk = base[i..k].concat();
+        let ours_hunk = ours[a..ak].concat();
+        let theirs_hunk = theirs[b..bk].concat();
+        if ours_hunk == theirs_hunk {
+            merge.text.push_str(&ours_hunk);
+            if ours_hunk != base_hunk {
+                merge.auto_merged_hunks += 1;
+            }
+        } else if ours_hunk == base_hunk {
+            merge.text.push_str(&theirs_hunk);
+            merge.auto_merged_hunks += 1;
+        } else if theirs_hunk == base_hunk {
+            merge.text.push_str(&ours_hunk);
+            merge.auto_merged_hunks += 1;
+        } else {
+            push_conflict(&mut merge.text, &ours_hunk, &theirs_hunk);
+            merge.conflict_hunks += 1;
+        }
+
+        i = k;
+        a = ak;
+        b = bk;
+    }
+    merge
+}
+
+fn push_conflict(text: &mut String, ours: &str, theirs: &str) {
+    ensure_line_end(text);
+    text.push_str(OURS_MARKER);
+    text.push('\n');
+    text.push_str(ours);
+    ensure_line_end(text);
+    text.push_str(SEPARATOR_MARKER);
+    text.push('\n');
+    text.push_str(theirs);
+    ensure_line_end(text);
+    text.push_str(THEIRS_MARKER);
+    text.push('\n');
+}
+
+fn ensure_line_end(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+// a 中每行在 b 中对应的行号（最长公共子序列）
+fn lcs_matches(a: &[&str], b: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; a.len()];
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    for (i, item) in matches.iter_mut().enumerate().take(prefix) {
+        *item = Some(i);
+    }
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    for k in 0..suffix {
+        matches[a.len() - 1 - k] = Some(b.len() - 1 - k);
+    }
+
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+    let (n, m) = (a_mid.len(), b_mid.len());
+    if n == 0 || m == 0 || n * m > MAX_LCS_CELLS {
+        return matches;
+    }
+
+    // lengths[i * (m + 1) + j] 为 a_mid[i..] 与 b_mid[j..] 的公共子序列长度
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * (m + 1) + j] = if a_mid[i] == b_mid[j] {
+                lengths[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a_mid[i] == b_mid[j] {
+            matches[prefix + i] = Some(prefix + j);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::{ForkSource, TranslationSource};
+
+    fn note(tags: &str, favorite: bool, modified: &str, body: &str) -> String {
+        format!(
+            "---\nid: n1\ntitle: Note\ntags: [{}]\ncreated: 2024-01-01T00:00:00+08:00\nmodified: {}\ntype: note\nfavorite: {}\n---\n\n{}",
+            tags, modified, favorite, body
+        )
+    }
+
+    #[test]
+    fn merges_front_matter_fields() {
+        let base = note("rust", false, "2024-01-01T00:00:00+08:00", "body\n");
+        let ours = note("rust, git", false, "2024-01-02T00:00:00+08:00", "body\n");
+        let theirs = note("", true, "2024-01-03T00:00:00+08:00", "body\n");
+        let report = merge_note_contents("a.md", Some(&base), &ours, &theirs).unwrap();
+
+        assert!(report.is_clean());
+        let (fm, body) = try_parse_front_matter(&report.merged_content);
+        let fm = fm.unwrap();
+        assert_eq!(fm.tags, vec!["git"]);
+        assert!(fm.favorite);
+        assert_eq!(fm.modified, "2024-01-03T00:00:00+08:00");
+        assert_eq!(body, "body\n");
+        assert_eq!(report.merged_fields, vec!["tags", "favorite", "modified"]);
+    }
+
+    #[test]
+    fn merges_non_overlapping_body_changes() {
+        let merge = merge_lines(
+            "# T\n\none\ntwo\nthree\n",
+            "# T\n\nONE\ntwo\nthree\n",
+            "# T\n\none\ntwo\nthree\nfour\n",
+        );
+        assert_eq!(merge.text, "# T\n\nONE\ntwo\nthree\nfour\n");
+        assert_eq!(merge.auto_merged_hunks, 2);
+        assert_eq!(merge.conflict_hunks, 0);
+    }
+
+    #[test]
+    fn leaves_overlapping_changes_marked() {
+        let merge = merge_lines("a\nb\nc\n", "a\nB1\nc\n", "a\nB2\nc\n");
+        assert_eq!(merge.conflict_hunks, 1);
+        assert_eq!(
+            merge.text,
+            "a\n<<<<<<< 本地\nB1\n=======\nB2\n>>>>>>> 远程\nc\n"
+        );
+
+        let report = merge_note_contents(
+            "a.md",
+            Some(&note("", false, "2024-01-01T00:00:00Z", "x\n").replace("Note", "Base")),
+            &note("", false, "2024-01-01T00:00:00Z", "x\n").replace("Note", "Ours"),
+            &note("", false, "2024-01-01T00:00:00Z", "x\n").replace("Note", "Theirs"),
+        )
+        .unwrap();
+        assert_eq!(report.conflict_fields, vec!["title"]);
+        assert!(!report.is_clean());
+    }
+
+    fn front_matter(side: &str, created: &str, modified: &str, favorite: bool) -> FrontMatter {
+        FrontMatter {
+            id: format!("{}-id", side),
+            title: format!("{} title", side),
+            tags: vec![side.to_string()],
+            created: created.to_string(),
+            modified: modified.to_string(),
+            fragment_type: format!("{}-type", side),
+            language: Some(format!("{}-language", side)),
+            framework: Some(format!("{}-framework", side)),
+            kind: Some(format!("{}-kind", side)),
+            summary: Some(format!("{} summary", side)),
+            favorite,
+            forked_from: Some(ForkSource {
+                library: side.to_string(),
+                url: format!("https://example.com/{}.git", side),
+                path: format!("{}.md", side),
+                commit: None,
+                forked_at: modified.to_string(),
+            }),
+            chat_id: Some(format!("{}-chat", side)),
+            translated_from: Some(TranslationSource {
+                path: format!("{}.md", side),
+                title: format!("{} source", side),
+                from: side.to_string(),
+                to: side.to_string(),
+                translated_at: modified.to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn merges_every_front_matter_field() {
+        // 本地等于基线，远端每个字段都不同：合并结果必须逐字段等于远端
+        let ours = front_matter(
+            "ours",
+            "2024-01-02T00:00:00Z",
+            "2024-01-02T00:00:00Z",
+            false,
+        );
+        let theirs = front_matter(
+            "theirs",
+            "2024-01-01T00:00:00Z",
+            "2024-01-03T00:00:00Z",
+            true,
+        );
+
+        // 不带 `..` 的解构：FrontMatter 新增字段时这里无法编译，需同时补进 merge_front_matter
+        let FrontMatter {
+            id,
+            title,
+            tags,
+            created,
+            modified,
+            fragment_type,
+            language,
+            framework,
+            kind,
+            summary,
+            favorite,
+            forked_from,
+            chat_id,
+            translated_from,
+        } = &theirs;
+        assert!(
+            [
+                ours.id != *id,
+                ours.title != *title,
+                ours.tags != *tags,
+                ours.created != *created,
+                ours.modified != *modified,
+                ours.fragment_type != *fragment_type,
+                ours.language != *language,
+                ours.framework != *framework,
+                ours.kind != *kind,
+                ours.summary != *summary,
+                ours.favorite != *favorite,
+                ours.forked_from != *forked_from,
+                ours.chat_id != *chat_id,
+                ours.translated_from != *translated_from,
+            ]
+            .iter()
+            .all(|differs| *differs),
+            "测试数据中两端的每个字段都应不同"
+        );
+
+        let mut merged_fields = Vec::new();
+        let mut conflict_fields = Vec::new();
+        let merged = merge_front_matter(
+            Some(&ours),
+            &ours,
+            &theirs,
+            &mut merged_fields,
+            &mut conflict_fields,
+        );
+        assert_eq!(merged, theirs);
+        assert!(conflict_fields.is_empty());
+        assert_eq!(merged_fields.len(), 14);
+    }
+
+    #[test]
+    fn favorite_is_kept_when_either_side_has_it() {
+        let base = front_matter("base", "2024-01-01T00:00:00Z", "2024-01-01T00:00:00Z", true);
+        let mut ours = base.clone();
+        ours.favorite = false;
+        let merged =
+            merge_front_matter(Some(&base), &ours, &base, &mut Vec::new(), &mut Vec::new());
+        assert!(merged.favorite);
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> std::process::Output {
+        crate::git_common::git_command()
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+    }
+
+    fn git_ok(dir: &Path, args: &[&str]) {
+        let output = git(dir, args);
+        assert!(
+            output.status.success(),
+            "git {:?} 失败: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn resolves_conflicting_pull_in_a_real_repository() {
+        let root = std::env::temp_dir().join(format!("git-merge-test-{}", uuid::Uuid::new_v4()));
+        let remote = root.join("remote.git");
+        let (device_a, device_b) = (root.join("a"), root.join("b"));
+        std::fs::create_dir_all(&remote).unwrap();
+        std::fs::create_dir_all(&device_a).unwrap();
+        git_ok(&remote, &["init", "--bare", "--quiet", "-b", "main"]);
+
+        let body = "first\nsecond\nthird\nfourth\nfifth\n";
+        std::fs::write(
+            device_a.join("note.md"),
+            note("rust", false, "2024-01-01T00:00:00+08:00", body),
+        )
+        .unwrap();
+        git_ok(&device_a, &["init", "--quiet", "-b", "main"]);
+        git_ok(&device_a, &["add", "."]);
+        git_ok(&device_a, &["commit", "--quiet", "-m", "base"]);
+        git_ok(
+            &device_a,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+        git_ok(&device_a, &["push", "--quiet", "origin", "main"]);
+        git_ok(&root, &["clone", "--quiet", remote.to_str().unwrap(), "b"]);
+
+        // 两台设备都改了 modified 所在行，Git 逐行合并必然冲突
+        std::fs::write(
+            device_a.join("note.md"),
+            note(
+                "rust, git",
+                false,
+                "2024-01-02T00:00:00+08:00",
+                "FIRST\nsecond\nthird\nfourth\nfifth\n",
+            ),
+        )
+        .unwrap();
+        git_ok(&device_a, &["commit", "--quiet", "-am", "a"]);
+        git_ok(&device_a, &["push", "--quiet", "origin", "main"]);
+
+        std::fs::write(
+            device_b.join("note.md"),
+            note(
+                "rust",
+                true,
+                "2024-01-03T00:00:00+08:00",
+                "first\nsecond\nthird\nfourth\nFIFTH\n",
+            ),
+        )
+        .unwrap();
+        git_ok(&device_b, &["commit", "--quiet", "-am", "b"]);
+        let pull = git(
+            &device_b,
+            &["pull", "--no-rebase", "--quiet", "origin", "main"],
+        );
+        assert!(!pull.status.success());
+
+        let reports = auto_merge_note_conflicts(&device_b, &["note.md".to_string()]).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].is_clean());
+
+        let merged = std::fs::read_to_string(device_b.join("note.md")).unwrap();
+        let (fm, merged_body) = try_parse_front_matter(&merged);
+        let fm = fm.unwrap();
+        assert_eq!(fm.tags, vec!["rust", "git"]);
+        assert!(fm.favorite);
+        assert_eq!(fm.modified, "2024-01-03T00:00:00+08:00");
+        assert_eq!(merged_body, "FIRST\nsecond\nthird\nfourth\nFIFTH\n");
+
+        let unmerged = git(&device_b, &["diff", "--name-only", "--diff-filter=U"]);
+        assert!(String::from_utf8_lossy(&unmerged.stdout).trim().is_empty());
+        git_ok(&device_b, &["commit", "--quiet", "--no-edit"]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
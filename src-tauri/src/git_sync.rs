@@ -101,6 +101,9 @@ pub struct PullResult {
     /// 需要用户选择分支时返回候选分支
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch_selection: Option<BranchSelection>,
+    /// 冲突笔记的自动合并结果（含仍需手动解决的笔记）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merge_reports: Vec<crate::git_merge::NoteMergeReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -988,17 +991,17 @@ fn build_branch_selection(
 
 /// 变更文件分类（用于增量更新）
 #[derive(Debug, Default)]
-struct ChangedFilesByStatus {
-    created: Vec<String>,
-    modified: Vec<String>,
-    deleted: Vec<String>,
-    attachment_files: Vec<String>,
-    sync_protocol_files: Vec<String>,
+pub(crate) struct ChangedFilesByStatus {
+    pub(crate) created: Vec<String>,
+    pub(crate) modified: Vec<String>,
+    pub(crate) deleted: Vec<String>,
+    pub(crate) attachment_files: Vec<String>,
+    pub(crate) sync_protocol_files: Vec<String>,
 }
 
 impl ChangedFilesByStatus {
     /// Markdown 内容变更。现有 cache 和内容刷新事件只消费这一组。
-    fn all(&self) -> Vec<String> {
+    pub(crate) fn all(&self) -> Vec<String> {
         self.created
             .iter()
             .chain(self.modified.iter())
@@ -1007,7 +1010,7 @@ impl ChangedFilesByStatus {
             .collect()
     }
 
-    fn total_count(&self) -> usize {
+    pub(crate) fn total_count(&self) -> usize {
         self.created.len()
             + self.modified.len()
             + self.deleted.len()
@@ -1015,7 +1018,7 @@ impl ChangedFilesByStatus {
             + self.sync_protocol_files.len()
     }
 
-    fn record_path(&mut self, workspace_root: &Path, status: &str, path: String) {
+    pub(crate) fn record_path(&mut self, workspace_root: &Path, status: &str, path: String) {
         if path.to_ascii_lowercase().ends_with(".md") {
             match status {
                 "A" => self.created.push(path),
@@ -1031,7 +1034,7 @@ impl ChangedFilesByStatus {
 }
 
 /// 获取 Git Pull 后变更的文件列表（按状态分类，用于真正的增量更新）
-fn get_changed_files_with_status(
+pub(crate) fn get_changed_files_with_status(
     workspace_root: &Path,
     pre_pull_head: Option<&str>,
 ) -> Result<ChangedFilesByStatus, String> {
@@ -1149,7 +1152,7 @@ fn get_changed_files_with_status(
     Ok(result)
 }
 
-fn import_portable_sync_config(app_handle: &tauri::AppHandle, workspace_root: &Path) {
+pub(crate) fn import_portable_sync_config(app_handle: &tauri::AppHandle, workspace_root: &Path) {
     match crate::sync_data::import_sync_bundle(app_handle, workspace_root) {
         Ok(report) if report.found_sync_bundle => {
             info!(
@@ -1184,6 +1187,77 @@ fn apply_non_content_sync_changes(app_handle: &tauri::AppHandle, changes: &Chang
     }
 }
 
+/// Pull 后刷新本地状态：忽略文件监听、增量更新 cache 并通知前端重新加载
+pub(crate) fn refresh_pulled_files(
+    app_handle: &tauri::AppHandle,
+    workspace_root: &Path,
+    by_status: &ChangedFilesByStatus,
+) {
+    let changed_files = by_status.all();
+    apply_non_content_sync_changes(app_handle, by_status);
+
+    if !changed_files.is_empty() {
+        info!(
+            "📋 [AutoSync] 检测到 {} 个 .md 文件变更",
+            changed_files.len()
+        );
+
+        // 将变更文件添加到 FileWatcher 忽略列表
+        if let Some(watcher_state) =
+            app_handle.try_state::<Arc<Mutex<Option<crate::markdown::FileWatcher>>>>()
+        {
+            if let Ok(watcher_lock) = watcher_state.lock() {
+                if let Some(ref watcher) = *watcher_lock {
+                    for file in &changed_files {
+                        let file_path = workspace_root.join(file);
+                        watcher.ignore_next_change(file_path);
+                    }
+                    info!(
+                        "🔕 [AutoSync] 已将 {} 个文件添加到 FileWatcher 忽略列表",
+                        changed_files.len()
+                    );
+                }
+            }
+        }
+
+        // 增量更新 cache：新增/修改用 scan_files，删除用 remove_file
+        if let Some(cache_state) =
+            app_handle.try_state::<Arc<StdRwLock<crate::markdown::CacheManager>>>()
+        {
+            if let Ok(mut cache) = cache_state.write() {
+                let to_scan: Vec<String> = by_status
+                    .created
+                    .iter()
+                    .chain(by_status.modified.iter())
+                    .cloned()
+                    .collect();
+                if !to_scan.is_empty() {
+                    if let Ok(added_count) = cache.scan_files(&to_scan, workspace_root) {
+                        info!("✅ [AutoSync] 增量扫描完成，添加 {} 个新文件", added_count);
+                    }
+                }
+                for rel_path in &by_status.deleted {
+                    let abs_path = workspace_root.join(rel_path);
+                    let _ = cache.remove_file(&abs_path, workspace_root);
+                }
+                let _ = cache.save();
+            }
+        }
+
+        // 发送 files-changed-batch，使 Content 页面能重新加载已修改的当前文件
+        if let Some(config_window) = app_handle.get_webview_window("config") {
+            let payload = serde_json::json!({
+                "created": by_status.created,
+                "modified": by_status.modified,
+                "deleted": by_status.deleted,
+            });
+            if let Err(e) = config_window.emit("files-changed-batch", payload) {
+                error!("❌ [AutoSync] 发送 files-changed-batch 失败: {}", e);
+            }
+        }
+    }
+}
+
 /// 检查远端是否会删除本地已有的 Markdown 文件。
 ///
 /// 这类删除不一定会触发 Git merge 冲突，但对笔记应用来说属于高风险同步：
@@ -1276,6 +1350,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
             untracked_files: vec![],
             last_sync_time: None,
             branch_selection: None,
+            merge_reports: vec![],
         });
     }
 
@@ -1296,6 +1371,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
             untracked_files: vec![],
             last_sync_time: None,
             branch_selection: Some(branch_selection),
+            merge_reports: vec![],
         });
     }
 
@@ -1331,6 +1407,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                     untracked_files: vec![],
                     last_sync_time: None,
                     branch_selection: None,
+                    merge_reports: vec![],
                 });
             }
         }
@@ -1363,6 +1440,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                     untracked_files: vec![],
                     last_sync_time: None,
                     branch_selection: None,
+                    merge_reports: vec![],
                 });
             }
         }
@@ -1386,14 +1464,65 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                     untracked_files,
                     last_sync_time: None,
                     branch_selection: None,
+                    merge_reports: vec![],
                 });
             }
         }
 
         // 检查是否是冲突
         if stderr.contains("CONFLICT") || stdout.contains("CONFLICT") {
-            let conflict_files = detect_conflicts(workspace_root)?;
+            let mut conflict_files = detect_conflicts(workspace_root)?;
             warn!("⚠️ [Git] Pull 发生冲突，冲突文件: {:?}", conflict_files);
+
+            // 笔记先按 frontmatter 字段与正文 diff3 自动合并，只留下真正重叠的改动
+            let merge_reports = match crate::git_merge::auto_merge_note_conflicts(
+                workspace_root,
+                &conflict_files,
+            ) {
+                Ok(reports) => reports,
+                Err(error) => {
+                    warn!("⚠️ [Git] 笔记冲突自动合并失败: {}", error);
+                    Vec::new()
+                }
+            };
+            let auto_merged_notes = merge_reports
+                .iter()
+                .filter(|report| report.is_clean())
+                .count();
+            if auto_merged_notes > 0 {
+                conflict_files = detect_conflicts(workspace_root)?;
+                if conflict_files.is_empty() {
+                    let output = crate::git_common::git_command()
+                        .args(["commit", "--no-edit"])
+                        .current_dir(workspace_root)
+                        .output()
+                        .map_err(|e| format!("提交自动合并的笔记失败: {}", e))?;
+                    if !output.status.success() {
+                        return Err(format!(
+                            "提交自动合并的笔记失败: {}",
+                            get_git_stderr(&output)
+                        ));
+                    }
+                    let changes =
+                        get_changed_files_with_status(workspace_root, pre_pull_head.as_deref())?;
+                    let last_sync_time =
+                        chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+                    info!("✅ [Git] {} 篇笔记的冲突已自动合并", auto_merged_notes);
+                    return Ok(PullResult {
+                        success: true,
+                        files_updated: changes.total_count(),
+                        has_conflicts: false,
+                        conflict_files: vec![],
+                        message: format!("{} 篇笔记的冲突已自动合并", auto_merged_notes),
+                        pre_pull_head,
+                        untracked_files: vec![],
+                        last_sync_time: Some(last_sync_time),
+                        branch_selection: None,
+                        merge_reports,
+                    });
+                }
+            }
+
             match crate::sync_data::resolve_sync_protocol_conflicts(workspace_root, &conflict_files)
             {
                 Ok(true) => {
@@ -1411,6 +1540,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                         untracked_files: vec![],
                         last_sync_time: Some(last_sync_time),
                         branch_selection: None,
+                        merge_reports,
                     });
                 }
                 Ok(false) => {}
@@ -1418,16 +1548,25 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                     warn!("⚠️ [Git] 同步配置冲突自动合并失败: {}", error);
                 }
             }
+            let message = if auto_merged_notes > 0 {
+                format!(
+                    "已自动合并 {} 篇笔记，其余冲突请解决后重试",
+                    auto_merged_notes
+                )
+            } else {
+                "Pull 发生冲突，请解决冲突后重试".to_string()
+            };
             return Ok(PullResult {
                 success: false,
                 files_updated: 0,
                 has_conflicts: true,
                 conflict_files,
-                message: "Pull 发生冲突，请解决冲突后重试".to_string(),
+                message,
                 pre_pull_head,
                 untracked_files: vec![],
                 last_sync_time: None,
                 branch_selection: None,
+                merge_reports,
             });
         }
 
@@ -1445,6 +1584,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                 untracked_files: vec![],
                 last_sync_time: None,
                 branch_selection: None,
+                merge_reports: vec![],
             });
         }
 
@@ -1469,6 +1609,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                         untracked_files: vec![],
                         last_sync_time: None,
                         branch_selection: None,
+                        merge_reports: vec![],
                     });
                 }
             }
@@ -1483,6 +1624,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                 untracked_files: vec![],
                 last_sync_time: None,
                 branch_selection: None,
+                merge_reports: vec![],
             });
         }
 
@@ -1504,6 +1646,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
                 untracked_files: vec![],
                 last_sync_time: None,
                 branch_selection: None,
+                merge_reports: vec![],
             });
         }
 
@@ -1554,6 +1697,7 @@ pub async fn git_pull(workspace_root: &Path) -> Result<PullResult, String> {
         untracked_files: vec![],
         last_sync_time: Some(last_sync_time),
         branch_selection: None,
+        merge_reports: vec![],
     })
 }
 
@@ -1655,7 +1799,7 @@ pub async fn git_push(workspace_root: &Path, message: &str) -> Result<PushResult
     })
 }
 
-fn push_current_branch(workspace_root: &Path) -> Result<(), String> {
+pub(crate) fn push_current_branch(workspace_root: &Path) -> Result<(), String> {
     let _git_operation_guard = GIT_OPERATION_LOCK
         .lock()
         .map_err(|e| format!("获取 Git 操作锁失败: {}", e))?;
@@ -1919,6 +2063,7 @@ const DEFAULT_GITIGNORE: &str = r#"# ================================
 # ------------------------------
 .snippets-code/*
 !.snippets-code/sync.json
+!.snippets-code/snippets.json
 
 *.db
 *.db-wal
@@ -1972,9 +2117,10 @@ uninstall.exe
 const SYNC_GITIGNORE_RULES: &str = r#"
 
 # Snippets Code 同步边界（由应用维护）
-# 除 sync.json 外，.snippets-code 均是本机布局、缓存或运行状态。
+# 除 sync.json 和 snippets.json 外，.snippets-code 均是本机布局、缓存或运行状态。
 .snippets-code/*
 !.snippets-code/sync.json
+!.snippets-code/snippets.json
 "#;
 
 /// 确保工作区存在正确的同步忽略规则。
@@ -2646,6 +2792,7 @@ pub fn test_git_connection_command(
 #[command]
 pub async fn git_pull_command(app_handle: AppHandle) -> Result<PullResult, String> {
     require_git_sync_plugin(&app_handle)?;
+    crate::sync_backends::ensure_plaintext_git_allowed(&app_handle)?;
     let workspace_root =
         crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
 
@@ -2915,6 +3062,7 @@ pub async fn git_push_command(
     message: Option<String>,
 ) -> Result<PushResult, String> {
     require_git_sync_plugin(&app_handle)?;
+    crate::sync_backends::ensure_plaintext_git_allowed(&app_handle)?;
     let workspace_root =
         crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
     ensure_gitignore(&workspace_root)?;
@@ -3031,12 +3179,18 @@ pub struct AutoSyncManager {
     is_running: Arc<Mutex<bool>>,
     is_paused: Arc<Mutex<bool>>, // 新增：暂停状态（用于冲突处理）
     worker_generation: Arc<AtomicU64>,
+    backend: Arc<dyn crate::sync_backends::SyncBackend>,
     app_handle: tauri::AppHandle,
 }
 
 impl AutoSyncManager {
     /// 创建新的自动同步管理器
-    pub fn new(workspace_root: PathBuf, delay_minutes: u64, app_handle: tauri::AppHandle) -> Self {
+    pub(crate) fn new(
+        workspace_root: PathBuf,
+        delay_minutes: u64,
+        backend: Arc<dyn crate::sync_backends::SyncBackend>,
+        app_handle: tauri::AppHandle,
+    ) -> Self {
         Self {
             workspace_root,
             delay_minutes,
@@ -3044,12 +3198,15 @@ impl AutoSyncManager {
             is_running: Arc::new(Mutex::new(false)),
             is_paused: Arc::new(Mutex::new(false)),
             worker_generation: Arc::new(AtomicU64::new(0)),
+            backend,
             app_handle,
         }
     }
 
-    fn matches_config(&self, workspace_root: &Path, delay_minutes: u64) -> bool {
-        self.workspace_root == workspace_root && self.delay_minutes == delay_minutes
+    fn matches_config(&self, workspace_root: &Path, delay_minutes: u64, target: &str) -> bool {
+        self.workspace_root == workspace_root
+            && self.delay_minutes == delay_minutes
+            && self.backend.target() == target
     }
 
     /// 启动自动同步
@@ -3072,13 +3229,15 @@ impl AutoSyncManager {
         let is_running_clone = Arc::clone(&self.is_running);
         let is_paused_clone = Arc::clone(&self.is_paused);
         let worker_generation = Arc::clone(&self.worker_generation);
+        let backend = Arc::clone(&self.backend);
         let app_handle = self.app_handle.clone();
 
         // 启动后台任务
         tauri::async_runtime::spawn(async move {
             info!(
-                "🚀 [AutoSync] 自动同步管理器已启动，延迟: {} 分钟",
-                delay_minutes
+                "🚀 [AutoSync] 自动同步管理器已启动，延迟: {} 分钟，同步目标: {}",
+                delay_minutes,
+                backend.target()
             );
 
             let pull_interval = Duration::from_secs(delay_minutes * 60); // Pull 间隔
@@ -3128,7 +3287,7 @@ impl AutoSyncManager {
                             break 'sync_loop;
                         }
 
-                        let current_pull_result = git_pull(&workspace_root).await;
+                        let current_pull_result = backend.pull().await;
                         if !is_current_worker() {
                             break 'sync_loop;
                         }
@@ -3176,76 +3335,14 @@ impl AutoSyncManager {
                                         result.files_updated
                                     );
 
-                                    let by_status = match get_changed_files_with_status(
-                                        &workspace_root,
-                                        result.pre_pull_head.as_deref(),
-                                    ) {
+                                    let by_status = match backend.changed_files(&result) {
                                         Ok(s) => s,
                                         Err(e) => {
                                             warn!("⚠️ [AutoSync] 获取变更文件失败: {}", e);
                                             ChangedFilesByStatus::default()
                                         }
                                     };
-                                    let changed_files = by_status.all();
-                                    apply_non_content_sync_changes(&app_handle, &by_status);
-
-                                    if !changed_files.is_empty() {
-                                        info!(
-                                            "📋 [AutoSync] 检测到 {} 个 .md 文件变更",
-                                            changed_files.len()
-                                        );
-
-                                        // 将变更文件添加到 FileWatcher 忽略列表
-                                        if let Some(watcher_state) = app_handle.try_state::<Arc<Mutex<Option<crate::markdown::FileWatcher>>>>() {
-                                            if let Ok(watcher_lock) = watcher_state.lock() {
-                                                if let Some(ref watcher) = *watcher_lock {
-                                                    for file in &changed_files {
-                                                        let file_path = workspace_root.join(file);
-                                                        watcher.ignore_next_change(file_path);
-                                                    }
-                                                    info!("🔕 [AutoSync] 已将 {} 个文件添加到 FileWatcher 忽略列表", changed_files.len());
-                                                }
-                                            }
-                                        }
-
-                                        // 增量更新 cache：新增/修改用 scan_files，删除用 remove_file
-                                        if let Some(cache_state) = app_handle.try_state::<Arc<StdRwLock<crate::markdown::CacheManager>>>() {
-                                            if let Ok(mut cache) = cache_state.write() {
-                                                let to_scan: Vec<String> = by_status
-                                                    .created
-                                                    .iter()
-                                                    .chain(by_status.modified.iter())
-                                                    .cloned()
-                                                    .collect();
-                                                if !to_scan.is_empty() {
-                                                    if let Ok(added_count) = cache.scan_files(&to_scan, &workspace_root) {
-                                                        info!("✅ [AutoSync] 增量扫描完成，添加 {} 个新文件", added_count);
-                                                    }
-                                                }
-                                                for rel_path in &by_status.deleted {
-                                                    let abs_path = workspace_root.join(rel_path);
-                                                    let _ = cache.remove_file(&abs_path, &workspace_root);
-                                                }
-                                                let _ = cache.save();
-                                            }
-                                        }
-
-                                        // 发送 files-changed-batch，使 Content 页面能重新加载已修改的当前文件
-                                        if let Some(config_window) =
-                                            app_handle.get_webview_window("config")
-                                        {
-                                            let payload = serde_json::json!({
-                                                "created": by_status.created,
-                                                "modified": by_status.modified,
-                                                "deleted": by_status.deleted,
-                                            });
-                                            if let Err(e) =
-                                                config_window.emit("files-changed-batch", payload)
-                                            {
-                                                error!("❌ [AutoSync] 发送 files-changed-batch 失败: {}", e);
-                                            }
-                                        }
-                                    }
+                                    refresh_pulled_files(&app_handle, &workspace_root, &by_status);
 
                                     // 发送 Pull 完成事件到 config 窗口
                                     if let Err(e) = app_handle.emit_to(
@@ -3479,8 +3576,8 @@ impl AutoSyncManager {
                         let delay_duration = Duration::from_secs(delay_minutes * 60);
                         elapsed >= delay_duration
                     } else {
-                        match get_git_status(&workspace_root) {
-                            Ok(status) => status.has_changes,
+                        match backend.has_local_changes() {
+                            Ok(has_changes) => has_changes,
                             Err(e) => {
                                 warn!("⚠️ [AutoSync] 检查本地待推送变更失败: {}", e);
                                 false
@@ -3494,13 +3591,7 @@ impl AutoSyncManager {
                         break;
                     }
 
-                    let mut push_result = match ensure_gitignore(&workspace_root).and_then(|_| {
-                        crate::sync_data::export_sync_bundle(&app_handle, &workspace_root)
-                            .map(|_| ())
-                    }) {
-                        Ok(()) => git_push(&workspace_root, "Auto sync").await,
-                        Err(error) => Err(error),
-                    };
+                    let mut push_result = backend.push("Auto sync").await;
                     if !is_current_worker() {
                         break;
                     }
@@ -3517,7 +3608,7 @@ impl AutoSyncManager {
                                 break;
                             }
 
-                            let current_pull_result = git_pull(&workspace_root).await;
+                            let current_pull_result = backend.pull().await;
                             if !is_current_worker() {
                                 break;
                             }
@@ -3559,10 +3650,7 @@ impl AutoSyncManager {
                                                 &workspace_root,
                                             );
                                         }
-                                        if let Ok(changes) = get_changed_files_with_status(
-                                            &workspace_root,
-                                            pull_result.pre_pull_head.as_deref(),
-                                        ) {
+                                        if let Ok(changes) = backend.changed_files(&pull_result) {
                                             apply_non_content_sync_changes(&app_handle, &changes);
                                             if let Some(cache_state) = app_handle.try_state::<Arc<
                                                 StdRwLock<crate::markdown::CacheManager>,
@@ -3591,17 +3679,9 @@ impl AutoSyncManager {
                                         if !is_current_worker() {
                                             break;
                                         }
-                                        match push_current_branch(&workspace_root) {
-                                            Ok(()) => {
-                                                info!("✅ [AutoSync] 重试 Push 成功");
-                                                push_result = Ok(PushResult {
-                                                    success: true,
-                                                    files_pushed: 1,
-                                                    commit_hash: String::new(),
-                                                    message: "Push 成功".to_string(),
-                                                });
-                                            }
-                                            Err(error) => push_result = Err(error),
+                                        push_result = backend.push_after_pull().await;
+                                        if push_result.is_ok() {
+                                            info!("✅ [AutoSync] 重试 Push 成功");
                                         }
                                     }
                                 }
@@ -3713,6 +3793,11 @@ impl AutoSyncManager {
         Ok(())
     }
 
+    /// 当前使用的同步后端
+    pub(crate) fn backend(&self) -> Arc<dyn crate::sync_backends::SyncBackend> {
+        Arc::clone(&self.backend)
+    }
+
     /// 停止自动同步
     pub fn stop(&self) -> Result<(), String> {
         let mut is_running = self
@@ -3795,6 +3880,9 @@ pub fn start_auto_sync_command(app_handle: AppHandle) -> Result<(), String> {
     }
 
     let delay_minutes = git_settings.auto_sync_delay;
+    drop(manager);
+    let backend = crate::sync_backends::create_backend(&app_handle, &workspace_root)?;
+    let target = backend.target();
 
     // 创建或获取 AutoSyncManager
     if let Some(sync_state) = app_handle.try_state::<Arc<Mutex<Option<AutoSyncManager>>>>() {
@@ -3802,9 +3890,9 @@ pub fn start_auto_sync_command(app_handle: AppHandle) -> Result<(), String> {
             .lock()
             .map_err(|e| format!("获取同步管理器锁失败: {}", e))?;
 
-        let should_replace = sync_manager
-            .as_ref()
-            .is_some_and(|manager| !manager.matches_config(&workspace_root, delay_minutes));
+        let should_replace = sync_manager.as_ref().is_some_and(|manager| {
+            !manager.matches_config(&workspace_root, delay_minutes, &target)
+        });
 
         if should_replace {
             if let Some(manager) = sync_manager.as_ref() {
@@ -3817,6 +3905,7 @@ pub fn start_auto_sync_command(app_handle: AppHandle) -> Result<(), String> {
             *sync_manager = Some(AutoSyncManager::new(
                 workspace_root,
                 delay_minutes,
+                backend,
                 app_handle.clone(),
             ));
         }
@@ -3826,7 +3915,8 @@ pub fn start_auto_sync_command(app_handle: AppHandle) -> Result<(), String> {
         }
     } else {
         // 首次创建
-        let manager = AutoSyncManager::new(workspace_root, delay_minutes, app_handle.clone());
+        let manager =
+            AutoSyncManager::new(workspace_root, delay_minutes, backend, app_handle.clone());
         manager.start()?;
         app_handle.manage(Arc::new(Mutex::new(Some(manager))));
     }
@@ -3928,6 +4018,9 @@ pub struct ConflictFileContent {
     pub remote_content: String,
     pub local_content: String,
     pub base_content: Option<String>,
+    /// 笔记的三方合并结果，仍有冲突的段落带冲突标记
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge_report: Option<crate::git_merge::NoteMergeReport>,
 }
 
 /// 获取冲突文件内容
@@ -4022,6 +4115,17 @@ pub fn get_conflict_file_content(
         }
     });
 
+    let merge_report = if is_merge_conflict {
+        crate::git_merge::merge_conflicted_note(&workspace_root, &normalized_path).unwrap_or_else(
+            |error| {
+                warn!("⚠️ [Git] 笔记三方合并失败: {}", error);
+                None
+            },
+        )
+    } else {
+        None
+    };
+
     info!("✅ [Git] 获取冲突文件内容: {}", normalized_path);
 
     Ok(ConflictFileContent {
@@ -4029,6 +4133,7 @@ pub fn get_conflict_file_content(
         remote_content,
         local_content,
         base_content,
+        merge_report,
     })
 }
 
@@ -4039,6 +4144,7 @@ pub async fn force_push_command(
     message: Option<String>,
 ) -> Result<PushResult, String> {
     require_git_sync_plugin(&app_handle)?;
+    crate::sync_backends::ensure_plaintext_git_allowed(&app_handle)?;
     let workspace_root =
         crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
     ensure_gitignore(&workspace_root)?;
@@ -4129,6 +4235,7 @@ pub async fn force_push_command(
 #[command]
 pub async fn force_pull_command(app_handle: AppHandle) -> Result<PullResult, String> {
     require_git_sync_plugin(&app_handle)?;
+    crate::sync_backends::ensure_plaintext_git_allowed(&app_handle)?;
     let workspace_root =
         crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
     let _git_operation_guard = GIT_OPERATION_LOCK
@@ -4248,6 +4355,7 @@ pub async fn force_pull_command(app_handle: AppHandle) -> Result<PullResult, Str
         untracked_files: vec![],
         last_sync_time: Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
         branch_selection: None,
+        merge_reports: vec![],
     };
 
     if let Err(e) = app_handle.emit_to(
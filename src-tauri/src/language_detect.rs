@@ -0,0 +1,275 @@
+use serde::Serialize;
+
+// 只取开头一段做统计，长笔记不必全文扫描
+const SAMPLE_CHARS: usize = 4000;
+// 拉丁、西里尔字母按半个字计权，避免中文里夹杂的英文单词压过正文
+const ALPHABET_WEIGHT: f64 = 0.5;
+const MIN_REPORTED_CONFIDENCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageGuess {
+    pub code: String,
+    pub confidence: f64,
+}
+
+#[derive(Default)]
+pub(crate) struct ScriptStats {
+    pub compact_count: usize,
+    pub cjk_count: usize,
+    pub kana_count: usize,
+    pub hangul_count: usize,
+    pub ascii_letter_count: usize,
+    pub latin_count: usize,
+    pub cyrillic_count: usize,
+}
+
+pub(crate) fn script_stats(text: &str) -> ScriptStats {
+    let mut stats = ScriptStats::default();
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            continue;
+        }
+
+        stats.compact_count += 1;
+        if ch.is_ascii_alphabetic() {
+            stats.ascii_letter_count += 1;
+        }
+        if is_latin_letter(ch) {
+            stats.latin_count += 1;
+        }
+        let code = ch as u32;
+        if matches!(code, 0x3400..=0x4DBF | 0x4E00..=0x9FFF) {
+            stats.cjk_count += 1;
+        }
+        if matches!(code, 0x3040..=0x30FF) {
+            stats.kana_count += 1;
+        }
+        if matches!(code, 0xAC00..=0xD7AF) {
+            stats.hangul_count += 1;
+        }
+        if matches!(code, 0x0400..=0x04FF) {
+            stats.cyrillic_count += 1;
+        }
+    }
+    stats
+}
+
+fn is_latin_letter(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || (matches!(ch as u32, 0x00C0..=0x024F) && ch != '×' && ch != '÷')
+}
+
+// 繁简对照的常用字，按位置一一对应
+const SIMPLIFIED_ONLY: &str =
+    "这们个来说时会对为国学与后还发经体点过么没动实开关问间种东车长电见头气产业认员书从听让门话应题将结无习爱变边处";
+const TRADITIONAL_ONLY: &str =
+    "這們個來說時會對為國學與後還發經體點過麼沒動實開關問間種東車長電見頭氣產業認員書從聽讓門話應題將結無習愛變邊處";
+
+struct LatinProfile {
+    code: &'static str,
+    // 没有任何特征命中时的倾向，短文本默认偏向英语
+    prior: f64,
+    // 按频率排序的字符三元组，_ 表示词边界
+    trigrams: &'static str,
+    words: &'static str,
+    marks: &'static str,
+}
+
+const LATIN_PROFILES: &[LatinProfile] = &[
+    LatinProfile {
+        code: "en",
+        prior: 1.0,
+        trigrams: "_th the he_ _an nd_ and ion _of of_ _to to_ tio _in ing ng_ ed_ _is is_ \
+                   on_ at_ ent hat tha _co _be for _fo or_ _wh ter all _wi wit ith his you \
+                   _yo ou_ _it it_ are _ar ly_ _ha ve_",
+        words: "the and of to in is that it for you with on are was this be have not what \
+                can will from they there would",
+        marks: "",
+    },
+    LatinProfile {
+        code: "de",
+        prior: 0.5,
+        trigrams: "en_ er_ _de der ie_ ich _di die ein sch che _un und nd_ _ei den cht ch_ \
+                   in_ te_ gen _ge ine ung _da das es_ ist _is st_ _zu zu_ nic mit _mi auf \
+                   _au _si sie ber _be ten eit",
+        words: "der die und das ist nicht ich sie mit den ein eine zu auf von dem des sich \
+                auch wir wird noch aber",
+        marks: "äöüß",
+    },
+    LatinProfile {
+        code: "fr",
+        prior: 0.5,
+        trigrams: "es_ _de de_ le_ _le ent _la la_ ion on_ _et et_ les _pa nt_ re_ des _co \
+                   que _qu ue_ tio _un men _po our pou ur_ ait ans _da dan _pr est _es par \
+                   _en ne_ _ne une ous",
+        words: "le la les et des est une un pour dans que qui pas sur du au avec ce il ne \
+                nous vous sont mais",
+        marks: "éèêàçùâîôûëïœ",
+    },
+];
+
+/// 识别文本语种，按置信度从高到低返回；无可识别文字时返回空列表
+pub(crate) fn detect_languages(text: &str) -> Vec<LanguageGuess> {
+    let sample: String = text.chars().take(SAMPLE_CHARS).collect();
+    let stats = script_stats(&sample);
+
+    let han = stats.cjk_count as f64;
+    let kana = stats.kana_count as f64;
+    let hangul = stats.hangul_count as f64;
+    let cyrillic = stats.cyrillic_count as f64 * ALPHABET_WEIGHT;
+    let latin = stats.latin_count as f64 * ALPHABET_WEIGHT;
+    let total = han + kana + hangul + cyrillic + latin;
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut scores: Vec<(&'static str, f64)> = Vec::new();
+    // 日文正文里假名通常占一成以上，此时汉字也算作日文
+    if kana > 0.0 && kana * 10.0 >= han {
+        scores.push(("ja", (han + kana) / total));
+    } else {
+        scores.push(("ja", kana / total));
+        let (simplified, traditional) = chinese_variant_counts(&sample);
+        let traditional_share = if simplified + traditional == 0 {
+            0.0
+        } else {
+            traditional as f64 / (simplified + traditional) as f64
+        };
+        scores.push(("zh", han / total * (1.0 - traditional_share)));
+        scores.push(("zh_tw", han / total * traditional_share));
+    }
+    scores.push(("ko", hangul / total));
+    scores.push(("ru", cyrillic / total));
+    if latin > 0.0 {
+        for (code, share) in latin_language_shares(&sample) {
+            scores.push((code, latin / total * share));
+        }
+    }
+
+    let mut guesses: Vec<LanguageGuess> = scores
+        .into_iter()
+        .filter(|(_, confidence)| *confidence >= MIN_REPORTED_CONFIDENCE)
+        .map(|(code, confidence)| LanguageGuess {
+            code: code.to_string(),
+            confidence: (confidence * 1000.0).round() / 1000.0,
+        })
+        .collect();
+    guesses.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    guesses
+}
+
+/// 置信度最高且不低于阈值的语种
+pub(crate) fn detect_language(text: &str, min_confidence: f64) -> Option<LanguageGuess> {
+    detect_languages(text)
+        .into_iter()
+        .next()
+        .filter(|guess| guess.confidence >= min_confidence)
+}
+
+fn chinese_variant_counts(text: &str) -> (usize, usize) {
+    let mut simplified = 0;
+    let mut traditional = 0;
+    for ch in text.chars() {
+        if SIMPLIFIED_ONLY.contains(ch) {
+            simplified += 1;
+        } else if TRADITIONAL_ONLY.contains(ch) {
+            traditional += 1;
+        }
+    }
+    (simplified, traditional)
+}
+
+// 拉丁字母文本在英、德、法之间的占比：三元组按排名计分，常用词与变音符号加分
+fn latin_language_shares(text: &str) -> Vec<(&'static str, f64)> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|ch: char| !is_latin_letter(ch))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut scores: Vec<(&'static str, f64)> = LATIN_PROFILES
+        .iter()
+        .map(|profile| (profile.code, profile.prior))
+        .collect();
+
+    for word in &words {
+        let padded: Vec<char> = format!("_{}_", word).chars().collect();
+        for window in padded.windows(3) {
+            let trigram: String = window.iter().collect();
+            for (profile, score) in LATIN_PROFILES.iter().zip(scores.iter_mut()) {
+                let ranked: Vec<&str> = profile.trigrams.split_whitespace().collect();
+                if let Some(rank) = ranked.iter().position(|item| *item == trigram) {
+                    score.1 += 1.0 - rank as f64 / ranked.len() as f64 * 0.5;
+                }
+            }
+        }
+        for (profile, score) in LATIN_PROFILES.iter().zip(scores.iter_mut()) {
+            if profile.words.split_whitespace().any(|item| item == *word) {
+                score.1 += 2.0;
+            }
+        }
+    }
+
+    for ch in lowered.chars() {
+        for (profile, score) in LATIN_PROFILES.iter().zip(scores.iter_mut()) {
+            if profile.marks.contains(ch) {
+                score.1 += 3.0;
+            }
+        }
+    }
+
+    let sum: f64 = scores.iter().map(|(_, score)| score).sum();
+    scores
+        .into_iter()
+        .map(|(code, score)| (code, score / sum))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn top(text: &str) -> String {
+        detect_languages(text)
+            .first()
+            .map(|guess| guess.code.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn detects_scripts() {
+        assert_eq!(top("今天我们去公园散步，天气很好。"), "zh");
+        assert_eq!(top("今天我們去公園散步，這裡的天氣很好。"), "zh_tw");
+        assert_eq!(
+            top("今日は公園を散歩しました。天気がとても良かったです。"),
+            "ja"
+        );
+        assert_eq!(top("오늘은 공원에서 산책을 했습니다."), "ko");
+        assert_eq!(top("Сегодня мы гуляли в парке."), "ru");
+        assert!(detect_languages("12345 !!! ---").is_empty());
+    }
+
+    #[test]
+    fn separates_latin_languages() {
+        assert_eq!(
+            top("The weather is nice and we are going to the park."),
+            "en"
+        );
+        assert_eq!(
+            top("Das Wetter ist schön und wir gehen heute in den Park."),
+            "de"
+        );
+        assert_eq!(
+            top("Le temps est beau et nous allons au parc avec les enfants."),
+            "fr"
+        );
+    }
+
+    #[test]
+    fn mixed_text_prefers_main_script() {
+        let guesses = detect_languages("我今天用 Rust 写了一个命令行工具，效果不错");
+        assert_eq!(guesses[0].code, "zh");
+        assert!(guesses[0].confidence > 0.7);
+        assert!(detect_language("ok", 0.9).is_none());
+    }
+}
@@ -9,15 +9,22 @@ mod config;
 mod db;
 mod desktop_watcher;
 pub mod git_common;
+mod git_merge;
 mod git_sync;
 mod hotkey;
 mod icon;
 mod json_config;
+mod language_detect;
+mod library_sources;
 mod markdown;
 mod ocr;
 mod plugins;
 mod search;
+mod sync_backends;
 mod sync_data;
+#[cfg(test)]
+mod test_http;
+mod text_utils;
 mod tray;
 mod uninstall;
 mod update;
@@ -195,6 +202,8 @@ pub fn run() {
 
             // 初始化 index_manager 状态（先设置为 None，后续异步初始化）
             app.manage(Arc::new(RwLock::new(None::<markdown::IndexManager>)));
+            // 注册工作区的索引在首次联合搜索时懒加载
+            app.manage(Arc::new(RwLock::new(markdown::WorkspaceIndexRegistry::default())));
 
             // 应用级配置存放在 data_dir/.snippets-code，不能依赖 Markdown 工作区存在。
             // 插件安装/启用状态、设置页等都需要在未设置工作区时正常工作。
@@ -344,6 +353,9 @@ pub fn run() {
                 }
                 app_config::ensure_enabled_plugin_storage(app.handle());
 
+                // 只读片段库按各自的间隔在后台拉取
+                library_sources::start_library_scheduler(app.handle().clone());
+
                 // 初始化 Markdown 文件系统（如果已配置工作区）
                 let app_handle_markdown = app.handle().clone();
 
@@ -693,9 +705,26 @@ pub fn run() {
             set_auto_hide_on_blur,            // 设置自动失焦隐藏
             get_auto_hide_on_blur,            // 获取自动失焦隐藏设置
             plugins::translation::translate_text,                   // 翻译文本
+            plugins::translation::detect_text_language,             // 离线识别文本语种
+            plugins::translation::get_translation_providers_config, // 获取翻译服务配置
+            plugins::translation::set_translation_providers_config, // 保存翻译服务配置
+            plugins::translation::list_translation_providers,       // 列出翻译服务
+            plugins::translation::get_translation_languages,        // 查询翻译服务支持的语言
+            plugins::translation_memory::list_translation_history,  // 分页搜索翻译历史
+            plugins::translation_memory::delete_translation_history, // 删除翻译历史
+            plugins::translation_memory::clear_translation_history, // 清空翻译历史
+            plugins::translation_memory::export_translation_history, // 导出翻译历史
+            plugins::translation_memory::list_translation_glossary, // 获取术语表
+            plugins::translation_memory::save_translation_glossary_term, // 保存术语
+            plugins::translation_memory::delete_translation_glossary_term, // 删除术语
+            plugins::translation_notes::translate_note,             // 整篇翻译笔记
+            plugins::translation_notes::cancel_note_translation,    // 取消整篇翻译
+            plugins::translation_notes::list_note_translation_jobs, // 列出可继续的整篇翻译
+            plugins::translation_notes::discard_note_translation_job, // 放弃中断的整篇翻译
             plugins::local_ai::local_ai_get_config,                 // 获取本地 AI 配置
             plugins::local_ai::local_ai_save_config,                // 保存本地 AI 配置
             plugins::local_ai::local_ai_scan_models,                // 扫描本地 AI 模型
+            plugins::local_ai::local_ai_list_models,                // 列出服务提供方的模型
             plugins::local_ai::local_ai_get_runtime_status,         // 获取本地 AI 运行时状态
             plugins::local_ai::local_ai_get_status,                 // 获取本地 AI 服务状态
             plugins::local_ai::local_ai_start_service,              // 启动本地 AI 服务
@@ -705,11 +734,33 @@ pub fn run() {
             plugins::local_ai::local_ai_chat_stream,                // 本地 AI 流式聊天
             plugins::local_ai::local_ai_cancel_chat_stream,         // 取消本地 AI 流式聊天
             plugins::local_ai::local_ai_read_attachment_files,      // 读取本地 AI 附件
-            plugins::local_ai::local_ai_get_chat_histories,         // 获取聊天历史
-            plugins::local_ai::local_ai_save_chat_history,          // 保存聊天历史
-            plugins::local_ai::local_ai_delete_chat_history,        // 删除聊天历史
-            plugins::local_ai::local_ai_clear_chat_histories,       // 清空聊天历史
+            plugins::local_ai_history::local_ai_get_chat_histories, // 获取聊天历史
+            plugins::local_ai_history::local_ai_save_chat_history,  // 保存聊天历史
+            plugins::local_ai_history::local_ai_delete_chat_history, // 删除聊天历史
+            plugins::local_ai_history::local_ai_clear_chat_histories, // 清空聊天历史
+            plugins::local_ai_history::local_ai_list_chat_conversations, // 分页列出对话
+            plugins::local_ai_history::local_ai_get_chat_history,   // 读取单个对话
+            plugins::local_ai_history::local_ai_search_chat_histories, // 全文搜索对话
+            plugins::local_ai_history::local_ai_set_chat_history_pinned, // 置顶/取消置顶对话
+            plugins::local_ai_history::local_ai_set_chat_history_tags, // 设置对话标签
+            plugins::local_ai_history::local_ai_export_chat_history, // 导出对话为笔记
+            plugins::local_ai_prompts::local_ai_list_prompts,       // 列出提示词模板
+            plugins::local_ai_prompts::local_ai_render_prompt,      // 展开提示词模板
+            plugins::local_ai_prompts::local_ai_run_snippet_action, // 对片段执行提示词命令
+            plugins::local_ai_prompts::local_ai_save_snippet_action_result, // 保存片段操作结果
             plugins::local_ai::local_ai_translate,                  // 本地 AI 翻译
+            plugins::local_ai_embeddings::local_ai_refresh_embeddings, // 同步笔记向量
+            plugins::local_ai_embeddings::local_ai_semantic_search, // 语义/混合搜索
+            plugins::local_ai_embeddings::local_ai_clear_embeddings, // 清空笔记向量
+            plugins::local_ai_tools::local_ai_respond_tool_call,    // 确认/拒绝 AI 写操作
+            plugins::local_ai_tagging::local_ai_start_tagging,      // 启动 AI 标签/摘要建议任务
+            plugins::local_ai_tagging::local_ai_pause_tagging,      // 暂停标签建议任务
+            plugins::local_ai_tagging::local_ai_resume_tagging,     // 继续标签建议任务
+            plugins::local_ai_tagging::local_ai_cancel_tagging,     // 取消标签建议任务
+            plugins::local_ai_tagging::local_ai_get_tagging_status, // 获取标签建议任务进度
+            plugins::local_ai_tagging::local_ai_list_tag_suggestions, // 列出待审核的标签建议
+            plugins::local_ai_tagging::local_ai_apply_tag_suggestion, // 采纳标签建议
+            plugins::local_ai_tagging::local_ai_reject_tag_suggestion, // 拒绝标签建议
             add_search_history,               // 添加搜索历史
             get_search_history,               // 获取搜索历史
             clear_search_history,             // 按来源清理搜索历史
@@ -827,6 +878,27 @@ pub fn run() {
             markdown::get_all_file_titles,              // 获取所有文件标题
             markdown::update_wikilinks,                 // 更新 wikilinks
             markdown::find_files_with_wikilink,         // 查找包含 wikilink 的文件
+            markdown::export::export_static_site,       // 导出静态 HTML 站点
+            markdown::export_book::export_epub,         // 导出 EPUB
+            markdown::export_book::export_single_html,  // 导出单文件 HTML
+            markdown::export_book::get_export_selections, // 获取导出选集
+            markdown::export_book::save_export_selection, // 保存导出选集
+            markdown::export_book::delete_export_selection, // 删除导出选集
+            markdown::stats::get_workspace_statistics,  // 工作区统计
+            markdown::workspace_registry::get_registered_workspaces, // 获取注册的工作区
+            markdown::workspace_registry::register_workspace, // 注册工作区
+            markdown::workspace_registry::update_registered_workspace, // 更新注册的工作区
+            markdown::workspace_registry::unregister_workspace, // 取消注册工作区
+            markdown::workspace_registry::rebuild_registered_workspace_index, // 重建注册工作区索引
+            markdown::workspace_registry::search_federated, // 联合搜索
+            markdown::code_snippets::get_code_snippet_extensions, // 获取代码片段扩展名
+            markdown::code_snippets::set_code_snippet_extensions, // 设置代码片段扩展名
+            markdown::code_snippets::create_code_snippet, // 创建代码片段文件
+            markdown::classifier::suggest_note_metadata, // 推断单个文件的语义元数据
+            markdown::classifier::suggest_workspace_metadata, // 批量元数据建议报告
+            markdown::classifier::apply_metadata_suggestions, // 应用选中的元数据建议
+            markdown::duplicates::find_duplicate_notes, // 查找近似重复片段
+            markdown::duplicates::merge_duplicate_notes, // 合并重复片段
             // 附件管理命令
             attachment::save_image_attachment,          // 保存图片附件
             attachment::get_attachment_config,          // 获取附件配置
@@ -899,6 +971,20 @@ pub fn run() {
             git_sync::resolve_conflicts_batch,         // 批量解决冲突
             git_sync::write_conflict_file,              // 写入冲突文件内容
             git_sync::remove_untracked_file_command,    // 删除未跟踪文件
+            sync_backends::get_sync_backend_config,     // 获取同步后端配置
+            sync_backends::set_sync_backend_config,     // 保存同步后端配置
+            sync_backends::test_sync_backend,           // 测试 WebDAV / 文件夹同步目标
+            sync_backends::sync_backend_now,            // 立即同步（WebDAV / 文件夹 / 加密 Git）
+            sync_backends::get_encrypted_sync_status,   // 获取端到端加密同步状态
+            sync_backends::setup_encrypted_sync,        // 用口令启用或加入加密同步
+            sync_backends::migrate_to_encrypted_sync,   // 把明文仓库迁移为加密同步
+            library_sources::get_library_sources,       // 获取只读片段库
+            library_sources::add_library_source,        // 添加片段库（克隆）
+            library_sources::update_library_source,     // 更新片段库设置
+            library_sources::remove_library_source,     // 移除片段库
+            library_sources::pull_library_source,       // 立即拉取片段库
+            library_sources::read_library_note,         // 只读查看库中笔记
+            library_sources::fork_library_note,         // 复制库中笔记到工作区
             app_setup::register_app_init_request,        // 注册 App 初始化请求（防抖）
             app_setup::should_execute_app_init,            // 检查是否应该执行 App 初始化（防抖）
             // 文件系统命令
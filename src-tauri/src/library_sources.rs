@@ -0,0 +1,792 @@
+// 只读片段库（Git 仓库）
+//
+// 片段库克隆到 data_dir/libraries/<id>，只通过 fetch + reset 跟随远程，应用不会在其中写入
+// 任何文件。库中的笔记参与联合搜索；需要修改时先复制（fork）到当前工作区，并在 frontmatter
+// 的 forked_from 中记录来源库、路径和提交。
+
+use crate::app_config::{AppConfigManager, LibrarySource};
+use crate::git_common::{get_git_stderr, get_git_stdout, is_git_success, remove_token_from_url};
+use crate::json_config::get_data_dir;
+use crate::markdown::export::{category_from_relative_path, is_inside_workspace};
+use crate::markdown::file_ops::get_relative_path;
+use crate::markdown::file_system_manager::FileSystemManager;
+use crate::markdown::metadata::{try_parse_front_matter, FileMetadata, ForkSource, FrontMatter};
+use crate::markdown::workspace_registry::{invalidate_source_index, SharedRegistry};
+use crate::markdown::{CacheManager, IndexManager};
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+const LIBRARIES_DIR: &str = "libraries";
+// 调度器检查间隔；各库按自己的 pull_interval_minutes 决定是否拉取
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+// 片段库运行时状态（不持久化，重启后从头计时）
+#[derive(Debug, Clone, Default)]
+struct LibraryRuntimeState {
+    last_attempt: Option<Instant>,
+    last_pulled_at: Option<String>,
+    last_error: Option<String>,
+    pulling: bool,
+}
+
+static LIBRARY_STATES: LazyLock<Mutex<HashMap<String, LibraryRuntimeState>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// 片段库及其同步状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibrarySourceStatus {
+    #[serde(flatten)]
+    pub source: LibrarySource,
+    pub cloned: bool,
+    pub head_commit: Option<String>,
+    pub last_pulled_at: Option<String>,
+    pub last_error: Option<String>,
+    pub pulling: bool,
+}
+
+// 拉取结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryPullResult {
+    pub id: String,
+    // HEAD 是否变化（首次克隆也视为变化）
+    pub updated: bool,
+    pub head_commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// 库中笔记的只读视图
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryNote {
+    pub library_id: String,
+    pub library_name: String,
+    pub relative_path: String,
+    pub title: String,
+    pub content: String,
+    pub front_matter: Option<FrontMatter>,
+    pub head_commit: Option<String>,
+}
+
+// ============= 路径与 Git 辅助函数 =============
+
+/// 片段库的克隆目录
+pub fn library_dir(app_handle: &AppHandle, id: &str) -> PathBuf {
+    get_data_dir(app_handle).join(LIBRARIES_DIR).join(id)
+}
+
+/// 片段库是否已克隆完成
+pub fn is_cloned(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+fn head_commit(dir: &Path) -> Option<String> {
+    if !is_cloned(dir) {
+        return None;
+    }
+    crate::git_common::run_git_command(dir, &["rev-parse", "HEAD"])
+        .ok()
+        .filter(is_git_success)
+        .map(|output| get_git_stdout(&output))
+        .filter(|commit| !commit.is_empty())
+}
+
+// Git 输出中可能带有含 token 的远程地址
+fn redact_git_error(source: &LibrarySource, message: &str) -> String {
+    message.replace(
+        &source.remote_url,
+        &remove_token_from_url(&source.remote_url),
+    )
+}
+
+fn clone_library(source: &LibrarySource, dir: &Path) -> Result<(), String> {
+    let parent = dir.parent().ok_or("片段库目录无效")?;
+    std::fs::create_dir_all(parent).map_err(|e| format!("创建片段库目录失败: {}", e))?;
+    // 上次克隆中断留下的半成品目录
+    if dir.exists() {
+        std::fs::remove_dir_all(dir).map_err(|e| format!("清理片段库目录失败: {}", e))?;
+    }
+
+    let dir_str = dir.to_string_lossy().to_string();
+    let mut args = vec!["clone", "--quiet"];
+    if let Some(branch) = source.branch.as_deref() {
+        args.extend(["--branch", branch, "--single-branch"]);
+    }
+    args.extend(["--", source.remote_url.as_str(), dir_str.as_str()]);
+
+    let output = crate::git_common::run_git_command(parent, &args)?;
+    if !is_git_success(&output) {
+        let _ = std::fs::remove_dir_all(dir);
+        return Err(format!(
+            "克隆片段库失败: {}",
+            redact_git_error(source, &get_git_stderr(&output))
+        ));
+    }
+    Ok(())
+}
+
+// 拉取远程并强制对齐：片段库是只读镜像，本地改动一律丢弃
+fn pull_library(source: &LibrarySource, dir: &Path) -> Result<bool, String> {
+    if !is_cloned(dir) {
+        clone_library(source, dir)?;
+        return Ok(true);
+    }
+
+    let before = head_commit(dir);
+    let steps: [&[&str]; 3] = [
+        &["fetch", "--prune", "--quiet", "origin"],
+        &["reset", "--hard", "--quiet", "@{upstream}"],
+        &["clean", "-fdq"],
+    ];
+    for args in steps {
+        let output = crate::git_common::run_git_command(dir, args)?;
+        if !is_git_success(&output) {
+            return Err(format!(
+                "拉取片段库失败 (git {}): {}",
+                args[0],
+                redact_git_error(source, &get_git_stderr(&output))
+            ));
+        }
+    }
+
+    Ok(head_commit(dir) != before)
+}
+
+// ============= 配置与状态 =============
+
+fn config_manager(
+    app_handle: &AppHandle,
+) -> Result<State<'_, Arc<RwLock<AppConfigManager>>>, String> {
+    app_handle
+        .try_state::<Arc<RwLock<AppConfigManager>>>()
+        .ok_or_else(|| "AppConfigManager 未初始化".to_string())
+}
+
+fn library_sources(app_handle: &AppHandle) -> Result<Vec<LibrarySource>, String> {
+    let state = config_manager(app_handle)?;
+    let manager = state.read().map_err(|e| format!("获取配置锁失败: {}", e))?;
+    Ok(manager.get_library_sources().to_vec())
+}
+
+fn find_library_source(app_handle: &AppHandle, id: &str) -> Result<LibrarySource, String> {
+    library_sources(app_handle)?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("片段库不存在: {}", id))
+}
+
+fn save_library_source(app_handle: &AppHandle, source: LibrarySource) -> Result<(), String> {
+    let state = config_manager(app_handle)?;
+    let mut manager = state
+        .write()
+        .map_err(|e| format!("获取配置锁失败: {}", e))?;
+    manager.upsert_library_source(source);
+    manager.save()
+}
+
+fn library_status(app_handle: &AppHandle, source: LibrarySource) -> LibrarySourceStatus {
+    let dir = library_dir(app_handle, &source.id);
+    let runtime = LIBRARY_STATES
+        .lock()
+        .ok()
+        .and_then(|states| states.get(&source.id).cloned())
+        .unwrap_or_default();
+
+    LibrarySourceStatus {
+        cloned: is_cloned(&dir),
+        head_commit: head_commit(&dir),
+        last_pulled_at: runtime.last_pulled_at,
+        last_error: runtime.last_error,
+        pulling: runtime.pulling,
+        source,
+    }
+}
+
+fn normalize_branch(branch: Option<String>) -> Option<String> {
+    branch
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+}
+
+// 拉取一个片段库并更新运行时状态；HEAD 变化时丢弃其搜索索引
+async fn run_pull(app_handle: &AppHandle, source: LibrarySource) -> LibraryPullResult {
+    {
+        let mut states = match LIBRARY_STATES.lock() {
+            Ok(states) => states,
+            Err(e) => {
+                return LibraryPullResult {
+                    id: source.id,
+                    updated: false,
+                    head_commit: None,
+                    error: Some(format!("获取片段库状态锁失败: {}", e)),
+                }
+            }
+        };
+        let state = states.entry(source.id.clone()).or_default();
+        if state.pulling {
+            return LibraryPullResult {
+                id: source.id,
+                updated: false,
+                head_commit: None,
+                error: Some("片段库正在拉取中".to_string()),
+            };
+        }
+        state.pulling = true;
+        state.last_attempt = Some(Instant::now());
+    }
+
+    let dir = library_dir(app_handle, &source.id);
+    let task_source = source.clone();
+    let task_dir = dir.clone();
+    let result = tokio::task::spawn_blocking(move || pull_library(&task_source, &task_dir))
+        .await
+        .map_err(|e| format!("片段库拉取任务执行失败: {}", e))
+        .and_then(|r| r);
+
+    let (updated, error) = match result {
+        Ok(updated) => (updated, None),
+        Err(e) => (false, Some(e)),
+    };
+
+    if let Ok(mut states) = LIBRARY_STATES.lock() {
+        let state = states.entry(source.id.clone()).or_default();
+        state.pulling = false;
+        match &error {
+            Some(e) => state.last_error = Some(e.clone()),
+            None => {
+                state.last_error = None;
+                state.last_pulled_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+        }
+    }
+
+    match &error {
+        Some(e) => warn!("⚠️ [片段库] 拉取失败 {}: {}", source.name, e),
+        None if updated => info!("✅ [片段库] 已更新: {}", source.name),
+        None => debug!("[片段库] 无更新: {}", source.name),
+    }
+
+    if updated {
+        if let Some(registry) = app_handle.try_state::<SharedRegistry>() {
+            invalidate_source_index(registry.inner(), &source.id);
+        }
+    }
+
+    let result = LibraryPullResult {
+        id: source.id,
+        updated,
+        head_commit: head_commit(&dir),
+        error,
+    };
+    let _ = app_handle.emit("library-source-pulled", result.clone());
+    result
+}
+
+/// 启动片段库定时拉取（应用启动时调用一次）
+pub fn start_library_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULER_TICK).await;
+
+            let sources = match library_sources(&app_handle) {
+                Ok(sources) => sources,
+                Err(e) => {
+                    warn!("⚠️ [片段库] 读取配置失败: {}", e);
+                    continue;
+                }
+            };
+
+            for source in sources {
+                if source.pull_interval_minutes == 0 {
+                    continue;
+                }
+                let interval = Duration::from_secs(source.pull_interval_minutes * 60);
+                let due = LIBRARY_STATES
+                    .lock()
+                    .map(|states| {
+                        states
+                            .get(&source.id)
+                            .and_then(|s| s.last_attempt)
+                            .is_none_or(|last| last.elapsed() >= interval)
+                    })
+                    .unwrap_or(false);
+                // 本轮前面的拉取期间该库可能已被移除
+                if due && find_library_source(&app_handle, &source.id).is_ok() {
+                    run_pull(&app_handle, source).await;
+                }
+            }
+        }
+    });
+}
+
+// 解析库内笔记路径：接受相对路径或联合搜索返回的绝对路径，拒绝越出库目录的路径
+fn resolve_library_note(dir: &Path, file_path: &str) -> Result<(PathBuf, String), String> {
+    let requested = PathBuf::from(file_path);
+    let path = if requested.is_absolute() {
+        requested
+    } else {
+        dir.join(requested)
+    };
+
+    if path.extension().and_then(|e| e.to_str()) != Some("md") {
+        return Err("只能读取 Markdown 笔记".to_string());
+    }
+    if !path.is_file() {
+        return Err(format!("笔记不存在: {}", file_path));
+    }
+    if !is_inside_workspace(&path, dir)? {
+        return Err("笔记路径不在片段库内".to_string());
+    }
+
+    let canonical_dir = dir
+        .canonicalize()
+        .map_err(|e| format!("无法规范化片段库路径: {}", e))?;
+    let canonical_path = path
+        .canonicalize()
+        .map_err(|e| format!("无法规范化笔记路径: {}", e))?;
+    let relative_path = get_relative_path(&canonical_dir, &canonical_path)?.replace('\\', "/");
+    Ok((canonical_path, relative_path))
+}
+
+fn read_note(
+    app_handle: &AppHandle,
+    source: &LibrarySource,
+    file_path: &str,
+) -> Result<LibraryNote, String> {
+    let dir = library_dir(app_handle, &source.id);
+    if !is_cloned(&dir) {
+        return Err(format!("片段库尚未克隆: {}", source.name));
+    }
+
+    let (path, relative_path) = resolve_library_note(&dir, file_path)?;
+    let raw = std::fs::read_to_string(&path).map_err(|e| format!("读取笔记失败: {}", e))?;
+    let (front_matter, body) = try_parse_front_matter(&raw);
+    let title = front_matter
+        .as_ref()
+        .map(|fm| fm.title.clone())
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("未命名")
+                .to_string()
+        });
+
+    Ok(LibraryNote {
+        library_id: source.id.clone(),
+        library_name: source.name.clone(),
+        relative_path,
+        title,
+        content: body,
+        front_matter,
+        head_commit: head_commit(&dir),
+    })
+}
+
+// ============= 命令 =============
+
+// 获取片段库及同步状态
+#[command]
+pub fn get_library_sources(app_handle: AppHandle) -> Result<Vec<LibrarySourceStatus>, String> {
+    Ok(library_sources(&app_handle)?
+        .into_iter()
+        .map(|source| library_status(&app_handle, source))
+        .collect())
+}
+
+// 添加片段库：先克隆成功再写入配置
+#[command]
+pub async fn add_library_source(
+    app_handle: AppHandle,
+    name: String,
+    remote_url: String,
+    branch: Option<String>,
+    pull_interval_minutes: Option<u64>,
+) -> Result<LibrarySourceStatus, String> {
+    let remote_url = remote_url.trim().to_string();
+    if remote_url.is_empty() {
+        return Err("仓库地址不能为空".to_string());
+    }
+    if library_sources(&app_handle)?
+        .iter()
+        .any(|s| s.remote_url == remote_url)
+    {
+        return Err(format!(
+            "片段库已存在: {}",
+            remove_token_from_url(&remote_url)
+        ));
+    }
+
+    let name = name.trim().to_string();
+    let source = LibrarySource {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: if name.is_empty() {
+            remove_token_from_url(&remote_url)
+        } else {
+            name
+        },
+        remote_url,
+        branch: normalize_branch(branch),
+        pull_interval_minutes: pull_interval_minutes.unwrap_or(60),
+        searchable: true,
+    };
+
+    let result = run_pull(&app_handle, source.clone()).await;
+    if let Some(error) = result.error {
+        // 克隆失败的库不会写入配置，运行时状态也一并丢弃
+        if let Ok(mut states) = LIBRARY_STATES.lock() {
+            states.remove(&source.id);
+        }
+        return Err(error);
+    }
+
+    save_library_source(&app_handle, source.clone())?;
+    info!("✅ [片段库] 已添加: {}", source.name);
+    Ok(library_status(&app_handle, source))
+}
+
+// 更新片段库设置；仓库地址或分支变化时重新克隆
+#[command]
+pub async fn update_library_source(
+    app_handle: AppHandle,
+    id: String,
+    name: Option<String>,
+    remote_url: Option<String>,
+    branch: Option<String>,
+    pull_interval_minutes: Option<u64>,
+    searchable: Option<bool>,
+) -> Result<LibrarySourceStatus, String> {
+    let mut source = find_library_source(&app_handle, &id)?;
+    let mut needs_reclone = false;
+
+    if let Some(name) = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        source.name = name;
+    }
+    if let Some(url) = remote_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+    {
+        needs_reclone |= url != source.remote_url;
+        source.remote_url = url;
+    }
+    // 传入空字符串表示改回远程默认分支
+    if let Some(branch) = branch {
+        let branch = normalize_branch(Some(branch));
+        needs_reclone |= branch != source.branch;
+        source.branch = branch;
+    }
+    if let Some(minutes) = pull_interval_minutes {
+        source.pull_interval_minutes = minutes;
+    }
+    if let Some(searchable) = searchable {
+        source.searchable = searchable;
+    }
+
+    save_library_source(&app_handle, source.clone())?;
+
+    if needs_reclone {
+        let dir = library_dir(&app_handle, &source.id);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).map_err(|e| format!("清理片段库目录失败: {}", e))?;
+        }
+        if let Some(registry) = app_handle.try_state::<SharedRegistry>() {
+            invalidate_source_index(registry.inner(), &source.id);
+        }
+        // 新设置已保存，克隆失败时状态中保留错误，用户修正后可再次更新或手动拉取
+        if let Some(error) = run_pull(&app_handle, source.clone()).await.error {
+            return Err(error);
+        }
+    }
+
+    Ok(library_status(&app_handle, source))
+}
+
+// 移除片段库，同时删除本地克隆；拉取进行中时拒绝移除
+#[command]
+pub fn remove_library_source(
+    app_handle: AppHandle,
+    registry: State<'_, SharedRegistry>,
+    id: String,
+) -> Result<bool, String> {
+    // 占用拉取标记，移除期间不会有新的拉取写入克隆目录
+    {
+        let mut states = LIBRARY_STATES
+            .lock()
+            .map_err(|e| format!("获取片段库状态锁失败: {}", e))?;
+        let state = states.entry(id.clone()).or_default();
+        if state.pulling {
+            return Err("片段库正在拉取中，请稍后再移除".to_string());
+        }
+        state.pulling = true;
+    }
+
+    let result = remove_library(&app_handle, &id);
+    invalidate_source_index(registry.inner(), &id);
+    if let Ok(mut states) = LIBRARY_STATES.lock() {
+        states.remove(&id);
+    }
+    result
+}
+
+fn remove_library(app_handle: &AppHandle, id: &str) -> Result<bool, String> {
+    let removed = {
+        let state = config_manager(app_handle)?;
+        let mut manager = state
+            .write()
+            .map_err(|e| format!("获取配置锁失败: {}", e))?;
+        let removed = manager.remove_library_source(id);
+        if removed {
+            manager.save()?;
+        }
+        removed
+    };
+
+    let dir = library_dir(app_handle, id);
+    if removed && dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("删除片段库目录失败: {}", e))?;
+    }
+
+    Ok(removed)
+}
+
+// 立即拉取片段库
+#[command]
+pub async fn pull_library_source(
+    app_handle: AppHandle,
+    id: String,
+) -> Result<LibraryPullResult, String> {
+    let source = find_library_source(&app_handle, &id)?;
+    let result = run_pull(&app_handle, source).await;
+    match result.error {
+        Some(error) => Err(error),
+        None => Ok(result),
+    }
+}
+
+// 只读查看库中的笔记
+#[command]
+pub fn read_library_note(
+    app_handle: AppHandle,
+    id: String,
+    file_path: String,
+) -> Result<LibraryNote, String> {
+    let source = find_library_source(&app_handle, &id)?;
+    read_note(&app_handle, &source, &file_path)
+}
+
+// 将库中的笔记复制到当前工作区，返回新文件路径
+#[command]
+pub async fn fork_library_note(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    id: String,
+    file_path: String,
+    category: Option<String>,
+) -> Result<String, String> {
+    let source = find_library_source(&app_handle, &id)?;
+    let note = read_note(&app_handle, &source, &file_path)?;
+    let workspace_root = crate::json_config::get_workspace_root(&app_handle)?
+        .ok_or("工作区未配置，请先设置工作区根目录")?;
+
+    let now = chrono::Utc::now();
+    let now_timestamp = now.timestamp_millis();
+    let original = note.front_matter.as_ref();
+
+    // 复制后的笔记是独立副本：新 id、新时间戳，不继承收藏状态
+    let front_matter = FrontMatter {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: note.title.clone(),
+        tags: original.map(|fm| fm.tags.clone()).unwrap_or_default(),
+        created: now.to_rfc3339(),
+        modified: now.to_rfc3339(),
+        fragment_type: original
+            .map(|fm| fm.fragment_type.clone())
+            .unwrap_or_else(|| "note".to_string()),
+        language: original.and_then(|fm| fm.language.clone()),
+        framework: original.and_then(|fm| fm.framework.clone()),
+        kind: original.and_then(|fm| fm.kind.clone()),
+        summary: original.and_then(|fm| fm.summary.clone()),
+        favorite: false,
+        forked_from: Some(ForkSource {
+            library: source.name.clone(),
+            url: remove_token_from_url(&source.remote_url),
+            path: note.relative_path.clone(),
+            commit: note.head_commit.clone(),
+            forked_at: now.to_rfc3339(),
+        }),
+        chat_id: None,
+        translated_from: None,
+    };
+
+    let category = category
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| category_from_relative_path(&note.relative_path));
+
+    let fs_manager = FileSystemManager::new(workspace_root.clone());
+    let new_path = fs_manager.create_markdown_file(
+        Some(&category),
+        &note.title,
+        &note.content,
+        &front_matter,
+    )?;
+    let relative_path = get_relative_path(&workspace_root, &new_path)?;
+
+    {
+        let mut cache = cache_manager
+            .write()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        cache.set_file_metadata(
+            relative_path,
+            FileMetadata {
+                id: front_matter.id.clone(),
+                created: now_timestamp,
+                modified: now_timestamp,
+                size: Some(note.content.len() as u64),
+                hash: None,
+            },
+        );
+        cache.save()?;
+    }
+
+    if let Ok(manager_lock) = index_manager.read() {
+        if let Some(ref manager) = *manager_lock {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            let _ = manager.update_entry(&new_path, &workspace_root, &cache);
+        }
+    }
+
+    info!(
+        "✅ [片段库] 已复制笔记 {} / {} -> {}",
+        source.name,
+        note.relative_path,
+        new_path.display()
+    );
+    Ok(new_path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn file_url(path: &Path) -> String {
+        let path = path.to_string_lossy().replace('\\', "/");
+        if path.starts_with('/') {
+            format!("file://{}", path)
+        } else {
+            format!("file:///{}", path)
+        }
+    }
+
+    fn test_source(remote_url: String) -> LibrarySource {
+        LibrarySource {
+            id: "lib".to_string(),
+            name: "lib".to_string(),
+            remote_url,
+            branch: Some("main".to_string()),
+            pull_interval_minutes: 0,
+            searchable: true,
+        }
+    }
+
+    #[test]
+    fn pull_library_clones_and_follows_remote() {
+        let base = std::env::temp_dir().join(format!("library-test-{}", uuid::Uuid::new_v4()));
+        let remote = base.join("remote.git");
+        std::fs::create_dir_all(&remote).unwrap();
+        git(&remote, &["init", "--bare", "--quiet", "-b", "main"]);
+
+        let author = base.join("author");
+        std::fs::create_dir_all(author.join("Rust")).unwrap();
+        std::fs::write(author.join("Rust/a.md"), "# A").unwrap();
+        git(&author, &["init", "--quiet", "-b", "main"]);
+        git(&author, &["add", "-A"]);
+        git(&author, &["commit", "--quiet", "-m", "first"]);
+        git(&author, &["remote", "add", "origin", &file_url(&remote)]);
+        git(&author, &["push", "--quiet", "origin", "main"]);
+
+        // 首次拉取即克隆
+        let source = test_source(file_url(&remote));
+        let dir = base.join("libraries").join("lib");
+        assert!(pull_library(&source, &dir).unwrap());
+        assert!(is_cloned(&dir));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("Rust/a.md")).unwrap(),
+            "# A"
+        );
+        assert_eq!(
+            head_commit(&dir),
+            Some(git(&author, &["rev-parse", "HEAD"]))
+        );
+        assert!(!pull_library(&source, &dir).unwrap());
+
+        // 远程更新后跟随，本地改动和多余文件被丢弃
+        std::fs::write(author.join("Rust/a.md"), "# A v2").unwrap();
+        git(&author, &["commit", "--quiet", "-am", "second"]);
+        git(&author, &["push", "--quiet", "origin", "main"]);
+        std::fs::write(dir.join("Rust/a.md"), "local edit").unwrap();
+        std::fs::write(dir.join("stray.md"), "stray").unwrap();
+
+        assert!(pull_library(&source, &dir).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("Rust/a.md")).unwrap(),
+            "# A v2"
+        );
+        assert!(!dir.join("stray.md").exists());
+        assert_eq!(
+            head_commit(&dir),
+            Some(git(&author, &["rev-parse", "HEAD"]))
+        );
+
+        // 克隆失败不留下半成品目录
+        let missing = test_source(file_url(&base.join("missing.git")));
+        let missing_dir = base.join("libraries").join("missing");
+        assert!(pull_library(&missing, &missing_dir).is_err());
+        assert!(!missing_dir.exists());
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn resolve_library_note_rejects_paths_outside_library() {
+        let base = std::env::temp_dir().join(format!("library-test-{}", uuid::Uuid::new_v4()));
+        let library = base.join("lib");
+        std::fs::create_dir_all(library.join("Rust")).unwrap();
+        std::fs::write(library.join("Rust").join("a.md"), "# A").unwrap();
+        std::fs::write(base.join("outside.md"), "# Outside").unwrap();
+
+        let (_, relative) = resolve_library_note(&library, "Rust/a.md").unwrap();
+        assert_eq!(relative, "Rust/a.md");
+        assert!(resolve_library_note(&library, "../outside.md").is_err());
+        assert!(resolve_library_note(&library, "Rust/missing.md").is_err());
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -1,16 +1,28 @@
 // cache.json 管理模块
 // 负责管理文件元数据的缓存
 
-use super::metadata::{try_parse_front_matter, CacheConfig, CategoryMetadata, FileMetadata};
-use super::workspace::{read_cache, read_cache_silent, write_cache};
+use super::code_snippets::{is_snippet_path, load_code_extensions};
+use super::ignore::load_workspace_ignore;
+use super::metadata::{
+    try_parse_front_matter, CacheConfig, CategoryMetadata, CodeSnippetMetadata, FileMetadata,
+};
+use super::workspace::{
+    read_cache, read_cache_silent, read_snippet_metadata, write_cache, write_snippet_metadata,
+};
 use log::{info, warn};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// 从 .md 文件的 Frontmatter 提取索引所需的核心字段（id/created/modified/size）。
 /// cache.json 只作为文件系统索引，内容元数据（tags/type/language/favorite）
-/// 统一从 Frontmatter 读取，不再写入 cache.json。
+/// 统一从 Frontmatter 读取，不再写入 cache.json。代码片段文件没有 Frontmatter，
+/// 直接使用文件系统时间戳。
 fn build_index_metadata(file_path: &Path) -> FileMetadata {
-    if let Ok(raw) = std::fs::read_to_string(file_path) {
+    let is_markdown = file_path.extension().and_then(|s| s.to_str()) == Some("md");
+    if let Some(raw) = is_markdown
+        .then(|| std::fs::read_to_string(file_path).ok())
+        .flatten()
+    {
         let (fm_opt, body) = try_parse_front_matter(&raw);
         if let Some(fm) = fm_opt {
             return FileMetadata {
@@ -50,11 +62,37 @@ fn build_index_metadata(file_path: &Path) -> FileMetadata {
 
 // Cache 管理器
 //
-// 负责管理 cache.json 文件，提供文件和分类元数据的读写操作
+// 负责管理 cache.json 文件，提供文件和分类元数据的读写操作；代码片段元数据单独存放在
+// 随工作区同步的 snippets.json 中
 #[derive(Clone)]
 pub struct CacheManager {
     config_dir: PathBuf,
     cache: CacheConfig,
+    // 代码片段元数据只在应用内删除或重命名片段时变更条目；文件暂时缺失（尚未同步、被忽略、
+    // 扩展名停用）时保留，避免把另一台设备上的元数据同步删除
+    snippets: HashMap<String, CodeSnippetMetadata>,
+    // snippets.json 无法解析时不写回，避免覆盖用户或其他设备的内容
+    snippets_writable: bool,
+}
+
+// 读取 snippets.json，并合入旧版本存放在 cache.json 中的条目
+fn load_snippet_metadata(
+    config_dir: &Path,
+    cache: &mut CacheConfig,
+) -> (HashMap<String, CodeSnippetMetadata>, bool) {
+    let legacy = std::mem::take(&mut cache.snippets);
+    match read_snippet_metadata(config_dir) {
+        Ok(mut snippets) => {
+            for (path, metadata) in legacy {
+                snippets.entry(path).or_insert(metadata);
+            }
+            (snippets, true)
+        }
+        Err(e) => {
+            warn!("⚠️ [CacheManager] {}，代码片段元数据暂不写回", e);
+            (legacy, false)
+        }
+    }
 }
 
 impl CacheManager {
@@ -78,7 +116,7 @@ impl CacheManager {
             read_cache(&config_dir)
         };
 
-        let cache = match cache_result {
+        let mut cache = match cache_result {
             Ok(c) => c,
             Err(e) => {
                 warn!(
@@ -88,21 +126,47 @@ impl CacheManager {
                 CacheConfig::default()
             }
         };
-
-        Ok(Self { config_dir, cache })
+        let (snippets, snippets_writable) = load_snippet_metadata(&config_dir, &mut cache);
+
+        Ok(Self {
+            config_dir,
+            cache,
+            snippets,
+            snippets_writable,
+        })
     }
 
-    // 保存 cache 到文件
+    // 保存 cache 到文件（先写 snippets.json，迁移旧条目时不会因中途失败丢失）
     pub fn save(&self) -> Result<(), String> {
+        if self.snippets_writable
+            && (!self.snippets.is_empty() || self.config_dir.join("snippets.json").exists())
+        {
+            write_snippet_metadata(&self.config_dir, &self.snippets)?;
+        }
         write_cache(&self.config_dir, &self.cache)
     }
 
+    // 重新读取 snippets.json（同步拉取可能带来其他设备的修改）
+    pub fn reload_snippet_metadata(&mut self) {
+        match read_snippet_metadata(&self.config_dir) {
+            Ok(snippets) => {
+                self.snippets = snippets;
+                self.snippets_writable = true;
+            }
+            Err(e) => {
+                warn!("⚠️ [CacheManager] {}", e);
+                self.snippets_writable = false;
+            }
+        }
+    }
+
     // 从磁盘重新加载 cache.json（用于迁移后刷新内存状态）
     #[allow(dead_code)]
     pub fn reload_from_disk(&mut self) -> Result<(), String> {
         match read_cache(&self.config_dir) {
             Ok(new_cache) => {
                 self.cache = new_cache;
+                self.reload_snippet_metadata();
                 Ok(())
             }
             Err(e) => Err(format!("重新加载 cache.json 失败: {}", e)),
@@ -152,9 +216,38 @@ impl CacheManager {
     // # Arguments
     // * `file_path` - 文件相对路径
     pub fn remove_file_metadata(&mut self, file_path: &str) -> Option<FileMetadata> {
+        self.snippets.remove(file_path);
         self.cache.files.remove(file_path)
     }
 
+    // 重命名/移动文件时迁移元数据（含代码片段元数据）
+    //
+    // # Arguments
+    // * `old_path` - 原相对路径
+    // * `new_path` - 新相对路径
+    pub fn rename_file_metadata(&mut self, old_path: &str, new_path: &str) {
+        if let Some(metadata) = self.cache.files.remove(old_path) {
+            self.cache.files.insert(new_path.to_string(), metadata);
+        }
+        if let Some(snippet) = self.snippets.remove(old_path) {
+            self.snippets.insert(new_path.to_string(), snippet);
+        }
+    }
+
+    // 获取代码片段元数据（tags/favorite/kind 等，Markdown 文件存于 Frontmatter）
+    pub fn get_snippet_metadata(&self, file_path: &str) -> Option<&CodeSnippetMetadata> {
+        self.snippets.get(file_path)
+    }
+
+    // 设置代码片段元数据；全部为默认值时删除条目
+    pub fn set_snippet_metadata(&mut self, file_path: String, metadata: CodeSnippetMetadata) {
+        if metadata == CodeSnippetMetadata::default() {
+            self.snippets.remove(&file_path);
+        } else {
+            self.snippets.insert(file_path, metadata);
+        }
+    }
+
     // 获取分类元数据
     //
     // # Arguments
@@ -259,6 +352,33 @@ impl CacheManager {
         removed_count
     }
 
+    /// 清理被 .snippetsignore 排除的文件元数据（忽略规则变化后调用）
+    pub fn cleanup_ignored_files(&mut self, workspace_root: &Path) -> usize {
+        let ignore = load_workspace_ignore(workspace_root);
+        if ignore.is_empty() {
+            return 0;
+        }
+
+        let before = self.cache.files.len();
+        self.cache.files.retain(|file_path, _| {
+            let ignored = ignore.is_ignored(file_path, false);
+            if ignored {
+                info!("🙈 清理被忽略的文件元数据: {}", file_path);
+            }
+            !ignored
+        });
+        before - self.cache.files.len()
+    }
+
+    /// 清理不再启用为代码片段的文件元数据（扩展名设置变化后调用）
+    pub fn cleanup_disabled_code_files(&mut self, extensions: &[String]) -> usize {
+        let before = self.cache.files.len();
+        self.cache
+            .files
+            .retain(|file_path, _| is_snippet_path(Path::new(file_path), extensions));
+        before - self.cache.files.len()
+    }
+
     // 重建缓存（扫描工作区所有文件）
     //
     // # Arguments
@@ -270,12 +390,16 @@ impl CacheManager {
         use walkdir::WalkDir;
 
         info!("🔄 开始重建缓存...");
+        self.reload_snippet_metadata();
 
         let mut file_count = 0;
+        let ignore = load_workspace_ignore(workspace_root);
+        let code_extensions = load_code_extensions(workspace_root);
 
         for entry in WalkDir::new(workspace_root)
             .follow_links(true)
             .into_iter()
+            .filter_entry(|e| !ignore.is_entry_ignored(workspace_root, e))
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
@@ -289,8 +413,8 @@ impl CacheManager {
                 }
             }
 
-            // 只处理 .md 文件
-            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("md") {
+            // 只处理 .md 文件和启用的代码片段文件
+            if !path.is_file() || !is_snippet_path(path, &code_extensions) {
                 continue;
             }
 
@@ -325,8 +449,13 @@ impl CacheManager {
     /// # Returns
     /// * `Result<(), String>` - 成功或错误信息
     pub fn add_file(&mut self, file_path: &Path, workspace_root: &Path) -> Result<(), String> {
-        // 只处理 .md 文件
-        if file_path.extension().and_then(|s| s.to_str()) != Some("md") {
+        // 只处理 .md 文件和启用的代码片段文件
+        if !is_snippet_path(file_path, &load_code_extensions(workspace_root)) {
+            return Ok(());
+        }
+
+        // 被 .snippetsignore 排除的文件不进入缓存
+        if load_workspace_ignore(workspace_root).is_path_ignored(workspace_root, file_path, false) {
             return Ok(());
         }
 
@@ -365,10 +494,15 @@ impl CacheManager {
         workspace_root: &Path,
     ) -> Result<usize, String> {
         let mut added_count = 0;
+        self.reload_snippet_metadata();
+        let ignore = load_workspace_ignore(workspace_root);
+        let code_extensions = load_code_extensions(workspace_root);
 
         for file_path in file_paths {
-            // 只处理 .md 文件
-            if !file_path.ends_with(".md") {
+            // 只处理 .md 文件和启用的代码片段文件，跳过 .snippetsignore 排除的文件
+            if !is_snippet_path(Path::new(file_path), &code_extensions)
+                || ignore.is_ignored(file_path, false)
+            {
                 continue;
             }
 
@@ -417,8 +551,8 @@ impl CacheManager {
     /// # Returns
     /// * `Result<(), String>` - 成功或错误信息
     pub fn update_file(&mut self, file_path: &Path, workspace_root: &Path) -> Result<(), String> {
-        // 只处理 .md 文件
-        if file_path.extension().and_then(|s| s.to_str()) != Some("md") {
+        // 只处理 .md 文件和启用的代码片段文件
+        if !is_snippet_path(file_path, &load_code_extensions(workspace_root)) {
             return Ok(());
         }
 
@@ -698,3 +832,99 @@ impl CacheManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_config_dir() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("cache-manager-test-{}", uuid::Uuid::new_v4()))
+            .join(".snippets-code")
+    }
+
+    fn tagged(tag: &str) -> CodeSnippetMetadata {
+        CodeSnippetMetadata {
+            tags: vec![tag.to_string()],
+            favorite: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn snippet_metadata_lives_in_sidecar_and_survives_cleanup() {
+        let config_dir = temp_config_dir();
+        let mut cache = CacheManager::new(config_dir.clone()).unwrap();
+        cache.set_file_metadata(
+            "hooks/useFetch.ts".to_string(),
+            build_index_metadata(Path::new("missing.ts")),
+        );
+        cache.set_snippet_metadata("hooks/useFetch.ts".to_string(), tagged("react"));
+
+        // 停用扩展名和清理缺失文件只影响 cache.json 中的文件索引
+        cache.cleanup_disabled_code_files(&[]);
+        cache.cleanup_missing_files(config_dir.parent().unwrap());
+        cache.save().unwrap();
+
+        let cache_json = fs::read_to_string(config_dir.join("cache.json")).unwrap();
+        assert!(!cache_json.contains("react"));
+        let reloaded = CacheManager::new(config_dir.clone()).unwrap();
+        assert_eq!(
+            reloaded.get_snippet_metadata("hooks/useFetch.ts"),
+            Some(&tagged("react"))
+        );
+
+        let _ = fs::remove_dir_all(config_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn legacy_cache_entries_move_to_sidecar() {
+        let config_dir = temp_config_dir();
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("cache.json"),
+            r#"{"version":"1.0.0","files":{},"categories":{},"snippets":{"a.ts":{"tags":["legacy"],"favorite":true}}}"#,
+        )
+        .unwrap();
+
+        let cache = CacheManager::new(config_dir.clone()).unwrap();
+        assert_eq!(cache.get_snippet_metadata("a.ts"), Some(&tagged("legacy")));
+        cache.save().unwrap();
+
+        assert!(fs::read_to_string(config_dir.join("snippets.json"))
+            .unwrap()
+            .contains("legacy"));
+        assert!(!fs::read_to_string(config_dir.join("cache.json"))
+            .unwrap()
+            .contains("legacy"));
+
+        // cache.json 损坏时，片段元数据仍从 snippets.json 读取
+        fs::write(config_dir.join("cache.json"), "{").unwrap();
+        let recovered = CacheManager::new(config_dir.clone()).unwrap();
+        assert_eq!(
+            recovered.get_snippet_metadata("a.ts"),
+            Some(&tagged("legacy"))
+        );
+
+        let _ = fs::remove_dir_all(config_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn unreadable_sidecar_is_not_overwritten() {
+        let config_dir = temp_config_dir();
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join("snippets.json"), "not json").unwrap();
+
+        let mut cache = CacheManager::new(config_dir.clone()).unwrap();
+        cache.set_snippet_metadata("a.ts".to_string(), tagged("new"));
+        cache.save().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(config_dir.join("snippets.json")).unwrap(),
+            "not json"
+        );
+
+        let _ = fs::remove_dir_all(config_dir.parent().unwrap());
+    }
+}
@@ -0,0 +1,791 @@
+// 片段语义元数据推断（language / framework / kind）
+//
+// 纯启发式：代码块 info string、shebang、文件扩展名、import 语句和语法特征分别加权计分，
+// 得分不足时不给出建议。推断结果只用于填补空字段，从不覆盖用户显式填写的值。
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::code_snippets::{
+    code_snippet_info, is_code_snippet_path, language_for_extension, load_code_extensions,
+};
+use crate::markdown::file_ops::{get_relative_path, is_within_workspace};
+use crate::markdown::file_system_manager::FileSystemManager;
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::metadata::try_parse_front_matter;
+use crate::markdown::watcher::FileWatcher;
+use crate::markdown::{CacheManager, IndexManager};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{command, AppHandle, State};
+
+// 证据权重
+const WEIGHT_EXTENSION: u32 = 10;
+const WEIGHT_SHEBANG: u32 = 6;
+const WEIGHT_FENCE: u32 = 4;
+const WEIGHT_IMPORT: u32 = 3;
+const WEIGHT_SIGNATURE: u32 = 1;
+// 仅凭语法特征时至少需要的得分
+const MIN_SCORE: u32 = 2;
+
+// 不代表编程语言的代码块 info string
+const NON_CODE_FENCES: &[&str] = &[
+    "text",
+    "txt",
+    "plain",
+    "plaintext",
+    "output",
+    "console",
+    "log",
+    "diff",
+    "mermaid",
+    "math",
+    "latex",
+    "markdown",
+    "md",
+];
+
+// 语法特征：(子串, 语言)
+const LANGUAGE_SIGNATURES: &[(&str, &str)] = &[
+    ("fn main()", "rust"),
+    ("let mut ", "rust"),
+    ("pub fn ", "rust"),
+    ("use std::", "rust"),
+    ("#[derive(", "rust"),
+    ("impl ", "rust"),
+    ("def ", "python"),
+    ("elif ", "python"),
+    ("__name__", "python"),
+    ("self.", "python"),
+    ("package main", "go"),
+    ("func ", "go"),
+    (":= ", "go"),
+    ("fmt.", "go"),
+    (": string", "typescript"),
+    (": number", "typescript"),
+    ("interface ", "typescript"),
+    ("export type ", "typescript"),
+    (" as const", "typescript"),
+    ("console.log(", "javascript"),
+    ("module.exports", "javascript"),
+    ("require(", "javascript"),
+    ("=> {", "javascript"),
+    ("public class ", "java"),
+    ("System.out.println", "java"),
+    ("public static void main", "java"),
+    ("using System", "csharp"),
+    ("Console.WriteLine", "csharp"),
+    ("SELECT ", "sql"),
+    ("INSERT INTO ", "sql"),
+    ("CREATE TABLE ", "sql"),
+    ("WHERE ", "sql"),
+    ("echo ", "bash"),
+    ("fi\n", "bash"),
+    ("done\n", "bash"),
+    ("sudo ", "bash"),
+    ("<?php", "php"),
+    ("<!DOCTYPE", "html"),
+    ("</div>", "html"),
+    ("display: flex", "css"),
+    ("margin:", "css"),
+    ("px;", "css"),
+    ("<template>", "vue"),
+    ("<script setup", "vue"),
+];
+
+// 框架特征：(子串, 框架, 权重)
+const FRAMEWORK_SIGNATURES: &[(&str, &str, u32)] = &[
+    ("from 'vue'", "vue", WEIGHT_IMPORT),
+    ("from \"vue\"", "vue", WEIGHT_IMPORT),
+    ("<template>", "vue", WEIGHT_IMPORT),
+    ("<script setup", "vue", WEIGHT_IMPORT),
+    ("defineProps(", "vue", WEIGHT_SIGNATURE),
+    ("defineComponent(", "vue", WEIGHT_SIGNATURE),
+    ("from 'react'", "react", WEIGHT_IMPORT),
+    ("from \"react\"", "react", WEIGHT_IMPORT),
+    ("import React", "react", WEIGHT_IMPORT),
+    ("useState(", "react", WEIGHT_SIGNATURE),
+    ("useEffect(", "react", WEIGHT_SIGNATURE),
+    ("className=", "react", WEIGHT_SIGNATURE),
+    ("from 'next/", "nextjs", WEIGHT_IMPORT),
+    ("from \"next/", "nextjs", WEIGHT_IMPORT),
+    ("defineNuxtConfig(", "nuxt", WEIGHT_IMPORT),
+    ("from '#app'", "nuxt", WEIGHT_IMPORT),
+    ("from 'svelte", "svelte", WEIGHT_IMPORT),
+    ("from \"svelte", "svelte", WEIGHT_IMPORT),
+    ("@angular/", "angular", WEIGHT_IMPORT),
+    ("@Component(", "angular", WEIGHT_SIGNATURE),
+    ("require('express')", "express", WEIGHT_IMPORT),
+    ("from 'express'", "express", WEIGHT_IMPORT),
+    ("from \"express\"", "express", WEIGHT_IMPORT),
+    ("@tailwind ", "tailwind", WEIGHT_IMPORT),
+    ("from django", "django", WEIGHT_IMPORT),
+    ("from flask", "flask", WEIGHT_IMPORT),
+    ("Flask(__name__)", "flask", WEIGHT_SIGNATURE),
+    ("from fastapi", "fastapi", WEIGHT_IMPORT),
+    ("#[tauri::command]", "tauri", WEIGHT_IMPORT),
+    ("@tauri-apps/", "tauri", WEIGHT_IMPORT),
+    ("#[tokio::main]", "tokio", WEIGHT_IMPORT),
+    ("use tokio", "tokio", WEIGHT_IMPORT),
+    ("org.springframework", "spring", WEIGHT_IMPORT),
+    ("@SpringBootApplication", "spring", WEIGHT_IMPORT),
+];
+
+// 元框架得分足够时优先于其基础框架：(元框架, 基础框架)
+const FRAMEWORK_REFINEMENTS: &[(&str, &str)] = &[("nextjs", "react"), ("nuxt", "vue")];
+
+/// 推断出的语义元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSuggestion {
+    pub language: Option<String>,
+    pub framework: Option<String>,
+    pub kind: Option<String>,
+}
+
+impl MetadataSuggestion {
+    pub fn is_empty(&self) -> bool {
+        self.language.is_none() && self.framework.is_none() && self.kind.is_none()
+    }
+
+    /// 只保留当前为空的字段（显式值优先）
+    pub fn only_missing(self, current: &MetadataSuggestion) -> Self {
+        let missing = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+        Self {
+            language: self.language.filter(|_| missing(&current.language)),
+            framework: self.framework.filter(|_| missing(&current.framework)),
+            kind: self.kind.filter(|_| missing(&current.kind)),
+        }
+    }
+}
+
+// 将代码块 info string 规范化为语言名
+fn normalize_fence_language(info: &str) -> Option<String> {
+    let tag = info
+        .trim()
+        .trim_start_matches(['{', '.'])
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '}' || c == '{')
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    if tag.is_empty() || NON_CODE_FENCES.contains(&tag.as_str()) {
+        return None;
+    }
+
+    let language = match tag.as_str() {
+        "typescript" | "javascript" | "python" | "rust" | "kotlin" | "ruby" | "csharp" => {
+            return Some(tag)
+        }
+        "shell" | "sh" | "zsh" | "shellscript" => "bash",
+        "golang" => "go",
+        "c++" => "cpp",
+        "c#" => "csharp",
+        "pwsh" => "powershell",
+        "node" => "javascript",
+        _ => match language_for_extension(&tag) {
+            Some(language) => language,
+            // 其他合法的语言名原样保留
+            None if tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '+') =>
+            {
+                return Some(tag)
+            }
+            None => return None,
+        },
+    };
+    Some(language.to_string())
+}
+
+// 从 shebang 行推断语言
+fn shebang_language(line: &str) -> Option<&'static str> {
+    let line = line.trim();
+    if !line.starts_with("#!") {
+        return None;
+    }
+    let language = if line.contains("python") {
+        "python"
+    } else if line.contains("deno") || line.contains("ts-node") {
+        "typescript"
+    } else if line.contains("node") || line.contains("bun") {
+        "javascript"
+    } else if line.contains("pwsh") {
+        "powershell"
+    } else if line.contains("ruby") {
+        "ruby"
+    } else if line.contains("bash") || line.contains("/sh") || line.contains("zsh") {
+        "bash"
+    } else {
+        return None;
+    };
+    Some(language)
+}
+
+struct FencedBlock {
+    info: String,
+    code: String,
+}
+
+// 提取围栏代码块（``` 或 ~~~，关闭围栏不短于开启围栏）
+fn fenced_blocks(content: &str) -> Vec<FencedBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(char, usize, FencedBlock)> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        let fence_len = fence_char
+            .map(|c| trimmed.chars().take_while(|x| *x == c).count())
+            .unwrap_or(0);
+
+        match current.take() {
+            Some((open_char, open_len, block)) => {
+                if fence_char == Some(open_char)
+                    && fence_len >= open_len
+                    && trimmed[fence_len..].trim().is_empty()
+                {
+                    blocks.push(block);
+                } else {
+                    let mut block = block;
+                    block.code.push_str(line);
+                    block.code.push('\n');
+                    current = Some((open_char, open_len, block));
+                }
+            }
+            None => {
+                if let Some(c) = fence_char.filter(|_| fence_len >= 3) {
+                    current = Some((
+                        c,
+                        fence_len,
+                        FencedBlock {
+                            info: trimmed[fence_len..].trim().to_string(),
+                            code: String::new(),
+                        },
+                    ));
+                }
+            }
+        }
+    }
+    // 未闭合的代码块同样计入
+    if let Some((_, _, block)) = current {
+        blocks.push(block);
+    }
+    blocks
+}
+
+// 取得分最高的项（同分取先出现的，保证结果稳定）
+fn best<'a>(scores: &[(&'a str, u32)], min_score: u32) -> Option<&'a str> {
+    let mut best: Option<(&str, u32)> = None;
+    for &(name, score) in scores {
+        if score >= min_score && best.is_none_or(|(_, s)| score > s) {
+            best = Some((name, score));
+        }
+    }
+    best.map(|(name, _)| name)
+}
+
+fn add_score<'a>(scores: &mut Vec<(&'a str, u32)>, name: &'a str, weight: u32) {
+    match scores.iter_mut().find(|(n, _)| *n == name) {
+        Some(entry) => entry.1 += weight,
+        None => scores.push((name, weight)),
+    }
+}
+
+// 是否定义了 React/Vue 风格的 hook（function useXxx / const useXxx =）
+fn defines_hook(code: &str) -> bool {
+    ["function use", "const use", "export function use"]
+        .iter()
+        .any(|prefix| {
+            code.match_indices(prefix).any(|(i, _)| {
+                code[i + prefix.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_uppercase())
+            })
+        })
+}
+
+/// 推断片段的 language / framework / kind
+///
+/// `file_name` 用于扩展名和文件名提示（如 `vite.config.ts`、`useDebounce.ts`）。
+pub fn infer_metadata(content: &str, file_name: Option<&str>) -> MetadataSuggestion {
+    let (_, body) = try_parse_front_matter(content);
+    let blocks = fenced_blocks(&body);
+
+    // 有代码块时只分析代码块内容，避免正文文字误判
+    let code = if blocks.is_empty() {
+        body.clone()
+    } else {
+        blocks
+            .iter()
+            .map(|b| b.code.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let extension = file_name
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .filter(|ext| ext != "md");
+    let stem = file_name
+        .and_then(|name| Path::new(name).file_stem())
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+
+    // ── language ──
+    let mut fence_languages: Vec<String> = Vec::new();
+    let mut language_scores: Vec<(&str, u32)> = Vec::new();
+    for block in &blocks {
+        if let Some(language) = normalize_fence_language(&block.info) {
+            fence_languages.push(language);
+        }
+    }
+    for language in &fence_languages {
+        add_score(&mut language_scores, language, WEIGHT_FENCE);
+    }
+    if let Some(language) = extension.as_deref().and_then(language_for_extension) {
+        add_score(&mut language_scores, language, WEIGHT_EXTENSION);
+    }
+    let first_lines = std::iter::once(body.as_str())
+        .chain(blocks.iter().map(|b| b.code.as_str()))
+        .filter_map(|text| text.lines().next());
+    for line in first_lines {
+        if let Some(language) = shebang_language(line) {
+            add_score(&mut language_scores, language, WEIGHT_SHEBANG);
+        }
+    }
+    for (pattern, language) in LANGUAGE_SIGNATURES {
+        if code.contains(pattern) {
+            add_score(&mut language_scores, language, WEIGHT_SIGNATURE);
+        }
+    }
+
+    let mut language = best(&language_scores, MIN_SCORE).map(str::to_string);
+    // 出现 TypeScript 特有语法时优先 TypeScript
+    if language.as_deref() == Some("javascript")
+        && language_scores
+            .iter()
+            .any(|(n, s)| *n == "typescript" && *s >= MIN_SCORE)
+    {
+        language = Some("typescript".to_string());
+    }
+
+    // ── framework ──
+    let mut framework_scores: Vec<(&str, u32)> = Vec::new();
+    for (pattern, framework, weight) in FRAMEWORK_SIGNATURES {
+        if body.contains(pattern) {
+            add_score(&mut framework_scores, framework, *weight);
+        }
+    }
+    for language in &fence_languages {
+        if matches!(language.as_str(), "vue" | "svelte") {
+            add_score(&mut framework_scores, language, WEIGHT_FENCE);
+        }
+    }
+    if let Some(framework) = extension
+        .as_deref()
+        .filter(|ext| matches!(*ext, "vue" | "svelte"))
+    {
+        add_score(&mut framework_scores, framework, WEIGHT_EXTENSION);
+    }
+    let mut framework = best(&framework_scores, MIN_SCORE);
+    for (refined, base) in FRAMEWORK_REFINEMENTS {
+        if framework == Some(*base)
+            && framework_scores
+                .iter()
+                .any(|(n, s)| n == refined && *s >= WEIGHT_IMPORT)
+        {
+            framework = Some(refined);
+        }
+    }
+    let framework = framework.map(str::to_string);
+
+    // ── kind ──
+    let is_test = code.contains("#[test]")
+        || code.contains("def test_")
+        || ((code.contains("describe(") || code.contains("it(") || code.contains("test("))
+            && code.contains("expect("));
+    let is_hook = defines_hook(&code)
+        || (stem.starts_with("use") && stem[3..].starts_with(|c: char| c.is_ascii_uppercase()));
+    let is_component = (matches!(framework.as_deref(), Some("vue" | "svelte"))
+        && (code.contains("<template>") || code.contains("<script")))
+        || code.contains("@Component(")
+        || (matches!(framework.as_deref(), Some("react" | "nextjs"))
+            && code.contains("return (")
+            && code.contains("</"));
+    let is_config = stem.ends_with(".config")
+        || (stem.ends_with("rc")
+            && matches!(extension.as_deref(), Some("json" | "js" | "yaml" | "yml")))
+        || matches!(extension.as_deref(), Some("toml" | "ini" | "yaml" | "yml"))
+        || code.contains("defineConfig(")
+        || code.contains("defineNuxtConfig(");
+    let has_shebang = language_scores.iter().any(|(_, s)| *s >= WEIGHT_SHEBANG)
+        && body.trim_start().starts_with("#!");
+
+    let kind = if is_test {
+        Some("test")
+    } else if is_hook {
+        Some("hook")
+    } else if is_component {
+        Some("component")
+    } else if is_config {
+        Some("config")
+    } else {
+        match language.as_deref() {
+            Some("sql") => Some("query"),
+            Some("css" | "scss" | "less") => Some("style"),
+            Some("bash" | "powershell" | "batch") => Some("script"),
+            _ if has_shebang => Some("script"),
+            _ => None,
+        }
+    }
+    .map(str::to_string);
+
+    MetadataSuggestion {
+        language,
+        framework,
+        kind,
+    }
+}
+
+// ============= 批量建议 =============
+
+// 单个文件的元数据建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSuggestionItem {
+    pub file_path: String,
+    pub title: String,
+    pub current: MetadataSuggestion,
+    // 仅包含当前为空的字段
+    pub suggested: MetadataSuggestion,
+}
+
+// 读取文件当前的语义元数据和标题
+fn read_current_metadata(
+    workspace_root: &Path,
+    path: &Path,
+    code_extensions: &[String],
+    cache: &CacheManager,
+) -> Result<(String, MetadataSuggestion, String), String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    if is_code_snippet_path(path, code_extensions) {
+        let relative_path = get_relative_path(workspace_root, path)?;
+        let meta = cache
+            .get_snippet_metadata(&relative_path)
+            .cloned()
+            .unwrap_or_default();
+        let title = code_snippet_info(path, &relative_path, cache).title;
+        let current = MetadataSuggestion {
+            language: meta.language,
+            framework: meta.framework,
+            kind: meta.kind,
+        };
+        return Ok((title, current, raw));
+    }
+
+    let (fm_opt, _) = try_parse_front_matter(&raw);
+    let title = fm_opt
+        .as_ref()
+        .map(|fm| fm.title.clone())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Untitled")
+                .to_string()
+        });
+    let current = fm_opt
+        .map(|fm| MetadataSuggestion {
+            language: fm.language,
+            framework: fm.framework,
+            kind: fm.kind,
+        })
+        .unwrap_or_default();
+    Ok((title, current, raw))
+}
+
+fn suggest_for_file(
+    workspace_root: &Path,
+    path: &Path,
+    code_extensions: &[String],
+    cache: &CacheManager,
+) -> Result<MetadataSuggestionItem, String> {
+    let (title, current, raw) =
+        read_current_metadata(workspace_root, path, code_extensions, cache)?;
+    let file_name = path.file_name().and_then(|n| n.to_str());
+    let suggested = infer_metadata(&raw, file_name).only_missing(&current);
+    Ok(MetadataSuggestionItem {
+        file_path: path.to_string_lossy().to_string(),
+        title,
+        current,
+        suggested,
+    })
+}
+
+// 将建议写入单个文件（只填补空字段），返回是否有改动
+fn apply_suggestion(
+    workspace_root: &Path,
+    path: &Path,
+    suggestion: &MetadataSuggestion,
+    code_extensions: &[String],
+    cache: &mut CacheManager,
+) -> Result<bool, String> {
+    let relative_path = get_relative_path(workspace_root, path)?;
+
+    if is_code_snippet_path(path, code_extensions) {
+        let mut meta = cache
+            .get_snippet_metadata(&relative_path)
+            .cloned()
+            .unwrap_or_default();
+        let current = MetadataSuggestion {
+            language: meta.language.clone(),
+            framework: meta.framework.clone(),
+            kind: meta.kind.clone(),
+        };
+        let fill = suggestion.clone().only_missing(&current);
+        if fill.is_empty() {
+            return Ok(false);
+        }
+        if fill.language.is_some() {
+            meta.language = fill.language;
+        }
+        if fill.framework.is_some() {
+            meta.framework = fill.framework;
+        }
+        if fill.kind.is_some() {
+            meta.kind = fill.kind;
+        }
+        cache.set_snippet_metadata(relative_path, meta);
+        return Ok(true);
+    }
+
+    let fs_manager = FileSystemManager::new(workspace_root.to_path_buf());
+    let (mut fm, _) = fs_manager.read_markdown_file(path)?;
+    let current = MetadataSuggestion {
+        language: fm.language.clone(),
+        framework: fm.framework.clone(),
+        kind: fm.kind.clone(),
+    };
+    let fill = suggestion.clone().only_missing(&current);
+    if fill.is_empty() {
+        return Ok(false);
+    }
+
+    // 无 Frontmatter 的文件沿用 cache 中的 id 和创建时间
+    if try_parse_front_matter(&fs::read_to_string(path).unwrap_or_default())
+        .0
+        .is_none()
+    {
+        if let Some(file_meta) = cache.get_file_metadata(&relative_path) {
+            fm.id = file_meta.id.clone();
+            if let Some(created) = chrono::DateTime::from_timestamp_millis(file_meta.created) {
+                fm.created = created.to_rfc3339();
+            }
+        }
+    }
+    if fill.language.is_some() {
+        fm.language = fill.language;
+    }
+    if fill.framework.is_some() {
+        fm.framework = fill.framework;
+    }
+    if fill.kind.is_some() {
+        fm.kind = fill.kind;
+    }
+    fm.modified = chrono::Utc::now().to_rfc3339();
+    fs_manager.update_file_frontmatter(path, &fm)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let _ = cache.update_file_metadata(&relative_path, |m| m.modified = now);
+    Ok(true)
+}
+
+// 工作区内所有片段文件（Markdown 与启用的代码文件），遵守 .snippetsignore
+fn collect_snippet_files(workspace_root: &Path, code_extensions: &[String]) -> Vec<PathBuf> {
+    let ignore = load_workspace_ignore(workspace_root);
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(workspace_root)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || (!e.file_name().to_string_lossy().starts_with('.')
+                    && !ignore.is_entry_ignored(workspace_root, e))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| {
+            p.extension().and_then(|s| s.to_str()) == Some("md")
+                || is_code_snippet_path(p, code_extensions)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+// ============= 命令 =============
+
+// 推断单个文件的语义元数据（只返回当前为空的字段）
+#[command]
+pub fn suggest_note_metadata(
+    app_handle: AppHandle,
+    file_path: String,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<MetadataSuggestionItem, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let path = PathBuf::from(&file_path);
+    if !is_within_workspace(&workspace_root, &path) {
+        return Err("文件不在工作区内".to_string());
+    }
+    let code_extensions = load_code_extensions(&workspace_root);
+    let cache = cache_manager
+        .read()
+        .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+    suggest_for_file(&workspace_root, &path, &code_extensions, &cache)
+}
+
+// 生成工作区的批量元数据建议报告（只列出有建议的文件）
+#[command]
+pub async fn suggest_workspace_metadata(
+    app_handle: AppHandle,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<Vec<MetadataSuggestionItem>, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let code_extensions = load_code_extensions(&workspace_root);
+    let cache = cache_manager
+        .read()
+        .map_err(|e| format!("获取 cache 锁失败: {}", e))?
+        .clone();
+
+    let report = tokio::task::spawn_blocking(move || {
+        collect_snippet_files(&workspace_root, &code_extensions)
+            .into_iter()
+            .filter_map(|path| {
+                match suggest_for_file(&workspace_root, &path, &code_extensions, &cache) {
+                    Ok(item) if !item.suggested.is_empty() => Some(item),
+                    Ok(_) => None,
+                    Err(e) => {
+                        warn!("⚠️ [元数据推断] {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("生成元数据建议失败: {}", e))?;
+
+    info!("🏷️ [元数据推断] 共 {} 个文件有建议", report.len());
+    Ok(report)
+}
+
+// 应用选中的元数据建议（只填补空字段，不覆盖已有值），返回实际更新的文件数
+#[command]
+pub async fn apply_metadata_suggestions(
+    app_handle: AppHandle,
+    suggestions: HashMap<String, MetadataSuggestion>,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    watcher: State<'_, Arc<Mutex<Option<FileWatcher>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<usize, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let code_extensions = load_code_extensions(&workspace_root);
+
+    let mut updated: Vec<PathBuf> = Vec::new();
+    {
+        let mut cache = cache_manager
+            .write()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        for (file_path, suggestion) in &suggestions {
+            let path = PathBuf::from(file_path);
+            if !is_within_workspace(&workspace_root, &path) || !path.is_file() {
+                warn!("⚠️ [元数据推断] 跳过无效路径: {}", file_path);
+                continue;
+            }
+            if let Ok(watcher_lock) = watcher.lock() {
+                if let Some(ref w) = *watcher_lock {
+                    w.ignore_next_change(path.clone());
+                }
+            }
+            match apply_suggestion(
+                &workspace_root,
+                &path,
+                suggestion,
+                &code_extensions,
+                &mut cache,
+            ) {
+                Ok(true) => updated.push(path),
+                Ok(false) => debug!("⏭️ [元数据推断] 无需更新: {}", file_path),
+                Err(e) => warn!("⚠️ [元数据推断] 应用建议失败 {}: {}", file_path, e),
+            }
+        }
+        if !updated.is_empty() {
+            cache.save()?;
+        }
+    }
+
+    if let Ok(manager_lock) = index_manager.read() {
+        if let Some(ref manager) = *manager_lock {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            for path in &updated {
+                let _ = manager.update_entry(path, &workspace_root, &cache);
+            }
+        }
+    }
+
+    info!("✅ [元数据推断] 已更新 {} 个文件", updated.len());
+    Ok(updated.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_from_fences_imports_and_signatures() {
+        let vue = "# 按钮\n\n```vue\n<template><button /></template>\n<script setup lang=\"ts\">\nimport { ref } from 'vue'\n</script>\n```\n";
+        let suggestion = infer_metadata(vue, Some("按钮.md"));
+        assert_eq!(suggestion.language.as_deref(), Some("vue"));
+        assert_eq!(suggestion.framework.as_deref(), Some("vue"));
+        assert_eq!(suggestion.kind.as_deref(), Some("component"));
+
+        let hook = "import { useState } from 'react'\n\nexport function useToggle(initial: boolean) {\n  const [on, setOn] = useState(initial)\n  return [on, () => setOn(!on)] as const\n}\n";
+        let suggestion = infer_metadata(hook, Some("useToggle.ts"));
+        assert_eq!(suggestion.language.as_deref(), Some("typescript"));
+        assert_eq!(suggestion.framework.as_deref(), Some("react"));
+        assert_eq!(suggestion.kind.as_deref(), Some("hook"));
+
+        let script = "#!/usr/bin/env bash\nset -e\necho done\n";
+        let suggestion = infer_metadata(script, None);
+        assert_eq!(suggestion.language.as_deref(), Some("bash"));
+        assert_eq!(suggestion.kind.as_deref(), Some("script"));
+
+        assert!(infer_metadata("今天的会议纪要。", Some("纪要.md")).is_empty());
+    }
+
+    #[test]
+    fn suggestions_never_overwrite_explicit_values() {
+        let inferred = MetadataSuggestion {
+            language: Some("typescript".to_string()),
+            framework: Some("react".to_string()),
+            kind: Some("hook".to_string()),
+        };
+        let current = MetadataSuggestion {
+            language: Some("javascript".to_string()),
+            framework: Some(" ".to_string()),
+            kind: None,
+        };
+        let filled = inferred.only_missing(&current);
+        assert_eq!(filled.language, None);
+        assert_eq!(filled.framework.as_deref(), Some("react"));
+        assert_eq!(filled.kind.as_deref(), Some("hook"));
+    }
+}
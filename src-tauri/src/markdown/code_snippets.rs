@@ -0,0 +1,587 @@
+// 纯代码文件片段
+//
+// 工作区设置 code_snippet_extensions 中列出的扩展名（如 ts、vue、css、sh）会像笔记一样被索引、列出、
+// 创建、移动和删除。代码文件保持原样，不写入 Frontmatter：id 和时间戳在 cache.json 的 files 中，
+// tags/favorite/kind 等在随工作区同步的 snippets.json 中，语言未指定时按扩展名推断。
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::classifier::{infer_metadata, MetadataSuggestion};
+use crate::markdown::commands::MarkdownFile;
+use crate::markdown::file_ops::{get_relative_path, FileNameGenerator};
+use crate::markdown::file_system_manager::FileSystemManager;
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::metadata::{CodeSnippetMetadata, FileMetadata};
+use crate::markdown::workspace::read_workspace;
+use crate::markdown::{CacheManager, IndexManager, WorkspaceManager};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::SystemTime;
+use tauri::{command, AppHandle, Manager, State};
+
+// 启用的扩展名缓存：按 workspace.json 修改时间失效，设置变化后下一次查询即生效
+static EXTENSIONS_CACHE: LazyLock<Mutex<HashMap<PathBuf, (Option<SystemTime>, Arc<Vec<String>>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 规范化扩展名列表：去掉前导点、转小写、去重，排除 md
+pub fn normalize_extensions(extensions: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for ext in extensions {
+        let ext = ext.trim().trim_start_matches('.').to_lowercase();
+        if ext.is_empty() || ext == "md" || ext.contains(['/', '\\']) {
+            continue;
+        }
+        if !normalized.contains(&ext) {
+            normalized.push(ext);
+        }
+    }
+    normalized
+}
+
+/// 读取工作区启用的代码片段扩展名（未配置时为空）
+pub fn load_code_extensions(workspace_root: &Path) -> Arc<Vec<String>> {
+    let config_dir = workspace_root.join(".snippets-code");
+    let modified = fs::metadata(config_dir.join("workspace.json"))
+        .and_then(|m| m.modified())
+        .ok();
+
+    if let Ok(cache) = EXTENSIONS_CACHE.lock() {
+        if let Some((cached_modified, extensions)) = cache.get(workspace_root) {
+            if *cached_modified == modified {
+                return extensions.clone();
+            }
+        }
+    }
+
+    let extensions = Arc::new(match modified {
+        Some(_) => read_workspace(&config_dir)
+            .map(|config| normalize_extensions(&config.settings.code_snippet_extensions))
+            .unwrap_or_default(),
+        None => Vec::new(),
+    });
+
+    if let Ok(mut cache) = EXTENSIONS_CACHE.lock() {
+        cache.insert(workspace_root.to_path_buf(), (modified, extensions.clone()));
+    }
+    extensions
+}
+
+fn lowercase_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+}
+
+/// 是否为启用的代码片段文件
+pub fn is_code_snippet_path(path: &Path, extensions: &[String]) -> bool {
+    lowercase_extension(path).is_some_and(|ext| extensions.contains(&ext))
+}
+
+/// 是否为片段文件（Markdown 或启用的代码文件）
+pub fn is_snippet_path(path: &Path, extensions: &[String]) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("md")
+        || is_code_snippet_path(path, extensions)
+}
+
+/// 按工作区配置判断是否为代码片段文件
+pub fn is_code_snippet_file(workspace_root: &Path, path: &Path) -> bool {
+    is_code_snippet_path(path, &load_code_extensions(workspace_root))
+}
+
+/// 由扩展名推断语言（与代码块 info string 的常用写法一致）
+pub fn language_for_extension(ext: &str) -> Option<&'static str> {
+    let language = match ext.to_lowercase().as_str() {
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "css" => "css",
+        "scss" => "scss",
+        "less" => "less",
+        "html" | "htm" => "html",
+        "sh" | "bash" | "zsh" => "bash",
+        "ps1" => "powershell",
+        "bat" | "cmd" => "batch",
+        "py" => "python",
+        "rs" => "rust",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "cxx" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "lua" => "lua",
+        "dart" => "dart",
+        "sql" => "sql",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "xml" => "xml",
+        _ => return None,
+    };
+    Some(language)
+}
+
+// 代码片段的内容元数据（cache.json + 推断）
+pub(crate) struct CodeSnippetInfo {
+    pub title: String,
+    pub tags: Vec<String>,
+    pub favorite: bool,
+    pub language: Option<String>,
+    pub framework: Option<String>,
+    pub kind: Option<String>,
+    pub created: String,
+    pub modified: String,
+}
+
+fn millis_to_rfc3339(millis: i64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(millis)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
+fn file_modified_rfc3339(path: &Path) -> String {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339())
+        .unwrap_or_default()
+}
+
+pub(crate) fn code_snippet_info(
+    path: &Path,
+    relative_path: &str,
+    cache: &CacheManager,
+) -> CodeSnippetInfo {
+    let meta = cache
+        .get_snippet_metadata(relative_path)
+        .cloned()
+        .unwrap_or_default();
+    let (created, modified) = match cache.get_file_metadata(relative_path) {
+        Some(file_meta) => (
+            millis_to_rfc3339(file_meta.created),
+            millis_to_rfc3339(file_meta.modified),
+        ),
+        None => {
+            let modified = file_modified_rfc3339(path);
+            (modified.clone(), modified)
+        }
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Untitled")
+        .to_string();
+
+    CodeSnippetInfo {
+        title: meta.title.unwrap_or(file_name),
+        tags: meta.tags,
+        favorite: meta.favorite,
+        language: meta.language.or_else(|| {
+            lowercase_extension(path)
+                .and_then(|ext| language_for_extension(&ext))
+                .map(str::to_string)
+        }),
+        framework: meta.framework,
+        kind: meta.kind,
+        created,
+        modified,
+    }
+}
+
+pub(crate) fn code_snippet_to_markdown_file(
+    path: &Path,
+    workspace_root: &Path,
+    cache: &CacheManager,
+    content: String,
+) -> Result<MarkdownFile, String> {
+    let relative_path = get_relative_path(workspace_root, path)?;
+    let info = code_snippet_info(path, &relative_path, cache);
+    let category_name = cache.extract_category_from_path(&relative_path);
+    let category_id = cache.get_category_id(&category_name).unwrap_or(0);
+
+    Ok(MarkdownFile {
+        id: path.to_string_lossy().to_string(),
+        title: info.title,
+        content,
+        category_id,
+        category_name,
+        tags: info.tags,
+        created: info.created,
+        modified: info.modified,
+        file_type: "code".to_string(),
+        language: info.language,
+        framework: info.framework,
+        kind: info.kind,
+        favorite: info.favorite,
+        file_path: path.to_string_lossy().to_string(),
+        score: None,
+    })
+}
+
+/// 列出分类下的代码片段文件（语义与 list_markdown_files 一致：指定分类时不递归，None 时递归全部）
+pub(crate) fn list_code_snippet_files(
+    workspace_root: &Path,
+    category: Option<&str>,
+) -> Result<Vec<PathBuf>, String> {
+    let extensions = load_code_extensions(workspace_root);
+    if extensions.is_empty() {
+        return Ok(Vec::new());
+    }
+    // 代码目录（node_modules、dist 等）通常较大，列表同样遵守 .snippetsignore
+    let ignore = load_workspace_ignore(workspace_root);
+
+    let mut files: Vec<PathBuf> = match category {
+        Some(cat) => {
+            if Path::new(cat)
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_)))
+            {
+                return Err(format!("无效的分类路径: {}", cat));
+            }
+            let folder_path = workspace_root.join(cat);
+            if !folder_path.is_dir() {
+                return Ok(Vec::new());
+            }
+            fs::read_dir(&folder_path)
+                .map_err(|e| format!("读取目录失败: {}", e))?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.is_file()
+                        && is_code_snippet_path(p, &extensions)
+                        && !ignore.is_path_ignored(workspace_root, p, false)
+                })
+                .collect()
+        }
+        None => walkdir::WalkDir::new(workspace_root)
+            .follow_links(true)
+            .into_iter()
+            .filter_entry(|e| {
+                e.depth() == 0
+                    || (!e.file_name().to_string_lossy().starts_with('.')
+                        && !ignore.is_entry_ignored(workspace_root, e))
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && is_code_snippet_path(e.path(), &extensions))
+            .map(|e| e.into_path())
+            .collect(),
+    };
+
+    files.sort();
+    Ok(files)
+}
+
+// 读取可选的字符串字段：键存在时返回 Some(值)，null/空字符串表示清空
+fn optional_string_field(meta: &serde_json::Value, key: &str) -> Option<Option<String>> {
+    meta.get(key).map(|v| {
+        v.as_str()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    })
+}
+
+// 将前端传入的元数据合并到代码片段元数据；只更新出现的字段
+fn apply_metadata(target: &mut CodeSnippetMetadata, meta: &serde_json::Value, file_name: &str) {
+    if let Some(title) = optional_string_field(meta, "title") {
+        // 标题与文件名相同时不单独存储
+        target.title = title.filter(|t| t != file_name);
+    }
+    if let Some(tags) = meta.get("tags").and_then(|v| v.as_array()) {
+        target.tags = tags
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect();
+    }
+    if let Some(favorite) = meta.get("favorite").and_then(|v| v.as_bool()) {
+        target.favorite = favorite;
+    }
+    if let Some(language) = optional_string_field(meta, "language") {
+        target.language = language;
+    }
+    if let Some(framework) = optional_string_field(meta, "framework") {
+        target.framework = framework;
+    }
+    if let Some(kind) = optional_string_field(meta, "kind") {
+        target.kind = kind;
+    }
+    if let Some(summary) = optional_string_field(meta, "summary") {
+        target.summary = summary;
+    }
+}
+
+/// 读取代码片段（read_markdown_file 对代码文件的分支）
+pub(crate) fn read_code_snippet(
+    workspace_root: &Path,
+    path: &Path,
+    cache: &CacheManager,
+) -> Result<MarkdownFile, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("读取文件失败 '{}': {}", path.display(), e))?;
+    code_snippet_to_markdown_file(path, workspace_root, cache, content)
+}
+
+/// 更新代码片段内容和元数据（update_markdown_file 对代码文件的分支）
+///
+/// 代码文件不会因标题变化而重命名；返回是否写入了文件内容。
+pub(crate) fn update_code_snippet(
+    workspace_root: &Path,
+    path: &Path,
+    content: Option<&str>,
+    metadata: Option<&serde_json::Value>,
+    cache: &mut CacheManager,
+) -> Result<bool, String> {
+    let relative_path = get_relative_path(workspace_root, path)?;
+    if cache.get_file_metadata(&relative_path).is_none() {
+        cache.add_file(path, workspace_root)?;
+    }
+
+    let mut written = false;
+    if let Some(content) = content {
+        let current = fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?;
+        if current != content {
+            fs::write(path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+            written = true;
+        }
+    }
+
+    if let Some(meta) = metadata {
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let mut snippet = cache
+            .get_snippet_metadata(&relative_path)
+            .cloned()
+            .unwrap_or_default();
+        apply_metadata(&mut snippet, meta, &file_name);
+        cache.set_snippet_metadata(relative_path.clone(), snippet);
+    }
+
+    if written || metadata.is_some() {
+        let size = content.map(|c| c.len() as u64);
+        cache.update_file_metadata(&relative_path, |m| {
+            m.modified = chrono::Utc::now().timestamp_millis();
+            if size.is_some() {
+                m.size = size;
+            }
+        })?;
+    }
+
+    Ok(written)
+}
+
+/// 设置代码片段收藏状态（toggle_favorite 对代码文件的分支）
+pub(crate) fn set_code_snippet_favorite(
+    workspace_root: &Path,
+    path: &Path,
+    favorite: bool,
+    cache: &mut CacheManager,
+) -> Result<(), String> {
+    update_code_snippet(
+        workspace_root,
+        path,
+        None,
+        Some(&serde_json::json!({ "favorite": favorite })),
+        cache,
+    )
+    .map(|_| ())
+}
+
+// 替换当前工作区的搜索索引
+async fn rebuild_index(
+    workspace_root: &Path,
+    index_manager: &Arc<RwLock<Option<IndexManager>>>,
+    cache_manager: &Arc<RwLock<CacheManager>>,
+) -> Result<(), String> {
+    let cache = cache_manager
+        .read()
+        .map_err(|e| format!("获取 cache 锁失败: {}", e))?
+        .clone();
+    let rebuilt = IndexManager::build_index(workspace_root, &cache).await?;
+    let mut index_lock = index_manager
+        .write()
+        .map_err(|e| format!("获取索引管理器写锁失败: {}", e))?;
+    *index_lock = Some(rebuilt);
+    Ok(())
+}
+
+// ============= 命令 =============
+
+// 获取作为片段收录的代码文件扩展名
+#[command]
+pub fn get_code_snippet_extensions(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    match app_handle.try_state::<Arc<RwLock<WorkspaceManager>>>() {
+        Some(state) => {
+            let manager = state
+                .read()
+                .map_err(|e| format!("获取工作区配置锁失败: {}", e))?;
+            Ok(manager.get_code_snippet_extensions().to_vec())
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+// 设置作为片段收录的代码文件扩展名，并重新扫描工作区
+#[command]
+pub async fn set_code_snippet_extensions(
+    app_handle: AppHandle,
+    extensions: Vec<String>,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<Vec<String>, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let extensions = normalize_extensions(&extensions);
+
+    {
+        let state = app_handle
+            .try_state::<Arc<RwLock<WorkspaceManager>>>()
+            .ok_or("WorkspaceManager 未初始化")?;
+        let mut manager = state
+            .write()
+            .map_err(|e| format!("获取工作区配置锁失败: {}", e))?;
+        manager.set_code_snippet_extensions(extensions.clone());
+        manager.save()?;
+    }
+
+    {
+        let mut cache = cache_manager
+            .write()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        let removed = cache.cleanup_disabled_code_files(&extensions);
+        cache.rebuild_cache(&workspace_root)?;
+        cache.save()?;
+        debug!("🧹 [代码片段] 移除已停用扩展名的文件 {} 个", removed);
+    }
+
+    rebuild_index(
+        &workspace_root,
+        index_manager.inner(),
+        cache_manager.inner(),
+    )
+    .await?;
+
+    info!("✅ [代码片段] 扩展名已设置为: {:?}", extensions);
+    Ok(extensions)
+}
+
+// 创建代码片段文件，返回文件路径
+#[command]
+pub async fn create_code_snippet(
+    app_handle: AppHandle,
+    category: Option<String>,
+    file_name: String,
+    content: String,
+    metadata: Option<serde_json::Value>,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<String, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+
+    let requested = Path::new(file_name.trim());
+    let extension = lowercase_extension(requested).ok_or("文件名缺少扩展名")?;
+    if !load_code_extensions(&workspace_root).contains(&extension) {
+        return Err(format!("扩展名 .{} 未启用为代码片段", extension));
+    }
+    let stem = requested
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or("文件名不能为空")?;
+
+    let fs_manager = FileSystemManager::new(workspace_root.clone());
+    let folder_path = fs_manager.create_category_folder(category.as_deref().unwrap_or("未分类"))?;
+
+    let dotted_extension = format!(".{}", extension);
+    let safe_filename =
+        FileNameGenerator::generate_filename_with_extension(stem, &dotted_extension);
+    let resolved = FileNameGenerator::resolve_conflict_with_extension(
+        &folder_path,
+        &safe_filename,
+        &dotted_extension,
+    );
+    let file_path = folder_path.join(resolved);
+
+    fs::write(&file_path, &content).map_err(|e| format!("写入文件失败: {}", e))?;
+    let relative_path = get_relative_path(&workspace_root, &file_path)?;
+
+    {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut cache = cache_manager
+            .write()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        cache.set_file_metadata(
+            relative_path.clone(),
+            FileMetadata {
+                id: uuid::Uuid::new_v4().to_string(),
+                created: now,
+                modified: now,
+                size: Some(content.len() as u64),
+                hash: None,
+            },
+        );
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        let mut snippet = CodeSnippetMetadata::default();
+        if let Some(meta) = metadata.as_ref() {
+            apply_metadata(&mut snippet, meta, file_name);
+        }
+        // 未填写的 framework/kind 由内容推断（language 读取时按扩展名推断，无需存储）
+        let inferred =
+            infer_metadata(&content, Some(file_name)).only_missing(&MetadataSuggestion {
+                language: snippet.language.clone(),
+                framework: snippet.framework.clone(),
+                kind: snippet.kind.clone(),
+            });
+        snippet.framework = snippet.framework.or(inferred.framework);
+        snippet.kind = snippet.kind.or(inferred.kind);
+        cache.set_snippet_metadata(relative_path.clone(), snippet);
+        cache.save()?;
+    }
+
+    if let Ok(manager_lock) = index_manager.read() {
+        if let Some(ref manager) = *manager_lock {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            if let Err(e) = manager.update_entry(&file_path, &workspace_root, &cache) {
+                warn!("⚠️ [代码片段] 更新索引失败 {}: {}", relative_path, e);
+            }
+        }
+    }
+
+    debug!("✅ [代码片段] 已创建: {}", relative_path);
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_extensions_strips_dots_and_markdown() {
+        let normalized = normalize_extensions(&[
+            ".TS".to_string(),
+            "vue".to_string(),
+            "ts".to_string(),
+            "md".to_string(),
+            " ".to_string(),
+        ]);
+        assert_eq!(normalized, vec!["ts".to_string(), "vue".to_string()]);
+        assert!(is_code_snippet_path(Path::new("a/b.Ts"), &normalized));
+        assert!(!is_code_snippet_path(Path::new("a/b.css"), &normalized));
+        assert!(is_snippet_path(Path::new("a/b.md"), &normalized));
+    }
+}
@@ -1,9 +1,16 @@
 // Markdown 文件操作的 Tauri 命令
 
 use crate::json_config::get_workspace_root;
+use crate::markdown::classifier::{infer_metadata, MetadataSuggestion};
+use crate::markdown::code_snippets::{
+    code_snippet_to_markdown_file, is_code_snippet_file, list_code_snippet_files,
+    read_code_snippet, set_code_snippet_favorite, update_code_snippet,
+};
 use crate::markdown::file_ops::{get_relative_path, FileNameGenerator};
 use crate::markdown::file_system_manager::FileSystemManager;
-use crate::markdown::metadata::{try_parse_front_matter, FileMetadata, FrontMatter};
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::index_optimized::IndexEntry;
+use crate::markdown::metadata::{try_parse_front_matter, FileMetadata, ForkSource, FrontMatter};
 use crate::markdown::watcher::FileWatcher;
 use crate::markdown::CacheManager;
 use crate::markdown::IndexManager; // 使用模块级别的 IndexManager（已重命名为 OptimizedIndexManager）
@@ -229,11 +236,32 @@ pub async fn create_markdown_file(
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
+    // 未填写的 language/framework/kind 由内容推断，显式值保持不变
+    let inferred = infer_metadata(&content, None).only_missing(&MetadataSuggestion {
+        language: language.clone(),
+        framework: framework.clone(),
+        kind: kind.clone(),
+    });
+    let language = inferred.language.or(language);
+    let framework = inferred.framework.or(framework);
+    let kind = inferred.kind.or(kind);
+
     let favorite = metadata
         .get("favorite")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    // 从本地 AI 对话导出的笔记记录对话 id
+    let chat_id = metadata
+        .get("chatId")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    // 片段操作生成的新版本记录原笔记
+    let forked_from = metadata
+        .get("forkedFrom")
+        .and_then(|v| serde_json::from_value::<ForkSource>(v.clone()).ok());
+
     // 生成 ID 和时间戳
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now();
@@ -250,7 +278,11 @@ pub async fn create_markdown_file(
         language: language.clone(),
         framework: framework.clone(),
         kind: kind.clone(),
+        summary: None,
         favorite,
+        forked_from,
+        chat_id,
+        translated_from: None,
     };
 
     let fs_manager = get_fs_manager(&app_handle)?;
@@ -315,6 +347,14 @@ pub fn read_markdown_file(
     // 读取文件完整内容
     let raw_content = fs_manager.read_markdown_file_content(&path)?;
 
+    // 代码片段文件原样返回，元数据来自 cache.json，不写入 Frontmatter
+    if is_code_snippet_file(&workspace_root, &path) {
+        let cache = cache_manager
+            .read()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        return code_snippet_to_markdown_file(&path, &workspace_root, &cache, raw_content);
+    }
+
     // 获取相对路径
     let relative_path = get_relative_path(&workspace_root, &path)?;
 
@@ -386,7 +426,11 @@ pub fn read_markdown_file(
                 language: None,
                 framework: None,
                 kind: None,
+                summary: None,
                 favorite: false,
+                forked_from: None,
+                chat_id: None,
+                translated_from: None,
             };
 
             // 写回默认 Frontmatter（不改变正文）
@@ -457,6 +501,33 @@ pub async fn update_markdown_file(
         }
     }
 
+    // 代码片段文件：内容原样写入，元数据保存到 cache.json，标题变化不重命名文件
+    if is_code_snippet_file(&workspace_root, &path) {
+        {
+            let mut cache = cache_manager
+                .write()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            update_code_snippet(
+                &workspace_root,
+                &path,
+                content.as_deref(),
+                metadata.as_ref(),
+                &mut cache,
+            )?;
+            cache.save()?;
+        }
+        if let Ok(manager_lock) = index_manager.read() {
+            if let Some(ref manager) = *manager_lock {
+                let cache = cache_manager
+                    .read()
+                    .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+                let _ = manager.update_entry(&path, &workspace_root, &cache);
+            }
+        }
+        debug!("✅ [更新文件] 完成（代码片段）: {}", path.display());
+        return Ok(None);
+    }
+
     // 获取相对路径
     let mut relative_path = get_relative_path(&workspace_root, &path)?;
 
@@ -596,10 +667,29 @@ pub async fn update_markdown_file(
                 .get("kind")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
+            summary: meta
+                .get("summary")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .or_else(|| {
+                    current_frontmatter
+                        .as_ref()
+                        .and_then(|fm| fm.summary.clone())
+                }),
             favorite: meta
                 .get("favorite")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false),
+            // 来源信息不由前端编辑，沿用文件中已有的值
+            forked_from: current_frontmatter
+                .as_ref()
+                .and_then(|fm| fm.forked_from.clone()),
+            chat_id: current_frontmatter
+                .as_ref()
+                .and_then(|fm| fm.chat_id.clone()),
+            translated_from: current_frontmatter
+                .as_ref()
+                .and_then(|fm| fm.translated_from.clone()),
         })
     } else {
         None
@@ -948,17 +1038,12 @@ pub fn rename_category_folder(
 
     // 更新每个文件的路径
     for old_file_path in files_to_update {
-        if let Some(file_metadata) = cache.get_file_metadata(&old_file_path) {
-            let updated_metadata = file_metadata.clone();
-
+        if cache.get_file_metadata(&old_file_path).is_some() {
             // 更新文件路径
             let new_file_path = old_file_path.replace(&old_prefix, &new_prefix);
 
-            // 删除旧路径的元数据
-            cache.remove_file_metadata(&old_file_path);
-
-            // 添加新路径的元数据
-            cache.set_file_metadata(new_file_path.clone(), updated_metadata);
+            // 迁移元数据（含代码片段元数据）
+            cache.rename_file_metadata(&old_file_path, &new_file_path);
 
             info!("  ✅ 更新文件路径: {} -> {}", old_file_path, new_file_path);
         }
@@ -1014,6 +1099,13 @@ pub fn get_files_by_category(
     let file_paths = fs_manager.list_markdown_files(category_name.as_deref())?;
 
     let mut files = Vec::new();
+    // 启用的代码片段文件与笔记一起列出
+    for path in list_code_snippet_files(&workspace_root, category_name.as_deref())? {
+        match read_code_snippet(&workspace_root, &path, &cache) {
+            Ok(file) => files.push(file),
+            Err(e) => warn!("⚠️ [获取文件列表] {}", e),
+        }
+    }
     for path in file_paths {
         // 读取文件内容
         match fs_manager.read_markdown_file_content(&path) {
@@ -1112,6 +1204,27 @@ pub async fn toggle_favorite(
     // 获取相对路径
     let relative_path = get_relative_path(&workspace_root, &path)?;
 
+    // 代码片段的收藏状态保存在 cache.json
+    if is_code_snippet_file(&workspace_root, &path) {
+        {
+            let mut cache = cache_manager
+                .write()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            set_code_snippet_favorite(&workspace_root, &path, favorite, &mut cache)?;
+            cache.save()?;
+        }
+        if let Ok(manager_lock) = index_manager.read() {
+            if let Some(ref manager) = *manager_lock {
+                let cache = cache_manager
+                    .read()
+                    .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+                let _ = manager.update_entry(&path, &workspace_root, &cache);
+            }
+        }
+        info!("✅ 切换收藏状态: {} -> {}", path.display(), favorite);
+        return Ok(());
+    }
+
     // 更新 Frontmatter（唯一数据源，cache.json 不再存储 favorite）
     if let Ok((mut fm, _)) = fs_manager.read_markdown_file(&path) {
         fm.favorite = favorite;
@@ -1180,9 +1293,11 @@ pub fn find_file_by_title(
     let workspace_root = fs_manager.workspace_root();
 
     // 遍历所有文件查找匹配的标题
+    let ignore = load_workspace_ignore(workspace_root);
     for entry in walkdir::WalkDir::new(workspace_root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|e| !ignore.is_entry_ignored(workspace_root, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -1213,9 +1328,11 @@ pub fn get_all_file_titles(app_handle: AppHandle) -> Result<Vec<String>, String>
 
     let mut titles = Vec::new();
 
+    let ignore = load_workspace_ignore(workspace_root);
     for entry in walkdir::WalkDir::new(workspace_root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|e| !ignore.is_entry_ignored(workspace_root, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -1249,9 +1366,11 @@ pub async fn update_wikilinks(
     let old_link = format!("[[{}]]", old_title);
     let new_link = format!("[[{}]]", new_title);
 
+    let ignore = load_workspace_ignore(&workspace_root);
     for entry in walkdir::WalkDir::new(&workspace_root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|e| !ignore.is_entry_ignored(&workspace_root, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -1306,9 +1425,11 @@ pub fn find_files_with_wikilink(
     let mut files = Vec::new();
     let link = format!("[[{}]]", title);
 
+    let ignore = load_workspace_ignore(workspace_root);
     for entry in walkdir::WalkDir::new(workspace_root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|e| !ignore.is_entry_ignored(workspace_root, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -1391,9 +1512,8 @@ pub async fn move_markdown_file(
     // 标准化新路径（统一使用 / 作为分隔符）
     let new_relative_path_str = new_relative_path.to_string_lossy().replace('\\', "/");
 
-    if let Some(metadata) = cache.get_file_metadata(&old_relative_path).cloned() {
-        cache.remove_file_metadata(&old_relative_path);
-        cache.set_file_metadata(new_relative_path_str.clone(), metadata);
+    if cache.get_file_metadata(&old_relative_path).is_some() {
+        cache.rename_file_metadata(&old_relative_path, &new_relative_path_str);
     } else {
         warn!("⚠️ [移动文件] 未找到旧文件的元数据: {}", old_relative_path);
         return Err(format!("未找到文件元数据: {}", old_relative_path));
@@ -1482,52 +1602,90 @@ pub async fn search_markdown_files_optimized(
     // 将 IndexEntry 转换为 MarkdownFile
     let markdown_files: Vec<MarkdownFile> = results
         .into_iter()
-        .map(|(entry, score)| {
-            // 类型字段：直接使用索引中的值（'code' 或 'note'）
-            let file_type = entry.file_type;
-
-            // 从文件路径推断分类信息
-            let file_path_str = entry.file_path.to_string_lossy().to_string();
-
-            // 将绝对路径转换为相对路径
-            let relative_path = match get_relative_path(&workspace_root, &entry.file_path) {
-                Ok(path) => path,
-                Err(_) => {
-                    // 如果转换失败，使用文件名
-                    entry
-                        .file_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unknown.md")
-                        .to_string()
-                }
-            };
-
-            let category_name = cache.extract_category_from_path(&relative_path);
-            let category_id = cache.get_category_id(&category_name).unwrap_or(0);
-
-            MarkdownFile {
-                id: entry.id,
-                title: entry.title,
-                content: entry.full_content,
-                category_id,
-                category_name,
-                tags: entry.tags,
-                created: "".to_string(),
-                modified: "".to_string(),
-                file_type,
-                language: entry.language,
-                framework: entry.framework,
-                kind: entry.kind,
-                favorite: entry.favorite,
-                file_path: file_path_str,
-                score: Some(score),
-            }
-        })
+        .map(|(entry, score)| search_result_to_markdown_file(entry, score, &workspace_root, &cache))
         .collect();
 
     Ok(markdown_files)
 }
+
+// 将索引项转换为搜索结果（分类信息从对应工作区的 cache.json 推断）
+pub(crate) fn search_result_to_markdown_file(
+    entry: IndexEntry,
+    score: f32,
+    workspace_root: &Path,
+    cache: &CacheManager,
+) -> MarkdownFile {
+    // 类型字段：直接使用索引中的值（'code' 或 'note'）
+    let file_type = entry.file_type;
+
+    // 从文件路径推断分类信息
+    let file_path_str = entry.file_path.to_string_lossy().to_string();
+
+    // 将绝对路径转换为相对路径
+    let relative_path = match get_relative_path(workspace_root, &entry.file_path) {
+        Ok(path) => path,
+        Err(_) => {
+            // 如果转换失败，使用文件名
+            entry
+                .file_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown.md")
+                .to_string()
+        }
+    };
+
+    let category_name = cache.extract_category_from_path(&relative_path);
+    let category_id = cache.get_category_id(&category_name).unwrap_or(0);
+
+    // 时间优先使用索引中的 frontmatter，其次是 cache.json 中的文件元数据，最后回退到文件系统时间
+    let file_meta = cache.get_file_metadata(&relative_path);
+    let fs_meta = std::fs::metadata(&entry.file_path).ok();
+    let created = resolve_timestamp(
+        entry.created,
+        file_meta.map(|m| m.created),
+        fs_meta.as_ref().and_then(|m| m.created().ok()),
+    );
+    let modified = resolve_timestamp(
+        entry.modified,
+        file_meta.map(|m| m.modified),
+        fs_meta.as_ref().and_then(|m| m.modified().ok()),
+    );
+
+    MarkdownFile {
+        id: entry.id,
+        title: entry.title,
+        content: entry.full_content,
+        category_id,
+        category_name,
+        tags: entry.tags,
+        created,
+        modified,
+        file_type,
+        language: entry.language,
+        framework: entry.framework,
+        kind: entry.kind,
+        favorite: entry.favorite,
+        file_path: file_path_str,
+        score: Some(score),
+    }
+}
+
+fn resolve_timestamp(
+    indexed: String,
+    cached_millis: Option<i64>,
+    file_time: Option<std::time::SystemTime>,
+) -> String {
+    if !indexed.is_empty() {
+        return indexed;
+    }
+    cached_millis
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .or_else(|| file_time.map(chrono::DateTime::<chrono::Utc>::from))
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
 // 清理 cache.json 中已删除文件的元数据
 #[command]
 pub fn cleanup_cache(
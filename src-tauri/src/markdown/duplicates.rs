@@ -0,0 +1,722 @@
+// 近似重复片段检测与合并
+//
+// 对索引中每个片段的正文做规范化分词（忽略大小写、空白和标点，中文按字切分），取 k 词 shingle，
+// 用 MinHash 签名 + LSH 分桶找出候选对，再以 shingle 集合的 Jaccard 相似度确认。
+// 相似度达到阈值的片段用并查集归为一组，并给出两两之间重叠的段落/代码块。
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::code_snippets::{code_snippet_info, is_code_snippet_file};
+use crate::markdown::file_ops::{get_relative_path, is_within_workspace};
+use crate::markdown::file_system_manager::FileSystemManager;
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::index_optimized::IndexEntry;
+use crate::markdown::watcher::FileWatcher;
+use crate::markdown::{CacheManager, IndexManager};
+use crate::text_utils::is_cjk;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{command, AppHandle, State};
+
+const SHINGLE_SIZE: usize = 5;
+const BLOCK_SHINGLE_SIZE: usize = 3;
+const NUM_HASHES: usize = 128;
+const DEFAULT_THRESHOLD: f32 = 0.8;
+// 块级重叠的判定阈值
+const BLOCK_THRESHOLD: f32 = 0.7;
+// 词数过少的片段不参与比较（几乎必然误报）
+const MIN_TOKENS: usize = 8;
+const MIN_BLOCK_TOKENS: usize = 3;
+const PREVIEW_CHARS: usize = 120;
+
+// ============= 规范化与 shingle =============
+
+// 规范化分词：ASCII 字母数字和下划线组成词，CJK 字符单字成词，其余字符视为分隔符
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if (c.is_alphanumeric() && !is_cjk(c)) || c == '_' {
+            current.extend(c.to_lowercase());
+            continue;
+        }
+        if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if is_cjk(c) {
+            tokens.push(c.to_string());
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+// FNV-1a：跨进程稳定的字符串哈希
+fn fnv1a(parts: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part.as_bytes().iter().chain(std::iter::once(&0x1f)) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+fn shingles(tokens: &[String], size: usize) -> HashSet<u64> {
+    if tokens.len() <= size {
+        return std::iter::once(fnv1a(tokens)).collect();
+    }
+    tokens.windows(size).map(fnv1a).collect()
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    let intersection = a.intersection(b).count();
+    intersection as f32 / (a.len() + b.len() - intersection) as f32
+}
+
+// ============= MinHash / LSH =============
+
+// splitmix64 终结函数，作为哈希族的置换
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+fn minhash_seeds() -> Vec<u64> {
+    (0..NUM_HASHES as u64)
+        .map(|i| mix64(0x9e3779b97f4a7c15u64.wrapping_mul(i + 1)))
+        .collect()
+}
+
+fn minhash_signature(shingles: &HashSet<u64>, seeds: &[u64]) -> Vec<u64> {
+    seeds
+        .iter()
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|s| mix64(s ^ seed))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+// 按阈值选择每个 band 的行数：LSH 的 S 曲线拐点 (1/b)^(1/r) 需略低于阈值，保证召回
+fn rows_per_band(threshold: f32) -> usize {
+    let mut rows = 1;
+    for r in [2usize, 4, 8, 16] {
+        let bands = (NUM_HASHES / r) as f32;
+        if (1.0 / bands).powf(1.0 / r as f32) <= threshold - 0.05 {
+            rows = r;
+        }
+    }
+    rows
+}
+
+fn candidate_pairs(signatures: &[Vec<u64>], rows: usize) -> HashSet<(usize, usize)> {
+    let mut pairs = HashSet::new();
+    for band in 0..NUM_HASHES / rows {
+        let mut buckets: HashMap<&[u64], Vec<usize>> = HashMap::new();
+        for (index, signature) in signatures.iter().enumerate() {
+            buckets
+                .entry(&signature[band * rows..(band + 1) * rows])
+                .or_default()
+                .push(index);
+        }
+        for members in buckets.values().filter(|m| m.len() > 1) {
+            for (i, &a) in members.iter().enumerate() {
+                for &b in &members[i + 1..] {
+                    pairs.insert((a, b));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = x;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[rb] = ra;
+        }
+    }
+}
+
+// ============= 块级重叠 =============
+
+struct ContentBlock {
+    // 起始行号（从 1 开始）
+    line: usize,
+    text: String,
+    shingles: HashSet<u64>,
+}
+
+// 按空行切分段落，围栏代码块整体作为一个块
+fn split_blocks(content: &str) -> Vec<ContentBlock> {
+    let mut raw_blocks: Vec<(usize, String)> = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    let mut fence: Option<String> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+
+        if let Some(marker) = fence.as_ref() {
+            let block = current.get_or_insert_with(|| (index + 1, String::new()));
+            block.1.push_str(line);
+            block.1.push('\n');
+            if is_fence && trimmed.starts_with(marker.as_str()) {
+                fence = None;
+                raw_blocks.extend(current.take());
+            }
+            continue;
+        }
+
+        if is_fence {
+            raw_blocks.extend(current.take());
+            fence = Some(trimmed[..3].to_string());
+            current = Some((index + 1, format!("{}\n", line)));
+        } else if trimmed.is_empty() {
+            raw_blocks.extend(current.take());
+        } else {
+            let block = current.get_or_insert_with(|| (index + 1, String::new()));
+            block.1.push_str(line);
+            block.1.push('\n');
+        }
+    }
+    raw_blocks.extend(current);
+
+    raw_blocks
+        .into_iter()
+        .filter_map(|(line, text)| {
+            let tokens = tokenize(&text);
+            (tokens.len() >= MIN_BLOCK_TOKENS).then(|| ContentBlock {
+                line,
+                shingles: shingles(&tokens, BLOCK_SHINGLE_SIZE),
+                text,
+            })
+        })
+        .collect()
+}
+
+fn preview(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() > PREVIEW_CHARS {
+        format!(
+            "{}...",
+            text.chars().take(PREVIEW_CHARS).collect::<String>()
+        )
+    } else {
+        text.to_string()
+    }
+}
+
+// 重叠的块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverlap {
+    pub left_line: usize,
+    pub right_line: usize,
+    pub similarity: f32,
+    pub left_preview: String,
+    pub right_preview: String,
+}
+
+fn block_overlaps(left: &[ContentBlock], right: &[ContentBlock]) -> Vec<BlockOverlap> {
+    let mut overlaps = Vec::new();
+    let mut used_right: HashSet<usize> = HashSet::new();
+    for l in left {
+        let best = right
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| !used_right.contains(j))
+            .map(|(j, r)| (j, jaccard(&l.shingles, &r.shingles)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((j, similarity)) = best.filter(|(_, s)| *s >= BLOCK_THRESHOLD) {
+            used_right.insert(j);
+            overlaps.push(BlockOverlap {
+                left_line: l.line,
+                right_line: right[j].line,
+                similarity,
+                left_preview: preview(&l.text),
+                right_preview: preview(&right[j].text),
+            });
+        }
+    }
+    overlaps
+}
+
+// ============= 检测 =============
+
+// 重复组中的片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateNote {
+    pub file_path: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub modified: String,
+}
+
+// 组内相似的一对片段（下标对应 notes）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatePair {
+    pub left: usize,
+    pub right: usize,
+    pub similarity: f32,
+    pub overlaps: Vec<BlockOverlap>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub notes: Vec<DuplicateNote>,
+    pub pairs: Vec<DuplicatePair>,
+    // 组内最高相似度，用于排序
+    pub max_similarity: f32,
+}
+
+/// 在索引项中查找相似度不低于 threshold 的片段组
+pub fn find_duplicate_groups(entries: &[IndexEntry], threshold: f32) -> Vec<DuplicateGroup> {
+    let candidates: Vec<(&IndexEntry, HashSet<u64>)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let tokens = tokenize(&entry.full_content);
+            (tokens.len() >= MIN_TOKENS).then(|| (entry, shingles(&tokens, SHINGLE_SIZE)))
+        })
+        .collect();
+
+    let seeds = minhash_seeds();
+    let signatures: Vec<Vec<u64>> = candidates
+        .iter()
+        .map(|(_, set)| minhash_signature(set, &seeds))
+        .collect();
+
+    // LSH 候选对，再以精确 Jaccard 确认
+    let mut confirmed: Vec<(usize, usize, f32)> =
+        candidate_pairs(&signatures, rows_per_band(threshold))
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let similarity = jaccard(&candidates[a].1, &candidates[b].1);
+                (similarity >= threshold).then_some((a, b, similarity))
+            })
+            .collect();
+    confirmed.sort_by_key(|x| (x.0, x.1));
+
+    let mut union_find = UnionFind::new(candidates.len());
+    for &(a, b, _) in &confirmed {
+        union_find.union(a, b);
+    }
+
+    // 按并查集根节点分组
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(a, b, _) in &confirmed {
+        for index in [a, b] {
+            let root = union_find.find(index);
+            let group = members.entry(root).or_default();
+            if !group.contains(&index) {
+                group.push(index);
+            }
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = members
+        .into_values()
+        .map(|mut indices| {
+            indices.sort_unstable();
+            let position: HashMap<usize, usize> = indices
+                .iter()
+                .enumerate()
+                .map(|(i, &idx)| (idx, i))
+                .collect();
+            let blocks: Vec<Vec<ContentBlock>> = indices
+                .iter()
+                .map(|&i| split_blocks(&candidates[i].0.full_content))
+                .collect();
+
+            let pairs: Vec<DuplicatePair> = confirmed
+                .iter()
+                .filter(|(a, b, _)| position.contains_key(a) && position.contains_key(b))
+                .map(|&(a, b, similarity)| {
+                    let (left, right) = (position[&a], position[&b]);
+                    DuplicatePair {
+                        left,
+                        right,
+                        similarity,
+                        overlaps: block_overlaps(&blocks[left], &blocks[right]),
+                    }
+                })
+                .collect();
+            let max_similarity = pairs.iter().map(|p| p.similarity).fold(0.0, f32::max);
+
+            DuplicateGroup {
+                notes: indices
+                    .iter()
+                    .map(|&i| {
+                        let entry = candidates[i].0;
+                        DuplicateNote {
+                            file_path: entry.file_path.to_string_lossy().to_string(),
+                            title: entry.title.clone(),
+                            tags: entry.tags.clone(),
+                            modified: entry.modified.clone(),
+                        }
+                    })
+                    .collect(),
+                pairs,
+                max_similarity,
+            }
+        })
+        .collect();
+
+    groups.sort_by(|a, b| b.max_similarity.total_cmp(&a.max_similarity));
+    groups
+}
+
+// ============= 合并 =============
+
+/// 将指向 old_titles 的 wikilink（含 `[[标题|别名]]`、`[[标题#小节]]`）改写为 new_title
+pub fn rewrite_wikilinks(content: &str, old_titles: &[String], new_title: &str) -> Option<String> {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    let mut changed = false;
+
+    while let Some(start) = rest.find("[[") {
+        let Some(len) = rest[start + 2..].find("]]") else {
+            break;
+        };
+        let inner = &rest[start + 2..start + 2 + len];
+        let target_end = inner.find(['|', '#']).unwrap_or(inner.len());
+        let target = inner[..target_end].trim();
+
+        result.push_str(&rest[..start + 2]);
+        if old_titles.iter().any(|t| t == target) {
+            result.push_str(new_title);
+            result.push_str(&inner[target_end..]);
+            changed = true;
+        } else {
+            result.push_str(inner);
+        }
+        result.push_str("]]");
+        rest = &rest[start + 2 + len + 2..];
+    }
+    result.push_str(rest);
+
+    changed.then_some(result)
+}
+
+// 合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    pub survivor: String,
+    pub tags: Vec<String>,
+    pub rewritten_files: usize,
+    pub deleted: usize,
+}
+
+// 读取片段的标题和标签（Markdown 来自 Frontmatter，代码片段来自 cache.json）
+fn read_title_and_tags(
+    fs_manager: &FileSystemManager,
+    workspace_root: &Path,
+    path: &Path,
+    cache: &CacheManager,
+) -> Result<(String, Vec<String>), String> {
+    if is_code_snippet_file(workspace_root, path) {
+        let relative_path = get_relative_path(workspace_root, path)?;
+        let info = code_snippet_info(path, &relative_path, cache);
+        return Ok((info.title, info.tags));
+    }
+    let (fm, _) = fs_manager.read_markdown_file(path)?;
+    Ok((fm.title, fm.tags))
+}
+
+// 合并近似重复的片段：标签并入保留的片段，指向被合并片段的 wikilink 改为指向保留的片段，
+// delete_duplicates 为 true 时删除被合并的片段
+#[command]
+pub async fn merge_duplicate_notes(
+    app_handle: AppHandle,
+    survivor: String,
+    duplicates: Vec<String>,
+    delete_duplicates: bool,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    watcher: State<'_, Arc<Mutex<Option<FileWatcher>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<MergeResult, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let fs_manager = FileSystemManager::new(workspace_root.clone());
+
+    let survivor_path = PathBuf::from(&survivor);
+    let duplicate_paths: Vec<PathBuf> = duplicates
+        .iter()
+        .map(PathBuf::from)
+        .filter(|p| *p != survivor_path)
+        .collect();
+    if duplicate_paths.is_empty() {
+        return Err("没有需要合并的片段".to_string());
+    }
+    for path in std::iter::once(&survivor_path).chain(&duplicate_paths) {
+        if !is_within_workspace(&workspace_root, path) || !path.is_file() {
+            return Err(format!("文件不存在或不在工作区内: {}", path.display()));
+        }
+    }
+
+    let ignore_change = |path: &Path| {
+        if let Ok(watcher_lock) = watcher.lock() {
+            if let Some(ref w) = *watcher_lock {
+                w.ignore_next_change(path.to_path_buf());
+            }
+        }
+    };
+
+    let mut changed: Vec<PathBuf> = Vec::new();
+    let mut cache = cache_manager
+        .write()
+        .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+
+    // 1. 合并标签
+    let (survivor_title, mut tags) =
+        read_title_and_tags(&fs_manager, &workspace_root, &survivor_path, &cache)?;
+    let mut old_titles: Vec<String> = Vec::new();
+    for path in &duplicate_paths {
+        let (title, duplicate_tags) =
+            read_title_and_tags(&fs_manager, &workspace_root, path, &cache)?;
+        for tag in duplicate_tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        if title != survivor_title && !old_titles.contains(&title) {
+            old_titles.push(title);
+        }
+    }
+
+    let survivor_relative = get_relative_path(&workspace_root, &survivor_path)?;
+    ignore_change(&survivor_path);
+    if is_code_snippet_file(&workspace_root, &survivor_path) {
+        let mut meta = cache
+            .get_snippet_metadata(&survivor_relative)
+            .cloned()
+            .unwrap_or_default();
+        meta.tags = tags.clone();
+        cache.set_snippet_metadata(survivor_relative.clone(), meta);
+    } else {
+        let (mut fm, _) = fs_manager.read_markdown_file(&survivor_path)?;
+        fm.tags = tags.clone();
+        fm.modified = chrono::Utc::now().to_rfc3339();
+        fs_manager.update_file_frontmatter(&survivor_path, &fm)?;
+    }
+    let now = chrono::Utc::now().timestamp_millis();
+    let _ = cache.update_file_metadata(&survivor_relative, |m| m.modified = now);
+    changed.push(survivor_path.clone());
+
+    // 2. 改写 wikilink
+    let mut rewritten_files = 0;
+    if !old_titles.is_empty() {
+        let ignore = load_workspace_ignore(&workspace_root);
+        for entry in walkdir::WalkDir::new(&workspace_root)
+            .follow_links(true)
+            .into_iter()
+            .filter_entry(|e| !ignore.is_entry_ignored(&workspace_root, e))
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if !path.is_file()
+                || path.extension().and_then(|s| s.to_str()) != Some("md")
+                || (delete_duplicates && duplicate_paths.iter().any(|p| p == path))
+            {
+                continue;
+            }
+            let Ok((metadata, content)) = fs_manager.read_markdown_file(path) else {
+                continue;
+            };
+            if let Some(new_content) = rewrite_wikilinks(&content, &old_titles, &survivor_title) {
+                ignore_change(path);
+                fs_manager.update_markdown_file(path, Some(&new_content), Some(&metadata))?;
+                rewritten_files += 1;
+                if !changed.iter().any(|p| p == path) {
+                    changed.push(path.to_path_buf());
+                }
+            }
+        }
+    }
+
+    // 3. 删除被合并的片段
+    let mut deleted = 0;
+    if delete_duplicates {
+        for path in &duplicate_paths {
+            ignore_change(path);
+            match fs_manager.delete_markdown_file(path) {
+                Ok(()) => {
+                    if let Ok(relative_path) = get_relative_path(&workspace_root, path) {
+                        cache.remove_file_metadata(&relative_path);
+                    }
+                    deleted += 1;
+                }
+                Err(e) => warn!("⚠️ [重复检测] 删除失败 {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    cache.save()?;
+    drop(cache);
+
+    if let Ok(manager_lock) = index_manager.read() {
+        if let Some(ref manager) = *manager_lock {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            for path in &changed {
+                let _ = manager.update_entry(path, &workspace_root, &cache);
+            }
+            if delete_duplicates {
+                for path in &duplicate_paths {
+                    let _ = manager.remove_entry(path);
+                }
+            }
+        }
+    }
+
+    info!(
+        "✅ [重复检测] 已合并 {} 个片段到 {}（改写 {} 个文件的链接，删除 {} 个）",
+        duplicate_paths.len(),
+        survivor_title,
+        rewritten_files,
+        deleted
+    );
+    Ok(MergeResult {
+        survivor,
+        tags,
+        rewritten_files,
+        deleted,
+    })
+}
+
+// 查找近似重复的片段组；threshold 为 Jaccard 相似度阈值（0~1，默认 0.8）
+#[command]
+pub async fn find_duplicate_notes(
+    threshold: Option<f32>,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD).clamp(0.1, 1.0);
+    let entries = {
+        let manager_lock = index_manager
+            .read()
+            .map_err(|e| format!("获取索引管理器读锁失败: {}", e))?;
+        manager_lock
+            .as_ref()
+            .ok_or("搜索索引尚未建立")?
+            .get_all_entries()
+    };
+
+    let total = entries.len();
+    let groups = tokio::task::spawn_blocking(move || find_duplicate_groups(&entries, threshold))
+        .await
+        .map_err(|e| format!("重复检测失败: {}", e))?;
+
+    debug!(
+        "🔍 [重复检测] {} 个片段，阈值 {:.2}，发现 {} 组",
+        total,
+        threshold,
+        groups.len()
+    );
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, content: &str) -> IndexEntry {
+        IndexEntry {
+            file_path: PathBuf::from(path),
+            id: path.to_string(),
+            title: path.to_string(),
+            tags: vec![],
+            favorite: false,
+            file_type: "note".to_string(),
+            language: None,
+            framework: None,
+            kind: None,
+            created: String::new(),
+            modified: String::new(),
+            content_summary: String::new(),
+            full_content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn groups_near_duplicates_and_reports_overlapping_blocks() {
+        let code = "```ts\nexport function debounce(fn: Function, wait: number) {\n  let timer: number | undefined\n  return (...args: unknown[]) => {\n    clearTimeout(timer)\n    timer = setTimeout(() => fn(...args), wait)\n  }\n}\n```\n";
+        let a = format!("防抖函数，常用于输入框搜索。\n\n{}", code);
+        let b = format!(
+            "防抖函数，常用于输入框的搜索。\n\n{}",
+            code.replace("wait", "Wait")
+        );
+        let unrelated =
+            "SELECT id, name FROM users WHERE created_at > now() - interval '7 days' ORDER BY name";
+
+        let groups = find_duplicate_groups(
+            &[
+                entry("a.md", &a),
+                entry("b.md", &b),
+                entry("c.md", unrelated),
+            ],
+            0.6,
+        );
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].notes.len(), 2);
+        let pair = &groups[0].pairs[0];
+        assert!(pair.similarity >= 0.6);
+        assert!(pair
+            .overlaps
+            .iter()
+            .any(|o| o.left_line == 3 && o.right_line == 3));
+    }
+
+    #[test]
+    fn rewrites_plain_aliased_and_heading_links() {
+        let content = "见 [[旧标题]]、[[旧标题|别名]] 和 [[旧标题#用法]]，不改 [[其他]]。";
+        let rewritten = rewrite_wikilinks(content, &["旧标题".to_string()], "新标题").unwrap();
+        assert_eq!(
+            rewritten,
+            "见 [[新标题]]、[[新标题|别名]] 和 [[新标题#用法]]，不改 [[其他]]。"
+        );
+        assert!(rewrite_wikilinks("[[其他]]", &["旧标题".to_string()], "新标题").is_none());
+    }
+}
@@ -0,0 +1,1450 @@
+// 工作区导出模块
+// 将笔记渲染为可通过 file:// 直接浏览的静态 HTML 站点；
+// 渲染与链接改写逻辑同时供 export_book（EPUB / 单文件 HTML）复用
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::file_ops::{get_relative_path, is_within_workspace, FileNameGenerator};
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::metadata::try_parse_front_matter;
+use log::{info, warn};
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use pulldown_cmark_toc::TableOfContents;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::{command, AppHandle};
+use walkdir::WalkDir;
+
+// 站点内部资源目录（样式、脚本、索引页），与笔记分类目录并列
+const SITE_ASSET_DIR: &str = "_site";
+// 导出标记文件：再次导出到同一目录时据此确认可以安全清空
+const SITE_MANIFEST_FILE: &str = "manifest.json";
+const UNCATEGORIZED: &str = "未分类";
+// 搜索索引中每篇笔记保留的正文字符数
+const SEARCH_TEXT_LIMIT: usize = 4000;
+
+// ============= 共享的笔记收集与渲染 =============
+
+// 待导出的笔记
+#[derive(Debug, Clone)]
+pub(crate) struct ExportNote {
+    pub id: String,
+    pub title: String,
+    // 相对工作区根目录的路径（统一使用 /）
+    pub relative_path: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub language: Option<String>,
+    pub framework: Option<String>,
+    pub kind: Option<String>,
+    pub created: String,
+    pub modified: String,
+    pub body: String,
+}
+
+// 笔记中的标题（用于目录和导航文档）
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ExportHeading {
+    pub level: usize,
+    pub text: String,
+    pub anchor: String,
+}
+
+// 单篇笔记的渲染结果
+pub(crate) struct RenderedNote {
+    pub html: String,
+    pub headings: Vec<ExportHeading>,
+    // 通过 wikilink 或相对 .md 链接引用的笔记索引
+    pub links_to: Vec<usize>,
+    // 无法解析的 wikilink 目标
+    pub unresolved: Vec<String>,
+}
+
+// 导出目标格式决定链接与附件的落地方式（站点相对路径、EPUB 章节、data URI 等）
+pub(crate) trait ExportLinker {
+    // 生成指向另一篇笔记的链接
+    fn note_href(&mut self, target: usize, fragment: Option<&str>) -> String;
+    // 处理本地附件，返回可在输出中使用的地址；None 表示保留原链接
+    fn asset_href(&mut self, workspace_relative: &str, absolute: &Path) -> Option<String>;
+    // 标题元素的 id；多篇笔记合并到同一文档时需要加前缀避免冲突
+    fn heading_id(&self, anchor: &str) -> String {
+        anchor.to_string()
+    }
+    // 是否保留正文中的原始 HTML；XHTML 输出（EPUB）需要转义以保证文档合法
+    fn keep_raw_html(&self) -> bool {
+        true
+    }
+}
+
+// 按标题、文件名和相对路径查找笔记
+pub(crate) struct NoteLookup {
+    by_title: HashMap<String, usize>,
+    by_path: HashMap<String, usize>,
+}
+
+impl NoteLookup {
+    pub fn new(notes: &[ExportNote]) -> Self {
+        Self::from_titles(
+            notes
+                .iter()
+                .map(|n| (n.title.as_str(), n.relative_path.as_str())),
+        )
+    }
+
+    // 由 (标题, 相对路径) 序列构建，索引即序列中的位置
+    pub fn from_titles<'a>(items: impl Iterator<Item = (&'a str, &'a str)> + Clone) -> Self {
+        let mut by_title = HashMap::new();
+        let mut by_path = HashMap::new();
+
+        for (index, (title, relative_path)) in items.clone().enumerate() {
+            by_title.entry(title.to_lowercase()).or_insert(index);
+            by_path.insert(relative_path.to_lowercase(), index);
+        }
+
+        // 文件名作为标题的回退（外部编辑器常用文件名写 wikilink）
+        for (index, (_, relative_path)) in items.enumerate() {
+            if let Some(stem) = Path::new(relative_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+            {
+                by_title.entry(stem.to_lowercase()).or_insert(index);
+            }
+        }
+
+        Self { by_title, by_path }
+    }
+
+    pub fn find_by_title(&self, title: &str) -> Option<usize> {
+        let key = title.trim().to_lowercase();
+        let key = key.strip_suffix(".md").unwrap_or(&key);
+        self.by_title.get(key).copied()
+    }
+
+    pub fn find_by_path(&self, relative_path: &str) -> Option<usize> {
+        self.by_path.get(&relative_path.to_lowercase()).copied()
+    }
+}
+
+// 扫描工作区，按分类和标签收集待导出的笔记
+//
+// # Arguments
+// * `workspace_root` - 工作区根目录
+// * `categories` - 分类白名单（为空表示全部）
+// * `tags` - 标签白名单（为空表示全部，命中任意一个即可）
+pub(crate) fn collect_export_notes(
+    workspace_root: &Path,
+    categories: &[String],
+    tags: &[String],
+) -> Result<Vec<ExportNote>, String> {
+    let tag_filter: HashSet<String> = tags.iter().map(|t| t.trim().to_lowercase()).collect();
+    let mut notes = Vec::new();
+    let ignore = load_workspace_ignore(workspace_root);
+
+    for entry in WalkDir::new(workspace_root)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| {
+            // 跳过隐藏目录（.git、.snippets-code 等）和 .snippetsignore 排除的路径
+            e.depth() == 0
+                || (!e.file_name().to_string_lossy().starts_with('.')
+                    && !ignore.is_entry_ignored(workspace_root, e))
+        })
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("md") {
+            continue;
+        }
+
+        let relative_path = get_relative_path(workspace_root, path)?;
+        let category = category_from_relative_path(&relative_path);
+        if !categories.is_empty() && !categories.iter().any(|c| c == &category) {
+            continue;
+        }
+
+        let raw = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) => {
+                warn!("⚠️ [导出] 读取文件失败 {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let (fm_opt, body) = try_parse_front_matter(&raw);
+        let note = match fm_opt {
+            Some(fm) => ExportNote {
+                id: fm.id,
+                title: fm.title,
+                relative_path,
+                category,
+                tags: fm.tags,
+                language: fm.language,
+                framework: fm.framework,
+                kind: fm.kind,
+                created: fm.created,
+                modified: fm.modified,
+                body,
+            },
+            None => ExportNote {
+                id: relative_path.clone(),
+                title: path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Untitled")
+                    .to_string(),
+                relative_path,
+                category,
+                tags: Vec::new(),
+                language: None,
+                framework: None,
+                kind: None,
+                created: String::new(),
+                modified: String::new(),
+                body,
+            },
+        };
+
+        if !tag_filter.is_empty()
+            && !note
+                .tags
+                .iter()
+                .any(|t| tag_filter.contains(&t.to_lowercase()))
+        {
+            continue;
+        }
+
+        notes.push(note);
+    }
+
+    notes.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(notes)
+}
+
+// 从相对路径提取分类（与 CacheManager::extract_category_from_path 规则一致）
+pub(crate) fn category_from_relative_path(relative_path: &str) -> String {
+    let parts: Vec<&str> = relative_path.split('/').filter(|p| !p.is_empty()).collect();
+    if parts.len() > 1 {
+        parts[0].to_string()
+    } else {
+        UNCATEGORIZED.to_string()
+    }
+}
+
+// 生成 GitHub 风格的标题锚点
+pub(crate) fn slugify_heading(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else if c.is_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// 转义 HTML 文本和属性值
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 是否为外部或页内链接（不需要重写）
+pub(crate) fn is_external_url(url: &str) -> bool {
+    let lower = url.to_lowercase();
+    url.starts_with('#')
+        || url.starts_with('/')
+        || lower.starts_with("data:")
+        || lower.starts_with("mailto:")
+        || lower.contains("://")
+}
+
+// 将笔记内的相对链接解析为工作区相对路径；越出工作区的链接返回 None。
+// 含 `:` 的路径段（Windows 盘符如 `C:`）会让 join 得到绝对路径，同样拒绝
+pub(crate) fn resolve_workspace_relative(note_relative_path: &str, link: &str) -> Option<String> {
+    let decoded = urlencoding::decode(link)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| link.to_string());
+    let mut parts: Vec<&str> = note_relative_path.split('/').collect();
+    parts.pop(); // 去掉笔记文件名，得到笔记所在目录
+
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ if segment.contains(':') => return None,
+            _ => parts.push(segment),
+        }
+    }
+
+    let resolved = parts.join("/");
+    let is_plain = Path::new(&resolved)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if parts.is_empty() || !is_plain {
+        None
+    } else {
+        Some(resolved)
+    }
+}
+
+// 解析出的附件拼到工作区根目录后仍须位于工作区内，才会被复制或内联
+fn workspace_file(workspace_root: &Path, resolved: &str) -> Option<PathBuf> {
+    let absolute = workspace_root.join(resolved);
+    (is_within_workspace(workspace_root, &absolute) && absolute.is_file()).then_some(absolute)
+}
+
+// 拆分 `Title#Heading` 形式的 wikilink 目标
+pub(crate) fn split_link_fragment(target: &str) -> (&str, Option<&str>) {
+    match target.split_once('#') {
+        Some((title, fragment)) => (title, Some(fragment).filter(|f| !f.is_empty())),
+        None => (target, None),
+    }
+}
+
+// 用 pulldown-cmark-toc 提取标题，并分配去重后的锚点
+pub(crate) fn extract_headings(body: &str) -> Vec<ExportHeading> {
+    let toc = TableOfContents::new(body);
+    let mut used: HashMap<String, usize> = HashMap::new();
+
+    toc.headings()
+        .map(|heading| {
+            let text = heading.text();
+            let base = slugify_heading(&text);
+            let base = if base.is_empty() {
+                "section".to_string()
+            } else {
+                base
+            };
+            let count = used.entry(base.clone()).or_insert(0);
+            let anchor = if *count == 0 {
+                base.clone()
+            } else {
+                format!("{}-{}", base, count)
+            };
+            *count += 1;
+
+            ExportHeading {
+                level: heading.level() as usize,
+                text,
+                anchor,
+            }
+        })
+        .collect()
+}
+
+// 渲染嵌套的目录列表
+pub(crate) fn render_toc_html(headings: &[ExportHeading]) -> String {
+    render_heading_list(headings, "ul", &|heading| format!("#{}", heading.anchor))
+}
+
+// 渲染嵌套的标题列表（站点目录与 EPUB 导航文档共用）
+//
+// 标题层级跳跃（如 h1 直接到 h3）时每次最多缩进一级，
+// 保证生成的列表结构合法（EPUB 导航文档要求 li 内至多一个子列表）。
+pub(crate) fn render_heading_list(
+    headings: &[ExportHeading],
+    list_tag: &str,
+    href_for: &dyn Fn(&ExportHeading) -> String,
+) -> String {
+    if headings.is_empty() {
+        return String::new();
+    }
+
+    let min_level = headings.iter().map(|h| h.level).min().unwrap_or(1);
+    let mut html = format!("<{}>", list_tag);
+    let mut depth = 0usize;
+
+    for (index, heading) in headings.iter().enumerate() {
+        let target = if index == 0 {
+            0
+        } else {
+            heading.level.saturating_sub(min_level).min(depth + 1)
+        };
+        if index > 0 {
+            if target > depth {
+                html.push_str(&format!("<{}>", list_tag));
+            } else {
+                html.push_str("</li>");
+                for _ in target..depth {
+                    html.push_str(&format!("</{}></li>", list_tag));
+                }
+            }
+        }
+        depth = target;
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>",
+            escape_html(&href_for(heading)),
+            escape_html(&heading.text)
+        ));
+    }
+
+    html.push_str("</li>");
+    for _ in 0..depth {
+        html.push_str(&format!("</{}></li>", list_tag));
+    }
+    html.push_str(&format!("</{}>", list_tag));
+    html
+}
+
+// 将笔记正文渲染为 HTML
+//
+// wikilink 和指向 .md 的相对链接交给 `linker` 改写为目标格式的地址，
+// 本地附件同样交由 `linker` 处理；代码块保留 `language-*` class。
+pub(crate) fn render_note(
+    notes: &[ExportNote],
+    index: usize,
+    lookup: &NoteLookup,
+    workspace_root: &Path,
+    linker: &mut dyn ExportLinker,
+) -> RenderedNote {
+    let note = &notes[index];
+    let headings = extract_headings(&note.body);
+    let mut links_to = Vec::new();
+    let mut unresolved = Vec::new();
+    let mut heading_cursor = 0usize;
+    // Start(Link) 是否被替换为 <span>，用于在 End(Link) 时对称关闭
+    let mut link_stack: Vec<bool> = Vec::new();
+
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_WIKILINKS;
+
+    let mut events: Vec<Event> = Vec::new();
+    for event in Parser::new_ext(&note.body, options) {
+        match event {
+            Event::Start(Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            }) => {
+                let anchor = headings
+                    .get(heading_cursor)
+                    .map(|h| h.anchor.clone())
+                    .unwrap_or_else(|| format!("section-{}", heading_cursor + 1));
+                heading_cursor += 1;
+                events.push(Event::Start(Tag::Heading {
+                    level,
+                    id: Some(id.unwrap_or_else(|| CowStr::from(linker.heading_id(&anchor)))),
+                    classes,
+                    attrs,
+                }));
+            }
+            Event::Start(Tag::Link {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                title,
+                id,
+            }) => {
+                let (target_title, fragment) = split_link_fragment(&dest_url);
+                match lookup.find_by_title(target_title) {
+                    Some(target) => {
+                        links_to.push(target);
+                        let href =
+                            linker.note_href(target, fragment.map(slugify_heading).as_deref());
+                        link_stack.push(false);
+                        events.push(Event::Start(Tag::Link {
+                            link_type: LinkType::Inline,
+                            dest_url: CowStr::from(href),
+                            title,
+                            id,
+                        }));
+                    }
+                    None => {
+                        unresolved.push(target_title.to_string());
+                        link_stack.push(true);
+                        events.push(Event::Html(CowStr::from(format!(
+                            "<span class=\"wikilink-missing\" title=\"{}\">",
+                            escape_html(target_title)
+                        ))));
+                    }
+                }
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) if !is_external_url(&dest_url) => {
+                let (path_part, fragment) = split_link_fragment(&dest_url);
+                let rewritten = resolve_workspace_relative(&note.relative_path, path_part)
+                    .and_then(|resolved| {
+                        if resolved.to_lowercase().ends_with(".md") {
+                            lookup.find_by_path(&resolved).map(|target| {
+                                links_to.push(target);
+                                linker.note_href(target, fragment)
+                            })
+                        } else {
+                            workspace_file(workspace_root, &resolved)
+                                .and_then(|absolute| linker.asset_href(&resolved, &absolute))
+                        }
+                    });
+                link_stack.push(false);
+                events.push(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url: rewritten.map(CowStr::from).unwrap_or(dest_url),
+                    title,
+                    id,
+                }));
+            }
+            Event::Start(Tag::Link { .. }) => {
+                link_stack.push(false);
+                events.push(event);
+            }
+            Event::End(TagEnd::Link) => {
+                if link_stack.pop().unwrap_or(false) {
+                    events.push(Event::Html(CowStr::Borrowed("</span>")));
+                } else {
+                    events.push(Event::End(TagEnd::Link));
+                }
+            }
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) if !is_external_url(&dest_url) => {
+                let is_file =
+                    |resolved: &String| workspace_file(workspace_root, resolved).is_some();
+                let rewritten = resolve_workspace_relative(&note.relative_path, &dest_url)
+                    .filter(is_file)
+                    .or_else(|| {
+                        // `![[image.png]]` 可能直接写文件名，回退到工作区根目录查找；
+                        // 同样经过规范化，越出工作区的 `..` 目标不会被读取或复制
+                        matches!(link_type, LinkType::WikiLink { .. })
+                            .then(|| resolve_workspace_relative("", &dest_url))
+                            .flatten()
+                            .filter(is_file)
+                    })
+                    .and_then(|resolved| {
+                        let absolute = workspace_file(workspace_root, &resolved)?;
+                        linker.asset_href(&resolved, &absolute)
+                    });
+                events.push(Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: rewritten.map(CowStr::from).unwrap_or(dest_url),
+                    title,
+                    id,
+                }));
+            }
+            Event::Html(raw) | Event::InlineHtml(raw) if !linker.keep_raw_html() => {
+                events.push(Event::Text(raw));
+            }
+            other => events.push(other),
+        }
+    }
+
+    let mut body_html = String::new();
+    html::push_html(&mut body_html, events.into_iter());
+
+    links_to.sort_unstable();
+    links_to.dedup();
+    links_to.retain(|target| *target != index);
+
+    RenderedNote {
+        html: body_html,
+        headings,
+        links_to,
+        unresolved,
+    }
+}
+
+// 提取纯文本（用于搜索索引），跳过代码块外的标记
+pub(crate) fn plain_text(body: &str, limit: usize) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(body, Options::ENABLE_TABLES) {
+        match event {
+            Event::Text(t) | Event::Code(t) => {
+                text.push_str(&t);
+                text.push(' ');
+            }
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+        if text.chars().count() >= limit {
+            break;
+        }
+    }
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed.chars().take(limit).collect()
+}
+
+// 根据扩展名推断附件的 MIME 类型
+pub(crate) fn mime_for_path(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+// ============= 静态站点导出 =============
+
+// 静态站点导出选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticSiteExportOptions {
+    // 输出目录（不能位于工作区内）
+    pub output_dir: String,
+    // 仅导出这些分类（为空表示全部）
+    #[serde(default)]
+    pub categories: Vec<String>,
+    // 仅导出带有这些标签的笔记（为空表示全部）
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // 站点标题
+    #[serde(default)]
+    pub site_title: Option<String>,
+}
+
+// 静态站点导出结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticSiteExportResult {
+    pub output_dir: String,
+    pub index_path: String,
+    pub note_count: usize,
+    pub attachment_count: usize,
+    pub tag_count: usize,
+    pub category_count: usize,
+    // 无法解析的 wikilink（笔记标题 -> 目标）
+    pub unresolved_links: Vec<String>,
+}
+
+// 搜索索引项（写入 _site/search.json 和 _site/search-index.js）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchIndexItem {
+    id: String,
+    title: String,
+    url: String,
+    category: String,
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    headings: Vec<String>,
+    text: String,
+}
+
+// 导出清单（用于识别可安全覆盖的旧导出目录）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SiteManifest {
+    generator: String,
+    exported_at: String,
+    note_count: usize,
+}
+
+// 站点链接策略：所有地址都相对当前页面，保证 file:// 下可用
+struct SiteLinker<'a> {
+    root_prefix: String,
+    note_pages: &'a [String],
+    assets: &'a mut BTreeMap<String, PathBuf>,
+}
+
+impl ExportLinker for SiteLinker<'_> {
+    fn note_href(&mut self, target: usize, fragment: Option<&str>) -> String {
+        let mut href = format!(
+            "{}{}",
+            self.root_prefix,
+            encode_path(&self.note_pages[target])
+        );
+        if let Some(fragment) = fragment {
+            href.push('#');
+            href.push_str(&urlencoding::encode(fragment));
+        }
+        href
+    }
+
+    fn asset_href(&mut self, workspace_relative: &str, absolute: &Path) -> Option<String> {
+        self.assets
+            .insert(workspace_relative.to_string(), absolute.to_path_buf());
+        Some(format!(
+            "{}{}",
+            self.root_prefix,
+            encode_path(workspace_relative)
+        ))
+    }
+}
+
+// 对路径的每一段做 URL 编码，保留 `/`
+fn encode_path(relative: &str) -> String {
+    relative
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// 页面到站点根目录的相对前缀（如 `React/Hooks.html` -> `../`）
+fn root_prefix_for(page: &str) -> String {
+    "../".repeat(page.matches('/').count())
+}
+
+// 笔记页面路径：保持工作区目录结构，仅替换扩展名
+fn note_page_path(relative_path: &str) -> String {
+    match relative_path.strip_suffix(".md") {
+        Some(stem) => format!("{}.html", stem),
+        None => format!("{}.html", relative_path),
+    }
+}
+
+// 为标签/分类索引页分配不冲突的文件名
+fn assign_page_names<'a>(names: impl Iterator<Item = &'a String>) -> HashMap<String, String> {
+    let mut assigned = HashMap::new();
+    let mut used = HashSet::new();
+
+    for name in names {
+        let base = FileNameGenerator::sanitize_filename(name);
+        let mut candidate = base.clone();
+        let mut counter = 1;
+        while !used.insert(candidate.to_lowercase()) {
+            counter += 1;
+            candidate = format!("{}-{}", base, counter);
+        }
+        assigned.insert(name.clone(), format!("{}.html", candidate));
+    }
+
+    assigned
+}
+
+// 导出路径是否位于工作区内（导出产物不应被当作笔记再次索引）
+pub(crate) fn is_inside_workspace(path: &Path, workspace_root: &Path) -> Result<bool, String> {
+    let canonical_workspace = workspace_root
+        .canonicalize()
+        .map_err(|e| format!("无法规范化工作区路径: {}", e))?;
+
+    // 导出路径可能尚不存在，按最近的已存在祖先判断
+    let mut probe = path.to_path_buf();
+    while !probe.exists() {
+        match probe.parent() {
+            Some(parent) => probe = parent.to_path_buf(),
+            None => break,
+        }
+    }
+    Ok(probe
+        .canonicalize()
+        .map(|canonical_probe| canonical_probe.starts_with(&canonical_workspace))
+        .unwrap_or(false))
+}
+
+// 检查并准备输出目录
+fn prepare_output_dir(output_dir: &Path, workspace_root: &Path) -> Result<(), String> {
+    if is_inside_workspace(output_dir, workspace_root)? {
+        return Err("导出目录不能位于工作区内".to_string());
+    }
+
+    if output_dir.exists() {
+        let is_empty = fs::read_dir(output_dir)
+            .map_err(|e| format!("读取导出目录失败: {}", e))?
+            .next()
+            .is_none();
+        let manifest = output_dir.join(SITE_ASSET_DIR).join(SITE_MANIFEST_FILE);
+
+        if !is_empty {
+            if !manifest.exists() {
+                return Err("导出目录非空且不是之前导出的站点，请选择空目录".to_string());
+            }
+            // 之前导出的站点：整体清空，避免残留已删除的笔记页面
+            fs::remove_dir_all(output_dir).map_err(|e| format!("清理旧导出目录失败: {}", e))?;
+        }
+    }
+
+    fs::create_dir_all(output_dir).map_err(|e| format!("创建导出目录失败: {}", e))
+}
+
+fn write_site_file(output_dir: &Path, relative: &str, content: &str) -> Result<(), String> {
+    let path = output_dir.join(relative);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建目录失败 '{}': {}", parent.display(), e))?;
+    }
+    fs::write(&path, content).map_err(|e| format!("写入文件失败 '{}': {}", path.display(), e))
+}
+
+// 页面骨架
+fn render_page(site_title: &str, page_title: &str, root_prefix: &str, main_html: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN" data-root="{root}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{page_title} · {site_title}</title>
+<link rel="stylesheet" href="{root}{assets}/style.css">
+</head>
+<body>
+<header class="site-header">
+  <a class="site-title" href="{root}index.html">{site_title}</a>
+  <div class="site-search">
+    <input id="site-search" type="search" placeholder="搜索笔记…" autocomplete="off">
+    <ul id="search-results" hidden></ul>
+  </div>
+</header>
+<div class="site-body">
+{main}
+</div>
+<script src="{root}{assets}/search-index.js"></script>
+<script src="{root}{assets}/search.js"></script>
+</body>
+</html>
+"#,
+        root = root_prefix,
+        assets = SITE_ASSET_DIR,
+        page_title = escape_html(page_title),
+        site_title = escape_html(site_title),
+        main = main_html,
+    )
+}
+
+// 笔记列表（索引页通用）
+fn render_note_list(
+    notes: &[ExportNote],
+    indices: &[usize],
+    note_pages: &[String],
+    root_prefix: &str,
+) -> String {
+    let mut sorted: Vec<usize> = indices.to_vec();
+    sorted.sort_by(|a, b| {
+        notes[*a]
+            .title
+            .to_lowercase()
+            .cmp(&notes[*b].title.to_lowercase())
+    });
+
+    let mut html = String::from("<ul class=\"note-list\">");
+    for index in sorted {
+        let note = &notes[index];
+        html.push_str(&format!(
+            "<li><a href=\"{}{}\">{}</a>",
+            root_prefix,
+            encode_path(&note_pages[index]),
+            escape_html(&note.title)
+        ));
+        if let Some(language) = &note.language {
+            html.push_str(&format!(
+                " <span class=\"badge\">{}</span>",
+                escape_html(language)
+            ));
+        }
+        html.push_str("</li>");
+    }
+    html.push_str("</ul>");
+    html
+}
+
+fn render_tag_links(
+    tags: &[String],
+    tag_pages: &HashMap<String, String>,
+    root_prefix: &str,
+) -> String {
+    tags.iter()
+        .filter_map(|tag| {
+            tag_pages.get(tag).map(|page| {
+                format!(
+                    "<a class=\"tag\" href=\"{}{}/tags/{}\">#{}</a>",
+                    root_prefix,
+                    SITE_ASSET_DIR,
+                    encode_path(page),
+                    escape_html(tag)
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 导出静态站点（同步执行，由命令放入阻塞线程池）
+fn export_static_site_blocking(
+    workspace_root: &Path,
+    options: &StaticSiteExportOptions,
+) -> Result<StaticSiteExportResult, String> {
+    let output_dir = PathBuf::from(options.output_dir.trim());
+    if output_dir.as_os_str().is_empty() {
+        return Err("导出目录不能为空".to_string());
+    }
+
+    let notes = collect_export_notes(workspace_root, &options.categories, &options.tags)?;
+    if notes.is_empty() {
+        return Err("没有符合条件的笔记可导出".to_string());
+    }
+
+    prepare_output_dir(&output_dir, workspace_root)?;
+
+    let site_title = options
+        .site_title
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or("Snippets Code")
+        .to_string();
+
+    let lookup = NoteLookup::new(&notes);
+    let note_pages: Vec<String> = notes
+        .iter()
+        .map(|n| note_page_path(&n.relative_path))
+        .collect();
+
+    // 标签、分类 -> 笔记索引
+    let mut tag_map: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut category_map: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, note) in notes.iter().enumerate() {
+        for tag in &note.tags {
+            tag_map.entry(tag.clone()).or_default().push(index);
+        }
+        category_map
+            .entry(note.category.clone())
+            .or_default()
+            .push(index);
+    }
+    let tag_pages = assign_page_names(tag_map.keys());
+    let category_pages = assign_page_names(category_map.keys());
+
+    // 第一轮：渲染所有笔记，收集链接关系和附件
+    let mut assets: BTreeMap<String, PathBuf> = BTreeMap::new();
+    let mut rendered: Vec<RenderedNote> = Vec::with_capacity(notes.len());
+    for index in 0..notes.len() {
+        let mut linker = SiteLinker {
+            root_prefix: root_prefix_for(&note_pages[index]),
+            note_pages: &note_pages,
+            assets: &mut assets,
+        };
+        rendered.push(render_note(
+            &notes,
+            index,
+            &lookup,
+            workspace_root,
+            &mut linker,
+        ));
+    }
+
+    let mut backlinks: Vec<Vec<usize>> = vec![Vec::new(); notes.len()];
+    for (source, note) in rendered.iter().enumerate() {
+        for target in &note.links_to {
+            backlinks[*target].push(source);
+        }
+    }
+
+    // 第二轮：写出笔记页面
+    let mut unresolved_links = Vec::new();
+    for (index, note) in notes.iter().enumerate() {
+        let page = &note_pages[index];
+        let root = root_prefix_for(page);
+        let rendered_note = &rendered[index];
+
+        for target in &rendered_note.unresolved {
+            unresolved_links.push(format!("{} -> {}", note.title, target));
+        }
+
+        let mut meta = format!(
+            "<a class=\"category\" href=\"{}{}/categories/{}\">{}</a>",
+            root,
+            SITE_ASSET_DIR,
+            encode_path(&category_pages[&note.category]),
+            escape_html(&note.category)
+        );
+        for value in [&note.language, &note.framework, &note.kind]
+            .into_iter()
+            .flatten()
+        {
+            meta.push_str(&format!(
+                " <span class=\"badge\">{}</span>",
+                escape_html(value)
+            ));
+        }
+        let tag_links = render_tag_links(&note.tags, &tag_pages, &root);
+        if !tag_links.is_empty() {
+            meta.push(' ');
+            meta.push_str(&tag_links);
+        }
+        for (class, value) in [("created", &note.created), ("modified", &note.modified)] {
+            if !value.is_empty() {
+                meta.push_str(&format!(
+                    " <time class=\"{}\">{}</time>",
+                    class,
+                    escape_html(value.split('T').next().unwrap_or(value))
+                ));
+            }
+        }
+
+        let toc_html = render_toc_html(&rendered_note.headings);
+        let backlinks_html = if backlinks[index].is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<section class=\"backlinks\"><h2>反向链接</h2>{}</section>",
+                render_note_list(&notes, &backlinks[index], &note_pages, &root)
+            )
+        };
+
+        let main = format!(
+            "{toc}<article class=\"note\"><h1 class=\"note-title\">{title}</h1><div class=\"note-meta\">{meta}</div>{body}{backlinks}</article>",
+            toc = if toc_html.is_empty() {
+                String::new()
+            } else {
+                format!("<nav class=\"toc\"><h2>目录</h2>{}</nav>", toc_html)
+            },
+            title = escape_html(&note.title),
+            meta = meta,
+            body = rendered_note.html,
+            backlinks = backlinks_html,
+        );
+
+        write_site_file(
+            &output_dir,
+            page,
+            &render_page(&site_title, &note.title, &root, &main),
+        )?;
+    }
+
+    // 标签、分类索引页（位于 _site/tags、_site/categories，根前缀为 ../../）
+    let index_root = "../../";
+    for (tag, indices) in &tag_map {
+        let main = format!(
+            "<article class=\"index-page\"><h1>#{}</h1>{}</article>",
+            escape_html(tag),
+            render_note_list(&notes, indices, &note_pages, index_root)
+        );
+        write_site_file(
+            &output_dir,
+            &format!("{}/tags/{}", SITE_ASSET_DIR, tag_pages[tag]),
+            &render_page(&site_title, &format!("#{}", tag), index_root, &main),
+        )?;
+    }
+    for (category, indices) in &category_map {
+        let main = format!(
+            "<article class=\"index-page\"><h1>{}</h1>{}</article>",
+            escape_html(category),
+            render_note_list(&notes, indices, &note_pages, index_root)
+        );
+        write_site_file(
+            &output_dir,
+            &format!("{}/categories/{}", SITE_ASSET_DIR, category_pages[category]),
+            &render_page(&site_title, category, index_root, &main),
+        )?;
+    }
+
+    // 首页：分类、标签云和全部笔记
+    let mut home = String::from("<article class=\"index-page\">");
+    home.push_str(&format!("<h1>{}</h1>", escape_html(&site_title)));
+    home.push_str("<h2>分类</h2><ul class=\"category-list\">");
+    for (category, indices) in &category_map {
+        home.push_str(&format!(
+            "<li><a href=\"{}/categories/{}\">{}</a> <span class=\"count\">{}</span></li>",
+            SITE_ASSET_DIR,
+            encode_path(&category_pages[category]),
+            escape_html(category),
+            indices.len()
+        ));
+    }
+    home.push_str("</ul>");
+    if !tag_map.is_empty() {
+        home.push_str("<h2>标签</h2><div class=\"tag-cloud\">");
+        let all_tags: Vec<String> = tag_map.keys().cloned().collect();
+        home.push_str(&render_tag_links(&all_tags, &tag_pages, ""));
+        home.push_str("</div>");
+    }
+    home.push_str("<h2>全部笔记</h2>");
+    let all_indices: Vec<usize> = (0..notes.len()).collect();
+    home.push_str(&render_note_list(&notes, &all_indices, &note_pages, ""));
+    home.push_str("</article>");
+    write_site_file(
+        &output_dir,
+        "index.html",
+        &render_page(&site_title, "首页", "", &home),
+    )?;
+
+    // 客户端搜索索引：JSON 供外部工具使用，JS 版本供 file:// 页面直接加载
+    let search_items: Vec<SearchIndexItem> = notes
+        .iter()
+        .enumerate()
+        .map(|(index, note)| SearchIndexItem {
+            id: note.id.clone(),
+            title: note.title.clone(),
+            url: encode_path(&note_pages[index]),
+            category: note.category.clone(),
+            tags: note.tags.clone(),
+            language: note.language.clone(),
+            headings: rendered[index]
+                .headings
+                .iter()
+                .map(|h| h.text.clone())
+                .collect(),
+            text: plain_text(&note.body, SEARCH_TEXT_LIMIT),
+        })
+        .collect();
+    let search_json =
+        serde_json::to_string(&search_items).map_err(|e| format!("序列化搜索索引失败: {}", e))?;
+    write_site_file(
+        &output_dir,
+        &format!("{}/search.json", SITE_ASSET_DIR),
+        &search_json,
+    )?;
+    write_site_file(
+        &output_dir,
+        &format!("{}/search-index.js", SITE_ASSET_DIR),
+        &format!("window.SNIPPETS_SEARCH_INDEX = {};\n", search_json),
+    )?;
+    write_site_file(
+        &output_dir,
+        &format!("{}/search.js", SITE_ASSET_DIR),
+        SITE_SEARCH_JS,
+    )?;
+    write_site_file(
+        &output_dir,
+        &format!("{}/style.css", SITE_ASSET_DIR),
+        SITE_STYLE_CSS,
+    )?;
+
+    // 复制被引用的附件（保持工作区相对路径，笔记中的相对链接无需改写）
+    let mut attachment_count = 0;
+    for (relative, source) in &assets {
+        let target = output_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建附件目录失败: {}", e))?;
+        }
+        match fs::copy(source, &target) {
+            Ok(_) => attachment_count += 1,
+            Err(e) => warn!("⚠️ [导出] 复制附件失败 {}: {}", source.display(), e),
+        }
+    }
+
+    let manifest = SiteManifest {
+        generator: format!("snippets-code {}", env!("CARGO_PKG_VERSION")),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        note_count: notes.len(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("序列化导出清单失败: {}", e))?;
+    write_site_file(
+        &output_dir,
+        &format!("{}/{}", SITE_ASSET_DIR, SITE_MANIFEST_FILE),
+        &manifest_json,
+    )?;
+
+    info!(
+        "✅ [导出] 静态站点导出完成: {} 篇笔记, {} 个附件 -> {}",
+        notes.len(),
+        attachment_count,
+        output_dir.display()
+    );
+
+    Ok(StaticSiteExportResult {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        index_path: output_dir.join("index.html").to_string_lossy().to_string(),
+        note_count: notes.len(),
+        attachment_count,
+        tag_count: tag_map.len(),
+        category_count: category_map.len(),
+        unresolved_links,
+    })
+}
+
+// 导出工作区为静态 HTML 站点
+#[command]
+pub async fn export_static_site(
+    app_handle: AppHandle,
+    options: StaticSiteExportOptions,
+) -> Result<StaticSiteExportResult, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+
+    tokio::task::spawn_blocking(move || export_static_site_blocking(&workspace_root, &options))
+        .await
+        .map_err(|e| format!("导出任务执行失败: {}", e))?
+}
+
+const SITE_STYLE_CSS: &str = r#":root { --fg: #1f2328; --muted: #656d76; --bg: #ffffff; --accent: #0969da; --border: #d0d7de; --code-bg: #f6f8fa; }
+* { box-sizing: border-box; }
+body { margin: 0; font: 15px/1.65 -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; color: var(--fg); background: var(--bg); }
+a { color: var(--accent); text-decoration: none; }
+a:hover { text-decoration: underline; }
+.site-header { position: sticky; top: 0; display: flex; align-items: center; gap: 24px; padding: 10px 24px; background: var(--bg); border-bottom: 1px solid var(--border); z-index: 10; }
+.site-title { font-weight: 600; color: var(--fg); }
+.site-search { position: relative; flex: 1; max-width: 420px; }
+#site-search { width: 100%; padding: 6px 10px; border: 1px solid var(--border); border-radius: 6px; font-size: 14px; }
+#search-results { position: absolute; left: 0; right: 0; margin: 4px 0 0; padding: 4px 0; list-style: none; background: var(--bg); border: 1px solid var(--border); border-radius: 6px; max-height: 60vh; overflow: auto; box-shadow: 0 8px 24px rgba(0,0,0,.12); }
+#search-results li a { display: block; padding: 6px 12px; color: var(--fg); }
+#search-results li small { display: block; color: var(--muted); }
+.site-body { display: flex; gap: 32px; max-width: 1100px; margin: 0 auto; padding: 24px; }
+.toc { flex: 0 0 220px; position: sticky; top: 72px; align-self: flex-start; max-height: calc(100vh - 96px); overflow: auto; font-size: 13px; }
+.toc h2 { font-size: 13px; text-transform: uppercase; color: var(--muted); }
+.toc ul { padding-left: 14px; margin: 0; list-style: none; }
+.note, .index-page { flex: 1; min-width: 0; }
+.note-meta { color: var(--muted); font-size: 13px; margin-bottom: 24px; }
+.badge, .tag { display: inline-block; padding: 0 6px; border-radius: 10px; background: var(--code-bg); border: 1px solid var(--border); font-size: 12px; }
+.count { color: var(--muted); font-size: 12px; }
+pre { background: var(--code-bg); padding: 12px 16px; border-radius: 6px; overflow: auto; }
+code { font-family: "JetBrains Mono", Consolas, monospace; font-size: 13px; }
+:not(pre) > code { background: var(--code-bg); padding: 1px 4px; border-radius: 4px; }
+img { max-width: 100%; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid var(--border); padding: 4px 10px; }
+blockquote { margin: 0; padding: 0 16px; color: var(--muted); border-left: 4px solid var(--border); }
+.wikilink-missing { color: #cf222e; border-bottom: 1px dashed currentColor; }
+.backlinks { margin-top: 48px; padding-top: 16px; border-top: 1px solid var(--border); }
+.tag-cloud .tag { margin: 0 4px 6px 0; }
+@media (max-width: 800px) { .site-body { flex-direction: column; } .toc { position: static; max-height: none; } }
+"#;
+
+const SITE_SEARCH_JS: &str = r#"(function () {
+  var index = window.SNIPPETS_SEARCH_INDEX || [];
+  var root = document.documentElement.getAttribute('data-root') || '';
+  var input = document.getElementById('site-search');
+  var list = document.getElementById('search-results');
+  if (!input || !list) return;
+
+  function escapeHtml(text) {
+    return String(text).replace(/[&<>"']/g, function (c) {
+      return { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c];
+    });
+  }
+
+  function score(item, terms) {
+    var title = item.title.toLowerCase();
+    var tags = item.tags.join(' ').toLowerCase();
+    var headings = item.headings.join(' ').toLowerCase();
+    var text = item.text.toLowerCase();
+    var total = 0;
+    for (var i = 0; i < terms.length; i++) {
+      var term = terms[i];
+      var hit = 0;
+      if (title.indexOf(term) >= 0) hit += title === term ? 15 : 5;
+      if (tags.indexOf(term) >= 0) hit += 3;
+      if (headings.indexOf(term) >= 0) hit += 2;
+      if (text.indexOf(term) >= 0) hit += 1;
+      if (!hit) return 0;
+      total += hit;
+    }
+    return total;
+  }
+
+  input.addEventListener('input', function () {
+    var terms = input.value.trim().toLowerCase().split(/\s+/).filter(Boolean);
+    if (!terms.length) { list.hidden = true; list.innerHTML = ''; return; }
+    var results = index
+      .map(function (item) { return { item: item, score: score(item, terms) }; })
+      .filter(function (r) { return r.score > 0; })
+      .sort(function (a, b) { return b.score - a.score; })
+      .slice(0, 30);
+    list.innerHTML = results.length
+      ? results.map(function (r) {
+          return '<li><a href="' + root + r.item.url + '">' + escapeHtml(r.item.title) +
+            '<small>' + escapeHtml(r.item.category) + '</small></a></li>';
+        }).join('')
+      : '<li><a>没有匹配的笔记</a></li>';
+    list.hidden = false;
+  });
+
+  document.addEventListener('keydown', function (e) {
+    if (e.key === 'Escape') { list.hidden = true; input.blur(); }
+  });
+})();
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugifies_headings_like_github() {
+        assert_eq!(slugify_heading("Hello, World!"), "hello-world");
+        assert_eq!(
+            slugify_heading("使用 useEffect 清理"),
+            "使用-useeffect-清理"
+        );
+    }
+
+    #[test]
+    fn resolves_links_relative_to_note_directory() {
+        assert_eq!(
+            resolve_workspace_relative("React/Hooks.md", "../assets/Hooks/a%20b.png"),
+            Some("assets/Hooks/a b.png".to_string())
+        );
+        assert_eq!(
+            resolve_workspace_relative("Hooks.md", "../../etc/passwd"),
+            None
+        );
+        assert_eq!(
+            resolve_workspace_relative("Hooks.md", "C:/secret.png"),
+            None
+        );
+        assert_eq!(
+            resolve_workspace_relative("React/Hooks.md", "..\\C:\\secret.png"),
+            None
+        );
+        assert_eq!(resolve_workspace_relative("Hooks.md", "a%3Ab.png"), None);
+    }
+
+    #[test]
+    fn renders_nested_toc() {
+        let heading = |level, text: &str| ExportHeading {
+            level,
+            text: text.to_string(),
+            anchor: slugify_heading(text),
+        };
+        let html = render_toc_html(&[heading(2, "A"), heading(3, "B"), heading(2, "C")]);
+        assert_eq!(
+            html,
+            "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li><li><a href=\"#c\">C</a></li></ul>"
+        );
+    }
+
+    #[test]
+    fn toc_indents_one_level_when_headings_skip() {
+        let heading = |level, text: &str| ExportHeading {
+            level,
+            text: text.to_string(),
+            anchor: slugify_heading(text),
+        };
+        let html = render_toc_html(&[heading(1, "A"), heading(3, "B"), heading(1, "C")]);
+        assert_eq!(
+            html,
+            "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li><li><a href=\"#c\">C</a></li></ul>"
+        );
+    }
+
+    struct RecordingLinker {
+        assets: Vec<String>,
+    }
+
+    impl ExportLinker for RecordingLinker {
+        fn note_href(&mut self, _target: usize, _fragment: Option<&str>) -> String {
+            String::new()
+        }
+
+        fn asset_href(&mut self, workspace_relative: &str, _absolute: &Path) -> Option<String> {
+            self.assets.push(workspace_relative.to_string());
+            Some(workspace_relative.to_string())
+        }
+    }
+
+    #[test]
+    fn image_wikilinks_cannot_escape_workspace() {
+        let base = std::env::temp_dir().join(format!("export-test-{}", uuid::Uuid::new_v4()));
+        let workspace = base.join("workspace");
+        std::fs::create_dir_all(workspace.join("notes")).unwrap();
+        std::fs::write(base.join("secret.png"), "secret").unwrap();
+        std::fs::write(workspace.join("logo.png"), "logo").unwrap();
+
+        let notes = vec![ExportNote {
+            id: "1".to_string(),
+            title: "Note".to_string(),
+            relative_path: "notes/note.md".to_string(),
+            category: String::new(),
+            tags: vec![],
+            language: None,
+            framework: None,
+            kind: None,
+            created: String::new(),
+            modified: String::new(),
+            body: "![[../../secret.png]] ![[/../secret.png]] ![[logo.png]]".to_string(),
+        }];
+        let lookup = NoteLookup::new(&notes);
+        let mut linker = RecordingLinker { assets: vec![] };
+        render_note(&notes, 0, &lookup, &workspace, &mut linker);
+        assert_eq!(linker.assets, vec!["logo.png".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn exports_site_without_reading_outside_workspace() {
+        let base = std::env::temp_dir().join(format!("export-site-test-{}", uuid::Uuid::new_v4()));
+        let workspace = base.join("workspace");
+        let output = base.join("site");
+        std::fs::create_dir_all(workspace.join("React")).unwrap();
+        std::fs::create_dir_all(workspace.join("assets")).unwrap();
+        std::fs::create_dir_all(workspace.join("C:")).unwrap();
+        std::fs::write(base.join("secret.png"), "secret").unwrap();
+        std::fs::write(workspace.join("C:/secret.png"), "secret").unwrap();
+        std::fs::write(workspace.join("assets/logo.png"), "logo").unwrap();
+        std::fs::write(
+            workspace.join("React/Hooks.md"),
+            "---\nid: hooks\ntitle: Hooks\ntags: [react]\ncreated: 2024-05-01T09:00:00Z\n\
+             modified: 2024-05-01T09:00:00Z\ntype: note\n---\n# Hooks\n\n\
+             ![logo](../assets/logo.png) ![](../../secret.png) ![](C:/secret.png) \
+             ![[C:/secret.png]] [Effect](Effect.md) [[Missing]]\n",
+        )
+        .unwrap();
+        std::fs::write(workspace.join("React/Effect.md"), "# Effect\n").unwrap();
+
+        let result = export_static_site_blocking(
+            &workspace,
+            &StaticSiteExportOptions {
+                output_dir: output.to_string_lossy().to_string(),
+                categories: vec![],
+                tags: vec![],
+                site_title: Some("Test".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(result.note_count, 2);
+        assert_eq!(result.attachment_count, 1);
+        assert_eq!(result.tag_count, 1);
+        assert_eq!(
+            result.unresolved_links,
+            vec!["Hooks -> Missing".to_string()]
+        );
+        assert_eq!(
+            std::fs::read_to_string(output.join("assets/logo.png")).unwrap(),
+            "logo"
+        );
+        assert!(!output.join("C:").exists());
+        assert!(!output.join("secret.png").exists());
+
+        let page = std::fs::read_to_string(output.join("React/Hooks.html")).unwrap();
+        assert!(page.contains("src=\"../assets/logo.png\""));
+        assert!(page.contains("href=\"../React/Effect.html\""));
+        assert!(output.join("index.html").is_file());
+        assert!(output.join("_site/manifest.json").is_file());
+
+        // 再次导出到同一目录时整体替换旧站点
+        std::fs::remove_file(workspace.join("React/Effect.md")).unwrap();
+        let again = export_static_site_blocking(
+            &workspace,
+            &StaticSiteExportOptions {
+                output_dir: output.to_string_lossy().to_string(),
+                categories: vec![],
+                tags: vec![],
+                site_title: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(again.note_count, 1);
+        assert!(!output.join("React/Effect.html").exists());
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
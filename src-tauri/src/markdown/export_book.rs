@@ -0,0 +1,920 @@
+// 电子书导出模块
+// 将一个分类或保存的选集导出为 EPUB 3 包，或导出为图片内联的单文件 HTML
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::export::{
+    collect_export_notes, escape_html, is_inside_workspace, mime_for_path, render_heading_list,
+    render_note, ExportLinker, ExportNote, NoteLookup, RenderedNote,
+};
+use crate::markdown::{ExportSelection, WorkspaceManager};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tauri::{command, AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const EPUB_MIMETYPE: &str = "application/epub+zip";
+const DEFAULT_BOOK_TITLE: &str = "Snippets Code";
+const DEFAULT_BOOK_LANGUAGE: &str = "zh-CN";
+
+// 笔记排列方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookOrder {
+    // 按标题排序
+    Title,
+    // 按用户给定的顺序（customOrder 或选集中的顺序），未列出的笔记按标题追加在末尾
+    Custom,
+}
+
+// 电子书导出选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookExportOptions {
+    // 输出文件路径（.epub / .html，不能位于工作区内）
+    pub output_path: String,
+    // 导出的分类（为空表示不按分类过滤）
+    #[serde(default)]
+    pub category: Option<String>,
+    // 保存的选集名称
+    #[serde(default)]
+    pub selection: Option<String>,
+    // 排列方式；未指定时选集按保存顺序，分类按标题
+    #[serde(default)]
+    pub order: Option<BookOrder>,
+    // 自定义顺序（相对工作区根目录的笔记路径）
+    #[serde(default)]
+    pub custom_order: Vec<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+// 电子书导出结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookExportResult {
+    pub output_path: String,
+    pub note_count: usize,
+    pub image_count: usize,
+    // 无法解析或不在导出范围内的 wikilink（笔记标题 -> 目标）
+    pub unresolved_links: Vec<String>,
+}
+
+// 书籍元数据（已应用默认值）
+struct BookMeta {
+    title: String,
+    author: Option<String>,
+    language: String,
+}
+
+impl BookMeta {
+    fn from_options(options: &BookExportOptions) -> Self {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        Self {
+            title: non_empty(&options.title)
+                .or_else(|| non_empty(&options.selection))
+                .or_else(|| non_empty(&options.category))
+                .unwrap_or_else(|| DEFAULT_BOOK_TITLE.to_string()),
+            author: non_empty(&options.author),
+            language: non_empty(&options.language)
+                .unwrap_or_else(|| DEFAULT_BOOK_LANGUAGE.to_string()),
+        }
+    }
+}
+
+// 收集并排列待导出的笔记
+//
+// # Arguments
+// * `selection_paths` - 选集中的笔记路径（None 表示不使用选集）
+fn collect_book_notes(
+    workspace_root: &Path,
+    options: &BookExportOptions,
+    selection_paths: Option<&[String]>,
+) -> Result<Vec<ExportNote>, String> {
+    let categories: Vec<String> = options.category.iter().cloned().collect();
+    let mut notes = collect_export_notes(workspace_root, &categories, &[])?;
+
+    if let Some(paths) = selection_paths {
+        notes.retain(|note| paths.iter().any(|p| p == &note.relative_path));
+        if notes.len() < paths.len() {
+            warn!(
+                "⚠️ [导出] 选集中有 {} 篇笔记已不存在或不在所选分类中",
+                paths.len() - notes.len()
+            );
+        }
+    }
+
+    let (order, custom_order) = match (options.order, selection_paths) {
+        (Some(BookOrder::Custom), _) if !options.custom_order.is_empty() => {
+            (BookOrder::Custom, options.custom_order.as_slice())
+        }
+        (Some(BookOrder::Custom) | None, Some(paths)) => (BookOrder::Custom, paths),
+        (Some(order), _) => (order, options.custom_order.as_slice()),
+        (None, None) => (BookOrder::Title, &[][..]),
+    };
+
+    Ok(order_notes(notes, order, custom_order))
+}
+
+// 按标题或自定义顺序排列笔记
+fn order_notes(
+    mut notes: Vec<ExportNote>,
+    order: BookOrder,
+    custom_order: &[String],
+) -> Vec<ExportNote> {
+    let by_title = |a: &ExportNote, b: &ExportNote| {
+        a.title
+            .to_lowercase()
+            .cmp(&b.title.to_lowercase())
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    };
+
+    match order {
+        BookOrder::Title => notes.sort_by(by_title),
+        BookOrder::Custom => {
+            let positions: HashMap<&str, usize> = custom_order
+                .iter()
+                .enumerate()
+                .map(|(i, path)| (path.as_str(), i))
+                .collect();
+            notes.sort_by(|a, b| {
+                match (
+                    positions.get(a.relative_path.as_str()),
+                    positions.get(b.relative_path.as_str()),
+                ) {
+                    (Some(x), Some(y)) => x.cmp(y),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => by_title(a, b),
+                }
+            });
+        }
+    }
+
+    notes
+}
+
+// 笔记元信息行（分类、语言、标签）
+fn render_note_meta(note: &ExportNote) -> String {
+    let mut parts = vec![format!(
+        "<span class=\"category\">{}</span>",
+        escape_html(&note.category)
+    )];
+    for value in [&note.language, &note.framework, &note.kind]
+        .into_iter()
+        .flatten()
+    {
+        parts.push(format!(
+            "<span class=\"badge\">{}</span>",
+            escape_html(value)
+        ));
+    }
+    for tag in &note.tags {
+        parts.push(format!("<span class=\"tag\">#{}</span>", escape_html(tag)));
+    }
+    parts.join(" ")
+}
+
+fn collect_unresolved(notes: &[ExportNote], rendered: &[RenderedNote]) -> Vec<String> {
+    notes
+        .iter()
+        .zip(rendered)
+        .flat_map(|(note, r)| {
+            r.unresolved
+                .iter()
+                .map(move |target| format!("{} -> {}", note.title, target))
+        })
+        .collect()
+}
+
+// 检查输出文件路径
+fn prepare_output_file(output_path: &Path, workspace_root: &Path) -> Result<(), String> {
+    if output_path.as_os_str().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    if output_path.is_dir() {
+        return Err("导出路径是一个目录，请指定文件名".to_string());
+    }
+    if is_inside_workspace(output_path, workspace_root)? {
+        return Err("导出文件不能位于工作区内".to_string());
+    }
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    Ok(())
+}
+
+// ============= EPUB 3 =============
+
+// 章节文件名（位于 OEBPS/text/）
+fn chapter_file_name(index: usize) -> String {
+    format!("note-{:04}.xhtml", index + 1)
+}
+
+// EPUB 链接策略：笔记互链指向章节文件，图片打包进 OEBPS/images/
+struct EpubLinker<'a> {
+    // 工作区相对路径 -> (包内路径, 绝对路径)
+    images: &'a mut BTreeMap<String, (String, PathBuf)>,
+}
+
+impl ExportLinker for EpubLinker<'_> {
+    fn note_href(&mut self, target: usize, fragment: Option<&str>) -> String {
+        match fragment {
+            Some(fragment) => format!(
+                "{}#{}",
+                chapter_file_name(target),
+                urlencoding::encode(fragment)
+            ),
+            None => chapter_file_name(target),
+        }
+    }
+
+    fn asset_href(&mut self, workspace_relative: &str, absolute: &Path) -> Option<String> {
+        // 只打包图片，其他附件保留原链接（阅读器无法打开任意文件类型）
+        if !mime_for_path(absolute).starts_with("image/") {
+            return None;
+        }
+
+        let next_index = self.images.len() + 1;
+        let (package_path, _) = self
+            .images
+            .entry(workspace_relative.to_string())
+            .or_insert_with(|| {
+                let extension = absolute
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("bin")
+                    .to_lowercase();
+                (
+                    format!("images/img-{:04}.{}", next_index, extension),
+                    absolute.to_path_buf(),
+                )
+            });
+        Some(format!("../{}", package_path))
+    }
+
+    fn keep_raw_html(&self) -> bool {
+        false
+    }
+}
+
+fn render_chapter_xhtml(meta: &BookMeta, note: &ExportNote, rendered: &RenderedNote) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head>
+<meta charset="utf-8"/>
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="../style.css"/>
+</head>
+<body>
+<section epub:type="chapter">
+<h1 class="note-title">{title}</h1>
+<p class="note-meta">{meta}</p>
+{body}
+</section>
+</body>
+</html>
+"#,
+        lang = escape_html(&meta.language),
+        title = escape_html(&note.title),
+        meta = render_note_meta(note),
+        body = rendered.html,
+    )
+}
+
+// 导航文档：笔记为一级条目，笔记内的标题作为子条目
+fn render_nav_xhtml(meta: &BookMeta, notes: &[ExportNote], rendered: &[RenderedNote]) -> String {
+    let mut items = String::new();
+    for (index, note) in notes.iter().enumerate() {
+        let chapter = chapter_file_name(index);
+        items.push_str(&format!(
+            "<li><a href=\"text/{}\">{}</a>",
+            chapter,
+            escape_html(&note.title)
+        ));
+        items.push_str(&render_heading_list(
+            &rendered[index].headings,
+            "ol",
+            &|h| format!("text/{}#{}", chapter, h.anchor),
+        ));
+        items.push_str("</li>\n");
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head>
+<meta charset="utf-8"/>
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+<nav epub:type="toc" id="toc">
+<h1>{title}</h1>
+<ol>
+{items}</ol>
+</nav>
+</body>
+</html>
+"#,
+        lang = escape_html(&meta.language),
+        title = escape_html(&meta.title),
+        items = items,
+    )
+}
+
+fn render_package_opf(
+    meta: &BookMeta,
+    notes: &[ExportNote],
+    images: &BTreeMap<String, (String, PathBuf)>,
+) -> String {
+    let identifier = format!("urn:uuid:{}", uuid::Uuid::new_v4());
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+
+    let mut manifest = String::from(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n    <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine = String::new();
+    for index in 0..notes.len() {
+        manifest.push_str(&format!(
+            "    <item id=\"note-{n}\" href=\"text/{file}\" media-type=\"application/xhtml+xml\"/>\n",
+            n = index + 1,
+            file = chapter_file_name(index)
+        ));
+        spine.push_str(&format!("    <itemref idref=\"note-{}\"/>\n", index + 1));
+    }
+    for (index, (package_path, absolute)) in images.values().enumerate() {
+        manifest.push_str(&format!(
+            "    <item id=\"img-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+            index + 1,
+            escape_html(package_path),
+            mime_for_path(absolute)
+        ));
+    }
+
+    let creator = meta
+        .author
+        .as_deref()
+        .map(|author| format!("    <dc:creator>{}</dc:creator>\n", escape_html(author)))
+        .unwrap_or_default();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{lang}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>{lang}</dc:language>
+{creator}    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+        lang = escape_html(&meta.language),
+        identifier = identifier,
+        title = escape_html(&meta.title),
+        creator = creator,
+        modified = modified,
+        manifest = manifest,
+        spine = spine,
+    )
+}
+
+const EPUB_CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+fn add_epub_entry(
+    zip: &mut ZipWriter<fs::File>,
+    name: &str,
+    options: SimpleFileOptions,
+    data: &[u8],
+) -> Result<(), String> {
+    zip.start_file(name, options)
+        .map_err(|e| format!("写入 EPUB 条目失败 '{}': {}", name, e))?;
+    zip.write_all(data)
+        .map_err(|e| format!("写入 EPUB 条目失败 '{}': {}", name, e))
+}
+
+// 写出 EPUB 包（同步执行）
+fn write_epub(
+    output_path: &Path,
+    meta: &BookMeta,
+    notes: &[ExportNote],
+    workspace_root: &Path,
+) -> Result<BookExportResult, String> {
+    let lookup = NoteLookup::new(notes);
+    let mut images: BTreeMap<String, (String, PathBuf)> = BTreeMap::new();
+    let rendered: Vec<RenderedNote> = (0..notes.len())
+        .map(|index| {
+            let mut linker = EpubLinker {
+                images: &mut images,
+            };
+            render_note(notes, index, &lookup, workspace_root, &mut linker)
+        })
+        .collect();
+
+    let file = fs::File::create(output_path).map_err(|e| format!("创建导出文件失败: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // EPUB 规范要求 mimetype 为第一个条目且不压缩
+    add_epub_entry(&mut zip, "mimetype", stored, EPUB_MIMETYPE.as_bytes())?;
+    add_epub_entry(
+        &mut zip,
+        "META-INF/container.xml",
+        deflated,
+        EPUB_CONTAINER_XML.as_bytes(),
+    )?;
+    add_epub_entry(
+        &mut zip,
+        "OEBPS/content.opf",
+        deflated,
+        render_package_opf(meta, notes, &images).as_bytes(),
+    )?;
+    add_epub_entry(
+        &mut zip,
+        "OEBPS/nav.xhtml",
+        deflated,
+        render_nav_xhtml(meta, notes, &rendered).as_bytes(),
+    )?;
+    add_epub_entry(
+        &mut zip,
+        "OEBPS/style.css",
+        deflated,
+        BOOK_STYLE_CSS.as_bytes(),
+    )?;
+
+    for (index, note) in notes.iter().enumerate() {
+        add_epub_entry(
+            &mut zip,
+            &format!("OEBPS/text/{}", chapter_file_name(index)),
+            deflated,
+            render_chapter_xhtml(meta, note, &rendered[index]).as_bytes(),
+        )?;
+    }
+
+    let mut image_count = 0;
+    for (package_path, absolute) in images.values() {
+        match fs::read(absolute) {
+            Ok(data) => {
+                // 图片本身已压缩，无需再次压缩
+                add_epub_entry(&mut zip, &format!("OEBPS/{}", package_path), stored, &data)?;
+                image_count += 1;
+            }
+            Err(e) => warn!("⚠️ [导出] 读取图片失败 {}: {}", absolute.display(), e),
+        }
+    }
+
+    zip.finish()
+        .map_err(|e| format!("完成 EPUB 打包失败: {}", e))?;
+
+    Ok(BookExportResult {
+        output_path: output_path.to_string_lossy().to_string(),
+        note_count: notes.len(),
+        image_count,
+        unresolved_links: collect_unresolved(notes, &rendered),
+    })
+}
+
+// ============= 单文件 HTML =============
+
+// 单文件链接策略：笔记互链指向文档内锚点，图片转为 data URI
+struct InlineHtmlLinker<'a> {
+    current: usize,
+    // 工作区相对路径 -> data URI（同一图片只编码一次）
+    data_uris: &'a mut HashMap<String, String>,
+}
+
+fn note_section_id(index: usize) -> String {
+    format!("note-{}", index + 1)
+}
+
+impl ExportLinker for InlineHtmlLinker<'_> {
+    fn note_href(&mut self, target: usize, fragment: Option<&str>) -> String {
+        match fragment {
+            Some(fragment) => format!("#{}-{}", note_section_id(target), fragment),
+            None => format!("#{}", note_section_id(target)),
+        }
+    }
+
+    fn asset_href(&mut self, workspace_relative: &str, absolute: &Path) -> Option<String> {
+        let mime = mime_for_path(absolute);
+        if !mime.starts_with("image/") {
+            return None;
+        }
+        if let Some(uri) = self.data_uris.get(workspace_relative) {
+            return Some(uri.clone());
+        }
+
+        match fs::read(absolute) {
+            Ok(data) => {
+                let uri = format!("data:{};base64,{}", mime, STANDARD.encode(data));
+                self.data_uris
+                    .insert(workspace_relative.to_string(), uri.clone());
+                Some(uri)
+            }
+            Err(e) => {
+                warn!("⚠️ [导出] 读取图片失败 {}: {}", absolute.display(), e);
+                None
+            }
+        }
+    }
+
+    fn heading_id(&self, anchor: &str) -> String {
+        format!("{}-{}", note_section_id(self.current), anchor)
+    }
+}
+
+// 写出单文件 HTML（同步执行）
+fn write_single_html(
+    output_path: &Path,
+    meta: &BookMeta,
+    notes: &[ExportNote],
+    workspace_root: &Path,
+) -> Result<BookExportResult, String> {
+    let lookup = NoteLookup::new(notes);
+    let mut data_uris: HashMap<String, String> = HashMap::new();
+    let rendered: Vec<RenderedNote> = (0..notes.len())
+        .map(|index| {
+            let mut linker = InlineHtmlLinker {
+                current: index,
+                data_uris: &mut data_uris,
+            };
+            render_note(notes, index, &lookup, workspace_root, &mut linker)
+        })
+        .collect();
+
+    let mut toc = String::from("<ol>");
+    let mut sections = String::new();
+    for (index, note) in notes.iter().enumerate() {
+        let section_id = note_section_id(index);
+        toc.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            section_id,
+            escape_html(&note.title)
+        ));
+        toc.push_str(&render_heading_list(
+            &rendered[index].headings,
+            "ol",
+            &|h| format!("#{}-{}", section_id, h.anchor),
+        ));
+        toc.push_str("</li>");
+
+        sections.push_str(&format!(
+            "<section class=\"note\" id=\"{id}\"><h1 class=\"note-title\">{title}</h1><p class=\"note-meta\">{meta}</p>{body}</section>\n",
+            id = section_id,
+            title = escape_html(&note.title),
+            meta = render_note_meta(note),
+            body = rendered[index].html,
+        ));
+    }
+    toc.push_str("</ol>");
+
+    let author = meta
+        .author
+        .as_deref()
+        .map(|a| format!("<p class=\"book-author\">{}</p>", escape_html(a)))
+        .unwrap_or_default();
+
+    let document = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+{style}</style>
+</head>
+<body>
+<header class="book-header"><h1>{title}</h1>{author}</header>
+<nav class="toc"><h2>目录</h2>{toc}</nav>
+<main>
+{sections}</main>
+</body>
+</html>
+"#,
+        lang = escape_html(&meta.language),
+        title = escape_html(&meta.title),
+        style = BOOK_STYLE_CSS,
+        author = author,
+        toc = toc,
+        sections = sections,
+    );
+
+    fs::write(output_path, document).map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    Ok(BookExportResult {
+        output_path: output_path.to_string_lossy().to_string(),
+        note_count: notes.len(),
+        image_count: data_uris.len(),
+        unresolved_links: collect_unresolved(notes, &rendered),
+    })
+}
+
+// ============= 命令 =============
+
+#[derive(Clone, Copy)]
+enum BookFormat {
+    Epub,
+    SingleHtml,
+}
+
+// 读取保存的选集（优先使用应用状态中的 WorkspaceManager）
+fn load_export_selections(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+) -> Result<Vec<ExportSelection>, String> {
+    if let Some(workspace_state) = app_handle.try_state::<Arc<RwLock<WorkspaceManager>>>() {
+        let manager = workspace_state
+            .read()
+            .map_err(|e| format!("Failed to acquire read lock: {}", e))?;
+        Ok(manager.get_export_selections().to_vec())
+    } else {
+        let manager = WorkspaceManager::new(workspace_root.join(".snippets-code"))?;
+        Ok(manager.get_export_selections().to_vec())
+    }
+}
+
+async fn export_book(
+    app_handle: AppHandle,
+    options: BookExportOptions,
+    format: BookFormat,
+) -> Result<BookExportResult, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+
+    let selection_paths = match options.selection.as_deref().filter(|s| !s.is_empty()) {
+        Some(name) => Some(
+            load_export_selections(&app_handle, &workspace_root)?
+                .into_iter()
+                .find(|s| s.name == name)
+                .ok_or_else(|| format!("导出选集不存在: {}", name))?
+                .file_paths,
+        ),
+        None => None,
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let output_path = PathBuf::from(options.output_path.trim());
+        prepare_output_file(&output_path, &workspace_root)?;
+
+        let notes = collect_book_notes(&workspace_root, &options, selection_paths.as_deref())?;
+        if notes.is_empty() {
+            return Err("没有符合条件的笔记可导出".to_string());
+        }
+
+        let meta = BookMeta::from_options(&options);
+        let result = match format {
+            BookFormat::Epub => write_epub(&output_path, &meta, &notes, &workspace_root)?,
+            BookFormat::SingleHtml => {
+                write_single_html(&output_path, &meta, &notes, &workspace_root)?
+            }
+        };
+
+        info!(
+            "✅ [导出] 《{}》导出完成: {} 篇笔记, {} 张图片 -> {}",
+            meta.title,
+            result.note_count,
+            result.image_count,
+            output_path.display()
+        );
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("导出任务执行失败: {}", e))?
+}
+
+// 导出为 EPUB 3
+#[command]
+pub async fn export_epub(
+    app_handle: AppHandle,
+    options: BookExportOptions,
+) -> Result<BookExportResult, String> {
+    export_book(app_handle, options, BookFormat::Epub).await
+}
+
+// 导出为图片内联的单文件 HTML
+#[command]
+pub async fn export_single_html(
+    app_handle: AppHandle,
+    options: BookExportOptions,
+) -> Result<BookExportResult, String> {
+    export_book(app_handle, options, BookFormat::SingleHtml).await
+}
+
+// 获取保存的导出选集
+#[command]
+pub fn get_export_selections(app_handle: AppHandle) -> Result<Vec<ExportSelection>, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    load_export_selections(&app_handle, &workspace_root)
+}
+
+// 保存导出选集（同名覆盖）
+#[command]
+pub fn save_export_selection(
+    app_handle: AppHandle,
+    selection: ExportSelection,
+) -> Result<(), String> {
+    if selection.name.trim().is_empty() {
+        return Err("选集名称不能为空".to_string());
+    }
+
+    let workspace_state = app_handle
+        .try_state::<Arc<RwLock<WorkspaceManager>>>()
+        .ok_or("WorkspaceManager 未初始化")?;
+    let mut manager = workspace_state
+        .write()
+        .map_err(|e| format!("Failed to acquire write lock: {}", e))?;
+    manager.upsert_export_selection(selection);
+    manager.save()
+}
+
+// 删除导出选集
+#[command]
+pub fn delete_export_selection(app_handle: AppHandle, name: String) -> Result<bool, String> {
+    let workspace_state = app_handle
+        .try_state::<Arc<RwLock<WorkspaceManager>>>()
+        .ok_or("WorkspaceManager 未初始化")?;
+    let mut manager = workspace_state
+        .write()
+        .map_err(|e| format!("Failed to acquire write lock: {}", e))?;
+    let removed = manager.remove_export_selection(&name);
+    if removed {
+        manager.save()?;
+    }
+    Ok(removed)
+}
+
+const BOOK_STYLE_CSS: &str = r#"body { margin: 0 auto; max-width: 860px; padding: 0 1em; font-family: -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", serif; line-height: 1.65; color: #1f2328; }
+a { color: #0969da; text-decoration: none; }
+.book-header { padding: 2em 0 1em; border-bottom: 1px solid #d0d7de; }
+.book-author { color: #656d76; }
+.toc ol { padding-left: 1.2em; }
+.note { padding-top: 1.5em; border-top: 1px solid #d0d7de; margin-top: 2em; }
+.note-title { margin-bottom: 0.2em; }
+.note-meta { color: #656d76; font-size: 0.85em; }
+.badge, .tag { display: inline-block; padding: 0 0.4em; border: 1px solid #d0d7de; border-radius: 0.6em; font-size: 0.85em; }
+pre { background: #f6f8fa; padding: 0.8em 1em; border-radius: 6px; overflow: auto; white-space: pre-wrap; word-wrap: break-word; }
+code { font-family: "JetBrains Mono", Consolas, monospace; font-size: 0.9em; }
+img { max-width: 100%; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d7de; padding: 0.25em 0.6em; }
+blockquote { margin: 0; padding: 0 1em; color: #656d76; border-left: 4px solid #d0d7de; }
+.wikilink-missing { color: #cf222e; border-bottom: 1px dashed currentColor; }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn note(title: &str, relative_path: &str) -> ExportNote {
+        ExportNote {
+            id: relative_path.to_string(),
+            title: title.to_string(),
+            relative_path: relative_path.to_string(),
+            category: "Rust".to_string(),
+            tags: Vec::new(),
+            language: None,
+            framework: None,
+            kind: None,
+            created: String::new(),
+            modified: String::new(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn custom_order_puts_unlisted_notes_last_by_title() {
+        let notes = vec![
+            note("b", "Rust/b.md"),
+            note("C", "Rust/c.md"),
+            note("a", "Rust/a.md"),
+            note("z", "Rust/z.md"),
+        ];
+        let order = vec!["Rust/z.md".to_string(), "Rust/c.md".to_string()];
+
+        let titles: Vec<String> = order_notes(notes, BookOrder::Custom, &order)
+            .into_iter()
+            .map(|n| n.title)
+            .collect();
+
+        assert_eq!(titles, vec!["z", "C", "a", "b"]);
+    }
+
+    fn read_entry(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> String {
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    fn assert_well_formed(document: &str) {
+        for event in xml::reader::EventReader::from_str(document) {
+            event.unwrap();
+        }
+    }
+
+    #[test]
+    fn epub_package_is_structurally_valid() {
+        let base = std::env::temp_dir().join(format!("epub-test-{}", uuid::Uuid::new_v4()));
+        let workspace = base.join("workspace");
+        fs::create_dir_all(workspace.join("Rust")).unwrap();
+        fs::write(workspace.join("logo.png"), "png").unwrap();
+
+        let mut first = note("First", "Rust/first.md");
+        first.body = "# Intro\n\n## Details\n\n![[logo.png]] [[Second]]".to_string();
+        let mut second = note("Second", "Rust/second.md");
+        second.body = "Inline <b>html</b>".to_string();
+        let notes = vec![first, second];
+        let meta = BookMeta {
+            title: "Book".to_string(),
+            author: Some("Alice".to_string()),
+            language: "en".to_string(),
+        };
+        let output = base.join("book.epub");
+        let result = write_epub(&output, &meta, &notes, &workspace).unwrap();
+        assert_eq!(result.note_count, 2);
+        assert_eq!(result.image_count, 1);
+        assert!(result.unresolved_links.is_empty());
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&output).unwrap()).unwrap();
+        {
+            let mimetype = archive.by_index(0).unwrap();
+            assert_eq!(mimetype.name(), "mimetype");
+            assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        }
+        assert_eq!(read_entry(&mut archive, "mimetype"), EPUB_MIMETYPE);
+        assert!(read_entry(&mut archive, "META-INF/container.xml")
+            .contains("full-path=\"OEBPS/content.opf\""));
+
+        // 清单中的每个条目都在包内，spine 按笔记顺序引用章节
+        let opf = read_entry(&mut archive, "OEBPS/content.opf");
+        assert_well_formed(&opf);
+        assert!(opf.contains("properties=\"nav\""));
+        assert!(opf.contains("<dc:creator>Alice</dc:creator>"));
+        for href in opf.split("href=\"").skip(1) {
+            let href = href.split('"').next().unwrap();
+            assert!(
+                archive.by_name(&format!("OEBPS/{}", href)).is_ok(),
+                "{}",
+                href
+            );
+        }
+        let spine: Vec<&str> = opf
+            .split("<itemref idref=\"")
+            .skip(1)
+            .map(|rest| rest.split('"').next().unwrap())
+            .collect();
+        assert_eq!(spine, vec!["note-1", "note-2"]);
+
+        let nav = read_entry(&mut archive, "OEBPS/nav.xhtml");
+        assert_well_formed(&nav);
+        assert!(nav.contains("<nav epub:type=\"toc\" id=\"toc\">"));
+        assert!(nav.contains(
+            "<li><a href=\"text/note-0001.xhtml\">First</a><ol><li><a href=\"text/note-0001.xhtml#intro\">Intro</a><ol><li><a href=\"text/note-0001.xhtml#details\">Details</a>"
+        ));
+
+        let chapter = read_entry(&mut archive, "OEBPS/text/note-0001.xhtml");
+        assert_well_formed(&chapter);
+        assert!(chapter.contains("href=\"note-0002.xhtml\""));
+        assert!(chapter.contains("src=\"../images/img-0001.png\""));
+        assert_well_formed(&read_entry(&mut archive, "OEBPS/text/note-0002.xhtml"));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
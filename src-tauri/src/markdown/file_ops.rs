@@ -3,7 +3,7 @@
 use chrono::Local;
 use log::info;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 // 文件名生成器
 pub struct FileNameGenerator;
@@ -16,14 +16,28 @@ impl FileNameGenerator {
 
     // 从标题生成文件名
     pub fn generate_filename(title: &str) -> String {
+        Self::generate_filename_with_extension(title, Self::MARKDOWN_EXTENSION)
+    }
+
+    // 从标题生成指定扩展名的文件名（extension 含点，如 ".ts"）
+    pub fn generate_filename_with_extension(title: &str, extension: &str) -> String {
         let sanitized = Self::sanitize_filename(title);
-        let max_stem_bytes = Self::MAX_FILENAME_BYTES - Self::MARKDOWN_EXTENSION.len();
+        let max_stem_bytes = Self::MAX_FILENAME_BYTES.saturating_sub(extension.len());
         let truncated = Self::truncate_filename(&sanitized, max_stem_bytes);
-        format!("{}{}", truncated, Self::MARKDOWN_EXTENSION)
+        format!("{}{}", truncated, extension)
     }
 
     // 为重复文件名添加时间戳后缀（格式：-YYYYMMDDHHMMSS）
     pub fn resolve_conflict(base_path: &Path, filename: &str) -> String {
+        Self::resolve_conflict_with_extension(base_path, filename, Self::MARKDOWN_EXTENSION)
+    }
+
+    // 同 resolve_conflict，后缀插在指定扩展名之前
+    pub fn resolve_conflict_with_extension(
+        base_path: &Path,
+        filename: &str,
+        extension: &str,
+    ) -> String {
         let file_path = base_path.join(filename);
 
         // 如果文件不存在，直接返回原文件名
@@ -32,9 +46,7 @@ impl FileNameGenerator {
         }
 
         // 分离文件名和扩展名
-        let stem = filename
-            .strip_suffix(Self::MARKDOWN_EXTENSION)
-            .unwrap_or(filename);
+        let stem = filename.strip_suffix(extension).unwrap_or(filename);
 
         // 生成时间戳后缀：-YYYYMMDDHHMMSS
         let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
@@ -46,10 +58,10 @@ impl FileNameGenerator {
             } else {
                 format!("-{}-{}", timestamp, sequence)
             };
-            let max_stem_bytes = Self::MAX_FILENAME_BYTES
-                .saturating_sub(Self::MARKDOWN_EXTENSION.len() + suffix.len());
+            let max_stem_bytes =
+                Self::MAX_FILENAME_BYTES.saturating_sub(extension.len() + suffix.len());
             let candidate_stem = Self::truncate_filename(stem, max_stem_bytes);
-            let new_filename = format!("{}{}{}", candidate_stem, suffix, Self::MARKDOWN_EXTENSION);
+            let new_filename = format!("{}{}{}", candidate_stem, suffix, extension);
 
             if !base_path.join(&new_filename).exists() {
                 return new_filename;
@@ -181,6 +193,19 @@ pub fn get_relative_path(base_path: &Path, file_path: &Path) -> Result<String, S
         .map(|s| s.replace('\\', "/")) // 统一使用 / 作为路径分隔符
 }
 
+// 路径是否位于工作区内（不含工作区根目录本身）
+//
+// 前端传入的绝对路径可能带有 `..`，`root/../secret.md` 也能通过 starts_with 前缀检查，
+// 因此要求去掉前缀后只剩普通路径分量
+pub fn is_within_workspace(workspace_root: &Path, path: &Path) -> bool {
+    path.strip_prefix(workspace_root).is_ok_and(|relative| {
+        relative.components().next().is_some()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+    })
+}
+
 // 确保 .snippets-code 目录存在
 #[allow(dead_code)]
 pub fn ensure_config_dir(base_path: &Path) -> Result<PathBuf, String> {
@@ -192,3 +217,18 @@ pub fn ensure_config_dir(base_path: &Path) -> Result<PathBuf, String> {
 
     Ok(config_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_containment_rejects_parent_components() {
+        let inside = |path: &str| is_within_workspace(Path::new("/workspace"), Path::new(path));
+        assert!(inside("/workspace/notes/a.md"));
+        assert!(!inside("/workspace/../secret.md"));
+        assert!(!inside("/workspace/notes/../../secret.md"));
+        assert!(!inside("/workspace"));
+        assert!(!inside("/other/a.md"));
+    }
+}
@@ -281,7 +281,11 @@ impl FileSystemManager {
                     language: None,
                     framework: None,
                     kind: None,
+                    summary: None,
                     favorite: false,
+                    forked_from: None,
+                    chat_id: None,
+                    translated_from: None,
                 };
 
                 debug!("📖 读取文件（无 Front Matter）: {}", full_path.display());
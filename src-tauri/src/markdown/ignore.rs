@@ -0,0 +1,270 @@
+// 工作区忽略规则（.snippetsignore）
+//
+// 语法与 .gitignore 一致：`#` 注释、`!` 取反、末尾 `/` 仅匹配目录、含 `/` 的模式相对工作区根目录、
+// 不含 `/` 的模式匹配任意层级的文件/目录名、`**` 匹配任意层目录。规则按顺序求值，后面的规则覆盖前面的；
+// 与 git 相同，父目录被忽略后其中的文件无法再用 `!` 重新包含。
+//
+// 规则文件按修改时间缓存，编辑 .snippetsignore 后下一次查询即生效。
+
+use glob::{MatchOptions, Pattern};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+pub const IGNORE_FILE_NAME: &str = ".snippetsignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Pattern,
+    negate: bool,
+    dir_only: bool,
+    // 含 `/` 的模式匹配完整相对路径，否则只匹配最后一级名称
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn matches(&self, relative_path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            self.pattern.matches_with(relative_path, MATCH_OPTIONS)
+        } else {
+            let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+            self.pattern.matches_with(name, MATCH_OPTIONS)
+        }
+    }
+}
+
+// 解析后的忽略规则
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceIgnore {
+    rules: Vec<IgnoreRule>,
+}
+
+impl WorkspaceIgnore {
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negate, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            // `\#`、`\!` 表示字面量
+            let line = line
+                .strip_prefix('\\')
+                .filter(|rest| rest.starts_with('#') || rest.starts_with('!'))
+                .unwrap_or(line);
+
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let line = line.trim_start_matches('/');
+            if line.is_empty() {
+                continue;
+            }
+
+            match Pattern::new(line) {
+                Ok(pattern) => rules.push(IgnoreRule {
+                    pattern,
+                    negate,
+                    dir_only,
+                    anchored,
+                }),
+                Err(e) => warn!("⚠️ [忽略规则] 无效的模式 {}: {}", line, e),
+            }
+        }
+
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // 单个路径自身是否命中（最后匹配的规则生效）
+    fn matches_self(&self, relative_path: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.matches(relative_path, is_dir) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+
+    /// 判断相对路径（`/` 分隔）是否被忽略；任一父目录被忽略时其内容也被忽略
+    pub fn is_ignored(&self, relative_path: &str, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let relative_path = relative_path.trim_matches('/');
+
+        let mut end = 0;
+        while let Some(offset) = relative_path[end..].find('/') {
+            end += offset;
+            if self.matches_self(&relative_path[..end], true) {
+                return true;
+            }
+            end += 1;
+        }
+        self.matches_self(relative_path, is_dir)
+    }
+
+    /// 判断工作区内的绝对路径是否被忽略；工作区外的路径不受影响
+    ///
+    /// `is_dir` 由调用方提供：删除或重命名事件到达时路径已不存在，无法再从文件系统判断
+    pub fn is_path_ignored(&self, workspace_root: &Path, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        match path.strip_prefix(workspace_root) {
+            Ok(relative) => {
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                !relative.is_empty() && self.is_ignored(&relative, is_dir)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 供 `WalkDir::filter_entry` 使用：被忽略的目录整棵跳过
+    pub fn is_entry_ignored(&self, workspace_root: &Path, entry: &walkdir::DirEntry) -> bool {
+        if self.rules.is_empty() || entry.depth() == 0 {
+            return false;
+        }
+        match entry.path().strip_prefix(workspace_root) {
+            Ok(relative) => {
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                self.is_ignored(&relative, entry.file_type().is_dir())
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+struct CachedIgnore {
+    modified: Option<SystemTime>,
+    ignore: Arc<WorkspaceIgnore>,
+}
+
+static IGNORE_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedIgnore>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 是否为工作区根目录下的 .snippetsignore
+pub fn is_ignore_file(workspace_root: &Path, path: &Path) -> bool {
+    path.parent() == Some(workspace_root)
+        && path.file_name().and_then(|n| n.to_str()) == Some(IGNORE_FILE_NAME)
+}
+
+/// 加载工作区的忽略规则；文件不存在时返回空规则
+pub fn load_workspace_ignore(workspace_root: &Path) -> Arc<WorkspaceIgnore> {
+    let ignore_path = workspace_root.join(IGNORE_FILE_NAME);
+    let modified = std::fs::metadata(&ignore_path)
+        .and_then(|m| m.modified())
+        .ok();
+
+    if let Ok(cache) = IGNORE_CACHE.lock() {
+        if let Some(cached) = cache.get(workspace_root) {
+            if cached.modified == modified {
+                return cached.ignore.clone();
+            }
+        }
+    }
+
+    let ignore = Arc::new(match modified {
+        Some(_) => match std::fs::read_to_string(&ignore_path) {
+            Ok(content) => WorkspaceIgnore::parse(&content),
+            Err(e) => {
+                warn!("⚠️ [忽略规则] 读取 {} 失败: {}", ignore_path.display(), e);
+                WorkspaceIgnore::default()
+            }
+        },
+        None => WorkspaceIgnore::default(),
+    });
+    if !ignore.is_empty() {
+        debug!(
+            "📋 [忽略规则] 已加载 {} 条规则: {}",
+            ignore.rules.len(),
+            ignore_path.display()
+        );
+    }
+
+    if let Ok(mut cache) = IGNORE_CACHE.lock() {
+        cache.insert(
+            workspace_root.to_path_buf(),
+            CachedIgnore {
+                modified,
+                ignore: ignore.clone(),
+            },
+        );
+    }
+    ignore
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_gitignore_semantics() {
+        let ignore = WorkspaceIgnore::parse(
+            "# 草稿\n\
+             drafts/\n\
+             *.generated.md\n\
+             /vendor\n\
+             docs/**/api.md\n\
+             !keep.generated.md\n\
+             \\#literal.md\n",
+        );
+
+        assert!(ignore.is_ignored("drafts", true));
+        assert!(ignore.is_ignored("drafts/a.md", false));
+        assert!(ignore.is_ignored("notes/drafts/a.md", false));
+        assert!(!ignore.is_ignored("notes/drafts.md", false));
+
+        assert!(ignore.is_ignored("x/big.generated.md", false));
+        assert!(!ignore.is_ignored("x/keep.generated.md", false));
+
+        assert!(ignore.is_ignored("vendor/readme.md", false));
+        assert!(!ignore.is_ignored("notes/vendor/readme.md", false));
+
+        assert!(ignore.is_ignored("docs/api.md", false));
+        assert!(ignore.is_ignored("docs/v1/api.md", false));
+        assert!(!ignore.is_ignored("api.md", false));
+
+        assert!(ignore.is_ignored("#literal.md", false));
+    }
+
+    #[test]
+    fn cannot_reinclude_file_under_ignored_directory() {
+        let ignore = WorkspaceIgnore::parse("drafts/\n!drafts/keep.md\n");
+        assert!(ignore.is_ignored("drafts/keep.md", false));
+    }
+
+    #[test]
+    fn directory_rules_apply_to_paths_that_no_longer_exist() {
+        let ignore = WorkspaceIgnore::parse("drafts/\n");
+        let root = std::env::temp_dir().join("ignore-test-missing-workspace");
+        let removed_dir = root.join("notes").join("drafts");
+
+        assert!(!removed_dir.exists());
+        assert!(ignore.is_path_ignored(&root, &removed_dir, true));
+        assert!(!ignore.is_path_ignored(&root, &root.join("notes").join("drafts"), false));
+    }
+}
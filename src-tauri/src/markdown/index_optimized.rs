@@ -1,6 +1,11 @@
 // 优化的搜索索引实现
 // 集成中文分词、并行搜索和相关性评分
 
+use crate::markdown::code_snippets::{
+    code_snippet_info, is_code_snippet_file, is_code_snippet_path, load_code_extensions,
+};
+use crate::markdown::file_ops::get_relative_path;
+use crate::markdown::ignore::load_workspace_ignore;
 use crate::markdown::metadata::try_parse_front_matter;
 use crate::markdown::CacheManager;
 use fuzzy_matcher::skim::SkimMatcherV2;
@@ -18,6 +23,42 @@ use walkdir::WalkDir;
 
 type SharedIndex<T> = Arc<RwLock<T>>;
 type InvertedIndex = HashMap<String, Vec<(usize, usize)>>;
+// (title, tags, file_type, language, framework, kind, favorite, created, modified, body)
+type EntryFields = (
+    String,
+    Vec<String>,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    bool,
+    String,
+    String,
+    String,
+);
+
+// 代码片段文件：整个文件作为正文，内容元数据来自 cache.json
+fn code_entry_fields(
+    file_path: &Path,
+    workspace_root: &Path,
+    cache_manager: &CacheManager,
+    raw_content: String,
+) -> EntryFields {
+    let relative_path = get_relative_path(workspace_root, file_path).unwrap_or_default();
+    let info = code_snippet_info(file_path, &relative_path, cache_manager);
+    (
+        info.title,
+        info.tags,
+        "code".to_string(),
+        info.language,
+        info.framework,
+        info.kind,
+        info.favorite,
+        info.created,
+        info.modified,
+        raw_content,
+    )
+}
 
 // 搜索索引项
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +81,12 @@ pub struct IndexEntry {
     pub framework: Option<String>,
     // 片段语义类型
     pub kind: Option<String>,
+    // 创建时间（frontmatter，ISO 8601；无 frontmatter 时为空）
+    #[serde(default)]
+    pub created: String,
+    // 修改时间（frontmatter，ISO 8601；无 frontmatter 时为空）
+    #[serde(default)]
+    pub modified: String,
     // 内容摘要
     pub content_summary: String,
     // 完整内容（用于搜索）
@@ -187,7 +234,7 @@ impl OptimizedIndexManager {
     // 构建索引
     pub async fn build_index(
         workspace_root: &Path,
-        _cache_manager: &CacheManager,
+        cache_manager: &CacheManager,
     ) -> Result<Self, String> {
         let manager = Self::new();
 
@@ -196,16 +243,23 @@ impl OptimizedIndexManager {
         let mut tag_index: HashMap<String, Vec<usize>> = HashMap::new();
         let mut favorite_index = Vec::new();
         let mut inverted_index: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        let ignore = load_workspace_ignore(workspace_root);
+        let code_extensions = load_code_extensions(workspace_root);
 
         for entry in WalkDir::new(workspace_root)
             .follow_links(true)
             .into_iter()
+            .filter_entry(|e| !ignore.is_entry_ignored(workspace_root, e))
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
 
-            // 只处理 .md 文件
-            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("md") {
+            // 只处理 .md 文件和启用的代码片段文件
+            if !path.is_file() {
+                continue;
+            }
+            let is_code = is_code_snippet_path(path, &code_extensions);
+            if !is_code && path.extension().and_then(|s| s.to_str()) != Some("md") {
                 continue;
             }
 
@@ -219,7 +273,20 @@ impl OptimizedIndexManager {
             };
 
             // 从 Frontmatter 读取所有内容元数据（唯一数据源）
-            let (title, tags, file_type, language, framework, kind, favorite, body) = {
+            let (
+                title,
+                tags,
+                file_type,
+                language,
+                framework,
+                kind,
+                favorite,
+                created,
+                modified,
+                body,
+            ) = if is_code {
+                code_entry_fields(path, workspace_root, cache_manager, raw_content)
+            } else {
                 let (fm_opt, body) = try_parse_front_matter(&raw_content);
                 if let Some(fm) = fm_opt {
                     (
@@ -230,6 +297,8 @@ impl OptimizedIndexManager {
                         fm.framework,
                         fm.kind,
                         fm.favorite,
+                        fm.created,
+                        fm.modified,
                         body,
                     )
                 } else {
@@ -247,6 +316,8 @@ impl OptimizedIndexManager {
                         None,
                         None,
                         false,
+                        String::new(),
+                        String::new(),
                         raw_content.clone(),
                     )
                 }
@@ -271,6 +342,8 @@ impl OptimizedIndexManager {
                 language,
                 framework,
                 kind,
+                created,
+                modified,
                 content_summary,
                 full_content: body.clone(),
             };
@@ -550,45 +623,57 @@ impl OptimizedIndexManager {
     pub fn update_entry(
         &self,
         file_path: &Path,
-        _workspace_root: &Path,
-        _cache_manager: &CacheManager,
+        workspace_root: &Path,
+        cache_manager: &CacheManager,
     ) -> Result<(), String> {
+        // 被 .snippetsignore 排除的文件不进入索引（规则可能刚刚变化，顺带移除旧项）
+        if load_workspace_ignore(workspace_root).is_path_ignored(workspace_root, file_path, false) {
+            return self.remove_entry(file_path);
+        }
+
         // 读取文件内容
         let raw_content =
             fs::read_to_string(file_path).map_err(|e| format!("读取文件失败: {}", e))?;
 
         // 从 Frontmatter 读取所有内容元数据（唯一数据源）
-        let (title, tags, file_type, language, framework, kind, favorite, body) = {
-            let (fm_opt, body) = try_parse_front_matter(&raw_content);
-            if let Some(fm) = fm_opt {
-                (
-                    fm.title,
-                    fm.tags,
-                    fm.fragment_type,
-                    fm.language,
-                    fm.framework,
-                    fm.kind,
-                    fm.favorite,
-                    body,
-                )
+        let (title, tags, file_type, language, framework, kind, favorite, created, modified, body) =
+            if is_code_snippet_file(workspace_root, file_path) {
+                code_entry_fields(file_path, workspace_root, cache_manager, raw_content)
             } else {
-                let file_stem = file_path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Untitled")
-                    .to_string();
-                (
-                    file_stem,
-                    vec![],
-                    "note".to_string(),
-                    None,
-                    None,
-                    None,
-                    false,
-                    raw_content.clone(),
-                )
-            }
-        };
+                let (fm_opt, body) = try_parse_front_matter(&raw_content);
+                if let Some(fm) = fm_opt {
+                    (
+                        fm.title,
+                        fm.tags,
+                        fm.fragment_type,
+                        fm.language,
+                        fm.framework,
+                        fm.kind,
+                        fm.favorite,
+                        fm.created,
+                        fm.modified,
+                        body,
+                    )
+                } else {
+                    let file_stem = file_path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("Untitled")
+                        .to_string();
+                    (
+                        file_stem,
+                        vec![],
+                        "note".to_string(),
+                        None,
+                        None,
+                        None,
+                        false,
+                        String::new(),
+                        String::new(),
+                        raw_content.clone(),
+                    )
+                }
+            };
 
         // 生成内容摘要（安全地处理多字节字符）
         let content_summary = if body.chars().count() > 200 {
@@ -609,6 +694,8 @@ impl OptimizedIndexManager {
             language,
             framework,
             kind,
+            created,
+            modified,
             content_summary,
             full_content: body.clone(),
         };
@@ -36,6 +36,9 @@ pub struct CacheConfig {
     pub version: String,
     pub files: HashMap<String, FileMetadata>,
     pub categories: HashMap<String, CategoryMetadata>,
+    // 旧版本存放在这里的代码片段元数据，加载时迁移到 snippets.json，不再写回
+    #[serde(default, skip_serializing)]
+    pub snippets: HashMap<String, CodeSnippetMetadata>,
 }
 
 impl Default for CacheConfig {
@@ -44,6 +47,7 @@ impl Default for CacheConfig {
             version: "1.0.0".to_string(),
             files: HashMap::new(),
             categories: HashMap::new(),
+            snippets: HashMap::new(),
         }
     }
 }
@@ -65,6 +69,28 @@ pub struct FileMetadata {
     pub hash: Option<String>,
 }
 
+// 代码文件片段的元数据（存储在 snippets.json，键为文件相对路径）
+//
+// .ts/.vue/.css 等代码文件不能写入 Frontmatter，tags/favorite/kind 等存放在随工作区同步的
+// snippets.json 中；id 和时间戳仍在 cache.json 的 files 中。language 为空时按扩展名推断。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CodeSnippetMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
 // 分类元数据（存储在 cache.json）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategoryMetadata {
@@ -86,6 +112,21 @@ pub struct WorkspaceSettings {
     pub sync_enabled: bool,
     // 附件配置
     pub attachment: AttachmentSettings,
+    // 保存的导出选集（EPUB / 单文件 HTML）
+    #[serde(default)]
+    pub export_selections: Vec<ExportSelection>,
+    // 作为片段收录的代码文件扩展名（不含点，小写），为空时只收录 Markdown
+    #[serde(default)]
+    pub code_snippet_extensions: Vec<String>,
+}
+
+// 导出选集：一组按用户顺序排列的笔记
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSelection {
+    pub name: String,
+    // 相对工作区根目录的笔记路径（统一使用 /），顺序即导出顺序
+    pub file_paths: Vec<String>,
 }
 
 // 附件设置
@@ -152,9 +193,52 @@ pub struct FrontMatter {
     // 片段语义类型（如 component、hook、style、api、regex、error-fix）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kind: Option<String>,
+    // 一句话摘要（由用户填写或采纳 AI 建议）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     // 是否收藏
     #[serde(default)]
     pub favorite: bool,
+    // 从只读库复制而来时记录的来源
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<ForkSource>,
+    // 由本地 AI 对话导出时记录的对话 id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    // 整篇翻译生成的笔记记录原文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translated_from: Option<TranslationSource>,
+}
+
+// 笔记来源（从库复制到个人工作区，或由片段操作派生新版本时写入 frontmatter）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ForkSource {
+    // 库名称（派生自本工作区的笔记时为空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub library: String,
+    // 库的远程地址（不含 token）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    // 笔记在库或工作区中的相对路径
+    pub path: String,
+    // 复制时库所在的提交
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    // 复制时间 (ISO 8601)
+    pub forked_at: String,
+}
+
+// 译文笔记的原文（整篇翻译时写入 frontmatter）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranslationSource {
+    // 原文在工作区中的相对路径
+    pub path: String,
+    // 原文标题（译文正文开头的 wikilink 指向它）
+    pub title: String,
+    pub from: String,
+    pub to: String,
+    // 翻译时间 (ISO 8601)
+    pub translated_at: String,
 }
 
 /// 将 FrontMatter 序列化为 YAML 字符串（用于写入文件）
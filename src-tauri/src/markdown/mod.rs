@@ -1,14 +1,22 @@
 // Markdown 文件系统模块
 
 pub mod cache_manager;
+pub mod classifier; // 语义元数据推断
+pub mod code_snippets; // 纯代码文件片段
 pub mod commands;
+pub mod duplicates; // 近似重复检测与合并
+pub mod export; // 静态站点导出与共享渲染
+pub mod export_book; // EPUB / 单文件 HTML 导出
 pub mod file_ops;
 pub mod file_system_manager;
+pub mod ignore; // 工作区忽略规则（.snippetsignore）
 pub mod index_optimized; // 优化的搜索索引
 pub mod metadata;
+pub mod stats; // 工作区统计
 pub mod watcher;
 pub mod workspace;
 pub mod workspace_manager;
+pub mod workspace_registry; // 多工作区注册与联合搜索
 
 pub use metadata::*;
 pub use watcher::FileWatcher;
@@ -18,3 +26,4 @@ pub use index_optimized::OptimizedIndexManager as IndexManager;
 pub use cache_manager::CacheManager;
 pub use commands::*;
 pub use workspace_manager::WorkspaceManager;
+pub use workspace_registry::WorkspaceIndexRegistry;
@@ -0,0 +1,652 @@
+// 工作区统计模块
+// 基于搜索索引（OptimizedIndexManager）和 cache.json（CacheManager）汇总知识库的覆盖情况，
+// 只读取工作区文件，不依赖 git 同步
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::code_snippets::{is_snippet_path, load_code_extensions};
+use crate::markdown::export::{
+    is_external_url, resolve_workspace_relative, split_link_fragment, NoteLookup,
+};
+use crate::markdown::file_ops::get_relative_path;
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::index_optimized::IndexEntry;
+use crate::markdown::{CacheManager, IndexManager};
+use crate::text_utils::is_cjk;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use log::info;
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use tauri::{command, AppHandle, State};
+use walkdir::WalkDir;
+
+// 排行类列表的默认长度
+const DEFAULT_TOP_LIMIT: usize = 20;
+
+// 计数项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountItem {
+    pub name: String,
+    pub count: usize,
+}
+
+// 正文规模统计
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextTotals {
+    // 词数：拉丁字母/数字按连续片段计，中日韩字符每字计一词（不含代码块）
+    pub word_count: usize,
+    // 非空白字符数（不含代码块）
+    pub char_count: usize,
+    pub code_block_count: usize,
+    pub code_line_count: usize,
+}
+
+// 每周新增与修改数量（周一为一周的开始）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyGrowth {
+    pub week_start: String,
+    pub created: usize,
+    pub modified: usize,
+}
+
+// 笔记引用
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRef {
+    pub title: String,
+    pub relative_path: String,
+}
+
+// 被链接最多的笔记
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedNote {
+    pub title: String,
+    pub relative_path: String,
+    // 链接到该笔记的其他笔记数量
+    pub incoming: usize,
+}
+
+// 附件统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentStat {
+    pub relative_path: String,
+    pub size: u64,
+    // 引用该附件的笔记数量（0 表示未被引用）
+    pub references: usize,
+}
+
+// 覆盖缺口：缺少元数据的笔记数量和没有笔记的分类
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageGaps {
+    pub without_language: usize,
+    pub without_framework: usize,
+    pub without_kind: usize,
+    pub untagged: usize,
+    pub empty_categories: Vec<String>,
+}
+
+// 工作区统计结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStatistics {
+    pub note_count: usize,
+    // type 为 code 的片段数量
+    pub code_snippet_count: usize,
+    pub favorite_count: usize,
+    pub by_language: Vec<CountItem>,
+    pub by_framework: Vec<CountItem>,
+    pub by_kind: Vec<CountItem>,
+    pub by_tag: Vec<CountItem>,
+    pub by_category: Vec<CountItem>,
+    pub totals: TextTotals,
+    pub weekly_growth: Vec<WeeklyGrowth>,
+    pub most_linked: Vec<LinkedNote>,
+    // 既没有入链也没有出链的笔记
+    pub orphan_notes: Vec<NoteRef>,
+    pub attachment_count: usize,
+    pub attachment_total_size: u64,
+    pub largest_attachments: Vec<AttachmentStat>,
+    pub gaps: CoverageGaps,
+    pub generated_at: String,
+}
+
+// 单篇笔记正文的分析结果
+#[derive(Default)]
+struct BodyAnalysis {
+    totals: TextTotals,
+    // wikilink 目标标题
+    wikilinks: Vec<String>,
+    // 相对链接解析出的工作区相对路径（笔记或附件）
+    local_links: Vec<String>,
+}
+
+// 统计文本的词数和非空白字符数
+fn count_words(text: &str) -> (usize, usize) {
+    let mut words = 0;
+    let mut chars = 0;
+    let mut in_word = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            in_word = false;
+            continue;
+        }
+        chars += 1;
+
+        if is_cjk(c) {
+            words += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() || c == '_' || (in_word && (c == '\'' || c == '-')) {
+            if !in_word {
+                words += 1;
+                in_word = true;
+            }
+        } else {
+            in_word = false;
+        }
+    }
+
+    (words, chars)
+}
+
+// 分析正文：规模统计和链接目标
+fn analyze_body(relative_path: &str, body: &str) -> BodyAnalysis {
+    let mut analysis = BodyAnalysis::default();
+    let mut in_code_block = false;
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_WIKILINKS;
+
+    for event in Parser::new_ext(body, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                analysis.totals.code_block_count += 1;
+            }
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(text) if in_code_block => {
+                analysis.totals.code_line_count +=
+                    text.lines().filter(|l| !l.trim().is_empty()).count();
+            }
+            Event::Text(text) | Event::Code(text) => {
+                let (words, chars) = count_words(&text);
+                analysis.totals.word_count += words;
+                analysis.totals.char_count += chars;
+            }
+            Event::Start(Tag::Link {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            })
+            | Event::Start(Tag::Image {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => {
+                let (target, _) = split_link_fragment(&dest_url);
+                let looks_like_file = Path::new(target)
+                    .extension()
+                    .is_some_and(|ext| !ext.eq_ignore_ascii_case("md"));
+                if looks_like_file {
+                    // `![[image.png]]` 引用附件：可能相对工作区根目录，也可能相对笔记目录，两种都记录
+                    analysis
+                        .local_links
+                        .push(target.trim_start_matches('/').to_string());
+                    if let Some(resolved) = resolve_workspace_relative(relative_path, target) {
+                        analysis.local_links.push(resolved);
+                    }
+                } else {
+                    analysis.wikilinks.push(target.to_string());
+                }
+            }
+            Event::Start(Tag::Link { dest_url, .. })
+            | Event::Start(Tag::Image { dest_url, .. })
+                if !is_external_url(&dest_url) =>
+            {
+                let (path_part, _) = split_link_fragment(&dest_url);
+                if let Some(resolved) = resolve_workspace_relative(relative_path, path_part) {
+                    analysis.local_links.push(resolved);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    analysis
+}
+
+// 未写入的时间戳（0）或手填错误的年份会把增长曲线拉出成千上万个空白周，
+// 只接受 1970 年之后、不晚于明天（容忍时区差）的日期
+fn is_plausible_date(date: NaiveDate) -> bool {
+    date > DateTime::UNIX_EPOCH.date_naive() && date <= Utc::now().date_naive() + Duration::days(1)
+}
+
+// 解析 frontmatter 中的 ISO 8601 时间为日期
+fn parse_frontmatter_date(value: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|dt| dt.date_naive())
+        .filter(|date| is_plausible_date(*date))
+}
+
+fn millis_to_date(millis: i64) -> Option<NaiveDate> {
+    if millis <= 0 {
+        return None;
+    }
+    DateTime::<Utc>::from_timestamp_millis(millis)
+        .map(|dt| dt.date_naive())
+        .filter(|date| is_plausible_date(*date))
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+// 按周汇总创建/修改数量，空白周补零以便绘制连续曲线；范围取有效日期的最早和最晚一周
+fn weekly_growth(created: &[NaiveDate], modified: &[NaiveDate]) -> Vec<WeeklyGrowth> {
+    let mut buckets: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+    for date in created.iter().filter(|date| is_plausible_date(**date)) {
+        buckets.entry(week_start(*date)).or_default().0 += 1;
+    }
+    for date in modified.iter().filter(|date| is_plausible_date(**date)) {
+        buckets.entry(week_start(*date)).or_default().1 += 1;
+    }
+
+    let (Some(first), Some(last)) = (
+        buckets.keys().next().copied(),
+        buckets.keys().next_back().copied(),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut growth = Vec::new();
+    let mut week = first;
+    while week <= last {
+        let (created, modified) = buckets.get(&week).copied().unwrap_or_default();
+        growth.push(WeeklyGrowth {
+            week_start: week.format("%Y-%m-%d").to_string(),
+            created,
+            modified,
+        });
+        week += Duration::weeks(1);
+    }
+    growth
+}
+
+// 计数表转为按数量降序、名称升序排列的列表
+fn sorted_counts(counts: HashMap<String, usize>) -> Vec<CountItem> {
+    let mut items: Vec<CountItem> = counts
+        .into_iter()
+        .map(|(name, count)| CountItem { name, count })
+        .collect();
+    items.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    items
+}
+
+// 元数据值统一为小写（language/framework/kind 是标识符）
+fn normalized(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_lowercase)
+}
+
+// 扫描工作区中的附件（Markdown 和启用的代码片段以外的文件，跳过隐藏目录和 .snippetsignore 排除的路径）
+fn scan_attachments(workspace_root: &Path) -> Vec<(String, u64)> {
+    let ignore = load_workspace_ignore(workspace_root);
+    let code_extensions = load_code_extensions(workspace_root);
+    WalkDir::new(workspace_root)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || (!e.file_name().to_string_lossy().starts_with('.')
+                    && !ignore.is_entry_ignored(workspace_root, e))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| !is_snippet_path(e.path(), &code_extensions))
+        .filter_map(|e| {
+            let relative = get_relative_path(workspace_root, e.path()).ok()?;
+            let size = e.metadata().ok()?.len();
+            Some((relative, size))
+        })
+        .collect()
+}
+
+// 汇总统计（同步执行，由命令放入阻塞线程池）
+//
+// # Arguments
+// * `entries` - 搜索索引中的全部笔记
+// * `cache` - cache.json 快照（提供分类列表和无 frontmatter 笔记的时间）
+// * `limit` - 排行类列表的长度
+fn compute_statistics(
+    workspace_root: &Path,
+    entries: &[IndexEntry],
+    cache: &CacheManager,
+    limit: usize,
+) -> WorkspaceStatistics {
+    let relative_paths: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            get_relative_path(workspace_root, &e.file_path)
+                .unwrap_or_else(|_| e.file_path.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    let lookup = NoteLookup::from_titles(
+        entries
+            .iter()
+            .zip(&relative_paths)
+            .map(|(e, p)| (e.title.as_str(), p.as_str())),
+    );
+
+    let mut by_language = HashMap::new();
+    let mut by_framework = HashMap::new();
+    let mut by_kind = HashMap::new();
+    let mut by_tag = HashMap::new();
+    let mut by_category: HashMap<String, usize> = HashMap::new();
+    let mut totals = TextTotals::default();
+    let mut gaps = CoverageGaps::default();
+    let mut created_dates = Vec::new();
+    let mut modified_dates = Vec::new();
+    let mut incoming: Vec<HashSet<usize>> = vec![HashSet::new(); entries.len()];
+    let mut has_outgoing = vec![false; entries.len()];
+    let mut attachment_refs: HashMap<String, HashSet<usize>> = HashMap::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let relative_path = &relative_paths[index];
+
+        for (value, counts, missing) in [
+            (
+                &entry.language,
+                &mut by_language,
+                &mut gaps.without_language,
+            ),
+            (
+                &entry.framework,
+                &mut by_framework,
+                &mut gaps.without_framework,
+            ),
+            (&entry.kind, &mut by_kind, &mut gaps.without_kind),
+        ] {
+            match normalized(value) {
+                Some(value) => *counts.entry(value).or_insert(0) += 1,
+                None => *missing += 1,
+            }
+        }
+        if entry.tags.is_empty() {
+            gaps.untagged += 1;
+        }
+        for tag in &entry.tags {
+            *by_tag.entry(tag.clone()).or_insert(0) += 1;
+        }
+        *by_category
+            .entry(cache.extract_category_from_path(relative_path))
+            .or_insert(0) += 1;
+
+        // 时间以 frontmatter 为准；无 frontmatter 的笔记回退到 cache.json 记录
+        let cached = cache.get_file_metadata(relative_path);
+        if let Some(date) = parse_frontmatter_date(&entry.created)
+            .or_else(|| cached.and_then(|m| millis_to_date(m.created)))
+        {
+            created_dates.push(date);
+        }
+        if let Some(date) = parse_frontmatter_date(&entry.modified)
+            .or_else(|| cached.and_then(|m| millis_to_date(m.modified)))
+        {
+            modified_dates.push(date);
+        }
+
+        let analysis = analyze_body(relative_path, &entry.full_content);
+        totals.word_count += analysis.totals.word_count;
+        totals.char_count += analysis.totals.char_count;
+        totals.code_block_count += analysis.totals.code_block_count;
+        totals.code_line_count += analysis.totals.code_line_count;
+
+        let mut targets: Vec<usize> = analysis
+            .wikilinks
+            .iter()
+            .filter_map(|title| lookup.find_by_title(title))
+            .collect();
+        for path in &analysis.local_links {
+            if path.to_lowercase().ends_with(".md") {
+                targets.extend(lookup.find_by_path(path));
+            } else {
+                attachment_refs
+                    .entry(path.to_lowercase())
+                    .or_default()
+                    .insert(index);
+            }
+        }
+        for target in targets {
+            if target != index {
+                incoming[target].insert(index);
+                has_outgoing[index] = true;
+            }
+        }
+    }
+
+    let mut most_linked: Vec<LinkedNote> = incoming
+        .iter()
+        .enumerate()
+        .filter(|(_, sources)| !sources.is_empty())
+        .map(|(index, sources)| LinkedNote {
+            title: entries[index].title.clone(),
+            relative_path: relative_paths[index].clone(),
+            incoming: sources.len(),
+        })
+        .collect();
+    most_linked.sort_by(|a, b| {
+        b.incoming
+            .cmp(&a.incoming)
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    most_linked.truncate(limit);
+
+    let mut orphan_notes: Vec<NoteRef> = (0..entries.len())
+        .filter(|index| incoming[*index].is_empty() && !has_outgoing[*index])
+        .map(|index| NoteRef {
+            title: entries[index].title.clone(),
+            relative_path: relative_paths[index].clone(),
+        })
+        .collect();
+    orphan_notes.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    let attachments = scan_attachments(workspace_root);
+    let attachment_total_size = attachments.iter().map(|(_, size)| size).sum();
+    let mut largest_attachments: Vec<AttachmentStat> = attachments
+        .iter()
+        .map(|(relative_path, size)| AttachmentStat {
+            relative_path: relative_path.clone(),
+            size: *size,
+            references: attachment_refs
+                .get(&relative_path.to_lowercase())
+                .map_or(0, |sources| sources.len()),
+        })
+        .collect();
+    largest_attachments.sort_by(|a, b| {
+        b.size
+            .cmp(&a.size)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    largest_attachments.truncate(limit);
+
+    gaps.empty_categories = cache
+        .get_all_categories()
+        .iter()
+        .filter(|(name, meta)| !meta.is_system && !by_category.contains_key(*name))
+        .map(|(name, _)| name.clone())
+        .collect();
+    gaps.empty_categories.sort();
+
+    WorkspaceStatistics {
+        note_count: entries.len(),
+        code_snippet_count: entries.iter().filter(|e| e.file_type == "code").count(),
+        favorite_count: entries.iter().filter(|e| e.favorite).count(),
+        by_language: sorted_counts(by_language),
+        by_framework: sorted_counts(by_framework),
+        by_kind: sorted_counts(by_kind),
+        by_tag: sorted_counts(by_tag),
+        by_category: sorted_counts(by_category),
+        totals,
+        weekly_growth: weekly_growth(&created_dates, &modified_dates),
+        most_linked,
+        orphan_notes,
+        attachment_count: attachments.len(),
+        attachment_total_size,
+        largest_attachments,
+        gaps,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+// 获取工作区统计（语言/框架/类型/标签/分类分布、规模、增长、链接和附件）
+#[command]
+pub async fn get_workspace_statistics(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    limit: Option<usize>,
+) -> Result<WorkspaceStatistics, String> {
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+
+    let cache = {
+        let cache_lock = cache_manager
+            .read()
+            .map_err(|e| format!("获取缓存管理器锁失败: {}", e))?;
+        cache_lock.clone()
+    };
+
+    let indexed = {
+        let index_lock = index_manager
+            .read()
+            .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+        index_lock.as_ref().map(|manager| manager.get_all_entries())
+    };
+
+    // 索引尚未就绪时临时构建一份，不写回应用状态
+    let entries = match indexed {
+        Some(entries) => entries,
+        None => IndexManager::build_index(&workspace_root, &cache)
+            .await?
+            .get_all_entries(),
+    };
+
+    let limit = limit.filter(|l| *l > 0).unwrap_or(DEFAULT_TOP_LIMIT);
+    let statistics = tokio::task::spawn_blocking(move || {
+        compute_statistics(&workspace_root, &entries, &cache, limit)
+    })
+    .await
+    .map_err(|e| format!("统计任务执行失败: {}", e))?;
+
+    info!(
+        "📊 [统计] {} 篇笔记, {} 个附件",
+        statistics.note_count, statistics.attachment_count
+    );
+    Ok(statistics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn counts_cjk_characters_and_latin_words() {
+        assert_eq!(count_words("Hello, world"), (2, 11));
+        assert_eq!(count_words("使用 useState 管理状态"), (7, 14));
+        assert_eq!(count_words("don't re-render"), (2, 14));
+    }
+
+    #[test]
+    fn attachments_skip_hidden_and_ignored_paths() {
+        let root = std::env::temp_dir().join(format!("stats-test-{}", uuid::Uuid::new_v4()));
+        for dir in ["assets", "drafts", ".git"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("note.md"), "# Note").unwrap();
+        fs::write(root.join("assets/logo.png"), "png").unwrap();
+        fs::write(root.join("drafts/sketch.png"), "png").unwrap();
+        fs::write(root.join(".git/config"), "").unwrap();
+        fs::write(root.join(".snippetsignore"), "drafts/\n").unwrap();
+
+        let mut attachments: Vec<String> = scan_attachments(&root)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        attachments.sort();
+        assert_eq!(attachments, vec!["assets/logo.png"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn fills_empty_weeks_in_growth() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let growth = weekly_growth(
+            &[date("2024-01-03"), date("2024-01-04")],
+            &[date("2024-01-17")],
+        );
+
+        assert_eq!(
+            growth,
+            vec![
+                WeeklyGrowth {
+                    week_start: "2024-01-01".to_string(),
+                    created: 2,
+                    modified: 0
+                },
+                WeeklyGrowth {
+                    week_start: "2024-01-08".to_string(),
+                    created: 0,
+                    modified: 0
+                },
+                WeeklyGrowth {
+                    week_start: "2024-01-15".to_string(),
+                    created: 0,
+                    modified: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn growth_skips_zero_and_far_future_timestamps() {
+        assert_eq!(millis_to_date(0), None);
+        assert_eq!(millis_to_date(-1), None);
+        assert_eq!(parse_frontmatter_date("9999-01-01T00:00:00Z"), None);
+
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let growth = weekly_growth(
+            &[date("1970-01-01"), date("2024-01-03")],
+            &[date("2024-01-10"), date("9999-12-31")],
+        );
+
+        assert_eq!(
+            growth,
+            vec![
+                WeeklyGrowth {
+                    week_start: "2024-01-01".to_string(),
+                    created: 1,
+                    modified: 0
+                },
+                WeeklyGrowth {
+                    week_start: "2024-01-08".to_string(),
+                    created: 0,
+                    modified: 1
+                },
+            ]
+        );
+    }
+}
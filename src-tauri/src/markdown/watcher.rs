@@ -10,6 +10,8 @@ use tauri::{AppHandle, Emitter, Manager};
 use crate::attachment::{
     cleanup_attachments_for_deleted_files, sync_attachments_for_renamed_files,
 };
+use crate::markdown::code_snippets::{is_code_snippet_path, is_snippet_path, load_code_extensions};
+use crate::markdown::ignore::{is_ignore_file, load_workspace_ignore};
 
 // ── 发往前端的事件负载 ────────────────────────────────────────────────────────
 
@@ -119,10 +121,20 @@ impl FileWatcher {
             let mut last_event_time = Instant::now();
             let mut total_events_received: usize = 0;
             let mut total_batches_sent: usize = 0;
+            // .snippetsignore 变化后需要重新扫描，和其他事件一起防抖
+            let mut ignore_rules_changed = false;
 
             loop {
                 match rx.recv_timeout(Duration::from_millis(100)) {
                     Ok(event) => {
+                        if event
+                            .paths
+                            .iter()
+                            .any(|p| is_ignore_file(&workspace_root, p))
+                        {
+                            ignore_rules_changed = true;
+                            last_event_time = Instant::now();
+                        }
                         if Self::should_ignore(&event, &workspace_root) {
                             continue;
                         }
@@ -137,7 +149,10 @@ impl FileWatcher {
                         );
                     }
                     Err(_) => {
-                        if pending.is_empty() && remove_candidates.is_empty() {
+                        if pending.is_empty()
+                            && remove_candidates.is_empty()
+                            && !ignore_rules_changed
+                        {
                             continue;
                         }
 
@@ -146,6 +161,11 @@ impl FileWatcher {
                             continue;
                         }
 
+                        if ignore_rules_changed {
+                            ignore_rules_changed = false;
+                            Self::handle_ignore_rules_changed(&workspace_root, &app_handle);
+                        }
+
                         // 孤立的 Remove 候选 → 升级为真正的删除事件
                         let stale: Vec<PathBuf> = remove_candidates
                             .iter()
@@ -280,8 +300,15 @@ impl FileWatcher {
         let mut md_deleted: Vec<(PathBuf, String)> = Vec::new();
         let mut dir_created: Vec<String> = Vec::new();
         let mut dir_deleted: Vec<String> = Vec::new();
+        let ignore = load_workspace_ignore(workspace_root);
+        let code_extensions = load_code_extensions(workspace_root);
 
         for (path, kind) in &events {
+            // 被 .snippetsignore 排除的路径不同步到 cache 和前端
+            let is_dir = matches!(kind, PendingKind::DirCreate | PendingKind::DirRemove);
+            if ignore.is_path_ignored(workspace_root, path, is_dir) {
+                continue;
+            }
             match kind {
                 PendingKind::DirCreate => {
                     if let Some(rel) = Self::rel(workspace_root, path) {
@@ -296,8 +323,8 @@ impl FileWatcher {
                     }
                 }
                 _ => {
-                    // 只处理 .md 文件
-                    if path.extension().and_then(|s| s.to_str()) != Some("md") {
+                    // 只处理 .md 文件和启用的代码片段文件
+                    if !is_snippet_path(path, &code_extensions) {
                         continue;
                     }
                     if !path.starts_with(workspace_root) {
@@ -448,6 +475,12 @@ impl FileWatcher {
                     let new = workspace_root.join(&re.to);
                     // 重命名不能只删除旧项；否则新文件会从 cache.json 丢失，
                     // 后续收藏、保存和按时间排序都会读取不到它。
+                    // 代码片段没有 Frontmatter，id 和片段元数据只在 cache.json 中，需要整体迁移。
+                    if is_code_snippet_path(&new, &code_extensions) {
+                        cache.rename_file_metadata(&re.from, &re.to);
+                        dirty |= cache.add_file(&new, workspace_root).is_ok();
+                        continue;
+                    }
                     let removed = cache.remove_file(&old, workspace_root).is_ok();
                     let added = cache.add_file(&new, workspace_root).is_ok();
                     dirty |= removed || added;
@@ -573,6 +606,61 @@ impl FileWatcher {
         }
     }
 
+    // ── 忽略规则变化 ──────────────────────────────────────────────────────────
+
+    /// .snippetsignore 变化：同步 cache（移除新忽略的文件、补充解除忽略的文件），
+    /// 重建搜索索引，并通知前端刷新文件列表。
+    fn handle_ignore_rules_changed(workspace_root: &Path, app_handle: &AppHandle) {
+        info!("📋 [FileWatcher] 忽略规则已变化，重新扫描工作区");
+        let workspace_root = workspace_root.to_path_buf();
+        let app_handle = app_handle.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let cache_snapshot =
+                match app_handle.try_state::<Arc<RwLock<crate::markdown::CacheManager>>>() {
+                    Some(cs) => match cs.write() {
+                        Ok(mut cache) => {
+                            let removed = cache.cleanup_ignored_files(&workspace_root);
+                            if let Err(e) = cache.rebuild_cache(&workspace_root) {
+                                warn!("⚠️ [FileWatcher] 忽略规则变化后重建 cache 失败: {}", e);
+                            }
+                            if let Err(e) = cache.save() {
+                                error!("保存 cache 失败: {}", e);
+                            }
+                            debug!("🙈 [FileWatcher] 移除被忽略的文件 {} 个", removed);
+                            Some(cache.clone())
+                        }
+                        Err(e) => {
+                            warn!("获取 cache 写锁失败: {}", e);
+                            None
+                        }
+                    },
+                    None => None,
+                };
+
+            if let Some(cache) = cache_snapshot {
+                match crate::markdown::IndexManager::build_index(&workspace_root, &cache).await {
+                    Ok(new_index) => {
+                        if let Some(index_state) = app_handle
+                            .try_state::<Arc<RwLock<Option<crate::markdown::IndexManager>>>>()
+                        {
+                            if let Ok(mut index_lock) = index_state.write() {
+                                *index_lock = Some(new_index);
+                            }
+                        }
+                    }
+                    Err(e) => warn!("⚠️ [FileWatcher] 忽略规则变化后重建索引失败: {}", e),
+                }
+            }
+
+            if let Some(w) = app_handle.get_webview_window("config") {
+                if let Err(e) = w.emit("snippets-ignore-changed", ()) {
+                    warn!("发送 snippets-ignore-changed 失败: {}", e);
+                }
+            }
+        });
+    }
+
     // ── 辅助 ──────────────────────────────────────────────────────────────────
 
     fn rel(workspace_root: &Path, path: &Path) -> Option<String> {
@@ -590,14 +678,27 @@ impl FileWatcher {
                 }
             }
         }
-        // 必须涉及 .md 文件或目录
-        // 注意：Remove 事件到达时路径可能已不存在，is_dir() 会返回 false
-        // 用"路径不存在且无扩展名"作为已删除目录的启发式判断
-        let relevant = event.paths.iter().any(|p| {
-            p.is_dir()
-                || p.extension().and_then(|s| s.to_str()) == Some("md")
-                || (!p.exists() && p.extension().is_none())
-        });
+        // 忽略 .snippetsignore 排除的路径
+        let ignore = load_workspace_ignore(workspace_root);
+        if event
+            .paths
+            .iter()
+            .all(|p| ignore.is_path_ignored(workspace_root, p, Self::may_be_dir(p)))
+        {
+            return true;
+        }
+        // 必须涉及 .md 文件、启用的代码片段文件或目录
+        let code_extensions = load_code_extensions(workspace_root);
+        let relevant = event
+            .paths
+            .iter()
+            .any(|p| Self::may_be_dir(p) || is_snippet_path(p, &code_extensions));
         !relevant
     }
+
+    // 注意：Remove 事件到达时路径可能已不存在，is_dir() 会返回 false
+    // 用"路径不存在且无扩展名"作为已删除目录的启发式判断
+    fn may_be_dir(path: &Path) -> bool {
+        path.is_dir() || (!path.exists() && path.extension().is_none())
+    }
 }
@@ -1,7 +1,8 @@
 // workspace.json 和 cache.json 管理模块
 
-use super::metadata::{CacheConfig, WorkspaceConfig};
+use super::metadata::{CacheConfig, CodeSnippetMetadata, WorkspaceConfig};
 use log::info;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
@@ -81,6 +82,38 @@ pub fn write_cache(config_dir: &Path, cache: &CacheConfig) -> Result<(), String>
     Ok(())
 }
 
+// 读取 snippets.json（代码文件片段的元数据，随工作区同步；不存在时为空）
+pub fn read_snippet_metadata(
+    config_dir: &Path,
+) -> Result<HashMap<String, CodeSnippetMetadata>, String> {
+    let path = config_dir.join("snippets.json");
+    crate::json_config::recover_atomic_file(&path)?;
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("读取 snippets.json 失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析 snippets.json 失败: {}", e))
+}
+
+// 写入 snippets.json；按路径排序，两台设备修改不同片段时 Git 可以逐行合并
+pub fn write_snippet_metadata(
+    config_dir: &Path,
+    snippets: &HashMap<String, CodeSnippetMetadata>,
+) -> Result<(), String> {
+    ensure_config_dir_exists(config_dir)?;
+    let path = config_dir.join("snippets.json");
+
+    let sorted: BTreeMap<&String, &CodeSnippetMetadata> = snippets.iter().collect();
+    let json = serde_json::to_string_pretty(&sorted)
+        .map_err(|e| format!("序列化 snippets.json 失败: {}", e))?;
+
+    crate::json_config::write_text_atomic(&path, &json)
+        .map_err(|e| format!("写入 snippets.json 失败: {}", e))
+}
+
 /// 配置目录可能被用户或外部清理工具删除。每次写入前确保其存在，
 /// 让 cache/workspace 文件能够在下次保存时自动恢复。
 fn ensure_config_dir_exists(config_dir: &Path) -> Result<(), String> {
@@ -1,7 +1,7 @@
 // 工作区配置管理器
 // 统一管理 workspace.json 的读写操作
 
-use super::metadata::{AttachmentSettings, WorkspaceConfig};
+use super::metadata::{AttachmentSettings, ExportSelection, WorkspaceConfig};
 use super::workspace::{read_workspace, write_workspace};
 use log::{info, warn};
 use std::path::PathBuf;
@@ -62,4 +62,36 @@ impl WorkspaceManager {
     pub fn set_sync_enabled(&mut self, enabled: bool) {
         self.config.settings.sync_enabled = enabled;
     }
+
+    // 获取保存的导出选集
+    pub fn get_export_selections(&self) -> &[ExportSelection] {
+        &self.config.settings.export_selections
+    }
+
+    // 保存导出选集（同名覆盖）
+    pub fn upsert_export_selection(&mut self, selection: ExportSelection) {
+        let selections = &mut self.config.settings.export_selections;
+        match selections.iter_mut().find(|s| s.name == selection.name) {
+            Some(existing) => *existing = selection,
+            None => selections.push(selection),
+        }
+    }
+
+    // 获取作为片段收录的代码文件扩展名
+    pub fn get_code_snippet_extensions(&self) -> &[String] {
+        &self.config.settings.code_snippet_extensions
+    }
+
+    // 设置作为片段收录的代码文件扩展名（调用方负责规范化）
+    pub fn set_code_snippet_extensions(&mut self, extensions: Vec<String>) {
+        self.config.settings.code_snippet_extensions = extensions;
+    }
+
+    // 删除导出选集，返回是否存在
+    pub fn remove_export_selection(&mut self, name: &str) -> bool {
+        let selections = &mut self.config.settings.export_selections;
+        let before = selections.len();
+        selections.retain(|s| s.name != name);
+        selections.len() != before
+    }
 }
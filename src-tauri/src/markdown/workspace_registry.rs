@@ -0,0 +1,647 @@
+// 多工作区注册与联合搜索
+//
+// 当前工作区仍由 IndexManager / CacheManager 单例状态服务；注册的其他工作区各自持有
+// 一份只读索引，首次搜索时懒加载构建，过期后在后台刷新，不影响当前工作区的运行时状态。
+// 只读片段库（library_sources）的克隆目录同样作为搜索来源登记在这里。
+
+use crate::app_config::{same_path, AppConfigManager, RegisteredWorkspace};
+use crate::json_config::{ensure_workspace_not_app_data, get_workspace_root};
+use crate::library_sources;
+use crate::markdown::commands::{search_result_to_markdown_file, MarkdownFile};
+use crate::markdown::{CacheManager, IndexManager};
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Manager, State};
+
+// 注册工作区的索引没有文件监听，超过该时长后在后台重建
+const REGISTERED_INDEX_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_FEDERATED_LIMIT: usize = 50;
+// 倒数排名融合的平滑常数，越大越削弱头部名次的优势
+const RRF_K: f32 = 60.0;
+// 当前工作区在结果中的固定 id
+const CURRENT_WORKSPACE_ID: &str = "current";
+
+// 注册工作区的索引快照
+struct RegisteredIndex {
+    root: PathBuf,
+    index: Arc<IndexManager>,
+    // 只读加载的 cache.json，用于推断分类；不会写回注册的工作区
+    cache: Arc<CacheManager>,
+    built_at: Instant,
+}
+
+// 注册工作区的索引表（应用状态）
+#[derive(Default)]
+pub struct WorkspaceIndexRegistry {
+    indexes: HashMap<String, RegisteredIndex>,
+    // 正在后台重建的工作区，避免重复触发
+    refreshing: HashSet<String>,
+}
+
+impl WorkspaceIndexRegistry {
+    fn note_count(&self, id: &str) -> Option<usize> {
+        self.indexes
+            .get(id)
+            .map(|entry| entry.index.get_all_entries().len())
+    }
+}
+
+// 注册工作区及其索引状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredWorkspaceStatus {
+    #[serde(flatten)]
+    pub workspace: RegisteredWorkspace,
+    // 目录当前是否可访问（移动硬盘、网络盘可能暂时不可用）
+    pub available: bool,
+    // 已构建索引中的笔记数量；None 表示尚未构建
+    pub indexed_notes: Option<usize>,
+}
+
+// 联合搜索结果：搜索结果附带所属工作区徽标
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedSearchResult {
+    #[serde(flatten)]
+    pub file: MarkdownFile,
+    pub workspace_id: String,
+    pub workspace_name: String,
+    pub workspace_root: String,
+    pub is_current_workspace: bool,
+    // 来自只读片段库，前端只能查看或复制到当前工作区
+    pub read_only: bool,
+}
+
+pub(crate) type SharedRegistry = Arc<RwLock<WorkspaceIndexRegistry>>;
+
+// 联合搜索的来源：注册的工作区或已克隆的只读片段库
+#[derive(Debug, Clone)]
+struct SearchSource {
+    id: String,
+    name: String,
+    path: String,
+    searchable: bool,
+    read_only: bool,
+}
+
+impl From<&RegisteredWorkspace> for SearchSource {
+    fn from(workspace: &RegisteredWorkspace) -> Self {
+        Self {
+            id: workspace.id.clone(),
+            name: workspace.name.clone(),
+            path: workspace.path.clone(),
+            searchable: workspace.searchable,
+            read_only: false,
+        }
+    }
+}
+
+fn config_manager(
+    app_handle: &AppHandle,
+) -> Result<State<'_, Arc<RwLock<AppConfigManager>>>, String> {
+    app_handle
+        .try_state::<Arc<RwLock<AppConfigManager>>>()
+        .ok_or_else(|| "AppConfigManager 未初始化".to_string())
+}
+
+fn registered_workspaces(app_handle: &AppHandle) -> Result<Vec<RegisteredWorkspace>, String> {
+    let state = config_manager(app_handle)?;
+    let manager = state.read().map_err(|e| format!("获取配置锁失败: {}", e))?;
+    Ok(manager.get_registered_workspaces().to_vec())
+}
+
+// 所有搜索来源（不过滤 searchable）；片段库只在克隆完成后出现
+fn search_sources(app_handle: &AppHandle) -> Result<Vec<SearchSource>, String> {
+    let mut sources: Vec<SearchSource> = registered_workspaces(app_handle)?
+        .iter()
+        .map(SearchSource::from)
+        .collect();
+
+    let libraries = {
+        let state = config_manager(app_handle)?;
+        let manager = state.read().map_err(|e| format!("获取配置锁失败: {}", e))?;
+        manager.get_library_sources().to_vec()
+    };
+    for library in libraries {
+        let path = library_sources::library_dir(app_handle, &library.id);
+        if !library_sources::is_cloned(&path) {
+            continue;
+        }
+        sources.push(SearchSource {
+            id: library.id,
+            name: library.name,
+            path: path.to_string_lossy().to_string(),
+            searchable: library.searchable,
+            read_only: true,
+        });
+    }
+    Ok(sources)
+}
+
+// 丢弃某个来源的索引，下次搜索时重建（片段库拉取到新提交后调用）
+pub(crate) fn invalidate_source_index(registry: &SharedRegistry, id: &str) {
+    if let Ok(mut lock) = registry.write() {
+        lock.indexes.remove(id);
+    }
+}
+
+// 工作区徽标的默认名称：目录名
+fn default_workspace_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+// 构建注册工作区的索引（不写入应用状态）
+async fn build_registered_index(root: PathBuf) -> Result<RegisteredIndex, String> {
+    // 注册的工作区可能是只读的共享仓库，只读取 cache.json，不创建配置目录
+    let cache = CacheManager::new_silent(root.join(".snippets-code"))?;
+    let index = IndexManager::build_index(&root, &cache).await?;
+
+    Ok(RegisteredIndex {
+        root,
+        index: Arc::new(index),
+        cache: Arc::new(cache),
+        built_at: Instant::now(),
+    })
+}
+
+// 标记工作区开始重建；已有重建在进行时返回 false
+fn begin_refresh(registry: &SharedRegistry, id: &str) -> bool {
+    registry
+        .write()
+        .map(|mut lock| lock.refreshing.insert(id.to_string()))
+        .unwrap_or(false)
+}
+
+// 构建索引并写入注册表；构建期间工作区被移除或路径变化时丢弃结果
+//
+// 调用前须先用 begin_refresh 标记，结束时（无论成功与否）清除标记
+async fn refresh_registered_index(
+    app_handle: &AppHandle,
+    registry: &SharedRegistry,
+    workspace: &SearchSource,
+) -> Result<(), String> {
+    let root = PathBuf::from(&workspace.path);
+    let result = build_registered_index(root.clone()).await;
+
+    let still_registered = search_sources(app_handle).map(|sources| {
+        sources
+            .iter()
+            .any(|w| w.id == workspace.id && w.path == workspace.path)
+    });
+
+    let mut registry = registry
+        .write()
+        .map_err(|e| format!("获取工作区索引锁失败: {}", e))?;
+    registry.refreshing.remove(&workspace.id);
+
+    let built = result?;
+    if still_registered? {
+        info!(
+            "✅ [联合搜索] 工作区索引已构建: {} ({})",
+            workspace.name,
+            root.display()
+        );
+        registry.indexes.insert(workspace.id.clone(), built);
+    } else {
+        debug!("丢弃已移除工作区的索引: {}", root.display());
+    }
+    Ok(())
+}
+
+// 过期索引在后台重建，搜索继续使用旧索引
+fn schedule_background_refresh(
+    app_handle: &AppHandle,
+    registry: &SharedRegistry,
+    workspace: &SearchSource,
+) {
+    if !begin_refresh(registry, &workspace.id) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    let registry = registry.clone();
+    let workspace = workspace.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = refresh_registered_index(&app_handle, &registry, &workspace).await {
+            warn!("⚠️ [联合搜索] 刷新工作区索引失败 {}: {}", workspace.name, e);
+        }
+    });
+}
+
+// 合并多个工作区的结果
+//
+// 各索引的原始分数受文档数量和词频影响，不能直接比较；先在每个工作区内按分数排名，
+// 再按倒数排名融合排序，同分时当前工作区优先。结果中保留各索引的原始分数。
+fn merge_results(results: Vec<FederatedSearchResult>, limit: usize) -> Vec<FederatedSearchResult> {
+    let mut by_workspace: HashMap<String, Vec<FederatedSearchResult>> = HashMap::new();
+    for result in results {
+        by_workspace
+            .entry(result.workspace_id.clone())
+            .or_default()
+            .push(result);
+    }
+
+    let mut fused: Vec<(f32, FederatedSearchResult)> = Vec::new();
+    for mut group in by_workspace.into_values() {
+        group.sort_by(|a, b| {
+            b.file
+                .score
+                .unwrap_or(0.0)
+                .total_cmp(&a.file.score.unwrap_or(0.0))
+        });
+        fused.extend(
+            group
+                .into_iter()
+                .enumerate()
+                .map(|(rank, result)| (1.0 / (RRF_K + rank as f32 + 1.0), result)),
+        );
+    }
+
+    fused.sort_by(|(score_a, a), (score_b, b)| {
+        score_b
+            .total_cmp(score_a)
+            .then_with(|| b.is_current_workspace.cmp(&a.is_current_workspace))
+            .then_with(|| a.file.title.cmp(&b.file.title))
+            .then_with(|| a.workspace_id.cmp(&b.workspace_id))
+    });
+    fused
+        .into_iter()
+        .take(limit)
+        .map(|(_, result)| result)
+        .collect()
+}
+
+// ============= 命令 =============
+
+// 获取注册的工作区及索引状态
+#[command]
+pub fn get_registered_workspaces(
+    app_handle: AppHandle,
+    registry: State<'_, SharedRegistry>,
+) -> Result<Vec<RegisteredWorkspaceStatus>, String> {
+    let workspaces = registered_workspaces(&app_handle)?;
+    let registry = registry
+        .read()
+        .map_err(|e| format!("获取工作区索引锁失败: {}", e))?;
+
+    Ok(workspaces
+        .into_iter()
+        .map(|workspace| RegisteredWorkspaceStatus {
+            available: Path::new(&workspace.path).is_dir(),
+            indexed_notes: registry.note_count(&workspace.id),
+            workspace,
+        })
+        .collect())
+}
+
+// 注册一个参与联合搜索的工作区
+#[command]
+pub fn register_workspace(
+    app_handle: AppHandle,
+    registry: State<'_, SharedRegistry>,
+    path: String,
+    name: Option<String>,
+) -> Result<RegisteredWorkspace, String> {
+    let path_buf = PathBuf::from(path.trim());
+    if path_buf.as_os_str().is_empty() {
+        return Err("工作区路径不能为空".to_string());
+    }
+    if !path_buf.is_dir() {
+        return Err(format!("目录不存在: {}", path_buf.display()));
+    }
+    // 共享仓库可以是只读的，这里只要求可读
+    std::fs::read_dir(&path_buf).map_err(|e| format!("目录没有读取权限: {}", e))?;
+    ensure_workspace_not_app_data(&app_handle, &path_buf)?;
+
+    if let Some(current) = get_workspace_root(&app_handle)? {
+        if same_path(&current, &path_buf) {
+            return Err("该目录是当前工作区，无需注册".to_string());
+        }
+    }
+
+    let workspace = {
+        let state = config_manager(&app_handle)?;
+        let mut manager = state
+            .write()
+            .map_err(|e| format!("获取配置锁失败: {}", e))?;
+        if manager
+            .get_registered_workspaces()
+            .iter()
+            .any(|w| same_path(Path::new(&w.path), &path_buf))
+        {
+            return Err(format!("工作区已注册: {}", path_buf.display()));
+        }
+
+        let workspace = RegisteredWorkspace {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| default_workspace_name(&path_buf)),
+            path: path_buf.to_string_lossy().to_string(),
+            searchable: true,
+        };
+        manager.upsert_registered_workspace(workspace.clone());
+        manager.save()?;
+        workspace
+    };
+
+    info!(
+        "✅ [联合搜索] 已注册工作区: {} ({})",
+        workspace.name, workspace.path
+    );
+    schedule_background_refresh(
+        &app_handle,
+        registry.inner(),
+        &SearchSource::from(&workspace),
+    );
+    Ok(workspace)
+}
+
+// 更新注册工作区的名称或搜索开关
+#[command]
+pub fn update_registered_workspace(
+    app_handle: AppHandle,
+    id: String,
+    name: Option<String>,
+    searchable: Option<bool>,
+) -> Result<RegisteredWorkspace, String> {
+    let state = config_manager(&app_handle)?;
+    let mut manager = state
+        .write()
+        .map_err(|e| format!("获取配置锁失败: {}", e))?;
+
+    let mut workspace = manager
+        .get_registered_workspaces()
+        .iter()
+        .find(|w| w.id == id)
+        .cloned()
+        .ok_or_else(|| format!("工作区未注册: {}", id))?;
+
+    if let Some(name) = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        workspace.name = name;
+    }
+    if let Some(searchable) = searchable {
+        workspace.searchable = searchable;
+    }
+
+    manager.upsert_registered_workspace(workspace.clone());
+    manager.save()?;
+    Ok(workspace)
+}
+
+// 取消注册工作区，并释放其索引
+#[command]
+pub fn unregister_workspace(
+    app_handle: AppHandle,
+    registry: State<'_, SharedRegistry>,
+    id: String,
+) -> Result<bool, String> {
+    let removed = {
+        let state = config_manager(&app_handle)?;
+        let mut manager = state
+            .write()
+            .map_err(|e| format!("获取配置锁失败: {}", e))?;
+        let removed = manager.remove_registered_workspace(&id);
+        if removed {
+            manager.save()?;
+        }
+        removed
+    };
+
+    registry
+        .write()
+        .map_err(|e| format!("获取工作区索引锁失败: {}", e))?
+        .indexes
+        .remove(&id);
+
+    Ok(removed)
+}
+
+// 立即重建注册工作区（或已克隆片段库）的索引
+#[command]
+pub async fn rebuild_registered_workspace_index(
+    app_handle: AppHandle,
+    registry: State<'_, SharedRegistry>,
+    id: String,
+) -> Result<usize, String> {
+    let workspace = search_sources(&app_handle)?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("工作区未注册: {}", id))?;
+
+    if !begin_refresh(registry.inner(), &workspace.id) {
+        return Err("工作区索引正在重建，请稍后再试".to_string());
+    }
+    refresh_registered_index(&app_handle, registry.inner(), &workspace).await?;
+
+    let registry = registry
+        .read()
+        .map_err(|e| format!("获取工作区索引锁失败: {}", e))?;
+    Ok(registry.note_count(&id).unwrap_or(0))
+}
+
+// 联合搜索：当前工作区 + 所有参与搜索的注册工作区
+#[command]
+pub async fn search_federated(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    registry: State<'_, SharedRegistry>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FederatedSearchResult>, String> {
+    let limit = limit.filter(|l| *l > 0).unwrap_or(DEFAULT_FEDERATED_LIMIT);
+    let current_root = get_workspace_root(&app_handle)?;
+    let mut results = Vec::new();
+
+    // 当前工作区：复用常驻索引
+    if let Some(workspace_root) = current_root.as_ref() {
+        let manager_lock = index_manager
+            .read()
+            .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+        if let Some(manager) = manager_lock.as_ref() {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取缓存管理器锁失败: {}", e))?;
+            let workspace_name = default_workspace_name(workspace_root);
+            let workspace_root_str = workspace_root.to_string_lossy().to_string();
+
+            for (entry, score) in manager.search(&query) {
+                results.push(FederatedSearchResult {
+                    file: search_result_to_markdown_file(entry, score, workspace_root, &cache),
+                    workspace_id: CURRENT_WORKSPACE_ID.to_string(),
+                    workspace_name: workspace_name.clone(),
+                    workspace_root: workspace_root_str.clone(),
+                    is_current_workspace: true,
+                    read_only: false,
+                });
+            }
+        } else {
+            debug!("🔎 [联合搜索] 当前工作区索引未就绪，跳过");
+        }
+    }
+
+    // 注册的工作区和片段库：跳过与当前工作区相同的目录和不可访问的目录
+    let workspaces: Vec<SearchSource> = search_sources(&app_handle)?
+        .into_iter()
+        .filter(|w| w.searchable && Path::new(&w.path).is_dir())
+        .filter(|w| {
+            current_root
+                .as_ref()
+                .is_none_or(|current| !same_path(current, Path::new(&w.path)))
+        })
+        .collect();
+
+    for workspace in &workspaces {
+        let snapshot = {
+            let lock = registry
+                .read()
+                .map_err(|e| format!("获取工作区索引锁失败: {}", e))?;
+            lock.indexes
+                .get(&workspace.id)
+                .filter(|entry| entry.root == Path::new(&workspace.path))
+                .map(|entry| {
+                    (
+                        entry.index.clone(),
+                        entry.cache.clone(),
+                        entry.built_at.elapsed() > REGISTERED_INDEX_MAX_AGE,
+                    )
+                })
+        };
+
+        let (index, cache) = match snapshot {
+            Some((index, cache, stale)) => {
+                if stale {
+                    schedule_background_refresh(&app_handle, registry.inner(), workspace);
+                }
+                (index, cache)
+            }
+            None => {
+                // 首次搜索：同步构建，后续搜索直接复用；已有构建在进行时本次跳过该工作区
+                if !begin_refresh(registry.inner(), &workspace.id) {
+                    debug!("🔎 [联合搜索] 工作区索引构建中，跳过: {}", workspace.name);
+                    continue;
+                }
+                if let Err(e) =
+                    refresh_registered_index(&app_handle, registry.inner(), workspace).await
+                {
+                    warn!("⚠️ [联合搜索] 构建工作区索引失败 {}: {}", workspace.name, e);
+                    continue;
+                }
+                let lock = registry
+                    .read()
+                    .map_err(|e| format!("获取工作区索引锁失败: {}", e))?;
+                match lock.indexes.get(&workspace.id) {
+                    Some(entry) => (entry.index.clone(), entry.cache.clone()),
+                    None => continue,
+                }
+            }
+        };
+
+        let root = PathBuf::from(&workspace.path);
+        for (entry, score) in index.search(&query) {
+            results.push(FederatedSearchResult {
+                file: search_result_to_markdown_file(entry, score, &root, &cache),
+                workspace_id: workspace.id.clone(),
+                workspace_name: workspace.name.clone(),
+                workspace_root: workspace.path.clone(),
+                is_current_workspace: false,
+                read_only: workspace.read_only,
+            });
+        }
+    }
+
+    Ok(merge_results(results, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(workspace_id: &str, title: &str, score: f32) -> FederatedSearchResult {
+        FederatedSearchResult {
+            file: MarkdownFile {
+                id: format!("{}-{}", workspace_id, title),
+                title: title.to_string(),
+                content: String::new(),
+                category_id: 0,
+                category_name: String::new(),
+                tags: Vec::new(),
+                created: String::new(),
+                modified: String::new(),
+                file_type: "note".to_string(),
+                language: None,
+                framework: None,
+                kind: None,
+                favorite: false,
+                file_path: format!("{}/{}.md", workspace_id, title),
+                score: Some(score),
+            },
+            workspace_id: workspace_id.to_string(),
+            workspace_name: workspace_id.to_string(),
+            workspace_root: workspace_id.to_string(),
+            is_current_workspace: workspace_id == CURRENT_WORKSPACE_ID,
+            read_only: false,
+        }
+    }
+
+    fn titles(results: &[FederatedSearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.file.title.as_str()).collect()
+    }
+
+    #[test]
+    fn merge_ranks_each_workspace_instead_of_comparing_raw_scores() {
+        // 大工作区的原始分数整体偏高，不应把小工作区的最佳结果挤到最后
+        let merged = merge_results(
+            vec![
+                result("big", "b2", 90.0),
+                result("big", "b1", 120.0),
+                result("big", "b3", 80.0),
+                result("small", "s1", 3.0),
+                result("small", "s2", 1.0),
+            ],
+            10,
+        );
+        assert_eq!(titles(&merged), vec!["b1", "s1", "b2", "s2", "b3"]);
+    }
+
+    #[test]
+    fn merge_prefers_current_workspace_on_equal_rank_and_applies_limit() {
+        let merged = merge_results(
+            vec![
+                result("other", "a", 50.0),
+                result(CURRENT_WORKSPACE_ID, "z", 5.0),
+                result("other", "b", 40.0),
+            ],
+            2,
+        );
+        assert_eq!(titles(&merged), vec!["z", "a"]);
+    }
+
+    #[test]
+    fn refresh_is_started_once_until_it_finishes() {
+        let registry: SharedRegistry = Arc::new(RwLock::new(WorkspaceIndexRegistry::default()));
+        assert!(begin_refresh(&registry, "w1"));
+        assert!(!begin_refresh(&registry, "w1"));
+        assert!(begin_refresh(&registry, "w2"));
+
+        registry.write().unwrap().refreshing.remove("w1");
+        assert!(begin_refresh(&registry, "w1"));
+    }
+
+    #[test]
+    fn default_name_is_directory_name() {
+        assert_eq!(
+            default_workspace_name(Path::new("/data/shared-notes")),
+            "shared-notes"
+        );
+    }
+}
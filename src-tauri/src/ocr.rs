@@ -12,6 +12,8 @@ use std::thread;
 use std::time::Instant;
 use tauri::Manager;
 
+use crate::language_detect::{detect_language, script_stats};
+
 use std::os::windows::process::CommandExt;
 
 #[derive(Debug, Clone, Serialize)]
@@ -65,7 +67,7 @@ impl OcrLanguage {
             "zh" | "zh-cn" | "chinese" | "chinese_simplified" | "simplified" => {
                 Ok(Self::ChineseSimplified)
             }
-            "zh-tw" | "zh-hk" | "cht" | "traditional" | "chinese_traditional" => {
+            "zh-tw" | "zh_tw" | "zh-hk" | "cht" | "traditional" | "chinese_traditional" => {
                 Ok(Self::ChineseTraditional)
             }
             "en" | "eng" | "english" => Ok(Self::English),
@@ -75,6 +77,18 @@ impl OcrLanguage {
         }
     }
 
+    // 语种识别结果对应的识别模型，德、法等拉丁语系共用英文模型
+    fn from_detected(code: &str) -> Option<Self> {
+        match code {
+            "zh" => Some(Self::ChineseSimplified),
+            "zh_tw" => Some(Self::ChineseTraditional),
+            "en" | "de" | "fr" => Some(Self::English),
+            "ja" => Some(Self::Japanese),
+            "ko" => Some(Self::Korean),
+            _ => None,
+        }
+    }
+
     fn label(self) -> &'static str {
         match self {
             Self::ChineseSimplified => "zh",
@@ -696,7 +710,13 @@ fn run_rapidocr_sidecar(
         }
     }
 
-    if should_try_fallback_language_candidates(&result, best_score)
+    // 主语言结果里能认出日文假名等，说明应换用对应模型，优先且只重试该语言
+    let detected_language = detected_fallback_language(&result, language_hint, &primary_languages);
+    let fallback_languages = match detected_language {
+        Some(language) if fallback_languages.contains(&language) => vec![language],
+        _ => fallback_languages,
+    };
+    if (detected_language.is_some() || should_try_fallback_language_candidates(&result, best_score))
         && !fallback_languages.is_empty()
     {
         append_ocr_log(
@@ -704,10 +724,11 @@ fn run_rapidocr_sidecar(
             "backend",
             "RapidOCR primary languages still low quality, retry fallback languages",
             Some(&format!(
-                "current_language={}, current_score={:.2}, confidence={:.2}, fallback_languages={:?}",
+                "current_language={}, current_score={:.2}, confidence={:.2}, detected_language={:?}, fallback_languages={:?}",
                 result.language,
                 best_score,
                 result.confidence,
+                detected_language.map(|item| item.label()),
                 fallback_languages
                     .iter()
                     .map(|item| item.label())
@@ -1887,40 +1908,6 @@ fn text_competing_script_penalty(text: &str, language: OcrLanguage) -> f64 {
     }
 }
 
-#[derive(Default)]
-struct ScriptStats {
-    compact_count: usize,
-    cjk_count: usize,
-    kana_count: usize,
-    hangul_count: usize,
-    ascii_letter_count: usize,
-}
-
-fn script_stats(text: &str) -> ScriptStats {
-    let mut stats = ScriptStats::default();
-    for ch in text.chars() {
-        if ch.is_whitespace() {
-            continue;
-        }
-
-        stats.compact_count += 1;
-        if ch.is_ascii_alphabetic() {
-            stats.ascii_letter_count += 1;
-        }
-        let code = ch as u32;
-        if matches!(code, 0x3400..=0x4DBF | 0x4E00..=0x9FFF) {
-            stats.cjk_count += 1;
-        }
-        if matches!(code, 0x3040..=0x30FF) {
-            stats.kana_count += 1;
-        }
-        if matches!(code, 0xAC00..=0xD7AF) {
-            stats.hangul_count += 1;
-        }
-    }
-    stats
-}
-
 fn text_garbage_penalty(text: &str) -> f64 {
     let mut penalty = 0.0;
     let mut ascii_symbols = 0.0;
@@ -2226,6 +2213,19 @@ fn should_retry_transformed_image(result: &OcrRecognizeResult, score: f64) -> bo
         || breakdown.script_mismatch_penalty > 180.0
 }
 
+fn detected_fallback_language(
+    result: &OcrRecognizeResult,
+    language_hint: Option<OcrLanguage>,
+    primary_languages: &[OcrLanguage],
+) -> Option<OcrLanguage> {
+    if language_hint.is_some() {
+        return None;
+    }
+
+    let guess = detect_language(&result.full_text, 0.6)?;
+    OcrLanguage::from_detected(&guess.code).filter(|language| !primary_languages.contains(language))
+}
+
 fn should_try_fallback_language_candidates(result: &OcrRecognizeResult, score: f64) -> bool {
     if result.full_text.trim().chars().count() < 20 {
         return false;
@@ -18,6 +18,20 @@ use std::sync::{
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager, Window};
 
+use crate::plugins::local_ai_context::{fit_context, LocalAiContextStrategy};
+use crate::plugins::local_ai_gguf::{
+    mmproj_mismatch, read_model_info, recommended_ctx_size, LocalAiGgufInfo,
+};
+use crate::plugins::local_ai_provider::{
+    provider_for, LocalAiProvider, LocalAiProviderKind, LocalAiProviderModel,
+};
+use crate::plugins::local_ai_rag::estimate_tokens;
+use crate::plugins::local_ai_tools::{
+    invoke_tool, tool_definitions, AppToolHost, LocalAiToolHost, PendingToolCall,
+    ToolCallAccumulator,
+};
+use crate::plugins::translation_memory::{glossary_for, glossary_prompt, GlossaryTerm};
+
 use std::os::windows::process::CommandExt;
 
 const PLUGIN_ID: &str = "local-ai";
@@ -25,6 +39,7 @@ const RUNTIME_PLUGIN_ID: &str = "local-ai-llama-runtime";
 const DEFAULT_MODEL_DIR: &str = r"E:\Models\HauhauCS\Qwen3.5-4B-Uncensored-HauhauCS-Aggressive";
 const DEFAULT_HOST: &str = "127.0.0.1";
 const DEFAULT_PORT: u16 = 39281;
+const DEFAULT_CTX_SIZE: u32 = 4096;
 const HEALTH_TIMEOUT_SECS: u64 = 90;
 const IDLE_CHECK_INTERVAL_SECS: u64 = 30;
 const CHAT_PARALLEL_SLOTS: u32 = 1;
@@ -32,6 +47,10 @@ const CHAT_MAX_ATTACHMENTS: usize = 5;
 const CHAT_MAX_TEXT_BYTES: u64 = 1024 * 1024;
 const CHAT_MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
 const CHAT_MAX_TEXT_CHARS: usize = 40_000;
+// 单次回答中最多执行的工具轮数
+const MAX_TOOL_ROUNDS: usize = 4;
+// 当前配置结构版本；读取到更旧的版本时执行 migrate_config
+const CONFIG_VERSION: u32 = 1;
 static SERVICE_STATE: LazyLock<Mutex<LocalAiServiceState>> =
     LazyLock::new(|| Mutex::new(LocalAiServiceState::default()));
 static ACTIVE_STREAM_CANCELS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
@@ -42,6 +61,21 @@ static IDLE_MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalAiConfig {
+    // 配置结构版本，旧文件没有该字段时为 0
+    #[serde(default)]
+    pub config_version: u32,
+    // 服务提供方：托管 llama-server / OpenAI 兼容服务 / Ollama
+    #[serde(default)]
+    pub provider: LocalAiProviderKind,
+    // 外部服务地址（OpenAI 兼容或 Ollama），托管 llama-server 时忽略
+    #[serde(default)]
+    pub provider_base_url: Option<String>,
+    // OpenAI 兼容服务的 API Key（可选）
+    #[serde(default)]
+    pub api_key: Option<String>,
+    // 外部服务使用的模型名
+    #[serde(default)]
+    pub provider_model: Option<String>,
     pub model_dir: String,
     pub model_path: Option<String>,
     pub mmproj_path: Option<String>,
@@ -73,6 +107,18 @@ pub struct LocalAiConfig {
     pub repeat_last_n: u32,
     pub max_tokens: u32,
     pub request_timeout_secs: u32,
+    // 以 --embeddings 启动 llama-server，提供 /v1/embeddings（语义搜索）
+    #[serde(default)]
+    pub embeddings: bool,
+    // 独立的 OpenAI 兼容向量服务地址；设置后语义搜索不再使用托管的 llama-server
+    #[serde(default)]
+    pub embedding_base_url: Option<String>,
+    // 以 --jinja 启动 llama-server，使聊天模板支持 tools / tool_calls
+    #[serde(default)]
+    pub tool_calling: bool,
+    // 对话超出上下文窗口时的压缩策略
+    #[serde(default)]
+    pub context_strategy: LocalAiContextStrategy,
 }
 
 fn default_top_p() -> f32 {
@@ -101,13 +147,18 @@ impl Default for LocalAiConfig {
             .map(|threads| threads.get() as u32)
             .unwrap_or(4);
         Self {
+            config_version: CONFIG_VERSION,
+            provider: LocalAiProviderKind::default(),
+            provider_base_url: None,
+            api_key: None,
+            provider_model: None,
             model_dir: DEFAULT_MODEL_DIR.to_string(),
             model_path: None,
             mmproj_path: None,
             runtime_path: None,
             host: DEFAULT_HOST.to_string(),
             port: DEFAULT_PORT,
-            ctx_size: 4096,
+            ctx_size: DEFAULT_CTX_SIZE,
             gpu_layers: 28,
             main_gpu: 0,
             threads: available_threads.saturating_sub(2).max(2),
@@ -127,6 +178,10 @@ impl Default for LocalAiConfig {
             repeat_last_n: default_repeat_last_n(),
             max_tokens: 0,
             request_timeout_secs: 600,
+            embeddings: false,
+            embedding_base_url: None,
+            tool_calling: false,
+            context_strategy: LocalAiContextStrategy::default(),
         }
     }
 }
@@ -151,11 +206,18 @@ pub struct LocalAiModelScan {
     pub selected_model_path: Option<String>,
     pub selected_mmproj_path: Option<String>,
     pub message: Option<String>,
+    // 各 GGUF 文件头中读取的元数据（主模型与 mmproj）
+    pub models: Vec<LocalAiGgufInfo>,
+    // 按所选主模型训练上下文长度推荐的 ctx_size
+    pub recommended_ctx_size: Option<u32>,
+    // 所选 mmproj 与主模型不匹配时的提示
+    pub mmproj_warning: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalAiServiceStatus {
+    pub provider: LocalAiProviderKind,
     pub running: bool,
     pub healthy: bool,
     pub pid: Option<u32>,
@@ -175,6 +237,22 @@ pub struct LocalAiServiceStatus {
 pub struct LocalAiMessage {
     pub role: String,
     pub content: Value,
+    // assistant 消息发起的工具调用；发往服务端的字段名须为 OpenAI 的 snake_case
+    #[serde(
+        default,
+        rename = "tool_calls",
+        alias = "toolCalls",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tool_calls: Option<Value>,
+    // tool 消息对应的调用 id
+    #[serde(
+        default,
+        rename = "tool_call_id",
+        alias = "toolCallId",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tool_call_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -184,6 +262,40 @@ pub struct LocalAiChatTurn {
     pub role: String,
     pub content: String,
     pub created_at: String,
+    // 工作区问答引用的来源，回答中的 [n] 对应 index
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<LocalAiChatSource>,
+    // 本轮回答中执行过的工具调用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<LocalAiToolInvocation>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiChatSource {
+    pub index: usize,
+    pub note_id: String,
+    pub file_path: String,
+    pub title: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: String,
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiToolInvocation {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+    // "pending" 等待确认 / "ok" / "error" / "rejected"
+    pub status: String,
+    pub requires_confirmation: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -198,6 +310,14 @@ pub struct LocalAiChatHistory {
     pub current_node_id: Option<String>,
     #[serde(default)]
     pub messages: Option<Value>,
+    // 以下字段由对话历史库维护，保存对话时不会被覆盖
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // 导出的笔记（相对工作区路径）
+    #[serde(default)]
+    pub note_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -220,12 +340,22 @@ pub struct LocalAiChatRequest {
     pub temperature: Option<f32>,
     pub enable_thinking: Option<bool>,
     pub max_tokens: Option<u32>,
+    // 从工作区检索相关笔记作为带编号引用的上下文
+    #[serde(default)]
+    pub workspace_context: bool,
+    // 允许模型调用白名单内的工作区工具
+    #[serde(default)]
+    pub enable_tools: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalAiChatResponse {
     pub content: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<LocalAiChatSource>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<LocalAiToolInvocation>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -245,6 +375,17 @@ pub struct LocalAiChatStreamStats {
     pub tokens_per_second: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<String>,
+    // 发送前经 /tokenize 统计或估算的提示词 token 数（已计入压缩）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_prompt_tokens: Option<u32>,
+    // 提示词可用的 token 预算（上下文窗口减去回复预留）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_budget: Option<u32>,
+    // 因超出预算被丢弃或并入摘要的历史消息数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compacted_messages: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compaction_strategy: Option<LocalAiContextStrategy>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -255,6 +396,10 @@ pub struct LocalAiChatStreamPayload {
     pub content: Option<String>,
     pub error: Option<String>,
     pub stats: Option<LocalAiChatStreamStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<LocalAiChatSource>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call: Option<LocalAiToolInvocation>,
 }
 
 #[derive(Default)]
@@ -267,9 +412,11 @@ struct LocalAiServiceState {
     last_activity: Option<Instant>,
     active_requests: usize,
     last_error: Option<String>,
+    // 实际传给 llama-server 的上下文长度（可能被截断到模型训练长度）
+    ctx_size: Option<u32>,
 }
 
-struct LocalAiRequestGuard;
+pub(crate) struct LocalAiRequestGuard;
 
 impl Drop for LocalAiRequestGuard {
     fn drop(&mut self) {
@@ -280,11 +427,11 @@ impl Drop for LocalAiRequestGuard {
     }
 }
 
-fn require_plugin(app_handle: &AppHandle) -> Result<(), String> {
+pub(crate) fn require_plugin(app_handle: &AppHandle) -> Result<(), String> {
     crate::app_config::require_plugin_enabled(app_handle, PLUGIN_ID)
 }
 
-fn local_ai_state_file(app_handle: &AppHandle, file_name: &str) -> PathBuf {
+pub(crate) fn local_ai_state_file(app_handle: &AppHandle, file_name: &str) -> PathBuf {
     let data_dir = crate::json_config::get_data_dir(app_handle);
     let state_dir = data_dir.join("state").join("plugins").join(PLUGIN_ID);
     let target = state_dir.join(file_name);
@@ -328,37 +475,45 @@ fn config_path(app_handle: &AppHandle) -> PathBuf {
     local_ai_state_file(app_handle, "local-ai.json")
 }
 
-fn app_json_chat_histories(app_handle: &AppHandle) -> Vec<LocalAiChatHistory> {
-    crate::json_config::get_app_config_value(app_handle, "local_ai_chat_histories")
-        .unwrap_or_default()
-}
-
-fn save_app_json_chat_histories(
-    app_handle: &AppHandle,
-    histories: &[LocalAiChatHistory],
-) -> Result<(), String> {
-    crate::json_config::set_app_config_value(app_handle, "local_ai_chat_histories", histories)
-}
-
-fn load_chat_histories(app_handle: &AppHandle) -> Vec<LocalAiChatHistory> {
-    app_json_chat_histories(app_handle)
-}
-
-fn persist_chat_histories(
-    app_handle: &AppHandle,
-    histories: &[LocalAiChatHistory],
-) -> Result<(), String> {
-    save_app_json_chat_histories(app_handle, histories)
+/// 将旧版本配置迁移到当前结构，返回是否有改动
+fn migrate_config(config: &mut LocalAiConfig) -> bool {
+    if config.config_version >= CONFIG_VERSION {
+        return false;
+    }
+    // v0 -> v1：引入服务提供方，旧配置均为托管的 llama-server；
+    // 旧版默认的 1024 输出上限改为不限制
+    if config.config_version < 1 {
+        config.provider = LocalAiProviderKind::LlamaServer;
+        if config.max_tokens == 1024 {
+            config.max_tokens = 0;
+        }
+    }
+    config.config_version = CONFIG_VERSION;
+    true
 }
 
-fn read_config(app_handle: &AppHandle) -> LocalAiConfig {
+pub(crate) fn read_config(app_handle: &AppHandle) -> LocalAiConfig {
     let path = config_path(app_handle);
-    let mut config = fs::read_to_string(path)
+    let Some(mut config) = fs::read_to_string(&path)
         .ok()
         .and_then(|content| serde_json::from_str::<LocalAiConfig>(&content).ok())
-        .unwrap_or_default();
-    if config.max_tokens == 1024 {
-        config.max_tokens = 0;
+    else {
+        // 首次使用：按默认模型目录中主模型的训练上下文长度选择 ctx_size
+        let mut config = LocalAiConfig::default();
+        if let Some(ctx_size) = scan_model_dir(&config).recommended_ctx_size {
+            config.ctx_size = ctx_size;
+        }
+        return config;
+    };
+    if migrate_config(&mut config) {
+        if let Err(error) = write_config(app_handle, &config) {
+            warn!("[Plugin:local-ai] 保存迁移后的配置失败: {}", error);
+        } else {
+            info!(
+                "[Plugin:local-ai] config migrated to version {}",
+                CONFIG_VERSION
+            );
+        }
     }
     config
 }
@@ -375,8 +530,8 @@ fn display_path(path: &Path) -> String {
     path.to_string_lossy().to_string()
 }
 
-fn base_url(config: &LocalAiConfig) -> String {
-    format!("http://{}:{}", config.host, config.port)
+pub(crate) fn base_url(config: &LocalAiConfig) -> String {
+    provider_for(config).base_url().to_string()
 }
 
 fn completion_token_limit(config: &LocalAiConfig, request_max_tokens: Option<u32>) -> i64 {
@@ -528,26 +683,34 @@ fn scan_model_dir(config: &LocalAiConfig) -> LocalAiModelScan {
             selected_model_path: config.model_path.clone(),
             selected_mmproj_path: config.mmproj_path.clone(),
             message: Some("模型目录不存在".to_string()),
+            models: Vec::new(),
+            recommended_ctx_size: None,
+            mmproj_warning: None,
         };
     }
 
     let mut main_models = Vec::new();
     let mut mmproj_models = Vec::new();
+    let mut models = Vec::new();
     if let Ok(entries) = fs::read_dir(&model_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if !path.is_file() || !is_gguf_file(&path) {
                 continue;
             }
-            if is_mmproj_file(&path) {
+            // 文件名未标注 mmproj 的视觉投影文件按文件头识别
+            let info = read_model_info(&path);
+            if is_mmproj_file(&path) || info.is_vision_projector {
                 mmproj_models.push(display_path(&path));
             } else {
                 main_models.push(display_path(&path));
             }
+            models.push(info);
         }
     }
     main_models.sort();
     mmproj_models.sort();
+    models.sort_by(|left, right| left.path.cmp(&right.path));
 
     let selected_model_path = config
         .model_path
@@ -566,6 +729,14 @@ fn scan_model_dir(config: &LocalAiConfig) -> LocalAiModelScan {
     } else {
         None
     };
+    let selected_model = selected_model_path
+        .as_deref()
+        .map(|path| find_model_info(&models, path));
+    let recommended_ctx_size = selected_model.as_ref().and_then(recommended_ctx_size);
+    let mmproj_warning = match (selected_model.as_ref(), selected_mmproj_path.as_deref()) {
+        (Some(model), Some(mmproj)) => mmproj_mismatch(model, &find_model_info(&models, mmproj)),
+        _ => None,
+    };
 
     LocalAiModelScan {
         model_dir: config.model_dir.clone(),
@@ -575,6 +746,31 @@ fn scan_model_dir(config: &LocalAiConfig) -> LocalAiModelScan {
         selected_model_path,
         selected_mmproj_path,
         message,
+        models,
+        recommended_ctx_size,
+        mmproj_warning,
+    }
+}
+
+// 手动指定的模型可能不在模型目录中，此时单独读取文件头
+fn find_model_info(models: &[LocalAiGgufInfo], path: &str) -> LocalAiGgufInfo {
+    models
+        .iter()
+        .find(|info| info.path == path)
+        .cloned()
+        .unwrap_or_else(|| read_model_info(Path::new(path)))
+}
+
+// 超过模型训练长度的上下文没有意义，只会白占 KV cache，启动时截断
+fn effective_ctx_size(configured: u32, model: &LocalAiGgufInfo) -> u32 {
+    let configured = if configured == 0 {
+        DEFAULT_CTX_SIZE
+    } else {
+        configured
+    };
+    match model.context_length {
+        Some(trained) if trained > 0 && configured > trained => trained,
+        _ => configured,
     }
 }
 
@@ -600,6 +796,7 @@ fn build_server_args(
     config: &LocalAiConfig,
     model_path: &Path,
     mmproj_path: Option<&Path>,
+    ctx_size: u32,
 ) -> Vec<String> {
     let mut args = vec![
         "--host".to_string(),
@@ -609,7 +806,7 @@ fn build_server_args(
         "--model".to_string(),
         display_path(model_path),
         "--ctx-size".to_string(),
-        config.ctx_size.to_string(),
+        ctx_size.to_string(),
         "--parallel".to_string(),
         CHAT_PARALLEL_SLOTS.to_string(),
         "--threads".to_string(),
@@ -638,6 +835,12 @@ fn build_server_args(
     if !config.mmap {
         args.push("--no-mmap".to_string());
     }
+    if config.embeddings {
+        args.push("--embeddings".to_string());
+    }
+    if config.tool_calling {
+        args.push("--jinja".to_string());
+    }
 
     args
 }
@@ -662,7 +865,7 @@ async fn health_check_url(base_url: &str) -> bool {
         .unwrap_or(false)
 }
 
-async fn server_context_size(base_url: &str) -> Option<u32> {
+pub(crate) async fn server_context_size(base_url: &str) -> Option<u32> {
     let url = format!("{}/props", base_url.trim_end_matches('/'));
     let client = Client::builder()
         .timeout(Duration::from_secs(3))
@@ -853,7 +1056,7 @@ fn start_idle_monitor() {
     });
 }
 
-fn mark_request_started() -> LocalAiRequestGuard {
+pub(crate) fn mark_request_started() -> LocalAiRequestGuard {
     if let Ok(mut state) = SERVICE_STATE.lock() {
         state.active_requests += 1;
         state.last_activity = Some(Instant::now());
@@ -877,6 +1080,26 @@ fn has_active_streams() -> bool {
         .unwrap_or(false)
 }
 
+/// 是否有用户发起的请求正在占用服务（后台任务据此让路）
+pub(crate) fn has_foreground_requests() -> bool {
+    let active = SERVICE_STATE
+        .lock()
+        .map(|state| state.active_requests > 0)
+        .unwrap_or(false);
+    active || has_active_streams()
+}
+
+/// 服务是否仍可用：托管的 llama-server 被停止或被空闲监控回收后返回 false；外部服务不受本应用管理
+pub(crate) fn is_service_running(config: &LocalAiConfig) -> bool {
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        return true;
+    }
+    SERVICE_STATE
+        .lock()
+        .map(|mut state| is_child_running_locked(&mut state))
+        .unwrap_or(false)
+}
+
 fn remove_active_stream(request_id: &str) {
     if let Ok(mut cancels) = ACTIVE_STREAM_CANCELS.lock() {
         cancels.remove(request_id);
@@ -953,6 +1176,34 @@ fn service_config_matches(running: &LocalAiConfig, desired: &LocalAiConfig) -> b
         && running.flash_attn == desired.flash_attn
         && running.kv_offload == desired.kv_offload
         && running.mmap == desired.mmap
+        && running.embeddings == desired.embeddings
+        && running.tool_calling == desired.tool_calling
+}
+
+// 分词结果按模型缓存：托管服务以实际加载的模型文件区分
+fn tokenizer_key(provider: &dyn LocalAiProvider) -> String {
+    let model = if provider.kind() == LocalAiProviderKind::LlamaServer {
+        SERVICE_STATE
+            .lock()
+            .ok()
+            .and_then(|state| state.model_path.clone())
+            .unwrap_or_default()
+    } else {
+        provider.model_name().to_string()
+    };
+    format!("{}#{}", provider.base_url(), model)
+}
+
+// 托管服务运行中时返回实际生效的上下文长度
+fn running_ctx_size(config: &LocalAiConfig) -> u32 {
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        return config.ctx_size;
+    }
+    SERVICE_STATE
+        .lock()
+        .ok()
+        .and_then(|state| state.ctx_size)
+        .unwrap_or(config.ctx_size)
 }
 
 fn service_command_matches(command_line: Option<&str>) -> bool {
@@ -961,10 +1212,32 @@ fn service_command_matches(command_line: Option<&str>) -> bool {
         .unwrap_or(false)
 }
 
-async fn ensure_service_running(
+// 外部服务由用户自行启动，这里只检查可达性和模型选择
+async fn ensure_external_provider(config: &LocalAiConfig) -> Result<(), String> {
+    let provider = provider_for(config);
+    if provider.model_name().is_empty() {
+        return Err(format!(
+            "未选择 {} 的模型，请在本地 AI 设置中选择模型",
+            provider.kind().label()
+        ));
+    }
+    if !provider.health_check().await {
+        return Err(format!(
+            "无法连接 {}（{}），请确认服务已启动",
+            provider.kind().label(),
+            provider.base_url()
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) async fn ensure_service_running(
     app_handle: &AppHandle,
     config: &LocalAiConfig,
 ) -> Result<(), String> {
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        return ensure_external_provider(config).await;
+    }
     let desired = normalize_service_config(config.clone());
     let should_start = {
         let mut state = SERVICE_STATE
@@ -1007,6 +1280,10 @@ async fn start_service_inner(
     app_handle: &AppHandle,
     mut config: LocalAiConfig,
 ) -> Result<LocalAiServiceStatus, String> {
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        ensure_external_provider(&config).await?;
+        return local_ai_get_status(app_handle.clone()).await;
+    }
     config = normalize_service_config(config);
     if config.host != "127.0.0.1" && config.host != "localhost" {
         return Err("本地 AI 服务仅允许绑定 127.0.0.1 或 localhost".to_string());
@@ -1016,8 +1293,27 @@ async fn start_service_inner(
     let (runtime_path, runtime_source) = runtime.ok_or_else(|| {
         "未找到 llama-server.exe，请安装 local-ai-llama-runtime 资源包或手动指定路径".to_string()
     })?;
-    let (model_path, mmproj_path) = resolve_model_paths(&config)?;
-    let args = build_server_args(&config, &model_path, mmproj_path.as_deref());
+    let (model_path, mut mmproj_path) = resolve_model_paths(&config)?;
+    let model_info = read_model_info(&model_path);
+    let ctx_size = effective_ctx_size(config.ctx_size, &model_info);
+    if ctx_size != config.ctx_size {
+        warn!(
+            "[Plugin:local-ai] ctx_size {} exceeds the model's trained context, using {}",
+            config.ctx_size, ctx_size
+        );
+    }
+    // 不匹配的 mmproj 会让 llama-server 加载失败，跳过它以纯文本模式启动
+    if let Some(path) = mmproj_path.as_deref() {
+        if let Some(warning) = mmproj_mismatch(&model_info, &read_model_info(path)) {
+            warn!(
+                "[Plugin:local-ai] skipping mmproj {}: {}",
+                display_path(path),
+                warning
+            );
+            mmproj_path = None;
+        }
+    }
+    let args = build_server_args(&config, &model_path, mmproj_path.as_deref(), ctx_size);
     let command_line = format_command_line(&runtime_path, &args);
     let runtime_dir = runtime_path.parent().map(Path::to_path_buf);
     let log_path = log_path(app_handle);
@@ -1070,6 +1366,7 @@ async fn start_service_inner(
             .map_err(|error| format!("本地 AI 服务状态锁定失败: {}", error))?;
         state.child = Some(child);
         state.config = Some(config.clone());
+        state.ctx_size = Some(ctx_size);
         state.model_path = Some(display_path(&model_path));
         state.runtime_path = Some(display_path(&runtime_path));
         state.command_line = Some(command_line);
@@ -1097,7 +1394,7 @@ async fn start_service_inner(
     local_ai_get_status(app_handle.clone()).await
 }
 
-async fn chat_completion(
+pub(crate) async fn chat_completion(
     app_handle: &AppHandle,
     messages: Vec<LocalAiMessage>,
     temperature: Option<f32>,
@@ -1113,13 +1410,13 @@ async fn chat_completion(
     ensure_service_running(app_handle, &config).await?;
     let _guard = mark_request_started();
 
+    let provider = provider_for(&config);
     let client = Client::builder()
         .timeout(Duration::from_secs(config.request_timeout_secs as u64))
         .build()
         .map_err(|error| format!("创建本地 AI 请求客户端失败: {}", error))?;
-    let url = format!("{}/v1/chat/completions", base_url(&config));
     let body = serde_json::json!({
-        "model": "local-ai",
+        "model": provider.model_name(),
         "messages": messages,
         "temperature": temperature.unwrap_or(config.temperature),
         "top_p": config.top_p,
@@ -1136,9 +1433,9 @@ async fn chat_completion(
         "stream": false
     });
 
-    let response = client
-        .post(url)
-        .json(&body)
+    let response = provider
+        .authorize(client.post(provider.chat_url()))
+        .json(&provider.chat_body(body))
         .send()
         .await
         .map_err(|error| format!("请求本地 AI 服务失败: {}", error))?;
@@ -1151,13 +1448,8 @@ async fn chat_completion(
         return Err(format!("本地 AI 服务返回错误 {}: {}", status, value));
     }
 
-    value
-        .get("choices")
-        .and_then(|choices| choices.as_array())
-        .and_then(|choices| choices.first())
-        .and_then(|choice| choice.get("message"))
-        .and_then(|message| message.get("content"))
-        .and_then(|content| content.as_str())
+    provider
+        .completion_text(&value)
         .map(|content| content.trim().to_string())
         .filter(|content| !content.is_empty())
         .ok_or_else(|| "本地 AI 响应中没有可用内容".to_string())
@@ -1179,10 +1471,70 @@ fn emit_chat_stream(
             content,
             error,
             stats,
+            sources: None,
+            tool_call: None,
         },
     );
 }
 
+fn emit_chat_sources(window: &Window, request_id: &str, sources: &[LocalAiChatSource]) {
+    let _ = window.emit(
+        "local-ai-chat-stream",
+        LocalAiChatStreamPayload {
+            request_id: request_id.to_string(),
+            event: "sources".to_string(),
+            content: None,
+            error: None,
+            stats: None,
+            sources: Some(sources.to_vec()),
+            tool_call: None,
+        },
+    );
+}
+
+// 工具调用事件：status 为 pending 时前端需弹出确认
+fn emit_tool_call(window: &Window, request_id: &str, invocation: LocalAiToolInvocation) {
+    let _ = window.emit(
+        "local-ai-chat-stream",
+        LocalAiChatStreamPayload {
+            request_id: request_id.to_string(),
+            event: "tool".to_string(),
+            content: None,
+            error: None,
+            stats: None,
+            sources: None,
+            tool_call: Some(invocation),
+        },
+    );
+}
+
+// 工作区问答：检索失败不影响普通对话
+async fn workspace_sources(
+    app_handle: &AppHandle,
+    request: &mut LocalAiChatRequest,
+) -> Result<Vec<LocalAiChatSource>, String> {
+    if !request.workspace_context {
+        return Ok(Vec::new());
+    }
+    require_plugin(app_handle)?;
+    let config = read_config(app_handle);
+    let max_tokens = completion_token_limit(&config, request.max_tokens);
+    match crate::plugins::local_ai_rag::prepare_workspace_context(
+        app_handle,
+        &config,
+        &mut request.messages,
+        max_tokens,
+    )
+    .await
+    {
+        Ok(sources) => Ok(sources),
+        Err(error) => {
+            warn!("[Plugin:local-ai] workspace retrieval failed: {}", error);
+            Ok(Vec::new())
+        }
+    }
+}
+
 fn get_u32_field(value: &Value, key: &str) -> Option<u32> {
     value
         .get(key)
@@ -1240,6 +1592,10 @@ fn extract_stream_stats(value: &Value, ctx_size: u32) -> Option<LocalAiChatStrea
         generation_time_ms,
         tokens_per_second,
         finish_reason,
+        estimated_prompt_tokens: None,
+        prompt_budget: None,
+        compacted_messages: None,
+        compaction_strategy: None,
     })
 }
 
@@ -1295,16 +1651,20 @@ async fn chat_completion_stream(
     temperature: Option<f32>,
     max_tokens: Option<u32>,
     enable_thinking: Option<bool>,
-) -> Result<String, String> {
+    enable_tools: bool,
+) -> Result<(String, Vec<LocalAiToolInvocation>), String> {
     require_plugin(app_handle)?;
     if messages.is_empty() {
         return Err("消息不能为空".to_string());
     }
+    let config = read_config(app_handle);
+    if enable_tools && !config.tool_calling {
+        return Err("本地 AI 未启用工具调用，请先在本地 AI 设置中开启".to_string());
+    }
 
     let cancel_flag = Arc::new(AtomicBool::new(false));
     register_active_stream(&request_id, window.label(), Arc::clone(&cancel_flag));
     crate::tray::set_ai_response_status(app_handle, true);
-    let config = read_config(app_handle);
     let service_result = tokio::select! {
         result = ensure_service_running(app_handle, &config) => Some(result),
         _ = wait_for_stream_cancel(cancel_flag.as_ref()) => None,
@@ -1322,12 +1682,46 @@ async fn chat_completion_stream(
             start_idle_monitor();
             emit_chat_stream(&window, &request_id, "done", None, None, None);
             remove_active_stream(&request_id);
-            return Ok(String::new());
+            return Ok((String::new(), Vec::new()));
         }
     }
     let _guard = mark_request_started();
 
-    let request_ctx_size = config.ctx_size;
+    let provider = provider_for(&config);
+    let request_ctx_size = if config.provider == LocalAiProviderKind::LlamaServer {
+        running_ctx_size(&config)
+    } else {
+        provider
+            .context_size()
+            .await
+            .filter(|size| *size > 0)
+            .unwrap_or(config.ctx_size)
+    };
+    let completion_limit = completion_token_limit(&config, max_tokens);
+    let tool_tokens = if enable_tools {
+        estimate_tokens(&tool_definitions().to_string())
+    } else {
+        0
+    };
+    // 按“总结”策略压缩时需要一次额外推理，期间同样响应取消
+    let plan = tokio::select! {
+        plan = fit_context(
+            app_handle,
+            &config,
+            provider.as_ref(),
+            &tokenizer_key(provider.as_ref()),
+            messages,
+            request_ctx_size,
+            completion_limit,
+            tool_tokens,
+        ) => Some(plan),
+        _ = wait_for_stream_cancel(cancel_flag.as_ref()) => None,
+    };
+    let Some(plan) = plan else {
+        emit_chat_stream(&window, &request_id, "done", None, None, None);
+        remove_active_stream(&request_id);
+        return Ok((String::new(), Vec::new()));
+    };
     emit_chat_stream(
         &window,
         &request_id,
@@ -1342,24 +1736,32 @@ async fn chat_completion_stream(
             generation_time_ms: None,
             tokens_per_second: None,
             finish_reason: None,
+            estimated_prompt_tokens: u32::try_from(plan.prompt_tokens).ok(),
+            prompt_budget: u32::try_from(plan.budget).ok(),
+            compacted_messages: (plan.compacted > 0).then_some(plan.compacted as u32),
+            compaction_strategy: plan.strategy,
         }),
     );
 
-    let client = Client::builder()
+    let client = match Client::builder()
         .timeout(Duration::from_secs(config.request_timeout_secs as u64))
         .build()
-        .map_err(|error| format!("创建本地 AI 请求客户端失败: {}", error))?;
-    let url = format!("{}/v1/chat/completions", base_url(&config));
+    {
+        Ok(client) => client,
+        Err(error) => {
+            remove_active_stream(&request_id);
+            return Err(format!("创建本地 AI 请求客户端失败: {}", error));
+        }
+    };
     let body = serde_json::json!({
-        "model": "local-ai",
-        "messages": messages,
+        "model": provider.model_name(),
         "temperature": temperature.unwrap_or(config.temperature),
         "top_p": config.top_p,
         "top_k": config.top_k,
         "min_p": config.min_p,
         "repeat_penalty": config.repeat_penalty,
         "repeat_last_n": config.repeat_last_n,
-        "max_tokens": completion_token_limit(&config, max_tokens),
+        "max_tokens": completion_limit,
         "chat_template_kwargs": {
             "enable_thinking": enable_thinking.unwrap_or(false)
         },
@@ -1371,57 +1773,183 @@ async fn chat_completion_stream(
         }
     });
 
+    let emit = |event: ChatStreamEvent| match event {
+        ChatStreamEvent::Delta(delta) => {
+            emit_chat_stream(&window, &request_id, "delta", Some(delta), None, None)
+        }
+        ChatStreamEvent::Stats(stats) => {
+            emit_chat_stream(&window, &request_id, "stats", None, None, Some(stats))
+        }
+        ChatStreamEvent::ToolCall(invocation) => emit_tool_call(&window, &request_id, invocation),
+        ChatStreamEvent::Done => emit_chat_stream(&window, &request_id, "done", None, None, None),
+    };
+    let host = enable_tools.then(|| AppToolHost::new(app_handle.clone(), request_id.clone()));
+    let result = run_chat_stream(
+        &client,
+        provider.as_ref(),
+        &body,
+        plan.messages,
+        cancel_flag.as_ref(),
+        request_ctx_size,
+        enable_thinking.unwrap_or(false),
+        host.as_ref(),
+        &emit,
+    )
+    .await;
+    remove_active_stream(&request_id);
+    result
+}
+
+pub(crate) enum ChatStreamEvent {
+    Delta(String),
+    Stats(LocalAiChatStreamStats),
+    ToolCall(LocalAiToolInvocation),
+    Done,
+}
+
+enum StreamRoundEnd {
+    Completed {
+        text: String,
+        tool_calls: Vec<PendingToolCall>,
+    },
+    Cancelled,
+    Interrupted,
+}
+
+/// 多轮对话流：模型返回 tool_calls 时执行工具、追加结果后继续请求，直到给出最终回答
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_chat_stream<H: LocalAiToolHost>(
+    client: &Client,
+    provider: &dyn LocalAiProvider,
+    body: &Value,
+    mut messages: Vec<LocalAiMessage>,
+    cancel_flag: &AtomicBool,
+    ctx_size: u32,
+    enable_thinking: bool,
+    tools: Option<&H>,
+    emit: &(dyn Fn(ChatStreamEvent) + Sync),
+) -> Result<(String, Vec<LocalAiToolInvocation>), String> {
+    let mut content = String::new();
+    let mut invocations = Vec::new();
+
+    for round in 0..=MAX_TOOL_ROUNDS {
+        let mut round_body = body.clone();
+        round_body["messages"] = serde_json::json!(messages);
+        // 最后一轮不再提供工具，要求模型基于已有结果作答
+        if tools.is_some() && round < MAX_TOOL_ROUNDS {
+            round_body["tools"] = tool_definitions();
+        }
+
+        let round_end = stream_chat_round(
+            client,
+            provider,
+            &provider.chat_body(round_body),
+            cancel_flag,
+            ctx_size,
+            enable_thinking,
+            emit,
+            &mut content,
+        )
+        .await?;
+        let (text, tool_calls) = match round_end {
+            StreamRoundEnd::Completed { text, tool_calls } => (text, tool_calls),
+            StreamRoundEnd::Cancelled => break,
+            StreamRoundEnd::Interrupted => {
+                if !content.trim().is_empty() {
+                    return Err("本地 AI 流在完成前断开，已保留已生成内容。".to_string());
+                }
+                return Err("本地 AI 响应中没有可用内容".to_string());
+            }
+        };
+        let Some(host) = tools.filter(|_| !tool_calls.is_empty()) else {
+            break;
+        };
+
+        messages.push(LocalAiMessage {
+            role: "assistant".to_string(),
+            content: Value::String(strip_think(&text)),
+            tool_calls: Some(Value::Array(
+                tool_calls.iter().map(PendingToolCall::to_message).collect(),
+            )),
+            tool_call_id: None,
+        });
+        for call in tool_calls {
+            let (invocation, reply) = invoke_tool(host, call, cancel_flag, emit).await;
+            messages.push(reply);
+            invocations.push(invocation);
+        }
+        if cancel_flag.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+
+    emit(ChatStreamEvent::Done);
+    Ok((content.trim().to_string(), invocations))
+}
+
+// 去掉思考内容，回传给模型的 assistant 消息只保留正文
+pub(crate) fn strip_think(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<think>") {
+        result.push_str(&rest[..start]);
+        match rest[start..].find("</think>") {
+            Some(end) => rest = &rest[start + end + "</think>".len()..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    result.push_str(rest);
+    result.trim().to_string()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn stream_chat_round(
+    client: &Client,
+    provider: &dyn LocalAiProvider,
+    body: &Value,
+    cancel_flag: &AtomicBool,
+    ctx_size: u32,
+    enable_thinking: bool,
+    emit: &(dyn Fn(ChatStreamEvent) + Sync),
+    content: &mut String,
+) -> Result<StreamRoundEnd, String> {
     let response_result = tokio::select! {
-        response = client
-            .post(url)
+        response = provider
+            .authorize(client.post(provider.chat_url()))
             .header(ACCEPT, "text/event-stream")
             .header(CACHE_CONTROL, "no-cache")
-            .json(&body)
+            .json(body)
             .send() => Some(response),
-        _ = wait_for_stream_cancel(cancel_flag.as_ref()) => None,
+        _ = wait_for_stream_cancel(cancel_flag) => None,
     };
     let Some(response_result) = response_result else {
-        emit_chat_stream(&window, &request_id, "done", None, None, None);
-        remove_active_stream(&request_id);
-        return Ok(String::new());
-    };
-    let response = match response_result {
-        Ok(response) => response,
-        Err(error) => {
-            remove_active_stream(&request_id);
-            return Err(format!("请求本地 AI 服务失败: {}", error));
-        }
+        return Ok(StreamRoundEnd::Cancelled);
     };
+    let response = response_result.map_err(|error| format!("请求本地 AI 服务失败: {}", error))?;
     let status = response.status();
     if !status.is_success() {
         let value = response
             .text()
             .await
             .unwrap_or_else(|error| format!("读取错误响应失败: {}", error));
-        remove_active_stream(&request_id);
         return Err(format!("本地 AI 服务返回错误 {}: {}", status, value));
     }
 
     let mut stream = response.bytes_stream();
     let mut buffer = String::new();
-    let mut content = String::new();
+    let mut text = String::new();
+    let mut tool_calls = ToolCallAccumulator::default();
     let mut reasoning_open = false;
     loop {
         if cancel_flag.load(Ordering::Relaxed) {
             if reasoning_open {
                 content.push_str("</think>");
-                emit_chat_stream(
-                    &window,
-                    &request_id,
-                    "delta",
-                    Some("</think>".to_string()),
-                    None,
-                    None,
-                );
+                emit(ChatStreamEvent::Delta("</think>".to_string()));
             }
-            emit_chat_stream(&window, &request_id, "done", None, None, None);
-            remove_active_stream(&request_id);
-            return Ok(content.trim().to_string());
+            return Ok(StreamRoundEnd::Cancelled);
         }
 
         // 不直接无限等待下一个 SSE 分片。窗口销毁后即使模型暂时没有输出，
@@ -1438,55 +1966,44 @@ async fn chat_completion_stream(
         buffer.push_str(&String::from_utf8_lossy(&chunk));
 
         while let Some(index) = buffer.find('\n') {
-            let line = buffer[..index].trim().to_string();
+            let line = buffer[..index].to_string();
             buffer = buffer[index + 1..].to_string();
-            if line.is_empty() || !line.starts_with("data:") {
+            let Some(line) = provider.parse_stream_line(&line)? else {
                 continue;
-            }
+            };
 
-            let data = line.trim_start_matches("data:").trim();
-            if data == "[DONE]" {
+            if let Some(value) = line.chunk {
+                if let Some(stats) = extract_stream_stats(&value, ctx_size) {
+                    emit(ChatStreamEvent::Stats(stats));
+                }
+                tool_calls.push(&value);
+                if let Some(delta) =
+                    extract_stream_delta(&value, &mut reasoning_open, enable_thinking)
+                {
+                    content.push_str(&delta);
+                    text.push_str(&delta);
+                    emit(ChatStreamEvent::Delta(delta));
+                }
+            }
+            if line.done {
                 if reasoning_open {
                     let delta = "</think>".to_string();
                     content.push_str(&delta);
-                    emit_chat_stream(&window, &request_id, "delta", Some(delta), None, None);
+                    text.push_str(&delta);
+                    emit(ChatStreamEvent::Delta(delta));
                 }
-                emit_chat_stream(&window, &request_id, "done", None, None, None);
-                remove_active_stream(&request_id);
-                return Ok(content.trim().to_string());
-            }
-
-            let value = serde_json::from_str::<Value>(data)
-                .map_err(|error| format!("解析本地 AI 流响应失败: {}", error))?;
-            if let Some(stats) = extract_stream_stats(&value, request_ctx_size) {
-                emit_chat_stream(&window, &request_id, "stats", None, None, Some(stats));
-            }
-            if let Some(delta) = extract_stream_delta(
-                &value,
-                &mut reasoning_open,
-                enable_thinking.unwrap_or(false),
-            ) {
-                content.push_str(&delta);
-                emit_chat_stream(&window, &request_id, "delta", Some(delta), None, None);
+                return Ok(StreamRoundEnd::Completed {
+                    text,
+                    tool_calls: tool_calls.finish(),
+                });
             }
         }
     }
 
-    remove_active_stream(&request_id);
-    if !content.trim().is_empty() {
-        if reasoning_open {
-            emit_chat_stream(
-                &window,
-                &request_id,
-                "delta",
-                Some("</think>".to_string()),
-                None,
-                None,
-            );
-        }
-        return Err("本地 AI 流在完成前断开，已保留已生成内容。".to_string());
+    if !content.trim().is_empty() && reasoning_open {
+        emit(ChatStreamEvent::Delta("</think>".to_string()));
     }
-    Err("本地 AI 响应中没有可用内容".to_string())
+    Ok(StreamRoundEnd::Interrupted)
 }
 
 fn language_label(value: &str) -> &str {
@@ -1511,25 +2028,35 @@ fn language_label(value: &str) -> &str {
     }
 }
 
+/// 翻译用的系统提示词，也供 OpenAI 兼容翻译服务使用
+pub(crate) fn translation_prompt(from: &str, to: &str, glossary: &[GlossaryTerm]) -> String {
+    format!(
+        "You are a precise translation engine. Translate from {} to {}. Preserve Markdown, code fences, inline code, URLs, numbers, and line breaks, and keep placeholders such as ⟦#0⟧ exactly as written. Return only the translated text, with no explanations.{}",
+        language_label(from),
+        language_label(to),
+        glossary_prompt(glossary)
+    )
+}
+
 pub async fn translate_text(
     app_handle: AppHandle,
     text: String,
     from: String,
     to: String,
+    glossary: &[GlossaryTerm],
 ) -> Result<String, String> {
-    let system_prompt = format!(
-        "You are a precise translation engine. Translate from {} to {}. Preserve Markdown, code fences, inline code, URLs, numbers, and line breaks. Return only the translated text, with no explanations.",
-        language_label(&from),
-        language_label(&to)
-    );
     let messages = vec![
         LocalAiMessage {
             role: "system".to_string(),
-            content: Value::String(system_prompt),
+            content: Value::String(translation_prompt(&from, &to, glossary)),
+            tool_calls: None,
+            tool_call_id: None,
         },
         LocalAiMessage {
             role: "user".to_string(),
             content: Value::String(text),
+            tool_calls: None,
+            tool_call_id: None,
         },
     ];
     chat_completion(&app_handle, messages, Some(0.2), None, Some(false)).await
@@ -1568,6 +2095,33 @@ pub fn local_ai_scan_models(
     Ok(scan_model_dir(&config))
 }
 
+/// 列出当前服务提供方可用的模型；托管 llama-server 未运行时返回模型目录中扫描到的 GGUF 文件
+#[tauri::command]
+pub async fn local_ai_list_models(
+    app_handle: AppHandle,
+    config: Option<LocalAiConfig>,
+) -> Result<Vec<LocalAiProviderModel>, String> {
+    require_plugin(&app_handle)?;
+    let config = config.unwrap_or_else(|| read_config(&app_handle));
+    let provider = provider_for(&config);
+    if config.provider == LocalAiProviderKind::LlamaServer && !provider.health_check().await {
+        let scan = scan_model_dir(&config);
+        return Ok(scan
+            .main_models
+            .iter()
+            .map(|path| {
+                let info = find_model_info(&scan.models, path);
+                LocalAiProviderModel {
+                    id: path.clone(),
+                    size: Some(info.file_size),
+                    details: info.summary(),
+                }
+            })
+            .collect());
+    }
+    provider.list_models().await
+}
+
 #[tauri::command]
 pub fn local_ai_get_runtime_status(app_handle: AppHandle) -> Result<LocalAiRuntimeStatus, String> {
     require_plugin(&app_handle)?;
@@ -1591,10 +2145,49 @@ pub fn local_ai_get_runtime_status(app_handle: AppHandle) -> Result<LocalAiRunti
     })
 }
 
+// 外部服务没有本地进程，状态只反映可达性
+async fn external_provider_status(config: &LocalAiConfig) -> LocalAiServiceStatus {
+    let provider = provider_for(config);
+    let healthy = provider.health_check().await;
+    let ctx_size = if healthy {
+        provider.context_size().await.unwrap_or(config.ctx_size)
+    } else {
+        config.ctx_size
+    };
+    let active_requests = SERVICE_STATE
+        .lock()
+        .map(|state| state.active_requests)
+        .unwrap_or(0);
+    LocalAiServiceStatus {
+        provider: config.provider,
+        running: healthy,
+        healthy,
+        pid: None,
+        base_url: provider.base_url().to_string(),
+        model_path: config.provider_model.clone(),
+        runtime_path: None,
+        ctx_size,
+        command_line: None,
+        active_requests,
+        idle_timeout_minutes: config.idle_timeout_minutes,
+        keep_alive: config.keep_alive,
+        last_error: (!healthy).then(|| {
+            format!(
+                "无法连接 {}（{}）",
+                provider.kind().label(),
+                provider.base_url()
+            )
+        }),
+    }
+}
+
 #[tauri::command]
 pub async fn local_ai_get_status(app_handle: AppHandle) -> Result<LocalAiServiceStatus, String> {
     require_plugin(&app_handle)?;
     let config = read_config(&app_handle);
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        return Ok(external_provider_status(&config).await);
+    }
     let (
         running,
         pid,
@@ -1616,7 +2209,11 @@ pub async fn local_ai_get_status(app_handle: AppHandle) -> Result<LocalAiService
         } else {
             config.clone()
         };
-        let ctx_size = status_config.ctx_size;
+        let ctx_size = if running {
+            state.ctx_size.unwrap_or(status_config.ctx_size)
+        } else {
+            status_config.ctx_size
+        };
         (
             running,
             pid,
@@ -1642,6 +2239,7 @@ pub async fn local_ai_get_status(app_handle: AppHandle) -> Result<LocalAiService
     };
 
     Ok(LocalAiServiceStatus {
+        provider: LocalAiProviderKind::LlamaServer,
         running,
         healthy,
         pid,
@@ -1687,6 +2285,9 @@ pub fn local_ai_stop_service(app_handle: AppHandle) -> Result<(), String> {
     require_plugin(&app_handle)?;
     let config = read_config(&app_handle);
     stop_service_now();
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        return Ok(());
+    }
     if let Ok((model_path, _)) = resolve_model_paths(&config) {
         stop_orphan_llama_server_on_port(config.port, &model_path);
     }
@@ -1696,8 +2297,9 @@ pub fn local_ai_stop_service(app_handle: AppHandle) -> Result<(), String> {
 #[tauri::command]
 pub async fn local_ai_chat(
     app_handle: AppHandle,
-    request: LocalAiChatRequest,
+    mut request: LocalAiChatRequest,
 ) -> Result<LocalAiChatResponse, String> {
+    let sources = workspace_sources(&app_handle, &mut request).await?;
     let content = chat_completion(
         &app_handle,
         request.messages,
@@ -1706,16 +2308,24 @@ pub async fn local_ai_chat(
         request.enable_thinking,
     )
     .await?;
-    Ok(LocalAiChatResponse { content })
+    Ok(LocalAiChatResponse {
+        content,
+        sources,
+        tool_calls: Vec::new(),
+    })
 }
 
 #[tauri::command]
 pub async fn local_ai_chat_stream(
     app_handle: AppHandle,
     window: Window,
-    request: LocalAiChatRequest,
+    mut request: LocalAiChatRequest,
     request_id: String,
 ) -> Result<LocalAiChatResponse, String> {
+    let sources = workspace_sources(&app_handle, &mut request).await?;
+    if !sources.is_empty() {
+        emit_chat_sources(&window, &request_id, &sources);
+    }
     let result = match chat_completion_stream(
         &app_handle,
         window.clone(),
@@ -1724,10 +2334,15 @@ pub async fn local_ai_chat_stream(
         request.temperature,
         request.max_tokens,
         request.enable_thinking,
+        request.enable_tools,
     )
     .await
     {
-        Ok(content) => Ok(LocalAiChatResponse { content }),
+        Ok((content, tool_calls)) => Ok(LocalAiChatResponse {
+            content,
+            sources,
+            tool_calls,
+        }),
         Err(error) => {
             remove_active_stream(&request_id);
             emit_chat_stream(
@@ -1768,7 +2383,14 @@ pub async fn local_ai_translate(
     from: String,
     to: String,
 ) -> Result<String, String> {
-    translate_text(app_handle, text, from, to).await
+    // 翻译插件启用时同样应用用户术语表
+    let glossary = if crate::app_config::require_plugin_enabled(&app_handle, "translation").is_ok()
+    {
+        glossary_for(&text, &from, &to)
+    } else {
+        Vec::new()
+    };
+    translate_text(app_handle, text, from, to, &glossary).await
 }
 
 fn picked_attachment_error(
@@ -1907,51 +2529,6 @@ pub fn local_ai_read_attachment_files(
         .collect())
 }
 
-#[tauri::command]
-pub fn local_ai_get_chat_histories(
-    app_handle: AppHandle,
-) -> Result<Vec<LocalAiChatHistory>, String> {
-    require_plugin(&app_handle)?;
-    Ok(load_chat_histories(&app_handle))
-}
-
-#[tauri::command]
-pub fn local_ai_save_chat_history(
-    app_handle: AppHandle,
-    history: LocalAiChatHistory,
-) -> Result<Vec<LocalAiChatHistory>, String> {
-    require_plugin(&app_handle)?;
-    let mut histories = load_chat_histories(&app_handle);
-    if let Some(index) = histories.iter().position(|item| item.id == history.id) {
-        histories[index] = history;
-    } else {
-        histories.insert(0, history);
-    }
-    persist_chat_histories(&app_handle, &histories)?;
-    Ok(histories)
-}
-
-#[tauri::command]
-pub fn local_ai_delete_chat_history(
-    app_handle: AppHandle,
-    history_id: String,
-) -> Result<Vec<LocalAiChatHistory>, String> {
-    require_plugin(&app_handle)?;
-    let mut histories = load_chat_histories(&app_handle);
-    histories.retain(|item| item.id != history_id);
-    persist_chat_histories(&app_handle, &histories)?;
-    Ok(histories)
-}
-
-#[tauri::command]
-pub fn local_ai_clear_chat_histories(
-    app_handle: AppHandle,
-) -> Result<Vec<LocalAiChatHistory>, String> {
-    require_plugin(&app_handle)?;
-    persist_chat_histories(&app_handle, &[])?;
-    Ok(Vec::new())
-}
-
 pub fn apply_runtime_change(_app_handle: &AppHandle, enabled: bool) {
     if !enabled {
         stop_service_now();
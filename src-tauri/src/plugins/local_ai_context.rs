@@ -0,0 +1,596 @@
+// 本地 AI 上下文窗口管理
+//
+// 每次流式对话前统计消息的 token 数：托管的 llama-server 用 /tokenize 精确计数，
+// 其他服务或请求失败时按字符估算。超出提示词预算时按配置的策略压缩历史：
+// 丢弃最早的轮次、让模型把较早的轮次总结成摘要，或在丢弃时保留系统与附件消息。
+
+use crate::plugins::local_ai::{chat_completion, strip_think, LocalAiConfig, LocalAiMessage};
+use crate::plugins::local_ai_provider::LocalAiProvider;
+use crate::plugins::local_ai_rag::{estimate_tokens, inject_context, message_text};
+use futures::future::join_all;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{LazyLock, Mutex};
+use tauri::AppHandle;
+
+// 每条消息的模板开销（角色标记、分隔符）
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+// 图片在多模态模型中的大致 token 数
+const IMAGE_TOKENS: usize = 768;
+// 为聊天模板等无法统计的部分预留的窗口比例（1/20）
+const SAFETY_MARGIN_DIVISOR: usize = 20;
+// 生成摘要时保留的最近消息最多占预算的比例，留出余量让后续几轮复用同一摘要
+const SUMMARY_RECENT_SHARE: f32 = 0.5;
+const SUMMARY_MAX_TOKENS: u32 = 512;
+// 发给模型总结的对话记录约占预算的字符数倍数
+const SUMMARY_TRANSCRIPT_CHARS_PER_TOKEN: usize = 2;
+const TOKEN_CACHE_LIMIT: usize = 4096;
+const SUMMARY_CACHE_LIMIT: usize = 32;
+
+// 消息文本哈希 -> token 数；按模型区分
+static TOKEN_CACHE: LazyLock<Mutex<HashMap<u64, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+// 被总结的消息前缀哈希 -> 摘要；同一对话后续请求可直接复用
+static SUMMARY_CACHE: LazyLock<Mutex<HashMap<u64, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocalAiContextStrategy {
+    // 丢弃最早的轮次（开头的系统提示与最后一条消息始终保留）
+    #[default]
+    DropOldest,
+    // 让模型把较早的轮次总结为摘要并放入系统提示
+    Summarize,
+    // 丢弃最早的轮次，但保留所有系统消息与带附件的消息
+    PinImportant,
+}
+
+/// 压缩后的消息与本次请求的 token 使用情况
+pub(crate) struct ContextPlan {
+    pub messages: Vec<LocalAiMessage>,
+    pub prompt_tokens: usize,
+    pub budget: usize,
+    // 被丢弃或并入摘要的消息数
+    pub compacted: usize,
+    pub strategy: Option<LocalAiContextStrategy>,
+}
+
+/// 提示词预算：窗口减去回复预留与模板余量；回复上限过大时最多预留半个窗口
+pub(crate) fn prompt_budget(ctx_size: u32, max_tokens: i64) -> usize {
+    let ctx = ctx_size as usize;
+    let reserve = if max_tokens > 0 {
+        (max_tokens as usize).min(ctx / 2)
+    } else {
+        ctx / 4
+    };
+    ctx.saturating_sub(reserve + ctx / SAFETY_MARGIN_DIVISOR)
+}
+
+fn image_count(content: &Value) -> usize {
+    match content {
+        Value::Array(parts) => parts
+            .iter()
+            .filter(|part| part.get("type").and_then(Value::as_str) == Some("image_url"))
+            .count(),
+        _ => 0,
+    }
+}
+
+// 多段内容（图片或附带文件的文本段）视为附件消息
+fn has_attachment(message: &LocalAiMessage) -> bool {
+    match &message.content {
+        Value::Array(parts) => parts.len() > 1 || image_count(&message.content) > 0,
+        _ => false,
+    }
+}
+
+// 参与分词的文本：正文加上工具调用参数
+fn countable_text(message: &LocalAiMessage) -> String {
+    let mut text = message_text(&message.content);
+    if let Some(tool_calls) = &message.tool_calls {
+        text.push('\n');
+        text.push_str(&tool_calls.to_string());
+    }
+    text
+}
+
+fn estimate_message_tokens(message: &LocalAiMessage) -> usize {
+    estimate_tokens(&countable_text(message))
+        + image_count(&message.content) * IMAGE_TOKENS
+        + MESSAGE_OVERHEAD_TOKENS
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 统计每条消息的 token 数；服务端分词不可用时逐条退回估算
+pub(crate) async fn count_message_tokens(
+    provider: &dyn LocalAiProvider,
+    model_key: &str,
+    messages: &[LocalAiMessage],
+) -> Vec<usize> {
+    let counts = join_all(messages.iter().map(|message| async move {
+        let text = countable_text(message);
+        let key = hash_of(&(model_key, &text));
+        let cached = TOKEN_CACHE
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&key).copied());
+        let counted = match cached {
+            Some(count) => Some(count),
+            None => provider.count_tokens(&text).await,
+        };
+        if let (None, Some(count)) = (cached, counted) {
+            if let Ok(mut cache) = TOKEN_CACHE.lock() {
+                if cache.len() >= TOKEN_CACHE_LIMIT {
+                    cache.clear();
+                }
+                cache.insert(key, count);
+            }
+        }
+        match counted {
+            Some(count) => {
+                count + image_count(&message.content) * IMAGE_TOKENS + MESSAGE_OVERHEAD_TOKENS
+            }
+            None => estimate_message_tokens(message),
+        }
+    }))
+    .await;
+    counts
+}
+
+// 开头连续的系统消息（系统提示与工作区上下文）
+fn leading_system_count(messages: &[LocalAiMessage]) -> usize {
+    messages
+        .iter()
+        .take_while(|message| message.role == "system")
+        .count()
+}
+
+fn pinned_flags(messages: &[LocalAiMessage], strategy: LocalAiContextStrategy) -> Vec<bool> {
+    let leading = leading_system_count(messages);
+    let last = messages.len().saturating_sub(1);
+    messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| {
+            index < leading
+                || index == last
+                || (strategy == LocalAiContextStrategy::PinImportant
+                    && (message.role == "system" || has_attachment(message)))
+        })
+        .collect()
+}
+
+/// 从最早的消息开始丢弃，直到总数不超过预算；返回每条消息是否保留
+///
+/// assistant 消息被丢弃时一并丢弃紧随其后的 tool 结果；丢弃后保留的对话
+/// 不以 assistant / tool 开头，避免部分聊天模板要求的 user/assistant 交替被破坏。
+pub(crate) fn drop_oldest(
+    messages: &[LocalAiMessage],
+    tokens: &[usize],
+    pinned: &[bool],
+    fixed_tokens: usize,
+    budget: usize,
+) -> Vec<bool> {
+    let mut kept = vec![true; messages.len()];
+    let mut total = fixed_tokens + tokens.iter().sum::<usize>();
+    let mut index = 0;
+    while index < messages.len() && total > budget {
+        if pinned[index] || !kept[index] {
+            index += 1;
+            continue;
+        }
+        kept[index] = false;
+        total -= tokens[index];
+        let mut next = index + 1;
+        while next < messages.len() && messages[next].role == "tool" && !pinned[next] {
+            if kept[next] {
+                kept[next] = false;
+                total -= tokens[next];
+            }
+            next += 1;
+        }
+        index = next;
+    }
+
+    if kept.iter().any(|keep| !keep) {
+        for index in 0..messages.len() {
+            if !kept[index] || messages[index].role == "system" {
+                continue;
+            }
+            if messages[index].role == "user" || pinned[index] {
+                break;
+            }
+            kept[index] = false;
+        }
+    }
+    kept
+}
+
+fn apply_kept(messages: Vec<LocalAiMessage>, kept: &[bool]) -> Vec<LocalAiMessage> {
+    messages
+        .into_iter()
+        .zip(kept)
+        .filter_map(|(message, keep)| keep.then_some(message))
+        .collect()
+}
+
+/// 为总结准备的对话记录；过长时保留较新的部分
+pub(crate) fn summary_transcript(messages: &[LocalAiMessage], max_chars: usize) -> String {
+    let transcript = messages
+        .iter()
+        .filter(|message| message.role == "user" || message.role == "assistant")
+        .map(|message| {
+            let role = if message.role == "user" {
+                "用户"
+            } else {
+                "助手"
+            };
+            format!(
+                "{}：{}",
+                role,
+                strip_think(&message_text(&message.content)).trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let chars = transcript.chars().count();
+    if chars <= max_chars {
+        return transcript;
+    }
+    let tail: String = transcript.chars().skip(chars - max_chars).collect();
+    format!("…{}", tail)
+}
+
+/// 选择摘要分界：之后的消息不超过 target，且从一条 user 消息开始
+pub(crate) fn summary_split(
+    messages: &[LocalAiMessage],
+    tokens: &[usize],
+    start: usize,
+    target: usize,
+) -> Option<usize> {
+    let last_user = (start..messages.len())
+        .rev()
+        .find(|index| messages[*index].role == "user")?;
+    let mut suffix: usize = tokens[last_user..].iter().sum();
+    let mut split = last_user;
+    for index in (start..last_user).rev() {
+        suffix += tokens[index];
+        if suffix > target {
+            break;
+        }
+        if messages[index].role == "user" {
+            split = index;
+        }
+    }
+    (split > start).then_some(split)
+}
+
+fn summary_key(model_key: &str, messages: &[LocalAiMessage]) -> u64 {
+    let texts: Vec<(&str, String)> = messages
+        .iter()
+        .map(|message| (message.role.as_str(), message_text(&message.content)))
+        .collect();
+    hash_of(&(model_key, texts))
+}
+
+async fn summarize(
+    app_handle: &AppHandle,
+    messages: &[LocalAiMessage],
+    budget: usize,
+) -> Result<String, String> {
+    let prompt = vec![
+        LocalAiMessage {
+            role: "system".to_string(),
+            content: Value::String(
+                "你是对话记录整理助手。请把下面的对话压缩成简洁的要点摘要，保留用户的目标、已确认的事实、结论、代码或配置中的关键名称与数值，以及仍未解决的问题。只输出摘要本身，使用与对话相同的语言。"
+                    .to_string(),
+            ),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+        LocalAiMessage {
+            role: "user".to_string(),
+            content: Value::String(summary_transcript(
+                messages,
+                budget * SUMMARY_TRANSCRIPT_CHARS_PER_TOKEN,
+            )),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+    ];
+    let reply = chat_completion(
+        app_handle,
+        prompt,
+        Some(0.2),
+        Some(SUMMARY_MAX_TOKENS),
+        Some(false),
+    )
+    .await?;
+    let summary = strip_think(&reply).trim().to_string();
+    if summary.is_empty() {
+        return Err("模型未返回摘要".to_string());
+    }
+    Ok(summary)
+}
+
+fn summary_context(summary: &str) -> String {
+    format!("以下是此前对话的摘要，供继续对话时参考：\n{}", summary)
+}
+
+fn with_summary(
+    messages: &[LocalAiMessage],
+    leading: usize,
+    split: usize,
+    summary: &str,
+) -> Vec<LocalAiMessage> {
+    let mut compacted = messages[..leading].to_vec();
+    compacted.extend_from_slice(&messages[split..]);
+    inject_context(&mut compacted, &summary_context(summary));
+    compacted
+}
+
+// 先找能复用的已有摘要，否则让模型总结分界之前的消息
+async fn summarize_older_turns(
+    app_handle: &AppHandle,
+    provider: &dyn LocalAiProvider,
+    model_key: &str,
+    messages: &[LocalAiMessage],
+    tokens: &[usize],
+    fixed_tokens: usize,
+    budget: usize,
+) -> Option<(Vec<LocalAiMessage>, usize)> {
+    let leading = leading_system_count(messages);
+    for split in (leading + 1..messages.len()).rev() {
+        let key = summary_key(model_key, &messages[leading..split]);
+        let cached = SUMMARY_CACHE
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&key).cloned());
+        let Some(summary) = cached else {
+            continue;
+        };
+        let candidate = with_summary(messages, leading, split, &summary);
+        let total: usize = fixed_tokens
+            + count_message_tokens(provider, model_key, &candidate)
+                .await
+                .iter()
+                .sum::<usize>();
+        if total <= budget {
+            return Some((candidate, split - leading));
+        }
+    }
+
+    let target = (budget as f32 * SUMMARY_RECENT_SHARE) as usize;
+    let split = summary_split(messages, tokens, leading, target)?;
+    match summarize(app_handle, &messages[leading..split], budget).await {
+        Ok(summary) => {
+            if let Ok(mut cache) = SUMMARY_CACHE.lock() {
+                if cache.len() >= SUMMARY_CACHE_LIMIT {
+                    cache.clear();
+                }
+                cache.insert(
+                    summary_key(model_key, &messages[leading..split]),
+                    summary.clone(),
+                );
+            }
+            Some((
+                with_summary(messages, leading, split, &summary),
+                split - leading,
+            ))
+        }
+        Err(error) => {
+            warn!(
+                "⚠️ [LocalAI] 总结早期对话失败，改为丢弃最早的轮次: {}",
+                error
+            );
+            None
+        }
+    }
+}
+
+/// 统计 token 并在超出预算时按配置的策略压缩消息
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn fit_context(
+    app_handle: &AppHandle,
+    config: &LocalAiConfig,
+    provider: &dyn LocalAiProvider,
+    model_key: &str,
+    messages: Vec<LocalAiMessage>,
+    ctx_size: u32,
+    max_tokens: i64,
+    fixed_tokens: usize,
+) -> ContextPlan {
+    let budget = prompt_budget(ctx_size, max_tokens);
+    let tokens = count_message_tokens(provider, model_key, &messages).await;
+    let total = fixed_tokens + tokens.iter().sum::<usize>();
+    if total <= budget {
+        return ContextPlan {
+            messages,
+            prompt_tokens: total,
+            budget,
+            compacted: 0,
+            strategy: None,
+        };
+    }
+
+    let strategy = config.context_strategy;
+    let (messages, compacted) = if strategy == LocalAiContextStrategy::Summarize {
+        match summarize_older_turns(
+            app_handle,
+            provider,
+            model_key,
+            &messages,
+            &tokens,
+            fixed_tokens,
+            budget,
+        )
+        .await
+        {
+            Some((summarized, count)) => (summarized, count),
+            None => (messages, 0),
+        }
+    } else {
+        (messages, 0)
+    };
+
+    // 摘要后仍然超出（或其他策略）时丢弃最早的轮次
+    let tokens = if compacted > 0 {
+        count_message_tokens(provider, model_key, &messages).await
+    } else {
+        tokens
+    };
+    let pinned = pinned_flags(&messages, strategy);
+    let kept = drop_oldest(&messages, &tokens, &pinned, fixed_tokens, budget);
+    let dropped = kept.iter().filter(|keep| !**keep).count();
+    let prompt_tokens = fixed_tokens
+        + tokens
+            .iter()
+            .zip(&kept)
+            .filter_map(|(count, keep)| keep.then_some(*count))
+            .sum::<usize>();
+    let messages = apply_kept(messages, &kept);
+    if prompt_tokens > budget {
+        warn!(
+            "⚠️ [LocalAI] 压缩后提示词仍超出预算（{} > {} tokens），请求可能被服务端截断",
+            prompt_tokens, budget
+        );
+    }
+    info!(
+        "✂️ [LocalAI] 上下文压缩：策略 {:?}，{} 条消息被处理，{} -> {} tokens（预算 {}）",
+        strategy,
+        compacted + dropped,
+        total,
+        prompt_tokens,
+        budget
+    );
+    ContextPlan {
+        messages,
+        prompt_tokens,
+        budget,
+        compacted: compacted + dropped,
+        strategy: Some(strategy),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(role: &str, content: Value) -> LocalAiMessage {
+        LocalAiMessage {
+            role: role.to_string(),
+            content,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    fn text(role: &str, text: &str) -> LocalAiMessage {
+        message(role, Value::String(text.to_string()))
+    }
+
+    fn roles(messages: &[LocalAiMessage], kept: &[bool]) -> Vec<String> {
+        messages
+            .iter()
+            .zip(kept)
+            .filter(|(_, keep)| **keep)
+            .map(|(message, _)| message.role.clone())
+            .collect()
+    }
+
+    #[test]
+    fn budget_reserves_completion_and_margin() {
+        assert_eq!(prompt_budget(8192, 1024), 8192 - 1024 - 409);
+        assert_eq!(prompt_budget(8192, -1), 8192 - 2048 - 409);
+        // 回复上限超过窗口时最多预留一半
+        assert_eq!(prompt_budget(4096, 100_000), 4096 - 2048 - 204);
+    }
+
+    #[test]
+    fn drops_oldest_turns_and_keeps_pinned_messages() {
+        let messages = vec![
+            text("system", "prompt"),
+            text("user", "q1"),
+            text("assistant", "a1"),
+            message(
+                "user",
+                json!([
+                    { "type": "text", "text": "看这张图" },
+                    { "type": "image_url", "image_url": { "url": "data:," } }
+                ]),
+            ),
+            text("assistant", "a2"),
+            text("user", "q3"),
+        ];
+        let tokens = vec![10, 10, 10, 10, 10, 10];
+
+        let pinned = pinned_flags(&messages, LocalAiContextStrategy::DropOldest);
+        let kept = drop_oldest(&messages, &tokens, &pinned, 0, 40);
+        assert_eq!(
+            roles(&messages, &kept),
+            ["system", "user", "assistant", "user"]
+        );
+        assert!(!kept[1] && !kept[2] && kept[3]);
+
+        let pinned = pinned_flags(&messages, LocalAiContextStrategy::PinImportant);
+        let kept = drop_oldest(&messages, &tokens, &pinned, 0, 25);
+        assert!(kept[0] && kept[3] && kept[5]);
+        assert!(!kept[4]);
+
+        // 未超预算时原样保留
+        let kept = drop_oldest(&messages, &tokens, &pinned, 0, 100);
+        assert!(kept.iter().all(|keep| *keep));
+    }
+
+    #[test]
+    fn drops_tool_results_with_their_call_and_never_starts_with_assistant() {
+        let messages = vec![
+            text("user", "q1"),
+            text("assistant", "calling"),
+            text("tool", "result"),
+            text("assistant", "a1"),
+            text("user", "q2"),
+        ];
+        let tokens = vec![5, 5, 50, 5, 5];
+        let pinned = pinned_flags(&messages, LocalAiContextStrategy::DropOldest);
+        let kept = drop_oldest(&messages, &tokens, &pinned, 0, 20);
+        assert_eq!(roles(&messages, &kept), ["user"]);
+    }
+
+    #[test]
+    fn splits_summary_at_user_boundary() {
+        let messages = vec![
+            text("system", "prompt"),
+            text("user", "q1"),
+            text("assistant", "a1"),
+            text("user", "q2"),
+            text("assistant", "a2"),
+            text("user", "q3"),
+        ];
+        let tokens = vec![5, 40, 40, 10, 10, 10];
+        assert_eq!(summary_split(&messages, &tokens, 1, 30), Some(3));
+        // 最后一条消息本身就超出目标时只保留它
+        assert_eq!(summary_split(&messages, &tokens, 1, 5), Some(5));
+        // 没有可以总结的更早消息
+        assert_eq!(summary_split(&messages, &tokens, 1, 1000), None);
+
+        let summarized = with_summary(&messages, 1, 3, "用户在问 q1");
+        assert_eq!(summarized.len(), 4);
+        assert!(message_text(&summarized[0].content).contains("用户在问 q1"));
+
+        let transcript = summary_transcript(&messages[1..3], 100);
+        assert_eq!(transcript, "用户：q1\n\n助手：a1");
+        assert_eq!(summary_transcript(&messages[1..3], 4), "…手：a1");
+    }
+}
@@ -0,0 +1,1014 @@
+// 本地 AI 语义搜索
+//
+// 将笔记按标题段落与代码块切分为片段，调用本地 AI 运行时（llama-server --embeddings）或
+// 独立的 OpenAI 兼容服务的 /v1/embeddings 生成向量，连同内容哈希存入 SQLite。
+// 再次同步时只对哈希变化的片段重新请求向量；搜索时按余弦相似度排序，
+// 混合模式再与 OptimizedIndexManager 的关键词得分加权合并。
+
+use crate::db::open_plugin_store;
+use crate::json_config::get_workspace_root;
+use crate::markdown::commands::{search_result_to_markdown_file, MarkdownFile};
+use crate::markdown::file_ops::get_relative_path;
+use crate::markdown::index_optimized::IndexEntry;
+use crate::markdown::{CacheManager, IndexManager};
+use crate::plugins::local_ai::{
+    base_url, ensure_service_running, mark_request_started, read_config, require_plugin,
+    LocalAiConfig,
+};
+use crate::plugins::local_ai_provider::{provider_for, LocalAiProvider, LocalAiProviderKind};
+use crate::text_utils::{sha256_hex, truncate_chars};
+use log::{info, warn};
+use reqwest::Client;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+const PROGRESS_EVENT: &str = "local-ai-embeddings-progress";
+// 单个文本片段的目标长度（字符），超出时在空行处切分
+const CHUNK_TARGET_CHARS: usize = 1200;
+// 发送给向量模型的单条输入上限（字符）
+const EMBED_INPUT_MAX_CHARS: usize = 2000;
+const EMBED_BATCH_SIZE: usize = 16;
+const PREVIEW_CHARS: usize = 160;
+const DEFAULT_LIMIT: usize = 20;
+// 混合搜索中向量得分的权重，其余为关键词得分
+const HYBRID_VECTOR_WEIGHT: f32 = 0.6;
+// 纯语义模式下低于该相似度的结果不返回
+const MIN_SEMANTIC_SCORE: f32 = 0.2;
+
+// ============= 切分 =============
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteChunk {
+    pub index: usize,
+    // 所属标题路径，如 "安装 / Windows"
+    pub heading: String,
+    // "text" 或 "code"
+    pub kind: &'static str,
+    pub text: String,
+    // 向量输入（标题 + 段落标题 + 正文）的 SHA-256
+    pub hash: String,
+}
+
+fn heading_level(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+fn fence_marker(line: &str) -> Option<&'static str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("```") {
+        Some("```")
+    } else if trimmed.starts_with("~~~") {
+        Some("~~~")
+    } else {
+        None
+    }
+}
+
+fn embedding_input(title: &str, heading: &str, text: &str) -> String {
+    let mut input = String::from(title.trim());
+    if !heading.is_empty() {
+        input.push('\n');
+        input.push_str(heading);
+    }
+    input.push('\n');
+    input.push_str(text);
+    truncate_chars(&input, EMBED_INPUT_MAX_CHARS).to_string()
+}
+
+// 将过长的文本段在空行处切成若干不超过目标长度的部分
+fn split_long_text(text: &str) -> Vec<String> {
+    if text.chars().count() <= CHUNK_TARGET_CHARS {
+        return vec![text.to_string()];
+    }
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n") {
+        let paragraph = paragraph.trim();
+        if paragraph.is_empty() {
+            continue;
+        }
+        if !current.is_empty()
+            && current.chars().count() + paragraph.chars().count() > CHUNK_TARGET_CHARS
+        {
+            parts.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// 按 ATX 标题切分正文，围栏代码块单独成片段
+pub fn chunk_note(title: &str, content: &str) -> Vec<NoteChunk> {
+    let mut pieces: Vec<(String, &'static str, String)> = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut text = String::new();
+    let mut code = String::new();
+    let mut fence: Option<&'static str> = None;
+
+    let heading_path = |headings: &[(usize, String)]| {
+        headings
+            .iter()
+            .map(|(_, h)| h.as_str())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    };
+    let flush_text =
+        |pieces: &mut Vec<(String, &'static str, String)>, text: &mut String, heading: String| {
+            let trimmed = text.trim();
+            if !trimmed.is_empty() {
+                for part in split_long_text(trimmed) {
+                    pieces.push((heading.clone(), "text", part));
+                }
+            }
+            text.clear();
+        };
+
+    for line in content.lines() {
+        if let Some(marker) = fence {
+            code.push_str(line);
+            code.push('\n');
+            if line.trim_start().starts_with(marker) {
+                fence = None;
+                let block = code.trim().to_string();
+                if !block.is_empty() {
+                    pieces.push((heading_path(&headings), "code", block));
+                }
+                code.clear();
+            }
+            continue;
+        }
+        if let Some(marker) = fence_marker(line) {
+            flush_text(&mut pieces, &mut text, heading_path(&headings));
+            fence = Some(marker);
+            code.push_str(line);
+            code.push('\n');
+            continue;
+        }
+        if let Some((level, heading)) = heading_level(line) {
+            flush_text(&mut pieces, &mut text, heading_path(&headings));
+            headings.retain(|(l, _)| *l < level);
+            if !heading.is_empty() {
+                headings.push((level, heading.to_string()));
+            }
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    // 未闭合的围栏按代码处理
+    let block = code.trim();
+    if !block.is_empty() {
+        pieces.push((heading_path(&headings), "code", block.to_string()));
+    }
+    flush_text(&mut pieces, &mut text, heading_path(&headings));
+
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(index, (heading, kind, text))| {
+            let hash = sha256_hex(&embedding_input(title, &heading, &text));
+            NoteChunk {
+                index,
+                heading,
+                kind,
+                text,
+                hash,
+            }
+        })
+        .collect()
+}
+
+// ============= 向量服务 =============
+
+pub struct EmbeddingClient {
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    timeout: Duration,
+}
+
+fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+impl EmbeddingClient {
+    pub fn new(base_url: &str, model: &str, timeout: Duration) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: None,
+            timeout,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// 请求 OpenAI 兼容的 /v1/embeddings，返回与输入顺序一致的单位向量
+    pub async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let client = Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|error| format!("创建向量请求客户端失败: {}", error))?;
+        let mut request = client.post(format!("{}/v1/embeddings", self.base_url));
+        if let Some(key) = self.api_key.as_deref() {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .json(&serde_json::json!({
+                "model": self.model,
+                "input": inputs,
+            }))
+            .send()
+            .await
+            .map_err(|error| format!("请求向量服务失败: {}", error))?;
+        let status = response.status();
+        let value = response
+            .json::<Value>()
+            .await
+            .map_err(|error| format!("解析向量服务响应失败: {}", error))?;
+        if !status.is_success() {
+            return Err(format!("向量服务返回错误 {}: {}", status, value));
+        }
+
+        let data = value
+            .get("data")
+            .and_then(|data| data.as_array())
+            .ok_or_else(|| "向量服务响应缺少 data 字段".to_string())?;
+        let mut vectors: Vec<(usize, Vec<f32>)> = data
+            .iter()
+            .enumerate()
+            .map(|(position, item)| {
+                let index = item
+                    .get("index")
+                    .and_then(|index| index.as_u64())
+                    .map(|index| index as usize)
+                    .unwrap_or(position);
+                let mut vector: Vec<f32> = item
+                    .get("embedding")
+                    .and_then(|embedding| embedding.as_array())
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|v| v.as_f64())
+                            .map(|v| v as f32)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                l2_normalize(&mut vector);
+                (index, vector)
+            })
+            .collect();
+        vectors.sort_by_key(|(index, _)| *index);
+
+        if vectors.len() != inputs.len() || vectors.iter().any(|(_, v)| v.is_empty()) {
+            return Err(format!(
+                "向量服务返回数量不符: 期望 {}，实际 {}",
+                inputs.len(),
+                vectors.len()
+            ));
+        }
+        Ok(vectors.into_iter().map(|(_, vector)| vector).collect())
+    }
+}
+
+// 未配置独立向量服务时使用当前服务提供方：托管的 llama-server 取模型文件名，外部服务取地址和模型名
+fn embedding_model_id(config: &LocalAiConfig) -> String {
+    if let Some(url) = external_embedding_url(config) {
+        return format!("external:{}", url);
+    }
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        let provider = provider_for(config);
+        return format!("{}#{}", provider.base_url(), provider.model_name());
+    }
+    let model = config
+        .model_path
+        .as_deref()
+        .filter(|path| !path.trim().is_empty())
+        .unwrap_or(&config.model_dir);
+    Path::new(model)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("local-ai")
+        .to_string()
+}
+
+fn external_embedding_url(config: &LocalAiConfig) -> Option<String> {
+    config
+        .embedding_base_url
+        .as_deref()
+        .map(|url| url.trim().trim_end_matches('/'))
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+}
+
+async fn embedding_client(
+    app_handle: &AppHandle,
+    config: &LocalAiConfig,
+) -> Result<EmbeddingClient, String> {
+    let timeout = Duration::from_secs(config.request_timeout_secs.max(1) as u64);
+    let model = embedding_model_id(config);
+    if let Some(url) = external_embedding_url(config) {
+        return Ok(EmbeddingClient::new(&url, &model, timeout));
+    }
+    // 外部服务使用同一地址的 /v1/embeddings（Ollama 也提供该兼容接口）
+    if config.provider != LocalAiProviderKind::LlamaServer {
+        ensure_service_running(app_handle, config).await?;
+        let provider = provider_for(config);
+        return Ok(
+            EmbeddingClient::new(provider.base_url(), provider.model_name(), timeout)
+                .with_api_key(config.api_key.clone().filter(|key| !key.trim().is_empty())),
+        );
+    }
+    if !config.embeddings {
+        return Err("本地 AI 未启用向量接口，请在本地 AI 设置中开启 embeddings".to_string());
+    }
+    ensure_service_running(app_handle, config).await?;
+    Ok(EmbeddingClient::new(&base_url(config), &model, timeout))
+}
+
+// ============= 存储 =============
+
+pub(crate) fn create_embedding_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS note_embeddings (
+             workspace TEXT NOT NULL,
+             file_path TEXT NOT NULL,
+             chunk_index INTEGER NOT NULL,
+             content_hash TEXT NOT NULL,
+             heading TEXT NOT NULL,
+             kind TEXT NOT NULL,
+             preview TEXT NOT NULL,
+             model TEXT NOT NULL,
+             dims INTEGER NOT NULL,
+             vector BLOB NOT NULL,
+             updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+             PRIMARY KEY (workspace, file_path, chunk_index)
+         );
+         CREATE INDEX IF NOT EXISTS idx_note_embeddings_hash
+             ON note_embeddings(workspace, content_hash);",
+    )
+}
+
+fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn blob_to_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// 待同步的笔记（相对路径、标题、正文）
+pub struct EmbeddingNote {
+    pub file_path: String,
+    pub title: String,
+    pub content: String,
+}
+
+struct PlannedChunk {
+    chunk: NoteChunk,
+    input: String,
+    vector: Option<Vec<f32>>,
+}
+
+struct PlannedFile {
+    file_path: String,
+    chunks: Vec<PlannedChunk>,
+}
+
+/// 同步计划：只有 files 中的文件需要重写，removed 为已不存在的文件
+pub struct EmbeddingPlan {
+    files: Vec<PlannedFile>,
+    removed: Vec<String>,
+    stats: SyncStats,
+}
+
+impl EmbeddingPlan {
+    fn pending(&self) -> usize {
+        self.files
+            .iter()
+            .flat_map(|file| &file.chunks)
+            .filter(|chunk| chunk.vector.is_none())
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStats {
+    pub files: usize,
+    pub chunks: usize,
+    pub embedded: usize,
+    pub reused: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EmbeddingProgress {
+    done: usize,
+    total: usize,
+}
+
+/// 对比数据库中已有向量，生成同步计划（同一模型下哈希相同的片段直接复用）
+pub fn plan_embeddings(
+    conn: &Connection,
+    workspace: &str,
+    model: &str,
+    notes: &[EmbeddingNote],
+) -> Result<EmbeddingPlan, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_path, chunk_index, content_hash, vector FROM note_embeddings
+             WHERE workspace = ?1 AND model = ?2",
+        )
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![workspace, model], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Vec<u8>>(3)?,
+            ))
+        })
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+
+    let mut stored_by_file: HashMap<String, Vec<(i64, String)>> = HashMap::new();
+    let mut vectors_by_hash: HashMap<String, Vec<f32>> = HashMap::new();
+    for row in rows {
+        let (file_path, chunk_index, hash, blob) =
+            row.map_err(|e| format!("读取向量失败: {}", e))?;
+        stored_by_file
+            .entry(file_path)
+            .or_default()
+            .push((chunk_index, hash.clone()));
+        vectors_by_hash
+            .entry(hash)
+            .or_insert_with(|| blob_to_vector(&blob));
+    }
+    drop(stmt);
+
+    // 其他模型生成的行同样计入，以便重写或删除
+    let mut row_counts: HashMap<String, usize> = HashMap::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_path, COUNT(*) FROM note_embeddings WHERE workspace = ?1
+             GROUP BY file_path",
+        )
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![workspace], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+    for row in rows {
+        let (file_path, count) = row.map_err(|e| format!("读取向量失败: {}", e))?;
+        row_counts.insert(file_path, count as usize);
+    }
+
+    let mut stats = SyncStats {
+        files: notes.len(),
+        ..Default::default()
+    };
+    let mut files = Vec::new();
+    let mut present = HashSet::new();
+    for note in notes {
+        present.insert(note.file_path.as_str());
+        let chunks = chunk_note(&note.title, &note.content);
+        stats.chunks += chunks.len();
+
+        let mut stored = stored_by_file
+            .get(&note.file_path)
+            .cloned()
+            .unwrap_or_default();
+        stored.sort();
+        let unchanged = stored.len() == chunks.len()
+            && row_counts.get(&note.file_path).copied().unwrap_or(0) == chunks.len()
+            && stored.iter().zip(&chunks).all(|((index, hash), chunk)| {
+                *index as usize == chunk.index && *hash == chunk.hash
+            });
+        if unchanged {
+            stats.reused += chunks.len();
+            continue;
+        }
+
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| {
+                let vector = vectors_by_hash.get(&chunk.hash).cloned();
+                if vector.is_some() {
+                    stats.reused += 1;
+                }
+                let input = embedding_input(&note.title, &chunk.heading, &chunk.text);
+                PlannedChunk {
+                    chunk,
+                    input,
+                    vector,
+                }
+            })
+            .collect();
+        files.push(PlannedFile {
+            file_path: note.file_path.clone(),
+            chunks,
+        });
+    }
+
+    let removed: Vec<String> = row_counts
+        .into_keys()
+        .filter(|file_path| !present.contains(file_path.as_str()))
+        .collect();
+    Ok(EmbeddingPlan {
+        files,
+        removed,
+        stats,
+    })
+}
+
+/// 为计划中缺少向量的片段分批请求向量
+pub async fn embed_pending(
+    client: &EmbeddingClient,
+    plan: &mut EmbeddingPlan,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<(), String> {
+    let total = plan.pending();
+    let mut done = 0;
+    let mut targets: Vec<&mut PlannedChunk> = plan
+        .files
+        .iter_mut()
+        .flat_map(|file| file.chunks.iter_mut())
+        .filter(|chunk| chunk.vector.is_none())
+        .collect();
+
+    for batch in targets.chunks_mut(EMBED_BATCH_SIZE) {
+        let inputs: Vec<String> = batch.iter().map(|chunk| chunk.input.clone()).collect();
+        let vectors = client.embed(&inputs).await?;
+        for (chunk, vector) in batch.iter_mut().zip(vectors) {
+            chunk.vector = Some(vector);
+        }
+        done += batch.len();
+        on_progress(done, total);
+    }
+    plan.stats.embedded += done;
+    Ok(())
+}
+
+/// 将计划写入数据库：逐文件替换片段行，并删除已不存在文件的向量
+pub fn apply_plan(
+    conn: &mut Connection,
+    workspace: &str,
+    model: &str,
+    plan: EmbeddingPlan,
+) -> Result<SyncStats, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    for file in &plan.files {
+        tx.execute(
+            "DELETE FROM note_embeddings WHERE workspace = ?1 AND file_path = ?2",
+            params![workspace, file.file_path],
+        )
+        .map_err(|e| format!("清理旧向量失败: {}", e))?;
+        for planned in &file.chunks {
+            let Some(vector) = planned.vector.as_ref() else {
+                continue;
+            };
+            let preview = truncate_chars(&planned.chunk.text, PREVIEW_CHARS);
+            tx.execute(
+                "INSERT INTO note_embeddings
+                     (workspace, file_path, chunk_index, content_hash, heading, kind, preview,
+                      model, dims, vector, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+                params![
+                    workspace,
+                    file.file_path,
+                    planned.chunk.index as i64,
+                    planned.chunk.hash,
+                    planned.chunk.heading,
+                    planned.chunk.kind,
+                    preview,
+                    model,
+                    vector.len() as i64,
+                    vector_to_blob(vector),
+                ],
+            )
+            .map_err(|e| format!("写入向量失败: {}", e))?;
+        }
+    }
+    for file_path in &plan.removed {
+        tx.execute(
+            "DELETE FROM note_embeddings WHERE workspace = ?1 AND file_path = ?2",
+            params![workspace, file_path],
+        )
+        .map_err(|e| format!("删除向量失败: {}", e))?;
+    }
+    tx.commit().map_err(|e| format!("提交向量失败: {}", e))?;
+
+    let mut stats = plan.stats;
+    stats.removed = plan.removed.len();
+    Ok(stats)
+}
+
+// ============= 排序 =============
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// 按余弦相似度为文件打分，每个文件取最相似的片段（向量已归一化）
+pub fn rank_by_vector(
+    conn: &Connection,
+    workspace: &str,
+    model: &str,
+    query: &[f32],
+) -> Result<Vec<(String, f32)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_path, vector FROM note_embeddings
+             WHERE workspace = ?1 AND model = ?2 AND dims = ?3",
+        )
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![workspace, model, query.len() as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+
+    let mut best: HashMap<String, f32> = HashMap::new();
+    for row in rows {
+        let (file_path, blob) = row.map_err(|e| format!("读取向量失败: {}", e))?;
+        let score = dot(query, &blob_to_vector(&blob));
+        let entry = best.entry(file_path).or_insert(f32::MIN);
+        if score > *entry {
+            *entry = score;
+        }
+    }
+    let mut ranked: Vec<(String, f32)> = best.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(ranked)
+}
+
+/// 合并向量得分与关键词得分：关键词得分按最大值归一化，向量得分截断到 0..1
+pub fn merge_hybrid(
+    vector_scores: &[(String, f32)],
+    keyword_scores: &[(String, f32)],
+    vector_weight: f32,
+) -> Vec<(String, f32)> {
+    let max_keyword = keyword_scores
+        .iter()
+        .map(|(_, score)| *score)
+        .fold(0.0f32, f32::max);
+    let mut merged: HashMap<&str, f32> = HashMap::new();
+    for (file_path, score) in vector_scores {
+        *merged.entry(file_path).or_default() += vector_weight * score.clamp(0.0, 1.0);
+    }
+    if max_keyword > 0.0 {
+        for (file_path, score) in keyword_scores {
+            *merged.entry(file_path).or_default() += (1.0 - vector_weight) * (score / max_keyword);
+        }
+    }
+    let mut ranked: Vec<(String, f32)> = merged
+        .into_iter()
+        .map(|(file_path, score)| (file_path.to_string(), score))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+// ============= 命令 =============
+
+fn index_entries(
+    index_manager: &State<'_, Arc<RwLock<Option<IndexManager>>>>,
+) -> Result<Vec<IndexEntry>, String> {
+    let manager_lock = index_manager
+        .read()
+        .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+    let manager = manager_lock
+        .as_ref()
+        .ok_or_else(|| "索引尚未建立，请稍后重试".to_string())?;
+    Ok(manager.get_all_entries())
+}
+
+/// 同步当前工作区所有笔记的向量，只为新增或变化的片段请求向量服务
+#[tauri::command]
+pub async fn local_ai_refresh_embeddings(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+) -> Result<SyncStats, String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let workspace = workspace_root.to_string_lossy().to_string();
+    let notes: Vec<EmbeddingNote> = index_entries(&index_manager)?
+        .into_iter()
+        .filter_map(|entry| {
+            Some(EmbeddingNote {
+                file_path: get_relative_path(&workspace_root, &entry.file_path).ok()?,
+                title: entry.title,
+                content: entry.full_content,
+            })
+        })
+        .collect();
+
+    let config = read_config(&app_handle);
+    let model = embedding_model_id(&config);
+    let mut plan = {
+        let conn = open_plugin_store()?;
+        plan_embeddings(&conn, &workspace, &model, &notes)?
+    };
+
+    let pending = plan.pending();
+    if pending > 0 {
+        let client = embedding_client(&app_handle, &config).await?;
+        let _guard = mark_request_started();
+        let _ = app_handle.emit(
+            PROGRESS_EVENT,
+            EmbeddingProgress {
+                done: 0,
+                total: pending,
+            },
+        );
+        embed_pending(&client, &mut plan, |done, total| {
+            let _ = app_handle.emit(PROGRESS_EVENT, EmbeddingProgress { done, total });
+        })
+        .await?;
+    }
+
+    let mut conn = open_plugin_store()?;
+    let stats = apply_plan(&mut conn, &workspace, &model, plan)?;
+    info!(
+        "🧠 [语义搜索] 同步完成: {} 个文件，{} 个片段，新生成 {}，复用 {}，删除 {} 个文件",
+        stats.files, stats.chunks, stats.embedded, stats.reused, stats.removed
+    );
+    Ok(stats)
+}
+
+/// 语义搜索；mode 为 "hybrid" 时与关键词索引得分合并
+#[tauri::command]
+pub async fn local_ai_semantic_search(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    query: String,
+    mode: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<MarkdownFile>, String> {
+    require_plugin(&app_handle)?;
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let hybrid = match mode.as_deref().unwrap_or("semantic") {
+        "semantic" => false,
+        "hybrid" => true,
+        other => return Err(format!("不支持的搜索模式: {}", other)),
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let workspace = workspace_root.to_string_lossy().to_string();
+
+    let config = read_config(&app_handle);
+    let model = embedding_model_id(&config);
+    let client = embedding_client(&app_handle, &config).await?;
+    let query_vector = {
+        let _guard = mark_request_started();
+        client
+            .embed(std::slice::from_ref(&query))
+            .await?
+            .pop()
+            .unwrap_or_default()
+    };
+    let vector_scores = {
+        let conn = open_plugin_store()?;
+        rank_by_vector(&conn, &workspace, &model, &query_vector)?
+    };
+    if vector_scores.is_empty() {
+        warn!("🧠 [语义搜索] 当前工作区没有可用向量，请先同步向量");
+    }
+
+    let manager_lock = index_manager
+        .read()
+        .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+    let manager = manager_lock
+        .as_ref()
+        .ok_or_else(|| "索引尚未建立，请稍后重试".to_string())?;
+    let entries: HashMap<String, IndexEntry> = manager
+        .get_all_entries()
+        .into_iter()
+        .filter_map(|entry| {
+            Some((
+                get_relative_path(&workspace_root, &entry.file_path).ok()?,
+                entry,
+            ))
+        })
+        .collect();
+
+    let ranked = if hybrid {
+        let keyword_scores: Vec<(String, f32)> = manager
+            .search(&query)
+            .into_iter()
+            .filter_map(|(entry, score)| {
+                Some((
+                    get_relative_path(&workspace_root, &entry.file_path).ok()?,
+                    score,
+                ))
+            })
+            .collect();
+        merge_hybrid(&vector_scores, &keyword_scores, HYBRID_VECTOR_WEIGHT)
+    } else {
+        vector_scores
+            .into_iter()
+            .filter(|(_, score)| *score >= MIN_SEMANTIC_SCORE)
+            .collect()
+    };
+
+    let cache = cache_manager
+        .read()
+        .map_err(|e| format!("获取缓存管理器锁失败: {}", e))?;
+    Ok(ranked
+        .into_iter()
+        .filter_map(|(file_path, score)| {
+            let entry = entries.get(&file_path)?.clone();
+            Some(search_result_to_markdown_file(
+                entry,
+                score,
+                &workspace_root,
+                &cache,
+            ))
+        })
+        .take(limit)
+        .collect())
+}
+
+/// 清空当前工作区的向量（例如切换向量模型后）
+#[tauri::command]
+pub fn local_ai_clear_embeddings(app_handle: AppHandle) -> Result<usize, String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let conn = open_plugin_store()?;
+    let removed = conn
+        .execute(
+            "DELETE FROM note_embeddings WHERE workspace = ?1",
+            params![workspace_root.to_string_lossy().to_string()],
+        )
+        .map_err(|e| format!("清空向量失败: {}", e))?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_http::{spawn_stub_server, StubResponse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 确定性向量：按字节累加到 8 维桶中
+    fn fake_vector(text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; 8];
+        for (i, byte) in text.bytes().enumerate() {
+            vector[(byte as usize + i) % 8] += 1.0;
+        }
+        vector
+    }
+
+    // 最小的 /v1/embeddings 桩服务，返回确定性向量并统计收到的输入条数
+    fn spawn_embedding_stub() -> (String, Arc<AtomicUsize>) {
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        let url = spawn_stub_server(move |request| {
+            let inputs = request.json()["input"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            counter.fetch_add(inputs.len(), Ordering::SeqCst);
+            let data: Vec<Value> = inputs
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, input)| {
+                    serde_json::json!({
+                        "index": index,
+                        "embedding": fake_vector(input.as_str().unwrap_or_default()),
+                    })
+                })
+                .collect();
+            StubResponse::json(200, &serde_json::json!({ "data": data }))
+        });
+        (url, received)
+    }
+
+    fn stored_chunk_count(conn: &Connection) -> i64 {
+        conn.query_row(
+            "SELECT COUNT(*) FROM note_embeddings WHERE workspace = 'ws'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn sync(conn: &mut Connection, client: &EmbeddingClient, notes: &[EmbeddingNote]) -> SyncStats {
+        let mut plan = plan_embeddings(conn, "ws", "stub", notes).unwrap();
+        tauri::async_runtime::block_on(embed_pending(client, &mut plan, |_, _| {})).unwrap();
+        apply_plan(conn, "ws", "stub", plan).unwrap()
+    }
+
+    #[test]
+    fn chunks_by_heading_and_code_block() {
+        let content = "intro\n\n# Setup\nrun it\n\n```sh\nnpm i\n# not a heading\n```\n## Windows\nuse scoop\n";
+        let chunks = chunk_note("Guide", content);
+        let summary: Vec<(&str, &str)> = chunks
+            .iter()
+            .map(|chunk| (chunk.heading.as_str(), chunk.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("", "text"),
+                ("Setup", "text"),
+                ("Setup", "code"),
+                ("Setup / Windows", "text"),
+            ]
+        );
+        assert!(chunks[2].text.contains("# not a heading"));
+        assert_eq!(chunks, chunk_note("Guide", content));
+    }
+
+    #[test]
+    fn only_changed_chunks_are_re_embedded() {
+        let (url, received) = spawn_embedding_stub();
+        let client = EmbeddingClient::new(&url, "stub", Duration::from_secs(5));
+        let mut conn = Connection::open_in_memory().unwrap();
+        create_embedding_tables(&conn).unwrap();
+
+        let mut notes = vec![
+            EmbeddingNote {
+                file_path: "a.md".into(),
+                title: "A".into(),
+                content: "# One\nalpha beta\n# Two\ngamma delta\n".into(),
+            },
+            EmbeddingNote {
+                file_path: "b.md".into(),
+                title: "B".into(),
+                content: "```rust\nfn main() {}\n```\n".into(),
+            },
+        ];
+        let stats = sync(&mut conn, &client, &notes);
+        assert_eq!((stats.chunks, stats.embedded, stats.reused), (3, 3, 0));
+        assert_eq!(received.load(Ordering::SeqCst), 3);
+
+        let stats = sync(&mut conn, &client, &notes);
+        assert_eq!((stats.embedded, stats.reused), (0, 3));
+        assert_eq!(received.load(Ordering::SeqCst), 3);
+
+        notes[0].content = "# One\nalpha beta\n# Two\ngamma epsilon\n".into();
+        notes.pop();
+        let stats = sync(&mut conn, &client, &notes);
+        assert_eq!((stats.embedded, stats.reused, stats.removed), (1, 1, 1));
+        assert_eq!(received.load(Ordering::SeqCst), 4);
+        assert_eq!(stored_chunk_count(&conn), 2);
+
+        let query = tauri::async_runtime::block_on(client.embed(&["A\nTwo\ngamma epsilon".into()]))
+            .unwrap()
+            .pop()
+            .unwrap();
+        let ranked = rank_by_vector(&conn, "ws", "stub", &query).unwrap();
+        assert_eq!(ranked[0].0, "a.md");
+        assert!((ranked[0].1 - 1.0).abs() < 1e-5);
+
+        let merged = merge_hybrid(&ranked, &[("c.md".into(), 4.0)], 0.6);
+        assert_eq!(merged[0].0, "a.md");
+        assert!((merged[1].1 - 0.4).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,734 @@
+// GGUF 模型元数据读取
+//
+// 只解析文件头、键值元数据与张量信息表，不读取张量数据，
+// 用于模型扫描时展示架构、参数量、量化类型、训练上下文长度与聊天模板，
+// 并据此推荐 ctx_size、检查 mmproj 与主模型是否匹配。
+// 格式参考 ggml/docs/gguf.md（版本 2 / 3，小端序）。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+// 损坏文件的防护上限，正常模型远低于这些数值
+const MAX_KV_COUNT: u64 = 1 << 20;
+const MAX_TENSOR_COUNT: u64 = 1 << 24;
+const MAX_TENSOR_DIMS: u32 = 8;
+const MAX_ARRAY_DEPTH: u32 = 8;
+// 超过该长度的字符串值只跳过不保存（聊天模板通常只有几 KB）
+const MAX_STRING_BYTES: u64 = 1024 * 1024;
+// 自动上下文长度上限：训练长度更长时也只取该值，避免 KV cache 占满显存
+pub(crate) const AUTO_CTX_SIZE_MAX: u32 = 8192;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum GgufValue {
+    Uint(u64),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    // 数组只记录元素个数（词表等数组很大，扫描时不需要内容）
+    Array(u64),
+}
+
+impl GgufValue {
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            GgufValue::Uint(value) => Some(*value),
+            GgufValue::Int(value) => u64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            GgufValue::String(value) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            GgufValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GgufMetadata {
+    pub version: u32,
+    pub values: HashMap<String, GgufValue>,
+    // 所有张量的元素总数，即参数量
+    pub element_count: u64,
+    // 按元素数统计的张量类型分布，用于缺少 general.file_type 时推断量化类型
+    pub tensor_types: HashMap<u32, u64>,
+}
+
+impl GgufMetadata {
+    fn get(&self, key: &str) -> Option<&GgufValue> {
+        self.values.get(key)
+    }
+
+    fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(GgufValue::as_u64)
+    }
+
+    fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(GgufValue::as_str)
+    }
+
+    fn architecture(&self) -> Option<&str> {
+        self.get_str("general.architecture")
+    }
+
+    // 架构相关的键以架构名为前缀，如 qwen3.context_length
+    fn arch_u64(&self, suffix: &str) -> Option<u64> {
+        let arch = self.architecture()?;
+        self.get_u64(&format!("{}.{}", arch, suffix))
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, String> {
+    let mut buffer = [0u8; 4];
+    reader
+        .read_exact(&mut buffer)
+        .map_err(|error| format!("读取 GGUF 失败: {}", error))?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, String> {
+    let mut buffer = [0u8; 8];
+    reader
+        .read_exact(&mut buffer)
+        .map_err(|error| format!("读取 GGUF 失败: {}", error))?;
+    Ok(u64::from_le_bytes(buffer))
+}
+
+fn skip<R: Read + Seek>(reader: &mut BufReader<R>, bytes: u64) -> Result<(), String> {
+    let bytes = i64::try_from(bytes).map_err(|_| "GGUF 数据长度异常".to_string())?;
+    reader
+        .seek_relative(bytes)
+        .map_err(|error| format!("读取 GGUF 失败: {}", error))
+}
+
+fn read_string<R: Read + Seek>(reader: &mut BufReader<R>) -> Result<Option<String>, String> {
+    let len = read_u64(reader)?;
+    if len > MAX_STRING_BYTES {
+        skip(reader, len)?;
+        return Ok(None);
+    }
+    let mut buffer = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut buffer)
+        .map_err(|error| format!("读取 GGUF 失败: {}", error))?;
+    Ok(Some(String::from_utf8_lossy(&buffer).into_owned()))
+}
+
+// 定长标量类型的字节数；字符串（8）与数组（9）为变长
+fn scalar_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        0 | 1 | 7 => Some(1),
+        2 | 3 => Some(2),
+        4..=6 => Some(4),
+        10..=12 => Some(8),
+        _ => None,
+    }
+}
+
+fn read_scalar<R: Read>(reader: &mut R, value_type: u32) -> Result<GgufValue, String> {
+    let size = scalar_size(value_type)
+        .ok_or_else(|| format!("未知的 GGUF 值类型: {}", value_type))? as usize;
+    let mut buffer = [0u8; 8];
+    reader
+        .read_exact(&mut buffer[..size])
+        .map_err(|error| format!("读取 GGUF 失败: {}", error))?;
+    let value = match value_type {
+        0 => GgufValue::Uint(buffer[0] as u64),
+        1 => GgufValue::Int(buffer[0] as i8 as i64),
+        2 => GgufValue::Uint(u16::from_le_bytes([buffer[0], buffer[1]]) as u64),
+        3 => GgufValue::Int(i16::from_le_bytes([buffer[0], buffer[1]]) as i64),
+        4 => GgufValue::Uint(u32::from_le_bytes(buffer[..4].try_into().unwrap()) as u64),
+        5 => GgufValue::Int(i32::from_le_bytes(buffer[..4].try_into().unwrap()) as i64),
+        6 => GgufValue::Float(f32::from_le_bytes(buffer[..4].try_into().unwrap()) as f64),
+        7 => GgufValue::Bool(buffer[0] != 0),
+        10 => GgufValue::Uint(u64::from_le_bytes(buffer)),
+        11 => GgufValue::Int(i64::from_le_bytes(buffer)),
+        _ => GgufValue::Float(f64::from_le_bytes(buffer)),
+    };
+    Ok(value)
+}
+
+fn read_value<R: Read + Seek>(
+    reader: &mut BufReader<R>,
+    value_type: u32,
+) -> Result<Option<GgufValue>, String> {
+    match value_type {
+        8 => Ok(read_string(reader)?.map(GgufValue::String)),
+        9 => {
+            let item_type = read_u32(reader)?;
+            let len = read_u64(reader)?;
+            skip_array(reader, item_type, len, 1)?;
+            Ok(Some(GgufValue::Array(len)))
+        }
+        _ => read_scalar(reader, value_type).map(Some),
+    }
+}
+
+fn skip_array<R: Read + Seek>(
+    reader: &mut BufReader<R>,
+    item_type: u32,
+    len: u64,
+    depth: u32,
+) -> Result<(), String> {
+    if depth > MAX_ARRAY_DEPTH {
+        return Err("GGUF 数组嵌套层数过多".to_string());
+    }
+    if let Some(size) = scalar_size(item_type) {
+        let bytes = len
+            .checked_mul(size)
+            .ok_or_else(|| "GGUF 数组长度异常".to_string())?;
+        return skip(reader, bytes);
+    }
+    for _ in 0..len {
+        match item_type {
+            8 => {
+                let item_len = read_u64(reader)?;
+                skip(reader, item_len)?;
+            }
+            9 => {
+                let nested_type = read_u32(reader)?;
+                let nested_len = read_u64(reader)?;
+                skip_array(reader, nested_type, nested_len, depth + 1)?;
+            }
+            _ => return Err(format!("未知的 GGUF 值类型: {}", item_type)),
+        }
+    }
+    Ok(())
+}
+
+/// 从任意可定位的数据源解析 GGUF 元数据（不读取张量数据）
+pub(crate) fn parse_gguf<R: Read + Seek>(reader: R) -> Result<GgufMetadata, String> {
+    let mut reader = BufReader::new(reader);
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| "文件过短，不是 GGUF 模型".to_string())?;
+    if &magic != GGUF_MAGIC {
+        return Err("文件头不是 GGUF 格式".to_string());
+    }
+    let version = read_u32(&mut reader)?;
+    if !(2..=3).contains(&version) {
+        return Err(format!("不支持的 GGUF 版本: {}", version));
+    }
+    let tensor_count = read_u64(&mut reader)?;
+    let kv_count = read_u64(&mut reader)?;
+    if kv_count > MAX_KV_COUNT || tensor_count > MAX_TENSOR_COUNT {
+        return Err("GGUF 文件头数量异常，文件可能已损坏".to_string());
+    }
+
+    let mut metadata = GgufMetadata {
+        version,
+        ..GgufMetadata::default()
+    };
+    for _ in 0..kv_count {
+        let key = read_string(&mut reader)?.ok_or_else(|| "GGUF 键名过长".to_string())?;
+        let value_type = read_u32(&mut reader)?;
+        if let Some(value) = read_value(&mut reader, value_type)? {
+            metadata.values.insert(key, value);
+        }
+    }
+
+    for _ in 0..tensor_count {
+        read_string(&mut reader)?;
+        let n_dims = read_u32(&mut reader)?;
+        if n_dims > MAX_TENSOR_DIMS {
+            return Err(format!("GGUF 张量维度异常: {}", n_dims));
+        }
+        let mut elements: u64 = 1;
+        for _ in 0..n_dims {
+            elements = elements.saturating_mul(read_u64(&mut reader)?);
+        }
+        let tensor_type = read_u32(&mut reader)?;
+        read_u64(&mut reader)?;
+        metadata.element_count = metadata.element_count.saturating_add(elements);
+        // 一维张量（norm、bias）通常保持 F32，不参与量化类型推断
+        if n_dims >= 2 {
+            *metadata.tensor_types.entry(tensor_type).or_insert(0) += elements;
+        }
+    }
+
+    Ok(metadata)
+}
+
+// llama_ftype（general.file_type）
+fn file_type_name(file_type: u64) -> Option<&'static str> {
+    let name = match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        36 => "TQ1_0",
+        37 => "TQ2_0",
+        38 => "MXFP4_MOE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// ggml_type（张量信息中的类型）
+fn tensor_type_name(tensor_type: u32) -> Option<&'static str> {
+    let name = match tensor_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        6 => "Q5_0",
+        7 => "Q5_1",
+        8 => "Q8_0",
+        9 => "Q8_1",
+        10 => "Q2_K",
+        11 => "Q3_K",
+        12 => "Q4_K",
+        13 => "Q5_K",
+        14 => "Q6_K",
+        15 => "Q8_K",
+        16 => "IQ2_XXS",
+        17 => "IQ2_XS",
+        18 => "IQ3_XXS",
+        19 => "IQ1_S",
+        20 => "IQ4_NL",
+        21 => "IQ3_S",
+        22 => "IQ2_S",
+        23 => "IQ4_XS",
+        24 => "I8",
+        25 => "I16",
+        26 => "I32",
+        27 => "I64",
+        28 => "F64",
+        29 => "IQ1_M",
+        30 => "BF16",
+        34 => "TQ1_0",
+        35 => "TQ2_0",
+        39 => "MXFP4",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiGgufInfo {
+    pub path: String,
+    pub file_size: u64,
+    pub gguf_version: Option<u32>,
+    pub architecture: Option<String>,
+    pub name: Option<String>,
+    // 模型自带的规模标签，如 "4B"
+    pub size_label: Option<String>,
+    pub parameter_count: Option<u64>,
+    pub quantization: Option<String>,
+    // 训练时的上下文长度
+    pub context_length: Option<u32>,
+    pub embedding_length: Option<u32>,
+    pub chat_template: Option<String>,
+    // mmproj 视觉投影文件
+    pub is_vision_projector: bool,
+    pub projector_type: Option<String>,
+    // 视觉投影输出维度，需与主模型的 embedding_length 一致
+    pub projection_dim: Option<u32>,
+    // 解析失败的原因；此时其余字段为空
+    pub error: Option<String>,
+}
+
+impl LocalAiGgufInfo {
+    /// 模型列表中展示的简要说明，如 "qwen3 · 4.02B · Q4_K_M"
+    pub(crate) fn summary(&self) -> Option<String> {
+        let parameters = self
+            .size_label
+            .clone()
+            .or_else(|| self.parameter_count.map(format_parameter_count));
+        let parts = [
+            self.architecture.clone(),
+            parameters,
+            self.quantization.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+}
+
+fn format_parameter_count(count: u64) -> String {
+    if count >= 1_000_000_000 {
+        format!("{:.2}B", count as f64 / 1e9)
+    } else if count >= 1_000_000 {
+        format!("{:.0}M", count as f64 / 1e6)
+    } else {
+        count.to_string()
+    }
+}
+
+fn to_u32(value: Option<u64>) -> Option<u32> {
+    value.and_then(|value| u32::try_from(value).ok())
+}
+
+/// 将解析结果整理为扫描展示用的模型信息
+pub(crate) fn model_info(path: String, file_size: u64, metadata: &GgufMetadata) -> LocalAiGgufInfo {
+    let architecture = metadata.architecture().map(str::to_string);
+    let is_vision_projector = architecture.as_deref() == Some("clip")
+        || metadata.get_str("general.type") == Some("mmproj")
+        || metadata
+            .get("clip.has_vision_encoder")
+            .and_then(GgufValue::as_bool)
+            .unwrap_or(false);
+    let quantization = metadata
+        .get_u64("general.file_type")
+        .and_then(file_type_name)
+        .or_else(|| {
+            metadata
+                .tensor_types
+                .iter()
+                .max_by_key(|(tensor_type, elements)| {
+                    (**elements, std::cmp::Reverse(**tensor_type))
+                })
+                .and_then(|(tensor_type, _)| tensor_type_name(*tensor_type))
+        })
+        .map(str::to_string);
+    let parameter_count = (metadata.element_count > 0).then_some(metadata.element_count);
+
+    LocalAiGgufInfo {
+        path,
+        file_size,
+        gguf_version: Some(metadata.version),
+        architecture,
+        name: metadata.get_str("general.name").map(str::to_string),
+        size_label: metadata.get_str("general.size_label").map(str::to_string),
+        parameter_count,
+        quantization,
+        context_length: to_u32(metadata.arch_u64("context_length")),
+        embedding_length: to_u32(metadata.arch_u64("embedding_length")),
+        chat_template: metadata
+            .get_str("tokenizer.chat_template")
+            .map(str::to_string),
+        is_vision_projector,
+        projector_type: metadata
+            .get_str("clip.projector_type")
+            .or_else(|| metadata.get_str("clip.vision.projector_type"))
+            .map(str::to_string),
+        projection_dim: to_u32(metadata.get_u64("clip.vision.projection_dim")),
+        error: None,
+    }
+}
+
+/// 读取磁盘上的 GGUF 文件；失败时返回带 error 的信息而不是错误，便于扫描列表展示
+pub(crate) fn read_model_info(path: &Path) -> LocalAiGgufInfo {
+    let display = path.to_string_lossy().to_string();
+    let file_size = path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    let parsed = File::open(path)
+        .map_err(|error| format!("打开模型文件失败: {}", error))
+        .and_then(parse_gguf);
+    match parsed {
+        Ok(metadata) => model_info(display, file_size, &metadata),
+        Err(error) => LocalAiGgufInfo {
+            path: display,
+            file_size,
+            error: Some(error),
+            ..LocalAiGgufInfo::default()
+        },
+    }
+}
+
+/// 按训练上下文长度推荐 ctx_size；无法判断时返回 None
+pub(crate) fn recommended_ctx_size(info: &LocalAiGgufInfo) -> Option<u32> {
+    if info.is_vision_projector {
+        return None;
+    }
+    info.context_length
+        .filter(|length| *length > 0)
+        .map(|length| length.min(AUTO_CTX_SIZE_MAX))
+}
+
+/// 检查 mmproj 与主模型是否匹配，不匹配时返回提示
+pub(crate) fn mmproj_mismatch(model: &LocalAiGgufInfo, mmproj: &LocalAiGgufInfo) -> Option<String> {
+    if model.error.is_some() || mmproj.error.is_some() {
+        return None;
+    }
+    if model.is_vision_projector {
+        return Some("所选主模型是视觉投影（mmproj）文件，无法单独加载".to_string());
+    }
+    if !mmproj.is_vision_projector {
+        return Some("所选 mmproj 文件不是视觉投影模型".to_string());
+    }
+    match (mmproj.projection_dim, model.embedding_length) {
+        (Some(projection_dim), Some(embedding_length)) if projection_dim != embedding_length => {
+            Some(format!(
+                "mmproj 输出维度 {} 与主模型{}的嵌入维度 {} 不一致，该视觉投影不适用于此模型",
+                projection_dim,
+                model
+                    .architecture
+                    .as_deref()
+                    .map(|arch| format!("（{}）", arch))
+                    .unwrap_or_default(),
+                embedding_length
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // 构造只含文件头、元数据与张量信息的 GGUF 字节
+    #[derive(Default)]
+    struct Fixture {
+        kv: Vec<u8>,
+        kv_count: u64,
+        tensors: Vec<u8>,
+        tensor_count: u64,
+    }
+
+    fn push_string(buffer: &mut Vec<u8>, value: &str) {
+        buffer.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        buffer.extend_from_slice(value.as_bytes());
+    }
+
+    impl Fixture {
+        fn key(&mut self, key: &str, value_type: u32) -> &mut Vec<u8> {
+            self.kv_count += 1;
+            push_string(&mut self.kv, key);
+            self.kv.extend_from_slice(&value_type.to_le_bytes());
+            &mut self.kv
+        }
+
+        fn string(mut self, key: &str, value: &str) -> Self {
+            push_string(self.key(key, 8), value);
+            self
+        }
+
+        fn u32(mut self, key: &str, value: u32) -> Self {
+            self.key(key, 4).extend_from_slice(&value.to_le_bytes());
+            self
+        }
+
+        fn bool(mut self, key: &str, value: bool) -> Self {
+            self.key(key, 7).push(value as u8);
+            self
+        }
+
+        fn string_array(mut self, key: &str, values: &[&str]) -> Self {
+            let buffer = self.key(key, 9);
+            buffer.extend_from_slice(&8u32.to_le_bytes());
+            buffer.extend_from_slice(&(values.len() as u64).to_le_bytes());
+            for value in values {
+                push_string(buffer, value);
+            }
+            self
+        }
+
+        fn f32_array(mut self, key: &str, values: &[f32]) -> Self {
+            let buffer = self.key(key, 9);
+            buffer.extend_from_slice(&6u32.to_le_bytes());
+            buffer.extend_from_slice(&(values.len() as u64).to_le_bytes());
+            for value in values {
+                buffer.extend_from_slice(&value.to_le_bytes());
+            }
+            self
+        }
+
+        fn nested_array(mut self, key: &str, depth: u32) -> Self {
+            let buffer = self.key(key, 9);
+            for _ in 1..depth {
+                buffer.extend_from_slice(&9u32.to_le_bytes());
+                buffer.extend_from_slice(&1u64.to_le_bytes());
+            }
+            buffer.extend_from_slice(&4u32.to_le_bytes());
+            buffer.extend_from_slice(&1u64.to_le_bytes());
+            buffer.extend_from_slice(&0u32.to_le_bytes());
+            self
+        }
+
+        fn tensor(mut self, name: &str, dims: &[u64], tensor_type: u32) -> Self {
+            self.tensor_count += 1;
+            push_string(&mut self.tensors, name);
+            self.tensors
+                .extend_from_slice(&(dims.len() as u32).to_le_bytes());
+            for dim in dims {
+                self.tensors.extend_from_slice(&dim.to_le_bytes());
+            }
+            self.tensors.extend_from_slice(&tensor_type.to_le_bytes());
+            self.tensors.extend_from_slice(&0u64.to_le_bytes());
+            self
+        }
+
+        fn build(self) -> Vec<u8> {
+            let mut bytes = GGUF_MAGIC.to_vec();
+            bytes.extend_from_slice(&3u32.to_le_bytes());
+            bytes.extend_from_slice(&self.tensor_count.to_le_bytes());
+            bytes.extend_from_slice(&self.kv_count.to_le_bytes());
+            bytes.extend_from_slice(&self.kv);
+            bytes.extend_from_slice(&self.tensors);
+            bytes
+        }
+    }
+
+    fn info(bytes: Vec<u8>) -> LocalAiGgufInfo {
+        let size = bytes.len() as u64;
+        let metadata = parse_gguf(Cursor::new(bytes)).unwrap();
+        model_info("model.gguf".to_string(), size, &metadata)
+    }
+
+    fn text_model(embedding_length: u32) -> Vec<u8> {
+        Fixture::default()
+            .string("general.architecture", "qwen3")
+            .string("general.name", "Qwen3 4B")
+            .u32("general.file_type", 15)
+            .u32("qwen3.context_length", 40960)
+            .u32("qwen3.embedding_length", embedding_length)
+            .string_array("tokenizer.ggml.tokens", &["<s>", "</s>", "你好"])
+            .f32_array("tokenizer.ggml.scores", &[0.0, 0.0, -1.5])
+            .string(
+                "tokenizer.chat_template",
+                "{% for m in messages %}{{ m.content }}{% endfor %}",
+            )
+            .tensor("token_embd.weight", &[embedding_length as u64, 3], 12)
+            .tensor("output_norm.weight", &[embedding_length as u64], 0)
+            .build()
+    }
+
+    fn mmproj(projection_dim: u32) -> Vec<u8> {
+        Fixture::default()
+            .string("general.architecture", "clip")
+            .string("general.type", "mmproj")
+            .bool("clip.has_vision_encoder", true)
+            .string("clip.projector_type", "qwen2vl_merger")
+            .u32("clip.vision.projection_dim", projection_dim)
+            .u32("general.file_type", 1)
+            .tensor("v.patch_embd.weight", &[16, 16, 3, 8], 1)
+            .build()
+    }
+
+    #[test]
+    fn reads_text_model_metadata() {
+        let info = info(text_model(64));
+        assert_eq!(info.gguf_version, Some(3));
+        assert_eq!(info.architecture.as_deref(), Some("qwen3"));
+        assert_eq!(info.name.as_deref(), Some("Qwen3 4B"));
+        assert_eq!(info.quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(info.context_length, Some(40960));
+        assert_eq!(info.embedding_length, Some(64));
+        assert_eq!(info.parameter_count, Some(64 * 3 + 64));
+        assert!(info.chat_template.as_deref().unwrap().contains("messages"));
+        assert!(!info.is_vision_projector);
+        assert_eq!(info.summary().as_deref(), Some("qwen3 · 256 · Q4_K_M"));
+        assert_eq!(format_parameter_count(4_022_468_096), "4.02B");
+    }
+
+    #[test]
+    fn infers_quantization_from_tensor_types() {
+        let bytes = Fixture::default()
+            .string("general.architecture", "llama")
+            .tensor("blk.0.attn_q.weight", &[32, 32], 8)
+            .tensor("blk.0.ffn_up.weight", &[32, 64], 8)
+            .tensor("output.weight", &[32, 16], 14)
+            .tensor("blk.0.attn_norm.weight", &[4096], 0)
+            .build();
+        let info = info(bytes);
+        assert_eq!(info.quantization.as_deref(), Some("Q8_0"));
+        assert_eq!(info.context_length, None);
+        assert_eq!(recommended_ctx_size(&info), None);
+    }
+
+    #[test]
+    fn detects_vision_projector_and_mismatch() {
+        let projector = info(mmproj(64));
+        assert!(projector.is_vision_projector);
+        assert_eq!(projector.projector_type.as_deref(), Some("qwen2vl_merger"));
+        assert_eq!(projector.quantization.as_deref(), Some("F16"));
+        assert_eq!(recommended_ctx_size(&projector), None);
+
+        let matching = info(text_model(64));
+        assert_eq!(mmproj_mismatch(&matching, &projector), None);
+
+        let other = info(text_model(128));
+        let warning = mmproj_mismatch(&other, &projector).unwrap();
+        assert!(warning.contains("64") && warning.contains("128"));
+
+        assert!(mmproj_mismatch(&matching, &matching).is_some());
+        assert!(mmproj_mismatch(&projector, &projector).is_some());
+    }
+
+    #[test]
+    fn recommends_capped_context() {
+        let mut info = info(text_model(64));
+        assert_eq!(recommended_ctx_size(&info), Some(AUTO_CTX_SIZE_MAX));
+        info.context_length = Some(2048);
+        assert_eq!(recommended_ctx_size(&info), Some(2048));
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(parse_gguf(Cursor::new(b"GGML".to_vec())).is_err());
+        assert!(parse_gguf(Cursor::new(Vec::new())).is_err());
+
+        let mut old_version = GGUF_MAGIC.to_vec();
+        old_version.extend_from_slice(&1u32.to_le_bytes());
+        assert!(parse_gguf(Cursor::new(old_version))
+            .unwrap_err()
+            .contains("版本"));
+
+        // 元数据声明的条目超出文件长度
+        let mut truncated = text_model(64);
+        truncated.truncate(truncated.len() - 20);
+        assert!(parse_gguf(Cursor::new(truncated)).is_err());
+    }
+
+    #[test]
+    fn limits_nested_array_depth() {
+        let allowed = Fixture::default()
+            .nested_array("tokenizer.nested", MAX_ARRAY_DEPTH)
+            .string("general.architecture", "llama")
+            .build();
+        let metadata = parse_gguf(Cursor::new(allowed)).unwrap();
+        assert_eq!(metadata.architecture(), Some("llama"));
+
+        let too_deep = Fixture::default()
+            .nested_array("tokenizer.nested", MAX_ARRAY_DEPTH + 1)
+            .build();
+        assert!(parse_gguf(Cursor::new(too_deep))
+            .unwrap_err()
+            .contains("嵌套"));
+    }
+}
@@ -0,0 +1,953 @@
+// 本地 AI 对话历史
+//
+// 对话与消息存入 SQLite（ai_chat_conversations / ai_chat_messages），消息正文建立
+// FTS5 trigram 索引用于全文搜索；不足三个字符的查询词无法走 trigram，退回 LIKE。
+// 首次访问时把旧版存放在 app.json 的 local_ai_chat_histories 一次性迁移进来。
+// 对话可导出为工作区笔记，frontmatter 中的 chat_id 指回原对话。
+
+use crate::db::open_plugin_store;
+use crate::json_config::{get_app_config_value, get_workspace_root, set_app_config_value};
+use crate::markdown::{CacheManager, IndexManager};
+use crate::plugins::local_ai::{require_plugin, LocalAiChatHistory, LocalAiChatTurn};
+use crate::text_utils::{escape_like, truncate_chars};
+use log::{info, warn};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, State};
+
+// 旧版 app.json 中的对话历史键
+const LEGACY_HISTORY_KEY: &str = "local_ai_chat_histories";
+const EXPORT_CATEGORY: &str = "AI Chats";
+const DEFAULT_PAGE_SIZE: usize = 30;
+const MAX_PAGE_SIZE: usize = 200;
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const PREVIEW_CHARS: usize = 120;
+// 搜索结果摘录中命中词前后保留的字符数
+const EXCERPT_RADIUS: usize = 60;
+// trigram 分词要求查询词至少三个字符
+const FTS_MIN_TERM_CHARS: usize = 3;
+
+static LEGACY_MIGRATED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiChatConversation {
+    pub id: String,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub pinned: bool,
+    pub tags: Vec<String>,
+    pub message_count: usize,
+    // 最后一条消息的开头，用于列表展示
+    pub preview: String,
+    // 导出的笔记（相对工作区路径）
+    pub note_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiChatHistoryPage {
+    pub items: Vec<LocalAiChatConversation>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiChatSearchHit {
+    pub conversation_id: String,
+    pub title: String,
+    pub turn_id: String,
+    pub role: String,
+    pub excerpt: String,
+    pub created_at: String,
+}
+
+// ============= 存储 =============
+
+pub(crate) fn create_history_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ai_chat_conversations (
+             id TEXT PRIMARY KEY,
+             title TEXT NOT NULL,
+             created_at TEXT NOT NULL,
+             updated_at TEXT NOT NULL,
+             pinned INTEGER NOT NULL DEFAULT 0,
+             tags TEXT NOT NULL DEFAULT '[]',
+             current_node_id TEXT,
+             tree TEXT,
+             note_path TEXT
+         );
+         CREATE INDEX IF NOT EXISTS idx_ai_chat_conversations_order
+             ON ai_chat_conversations(pinned DESC, updated_at DESC);
+         CREATE TABLE IF NOT EXISTS ai_chat_messages (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             conversation_id TEXT NOT NULL,
+             position INTEGER NOT NULL,
+             turn_id TEXT NOT NULL,
+             role TEXT NOT NULL,
+             content TEXT NOT NULL,
+             created_at TEXT NOT NULL,
+             sources TEXT,
+             tool_calls TEXT
+         );
+         CREATE INDEX IF NOT EXISTS idx_ai_chat_messages_conversation
+             ON ai_chat_messages(conversation_id, position);",
+    )?;
+    // 全文索引不可用（SQLite 未编译 FTS5）时只记录警告，搜索退回 LIKE
+    if let Err(error) = create_fts_index(conn) {
+        warn!(
+            "⚠️ [LocalAI] 创建对话全文索引失败，搜索将使用 LIKE: {}",
+            error
+        );
+    }
+    Ok(())
+}
+
+// 消息只会整体插入或删除（保存对话时重写该对话的全部消息），因此只需两个触发器
+fn create_fts_index(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let existed = fts_available(conn);
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS ai_chat_messages_fts USING fts5(
+             content,
+             content='ai_chat_messages',
+             content_rowid='id',
+             tokenize='trigram'
+         );
+         CREATE TRIGGER IF NOT EXISTS ai_chat_messages_fts_insert
+             AFTER INSERT ON ai_chat_messages BEGIN
+             INSERT INTO ai_chat_messages_fts(rowid, content) VALUES (new.id, new.content);
+         END;
+         CREATE TRIGGER IF NOT EXISTS ai_chat_messages_fts_delete
+             AFTER DELETE ON ai_chat_messages BEGIN
+             INSERT INTO ai_chat_messages_fts(ai_chat_messages_fts, rowid, content)
+                 VALUES ('delete', old.id, old.content);
+         END;",
+    )?;
+    if !existed {
+        // 索引晚于消息创建时补齐已有数据
+        conn.execute(
+            "INSERT INTO ai_chat_messages_fts(ai_chat_messages_fts) VALUES ('rebuild')",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn fts_available(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'ai_chat_messages_fts'",
+        [],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+fn open_store(app_handle: &AppHandle) -> Result<Connection, String> {
+    let mut conn = open_plugin_store()?;
+    migrate_legacy_histories(app_handle, &mut conn)?;
+    Ok(conn)
+}
+
+// 旧版把所有对话作为一个 JSON 数组存放在 app.json；导入后清空该键
+fn migrate_legacy_histories(app_handle: &AppHandle, conn: &mut Connection) -> Result<(), String> {
+    if LEGACY_MIGRATED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let legacy: Vec<LocalAiChatHistory> =
+        get_app_config_value(app_handle, LEGACY_HISTORY_KEY).unwrap_or_default();
+    if !legacy.is_empty() {
+        let imported = import_histories(conn, &legacy)?;
+        set_app_config_value(app_handle, LEGACY_HISTORY_KEY, Vec::<Value>::new())?;
+        info!(
+            "💬 [LocalAI] 已将 {} 条旧版对话历史迁移到数据库（共 {} 条）",
+            imported,
+            legacy.len()
+        );
+    }
+    LEGACY_MIGRATED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 导入对话，已存在的 id 保持不变；返回实际导入的数量
+pub(crate) fn import_histories(
+    conn: &mut Connection,
+    histories: &[LocalAiChatHistory],
+) -> Result<usize, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    let mut imported = 0;
+    for history in histories {
+        let exists = tx
+            .query_row(
+                "SELECT 1 FROM ai_chat_conversations WHERE id = ?1",
+                params![history.id],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(|e| format!("查询对话失败: {}", e))?
+            .is_some();
+        if exists {
+            continue;
+        }
+        write_history(&tx, history)?;
+        imported += 1;
+    }
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
+    Ok(imported)
+}
+
+fn to_json_text<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("序列化对话失败: {}", e))
+}
+
+// 写入对话并重写其全部消息；置顶、标签与导出路径只由专门的命令修改
+fn write_history(conn: &Connection, history: &LocalAiChatHistory) -> Result<(), String> {
+    let tree = history.messages.as_ref().map(to_json_text).transpose()?;
+    conn.execute(
+        "INSERT INTO ai_chat_conversations
+             (id, title, created_at, updated_at, pinned, tags, current_node_id, tree)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT(id) DO UPDATE SET
+             title = excluded.title,
+             updated_at = excluded.updated_at,
+             current_node_id = excluded.current_node_id,
+             tree = excluded.tree",
+        params![
+            history.id,
+            history.title,
+            history.created_at,
+            history.updated_at,
+            history.pinned,
+            to_json_text(&history.tags)?,
+            history.current_node_id,
+            tree
+        ],
+    )
+    .map_err(|e| format!("保存对话失败: {}", e))?;
+    conn.execute(
+        "DELETE FROM ai_chat_messages WHERE conversation_id = ?1",
+        params![history.id],
+    )
+    .map_err(|e| format!("保存对话失败: {}", e))?;
+    let mut stmt = conn
+        .prepare(
+            "INSERT INTO ai_chat_messages
+                 (conversation_id, position, turn_id, role, content, created_at, sources, tool_calls)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )
+        .map_err(|e| format!("保存对话失败: {}", e))?;
+    for (position, turn) in history.turns.iter().enumerate() {
+        let sources = (!turn.sources.is_empty())
+            .then(|| to_json_text(&turn.sources))
+            .transpose()?;
+        let tool_calls = (!turn.tool_calls.is_empty())
+            .then(|| to_json_text(&turn.tool_calls))
+            .transpose()?;
+        stmt.execute(params![
+            history.id,
+            position as i64,
+            turn.id,
+            turn.role,
+            turn.content,
+            turn.created_at,
+            sources,
+            tool_calls
+        ])
+        .map_err(|e| format!("保存对话消息失败: {}", e))?;
+    }
+    Ok(())
+}
+
+pub(crate) fn save_history(
+    conn: &mut Connection,
+    history: &LocalAiChatHistory,
+) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    write_history(&tx, history)?;
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
+
+pub(crate) fn delete_history(conn: &mut Connection, history_id: &str) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    tx.execute(
+        "DELETE FROM ai_chat_messages WHERE conversation_id = ?1",
+        params![history_id],
+    )
+    .map_err(|e| format!("删除对话失败: {}", e))?;
+    tx.execute(
+        "DELETE FROM ai_chat_conversations WHERE id = ?1",
+        params![history_id],
+    )
+    .map_err(|e| format!("删除对话失败: {}", e))?;
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
+
+fn clear_histories(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("DELETE FROM ai_chat_messages; DELETE FROM ai_chat_conversations;")
+        .map_err(|e| format!("清空对话历史失败: {}", e))
+}
+
+fn parse_json_column<T: serde::de::DeserializeOwned + Default>(text: Option<String>) -> T {
+    text.and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn load_turns(conn: &Connection, history_id: &str) -> Result<Vec<LocalAiChatTurn>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT turn_id, role, content, created_at, sources, tool_calls
+             FROM ai_chat_messages WHERE conversation_id = ?1 ORDER BY position",
+        )
+        .map_err(|e| format!("读取对话消息失败: {}", e))?;
+    let turns = stmt
+        .query_map(params![history_id], |row| {
+            Ok(LocalAiChatTurn {
+                id: row.get(0)?,
+                role: row.get(1)?,
+                content: row.get(2)?,
+                created_at: row.get(3)?,
+                sources: parse_json_column(row.get(4)?),
+                tool_calls: parse_json_column(row.get(5)?),
+            })
+        })
+        .map_err(|e| format!("读取对话消息失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取对话消息失败: {}", e))?;
+    Ok(turns)
+}
+
+const HISTORY_COLUMNS: &str =
+    "id, title, created_at, updated_at, pinned, tags, current_node_id, tree, note_path";
+
+fn history_from_row(row: &rusqlite::Row) -> rusqlite::Result<LocalAiChatHistory> {
+    Ok(LocalAiChatHistory {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+        pinned: row.get(4)?,
+        tags: parse_json_column(row.get(5)?),
+        current_node_id: row.get(6)?,
+        messages: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|text| serde_json::from_str(&text).ok()),
+        note_path: row.get(8)?,
+        turns: Vec::new(),
+    })
+}
+
+pub(crate) fn load_history(
+    conn: &Connection,
+    history_id: &str,
+) -> Result<Option<LocalAiChatHistory>, String> {
+    let history = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM ai_chat_conversations WHERE id = ?1",
+                HISTORY_COLUMNS
+            ),
+            params![history_id],
+            history_from_row,
+        )
+        .optional()
+        .map_err(|e| format!("读取对话失败: {}", e))?;
+    let Some(mut history) = history else {
+        return Ok(None);
+    };
+    history.turns = load_turns(conn, history_id)?;
+    Ok(Some(history))
+}
+
+fn load_all_histories(conn: &Connection) -> Result<Vec<LocalAiChatHistory>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM ai_chat_conversations ORDER BY pinned DESC, updated_at DESC",
+            HISTORY_COLUMNS
+        ))
+        .map_err(|e| format!("读取对话历史失败: {}", e))?;
+    let mut histories = stmt
+        .query_map([], history_from_row)
+        .map_err(|e| format!("读取对话历史失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取对话历史失败: {}", e))?;
+    for history in &mut histories {
+        history.turns = load_turns(conn, &history.id)?;
+    }
+    Ok(histories)
+}
+
+// 折叠空白后截断，用作对话列表中的预览
+fn preview_text(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let preview_len = truncate_chars(&collapsed, max_chars).len();
+    if preview_len < collapsed.len() {
+        format!("{}…", &collapsed[..preview_len])
+    } else {
+        collapsed
+    }
+}
+
+pub(crate) fn list_conversations(
+    conn: &Connection,
+    offset: usize,
+    limit: usize,
+    tag: Option<&str>,
+    pinned_only: bool,
+) -> Result<LocalAiChatHistoryPage, String> {
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+    let mut conditions = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+    if let Some(tag) = tag.filter(|tag| !tag.is_empty()) {
+        values.push(tag.to_string());
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM json_each(c.tags) WHERE json_each.value = ?{})",
+            values.len()
+        ));
+    }
+    if pinned_only {
+        conditions.push("c.pinned = 1".to_string());
+    }
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let total: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM ai_chat_conversations c {}", filter),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("统计对话失败: {}", e))?;
+
+    let sql = format!(
+        "SELECT c.id, c.title, c.created_at, c.updated_at, c.pinned, c.tags, c.note_path,
+                (SELECT COUNT(*) FROM ai_chat_messages m WHERE m.conversation_id = c.id),
+                (SELECT m.content FROM ai_chat_messages m WHERE m.conversation_id = c.id
+                 ORDER BY m.position DESC LIMIT 1)
+         FROM ai_chat_conversations c {}
+         ORDER BY c.pinned DESC, c.updated_at DESC
+         LIMIT {} OFFSET {}",
+        filter, limit, offset
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("读取对话列表失败: {}", e))?;
+    let items = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(LocalAiChatConversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+                pinned: row.get(4)?,
+                tags: parse_json_column(row.get(5)?),
+                note_path: row.get(6)?,
+                message_count: row.get::<_, i64>(7)? as usize,
+                preview: row
+                    .get::<_, Option<String>>(8)?
+                    .map(|content| preview_text(&content, PREVIEW_CHARS))
+                    .unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("读取对话列表失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取对话列表失败: {}", e))?;
+
+    Ok(LocalAiChatHistoryPage {
+        items,
+        total: total as usize,
+        offset,
+        limit,
+    })
+}
+
+// 以第一个命中的查询词为中心截取摘录
+pub(crate) fn excerpt_around(content: &str, terms: &[&str], radius: usize) -> String {
+    let lower = content.to_lowercase();
+    let hit = terms
+        .iter()
+        .filter_map(|term| lower.find(&term.to_lowercase()))
+        .min();
+    let chars: Vec<char> = content.chars().collect();
+    // to_lowercase 可能改变字节长度，这里按字符位置近似定位
+    let center = hit
+        .map(|byte| lower[..byte].chars().count())
+        .unwrap_or(0)
+        .min(chars.len());
+    let start = center.saturating_sub(radius);
+    let end = (center + radius).min(chars.len());
+    let mut excerpt = chars[start..end]
+        .iter()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if start > 0 {
+        excerpt.insert(0, '…');
+    }
+    if end < chars.len() {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+pub(crate) fn search_messages(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<LocalAiChatSearchHit>, String> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let use_fts = fts_available(conn)
+        && terms
+            .iter()
+            .all(|term| term.chars().count() >= FTS_MIN_TERM_CHARS);
+    let select = "SELECT m.conversation_id, c.title, m.turn_id, m.role, m.content, m.created_at
+                  FROM ai_chat_messages m
+                  JOIN ai_chat_conversations c ON c.id = m.conversation_id";
+    let (sql, values) = if use_fts {
+        // 每个词作为短语匹配（trigram 下即子串匹配），词之间为 AND
+        let expression = terms
+            .iter()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        (
+            format!(
+                "{} JOIN ai_chat_messages_fts f ON f.rowid = m.id
+                 WHERE ai_chat_messages_fts MATCH ?1
+                 ORDER BY bm25(ai_chat_messages_fts) LIMIT {}",
+                select, limit
+            ),
+            vec![expression],
+        )
+    } else {
+        let conditions = (1..=terms.len())
+            .map(|index| format!("m.content LIKE ?{} ESCAPE '\\'", index))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        (
+            format!(
+                "{} WHERE {} ORDER BY m.created_at DESC LIMIT {}",
+                select, conditions, limit
+            ),
+            terms
+                .iter()
+                .map(|term| format!("%{}%", escape_like(term)))
+                .collect(),
+        )
+    };
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("搜索对话失败: {}", e))?;
+    let hits = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            let content: String = row.get(4)?;
+            Ok(LocalAiChatSearchHit {
+                conversation_id: row.get(0)?,
+                title: row.get(1)?,
+                turn_id: row.get(2)?,
+                role: row.get(3)?,
+                excerpt: excerpt_around(&content, &terms, EXCERPT_RADIUS),
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("搜索对话失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("搜索对话失败: {}", e))?;
+    Ok(hits)
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_string();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+// ============= 导出 =============
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "用户",
+        "assistant" => "助手",
+        "system" => "系统",
+        "tool" => "工具",
+        other => other,
+    }
+}
+
+/// 将对话渲染为 Markdown 正文（不含 frontmatter）
+pub(crate) fn render_chat_markdown(history: &LocalAiChatHistory) -> String {
+    let mut output = format!(
+        "> 导出自本地 AI 对话「{}」（{}）\n",
+        history.title, history.created_at
+    );
+    for turn in &history.turns {
+        if turn.content.trim().is_empty() {
+            continue;
+        }
+        output.push_str(&format!(
+            "\n### {}\n\n{}\n",
+            role_label(&turn.role),
+            turn.content.trim()
+        ));
+        if !turn.sources.is_empty() {
+            output.push_str("\n来源：\n\n");
+            for source in &turn.sources {
+                output.push_str(&format!(
+                    "- [{}] {}（{}:{}-{}）\n",
+                    source.index,
+                    source.title,
+                    source.file_path,
+                    source.start_line,
+                    source.end_line
+                ));
+            }
+        }
+    }
+    output
+}
+
+// ============= 命令 =============
+
+#[tauri::command]
+pub fn local_ai_get_chat_histories(
+    app_handle: AppHandle,
+) -> Result<Vec<LocalAiChatHistory>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    load_all_histories(&conn)
+}
+
+#[tauri::command]
+pub fn local_ai_save_chat_history(
+    app_handle: AppHandle,
+    history: LocalAiChatHistory,
+) -> Result<LocalAiChatHistory, String> {
+    require_plugin(&app_handle)?;
+    let mut conn = open_store(&app_handle)?;
+    save_history(&mut conn, &history)?;
+    load_history(&conn, &history.id)?.ok_or_else(|| format!("对话保存后未找到: {}", history.id))
+}
+
+#[tauri::command]
+pub fn local_ai_delete_chat_history(
+    app_handle: AppHandle,
+    history_id: String,
+) -> Result<LocalAiChatHistoryPage, String> {
+    require_plugin(&app_handle)?;
+    let mut conn = open_store(&app_handle)?;
+    delete_history(&mut conn, &history_id)?;
+    list_conversations(&conn, 0, DEFAULT_PAGE_SIZE, None, false)
+}
+
+#[tauri::command]
+pub fn local_ai_clear_chat_histories(
+    app_handle: AppHandle,
+) -> Result<Vec<LocalAiChatHistory>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    clear_histories(&conn)?;
+    Ok(Vec::new())
+}
+
+/// 分页列出对话（不含消息正文），置顶的排在前面
+#[tauri::command]
+pub fn local_ai_list_chat_conversations(
+    app_handle: AppHandle,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    tag: Option<String>,
+    pinned_only: Option<bool>,
+) -> Result<LocalAiChatHistoryPage, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    list_conversations(
+        &conn,
+        offset.unwrap_or(0),
+        limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        tag.as_deref(),
+        pinned_only.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub fn local_ai_get_chat_history(
+    app_handle: AppHandle,
+    history_id: String,
+) -> Result<Option<LocalAiChatHistory>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    load_history(&conn, &history_id)
+}
+
+/// 在所有对话消息中全文搜索
+#[tauri::command]
+pub fn local_ai_search_chat_histories(
+    app_handle: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<LocalAiChatSearchHit>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    search_messages(
+        &conn,
+        &query,
+        limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_PAGE_SIZE),
+    )
+}
+
+#[tauri::command]
+pub fn local_ai_set_chat_history_pinned(
+    app_handle: AppHandle,
+    history_id: String,
+    pinned: bool,
+) -> Result<(), String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    conn.execute(
+        "UPDATE ai_chat_conversations SET pinned = ?1 WHERE id = ?2",
+        params![pinned, history_id],
+    )
+    .map_err(|e| format!("更新对话置顶失败: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn local_ai_set_chat_history_tags(
+    app_handle: AppHandle,
+    history_id: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_store(&app_handle)?;
+    let tags = normalize_tags(tags);
+    conn.execute(
+        "UPDATE ai_chat_conversations SET tags = ?1 WHERE id = ?2",
+        params![to_json_text(&tags)?, history_id],
+    )
+    .map_err(|e| format!("更新对话标签失败: {}", e))?;
+    Ok(tags)
+}
+
+/// 将对话导出为工作区中的 Markdown 笔记，返回笔记的相对路径
+#[tauri::command]
+pub async fn local_ai_export_chat_history(
+    app_handle: AppHandle,
+    history_id: String,
+    category: Option<String>,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<String, String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let history = {
+        let conn = open_store(&app_handle)?;
+        load_history(&conn, &history_id)?.ok_or("对话不存在或已删除")?
+    };
+    let category = category
+        .map(|category| category.trim().to_string())
+        .filter(|category| !category.is_empty())
+        .unwrap_or_else(|| EXPORT_CATEGORY.to_string());
+
+    let file_path = crate::markdown::commands::create_markdown_file(
+        app_handle.clone(),
+        Some(category),
+        json!({
+            "title": history.title,
+            "content": render_chat_markdown(&history),
+            "tags": history.tags,
+            "chatId": history.id,
+        }),
+        index_manager,
+        cache_manager,
+    )
+    .await?;
+    let relative_path = Path::new(&file_path)
+        .strip_prefix(&workspace_root)
+        .unwrap_or(Path::new(&file_path))
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let conn = open_store(&app_handle)?;
+    conn.execute(
+        "UPDATE ai_chat_conversations SET note_path = ?1 WHERE id = ?2",
+        params![relative_path, history_id],
+    )
+    .map_err(|e| format!("记录导出路径失败: {}", e))?;
+    info!(
+        "💬 [LocalAI] 对话 {} 已导出为笔记 {}",
+        history_id, relative_path
+    );
+    Ok(relative_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::local_ai::LocalAiChatSource;
+
+    fn turn(id: &str, role: &str, content: &str) -> LocalAiChatTurn {
+        LocalAiChatTurn {
+            id: id.to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            sources: Vec::new(),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    fn history(id: &str, updated_at: &str, turns: Vec<LocalAiChatTurn>) -> LocalAiChatHistory {
+        LocalAiChatHistory {
+            id: id.to_string(),
+            title: format!("对话 {}", id),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: updated_at.to_string(),
+            turns,
+            current_node_id: None,
+            messages: Some(json!({ "nodes": [] })),
+            pinned: false,
+            tags: Vec::new(),
+            note_path: None,
+        }
+    }
+
+    fn store() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_history_tables(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn saves_lists_and_searches_conversations() {
+        let mut conn = store();
+        let mut first = history(
+            "a",
+            "2026-01-02T00:00:00Z",
+            vec![
+                turn("1", "user", "如何配置 llama-server 的上下文长度？"),
+                turn("2", "assistant", "使用 --ctx-size 参数。"),
+            ],
+        );
+        first.turns[1].sources.push(LocalAiChatSource {
+            index: 1,
+            note_id: "n".to_string(),
+            file_path: "docs/llama.md".to_string(),
+            title: "llama".to_string(),
+            start_line: 1,
+            end_line: 3,
+            kind: "text".to_string(),
+            excerpt: String::new(),
+        });
+        save_history(&mut conn, &first).unwrap();
+        save_history(
+            &mut conn,
+            &history(
+                "b",
+                "2026-01-03T00:00:00Z",
+                vec![turn("1", "user", "Rust 生命周期")],
+            ),
+        )
+        .unwrap();
+
+        let loaded = load_history(&conn, "a").unwrap().unwrap();
+        assert_eq!(loaded.turns.len(), 2);
+        assert_eq!(loaded.turns[1].sources[0].file_path, "docs/llama.md");
+        assert_eq!(loaded.messages, Some(json!({ "nodes": [] })));
+
+        // 置顶与标签不被后续保存覆盖
+        conn.execute(
+            "UPDATE ai_chat_conversations SET pinned = 1, tags = '[\"llm\"]' WHERE id = 'a'",
+            [],
+        )
+        .unwrap();
+        save_history(&mut conn, &first).unwrap();
+        let page = list_conversations(&conn, 0, 10, None, false).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items[0].id, "a");
+        assert!(page.items[0].pinned);
+        assert_eq!(page.items[0].message_count, 2);
+        assert_eq!(page.items[0].preview, "使用 --ctx-size 参数。");
+        let tagged = list_conversations(&conn, 0, 10, Some("llm"), false).unwrap();
+        assert_eq!(tagged.total, 1);
+        let second_page = list_conversations(&conn, 1, 1, None, false).unwrap();
+        assert_eq!(second_page.items[0].id, "b");
+
+        // 三个字符以上走 FTS，较短的词走 LIKE
+        let hits = search_messages(&conn, "ctx-size", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].conversation_id, "a");
+        assert_eq!(hits[0].role, "assistant");
+        let hits = search_messages(&conn, "上下文 llama", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        let hits = search_messages(&conn, "生命", 10).unwrap();
+        assert_eq!(hits[0].conversation_id, "b");
+
+        delete_history(&mut conn, "a").unwrap();
+        assert!(search_messages(&conn, "ctx-size", 10).unwrap().is_empty());
+        assert!(load_history(&conn, "a").unwrap().is_none());
+    }
+
+    #[test]
+    fn imports_legacy_histories_once() {
+        let mut conn = store();
+        let legacy = vec![
+            history(
+                "a",
+                "2026-01-02T00:00:00Z",
+                vec![turn("1", "user", "hello")],
+            ),
+            history("b", "2026-01-03T00:00:00Z", Vec::new()),
+        ];
+        assert_eq!(import_histories(&mut conn, &legacy).unwrap(), 2);
+        assert_eq!(import_histories(&mut conn, &legacy).unwrap(), 0);
+        assert_eq!(load_all_histories(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn renders_markdown_and_excerpts() {
+        let mut chat = history(
+            "a",
+            "2026-01-02T00:00:00Z",
+            vec![turn("1", "user", "问题"), turn("2", "assistant", "回答")],
+        );
+        chat.turns.push(turn("3", "assistant", "  "));
+        let markdown = render_chat_markdown(&chat);
+        assert!(markdown.contains("### 用户\n\n问题\n"));
+        assert!(markdown.contains("### 助手\n\n回答\n"));
+        assert_eq!(markdown.matches("###").count(), 2);
+
+        let content = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let excerpt = excerpt_around(&content, &["NEEDLE"], 10);
+        assert_eq!(
+            excerpt,
+            format!("…{}needle{}…", "a".repeat(10), "b".repeat(4))
+        );
+    }
+}
@@ -0,0 +1,719 @@
+// 本地 AI 提示词库与片段操作
+//
+// 内置的片段操作（解释代码、补充 TypeScript 类型、编写单元测试、Options API 转 Composition API）
+// 与工作区中 kind: prompt 的笔记一起组成提示词库，在对话中以 /命令 调用。模板里的 {{selection}}、
+// {{language}} 等变量在这里展开；片段操作的结果可另存为链接回原片段的笔记，或原片段的新版本。
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::code_snippets::{create_code_snippet, is_code_snippet_file};
+use crate::markdown::commands::{create_markdown_file, read_markdown_file, MarkdownFile};
+use crate::markdown::file_ops::FileNameGenerator;
+use crate::markdown::metadata::{try_parse_front_matter, ForkSource};
+use crate::markdown::{CacheManager, IndexManager};
+use crate::plugins::local_ai::{
+    local_ai_chat_stream, require_plugin, LocalAiChatRequest, LocalAiChatResponse, LocalAiMessage,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Component, Path};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager, State, Window};
+
+const PROMPT_KIND: &str = "prompt";
+const DEFAULT_LANGUAGE: &str = "text";
+
+const SNIPPET_SYSTEM_PROMPT: &str = "你是资深的前端与全栈工程师，负责处理用户代码片段库中的代码。回答使用简体中文；需要给出代码时输出完整、可直接替换的代码，并放在一个带语言标记的代码块中。";
+
+struct BuiltinPrompt {
+    command: &'static str,
+    title: &'static str,
+    description: &'static str,
+    template: &'static str,
+}
+
+const BUILTIN_PROMPTS: &[BuiltinPrompt] = &[
+    BuiltinPrompt {
+        command: "explain",
+        title: "解释代码",
+        description: "说明代码的作用、关键逻辑与潜在问题",
+        template: "请解释下面这段 {{language}} 代码：先用一两句话说明它的作用，再逐步讲解关键逻辑，最后指出潜在的问题或可改进之处。\n\n```{{language}}\n{{selection}}\n```",
+    },
+    BuiltinPrompt {
+        command: "add-types",
+        title: "补充 TypeScript 类型",
+        description: "为参数、返回值和数据结构补充准确的类型",
+        template: "请为下面的代码补充准确的 TypeScript 类型：为参数、返回值、props 与数据结构声明类型或接口，必要时使用泛型，避免 any，不要改变运行逻辑。输出完整代码。\n\n```{{language}}\n{{selection}}\n```",
+    },
+    BuiltinPrompt {
+        command: "write-tests",
+        title: "编写单元测试",
+        description: "覆盖正常路径与边界情况的单元测试",
+        template: "请为下面的 {{language}} 代码编写单元测试，覆盖正常路径、边界值与错误情况。前端代码优先使用 Vitest（Vue 组件配合 @vue/test-utils）。输出完整的测试文件。\n\n```{{language}}\n{{selection}}\n```",
+    },
+    BuiltinPrompt {
+        command: "to-composition",
+        title: "转换为 Composition API",
+        description: "把 Vue Options API 组件改写为 <script setup>",
+        template: "请把下面的 Vue Options API 组件改写为 Composition API（<script setup>）：data 改为 ref/reactive，computed、watch 与生命周期钩子改用对应函数，保持 props、emits、模板与行为不变。输出完整的单文件组件。\n\n```{{language}}\n{{selection}}\n```",
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiPromptTemplate {
+    // 斜杠命令名（不含 /）
+    pub command: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub template: String,
+    // 来自工作区笔记时为笔记的相对路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub builtin: bool,
+    // 模板引用了 {{selection}}，适合作为片段操作
+    pub uses_selection: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiSnippetActionRequest {
+    pub file_path: String,
+    pub command: String,
+    // 编辑器中选中的文本，为空时使用整个片段
+    #[serde(default)]
+    pub selection: Option<String>,
+    // 用户在命令后追加的说明
+    #[serde(default)]
+    pub input: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub enable_thinking: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocalAiSnippetActionTarget {
+    // 新建笔记，正文开头链接回原片段
+    Note,
+    // 在原片段旁新建一个版本，元数据沿用原片段
+    Version,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiSnippetActionSaveRequest {
+    pub file_path: String,
+    pub command: String,
+    pub content: String,
+    pub target: LocalAiSnippetActionTarget,
+}
+
+// 模板中 {{name}} 占位符的位置与变量名（允许花括号内有空白）
+fn placeholders(template: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find("{{") {
+        let start = offset + start;
+        let Some(end) = template[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + end;
+        let name = template[start + 2..end].trim();
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        {
+            found.push((start..end + 2, name));
+            offset = end + 2;
+        } else {
+            offset = start + 2;
+        }
+    }
+    found
+}
+
+fn referenced_variables(template: &str) -> HashSet<String> {
+    placeholders(template)
+        .into_iter()
+        .map(|(_, name)| name.to_lowercase())
+        .collect()
+}
+
+/// 展开 {{name}} 变量（名称不区分大小写）；未提供的变量原样保留
+pub(crate) fn expand_template(template: &str, variables: &HashMap<String, String>) -> String {
+    let variables: HashMap<String, &str> = variables
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.as_str()))
+        .collect();
+    let mut expanded = String::with_capacity(template.len());
+    let mut last = 0;
+    for (range, name) in placeholders(template) {
+        if let Some(value) = variables.get(&name.to_lowercase()) {
+            expanded.push_str(&template[last..range.start]);
+            expanded.push_str(value);
+            last = range.end;
+        }
+    }
+    expanded.push_str(&template[last..]);
+    expanded
+}
+
+/// 展开模板；模板未引用的选中内容和补充说明追加在末尾，避免被静默丢弃
+pub(crate) fn render_prompt(template: &str, variables: &HashMap<String, String>) -> String {
+    let referenced = referenced_variables(template);
+    let mut rendered = expand_template(template, variables).trim_end().to_string();
+    let variables: HashMap<String, &str> = variables
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.as_str()))
+        .collect();
+    let value = |name: &str| {
+        variables
+            .get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    if !referenced.contains("selection") {
+        if let Some(selection) = value("selection") {
+            let language = value("language").unwrap_or(DEFAULT_LANGUAGE);
+            rendered.push_str(&format!("\n\n```{}\n{}\n```", language, selection));
+        }
+    }
+    if !referenced.contains("input") {
+        if let Some(input) = value("input") {
+            rendered.push_str("\n\n");
+            rendered.push_str(input);
+        }
+    }
+    rendered.trim().to_string()
+}
+
+/// 由笔记标题生成斜杠命令名：小写，空白与标点折叠为连字符，保留中文等字母字符
+pub(crate) fn command_slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// 围栏代码块：info 为小写的语言标记行，code 为块内代码在原文中的字节范围（不含末尾换行）
+struct FencedBlock {
+    info: String,
+    code: Range<usize>,
+}
+
+fn fenced_blocks(content: &str) -> Vec<FencedBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        match current.take() {
+            None => {
+                if let Some(info) = line.trim_start().strip_prefix("```") {
+                    current = Some((info.trim().to_lowercase(), offset));
+                }
+            }
+            Some((info, start)) => {
+                if trimmed == "```" {
+                    let code = &content[start..line_start];
+                    let end = start + code.trim_end_matches(['\r', '\n']).len();
+                    blocks.push(FencedBlock {
+                        info,
+                        code: start..end,
+                    });
+                } else {
+                    current = Some((info, start));
+                }
+            }
+        }
+    }
+    blocks
+}
+
+// 优先与片段语言一致的代码块，其次第一个代码块
+fn pick_block<'a>(blocks: &'a [FencedBlock], language: Option<&str>) -> Option<&'a FencedBlock> {
+    let language = language.map(str::to_lowercase);
+    blocks
+        .iter()
+        .find(|block| {
+            language
+                .as_deref()
+                .is_some_and(|language| block.info.split_whitespace().next() == Some(language))
+        })
+        .or_else(|| blocks.first())
+}
+
+/// 取回复中的代码：优先与片段语言一致的代码块，其次第一个代码块，没有代码块时用全文
+pub(crate) fn extract_code_block(content: &str, language: Option<&str>) -> String {
+    match pick_block(&fenced_blocks(content), language) {
+        Some(block) => content[block.code.clone()]
+            .lines()
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => content.trim().to_string(),
+    }
+}
+
+/// 用新代码替换笔记正文中对应的代码块，其余内容保持不变；正文没有代码块时追加到末尾
+pub(crate) fn replace_code_block(body: &str, language: Option<&str>, code: &str) -> String {
+    let code = code.trim_end();
+    match pick_block(&fenced_blocks(body), language) {
+        Some(block) if block.code.is_empty() => format!(
+            "{}{}\n{}",
+            &body[..block.code.start],
+            code,
+            &body[block.code.end..]
+        ),
+        Some(block) => format!(
+            "{}{}{}",
+            &body[..block.code.start],
+            code,
+            &body[block.code.end..]
+        ),
+        None => format!(
+            "{}\n\n```{}\n{}\n```\n",
+            body.trim_end(),
+            language.unwrap_or(DEFAULT_LANGUAGE),
+            code
+        ),
+    }
+}
+
+// 新版本标题：在原标题（去掉已有的版本后缀）后追加 " (vN)"，N 取同目录下尚未使用的最小编号
+fn next_version_title(folder: &Path, title: &str) -> String {
+    let base = title
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" (v"))
+        .filter(|(_, number)| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+        .map(|(base, _)| base)
+        .unwrap_or(title);
+    (2..)
+        .map(|version| format!("{} (v{})", base, version))
+        .find(|candidate| {
+            !folder
+                .join(FileNameGenerator::generate_filename(candidate))
+                .exists()
+        })
+        .unwrap_or_else(|| title.to_string())
+}
+
+fn builtin_templates() -> Vec<LocalAiPromptTemplate> {
+    BUILTIN_PROMPTS
+        .iter()
+        .map(|prompt| LocalAiPromptTemplate {
+            command: prompt.command.to_string(),
+            title: prompt.title.to_string(),
+            description: Some(prompt.description.to_string()),
+            template: prompt.template.to_string(),
+            path: None,
+            builtin: true,
+            uses_selection: true,
+        })
+        .collect()
+}
+
+// 工作区中 kind: prompt 的笔记，正文即模板；命令名与内置命令或彼此重复时追加序号
+fn workspace_templates(app_handle: &AppHandle) -> Result<Vec<LocalAiPromptTemplate>, String> {
+    let Some(workspace_root) = get_workspace_root(app_handle)? else {
+        return Ok(Vec::new());
+    };
+    let Some(index_manager) = app_handle.try_state::<Arc<RwLock<Option<IndexManager>>>>() else {
+        return Ok(Vec::new());
+    };
+    let mut entries: Vec<_> = {
+        let manager_lock = index_manager
+            .read()
+            .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+        let Some(manager) = manager_lock.as_ref() else {
+            return Ok(Vec::new());
+        };
+        manager
+            .get_all_entries()
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .kind
+                    .as_deref()
+                    .is_some_and(|kind| kind.eq_ignore_ascii_case(PROMPT_KIND))
+                    && entry.file_path.extension().and_then(|e| e.to_str()) == Some("md")
+            })
+            .collect()
+    };
+    entries.sort_by(|a, b| a.title.cmp(&b.title));
+
+    let mut used: HashSet<String> = BUILTIN_PROMPTS
+        .iter()
+        .map(|prompt| prompt.command.to_string())
+        .collect();
+    let mut templates = Vec::new();
+    for entry in entries {
+        let raw = match std::fs::read_to_string(&entry.file_path) {
+            Ok(raw) => raw,
+            Err(error) => {
+                warn!(
+                    "⚠️ [LocalAI] 读取提示词模板失败 {}: {}",
+                    entry.file_path.display(),
+                    error
+                );
+                continue;
+            }
+        };
+        let (front_matter, body) = try_parse_front_matter(&raw);
+        let template = body.trim().to_string();
+        if template.is_empty() {
+            continue;
+        }
+        let base = Some(command_slug(&entry.title))
+            .filter(|slug| !slug.is_empty())
+            .unwrap_or_else(|| PROMPT_KIND.to_string());
+        let mut command = base.clone();
+        let mut sequence = 2;
+        while !used.insert(command.clone()) {
+            command = format!("{}-{}", base, sequence);
+            sequence += 1;
+        }
+        let uses_selection = referenced_variables(&template).contains("selection");
+        templates.push(LocalAiPromptTemplate {
+            command,
+            title: entry.title,
+            description: front_matter.and_then(|front_matter| front_matter.summary),
+            template,
+            path: Some(
+                entry
+                    .file_path
+                    .strip_prefix(&workspace_root)
+                    .unwrap_or(&entry.file_path)
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+            ),
+            builtin: false,
+            uses_selection,
+        });
+    }
+    Ok(templates)
+}
+
+fn find_template(app_handle: &AppHandle, command: &str) -> Result<LocalAiPromptTemplate, String> {
+    let command = command.trim().trim_start_matches('/');
+    builtin_templates()
+        .into_iter()
+        .chain(workspace_templates(app_handle)?)
+        .find(|template| template.command == command)
+        .ok_or_else(|| format!("未找到提示词命令: /{}", command))
+}
+
+// 变量默认值：日期；调用方提供的同名变量优先
+fn base_variables() -> HashMap<String, String> {
+    HashMap::from([(
+        "date".to_string(),
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+    )])
+}
+
+// 读取片段（笔记或纯代码文件），返回内容与是否为纯代码文件
+fn load_snippet(app_handle: &AppHandle, file_path: &str) -> Result<(MarkdownFile, bool), String> {
+    let workspace_root =
+        get_workspace_root(app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let path = workspace_root.join(file_path);
+    let relative = path
+        .strip_prefix(&workspace_root)
+        .map_err(|_| format!("路径不在工作区内: {}", file_path))?;
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("非法路径: {}", file_path));
+    }
+    let is_code_file = is_code_snippet_file(&workspace_root, &path);
+    let is_note = path.extension().and_then(|e| e.to_str()) == Some("md");
+    if !(is_code_file || is_note) || !path.is_file() {
+        return Err(format!("片段不存在: {}", file_path));
+    }
+    let snippet = read_markdown_file(
+        app_handle.clone(),
+        path.to_string_lossy().to_string(),
+        app_handle.state::<Arc<RwLock<CacheManager>>>(),
+    )?;
+    Ok((snippet, is_code_file))
+}
+
+fn snippet_variables(snippet: &MarkdownFile, selection: Option<&str>) -> HashMap<String, String> {
+    let mut variables = base_variables();
+    let selection = selection
+        .filter(|selection| !selection.trim().is_empty())
+        .unwrap_or(&snippet.content);
+    variables.insert("selection".to_string(), selection.to_string());
+    variables.insert(
+        "language".to_string(),
+        snippet
+            .language
+            .clone()
+            .filter(|language| !language.is_empty())
+            .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string()),
+    );
+    variables.insert("title".to_string(), snippet.title.clone());
+    if let Some(framework) = snippet.framework.as_ref() {
+        variables.insert("framework".to_string(), framework.clone());
+    }
+    variables
+}
+
+// ============= 命令 =============
+
+/// 列出内置片段操作与工作区提示词模板
+#[tauri::command]
+pub fn local_ai_list_prompts(app_handle: AppHandle) -> Result<Vec<LocalAiPromptTemplate>, String> {
+    require_plugin(&app_handle)?;
+    let mut templates = builtin_templates();
+    templates.extend(workspace_templates(&app_handle)?);
+    Ok(templates)
+}
+
+/// 展开斜杠命令对应的模板，返回可直接作为用户消息发送的文本
+#[tauri::command]
+pub fn local_ai_render_prompt(
+    app_handle: AppHandle,
+    command: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<String, String> {
+    require_plugin(&app_handle)?;
+    let template = find_template(&app_handle, &command)?;
+    let mut merged = base_variables();
+    merged.extend(variables.unwrap_or_default());
+    Ok(render_prompt(&template.template, &merged))
+}
+
+/// 对代码片段执行提示词命令，结果以 local_ai_chat_stream 的事件流式返回
+#[tauri::command]
+pub async fn local_ai_run_snippet_action(
+    app_handle: AppHandle,
+    window: Window,
+    request: LocalAiSnippetActionRequest,
+    request_id: String,
+) -> Result<LocalAiChatResponse, String> {
+    require_plugin(&app_handle)?;
+    let template = find_template(&app_handle, &request.command)?;
+    let (snippet, _) = load_snippet(&app_handle, &request.file_path)?;
+    let mut variables = snippet_variables(&snippet, request.selection.as_deref());
+    if let Some(input) = request.input {
+        variables.insert("input".to_string(), input);
+    }
+    let prompt = render_prompt(&template.template, &variables);
+    info!(
+        "🧩 [LocalAI] 片段操作 /{}：{}",
+        template.command, snippet.title
+    );
+
+    let chat_request = LocalAiChatRequest {
+        messages: vec![
+            LocalAiMessage {
+                role: "system".to_string(),
+                content: Value::String(SNIPPET_SYSTEM_PROMPT.to_string()),
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            LocalAiMessage {
+                role: "user".to_string(),
+                content: Value::String(prompt),
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ],
+        temperature: request.temperature,
+        enable_thinking: request.enable_thinking,
+        max_tokens: request.max_tokens,
+        workspace_context: false,
+        enable_tools: false,
+    };
+    local_ai_chat_stream(app_handle, window, chat_request, request_id).await
+}
+
+/// 保存片段操作的结果：新建链接回原片段的笔记，或在原片段旁新建一个版本；返回新文件路径
+#[tauri::command]
+pub async fn local_ai_save_snippet_action_result(
+    app_handle: AppHandle,
+    request: LocalAiSnippetActionSaveRequest,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<String, String> {
+    require_plugin(&app_handle)?;
+    if request.content.trim().is_empty() {
+        return Err("结果内容为空".to_string());
+    }
+    let template = find_template(&app_handle, &request.command)?;
+    let (snippet, is_code_file) = load_snippet(&app_handle, &request.file_path)?;
+    let category = Some(snippet.category_name.clone()).filter(|category| !category.is_empty());
+
+    let file_path = match request.target {
+        LocalAiSnippetActionTarget::Note => {
+            create_markdown_file(
+                app_handle.clone(),
+                category,
+                json!({
+                    "title": format!("{} - {}", snippet.title, template.title),
+                    "content": format!(
+                        "来源片段：[[{}]]\n\n{}\n",
+                        snippet.title,
+                        request.content.trim()
+                    ),
+                    "tags": snippet.tags,
+                }),
+                index_manager,
+                cache_manager,
+            )
+            .await?
+        }
+        LocalAiSnippetActionTarget::Version => {
+            let code = extract_code_block(&request.content, snippet.language.as_deref());
+            let metadata = json!({
+                "title": snippet.title,
+                "tags": snippet.tags,
+                "language": snippet.language,
+                "framework": snippet.framework,
+                "kind": snippet.kind,
+            });
+            if is_code_file {
+                // 同名文件已存在，create_code_snippet 会在文件名后追加时间戳
+                let file_name = Path::new(&snippet.file_path)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or("无效的片段路径")?
+                    .to_string();
+                create_code_snippet(
+                    app_handle.clone(),
+                    category,
+                    file_name,
+                    format!("{}\n", code.trim_end()),
+                    Some(metadata),
+                    index_manager,
+                    cache_manager,
+                )
+                .await?
+            } else {
+                // 笔记保留完整正文，只替换其中的代码块；标题加版本后缀，forked_from 指回原笔记
+                let folder = Path::new(&snippet.file_path)
+                    .parent()
+                    .ok_or("无效的片段路径")?;
+                let mut metadata = metadata;
+                metadata["title"] = json!(next_version_title(folder, &snippet.title));
+                metadata["type"] = json!(snippet.file_type);
+                metadata["content"] = json!(replace_code_block(
+                    &snippet.content,
+                    snippet.language.as_deref(),
+                    &code
+                ));
+                metadata["forkedFrom"] = json!(ForkSource {
+                    library: String::new(),
+                    url: String::new(),
+                    path: request.file_path.replace('\\', "/"),
+                    commit: None,
+                    forked_at: chrono::Utc::now().to_rfc3339(),
+                });
+                create_markdown_file(
+                    app_handle.clone(),
+                    category,
+                    metadata,
+                    index_manager,
+                    cache_manager,
+                )
+                .await?
+            }
+        }
+    };
+    info!(
+        "🧩 [LocalAI] 片段操作 /{} 的结果已保存: {}",
+        template.command, file_path
+    );
+    Ok(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_variables_and_appends_unreferenced_input() {
+        let vars = variables(&[
+            ("selection", "const a = 1"),
+            ("Language", "ts"),
+            ("input", "用英文回答"),
+        ]);
+        assert_eq!(
+            render_prompt(
+                "解释 {{ language }}：\n{{selection}} {{unknown}} {{}}",
+                &vars
+            ),
+            "解释 ts：\nconst a = 1 {{unknown}} {{}}\n\n用英文回答"
+        );
+        assert_eq!(
+            render_prompt("检查这段代码", &vars),
+            "检查这段代码\n\n```ts\nconst a = 1\n```\n\n用英文回答"
+        );
+    }
+
+    #[test]
+    fn extracts_matching_code_block() {
+        let reply = "说明\n```js\nold()\n```\n改写后：\n```ts\nconst a: number = 1\n```\n";
+        assert_eq!(extract_code_block(reply, Some("ts")), "const a: number = 1");
+        assert_eq!(extract_code_block(reply, Some("vue")), "old()");
+        assert_eq!(extract_code_block("  纯文本  ", None), "纯文本");
+    }
+
+    #[test]
+    fn replaces_code_block_and_keeps_the_rest_of_the_note() {
+        let body = "# 防抖\n\n说明文字\n\n```js\nold()\n```\n\n```ts\nlet a = 1\n```\n\n## 用法\n";
+        assert_eq!(
+            replace_code_block(body, Some("ts"), "const a: number = 1\n"),
+            "# 防抖\n\n说明文字\n\n```js\nold()\n```\n\n```ts\nconst a: number = 1\n```\n\n## 用法\n"
+        );
+        assert_eq!(
+            replace_code_block("前言\n\n```\n```\n", None, "x()"),
+            "前言\n\n```\nx()\n```\n"
+        );
+        assert_eq!(
+            replace_code_block("只有说明\n", Some("ts"), "x()"),
+            "只有说明\n\n```ts\nx()\n```\n"
+        );
+    }
+
+    #[test]
+    fn version_titles_skip_existing_files() {
+        let folder = std::env::temp_dir().join(format!("prompt-version-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&folder).unwrap();
+        assert_eq!(next_version_title(&folder, "防抖"), "防抖 (v2)");
+        std::fs::write(
+            folder.join(FileNameGenerator::generate_filename("防抖 (v2)")),
+            "",
+        )
+        .unwrap();
+        assert_eq!(next_version_title(&folder, "防抖"), "防抖 (v3)");
+        assert_eq!(next_version_title(&folder, "防抖 (v2)"), "防抖 (v3)");
+        assert_eq!(next_version_title(&folder, "Vue (vite)"), "Vue (vite) (v2)");
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn builds_command_slugs() {
+        assert_eq!(command_slug(" Review PR: Vue 组件 "), "review-pr-vue-组件");
+        assert_eq!(command_slug("!!"), "");
+    }
+}
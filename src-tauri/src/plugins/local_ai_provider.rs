@@ -0,0 +1,786 @@
+// 本地 AI 服务提供方
+//
+// 托管的 llama-server、任意 OpenAI 兼容服务（LM Studio、vLLM 等，可带 API Key）和 Ollama 原生 API
+// 统一实现 LocalAiProvider：健康检查、模型列表、聊天请求体和流式响应解析。
+// 上层始终按 OpenAI Chat Completions 格式构造请求并消费 SSE 分片，
+// Ollama 的请求体与 NDJSON 流在这里和 OpenAI 格式互相转换。
+
+use crate::plugins::local_ai::{server_context_size, LocalAiConfig};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const DEFAULT_OLLAMA_URL: &str = "http://127.0.0.1:11434";
+// LM Studio 的默认地址
+const DEFAULT_OPENAI_COMPATIBLE_URL: &str = "http://127.0.0.1:1234";
+const PROBE_TIMEOUT_SECS: u64 = 3;
+// 只有 llama-server 认识的采样/缓存参数，发往其他 OpenAI 兼容服务前移除
+const LLAMA_ONLY_FIELDS: &[&str] = &[
+    "top_k",
+    "min_p",
+    "repeat_penalty",
+    "repeat_last_n",
+    "chat_template_kwargs",
+    "cache_prompt",
+    "timings_per_token",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocalAiProviderKind {
+    // 由本应用启动并管理的 llama-server
+    #[default]
+    LlamaServer,
+    // 外部 OpenAI 兼容服务
+    OpenaiCompatible,
+    // Ollama 原生 API
+    Ollama,
+}
+
+impl LocalAiProviderKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LlamaServer => "llama-server",
+            Self::OpenaiCompatible => "OpenAI 兼容服务",
+            Self::Ollama => "Ollama",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAiProviderModel {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    // 参数量、量化等补充说明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// 流式响应中的一行：chunk 为 OpenAI 格式的分片，done 表示流已结束
+pub(crate) struct StreamLine {
+    pub chunk: Option<Value>,
+    pub done: bool,
+}
+
+pub(crate) trait LocalAiProvider: Send + Sync {
+    fn kind(&self) -> LocalAiProviderKind;
+
+    fn base_url(&self) -> &str;
+
+    /// 请求体中的 model 字段；外部服务未选择模型时为空
+    fn model_name(&self) -> &str;
+
+    fn chat_url(&self) -> String {
+        format!("{}/v1/chat/completions", self.base_url())
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+    }
+
+    /// 把 OpenAI 格式的请求体转换为该服务接受的格式
+    fn chat_body(&self, body: Value) -> Value;
+
+    fn parse_stream_line(&self, line: &str) -> Result<Option<StreamLine>, String> {
+        parse_sse_line(line)
+    }
+
+    /// 非流式响应中的回答正文
+    fn completion_text(&self, value: &Value) -> Option<String> {
+        value
+            .get("choices")
+            .and_then(|choices| choices.as_array())
+            .and_then(|choices| choices.first())
+            .and_then(|choice| choice.get("message"))
+            .and_then(|message| message.get("content"))
+            .and_then(|content| content.as_str())
+            .map(str::to_string)
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, bool>;
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<LocalAiProviderModel>, String>>;
+
+    /// 服务端实际使用的上下文长度；未知时由调用方回退到配置值
+    fn context_size(&self) -> BoxFuture<'_, Option<u32>> {
+        async { None }.boxed()
+    }
+
+    /// 用服务端分词器统计文本的 token 数；不支持时返回 None，由调用方估算
+    fn count_tokens<'a>(&'a self, _text: &'a str) -> BoxFuture<'a, Option<usize>> {
+        async { None }.boxed()
+    }
+}
+
+fn probe_client() -> Option<Client> {
+    Client::builder()
+        .timeout(Duration::from_secs(PROBE_TIMEOUT_SECS))
+        .build()
+        .ok()
+}
+
+/// 规范化外部服务地址：去掉末尾的 / 和 /v1，路径由各接口自行拼接
+pub fn normalize_base_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    url.strip_suffix("/v1").unwrap_or(url).to_string()
+}
+
+fn parse_sse_line(line: &str) -> Result<Option<StreamLine>, String> {
+    let line = line.trim();
+    let Some(data) = line.strip_prefix("data:") else {
+        return Ok(None);
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return Ok(Some(StreamLine {
+            chunk: None,
+            done: true,
+        }));
+    }
+    let value = serde_json::from_str::<Value>(data)
+        .map_err(|error| format!("解析本地 AI 流响应失败: {}", error))?;
+    Ok(Some(StreamLine {
+        chunk: Some(value),
+        done: false,
+    }))
+}
+
+async fn openai_models(client: RequestBuilder) -> Result<Vec<LocalAiProviderModel>, String> {
+    let response = client
+        .send()
+        .await
+        .map_err(|error| format!("获取模型列表失败: {}", error))?;
+    let status = response.status();
+    let value = response
+        .json::<Value>()
+        .await
+        .map_err(|error| format!("解析模型列表失败: {}", error))?;
+    if !status.is_success() {
+        return Err(format!("获取模型列表失败 {}: {}", status, value));
+    }
+    Ok(value
+        .get("data")
+        .and_then(|data| data.as_array())
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|model| model.get("id").and_then(|id| id.as_str()))
+                .map(|id| LocalAiProviderModel {
+                    id: id.to_string(),
+                    size: None,
+                    details: None,
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// ============= llama-server =============
+
+pub(crate) struct LlamaServerProvider {
+    base_url: String,
+}
+
+impl LocalAiProvider for LlamaServerProvider {
+    fn kind(&self) -> LocalAiProviderKind {
+        LocalAiProviderKind::LlamaServer
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // llama-server 只加载一个模型，model 字段不参与路由
+    fn model_name(&self) -> &str {
+        "local-ai"
+    }
+
+    fn chat_body(&self, body: Value) -> Value {
+        body
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, bool> {
+        async move {
+            let Some(client) = probe_client() else {
+                return false;
+            };
+            client
+                .get(format!("{}/health", self.base_url))
+                .send()
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false)
+        }
+        .boxed()
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<LocalAiProviderModel>, String>> {
+        async move {
+            let client = probe_client().ok_or("创建请求客户端失败")?;
+            openai_models(client.get(format!("{}/v1/models", self.base_url))).await
+        }
+        .boxed()
+    }
+
+    fn context_size(&self) -> BoxFuture<'_, Option<u32>> {
+        server_context_size(&self.base_url).boxed()
+    }
+
+    fn count_tokens<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Option<usize>> {
+        async move {
+            let client = probe_client()?;
+            let value = client
+                .post(format!("{}/tokenize", self.base_url))
+                .json(&json!({ "content": text, "add_special": false }))
+                .send()
+                .await
+                .ok()?
+                .json::<Value>()
+                .await
+                .ok()?;
+            value
+                .get("tokens")
+                .and_then(|tokens| tokens.as_array())
+                .map(Vec::len)
+        }
+        .boxed()
+    }
+}
+
+// ============= OpenAI 兼容服务 =============
+
+pub(crate) struct OpenAiCompatibleProvider {
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl LocalAiProvider for OpenAiCompatibleProvider {
+    fn kind(&self) -> LocalAiProviderKind {
+        LocalAiProviderKind::OpenaiCompatible
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.api_key.as_deref() {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    fn chat_body(&self, mut body: Value) -> Value {
+        if let Some(fields) = body.as_object_mut() {
+            for field in LLAMA_ONLY_FIELDS {
+                fields.remove(*field);
+            }
+            // -1 是 llama-server 的“不限制”，其他服务要求正整数
+            if fields
+                .get("max_tokens")
+                .and_then(Value::as_i64)
+                .is_some_and(|limit| limit <= 0)
+            {
+                fields.remove("max_tokens");
+            }
+            fields.insert("model".to_string(), Value::String(self.model.clone()));
+        }
+        body
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, bool> {
+        async move {
+            let Some(client) = probe_client() else {
+                return false;
+            };
+            self.authorize(client.get(format!("{}/v1/models", self.base_url)))
+                .send()
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false)
+        }
+        .boxed()
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<LocalAiProviderModel>, String>> {
+        async move {
+            let client = probe_client().ok_or("创建请求客户端失败")?;
+            openai_models(self.authorize(client.get(format!("{}/v1/models", self.base_url)))).await
+        }
+        .boxed()
+    }
+}
+
+// ============= Ollama =============
+
+pub(crate) struct OllamaProvider {
+    base_url: String,
+    model: String,
+    ctx_size: u32,
+}
+
+// data:image/png;base64,xxx -> xxx
+fn data_url_payload(url: &str) -> Option<&str> {
+    url.strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(_, payload)| payload)
+}
+
+/// OpenAI 格式的消息转换为 Ollama /api/chat 的消息
+fn ollama_messages(messages: &Value) -> Value {
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+    let converted = messages
+        .as_array()
+        .map(|messages| messages.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|message| {
+            let role = message.get("role").cloned().unwrap_or(json!("user"));
+            let mut images = Vec::new();
+            let content = match message.get("content") {
+                Some(Value::String(text)) => text.clone(),
+                Some(Value::Array(parts)) => {
+                    let mut texts = Vec::new();
+                    for part in parts {
+                        match part.get("type").and_then(Value::as_str) {
+                            Some("text") => {
+                                if let Some(text) = part.get("text").and_then(Value::as_str) {
+                                    texts.push(text.to_string());
+                                }
+                            }
+                            Some("image_url") => {
+                                if let Some(payload) = part
+                                    .pointer("/image_url/url")
+                                    .and_then(Value::as_str)
+                                    .and_then(data_url_payload)
+                                {
+                                    images.push(Value::String(payload.to_string()));
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    texts.join("\n")
+                }
+                _ => String::new(),
+            };
+
+            let mut converted = Map::new();
+            converted.insert("role".to_string(), role);
+            converted.insert("content".to_string(), Value::String(content));
+            if !images.is_empty() {
+                converted.insert("images".to_string(), Value::Array(images));
+            }
+            if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+                let calls: Vec<Value> = calls
+                    .iter()
+                    .map(|call| {
+                        let name = call
+                            .pointer("/function/name")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default();
+                        if let Some(id) = call.get("id").and_then(Value::as_str) {
+                            tool_names.insert(id.to_string(), name.to_string());
+                        }
+                        // Ollama 的 arguments 是对象而不是 JSON 字符串
+                        let arguments = match call.pointer("/function/arguments") {
+                            Some(Value::String(text)) => {
+                                serde_json::from_str(text).unwrap_or_else(|_| json!({}))
+                            }
+                            Some(value) => value.clone(),
+                            None => json!({}),
+                        };
+                        json!({ "function": { "name": name, "arguments": arguments } })
+                    })
+                    .collect();
+                converted.insert("tool_calls".to_string(), Value::Array(calls));
+            }
+            if let Some(name) = message
+                .get("tool_call_id")
+                .and_then(Value::as_str)
+                .and_then(|id| tool_names.get(id))
+            {
+                converted.insert("tool_name".to_string(), Value::String(name.clone()));
+            }
+            Value::Object(converted)
+        })
+        .collect();
+    Value::Array(converted)
+}
+
+/// Ollama 的一行 NDJSON 转换为 OpenAI 格式的流分片
+fn ollama_chunk(value: &Value) -> Result<StreamLine, String> {
+    if let Some(error) = value.get("error").and_then(Value::as_str) {
+        return Err(format!("Ollama 返回错误: {}", error));
+    }
+    let message = value.get("message").cloned().unwrap_or_default();
+    let mut delta = Map::new();
+    if let Some(content) = message.get("content").and_then(Value::as_str) {
+        delta.insert("content".to_string(), json!(content));
+    }
+    if let Some(thinking) = message
+        .get("thinking")
+        .and_then(Value::as_str)
+        .filter(|text| !text.is_empty())
+    {
+        delta.insert("reasoning_content".to_string(), json!(thinking));
+    }
+    if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+        let calls: Vec<Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(index, call)| {
+                json!({
+                    "index": index,
+                    "type": "function",
+                    "function": {
+                        "name": call.pointer("/function/name").cloned().unwrap_or_default(),
+                        "arguments": call
+                            .pointer("/function/arguments")
+                            .map(Value::to_string)
+                            .unwrap_or_else(|| "{}".to_string()),
+                    }
+                })
+            })
+            .collect();
+        delta.insert("tool_calls".to_string(), Value::Array(calls));
+    }
+
+    let done = value.get("done").and_then(Value::as_bool).unwrap_or(false);
+    let finish_reason = if done {
+        value.get("done_reason").cloned().unwrap_or(json!("stop"))
+    } else {
+        Value::Null
+    };
+    let mut chunk = json!({
+        "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }]
+    });
+    if done {
+        let prompt = value.get("prompt_eval_count").and_then(Value::as_u64);
+        let completion = value.get("eval_count").and_then(Value::as_u64);
+        if let (Some(prompt), Some(completion)) = (prompt, completion) {
+            chunk["usage"] = json!({
+                "prompt_tokens": prompt,
+                "completion_tokens": completion,
+                "total_tokens": prompt + completion,
+            });
+        }
+        // eval_duration 单位为纳秒
+        if let (Some(completion), Some(duration)) = (
+            completion,
+            value
+                .get("eval_duration")
+                .and_then(Value::as_u64)
+                .filter(|duration| *duration > 0),
+        ) {
+            chunk["timings"] = json!({
+                "predicted_ms": duration as f64 / 1_000_000.0,
+                "predicted_per_second": completion as f64 / (duration as f64 / 1_000_000_000.0),
+            });
+        }
+    }
+    Ok(StreamLine {
+        chunk: Some(chunk),
+        done,
+    })
+}
+
+impl LocalAiProvider for OllamaProvider {
+    fn kind(&self) -> LocalAiProviderKind {
+        LocalAiProviderKind::Ollama
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn chat_url(&self) -> String {
+        format!("{}/api/chat", self.base_url)
+    }
+
+    fn chat_body(&self, body: Value) -> Value {
+        let mut options = Map::new();
+        for field in [
+            "temperature",
+            "top_p",
+            "top_k",
+            "min_p",
+            "repeat_penalty",
+            "repeat_last_n",
+        ] {
+            if let Some(value) = body.get(field) {
+                options.insert(field.to_string(), value.clone());
+            }
+        }
+        if let Some(limit) = body
+            .get("max_tokens")
+            .and_then(Value::as_i64)
+            .filter(|limit| *limit > 0)
+        {
+            options.insert("num_predict".to_string(), json!(limit));
+        }
+        if self.ctx_size > 0 {
+            options.insert("num_ctx".to_string(), json!(self.ctx_size));
+        }
+
+        let mut converted = json!({
+            "model": self.model,
+            "messages": ollama_messages(body.get("messages").unwrap_or(&Value::Null)),
+            "stream": body.get("stream").and_then(Value::as_bool).unwrap_or(false),
+            "options": options,
+        });
+        // 不支持思考的模型收到 think 字段会报错，只在开启时发送
+        if body
+            .pointer("/chat_template_kwargs/enable_thinking")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            converted["think"] = json!(true);
+        }
+        if let Some(tools) = body.get("tools") {
+            converted["tools"] = tools.clone();
+        }
+        converted
+    }
+
+    fn parse_stream_line(&self, line: &str) -> Result<Option<StreamLine>, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let value = serde_json::from_str::<Value>(line)
+            .map_err(|error| format!("解析 Ollama 流响应失败: {}", error))?;
+        ollama_chunk(&value).map(Some)
+    }
+
+    fn completion_text(&self, value: &Value) -> Option<String> {
+        value
+            .pointer("/message/content")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, bool> {
+        async move {
+            let Some(client) = probe_client() else {
+                return false;
+            };
+            client
+                .get(format!("{}/api/version", self.base_url))
+                .send()
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false)
+        }
+        .boxed()
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<LocalAiProviderModel>, String>> {
+        async move {
+            let client = probe_client().ok_or("创建请求客户端失败")?;
+            let response = client
+                .get(format!("{}/api/tags", self.base_url))
+                .send()
+                .await
+                .map_err(|error| format!("获取 Ollama 模型列表失败: {}", error))?;
+            let value = response
+                .json::<Value>()
+                .await
+                .map_err(|error| format!("解析 Ollama 模型列表失败: {}", error))?;
+            Ok(value
+                .get("models")
+                .and_then(|models| models.as_array())
+                .map(|models| {
+                    models
+                        .iter()
+                        .filter_map(|model| {
+                            let id = model.get("name").and_then(Value::as_str)?;
+                            let details = [
+                                model.pointer("/details/parameter_size"),
+                                model.pointer("/details/quantization_level"),
+                            ]
+                            .into_iter()
+                            .flatten()
+                            .filter_map(Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join(" · ");
+                            Some(LocalAiProviderModel {
+                                id: id.to_string(),
+                                size: model.get("size").and_then(Value::as_u64),
+                                details: (!details.is_empty()).then_some(details),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+        .boxed()
+    }
+}
+
+// ============= 创建 =============
+
+fn external_base_url(config: &LocalAiConfig, default_url: &str) -> String {
+    config
+        .provider_base_url
+        .as_deref()
+        .map(normalize_base_url)
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| default_url.to_string())
+}
+
+fn external_model(config: &LocalAiConfig) -> String {
+    config
+        .provider_model
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// 按配置创建当前使用的服务提供方
+pub(crate) fn provider_for(config: &LocalAiConfig) -> Box<dyn LocalAiProvider> {
+    match config.provider {
+        LocalAiProviderKind::LlamaServer => Box::new(LlamaServerProvider {
+            base_url: format!("http://{}:{}", config.host, config.port),
+        }),
+        LocalAiProviderKind::OpenaiCompatible => Box::new(OpenAiCompatibleProvider {
+            base_url: external_base_url(config, DEFAULT_OPENAI_COMPATIBLE_URL),
+            api_key: config
+                .api_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string),
+            model: external_model(config),
+        }),
+        LocalAiProviderKind::Ollama => Box::new(OllamaProvider {
+            base_url: external_base_url(config, DEFAULT_OLLAMA_URL),
+            model: external_model(config),
+            ctx_size: config.ctx_size,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_openai_messages_and_body_for_ollama() {
+        let provider = OllamaProvider {
+            base_url: DEFAULT_OLLAMA_URL.to_string(),
+            model: "qwen3:4b".to_string(),
+            ctx_size: 8192,
+        };
+        let body = provider.chat_body(json!({
+            "model": "local-ai",
+            "temperature": 0.3,
+            "top_k": 40,
+            "max_tokens": -1,
+            "chat_template_kwargs": { "enable_thinking": false },
+            "stream": true,
+            "messages": [
+                { "role": "user", "content": [
+                    { "type": "text", "text": "这张图是什么？" },
+                    { "type": "image_url", "image_url": { "url": "data:image/png;base64,AAAA" } }
+                ]},
+                { "role": "assistant", "content": "", "tool_calls": [
+                    { "id": "c1", "type": "function", "function": { "name": "read_note", "arguments": "{\"path\":\"a.md\"}" } }
+                ]},
+                { "role": "tool", "tool_call_id": "c1", "content": "{}" }
+            ]
+        }));
+
+        assert_eq!(body["model"], "qwen3:4b");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["options"]["top_k"], 40);
+        assert_eq!(body["options"]["num_ctx"], 8192);
+        assert!(body["options"].get("num_predict").is_none());
+        assert!(body.get("think").is_none());
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[0]["content"], "这张图是什么？");
+        assert_eq!(messages[0]["images"][0], "AAAA");
+        assert_eq!(
+            messages[1]["tool_calls"][0]["function"]["arguments"]["path"],
+            "a.md"
+        );
+        assert_eq!(messages[2]["tool_name"], "read_note");
+    }
+
+    #[test]
+    fn maps_ollama_stream_lines_to_openai_chunks() {
+        let provider = OllamaProvider {
+            base_url: DEFAULT_OLLAMA_URL.to_string(),
+            model: "qwen3:4b".to_string(),
+            ctx_size: 0,
+        };
+        let line = provider
+            .parse_stream_line(
+                r#"{"message":{"role":"assistant","content":"你好","thinking":"嗯"},"done":false}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(!line.done);
+        let chunk = line.chunk.unwrap();
+        assert_eq!(chunk["choices"][0]["delta"]["content"], "你好");
+        assert_eq!(chunk["choices"][0]["delta"]["reasoning_content"], "嗯");
+
+        let line = provider
+            .parse_stream_line(
+                r#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"list_tags","arguments":{}}}]},"done":true,"done_reason":"stop","prompt_eval_count":12,"eval_count":8,"eval_duration":400000000}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(line.done);
+        let chunk = line.chunk.unwrap();
+        assert_eq!(
+            chunk["choices"][0]["delta"]["tool_calls"][0]["function"]["arguments"],
+            "{}"
+        );
+        assert_eq!(chunk["usage"]["total_tokens"], 20);
+        assert_eq!(chunk["timings"]["predicted_per_second"], 20.0);
+        assert!(provider.parse_stream_line("   ").unwrap().is_none());
+        assert!(provider
+            .parse_stream_line(r#"{"error":"model not found"}"#)
+            .is_err());
+    }
+
+    #[test]
+    fn strips_llama_only_fields_for_openai_compatible() {
+        let provider = OpenAiCompatibleProvider {
+            base_url: normalize_base_url("http://localhost:1234/v1/"),
+            api_key: None,
+            model: "gpt-4o-mini".to_string(),
+        };
+        assert_eq!(provider.base_url(), "http://localhost:1234");
+        let body = provider.chat_body(json!({
+            "model": "local-ai",
+            "top_p": 0.9,
+            "top_k": 40,
+            "max_tokens": -1,
+            "cache_prompt": true,
+            "messages": []
+        }));
+        assert_eq!(body["model"], "gpt-4o-mini");
+        assert_eq!(body["top_p"], 0.9);
+        assert!(body.get("top_k").is_none());
+        assert!(body.get("max_tokens").is_none());
+        assert!(body.get("cache_prompt").is_none());
+    }
+}
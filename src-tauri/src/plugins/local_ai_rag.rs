@@ -0,0 +1,498 @@
+// 基于工作区的问答（RAG）
+//
+// 以最后一条用户消息为查询，通过搜索索引取回最相关的笔记，在每篇笔记中挑出命中最多的
+// 段落或代码块，按模型上下文预算编号注入系统提示。模型以 [n] 引用来源，
+// 前端据返回的来源列表把编号映射回笔记 id 与行号范围。
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::file_ops::get_relative_path;
+use crate::markdown::index_optimized::IndexEntry;
+use crate::markdown::IndexManager;
+use crate::plugins::local_ai::{LocalAiChatSource, LocalAiConfig, LocalAiMessage};
+use crate::plugins::local_ai_provider::{provider_for, LocalAiProvider};
+use crate::text_utils::{is_cjk, truncate_chars};
+use log::{debug, warn};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+
+// 从索引取回的候选笔记数
+const RETRIEVE_CANDIDATES: usize = 8;
+// 注入上下文的来源上限
+const MAX_SOURCES: usize = 6;
+// 每篇笔记最多引用的段落数
+const MAX_PASSAGES_PER_NOTE: usize = 2;
+// 相邻段落合并后的长度上限（字符）
+const PASSAGE_MAX_CHARS: usize = 900;
+// 上下文最多占用模型窗口的比例
+const CONTEXT_WINDOW_SHARE: f32 = 0.5;
+// 预算低于该值时不注入上下文
+const MIN_CONTEXT_TOKENS: usize = 200;
+const DEFAULT_CTX_SIZE: u32 = 4096;
+const EXCERPT_CHARS: usize = 200;
+
+const CONTEXT_INSTRUCTIONS: &str = "You are answering questions about the user's own notes. \
+Use the numbered workspace sources below as your primary evidence and cite them inline as [1], [2] \
+right after the statements they support. Only cite numbers that appear below. \
+If the sources do not contain the answer, say so before answering from general knowledge.";
+
+const SOURCES_HEADER: &str = "\n\n# Workspace sources\n\n";
+
+/// 取回的笔记（原始文件内容用于计算行号）
+pub struct RetrievedNote {
+    pub note_id: String,
+    pub file_path: String,
+    pub title: String,
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Passage {
+    start_line: usize,
+    end_line: usize,
+    kind: &'static str,
+    text: String,
+}
+
+/// 粗略估算 token 数：CJK 按一字一 token，其余按四字符一 token
+pub fn estimate_tokens(text: &str) -> usize {
+    let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), c| {
+        if is_cjk(c) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + other.div_ceil(4)
+}
+
+pub(crate) fn message_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn last_user_query(messages: &[LocalAiMessage]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|message| message.role == "user")
+        .map(|message| message_text(&message.content).trim().to_string())
+        .filter(|query| !query.is_empty())
+}
+
+fn flush_terms(word: &mut String, cjk_run: &mut Vec<char>, terms: &mut Vec<String>) {
+    if word.chars().count() >= 2 {
+        terms.push(word.to_lowercase());
+    }
+    word.clear();
+    if cjk_run.len() == 1 {
+        terms.push(cjk_run[0].to_string());
+    }
+    for pair in cjk_run.windows(2) {
+        terms.push(pair.iter().collect());
+    }
+    cjk_run.clear();
+}
+
+// 查询词：ASCII 单词（至少两个字符）与 CJK 二元组
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+    for c in query.chars() {
+        if is_cjk(c) {
+            flush_terms(&mut word, &mut Vec::new(), &mut terms);
+            cjk_run.push(c);
+        } else if c.is_alphanumeric() || c == '_' {
+            flush_terms(&mut String::new(), &mut cjk_run, &mut terms);
+            word.push(c);
+        } else {
+            flush_terms(&mut word, &mut cjk_run, &mut terms);
+        }
+    }
+    flush_terms(&mut word, &mut cjk_run, &mut terms);
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+// 跳过 Front Matter，返回正文首行的行号（从 0 开始）
+fn body_start_line(lines: &[&str]) -> usize {
+    if lines.first().map(|line| line.trim()) != Some("---") {
+        return 0;
+    }
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| line.trim() == "---")
+        .map(|offset| offset + 2)
+        .unwrap_or(0)
+}
+
+// 按空行与围栏代码块切分段落，同一标题下相邻的短段落合并；行号从 1 开始
+fn split_passages(raw: &str) -> Vec<Passage> {
+    let lines: Vec<&str> = raw.lines().collect();
+    let mut blocks: Vec<Passage> = Vec::new();
+    let mut current: Option<Passage> = None;
+    let mut fence: Option<&str> = None;
+
+    for (index, line) in lines.iter().enumerate().skip(body_start_line(&lines)) {
+        let line_no = index + 1;
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if let Some(block) = current.as_mut() {
+                block.text.push_str(line);
+                block.text.push('\n');
+                block.end_line = line_no;
+            }
+            if trimmed.starts_with(marker) {
+                fence = None;
+                blocks.extend(current.take());
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            blocks.extend(current.take());
+            fence = Some(&trimmed[..3]);
+            current = Some(Passage {
+                start_line: line_no,
+                end_line: line_no,
+                kind: "code",
+                text: format!("{}\n", line),
+            });
+            continue;
+        }
+        let is_heading = trimmed.starts_with('#');
+        if trimmed.is_empty() || is_heading {
+            blocks.extend(current.take());
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        let block = current.get_or_insert_with(|| Passage {
+            start_line: line_no,
+            end_line: line_no,
+            kind: "text",
+            text: String::new(),
+        });
+        block.text.push_str(line);
+        block.text.push('\n');
+        block.end_line = line_no;
+    }
+    blocks.extend(current);
+
+    // 合并：标题块总是与其后的文本合并，文本块在长度允许时继续合并
+    let mut passages: Vec<Passage> = Vec::new();
+    for block in blocks {
+        if let Some(last) = passages.last_mut() {
+            let last_is_heading =
+                last.text.trim_start().starts_with('#') && last.text.trim().lines().count() == 1;
+            let starts_heading = block.text.trim_start().starts_with('#');
+            if last.kind == "text"
+                && block.kind == "text"
+                && !starts_heading
+                && (last_is_heading
+                    || last.text.chars().count() + block.text.chars().count() <= PASSAGE_MAX_CHARS)
+            {
+                last.text.push('\n');
+                last.text.push_str(&block.text);
+                last.end_line = block.end_line;
+                continue;
+            }
+        }
+        passages.push(block);
+    }
+    for passage in &mut passages {
+        let trimmed = passage.text.trim_end().to_string();
+        passage.text = trimmed;
+    }
+    passages
+}
+
+fn score_passage(text: &str, terms: &[String]) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let lower = text.to_lowercase();
+    let hits = terms
+        .iter()
+        .filter(|term| lower.contains(term.as_str()))
+        .count();
+    hits as f32 / terms.len() as f32
+}
+
+fn format_source(source: &LocalAiChatSource, text: &str) -> String {
+    let fence = if source.kind == "code" {
+        ""
+    } else {
+        "```text\n"
+    };
+    let close = if source.kind == "code" { "" } else { "\n```" };
+    format!(
+        "[{}] {} ({}:{}-{})\n{}{}{}",
+        source.index,
+        source.title,
+        source.file_path,
+        source.start_line,
+        source.end_line,
+        fence,
+        text,
+        close
+    )
+}
+
+/// 在 token 预算内为候选笔记挑选段落，返回拼好的上下文与编号来源
+pub fn build_context(
+    notes: &[RetrievedNote],
+    query: &str,
+    budget_tokens: usize,
+) -> (String, Vec<LocalAiChatSource>) {
+    let terms = query_terms(query);
+    let mut remaining = budget_tokens
+        .saturating_sub(estimate_tokens(CONTEXT_INSTRUCTIONS) + estimate_tokens(SOURCES_HEADER));
+    let mut sections = Vec::new();
+    let mut sources: Vec<LocalAiChatSource> = Vec::new();
+
+    'notes: for note in notes {
+        let mut scored: Vec<(f32, Passage)> = split_passages(&note.raw)
+            .into_iter()
+            .map(|passage| (score_passage(&passage.text, &terms), passage))
+            .collect();
+        // 只有标题命中而正文没有命中时，退回使用笔记开头的段落
+        let any_hit = scored.iter().any(|(score, _)| *score > 0.0);
+        scored.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then(a.1.start_line.cmp(&b.1.start_line))
+        });
+        let picked = scored
+            .into_iter()
+            .filter(|(score, _)| !any_hit || *score > 0.0)
+            .take(if any_hit { MAX_PASSAGES_PER_NOTE } else { 1 });
+
+        for (_, passage) in picked {
+            if sources.len() >= MAX_SOURCES {
+                break 'notes;
+            }
+            let mut source = LocalAiChatSource {
+                index: sources.len() + 1,
+                note_id: note.note_id.clone(),
+                file_path: note.file_path.clone(),
+                title: note.title.clone(),
+                start_line: passage.start_line,
+                end_line: passage.end_line,
+                kind: passage.kind.to_string(),
+                excerpt: truncate_chars(&passage.text, EXCERPT_CHARS).to_string(),
+            };
+            let mut section = format_source(&source, &passage.text);
+            let mut cost = estimate_tokens(&section);
+            if cost > remaining {
+                // 放不下时截断正文；截断后仍太短则停止添加
+                let room_chars = remaining.saturating_sub(estimate_tokens(&source.title) + 16) * 2;
+                if room_chars < 120 {
+                    break 'notes;
+                }
+                let text = truncate_chars(&passage.text, room_chars);
+                source.end_line = passage.start_line + text.lines().count().saturating_sub(1);
+                section = format_source(&source, text);
+                cost = estimate_tokens(&section);
+                if cost > remaining {
+                    break 'notes;
+                }
+            }
+            // 段落之间的空行
+            remaining = remaining.saturating_sub(cost + 1);
+            sections.push(section);
+            sources.push(source);
+        }
+    }
+
+    if sources.is_empty() {
+        return (String::new(), sources);
+    }
+    let context = format!(
+        "{}{}{}",
+        CONTEXT_INSTRUCTIONS,
+        SOURCES_HEADER,
+        sections.join("\n\n")
+    );
+    (context, sources)
+}
+
+/// 把上下文并入首条系统消息（没有则插入一条），以兼容只接受首位 system 的聊天模板
+pub fn inject_context(messages: &mut Vec<LocalAiMessage>, context: &str) {
+    if let Some(first) = messages.first_mut() {
+        if first.role == "system" {
+            if let Value::String(text) = &mut first.content {
+                text.push_str("\n\n");
+                text.push_str(context);
+                return;
+            }
+        }
+    }
+    messages.insert(
+        0,
+        LocalAiMessage {
+            role: "system".to_string(),
+            content: Value::String(context.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+    );
+}
+
+/// 上下文预算：窗口减去已有消息与回复预留，且不超过窗口的一半
+pub fn context_budget(ctx_size: u32, messages: &[LocalAiMessage], max_tokens: i64) -> usize {
+    let ctx = ctx_size as usize;
+    let used: usize = messages
+        .iter()
+        .map(|message| estimate_tokens(&message_text(&message.content)) + 4)
+        .sum();
+    let reserve = if max_tokens > 0 {
+        max_tokens as usize
+    } else {
+        ctx / 4
+    };
+    let available = ctx.saturating_sub(used + reserve);
+    available.min((ctx as f32 * CONTEXT_WINDOW_SHARE) as usize)
+}
+
+fn retrieve_notes(app_handle: &AppHandle, query: &str) -> Result<Vec<RetrievedNote>, String> {
+    let Some(workspace_root) = get_workspace_root(app_handle)? else {
+        return Ok(Vec::new());
+    };
+    let Some(index_manager) = app_handle.try_state::<Arc<RwLock<Option<IndexManager>>>>() else {
+        return Ok(Vec::new());
+    };
+    let results: Vec<IndexEntry> = {
+        let manager_lock = index_manager
+            .read()
+            .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+        let Some(manager) = manager_lock.as_ref() else {
+            return Ok(Vec::new());
+        };
+        manager
+            .search(query)
+            .into_iter()
+            .take(RETRIEVE_CANDIDATES)
+            .map(|(entry, _)| entry)
+            .collect()
+    };
+
+    Ok(results
+        .into_iter()
+        .filter_map(|entry| {
+            let file_path = get_relative_path(&workspace_root, &entry.file_path).ok()?;
+            Some(RetrievedNote {
+                raw: std::fs::read_to_string(&entry.file_path).unwrap_or(entry.full_content),
+                file_path,
+                note_id: entry.id,
+                title: entry.title,
+            })
+        })
+        .collect())
+}
+
+/// 为聊天请求检索工作区上下文并注入消息，返回编号来源
+pub(crate) async fn prepare_workspace_context(
+    app_handle: &AppHandle,
+    config: &LocalAiConfig,
+    messages: &mut Vec<LocalAiMessage>,
+    max_tokens: i64,
+) -> Result<Vec<LocalAiChatSource>, String> {
+    let Some(query) = last_user_query(messages) else {
+        return Ok(Vec::new());
+    };
+    let notes = retrieve_notes(app_handle, &query)?;
+    if notes.is_empty() {
+        debug!("🔎 [LocalAI] 工作区问答未检索到相关笔记");
+        return Ok(Vec::new());
+    }
+
+    let ctx_size = match provider_for(config).context_size().await {
+        Some(size) if size > 0 => size,
+        _ if config.ctx_size > 0 => config.ctx_size,
+        _ => DEFAULT_CTX_SIZE,
+    };
+    let budget = context_budget(ctx_size, messages, max_tokens);
+    if budget < MIN_CONTEXT_TOKENS {
+        warn!(
+            "[LocalAI] 上下文窗口剩余不足（ctx={}，可用 {} tokens），跳过工作区上下文",
+            ctx_size, budget
+        );
+        return Ok(Vec::new());
+    }
+
+    let (context, sources) = build_context(&notes, &query, budget);
+    if !sources.is_empty() {
+        inject_context(messages, &context);
+        debug!(
+            "🔎 [LocalAI] 注入 {} 个工作区来源（预算 {} tokens）",
+            sources.len(),
+            budget
+        );
+    }
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: &str, raw: &str) -> RetrievedNote {
+        RetrievedNote {
+            note_id: id.to_string(),
+            file_path: format!("notes/{}.md", id),
+            title: id.to_string(),
+            raw: raw.to_string(),
+        }
+    }
+
+    #[test]
+    fn picks_matching_passages_with_file_line_ranges() {
+        let raw = "---\ntitle: deploy\n---\n# Intro\nGeneral words here.\n\n# Docker\nBuild the docker image first.\n\n```sh\ndocker build -t app .\n```\n";
+        let notes = vec![
+            note("deploy", raw),
+            note("other", "nothing relevant at all"),
+        ];
+        let (context, sources) = build_context(&notes, "how to build docker image", 2000);
+
+        assert_eq!(sources.len(), 3);
+        assert_eq!((sources[0].start_line, sources[0].end_line), (7, 8));
+        assert_eq!(sources[0].kind, "text");
+        assert_eq!((sources[1].start_line, sources[1].end_line), (10, 12));
+        assert_eq!(sources[1].kind, "code");
+        assert_eq!(sources[2].note_id, "other");
+        assert!(context.contains("[1] deploy (notes/deploy.md:7-8)"));
+        assert!(context.contains("[2] deploy (notes/deploy.md:10-12)\n```sh"));
+    }
+
+    #[test]
+    fn respects_token_budget_and_injects_system_message() {
+        let long = "docker ".repeat(600);
+        let notes = vec![note("a", &long), note("b", "docker compose up")];
+        let (context, sources) = build_context(&notes, "docker", 300);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].note_id, "a");
+        assert!(!context.contains(long.trim_end()));
+        assert!(estimate_tokens(&context) <= 300);
+
+        let mut messages = vec![LocalAiMessage {
+            role: "user".to_string(),
+            content: Value::String("docker?".to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+        }];
+        inject_context(&mut messages, &context);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(context_budget(4096, &messages, 0), 2048);
+    }
+}
@@ -0,0 +1,803 @@
+// 本地 AI 标签与摘要建议
+//
+// 后台任务遍历缺少标签或摘要的笔记，让正在运行的本地模型给出建议标签（优先使用工作区已有标签，
+// 最多额外新增 N 个）和一句话摘要。建议写入 SQLite 审核队列，由用户采纳后才写回 frontmatter
+// 或代码片段元数据。队列按正文哈希记录，已处理且内容未变的笔记在下次运行时跳过。
+
+use crate::db::open_plugin_store;
+use crate::json_config::get_workspace_root;
+use crate::markdown::code_snippets::is_code_snippet_file;
+use crate::markdown::file_ops::{get_relative_path, is_within_workspace};
+use crate::markdown::file_system_manager::FileSystemManager;
+use crate::markdown::metadata::try_parse_front_matter;
+use crate::markdown::watcher::FileWatcher;
+use crate::markdown::{CacheManager, IndexManager};
+use crate::plugins::local_ai::{
+    chat_completion, has_foreground_requests, is_service_running, read_config, require_plugin,
+    strip_think, LocalAiMessage,
+};
+use crate::text_utils::{sha256_hex, truncate_chars};
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+const PROGRESS_EVENT: &str = "local-ai-tagging-progress";
+const DEFAULT_MAX_NEW_TAGS: usize = 2;
+const MAX_TAGS_PER_NOTE: usize = 5;
+// 提示词中列出的已有标签上限，避免大词表挤占上下文
+const VOCABULARY_PROMPT_LIMIT: usize = 300;
+const NOTE_INPUT_MAX_CHARS: usize = 6000;
+const SUMMARY_MAX_CHARS: usize = 120;
+const COMPLETION_MAX_TOKENS: u32 = 256;
+// 相邻两次请求的默认间隔，给前台请求留出空档
+const DEFAULT_INTERVAL_MS: u64 = 1500;
+const WAIT_POLL_MS: u64 = 300;
+
+static JOB: LazyLock<Mutex<TaggingProgress>> =
+    LazyLock::new(|| Mutex::new(TaggingProgress::default()));
+static JOB_PAUSED: AtomicBool = AtomicBool::new(false);
+static JOB_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggingProgress {
+    // idle / running / paused / completed / cancelled / failed
+    pub status: String,
+    pub done: usize,
+    pub total: usize,
+    pub queued: usize,
+    pub failed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl Default for TaggingProgress {
+    fn default() -> Self {
+        Self {
+            status: "idle".to_string(),
+            done: 0,
+            total: 0,
+            queued: 0,
+            failed: 0,
+            current: None,
+            message: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagSuggestion {
+    // 工作区内的相对路径
+    pub file_path: String,
+    // 绝对路径（与 MarkdownFile.id 一致）
+    pub note_id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    // tags 中不在工作区已有标签里的部分
+    pub new_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub status: String,
+    pub created_at: String,
+}
+
+// ============= 提示词与解析 =============
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedSuggestion {
+    pub tags: Vec<String>,
+    pub summary: Option<String>,
+}
+
+pub fn build_prompt(
+    title: &str,
+    content: &str,
+    vocabulary: &[String],
+    max_new_tags: usize,
+) -> Vec<LocalAiMessage> {
+    let vocabulary_text = if vocabulary.is_empty() {
+        "(none yet)".to_string()
+    } else {
+        vocabulary
+            .iter()
+            .take(VOCABULARY_PROMPT_LIMIT)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let system_prompt = format!(
+        "You label notes in a personal knowledge base. Reply with a single JSON object and nothing else: \
+{{\"tags\": [\"...\"], \"summary\": \"...\"}}. Pick at most {} tags, preferring tags from the existing list; \
+you may introduce at most {} new short lowercase tags only when none of the existing ones fit. \
+The summary is one sentence of at most {} characters, written in the same language as the note.\n\n\
+Existing tags: {}",
+        MAX_TAGS_PER_NOTE, max_new_tags, SUMMARY_MAX_CHARS, vocabulary_text
+    );
+    let user_prompt = format!(
+        "Title: {}\n\n{}",
+        title,
+        truncate_chars(content, NOTE_INPUT_MAX_CHARS)
+    );
+    vec![
+        LocalAiMessage {
+            role: "system".to_string(),
+            content: Value::String(system_prompt),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+        LocalAiMessage {
+            role: "user".to_string(),
+            content: Value::String(user_prompt),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+    ]
+}
+
+/// 从模型回复中提取 JSON 对象（容忍 ```json 围栏和前后说明文字）
+pub fn parse_reply(reply: &str) -> Result<ParsedSuggestion, String> {
+    let text = strip_think(reply);
+    let start = text.find('{').ok_or("模型回复中没有 JSON 对象")?;
+    let end = text.rfind('}').ok_or("模型回复中没有 JSON 对象")?;
+    if end < start {
+        return Err("模型回复中没有 JSON 对象".to_string());
+    }
+    let value: Value =
+        serde_json::from_str(&text[start..=end]).map_err(|e| format!("解析模型回复失败: {}", e))?;
+
+    let tags = match value.get("tags") {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .map(|tag| tag.trim().trim_start_matches('#').trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        Some(Value::String(text)) => text
+            .split([',', '，'])
+            .map(|tag| tag.trim().trim_start_matches('#').trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+    let summary = value
+        .get("summary")
+        .and_then(|v| v.as_str())
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|s| !s.is_empty())
+        .map(|s| truncate_chars(&s, SUMMARY_MAX_CHARS).to_string());
+    if tags.is_empty() && summary.is_none() {
+        return Err("模型没有给出标签或摘要".to_string());
+    }
+    Ok(ParsedSuggestion { tags, summary })
+}
+
+/// 将建议标签约束到已有词表 + 最多 max_new 个新标签，返回 (全部标签, 其中的新标签)
+///
+/// 与词表大小写不同的标签归并为词表中的写法；已在笔记上的标签不再重复建议。
+pub fn limit_tags(
+    suggested: &[String],
+    vocabulary: &[String],
+    existing: &[String],
+    max_new: usize,
+) -> (Vec<String>, Vec<String>) {
+    let known: HashMap<String, &String> = vocabulary
+        .iter()
+        .map(|tag| (tag.to_lowercase(), tag))
+        .collect();
+    let mut tags: Vec<String> = Vec::new();
+    let mut new_tags: Vec<String> = Vec::new();
+    for tag in suggested {
+        if tags.len() >= MAX_TAGS_PER_NOTE {
+            break;
+        }
+        let key = tag.to_lowercase();
+        if existing.iter().any(|t| t.to_lowercase() == key)
+            || tags.iter().any(|t| t.to_lowercase() == key)
+        {
+            continue;
+        }
+        match known.get(&key) {
+            Some(canonical) => tags.push((*canonical).clone()),
+            None if new_tags.len() < max_new => {
+                tags.push(tag.clone());
+                new_tags.push(tag.clone());
+            }
+            None => {}
+        }
+    }
+    (tags, new_tags)
+}
+
+// ============= 审核队列 =============
+
+pub(crate) fn create_tagging_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ai_tag_suggestions (
+             workspace TEXT NOT NULL,
+             file_path TEXT NOT NULL,
+             title TEXT NOT NULL,
+             content_hash TEXT NOT NULL,
+             tags TEXT NOT NULL,
+             new_tags TEXT NOT NULL,
+             summary TEXT,
+             status TEXT NOT NULL DEFAULT 'pending',
+             created_at TEXT NOT NULL DEFAULT (datetime('now')),
+             PRIMARY KEY (workspace, file_path)
+         );",
+    )
+}
+
+// 已记录的正文哈希（任意状态），用于增量运行
+fn recorded_hashes(conn: &Connection, workspace: &str) -> Result<HashMap<String, String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT file_path, content_hash FROM ai_tag_suggestions WHERE workspace = ?1")
+        .map_err(|e| format!("查询标签建议失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![workspace], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| format!("查询标签建议失败: {}", e))?;
+    Ok(rows.filter_map(|row| row.ok()).collect())
+}
+
+fn save_suggestion(
+    conn: &Connection,
+    workspace: &str,
+    candidate: &TaggingCandidate,
+    tags: &[String],
+    new_tags: &[String],
+    summary: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO ai_tag_suggestions
+             (workspace, file_path, title, content_hash, tags, new_tags, summary, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'pending')",
+        params![
+            workspace,
+            candidate.file_path,
+            candidate.title,
+            candidate.hash,
+            serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(new_tags).unwrap_or_else(|_| "[]".to_string()),
+            summary,
+        ],
+    )
+    .map_err(|e| format!("保存标签建议失败: {}", e))?;
+    Ok(())
+}
+
+fn set_suggestion_status(
+    conn: &Connection,
+    workspace: &str,
+    file_path: &str,
+    status: &str,
+) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE ai_tag_suggestions SET status = ?3 WHERE workspace = ?1 AND file_path = ?2",
+            params![workspace, file_path, status],
+        )
+        .map_err(|e| format!("更新标签建议失败: {}", e))?;
+    if updated == 0 {
+        return Err(format!("找不到该笔记的标签建议: {}", file_path));
+    }
+    Ok(())
+}
+
+fn row_to_suggestion(
+    row: &rusqlite::Row<'_>,
+    workspace_root: &Path,
+) -> rusqlite::Result<TagSuggestion> {
+    let file_path: String = row.get(0)?;
+    let parse_list = |text: String| serde_json::from_str::<Vec<String>>(&text).unwrap_or_default();
+    Ok(TagSuggestion {
+        note_id: workspace_root
+            .join(&file_path)
+            .to_string_lossy()
+            .to_string(),
+        file_path,
+        title: row.get(1)?,
+        tags: parse_list(row.get(2)?),
+        new_tags: parse_list(row.get(3)?),
+        summary: row.get(4)?,
+        status: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+// ============= 后台任务 =============
+
+struct TaggingCandidate {
+    file_path: String,
+    title: String,
+    content: String,
+    hash: String,
+    tags: Vec<String>,
+}
+
+fn update_job(app_handle: &AppHandle, update: impl FnOnce(&mut TaggingProgress)) {
+    let snapshot = match JOB.lock() {
+        Ok(mut job) => {
+            update(&mut job);
+            job.clone()
+        }
+        Err(_) => return,
+    };
+    let _ = app_handle.emit(PROGRESS_EVENT, snapshot);
+}
+
+fn job_snapshot() -> TaggingProgress {
+    JOB.lock().map(|job| job.clone()).unwrap_or_default()
+}
+
+// 读取笔记当前的摘要：代码片段在 cache 中，Markdown 在 frontmatter 中
+fn current_summary(
+    workspace_root: &Path,
+    path: &Path,
+    relative_path: &str,
+    cache: &CacheManager,
+) -> Option<String> {
+    if is_code_snippet_file(workspace_root, path) {
+        return cache
+            .get_snippet_metadata(relative_path)
+            .and_then(|meta| meta.summary.clone());
+    }
+    let raw = std::fs::read_to_string(path).ok()?;
+    try_parse_front_matter(&raw).0.and_then(|fm| fm.summary)
+}
+
+// 收集缺少标签或摘要、且正文自上次处理后有变化的笔记
+fn collect_candidates(
+    workspace_root: &Path,
+    index_manager: &State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: &State<'_, Arc<RwLock<CacheManager>>>,
+    recorded: &HashMap<String, String>,
+) -> Result<(Vec<TaggingCandidate>, Vec<String>), String> {
+    let manager_lock = index_manager
+        .read()
+        .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+    let manager = manager_lock
+        .as_ref()
+        .ok_or_else(|| "索引尚未建立，请稍后重试".to_string())?;
+    let cache = cache_manager
+        .read()
+        .map_err(|e| format!("获取缓存管理器锁失败: {}", e))?;
+
+    let mut vocabulary = manager.get_all_tags();
+    vocabulary.sort();
+    let mut candidates = Vec::new();
+    for entry in manager.get_all_entries() {
+        if entry.full_content.trim().is_empty() {
+            continue;
+        }
+        let Ok(relative_path) = get_relative_path(workspace_root, &entry.file_path) else {
+            continue;
+        };
+        let hash = sha256_hex(&entry.full_content);
+        if recorded.get(&relative_path) == Some(&hash) {
+            continue;
+        }
+        let missing_summary =
+            current_summary(workspace_root, &entry.file_path, &relative_path, &cache).is_none();
+        if !entry.tags.is_empty() && !missing_summary {
+            continue;
+        }
+        candidates.push(TaggingCandidate {
+            file_path: relative_path,
+            title: entry.title,
+            content: entry.full_content,
+            hash,
+            tags: entry.tags,
+        });
+    }
+    Ok((candidates, vocabulary))
+}
+
+// 暂停时原地等待；取消时返回 false
+async fn wait_while_paused() -> bool {
+    while JOB_PAUSED.load(Ordering::SeqCst) {
+        if JOB_CANCELLED.load(Ordering::SeqCst) {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(WAIT_POLL_MS)).await;
+    }
+    !JOB_CANCELLED.load(Ordering::SeqCst)
+}
+
+// 有前台请求（对话、翻译等）时让路，避免占用唯一的推理槽位
+async fn wait_for_foreground() -> bool {
+    while has_foreground_requests() {
+        if JOB_CANCELLED.load(Ordering::SeqCst) {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(WAIT_POLL_MS)).await;
+    }
+    !JOB_CANCELLED.load(Ordering::SeqCst)
+}
+
+async fn run_job(
+    app_handle: AppHandle,
+    workspace: String,
+    candidates: Vec<TaggingCandidate>,
+    vocabulary: Vec<String>,
+    max_new_tags: usize,
+    interval: Duration,
+) {
+    for (position, candidate) in candidates.iter().enumerate() {
+        if !wait_while_paused().await || !wait_for_foreground().await {
+            break;
+        }
+        // 服务被手动停止或被空闲监控回收后不再自动拉起，暂停任务等待用户处理
+        if position > 0 && !is_service_running(&read_config(&app_handle)) {
+            JOB_PAUSED.store(true, Ordering::SeqCst);
+            update_job(&app_handle, |job| {
+                job.status = "paused".to_string();
+                job.message = Some("本地 AI 服务已停止，任务已暂停".to_string());
+            });
+            if !wait_while_paused().await {
+                break;
+            }
+        }
+        update_job(&app_handle, |job| {
+            // 等待期间用户可能刚点了暂停，保留暂停状态，下一篇开始前生效
+            if !JOB_PAUSED.load(Ordering::SeqCst) {
+                job.status = "running".to_string();
+                job.message = None;
+            }
+            job.current = Some(candidate.file_path.clone());
+        });
+
+        let messages = build_prompt(
+            &candidate.title,
+            &candidate.content,
+            &vocabulary,
+            max_new_tags,
+        );
+        let result = chat_completion(
+            &app_handle,
+            messages,
+            Some(0.2),
+            Some(COMPLETION_MAX_TOKENS),
+            Some(false),
+        )
+        .await
+        .and_then(|reply| parse_reply(&reply))
+        .and_then(|parsed| {
+            let (tags, new_tags) =
+                limit_tags(&parsed.tags, &vocabulary, &candidate.tags, max_new_tags);
+            let conn = open_plugin_store()?;
+            save_suggestion(
+                &conn,
+                &workspace,
+                candidate,
+                &tags,
+                &new_tags,
+                parsed.summary.as_deref(),
+            )
+        });
+
+        let failed = result.is_err();
+        if let Err(e) = result {
+            warn!("⚠️ [AI 标签] {} 生成建议失败: {}", candidate.file_path, e);
+        }
+        update_job(&app_handle, |job| {
+            job.done += 1;
+            if failed {
+                job.failed += 1;
+            } else {
+                job.queued += 1;
+            }
+        });
+
+        if position + 1 < candidates.len() {
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    let cancelled = JOB_CANCELLED.load(Ordering::SeqCst);
+    update_job(&app_handle, |job| {
+        job.status = if cancelled { "cancelled" } else { "completed" }.to_string();
+        job.current = None;
+    });
+    let progress = job_snapshot();
+    info!(
+        "🏷️ [AI 标签] 任务结束: 处理 {}/{}，新增建议 {}，失败 {}",
+        progress.done, progress.total, progress.queued, progress.failed
+    );
+}
+
+// ============= 命令 =============
+
+/// 启动后台标签/摘要建议任务；已在运行时返回错误
+#[tauri::command]
+pub async fn local_ai_start_tagging(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    max_new_tags: Option<usize>,
+    interval_ms: Option<u64>,
+) -> Result<TaggingProgress, String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let workspace = workspace_root.to_string_lossy().to_string();
+
+    let recorded = {
+        let conn = open_plugin_store()?;
+        recorded_hashes(&conn, &workspace)?
+    };
+    let (candidates, vocabulary) =
+        collect_candidates(&workspace_root, &index_manager, &cache_manager, &recorded)?;
+
+    {
+        let mut job = JOB
+            .lock()
+            .map_err(|e| format!("获取任务状态锁失败: {}", e))?;
+        if matches!(job.status.as_str(), "running" | "paused") {
+            return Err("标签建议任务已在运行".to_string());
+        }
+        *job = TaggingProgress {
+            status: "running".to_string(),
+            total: candidates.len(),
+            ..TaggingProgress::default()
+        };
+    }
+    JOB_PAUSED.store(false, Ordering::SeqCst);
+    JOB_CANCELLED.store(false, Ordering::SeqCst);
+    let progress = job_snapshot();
+    let _ = app_handle.emit(PROGRESS_EVENT, progress.clone());
+    info!("🏷️ [AI 标签] 开始生成建议: {} 篇笔记", candidates.len());
+
+    tauri::async_runtime::spawn(run_job(
+        app_handle.clone(),
+        workspace,
+        candidates,
+        vocabulary,
+        max_new_tags.unwrap_or(DEFAULT_MAX_NEW_TAGS),
+        Duration::from_millis(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS)),
+    ));
+    Ok(progress)
+}
+
+#[tauri::command]
+pub fn local_ai_pause_tagging(app_handle: AppHandle) -> Result<TaggingProgress, String> {
+    require_plugin(&app_handle)?;
+    if job_snapshot().status != "running" {
+        return Err("标签建议任务未在运行".to_string());
+    }
+    JOB_PAUSED.store(true, Ordering::SeqCst);
+    update_job(&app_handle, |job| job.status = "paused".to_string());
+    Ok(job_snapshot())
+}
+
+#[tauri::command]
+pub fn local_ai_resume_tagging(app_handle: AppHandle) -> Result<TaggingProgress, String> {
+    require_plugin(&app_handle)?;
+    if job_snapshot().status != "paused" {
+        return Err("标签建议任务未暂停".to_string());
+    }
+    JOB_PAUSED.store(false, Ordering::SeqCst);
+    update_job(&app_handle, |job| {
+        job.status = "running".to_string();
+        job.message = None;
+    });
+    Ok(job_snapshot())
+}
+
+/// 取消任务；当前请求完成后停止，已生成的建议保留在队列中
+#[tauri::command]
+pub fn local_ai_cancel_tagging(app_handle: AppHandle) -> Result<(), String> {
+    require_plugin(&app_handle)?;
+    JOB_CANCELLED.store(true, Ordering::SeqCst);
+    JOB_PAUSED.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn local_ai_get_tagging_status(app_handle: AppHandle) -> Result<TaggingProgress, String> {
+    require_plugin(&app_handle)?;
+    Ok(job_snapshot())
+}
+
+/// 列出审核队列；status 为空时只返回待审核的建议
+#[tauri::command]
+pub fn local_ai_list_tag_suggestions(
+    app_handle: AppHandle,
+    status: Option<String>,
+) -> Result<Vec<TagSuggestion>, String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let conn = open_plugin_store()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_path, title, tags, new_tags, summary, status, created_at
+             FROM ai_tag_suggestions
+             WHERE workspace = ?1 AND status = ?2
+             ORDER BY created_at DESC, file_path",
+        )
+        .map_err(|e| format!("查询标签建议失败: {}", e))?;
+    let rows = stmt
+        .query_map(
+            params![
+                workspace_root.to_string_lossy().to_string(),
+                status.as_deref().unwrap_or("pending")
+            ],
+            |row| row_to_suggestion(row, &workspace_root),
+        )
+        .map_err(|e| format!("查询标签建议失败: {}", e))?;
+    Ok(rows.filter_map(|row| row.ok()).collect())
+}
+
+/// 采纳建议：标签追加到笔记已有标签后，摘要只在笔记尚无摘要时写入
+///
+/// tags / summary 传入时使用用户编辑后的值，否则使用队列中的建议。
+#[tauri::command]
+pub async fn local_ai_apply_tag_suggestion(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    watcher: State<'_, Arc<Mutex<Option<FileWatcher>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    file_path: String,
+    tags: Option<Vec<String>>,
+    summary: Option<String>,
+) -> Result<(), String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let workspace = workspace_root.to_string_lossy().to_string();
+    let conn = open_plugin_store()?;
+    let suggestion = conn
+        .query_row(
+            "SELECT file_path, title, tags, new_tags, summary, status, created_at
+             FROM ai_tag_suggestions WHERE workspace = ?1 AND file_path = ?2",
+            params![workspace, file_path],
+            |row| row_to_suggestion(row, &workspace_root),
+        )
+        .optional()
+        .map_err(|e| format!("查询标签建议失败: {}", e))?
+        .ok_or_else(|| format!("找不到该笔记的标签建议: {}", file_path))?;
+
+    let path = PathBuf::from(&suggestion.note_id);
+    if !is_within_workspace(&workspace_root, &path) || !path.is_file() {
+        return Err(format!("文件不存在或不在工作区内: {}", path.display()));
+    }
+    let add_tags = tags.unwrap_or(suggestion.tags);
+    let summary = summary
+        .or(suggestion.summary)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    let merge_tags = |current: &mut Vec<String>| {
+        for tag in &add_tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !current.iter().any(|t| t == tag) {
+                current.push(tag.to_string());
+            }
+        }
+    };
+
+    if let Ok(watcher_lock) = watcher.lock() {
+        if let Some(ref w) = *watcher_lock {
+            w.ignore_next_change(path.clone());
+        }
+    }
+    {
+        let mut cache = cache_manager
+            .write()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        if is_code_snippet_file(&workspace_root, &path) {
+            let mut meta = cache
+                .get_snippet_metadata(&file_path)
+                .cloned()
+                .unwrap_or_default();
+            merge_tags(&mut meta.tags);
+            if meta.summary.is_none() {
+                meta.summary = summary;
+            }
+            cache.set_snippet_metadata(file_path.clone(), meta);
+        } else {
+            let fs_manager = FileSystemManager::new(workspace_root.clone());
+            let (mut fm, _) = fs_manager.read_markdown_file(&path)?;
+            // 无 Frontmatter 的文件沿用 cache 中的 id 和创建时间
+            if try_parse_front_matter(&std::fs::read_to_string(&path).unwrap_or_default())
+                .0
+                .is_none()
+            {
+                if let Some(file_meta) = cache.get_file_metadata(&file_path) {
+                    fm.id = file_meta.id.clone();
+                    if let Some(created) =
+                        chrono::DateTime::from_timestamp_millis(file_meta.created)
+                    {
+                        fm.created = created.to_rfc3339();
+                    }
+                }
+            }
+            merge_tags(&mut fm.tags);
+            if fm.summary.is_none() {
+                fm.summary = summary;
+            }
+            fm.modified = chrono::Utc::now().to_rfc3339();
+            fs_manager.update_file_frontmatter(&path, &fm)?;
+        }
+        let now = chrono::Utc::now().timestamp_millis();
+        let _ = cache.update_file_metadata(&file_path, |m| m.modified = now);
+        cache.save()?;
+    }
+
+    if let Ok(manager_lock) = index_manager.read() {
+        if let Some(ref manager) = *manager_lock {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            let _ = manager.update_entry(&path, &workspace_root, &cache);
+        }
+    }
+    set_suggestion_status(&conn, &workspace, &file_path, "accepted")?;
+    info!("🏷️ [AI 标签] 已采纳建议: {}", file_path);
+    Ok(())
+}
+
+/// 拒绝建议；正文不变时后续任务不会再次为该笔记生成建议
+#[tauri::command]
+pub fn local_ai_reject_tag_suggestion(
+    app_handle: AppHandle,
+    file_path: String,
+) -> Result<(), String> {
+    require_plugin(&app_handle)?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let conn = open_plugin_store()?;
+    set_suggestion_status(
+        &conn,
+        &workspace_root.to_string_lossy(),
+        &file_path,
+        "rejected",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_json_reply_with_fence_and_think() {
+        let reply = "<think>考虑一下 {tags}</think>\n```json\n{\"tags\": [\"#Rust\", \" async \", \"\"], \"summary\": \"  用 tokio\\n 实现定时任务。 \"}\n```";
+        let parsed = parse_reply(reply).unwrap();
+        assert_eq!(parsed.tags, strings(&["Rust", "async"]));
+        assert_eq!(parsed.summary.as_deref(), Some("用 tokio 实现定时任务。"));
+
+        let parsed = parse_reply("{\"tags\": \"vue, 组件\"}").unwrap();
+        assert_eq!(parsed.tags, strings(&["vue", "组件"]));
+        assert!(parsed.summary.is_none());
+
+        assert!(parse_reply("没有 JSON").is_err());
+        assert!(parse_reply("{\"tags\": []}").is_err());
+    }
+
+    #[test]
+    fn limits_tags_to_vocabulary_plus_new() {
+        let vocabulary = strings(&["Rust", "tokio", "vue"]);
+        let suggested = strings(&["rust", "Tokio", "cron", "scheduler", "timer", "vue"]);
+        let (tags, new_tags) = limit_tags(&suggested, &vocabulary, &strings(&["vue"]), 2);
+        assert_eq!(tags, strings(&["Rust", "tokio", "cron", "scheduler"]));
+        assert_eq!(new_tags, strings(&["cron", "scheduler"]));
+
+        let (tags, new_tags) = limit_tags(&suggested, &vocabulary, &[], 0);
+        assert_eq!(tags, strings(&["Rust", "tokio", "vue"]));
+        assert!(new_tags.is_empty());
+    }
+}
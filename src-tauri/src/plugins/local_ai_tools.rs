@@ -0,0 +1,750 @@
+// 本地 AI 工具调用
+//
+// 向模型暴露白名单内的工作区操作（OpenAI 风格 tools / tool_calls）：搜索片段、读取笔记、
+// 列出标签，以及在 "AI Inbox" 分类中创建草稿。写操作必须等待用户在界面上确认，
+// 每次调用都会作为 LocalAiToolInvocation 推送给前端并记录到聊天历史。
+
+use crate::json_config::get_workspace_root;
+use crate::markdown::code_snippets::is_code_snippet_file;
+use crate::markdown::file_ops::get_relative_path;
+use crate::markdown::ignore::load_workspace_ignore;
+use crate::markdown::metadata::try_parse_front_matter;
+use crate::markdown::{CacheManager, IndexManager};
+use crate::plugins::local_ai::{
+    require_plugin, ChatStreamEvent, LocalAiMessage, LocalAiToolInvocation,
+};
+use crate::text_utils::truncate_chars;
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const AI_INBOX_CATEGORY: &str = "AI Inbox";
+const SEARCH_DEFAULT_LIMIT: usize = 5;
+const SEARCH_MAX_LIMIT: usize = 10;
+const READ_MAX_CHARS: usize = 8_000;
+const SUMMARY_CHARS: usize = 300;
+// 记录到历史中的工具结果长度上限
+const RESULT_LOG_CHARS: usize = 2_000;
+// 等待用户确认写操作的时限
+const CONFIRM_TIMEOUT_SECS: u64 = 300;
+
+// 等待确认的写操作：key 为 "{request_id}:{call_id}"，值为用户的决定
+static PENDING_CONFIRMATIONS: LazyLock<Mutex<HashMap<String, Option<bool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 工具白名单：(名称, 是否为写操作)
+const TOOLS: &[(&str, bool)] = &[
+    ("search_snippets", false),
+    ("read_note", false),
+    ("list_tags", false),
+    ("create_draft_note", true),
+];
+
+pub(crate) fn tool_definitions() -> Value {
+    json!([
+        {
+            "type": "function",
+            "function": {
+                "name": "search_snippets",
+                "description": "Search the user's notes and code snippets by keywords. Returns titles, paths, tags and a short summary.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "Search keywords" },
+                        "limit": { "type": "integer", "description": "Maximum results (1-10)" }
+                    },
+                    "required": ["query"]
+                }
+            }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "read_note",
+                "description": "Read the full content of a note or code snippet by the path returned from search_snippets.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Workspace-relative path of the note" }
+                    },
+                    "required": ["path"]
+                }
+            }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "list_tags",
+                "description": "List all tags used in the workspace.",
+                "parameters": { "type": "object", "properties": {} }
+            }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "create_draft_note",
+                "description": "Create a new draft note in the \"AI Inbox\" category. The user must approve it before it is written.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "content": { "type": "string", "description": "Markdown body" },
+                        "tags": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["title", "content"]
+                }
+            }
+        }
+    ])
+}
+
+// ============= 流式 tool_calls 累积 =============
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct PendingToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+impl PendingToolCall {
+    // 回传给模型的 assistant.tool_calls 项
+    pub(crate) fn to_message(&self) -> Value {
+        json!({
+            "id": self.id,
+            "type": "function",
+            "function": { "name": self.name, "arguments": self.arguments }
+        })
+    }
+}
+
+/// 按 index 合并流式分片中的 tool_calls（名称与参数可能分多次到达）
+#[derive(Default)]
+pub(crate) struct ToolCallAccumulator {
+    calls: Vec<(u64, PendingToolCall)>,
+}
+
+impl ToolCallAccumulator {
+    pub(crate) fn push(&mut self, value: &Value) {
+        let Some(deltas) = value
+            .get("choices")
+            .and_then(|choices| choices.as_array())
+            .and_then(|choices| choices.first())
+            .and_then(|choice| choice.get("delta").or_else(|| choice.get("message")))
+            .and_then(|delta| delta.get("tool_calls"))
+            .and_then(|calls| calls.as_array())
+        else {
+            return;
+        };
+
+        for (position, delta) in deltas.iter().enumerate() {
+            let index = delta
+                .get("index")
+                .and_then(Value::as_u64)
+                .unwrap_or(position as u64);
+            let slot = match self.calls.iter().position(|(i, _)| *i == index) {
+                Some(slot) => slot,
+                None => {
+                    self.calls.push((index, PendingToolCall::default()));
+                    self.calls.len() - 1
+                }
+            };
+            let call = &mut self.calls[slot].1;
+            if let Some(id) = delta.get("id").and_then(Value::as_str) {
+                call.id = id.to_string();
+            }
+            if let Some(function) = delta.get("function") {
+                if let Some(name) = function.get("name").and_then(Value::as_str) {
+                    call.name.push_str(name);
+                }
+                match function.get("arguments") {
+                    Some(Value::String(arguments)) => call.arguments.push_str(arguments),
+                    Some(arguments) if !arguments.is_null() => {
+                        call.arguments.push_str(&arguments.to_string())
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<PendingToolCall> {
+        let mut calls = self.calls;
+        calls.sort_by_key(|(index, _)| *index);
+        calls
+            .into_iter()
+            .filter(|(_, call)| !call.name.is_empty())
+            .map(|(index, mut call)| {
+                if call.id.is_empty() {
+                    call.id = format!("call_{}", index);
+                }
+                call
+            })
+            .collect()
+    }
+}
+
+// ============= 执行 =============
+
+/// 工具的实际执行方；写操作的确认通过 request/poll 两步完成，便于在测试中替换
+pub(crate) trait LocalAiToolHost: Sync {
+    fn execute(
+        &self,
+        name: &str,
+        arguments: &Value,
+    ) -> impl Future<Output = Result<Value, String>> + Send;
+    fn request_confirmation(&self, call_id: &str);
+    fn confirmation(&self, call_id: &str) -> Option<bool>;
+    fn clear_confirmation(&self, call_id: &str);
+}
+
+fn tool_reply(call_id: &str, payload: &Value) -> LocalAiMessage {
+    LocalAiMessage {
+        role: "tool".to_string(),
+        content: Value::String(payload.to_string()),
+        tool_calls: None,
+        tool_call_id: Some(call_id.to_string()),
+    }
+}
+
+// 调用方须先 request_confirmation 再通知前端，否则前端很快给出的决定会找不到待确认项
+async fn wait_for_confirmation<H: LocalAiToolHost>(
+    host: &H,
+    call_id: &str,
+    cancel_flag: &AtomicBool,
+) -> bool {
+    let started = Instant::now();
+    let decision = loop {
+        if let Some(approved) = host.confirmation(call_id) {
+            break approved;
+        }
+        if cancel_flag.load(Ordering::Relaxed)
+            || started.elapsed() >= Duration::from_secs(CONFIRM_TIMEOUT_SECS)
+        {
+            break false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    host.clear_confirmation(call_id);
+    decision
+}
+
+/// 执行一次工具调用，返回历史记录项与回传给模型的 tool 消息
+pub(crate) async fn invoke_tool<H: LocalAiToolHost>(
+    host: &H,
+    call: PendingToolCall,
+    cancel_flag: &AtomicBool,
+    emit: &(dyn Fn(ChatStreamEvent) + Sync),
+) -> (LocalAiToolInvocation, LocalAiMessage) {
+    let requires_confirmation = TOOLS
+        .iter()
+        .any(|(name, write)| *write && *name == call.name);
+    let mut invocation = LocalAiToolInvocation {
+        id: call.id.clone(),
+        name: call.name.clone(),
+        arguments: Value::Null,
+        status: "pending".to_string(),
+        requires_confirmation,
+        result: None,
+        error: None,
+    };
+
+    let outcome = if !TOOLS.iter().any(|(name, _)| *name == call.name) {
+        Err(format!("未知工具: {}", call.name))
+    } else {
+        let raw = if call.arguments.trim().is_empty() {
+            "{}"
+        } else {
+            call.arguments.as_str()
+        };
+        match serde_json::from_str::<Value>(raw) {
+            Err(error) => Err(format!("工具参数不是合法 JSON: {}", error)),
+            Ok(arguments) => {
+                invocation.arguments = arguments.clone();
+                let approved = if requires_confirmation {
+                    host.request_confirmation(&call.id);
+                    emit(ChatStreamEvent::ToolCall(invocation.clone()));
+                    wait_for_confirmation(host, &call.id, cancel_flag).await
+                } else {
+                    true
+                };
+                if approved {
+                    host.execute(&call.name, &arguments).await
+                } else {
+                    invocation.status = "rejected".to_string();
+                    Err("用户拒绝了该操作".to_string())
+                }
+            }
+        }
+    };
+
+    let reply = match outcome {
+        Ok(result) => {
+            invocation.status = "ok".to_string();
+            let serialized = result.to_string();
+            invocation.result = Some(truncate_chars(&serialized, RESULT_LOG_CHARS).to_string());
+            tool_reply(&call.id, &result)
+        }
+        Err(error) => {
+            if invocation.status != "rejected" {
+                invocation.status = "error".to_string();
+            }
+            invocation.error = Some(error.clone());
+            tool_reply(&call.id, &json!({ "error": error }))
+        }
+    };
+    info!(
+        "🛠️ [LocalAI] 工具调用 {}({}) -> {}",
+        invocation.name, invocation.arguments, invocation.status
+    );
+    emit(ChatStreamEvent::ToolCall(invocation.clone()));
+    (invocation, reply)
+}
+
+// ============= 应用内实现 =============
+
+pub(crate) struct AppToolHost {
+    app_handle: AppHandle,
+    request_id: String,
+}
+
+impl AppToolHost {
+    pub(crate) fn new(app_handle: AppHandle, request_id: String) -> Self {
+        Self {
+            app_handle,
+            request_id,
+        }
+    }
+
+    fn confirmation_key(&self, call_id: &str) -> String {
+        format!("{}:{}", self.request_id, call_id)
+    }
+
+    fn workspace_root(&self) -> Result<PathBuf, String> {
+        get_workspace_root(&self.app_handle)?
+            .ok_or_else(|| "工作区未配置，请先设置工作区根目录".to_string())
+    }
+
+    fn with_index<T>(&self, f: impl FnOnce(&IndexManager) -> T) -> Result<T, String> {
+        let index_manager = self
+            .app_handle
+            .try_state::<Arc<RwLock<Option<IndexManager>>>>()
+            .ok_or_else(|| "索引尚未建立，请稍后重试".to_string())?;
+        let manager_lock = index_manager
+            .read()
+            .map_err(|e| format!("获取索引管理器锁失败: {}", e))?;
+        let manager = manager_lock
+            .as_ref()
+            .ok_or_else(|| "索引尚未建立，请稍后重试".to_string())?;
+        Ok(f(manager))
+    }
+
+    fn search_snippets(&self, arguments: &Value) -> Result<Value, String> {
+        let query = arguments
+            .get("query")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+            .ok_or("缺少 query 参数")?;
+        let limit = arguments
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map(|limit| (limit as usize).clamp(1, SEARCH_MAX_LIMIT))
+            .unwrap_or(SEARCH_DEFAULT_LIMIT);
+        let workspace_root = self.workspace_root()?;
+        let results = self.with_index(|manager| manager.search(query))?;
+        Ok(Value::Array(
+            results
+                .into_iter()
+                .take(limit)
+                .filter_map(|(entry, score)| {
+                    Some(json!({
+                        "path": get_relative_path(&workspace_root, &entry.file_path).ok()?,
+                        "title": entry.title,
+                        "tags": entry.tags,
+                        "type": entry.file_type,
+                        "score": score,
+                        "summary": truncate_chars(&entry.content_summary, SUMMARY_CHARS),
+                    }))
+                })
+                .collect(),
+        ))
+    }
+
+    fn read_note(&self, arguments: &Value) -> Result<Value, String> {
+        let path = arguments
+            .get("path")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .ok_or("缺少 path 参数")?;
+        let workspace_root = self.workspace_root()?;
+        let full_path = resolve_workspace_path(&workspace_root, path)?;
+        let is_snippet = full_path.extension().and_then(|e| e.to_str()) == Some("md")
+            || is_code_snippet_file(&workspace_root, &full_path);
+        if !is_snippet
+            || !full_path.is_file()
+            || load_workspace_ignore(&workspace_root).is_path_ignored(
+                &workspace_root,
+                &full_path,
+                false,
+            )
+        {
+            return Err(format!("笔记不存在: {}", path));
+        }
+        let raw = std::fs::read_to_string(&full_path)
+            .map_err(|e| format!("读取笔记失败 '{}': {}", path, e))?;
+        let (front_matter, body) = try_parse_front_matter(&raw);
+        let content = truncate_chars(&body, READ_MAX_CHARS);
+        let truncated = content.len() < body.len();
+        let title = front_matter
+            .map(|front_matter| front_matter.title)
+            .filter(|title| !title.is_empty())
+            .or_else(|| {
+                full_path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        Ok(json!({
+            "path": get_relative_path(&workspace_root, &full_path)?,
+            "title": title,
+            "content": content,
+            "truncated": truncated,
+        }))
+    }
+
+    fn list_tags(&self) -> Result<Value, String> {
+        Ok(json!(self.with_index(|manager| manager.get_all_tags())?))
+    }
+
+    async fn create_draft_note(&self, arguments: &Value) -> Result<Value, String> {
+        let title = arguments
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .ok_or("缺少 title 参数")?
+            .to_string();
+        let content = arguments
+            .get("content")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        let tags: Vec<String> = arguments
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let workspace_root = self.workspace_root()?;
+        let index_manager = self
+            .app_handle
+            .try_state::<Arc<RwLock<Option<IndexManager>>>>()
+            .ok_or("索引尚未建立，请稍后重试")?;
+        let cache_manager = self
+            .app_handle
+            .try_state::<Arc<RwLock<CacheManager>>>()
+            .ok_or("缓存尚未初始化，请稍后重试")?;
+        let file_path = crate::markdown::commands::create_markdown_file(
+            self.app_handle.clone(),
+            Some(AI_INBOX_CATEGORY.to_string()),
+            json!({ "title": title, "content": content, "tags": tags }),
+            index_manager,
+            cache_manager,
+        )
+        .await?;
+        Ok(json!({
+            "path": get_relative_path(&workspace_root, Path::new(&file_path))?,
+            "category": AI_INBOX_CATEGORY,
+        }))
+    }
+}
+
+impl LocalAiToolHost for AppToolHost {
+    async fn execute(&self, name: &str, arguments: &Value) -> Result<Value, String> {
+        match name {
+            "search_snippets" => self.search_snippets(arguments),
+            "read_note" => self.read_note(arguments),
+            "list_tags" => self.list_tags(),
+            "create_draft_note" => self.create_draft_note(arguments).await,
+            _ => Err(format!("未知工具: {}", name)),
+        }
+    }
+
+    fn request_confirmation(&self, call_id: &str) {
+        if let Ok(mut pending) = PENDING_CONFIRMATIONS.lock() {
+            pending.insert(self.confirmation_key(call_id), None);
+        }
+    }
+
+    fn confirmation(&self, call_id: &str) -> Option<bool> {
+        PENDING_CONFIRMATIONS
+            .lock()
+            .ok()
+            .and_then(|pending| pending.get(&self.confirmation_key(call_id)).copied())
+            .flatten()
+    }
+
+    fn clear_confirmation(&self, call_id: &str) {
+        if let Ok(mut pending) = PENDING_CONFIRMATIONS.lock() {
+            pending.remove(&self.confirmation_key(call_id));
+        }
+    }
+}
+
+// 只接受工作区内的相对路径（或指向工作区内的绝对路径）
+fn resolve_workspace_path(workspace_root: &Path, path: &str) -> Result<PathBuf, String> {
+    let candidate = Path::new(path);
+    let relative = if candidate.is_absolute() {
+        candidate
+            .strip_prefix(workspace_root)
+            .map_err(|_| format!("路径不在工作区内: {}", path))?
+    } else {
+        candidate
+    };
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("非法路径: {}", path));
+    }
+    Ok(workspace_root.join(relative))
+}
+
+/// 前端对待确认的写操作给出决定
+#[tauri::command]
+pub fn local_ai_respond_tool_call(
+    app_handle: AppHandle,
+    request_id: String,
+    call_id: String,
+    approved: bool,
+) -> Result<bool, String> {
+    require_plugin(&app_handle)?;
+    let mut pending = PENDING_CONFIRMATIONS
+        .lock()
+        .map_err(|error| format!("工具确认状态锁定失败: {}", error))?;
+    match pending.get_mut(&format!("{}:{}", request_id, call_id)) {
+        Some(decision) => {
+            *decision = Some(approved);
+            Ok(true)
+        }
+        None => {
+            warn!(
+                "[Plugin:local-ai] tool confirmation not pending: request={} call={}",
+                request_id, call_id
+            );
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::local_ai::{run_chat_stream, LocalAiConfig};
+    use crate::plugins::local_ai_provider::provider_for;
+    use crate::test_http::{spawn_stub_server, StubResponse};
+
+    struct ScriptedHost {
+        executed: Mutex<Vec<String>>,
+        approve_writes: bool,
+        pending: Mutex<HashMap<String, Option<bool>>>,
+    }
+
+    impl ScriptedHost {
+        fn new(approve_writes: bool) -> Self {
+            Self {
+                executed: Mutex::new(Vec::new()),
+                approve_writes,
+                pending: Mutex::new(HashMap::new()),
+            }
+        }
+
+        // 与 local_ai_respond_tool_call 一样，只能回应已登记的待确认项
+        fn respond(&self, call_id: &str) -> bool {
+            match self.pending.lock().unwrap().get_mut(call_id) {
+                Some(decision) => {
+                    *decision = Some(self.approve_writes);
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    impl LocalAiToolHost for ScriptedHost {
+        async fn execute(&self, name: &str, arguments: &Value) -> Result<Value, String> {
+            self.executed.lock().unwrap().push(name.to_string());
+            match name {
+                "search_snippets" => {
+                    Ok(json!([{ "path": "rust/async.md", "query": arguments["query"] }]))
+                }
+                _ => Ok(json!({ "ok": true })),
+            }
+        }
+        fn request_confirmation(&self, call_id: &str) {
+            self.pending
+                .lock()
+                .unwrap()
+                .insert(call_id.to_string(), None);
+        }
+        fn confirmation(&self, call_id: &str) -> Option<bool> {
+            self.pending.lock().unwrap().get(call_id).copied().flatten()
+        }
+        fn clear_confirmation(&self, call_id: &str) {
+            self.pending.lock().unwrap().remove(call_id);
+        }
+    }
+
+    fn sse(chunks: &[Value]) -> String {
+        let mut body: String = chunks
+            .iter()
+            .map(|chunk| format!("data: {}\n\n", chunk))
+            .collect();
+        body.push_str("data: [DONE]\n\n");
+        body
+    }
+
+    // 按顺序返回预设 SSE 响应的桩服务，并记录每次请求体
+    fn spawn_scripted_server(responses: Vec<String>) -> (u16, Arc<Mutex<Vec<Value>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let mut responses = responses.into_iter();
+        let url = spawn_stub_server(move |request| {
+            recorded.lock().unwrap().push(request.json());
+            match responses.next() {
+                Some(response) => {
+                    StubResponse::bytes(200, "text/event-stream", response.into_bytes())
+                }
+                None => StubResponse::status(500),
+            }
+        });
+        let port = url.rsplit(':').next().unwrap().parse().unwrap();
+        (port, requests)
+    }
+
+    #[test]
+    fn accumulates_streamed_tool_call_fragments() {
+        let mut accumulator = ToolCallAccumulator::default();
+        accumulator.push(&json!({"choices": [{"delta": {"tool_calls": [
+            {"index": 0, "id": "a", "function": {"name": "read_", "arguments": "{\"pa"}}
+        ]}}]}));
+        accumulator.push(&json!({"choices": [{"delta": {"tool_calls": [
+            {"index": 0, "function": {"name": "note", "arguments": "th\":\"x.md\"}"}},
+            {"index": 1, "function": {"name": "list_tags"}}
+        ]}}]}));
+        let calls = accumulator.finish();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].name, "read_note");
+        assert_eq!(calls[0].arguments, "{\"path\":\"x.md\"}");
+        assert_eq!(calls[1].id, "call_1");
+    }
+
+    #[test]
+    fn runs_tool_loop_against_scripted_server() {
+        let round_one = sse(&[
+            json!({"choices": [{"delta": {"tool_calls": [
+                {"index": 0, "id": "s1", "function": {"name": "search_snippets", "arguments": "{\"query\":\"tokio\"}"}},
+                {"index": 1, "id": "w1", "function": {"name": "create_draft_note", "arguments": "{\"title\":\"t\",\"content\":\"c\"}"}}
+            ]}}]}),
+            json!({"choices": [{"delta": {}, "finish_reason": "tool_calls"}]}),
+        ]);
+        let round_two = sse(&[json!({"choices": [{"delta": {"content": "See rust/async.md"}}]})]);
+        let (port, requests) = spawn_scripted_server(vec![round_one, round_two]);
+        let provider = provider_for(&LocalAiConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            ..LocalAiConfig::default()
+        });
+
+        let host = ScriptedHost::new(false);
+        let events = Mutex::new(Vec::new());
+        let emit = |event: ChatStreamEvent| {
+            if let ChatStreamEvent::ToolCall(invocation) = event {
+                if invocation.status == "pending" {
+                    assert!(host.respond(&invocation.id));
+                }
+                events.lock().unwrap().push(invocation.status);
+            }
+        };
+        let messages = vec![LocalAiMessage {
+            role: "user".to_string(),
+            content: json!("find my tokio notes"),
+            tool_calls: None,
+            tool_call_id: None,
+        }];
+        let client = reqwest::Client::new();
+        let cancel_flag = AtomicBool::new(false);
+        let (content, invocations) = tauri::async_runtime::block_on(run_chat_stream(
+            &client,
+            provider.as_ref(),
+            &json!({ "stream": true }),
+            messages,
+            &cancel_flag,
+            4096,
+            false,
+            Some(&host),
+            &emit,
+        ))
+        .unwrap();
+
+        assert_eq!(content, "See rust/async.md");
+        let statuses: Vec<&str> = invocations.iter().map(|i| i.status.as_str()).collect();
+        assert_eq!(statuses, vec!["ok", "rejected"]);
+        assert_eq!(*host.executed.lock().unwrap(), vec!["search_snippets"]);
+        assert_eq!(*events.lock().unwrap(), vec!["ok", "pending", "rejected"]);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0]["tools"].is_array());
+        let followup = requests[1]["messages"].as_array().unwrap();
+        assert_eq!(followup[1]["tool_calls"][1]["id"], "w1");
+        assert_eq!(followup[2]["role"], "tool");
+        assert_eq!(followup[2]["tool_call_id"], "s1");
+        assert!(followup[3]["content"]
+            .as_str()
+            .unwrap()
+            .contains("用户拒绝"));
+    }
+
+    #[test]
+    fn approved_write_runs_once_confirmed() {
+        let host = ScriptedHost::new(true);
+        let statuses = Mutex::new(Vec::new());
+        // 前端在收到待确认事件后立即批准
+        let emit = |event: ChatStreamEvent| {
+            if let ChatStreamEvent::ToolCall(invocation) = event {
+                if invocation.status == "pending" {
+                    assert!(host.respond(&invocation.id));
+                }
+                statuses.lock().unwrap().push(invocation.status);
+            }
+        };
+        let call = PendingToolCall {
+            id: "w1".to_string(),
+            name: "create_draft_note".to_string(),
+            arguments: "{\"title\":\"t\",\"content\":\"c\"}".to_string(),
+        };
+        let cancel_flag = AtomicBool::new(false);
+        let (invocation, reply) =
+            tauri::async_runtime::block_on(invoke_tool(&host, call, &cancel_flag, &emit));
+
+        assert_eq!(invocation.status, "ok");
+        assert!(invocation.requires_confirmation);
+        assert_eq!(*host.executed.lock().unwrap(), vec!["create_draft_note"]);
+        assert_eq!(*statuses.lock().unwrap(), vec!["pending", "ok"]);
+        assert_eq!(reply.tool_call_id.as_deref(), Some("w1"));
+        assert!(host.pending.lock().unwrap().is_empty());
+    }
+}
@@ -1,5 +1,14 @@
 pub mod desktop_files;
 pub mod local_ai;
+pub mod local_ai_context;
+pub mod local_ai_embeddings;
+pub mod local_ai_gguf;
+pub mod local_ai_history;
+pub mod local_ai_prompts;
+pub mod local_ai_provider;
+pub mod local_ai_rag;
+pub mod local_ai_tagging;
+pub mod local_ai_tools;
 pub mod local_launcher;
 pub mod screen_recorder;
 pub mod screenshot;
@@ -8,4 +17,7 @@ pub mod shared;
 pub mod system_theme;
 pub mod todo;
 pub mod translation;
+pub mod translation_memory;
+pub mod translation_notes;
+pub mod translation_providers;
 pub mod wallpaper_switcher;
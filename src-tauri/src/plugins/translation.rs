@@ -1,20 +1,16 @@
 use log::info;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
 
+use super::translation_memory::{glossary_for, recall, remember};
+use super::translation_providers::{
+    list_providers, provider_languages, read_providers_config, reset_provider_state,
+    translate_with_fallback, TranslationLanguage, TranslationProviderInfo,
+    TranslationProvidersConfig, PROVIDERS_CONFIG_KEY,
+};
+use crate::language_detect::{detect_languages, LanguageGuess};
 use crate::window::{WindowManager, WindowReadyCallback, WindowShowBehavior, WindowSpec};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct BingTranslation {
-    translations: Vec<BingTranslationText>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct BingTranslationText {
-    text: String,
-}
-
 fn require_translation_plugin(app_handle: &AppHandle, context: &str) -> bool {
     if let Err(error) = crate::app_config::require_plugin_enabled(app_handle, "translation") {
         log::warn!("[Plugin:translation] {} blocked: {}", context, error);
@@ -207,14 +203,121 @@ pub async fn translate_text(
         engine, from, to
     );
 
-    match engine.as_str() {
-        "bing" => translate_with_bing(text, from, to).await,
-        "google" => translate_with_google(text, from, to).await,
-        "local-ai" => super::local_ai::translate_text(app_handle, text, from, to).await,
-        _ => Err(format!("不支持的翻译引擎: {}", engine)),
+    let config = read_providers_config(&app_handle);
+    let direction = resolve_direction(&config, &text, &from, &to);
+    if direction.from != from || direction.to != to {
+        info!(
+            "🔎 [翻译] 自动识别语言: {} -> {}，置信度 {:.2}",
+            direction.from, direction.to, direction.confidence
+        );
+    }
+    translate_with_memory(
+        &app_handle,
+        &config,
+        &engine,
+        &text,
+        &direction.from,
+        &direction.to,
+    )
+    .await
+}
+
+// 低于该置信度时源语言保持 auto，交给翻译服务自行判断
+const AUTO_DETECT_MIN_CONFIDENCE: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationDirection {
+    pub from: String,
+    pub to: String,
+    pub confidence: f64,
+    pub candidates: Vec<LanguageGuess>,
+}
+
+/// 源语言为 auto 时使用离线识别结果；目标语言为 auto 时在首选与次选语言间互译
+pub(crate) fn resolve_direction(
+    config: &TranslationProvidersConfig,
+    text: &str,
+    from: &str,
+    to: &str,
+) -> TranslationDirection {
+    let candidates = detect_languages(text);
+    let detected = candidates
+        .first()
+        .filter(|guess| guess.confidence >= AUTO_DETECT_MIN_CONFIDENCE);
+    let confidence = detected.map(|guess| guess.confidence).unwrap_or(0.0);
+    let from = match (from, detected) {
+        ("auto", Some(guess)) => guess.code.clone(),
+        _ => from.to_string(),
+    };
+    let to = if to == "auto" {
+        auto_target(config, &from)
+    } else {
+        to.to_string()
+    };
+    TranslationDirection {
+        from,
+        to,
+        confidence,
+        candidates,
     }
 }
 
+fn auto_target(config: &TranslationProvidersConfig, from: &str) -> String {
+    // 简繁中文视为同一种语言
+    let same_language = |a: &str, b: &str| a == b || (a.starts_with("zh") && b.starts_with("zh"));
+    if same_language(from, &config.auto_primary_language) {
+        config.auto_secondary_language.clone()
+    } else {
+        config.auto_primary_language.clone()
+    }
+}
+
+/// 离线识别文本语种并给出自动翻译方向，供划词翻译等场景预先选择语言
+#[tauri::command]
+pub fn detect_text_language(
+    app_handle: AppHandle,
+    text: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<TranslationDirection, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    let config = read_providers_config(&app_handle);
+    Ok(resolve_direction(
+        &config,
+        &text,
+        from.as_deref().unwrap_or("auto"),
+        to.as_deref().unwrap_or("auto"),
+    ))
+}
+
+/// 先查翻译记忆，未命中时应用术语表并按回退顺序翻译，成功后写回翻译记忆
+pub(crate) async fn translate_with_memory(
+    app_handle: &AppHandle,
+    config: &TranslationProvidersConfig,
+    engine: &str,
+    text: &str,
+    from: &str,
+    to: &str,
+) -> Result<String, String> {
+    let glossary = glossary_for(text, from, to);
+    if config.memory_enabled {
+        if let Some(translated) = recall(text, from, to, engine, &glossary, config.memory_ttl_days)
+        {
+            info!("💾 [翻译] 命中翻译记忆，跳过网络请求");
+            return Ok(translated);
+        }
+    }
+
+    let (translated, provider) =
+        translate_with_fallback(app_handle, config, engine, text, from, to, &glossary).await?;
+    // 回退引擎的译文记在实际引擎名下，下次仍先尝试选定的引擎
+    if config.memory_enabled {
+        remember(text, from, to, provider, &glossary, &translated);
+    }
+    Ok(translated)
+}
+
 #[tauri::command]
 pub fn set_translation_engine(app_handle: AppHandle, engine: String) -> Result<(), String> {
     crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
@@ -227,226 +330,49 @@ pub fn get_translation_engine(app_handle: AppHandle) -> Result<String, String> {
     Ok(crate::config::get_translation_engine(app_handle))
 }
 
-// 使用Bing翻译
-async fn translate_with_bing(text: String, from: String, to: String) -> Result<String, String> {
-    let client = Client::new();
-    let token_url = "https://edge.microsoft.com/translate/auth";
-
-    // 获取token
-    let token_response = client
-        .get(token_url)
-        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/113.0.0.0 Safari/537.36 Edg/113.0.1774.42")
-        .send()
-        .await
-        .map_err(|e| format!("获取Bing令牌失败: {}", e))?;
-
-    let token = token_response
-        .text()
-        .await
-        .map_err(|e| format!("读取Bing令牌失败: {}", e))?;
-
-    if token.is_empty() {
-        return Err("获取Bing令牌为空".to_string());
-    }
-
-    // 转换语言代码
-    let from_code = match from.as_str() {
-        "auto" => "",
-        "zh" => "zh-Hans",
-        "zh_tw" => "zh-Hant",
-        "en" => "en",
-        "ja" => "ja",
-        "ko" => "ko",
-        "fr" => "fr",
-        "de" => "de",
-        "ru" => "ru",
-        "es" => "es",
-        "pt_pt" => "pt-pt",
-        "pt_br" => "pt",
-        "vi" => "vi",
-        "id" => "id",
-        "th" => "th",
-        "ar" => "ar",
-        _ => &from,
-    };
-
-    let to_code = match to.as_str() {
-        "zh" => "zh-Hans",
-        "zh_tw" => "zh-Hant",
-        "en" => "en",
-        "ja" => "ja",
-        "ko" => "ko",
-        "fr" => "fr",
-        "de" => "de",
-        "ru" => "ru",
-        "es" => "es",
-        "pt_pt" => "pt-pt",
-        "pt_br" => "pt",
-        "vi" => "vi",
-        "id" => "id",
-        "th" => "th",
-        "ar" => "ar",
-        _ => &to,
-    };
+// 获取各翻译服务的配置与回退顺序
+#[tauri::command]
+pub fn get_translation_providers_config(
+    app_handle: AppHandle,
+) -> Result<TranslationProvidersConfig, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    Ok(read_providers_config(&app_handle))
+}
 
-    // 构造翻译请求
-    let url = format!(
-        "https://api.cognitive.microsofttranslator.com/translate?api-version=3.0&from={}&to={}",
-        from_code, to_code
+// 保存翻译服务配置，清除冷却状态与语言列表缓存
+#[tauri::command]
+pub fn set_translation_providers_config(
+    app_handle: AppHandle,
+    config: TranslationProvidersConfig,
+) -> Result<TranslationProvidersConfig, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    let config = config.normalized();
+    crate::json_config::set_app_config_value(&app_handle, PROVIDERS_CONFIG_KEY, &config)?;
+    reset_provider_state();
+    info!(
+        "🔄 翻译服务配置已更新，回退顺序: {:?}",
+        config.fallback_order
     );
-
-    let json_body = serde_json::json!([{"text": text}]);
-
-    // 发送翻译请求
-    let response = client
-        .post(url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", token))
-        .json(&json_body)
-        .send()
-        .await
-        .map_err(|e| format!("Bing翻译请求失败: {}", e))?;
-
-    let status = response.status();
-
-    if status.is_success() {
-        let result: Vec<BingTranslation> = response
-            .json()
-            .await
-            .map_err(|e| format!("解析Bing翻译结果失败: {}", e))?;
-
-        if let Some(first_result) = result.first() {
-            if let Some(translation) = first_result.translations.first() {
-                return Ok(translation.text.trim().to_string());
-            }
-        }
-
-        Err("Bing翻译结果解析错误".to_string())
-    } else {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "无法获取错误详情".to_string());
-
-        Err(format!(
-            "Bing翻译API错误，状态码: {}，详情: {}",
-            status, error_text
-        ))
-    }
+    Ok(config)
 }
 
-// 使用Google翻译
-async fn translate_with_google(text: String, from: String, to: String) -> Result<String, String> {
-    let client = Client::new();
-
-    let from_code = match from.as_str() {
-        "auto" => "auto",
-        "zh" => "zh-CN",
-        "zh_tw" => "zh-TW",
-        "en" => "en",
-        "ja" => "ja",
-        "ko" => "ko",
-        "fr" => "fr",
-        "de" => "de",
-        "ru" => "ru",
-        "es" => "es",
-        "pt_pt" => "pt-PT",
-        "pt_br" => "pt-BR",
-        "vi" => "vi",
-        "id" => "id",
-        "th" => "th",
-        "ar" => "ar",
-        _ => &from,
-    };
-
-    let to_code = match to.as_str() {
-        "zh" => "zh-CN",
-        "zh_tw" => "zh-TW",
-        "en" => "en",
-        "ja" => "ja",
-        "ko" => "ko",
-        "fr" => "fr",
-        "de" => "de",
-        "ru" => "ru",
-        "es" => "es",
-        "pt_pt" => "pt-PT",
-        "pt_br" => "pt-BR",
-        "vi" => "vi",
-        "id" => "id",
-        "th" => "th",
-        "ar" => "ar",
-        _ => &to,
-    };
-
-    let url = "https://translate.google.com/translate_a/single";
-
-    // 构造请求参数
-    let response = client
-        .get(url)
-        .query(&[
-            ("client", "gtx"),
-            ("sl", from_code),
-            ("tl", to_code),
-            ("hl", to_code),
-            ("dt", "t"),
-            ("dt", "at"),
-            ("dt", "bd"),
-            ("dt", "ex"),
-            ("dt", "ld"),
-            ("dt", "md"),
-            ("dt", "qca"),
-            ("dt", "rw"),
-            ("dt", "rm"),
-            ("dt", "ss"),
-            ("ie", "UTF-8"),
-            ("oe", "UTF-8"),
-            ("otf", "1"),
-            ("ssel", "0"),
-            ("tsel", "0"),
-            ("kc", "7"),
-            ("q", &text),
-        ])
-        .header("Content-Type", "application/json")
-        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .send()
-        .await
-        .map_err(|e| format!("Google翻译请求失败: {}", e))?;
-
-    let status = response.status();
-
-    if status.is_success() {
-        let result: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| format!("解析Google翻译结果失败: {}", e))?;
-
-        let mut translated_text = String::new();
-
-        // 解析Google翻译结果
-        if let Some(outer_array) = result.get(0).and_then(|v| v.as_array()) {
-            for item in outer_array {
-                if let Some(inner_array) = item.get(0) {
-                    if let Some(text) = inner_array.as_str() {
-                        translated_text.push_str(text);
-                    }
-                }
-            }
-
-            if !translated_text.is_empty() {
-                return Ok(translated_text.trim().to_string());
-            }
-        }
+// 列出翻译服务及其配置、冷却状态
+#[tauri::command]
+pub fn list_translation_providers(
+    app_handle: AppHandle,
+) -> Result<Vec<TranslationProviderInfo>, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    let config = read_providers_config(&app_handle);
+    Ok(list_providers(&app_handle, &config))
+}
 
-        Err("Google翻译结果解析错误".to_string())
-    } else {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "无法获取错误详情".to_string());
-
-        Err(format!(
-            "Google翻译API错误，状态码: {}，详情: {}",
-            status, error_text
-        ))
-    }
+// 查询翻译服务支持的语言（LibreTranslate、DeepL 从服务端获取）
+#[tauri::command]
+pub async fn get_translation_languages(
+    app_handle: AppHandle,
+    engine: String,
+) -> Result<Vec<TranslationLanguage>, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    let config = read_providers_config(&app_handle);
+    provider_languages(&app_handle, &config, &engine).await
 }
@@ -0,0 +1,857 @@
+// 翻译记忆、历史与术语表
+//
+// 翻译结果按（规范化原文、语言对、引擎、原文命中的术语）的哈希存入 translation_memory，
+// 有效期内的相同请求直接复用，不再访问网络；过期条目仍作为历史保留，可搜索和导出。
+// 术语表（translation_glossary）对机器翻译引擎用占位符保护原文中的术语，译后替换为
+// 指定译法；对大模型引擎（OpenAI 兼容服务、本地 AI）则把术语写入系统提示。
+
+use crate::db::open_plugin_store;
+use crate::plugins::translation_providers::read_providers_config;
+use crate::text_utils::escape_like;
+use log::{info, warn};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tauri::AppHandle;
+
+// 历史最多保留的条目数，超出时删除最久未使用的
+const MAX_HISTORY_ENTRIES: usize = 5000;
+const DEFAULT_PAGE_SIZE: usize = 30;
+const MAX_PAGE_SIZE: usize = 200;
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+// 占位符使用罕见的括号，机器翻译通常原样保留
+const PLACEHOLDER_OPEN: char = '⟦';
+const PLACEHOLDER_CLOSE: char = '⟧';
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlossaryTerm {
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub term: String,
+    // 与原文相同表示保持不译
+    pub translation: String,
+    // 为空时适用于任意源语言 / 目标语言
+    #[serde(default)]
+    pub from_lang: String,
+    #[serde(default)]
+    pub to_lang: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationHistoryEntry {
+    pub id: i64,
+    pub source_text: String,
+    pub translated_text: String,
+    pub from_lang: String,
+    pub to_lang: String,
+    // 实际完成翻译的引擎
+    pub engine: String,
+    pub hit_count: i64,
+    pub created_at: i64,
+    pub last_used_at: i64,
+    // 超过有效期后不再复用，下次翻译会重新请求
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationHistoryPage {
+    pub items: Vec<TranslationHistoryEntry>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+// ============= 存储 =============
+
+pub(crate) fn create_memory_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS translation_memory (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             cache_key TEXT NOT NULL UNIQUE,
+             source_text TEXT NOT NULL,
+             translated_text TEXT NOT NULL,
+             from_lang TEXT NOT NULL,
+             to_lang TEXT NOT NULL,
+             engine TEXT NOT NULL,
+             hit_count INTEGER NOT NULL DEFAULT 0,
+             created_at INTEGER NOT NULL,
+             last_used_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_translation_memory_recent
+             ON translation_memory(last_used_at DESC);
+         CREATE TABLE IF NOT EXISTS translation_glossary (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             term TEXT NOT NULL,
+             translation TEXT NOT NULL,
+             from_lang TEXT NOT NULL DEFAULT '',
+             to_lang TEXT NOT NULL DEFAULT '',
+             case_sensitive INTEGER NOT NULL DEFAULT 0,
+             created_at INTEGER NOT NULL,
+             UNIQUE(term, from_lang, to_lang)
+         );",
+    )
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// 统一换行、合并行内连续空白并去掉首尾空白，作为翻译记忆的匹配依据
+pub(crate) fn normalize_text(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// 术语参与哈希，修改命中的术语后旧译文自然失效
+fn cache_key(
+    normalized: &str,
+    from: &str,
+    to: &str,
+    engine: &str,
+    glossary: &[GlossaryTerm],
+) -> String {
+    let mut hasher = Sha256::new();
+    for part in [engine, from, to, normalized] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    for term in glossary {
+        hasher.update(term.term.as_bytes());
+        hasher.update([0x1fu8]);
+        hasher.update(term.translation.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub(crate) fn lookup_memory(
+    conn: &Connection,
+    text: &str,
+    from: &str,
+    to: &str,
+    engine: &str,
+    glossary: &[GlossaryTerm],
+    ttl_days: u32,
+) -> Result<Option<String>, rusqlite::Error> {
+    let normalized = normalize_text(text);
+    if normalized.is_empty() {
+        return Ok(None);
+    }
+    let key = cache_key(&normalized, from, to, engine, glossary);
+    let now = now_millis();
+    let hit: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, translated_text FROM translation_memory
+             WHERE cache_key = ?1 AND created_at >= ?2",
+            params![key, now - i64::from(ttl_days) * DAY_MILLIS],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((id, translated)) = hit else {
+        return Ok(None);
+    };
+    conn.execute(
+        "UPDATE translation_memory SET hit_count = hit_count + 1, last_used_at = ?2 WHERE id = ?1",
+        params![id, now],
+    )?;
+    Ok(Some(translated))
+}
+
+pub(crate) fn store_memory(
+    conn: &Connection,
+    text: &str,
+    from: &str,
+    to: &str,
+    engine: &str,
+    glossary: &[GlossaryTerm],
+    translated: &str,
+) -> Result<(), rusqlite::Error> {
+    let normalized = normalize_text(text);
+    if normalized.is_empty() || translated.trim().is_empty() {
+        return Ok(());
+    }
+    let now = now_millis();
+    conn.execute(
+        "INSERT INTO translation_memory
+             (cache_key, source_text, translated_text, from_lang, to_lang, engine, created_at, last_used_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+         ON CONFLICT(cache_key) DO UPDATE SET
+             source_text = excluded.source_text,
+             translated_text = excluded.translated_text,
+             created_at = excluded.created_at,
+             last_used_at = excluded.last_used_at",
+        params![
+            cache_key(&normalized, from, to, engine, glossary),
+            text.trim(),
+            translated,
+            from,
+            to,
+            engine,
+            now
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM translation_memory WHERE id NOT IN (
+             SELECT id FROM translation_memory ORDER BY last_used_at DESC LIMIT ?1
+         )",
+        params![MAX_HISTORY_ENTRIES as i64],
+    )?;
+    Ok(())
+}
+
+/// 查询翻译记忆；数据库不可用时只记录警告，按未命中处理
+pub(crate) fn recall(
+    text: &str,
+    from: &str,
+    to: &str,
+    engine: &str,
+    glossary: &[GlossaryTerm],
+    ttl_days: u32,
+) -> Option<String> {
+    let result = open_plugin_store().and_then(|conn| {
+        lookup_memory(&conn, text, from, to, engine, glossary, ttl_days).map_err(|e| e.to_string())
+    });
+    match result {
+        Ok(hit) => hit,
+        Err(error) => {
+            warn!("⚠️ [翻译] 读取翻译记忆失败: {}", error);
+            None
+        }
+    }
+}
+
+pub(crate) fn remember(
+    text: &str,
+    from: &str,
+    to: &str,
+    engine: &str,
+    glossary: &[GlossaryTerm],
+    translated: &str,
+) {
+    let result = open_plugin_store().and_then(|conn| {
+        store_memory(&conn, text, from, to, engine, glossary, translated).map_err(|e| e.to_string())
+    });
+    if let Err(error) = result {
+        warn!("⚠️ [翻译] 写入翻译记忆失败: {}", error);
+    }
+}
+
+// ============= 历史 =============
+
+// 按关键词（原文或译文包含）和引擎筛选，返回 WHERE 子句与参数
+fn history_filter(query: &str, engine: &str) -> (String, Vec<String>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    for term in query.split_whitespace() {
+        values.push(format!("%{}%", escape_like(term)));
+        conditions.push(format!(
+            "(source_text LIKE ?{0} ESCAPE '\\' OR translated_text LIKE ?{0} ESCAPE '\\')",
+            values.len()
+        ));
+    }
+    if !engine.is_empty() {
+        values.push(engine.to_string());
+        conditions.push(format!("engine = ?{}", values.len()));
+    }
+    if conditions.is_empty() {
+        (String::new(), values)
+    } else {
+        (format!("WHERE {}", conditions.join(" AND ")), values)
+    }
+}
+
+pub(crate) fn query_history(
+    conn: &Connection,
+    query: &str,
+    engine: &str,
+    offset: usize,
+    limit: Option<usize>,
+    ttl_days: u32,
+) -> Result<(Vec<TranslationHistoryEntry>, usize), rusqlite::Error> {
+    let (filter, values) = history_filter(query, engine);
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM translation_memory {}", filter),
+        params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+    let limit_clause = match limit {
+        Some(limit) => format!("LIMIT {} OFFSET {}", limit, offset),
+        None => String::new(),
+    };
+    let expires_before = now_millis() - i64::from(ttl_days) * DAY_MILLIS;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, source_text, translated_text, from_lang, to_lang, engine, hit_count,
+                created_at, last_used_at
+         FROM translation_memory {} ORDER BY last_used_at DESC {}",
+        filter, limit_clause
+    ))?;
+    let items = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            let created_at: i64 = row.get(7)?;
+            Ok(TranslationHistoryEntry {
+                id: row.get(0)?,
+                source_text: row.get(1)?,
+                translated_text: row.get(2)?,
+                from_lang: row.get(3)?,
+                to_lang: row.get(4)?,
+                engine: row.get(5)?,
+                hit_count: row.get(6)?,
+                created_at,
+                last_used_at: row.get(8)?,
+                expired: created_at < expires_before,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((items, total.max(0) as usize))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_timestamp(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default()
+}
+
+// TMX 使用 BCP 47 语言标签；自动检测的源语言记为 und
+fn tmx_language(code: &str) -> String {
+    match code {
+        "auto" => "und".to_string(),
+        _ => match code.split_once('_') {
+            Some((language, region)) => format!("{}-{}", language, region.to_uppercase()),
+            None => code.to_string(),
+        },
+    }
+}
+
+fn render_csv(entries: &[TranslationHistoryEntry]) -> String {
+    let mut output =
+        String::from("source,translation,from,to,engine,hits,created_at,last_used_at\n");
+    for entry in entries {
+        let fields = [
+            csv_field(&entry.source_text),
+            csv_field(&entry.translated_text),
+            csv_field(&entry.from_lang),
+            csv_field(&entry.to_lang),
+            csv_field(&entry.engine),
+            entry.hit_count.to_string(),
+            format_timestamp(entry.created_at),
+            format_timestamp(entry.last_used_at),
+        ];
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+fn render_tmx(entries: &[TranslationHistoryEntry]) -> String {
+    use crate::markdown::export::escape_html;
+
+    let mut output = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n  <header creationtool=\"snippets-code\" creationtoolversion=\"{}\" segtype=\"paragraph\" o-tmf=\"sqlite\" adminlang=\"en\" srclang=\"*all*\" datatype=\"plaintext\"/>\n  <body>\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    for entry in entries {
+        let created = chrono::DateTime::from_timestamp_millis(entry.created_at)
+            .map(|time| time.format("%Y%m%dT%H%M%SZ").to_string())
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "    <tu creationdate=\"{}\" usagecount=\"{}\">\n      <prop type=\"x-engine\">{}</prop>\n      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n    </tu>\n",
+            created,
+            entry.hit_count,
+            escape_html(&entry.engine),
+            tmx_language(&entry.from_lang),
+            escape_html(&entry.source_text),
+            tmx_language(&entry.to_lang),
+            escape_html(&entry.translated_text)
+        ));
+    }
+    output.push_str("  </body>\n</tmx>\n");
+    output
+}
+
+// ============= 术语表 =============
+
+pub(crate) fn load_glossary(conn: &Connection) -> Result<Vec<GlossaryTerm>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, term, translation, from_lang, to_lang, case_sensitive
+         FROM translation_glossary ORDER BY term COLLATE NOCASE",
+    )?;
+    let terms = stmt
+        .query_map([], |row| {
+            Ok(GlossaryTerm {
+                id: Some(row.get(0)?),
+                term: row.get(1)?,
+                translation: row.get(2)?,
+                from_lang: row.get(3)?,
+                to_lang: row.get(4)?,
+                case_sensitive: row.get::<_, i64>(5)? != 0,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(terms)
+}
+
+fn save_glossary_term(conn: &Connection, term: GlossaryTerm) -> Result<(), String> {
+    let (source, translation) = (term.term.trim(), term.translation.trim());
+    if source.is_empty() || translation.is_empty() {
+        return Err("术语和译法不能为空".to_string());
+    }
+    let (from_lang, to_lang) = (term.from_lang.trim(), term.to_lang.trim());
+    let duplicate: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM translation_glossary WHERE term = ?1 AND from_lang = ?2 AND to_lang = ?3",
+            params![source, from_lang, to_lang],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("保存术语失败: {}", e))?;
+    if duplicate.is_some_and(|id| Some(id) != term.id) {
+        return Err(format!("术语「{}」已存在", source));
+    }
+    let result = match term.id {
+        Some(id) => conn.execute(
+            "UPDATE translation_glossary
+             SET term = ?2, translation = ?3, from_lang = ?4, to_lang = ?5, case_sensitive = ?6
+             WHERE id = ?1",
+            params![
+                id,
+                source,
+                translation,
+                from_lang,
+                to_lang,
+                term.case_sensitive
+            ],
+        ),
+        None => conn.execute(
+            "INSERT INTO translation_glossary
+                 (term, translation, from_lang, to_lang, case_sensitive, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                source,
+                translation,
+                from_lang,
+                to_lang,
+                term.case_sensitive,
+                now_millis()
+            ],
+        ),
+    };
+    result.map_err(|e| format!("保存术语失败: {}", e))?;
+    Ok(())
+}
+
+fn term_applies(term: &GlossaryTerm, from: &str, to: &str) -> bool {
+    (term.from_lang.is_empty() || from == "auto" || term.from_lang == from)
+        && (term.to_lang.is_empty() || term.to_lang == to)
+}
+
+// 拉丁字母等需要按词边界匹配，中日韩文字没有词边界
+fn is_word_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && (c as u32) < 0x2E80
+}
+
+fn chars_equal(a: char, b: char, case_sensitive: bool) -> bool {
+    a == b || (!case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+// 在 chars[start..] 处匹配术语，返回匹配的字符数
+fn match_term(chars: &[(usize, char)], start: usize, term: &GlossaryTerm) -> Option<usize> {
+    let term_chars: Vec<char> = term.term.chars().collect();
+    let (first, last) = (*term_chars.first()?, *term_chars.last()?);
+    let end = start + term_chars.len();
+    if end > chars.len() {
+        return None;
+    }
+    let matched = chars[start..end]
+        .iter()
+        .zip(&term_chars)
+        .all(|((_, c), t)| chars_equal(*c, *t, term.case_sensitive));
+    let before_ok = start == 0 || !(is_word_char(first) && is_word_char(chars[start - 1].1));
+    let after_ok = end == chars.len() || !(is_word_char(last) && is_word_char(chars[end].1));
+    (matched && before_ok && after_ok).then_some(term_chars.len())
+}
+
+// 原文中的术语出现位置（字节区间与术语下标），长术语优先且互不重叠
+fn find_terms(text: &str, terms: &[GlossaryTerm]) -> Vec<(usize, usize, usize)> {
+    let mut order: Vec<usize> = (0..terms.len()).collect();
+    order.sort_by_key(|index| std::cmp::Reverse(terms[*index].term.chars().count()));
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut matches = Vec::new();
+    let mut position = 0;
+    while position < chars.len() {
+        let found = order.iter().find_map(|index| {
+            match_term(&chars, position, &terms[*index]).map(|length| (*index, length))
+        });
+        match found {
+            Some((index, length)) => {
+                let start = chars[position].0;
+                let end = chars
+                    .get(position + length)
+                    .map(|(offset, _)| *offset)
+                    .unwrap_or(text.len());
+                matches.push((start, end, index));
+                position += length;
+            }
+            None => position += 1,
+        }
+    }
+    matches
+}
+
+/// 适用于该语言对且在原文中出现的术语，按术语排序以保证哈希稳定
+pub(crate) fn matching_terms(
+    text: &str,
+    from: &str,
+    to: &str,
+    glossary: &[GlossaryTerm],
+) -> Vec<GlossaryTerm> {
+    let applicable: Vec<GlossaryTerm> = glossary
+        .iter()
+        .filter(|term| term_applies(term, from, to))
+        .cloned()
+        .collect();
+    let mut matched: Vec<GlossaryTerm> = Vec::new();
+    for (_, _, index) in find_terms(text, &applicable) {
+        if !matched.contains(&applicable[index]) {
+            matched.push(applicable[index].clone());
+        }
+    }
+    matched.sort_by(|a, b| a.term.cmp(&b.term));
+    matched
+}
+
+/// 从术语表中取出本次翻译命中的术语；数据库不可用时不应用术语
+pub(crate) fn glossary_for(text: &str, from: &str, to: &str) -> Vec<GlossaryTerm> {
+    match open_plugin_store().and_then(|conn| load_glossary(&conn).map_err(|e| e.to_string())) {
+        Ok(glossary) => matching_terms(text, from, to, &glossary),
+        Err(error) => {
+            warn!("⚠️ [翻译] 读取术语表失败: {}", error);
+            Vec::new()
+        }
+    }
+}
+
+pub(crate) struct ProtectedText {
+    pub text: String,
+    replacements: Vec<String>,
+}
+
+impl ProtectedText {
+    /// 把译文中的占位符替换为术语的指定译法；允许引擎在括号内插入空白
+    pub fn restore(&self, translated: &str) -> String {
+        if self.replacements.is_empty() {
+            return translated.to_string();
+        }
+        let mut output = String::with_capacity(translated.len());
+        let mut rest = translated;
+        while let Some(open) = rest.find(PLACEHOLDER_OPEN) {
+            output.push_str(&rest[..open]);
+            let after = &rest[open + PLACEHOLDER_OPEN.len_utf8()..];
+            let replacement = after.find(PLACEHOLDER_CLOSE).and_then(|close| {
+                let index = after[..close].trim().parse::<usize>().ok()?;
+                Some((self.replacements.get(index)?, close))
+            });
+            match replacement {
+                Some((value, close)) => {
+                    output.push_str(value);
+                    rest = &after[close + PLACEHOLDER_CLOSE.len_utf8()..];
+                }
+                None => {
+                    output.push(PLACEHOLDER_OPEN);
+                    rest = after;
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// 用编号占位符替换原文中的术语，供不理解指令的机器翻译引擎使用
+pub(crate) fn protect_terms(text: &str, glossary: &[GlossaryTerm]) -> ProtectedText {
+    let mut replacements: Vec<String> = Vec::new();
+    let mut output = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end, index) in find_terms(text, glossary) {
+        let translation = &glossary[index].translation;
+        let slot = match replacements.iter().position(|value| value == translation) {
+            Some(slot) => slot,
+            None => {
+                replacements.push(translation.clone());
+                replacements.len() - 1
+            }
+        };
+        output.push_str(&text[cursor..start]);
+        output.push_str(&format!(
+            "{}{}{}",
+            PLACEHOLDER_OPEN, slot, PLACEHOLDER_CLOSE
+        ));
+        cursor = end;
+    }
+    output.push_str(&text[cursor..]);
+    ProtectedText {
+        text: output,
+        replacements,
+    }
+}
+
+/// 追加到大模型翻译提示中的术语要求
+pub(crate) fn glossary_prompt(glossary: &[GlossaryTerm]) -> String {
+    if glossary.is_empty() {
+        return String::new();
+    }
+    let lines = glossary
+        .iter()
+        .map(|term| format!("- {} → {}", term.term, term.translation))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "\n\nGlossary: always translate these terms exactly as given, even when the given translation is identical to the source term:\n{}",
+        lines
+    )
+}
+
+// ============= 命令 =============
+
+fn require_plugin(app_handle: &AppHandle) -> Result<(), String> {
+    crate::app_config::require_plugin_enabled(app_handle, "translation")
+}
+
+/// 分页列出翻译历史，最近使用的在前；query 按空白分词，原文或译文需包含每个词
+#[tauri::command]
+pub fn list_translation_history(
+    app_handle: AppHandle,
+    query: Option<String>,
+    engine: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<TranslationHistoryPage, String> {
+    require_plugin(&app_handle)?;
+    let config = read_providers_config(&app_handle);
+    let conn = open_plugin_store()?;
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let (items, total) = query_history(
+        &conn,
+        query.as_deref().unwrap_or_default(),
+        engine.as_deref().unwrap_or_default(),
+        offset,
+        Some(limit),
+        config.memory_ttl_days,
+    )
+    .map_err(|e| format!("读取翻译历史失败: {}", e))?;
+    Ok(TranslationHistoryPage {
+        items,
+        total,
+        offset,
+        limit,
+    })
+}
+
+#[tauri::command]
+pub fn delete_translation_history(app_handle: AppHandle, ids: Vec<i64>) -> Result<usize, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_plugin_store()?;
+    let mut deleted = 0;
+    for id in ids {
+        deleted += conn
+            .execute("DELETE FROM translation_memory WHERE id = ?1", params![id])
+            .map_err(|e| format!("删除翻译历史失败: {}", e))?;
+    }
+    Ok(deleted)
+}
+
+#[tauri::command]
+pub fn clear_translation_history(app_handle: AppHandle) -> Result<(), String> {
+    require_plugin(&app_handle)?;
+    let conn = open_plugin_store()?;
+    conn.execute("DELETE FROM translation_memory", [])
+        .map_err(|e| format!("清空翻译历史失败: {}", e))?;
+    info!("🧹 [翻译] 已清空翻译历史");
+    Ok(())
+}
+
+/// 将翻译历史（可按关键词筛选）导出为 CSV、JSON 或 TMX 文件，返回导出的条目数；
+/// 未指定格式时按文件扩展名判断
+#[tauri::command]
+pub fn export_translation_history(
+    app_handle: AppHandle,
+    path: String,
+    format: Option<String>,
+    query: Option<String>,
+) -> Result<usize, String> {
+    require_plugin(&app_handle)?;
+    let config = read_providers_config(&app_handle);
+    let format = format
+        .or_else(|| {
+            Path::new(&path)
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
+        .to_lowercase();
+    let conn = open_plugin_store()?;
+    let (entries, _) = query_history(
+        &conn,
+        query.as_deref().unwrap_or_default(),
+        "",
+        0,
+        None,
+        config.memory_ttl_days,
+    )
+    .map_err(|e| format!("读取翻译历史失败: {}", e))?;
+    let content = match format.as_str() {
+        "csv" => render_csv(&entries),
+        "json" => serde_json::to_string_pretty(&json!({ "entries": entries }))
+            .map_err(|e| format!("序列化翻译历史失败: {}", e))?,
+        "tmx" => render_tmx(&entries),
+        other => return Err(format!("不支持的导出格式: {}", other)),
+    };
+    std::fs::write(&path, content).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    info!("📤 [翻译] 已导出 {} 条翻译历史到 {}", entries.len(), path);
+    Ok(entries.len())
+}
+
+#[tauri::command]
+pub fn list_translation_glossary(app_handle: AppHandle) -> Result<Vec<GlossaryTerm>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_plugin_store()?;
+    load_glossary(&conn).map_err(|e| format!("读取术语表失败: {}", e))
+}
+
+/// 新增（id 为空）或修改术语，返回更新后的术语表
+#[tauri::command]
+pub fn save_translation_glossary_term(
+    app_handle: AppHandle,
+    term: GlossaryTerm,
+) -> Result<Vec<GlossaryTerm>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_plugin_store()?;
+    save_glossary_term(&conn, term)?;
+    load_glossary(&conn).map_err(|e| format!("读取术语表失败: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_translation_glossary_term(
+    app_handle: AppHandle,
+    id: i64,
+) -> Result<Vec<GlossaryTerm>, String> {
+    require_plugin(&app_handle)?;
+    let conn = open_plugin_store()?;
+    conn.execute(
+        "DELETE FROM translation_glossary WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| format!("删除术语失败: {}", e))?;
+    load_glossary(&conn).map_err(|e| format!("读取术语表失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(source: &str, translation: &str) -> GlossaryTerm {
+        GlossaryTerm {
+            id: None,
+            term: source.to_string(),
+            translation: translation.to_string(),
+            from_lang: String::new(),
+            to_lang: String::new(),
+            case_sensitive: false,
+        }
+    }
+
+    #[test]
+    fn protects_terms_on_word_boundaries_and_restores_translations() {
+        let glossary = vec![term("hook", "hook"), term("组件", "component")];
+        let protected = protect_terms("Use a Hook in hooks, 组件里也用 hook", &glossary);
+        assert_eq!(protected.text, "Use a ⟦0⟧ in hooks, ⟦1⟧里也用 ⟦0⟧");
+        assert_eq!(
+            protected.restore("在 hooks 中使用 ⟦ 0 ⟧，in the ⟦1⟧ too ⟦7⟧"),
+            "在 hooks 中使用 hook，in the component too ⟦7⟧"
+        );
+    }
+
+    #[test]
+    fn matching_terms_respects_language_pair() {
+        let mut chinese_only = term("组件", "component");
+        chinese_only.from_lang = "zh".to_string();
+        chinese_only.to_lang = "en".to_string();
+        let glossary = vec![chinese_only, term("hook", "hook")];
+        assert_eq!(matching_terms("组件 hook", "zh", "en", &glossary).len(), 2);
+        assert_eq!(
+            matching_terms("组件 hook", "auto", "en", &glossary).len(),
+            2
+        );
+        let to_japanese = matching_terms("组件 hook", "zh", "ja", &glossary);
+        assert_eq!(to_japanese, vec![term("hook", "hook")]);
+        assert!(matching_terms("no terms", "en", "zh", &glossary).is_empty());
+    }
+
+    #[test]
+    fn memory_reuses_normalized_text_until_expired() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_memory_tables(&conn).unwrap();
+        store_memory(
+            &conn,
+            "Hello   world\r\n",
+            "en",
+            "zh",
+            "bing",
+            &[],
+            "你好世界",
+        )
+        .unwrap();
+
+        let hit = lookup_memory(&conn, "  Hello world", "en", "zh", "bing", &[], 30).unwrap();
+        assert_eq!(hit.as_deref(), Some("你好世界"));
+        assert!(
+            lookup_memory(&conn, "Hello world", "en", "zh", "google", &[], 30)
+                .unwrap()
+                .is_none()
+        );
+        let glossary = vec![term("world", "World")];
+        assert!(
+            lookup_memory(&conn, "Hello world", "en", "zh", "bing", &glossary, 30)
+                .unwrap()
+                .is_none()
+        );
+
+        conn.execute(
+            "UPDATE translation_memory SET created_at = created_at - ?1",
+            params![31 * DAY_MILLIS],
+        )
+        .unwrap();
+        assert!(
+            lookup_memory(&conn, "Hello world", "en", "zh", "bing", &[], 30)
+                .unwrap()
+                .is_none()
+        );
+        let (entries, total) = query_history(&conn, "hello", "", 0, Some(10), 30).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(entries[0].hit_count, 1);
+        assert!(entries[0].expired);
+    }
+}
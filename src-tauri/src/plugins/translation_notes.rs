@@ -0,0 +1,1017 @@
+// 整篇笔记翻译
+//
+// 用 pulldown-cmark 解析正文，只把段落、标题、列表项和表格单元格中的文字交给翻译引擎：
+// 代码块、HTML 块原样保留；行内代码、HTML、链接地址、wikilink、脚注引用、裸 URL 和强调符号
+// 替换为 ⟦#n⟧ 占位符，译后还原；软换行合并为空格。frontmatter 只翻译 title 与 summary，
+// 其余键值原样保留。文本单元按长度分批请求，每批完成后把译文写入 translation_note_chunks，
+// 失败或取消后再次翻译同一笔记到同一语言时跳过已完成的批次。结果写为原笔记同目录下的
+// `文件名.语言.md`，frontmatter 的 translated_from 和正文开头的 wikilink 指回原文。
+
+use crate::db::open_plugin_store;
+use crate::json_config::get_workspace_root;
+use crate::markdown::file_ops::{get_relative_path, FileNameGenerator};
+use crate::markdown::metadata::{
+    format_frontmatter_block, try_parse_front_matter, FileMetadata, FrontMatter, TranslationSource,
+};
+use crate::markdown::{CacheManager, IndexManager};
+use crate::plugins::translation::{resolve_direction, translate_with_memory};
+use crate::plugins::translation_providers::{read_providers_config, TranslationProvidersConfig};
+use crate::text_utils::sha256_hex;
+use log::{info, warn};
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use tauri::{AppHandle, Emitter, State};
+
+const PROGRESS_EVENT: &str = "note-translation-progress";
+// 每批请求的最大字符数
+const CHUNK_MAX_CHARS: usize = 1800;
+// 批内文本单元之间用空行分隔，译后按非空行拆回
+const UNIT_SEPARATOR: &str = "\n\n";
+const PLACEHOLDER_OPEN: char = '⟦';
+const PLACEHOLDER_CLOSE: char = '⟧';
+
+// 正在运行的翻译任务及其取消标记
+static ACTIVE_JOBS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteTranslationProgress {
+    pub job_id: String,
+    // 原文相对路径
+    pub source_path: String,
+    // running / completed / failed / cancelled
+    pub status: String,
+    pub done: usize,
+    pub total: usize,
+    // 从上次中断处直接复用的批次数
+    pub resumed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteTranslationResult {
+    pub job_id: String,
+    // 译文笔记的绝对路径
+    pub target_path: String,
+    pub relative_path: String,
+    pub title: String,
+    pub chunks: usize,
+    pub resumed: usize,
+}
+
+// 中断后尚未完成的翻译任务
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteTranslationJob {
+    pub job_id: String,
+    pub source_path: String,
+    pub from: String,
+    pub to: String,
+    pub done: usize,
+    pub total: usize,
+    pub updated_at: i64,
+}
+
+// ============= 分段 =============
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    Kept(String),
+    Space,
+}
+
+// 一个需要翻译的文本单元：range 为正文中被替换的区间
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TextUnit {
+    pub range: Range<usize>,
+    // 交给翻译引擎的文本，受保护的片段为 ⟦#n⟧
+    pub source: String,
+    kept: Vec<String>,
+    // 单元首尾的受保护片段不参与翻译
+    prefix: String,
+    suffix: String,
+}
+
+impl TextUnit {
+    /// 还原占位符并补回首尾片段；引擎丢失的占位符追加到末尾，避免链接地址或代码丢失
+    pub fn render(&self, translated: &str) -> String {
+        let mut used = vec![false; self.kept.len()];
+        let mut output = String::with_capacity(translated.len() + self.prefix.len());
+        output.push_str(&self.prefix);
+        let mut rest = translated.trim();
+        while let Some(open) = rest.find(PLACEHOLDER_OPEN) {
+            output.push_str(&rest[..open]);
+            let after = &rest[open + PLACEHOLDER_OPEN.len_utf8()..];
+            let slot = after.find(PLACEHOLDER_CLOSE).and_then(|close| {
+                let inner = after[..close].trim().strip_prefix('#')?;
+                let index = inner.trim().parse::<usize>().ok()?;
+                (index < self.kept.len()).then_some((index, close))
+            });
+            match slot {
+                Some((index, close)) => {
+                    output.push_str(&self.kept[index]);
+                    used[index] = true;
+                    rest = &after[close + PLACEHOLDER_CLOSE.len_utf8()..];
+                }
+                None => {
+                    output.push(PLACEHOLDER_OPEN);
+                    rest = after;
+                }
+            }
+        }
+        output.push_str(rest);
+        for (index, kept) in self.kept.iter().enumerate() {
+            if !used[index] {
+                output.push(' ');
+                output.push_str(kept);
+            }
+        }
+        output.push_str(&self.suffix);
+        output
+    }
+}
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_WIKILINKS
+}
+
+fn is_inline_event(event: &Event) -> bool {
+    match event {
+        Event::Start(tag) => matches!(
+            tag,
+            Tag::Emphasis
+                | Tag::Strong
+                | Tag::Strikethrough
+                | Tag::Superscript
+                | Tag::Subscript
+                | Tag::Link { .. }
+                | Tag::Image { .. }
+        ),
+        Event::End(tag) => matches!(
+            tag,
+            TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Superscript
+                | TagEnd::Subscript
+                | TagEnd::Link
+                | TagEnd::Image
+        ),
+        Event::Text(_)
+        | Event::Code(_)
+        | Event::InlineHtml(_)
+        | Event::InlineMath(_)
+        | Event::DisplayMath(_)
+        | Event::FootnoteReference(_)
+        | Event::SoftBreak
+        | Event::HardBreak
+        | Event::TaskListMarker(_) => true,
+        _ => false,
+    }
+}
+
+// 文字中的裸 URL 区间（相对于 text）
+fn url_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut search_from = 0;
+    while let Some(found) = ["https://", "http://", "www."]
+        .iter()
+        .filter_map(|scheme| text[search_from..].find(scheme))
+        .min()
+    {
+        let start = search_from + found;
+        let length = text[start..]
+            .find(|c: char| c.is_whitespace() || !c.is_ascii() || matches!(c, '<' | '>' | '"'))
+            .unwrap_or(text.len() - start);
+        let url =
+            text[start..start + length].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'']);
+        ranges.push(start..start + url.len());
+        search_from = start + length.max(1);
+    }
+    ranges
+}
+
+fn build_unit(body: &str, run: &[(Event, Range<usize>)]) -> Option<TextUnit> {
+    let start = run.iter().map(|(_, range)| range.start).min()?;
+    let end = run.iter().map(|(_, range)| range.end).max()?;
+
+    // 整体保留的区间：wikilink、自动链接，以及普通链接 / 图片文字之后的 ](地址) 部分
+    let mut protected: Vec<Range<usize>> = Vec::new();
+    for (event, range) in run {
+        let (link_type, image) = match event {
+            Event::Start(Tag::Link { link_type, .. }) => (link_type, false),
+            Event::Start(Tag::Image { link_type, .. }) => (link_type, true),
+            _ => continue,
+        };
+        match link_type {
+            LinkType::WikiLink { .. } | LinkType::Autolink | LinkType::Email => {
+                protected.push(range.clone())
+            }
+            _ => {
+                let label_end = run
+                    .iter()
+                    .filter(|(event, inner)| {
+                        matches!(event, Event::Text(_) | Event::Code(_))
+                            && inner.start >= range.start
+                            && inner.end <= range.end
+                    })
+                    .map(|(_, inner)| inner.end)
+                    .max()
+                    .unwrap_or(range.start + if image { 2 } else { 1 });
+                protected.push(label_end..range.end);
+            }
+        }
+    }
+    let inside_protected = |range: &Range<usize>| {
+        protected
+            .iter()
+            .any(|outer| range.start >= outer.start && range.end <= outer.end)
+    };
+
+    // 可翻译的文字与软换行；其余字节（标记、行内代码、链接地址等）都保留原样
+    let mut spans: Vec<(Range<usize>, bool)> = Vec::new();
+    for (event, range) in run {
+        match event {
+            Event::Text(_) if !inside_protected(range) => {
+                let mut cursor = range.start;
+                for url in url_ranges(&body[range.clone()]) {
+                    let url = range.start + url.start..range.start + url.end;
+                    if url.start > cursor {
+                        spans.push((cursor..url.start, false));
+                    }
+                    cursor = url.end;
+                }
+                if cursor < range.end {
+                    spans.push((cursor..range.end, false));
+                }
+            }
+            Event::SoftBreak => spans.push((range.clone(), true)),
+            _ => {}
+        }
+    }
+    spans.sort_by_key(|(range, _)| range.start);
+
+    let mut pieces: Vec<Piece> = Vec::new();
+    let push_kept = |pieces: &mut Vec<Piece>, kept: &str| {
+        if kept.is_empty() {
+            return;
+        }
+        match pieces.last_mut() {
+            Some(Piece::Kept(previous)) => previous.push_str(kept),
+            _ => pieces.push(Piece::Kept(kept.to_string())),
+        }
+    };
+    let mut cursor = start;
+    let mut after_soft_break = false;
+    for (range, soft) in spans {
+        if range.start < cursor {
+            continue;
+        }
+        let mut gap = &body[cursor..range.start];
+        // 软换行后的缩进和引用标记属于容器，合并成一行后不再需要
+        if after_soft_break {
+            gap = gap.trim_start_matches([' ', '\t', '>']);
+        }
+        push_kept(&mut pieces, gap);
+        if soft {
+            pieces.push(Piece::Space);
+        } else {
+            pieces.push(Piece::Text(body[range.clone()].to_string()));
+        }
+        after_soft_break = soft;
+        cursor = range.end;
+    }
+    let mut tail = &body[cursor..end];
+    if after_soft_break {
+        tail = tail.trim_start_matches([' ', '\t', '>']);
+    }
+    push_kept(&mut pieces, tail);
+
+    if !pieces
+        .iter()
+        .any(|piece| matches!(piece, Piece::Text(text) if text.chars().any(char::is_alphabetic)))
+    {
+        return None;
+    }
+
+    let mut prefix = match pieces.first() {
+        Some(Piece::Kept(kept)) => {
+            let kept = kept.clone();
+            pieces.remove(0);
+            kept
+        }
+        _ => String::new(),
+    };
+    let mut suffix = match pieces.last() {
+        Some(Piece::Kept(kept)) => {
+            let kept = kept.clone();
+            pieces.pop();
+            kept
+        }
+        _ => String::new(),
+    };
+
+    let mut source = String::new();
+    let mut kept = Vec::new();
+    for piece in pieces {
+        match piece {
+            Piece::Text(text) => source.push_str(&text.replace('\n', " ")),
+            Piece::Space => {
+                if !source.ends_with(' ') {
+                    source.push(' ');
+                }
+            }
+            Piece::Kept(value) => {
+                source.push_str(&format!(
+                    "{}#{}{}",
+                    PLACEHOLDER_OPEN,
+                    kept.len(),
+                    PLACEHOLDER_CLOSE
+                ));
+                kept.push(value);
+            }
+        }
+    }
+
+    // 首尾空白留在单元外，避免与相邻的代码、链接粘连
+    let trimmed = source.trim();
+    let leading = source.len() - source.trim_start().len();
+    prefix.push_str(&source[..leading]);
+    suffix.insert_str(0, &source[leading + trimmed.len()..]);
+
+    Some(TextUnit {
+        range: start..end,
+        source: trimmed.to_string(),
+        kept,
+        prefix,
+        suffix,
+    })
+}
+
+/// 提取正文中需要翻译的文本单元（按出现顺序）；代码块、HTML 块中的内容不参与翻译
+pub(crate) fn extract_units(body: &str) -> Vec<TextUnit> {
+    let mut units = Vec::new();
+    let mut run: Vec<(Event, Range<usize>)> = Vec::new();
+    let mut verbatim_depth = 0usize;
+    for (event, range) in Parser::new_ext(body, markdown_options()).into_offset_iter() {
+        if verbatim_depth == 0 && is_inline_event(&event) {
+            run.push((event, range));
+            continue;
+        }
+        if let Some(unit) = build_unit(body, &run) {
+            units.push(unit);
+        }
+        run.clear();
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::HtmlBlock | Tag::MetadataBlock(_)) => {
+                verbatim_depth += 1
+            }
+            Event::End(TagEnd::CodeBlock | TagEnd::HtmlBlock | TagEnd::MetadataBlock(_)) => {
+                verbatim_depth = verbatim_depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+    }
+    if let Some(unit) = build_unit(body, &run) {
+        units.push(unit);
+    }
+    units
+}
+
+/// 用译文替换各文本单元，单元之外的内容保持原样
+pub(crate) fn apply_translations(
+    body: &str,
+    units: &[TextUnit],
+    translations: &[String],
+) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut cursor = 0;
+    for (unit, translated) in units.iter().zip(translations) {
+        output.push_str(&body[cursor..unit.range.start]);
+        output.push_str(&unit.render(translated));
+        cursor = unit.range.end;
+    }
+    output.push_str(&body[cursor..]);
+    output
+}
+
+/// 按长度把文本分成若干批，返回每批包含的单元下标区间
+pub(crate) fn plan_chunks(sources: &[String], max_chars: usize) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (index, source) in sources.iter().enumerate() {
+        let length = source.chars().count();
+        if index > start && chars + length > max_chars {
+            chunks.push(start..index);
+            start = index;
+            chars = 0;
+        }
+        chars += length + UNIT_SEPARATOR.len();
+    }
+    if start < sources.len() {
+        chunks.push(start..sources.len());
+    }
+    chunks
+}
+
+/// 把一批的译文按非空行拆回各单元；行数对不上时返回 None
+fn split_batch(translated: &str, expected: usize) -> Option<Vec<String>> {
+    let lines: Vec<String> = translated
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    (lines.len() == expected).then_some(lines)
+}
+
+// ============= 任务状态 =============
+
+pub(crate) fn create_note_job_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS translation_note_jobs (
+             job_id TEXT PRIMARY KEY,
+             source_path TEXT NOT NULL,
+             from_lang TEXT NOT NULL,
+             to_lang TEXT NOT NULL,
+             done_chunks INTEGER NOT NULL DEFAULT 0,
+             total_chunks INTEGER NOT NULL DEFAULT 0,
+             updated_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS translation_note_chunks (
+             job_id TEXT NOT NULL,
+             chunk_hash TEXT NOT NULL,
+             translations TEXT NOT NULL,
+             PRIMARY KEY (job_id, chunk_hash)
+         );",
+    )
+}
+
+// 各部分以 NUL 结尾后拼接求哈希，避免 ("ab", "c") 与 ("a", "bc") 相同
+fn hash_parts(parts: &[&str]) -> String {
+    let mut bytes = Vec::new();
+    for part in parts {
+        bytes.extend_from_slice(part.as_bytes());
+        bytes.push(0);
+    }
+    sha256_hex(bytes)
+}
+
+// 同一笔记翻译到同一语言共用一个任务，换引擎后仍可从中断处继续
+fn job_id(source_path: &str, from: &str, to: &str) -> String {
+    hash_parts(&[source_path, from, to])[..16].to_string()
+}
+
+fn load_chunk(job_id: &str, chunk_hash: &str, expected: usize) -> Option<Vec<String>> {
+    let conn = open_plugin_store().ok()?;
+    let saved: Option<String> = conn
+        .query_row(
+            "SELECT translations FROM translation_note_chunks WHERE job_id = ?1 AND chunk_hash = ?2",
+            params![job_id, chunk_hash],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()?;
+    let translations: Vec<String> = serde_json::from_str(&saved?).ok()?;
+    (translations.len() == expected).then_some(translations)
+}
+
+fn save_progress(
+    job: &NoteTranslationProgress,
+    done: usize,
+    from: &str,
+    to: &str,
+    chunk_hash: &str,
+    translations: &[String],
+) -> Result<(), String> {
+    let conn = open_plugin_store()?;
+    let serialized =
+        serde_json::to_string(translations).map_err(|e| format!("序列化译文失败: {}", e))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO translation_note_chunks (job_id, chunk_hash, translations)
+         VALUES (?1, ?2, ?3)",
+        params![job.job_id, chunk_hash, serialized],
+    )
+    .map_err(|e| format!("保存翻译进度失败: {}", e))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO translation_note_jobs
+             (job_id, source_path, from_lang, to_lang, done_chunks, total_chunks, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            job.job_id,
+            job.source_path,
+            from,
+            to,
+            done as i64,
+            job.total as i64,
+            chrono::Utc::now().timestamp_millis()
+        ],
+    )
+    .map_err(|e| format!("保存翻译进度失败: {}", e))?;
+    Ok(())
+}
+
+fn discard_job(job_id: &str) -> Result<(), String> {
+    let conn = open_plugin_store()?;
+    conn.execute(
+        "DELETE FROM translation_note_chunks WHERE job_id = ?1",
+        params![job_id],
+    )
+    .and_then(|_| {
+        conn.execute(
+            "DELETE FROM translation_note_jobs WHERE job_id = ?1",
+            params![job_id],
+        )
+    })
+    .map_err(|e| format!("清除翻译进度失败: {}", e))?;
+    Ok(())
+}
+
+// ============= 翻译 =============
+
+async fn translate_chunk(
+    app_handle: &AppHandle,
+    config: &TranslationProvidersConfig,
+    engine: &str,
+    sources: &[String],
+    from: &str,
+    to: &str,
+) -> Result<Vec<String>, String> {
+    if sources.len() > 1 {
+        let joined = sources.join(UNIT_SEPARATOR);
+        let translated =
+            translate_with_memory(app_handle, config, engine, &joined, from, to).await?;
+        if let Some(lines) = split_batch(&translated, sources.len()) {
+            return Ok(lines);
+        }
+        warn!(
+            "⚠️ [翻译] 批量译文段落数与原文不一致，改为逐段翻译（{} 段）",
+            sources.len()
+        );
+    }
+    let mut translations = Vec::with_capacity(sources.len());
+    for source in sources {
+        let translated =
+            translate_with_memory(app_handle, config, engine, source, from, to).await?;
+        translations.push(translated.lines().collect::<Vec<_>>().join(" "));
+    }
+    Ok(translations)
+}
+
+// 原笔记必须是工作区内的 Markdown 文件
+fn resolve_note(workspace_root: &Path, file_path: &str) -> Result<(PathBuf, String), String> {
+    let path = workspace_root.join(file_path);
+    let relative = path
+        .strip_prefix(workspace_root)
+        .map_err(|_| format!("路径不在工作区内: {}", file_path))?;
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("非法路径: {}", file_path));
+    }
+    if path.extension().and_then(|e| e.to_str()) != Some("md") || !path.is_file() {
+        return Err(format!("笔记不存在: {}", file_path));
+    }
+    let relative = get_relative_path(workspace_root, &path)?;
+    Ok((path, relative))
+}
+
+/// 译文笔记路径：同目录下的 `文件名.语言.md`；已存在且不是该原文的译文时加时间戳后缀
+fn target_path(source: &Path, source_relative: &str, to: &str) -> (PathBuf, Option<FrontMatter>) {
+    let folder = source.parent().unwrap_or(Path::new(""));
+    let stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = format!("{}.{}.md", stem, to.replace('_', "-"));
+    let candidate = folder.join(&file_name);
+    if !candidate.exists() {
+        return (candidate, None);
+    }
+    let existing = std::fs::read_to_string(&candidate)
+        .ok()
+        .and_then(|content| try_parse_front_matter(&content).0)
+        .filter(|fm| {
+            fm.translated_from
+                .as_ref()
+                .is_some_and(|origin| origin.path == source_relative)
+        });
+    match existing {
+        Some(fm) => (candidate, Some(fm)),
+        None => (
+            folder.join(FileNameGenerator::resolve_conflict(folder, &file_name)),
+            None,
+        ),
+    }
+}
+
+fn finish_job(job_id: &str) {
+    if let Ok(mut jobs) = ACTIVE_JOBS.lock() {
+        jobs.remove(job_id);
+    }
+}
+
+/// 翻译整篇笔记并写为同目录下的译文笔记；进度通过 note-translation-progress 事件推送。
+/// 失败或取消时已完成的批次会保留，再次调用时从中断处继续
+#[tauri::command]
+pub async fn translate_note(
+    app_handle: AppHandle,
+    index_manager: State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: State<'_, Arc<RwLock<CacheManager>>>,
+    file_path: String,
+    from: String,
+    to: String,
+    engine: String,
+) -> Result<NoteTranslationResult, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    let workspace_root =
+        get_workspace_root(&app_handle)?.ok_or("工作区未配置，请先设置工作区根目录")?;
+    let (source_path, source_relative) = resolve_note(&workspace_root, &file_path)?;
+    let raw = std::fs::read_to_string(&source_path).map_err(|e| format!("读取笔记失败: {}", e))?;
+    let (front_matter, body) = try_parse_front_matter(&raw);
+    let source_title = front_matter
+        .as_ref()
+        .map(|fm| fm.title.clone())
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| {
+            source_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+    let summary = front_matter.as_ref().and_then(|fm| fm.summary.clone());
+
+    // 标题与摘要作为前两个单元与正文一起分批
+    let units = extract_units(&body);
+    let mut sources = vec![source_title.clone()];
+    if let Some(summary) = &summary {
+        sources.push(summary.clone());
+    }
+    let header_count = sources.len();
+    sources.extend(units.iter().map(|unit| unit.source.clone()));
+    let chunks = plan_chunks(&sources, CHUNK_MAX_CHARS);
+
+    // 未指定语言时按正文识别翻译方向，任务编号使用识别后的语言对以便续译
+    let config = read_providers_config(&app_handle);
+    let direction = resolve_direction(&config, &sources.join("\n"), &from, &to);
+    let (from, to) = (direction.from, direction.to);
+
+    let job_id = job_id(&source_relative, &from, &to);
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut jobs = ACTIVE_JOBS
+            .lock()
+            .map_err(|e| format!("获取任务状态锁失败: {}", e))?;
+        if jobs.contains_key(&job_id) {
+            return Err("该笔记正在翻译".to_string());
+        }
+        jobs.insert(job_id.clone(), Arc::clone(&cancel_flag));
+    }
+
+    let mut progress = NoteTranslationProgress {
+        job_id: job_id.clone(),
+        source_path: source_relative.clone(),
+        status: "running".to_string(),
+        done: 0,
+        total: chunks.len(),
+        resumed: 0,
+        target_path: None,
+        message: None,
+    };
+    let _ = app_handle.emit(PROGRESS_EVENT, progress.clone());
+    info!(
+        "🌐 [翻译] 开始翻译笔记 {}: {} 个文本单元，{} 批",
+        source_relative,
+        sources.len(),
+        chunks.len()
+    );
+
+    let mut translations: Vec<String> = Vec::with_capacity(sources.len());
+    for chunk in &chunks {
+        let chunk_sources = &sources[chunk.clone()];
+        let chunk_hash = hash_parts(&[&chunk_sources.join(UNIT_SEPARATOR)]);
+        if let Some(saved) = load_chunk(&job_id, &chunk_hash, chunk_sources.len()) {
+            translations.extend(saved);
+            progress.resumed += 1;
+        } else {
+            let failure = if cancel_flag.load(Ordering::SeqCst) {
+                Some(("cancelled", "翻译已取消".to_string()))
+            } else {
+                match translate_chunk(&app_handle, &config, &engine, chunk_sources, &from, &to)
+                    .await
+                {
+                    Ok(translated) => {
+                        let done = progress.done + 1;
+                        if let Err(error) =
+                            save_progress(&progress, done, &from, &to, &chunk_hash, &translated)
+                        {
+                            warn!("⚠️ [翻译] {}", error);
+                        }
+                        translations.extend(translated);
+                        None
+                    }
+                    Err(error) => Some(("failed", error)),
+                }
+            };
+            if let Some((status, message)) = failure {
+                finish_job(&job_id);
+                let message = format!(
+                    "{}（已完成 {}/{} 批，再次翻译时从中断处继续）",
+                    message, progress.done, progress.total
+                );
+                progress.status = status.to_string();
+                progress.message = Some(message.clone());
+                let _ = app_handle.emit(PROGRESS_EVENT, progress);
+                return Err(message);
+            }
+        }
+        progress.done += 1;
+        let _ = app_handle.emit(PROGRESS_EVENT, progress.clone());
+    }
+
+    let result = write_translation(
+        &workspace_root,
+        &source_path,
+        &source_relative,
+        &source_title,
+        front_matter.as_ref(),
+        &body,
+        &units,
+        &translations,
+        header_count,
+        &from,
+        &to,
+        &index_manager,
+        &cache_manager,
+    );
+    finish_job(&job_id);
+    let (target, relative_path, title) = match result {
+        Ok(written) => written,
+        Err(error) => {
+            progress.status = "failed".to_string();
+            progress.message = Some(error.clone());
+            let _ = app_handle.emit(PROGRESS_EVENT, progress);
+            return Err(error);
+        }
+    };
+    if let Err(error) = discard_job(&job_id) {
+        warn!("⚠️ [翻译] {}", error);
+    }
+
+    let target_path = target.to_string_lossy().to_string();
+    progress.status = "completed".to_string();
+    progress.target_path = Some(target_path.clone());
+    let _ = app_handle.emit(PROGRESS_EVENT, progress.clone());
+    info!(
+        "✅ [翻译] 笔记 {} 已译为 {}（复用 {} 批）",
+        source_relative, relative_path, progress.resumed
+    );
+    Ok(NoteTranslationResult {
+        job_id,
+        target_path,
+        relative_path,
+        title,
+        chunks: progress.total,
+        resumed: progress.resumed,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_translation(
+    workspace_root: &Path,
+    source_path: &Path,
+    source_relative: &str,
+    source_title: &str,
+    front_matter: Option<&FrontMatter>,
+    body: &str,
+    units: &[TextUnit],
+    translations: &[String],
+    header_count: usize,
+    from: &str,
+    to: &str,
+    index_manager: &State<'_, Arc<RwLock<Option<IndexManager>>>>,
+    cache_manager: &State<'_, Arc<RwLock<CacheManager>>>,
+) -> Result<(PathBuf, String, String), String> {
+    let title = translations
+        .first()
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| source_title.to_string());
+    let summary = (header_count > 1).then(|| translations[1].clone());
+    let content = format!(
+        "> [[{}]]\n\n{}",
+        source_title,
+        apply_translations(body, units, &translations[header_count..]).trim_start()
+    );
+
+    let (target, existing) = target_path(source_path, source_relative, to);
+    let now = chrono::Utc::now();
+    let now_timestamp = now.timestamp_millis();
+    // 重新翻译时覆盖原有译文，保留其 id 与创建时间
+    let front_matter = FrontMatter {
+        id: existing
+            .as_ref()
+            .map(|fm| fm.id.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        title: title.clone(),
+        tags: front_matter.map(|fm| fm.tags.clone()).unwrap_or_default(),
+        created: existing
+            .as_ref()
+            .map(|fm| fm.created.clone())
+            .unwrap_or_else(|| now.to_rfc3339()),
+        modified: now.to_rfc3339(),
+        fragment_type: front_matter
+            .map(|fm| fm.fragment_type.clone())
+            .unwrap_or_else(|| "note".to_string()),
+        language: front_matter.and_then(|fm| fm.language.clone()),
+        framework: front_matter.and_then(|fm| fm.framework.clone()),
+        kind: front_matter.and_then(|fm| fm.kind.clone()),
+        summary,
+        favorite: false,
+        forked_from: None,
+        chat_id: None,
+        translated_from: Some(TranslationSource {
+            path: source_relative.to_string(),
+            title: source_title.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            translated_at: now.to_rfc3339(),
+        }),
+    };
+    let full_content = format!("{}\n{}", format_frontmatter_block(&front_matter)?, content);
+    std::fs::write(&target, &full_content).map_err(|e| format!("写入译文笔记失败: {}", e))?;
+    let relative_path = get_relative_path(workspace_root, &target)?;
+
+    {
+        let mut cache = cache_manager
+            .write()
+            .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+        let created = existing
+            .as_ref()
+            .and_then(|_| {
+                cache
+                    .get_file_metadata(&relative_path)
+                    .map(|meta| meta.created)
+            })
+            .unwrap_or(now_timestamp);
+        cache.set_file_metadata(
+            relative_path.clone(),
+            FileMetadata {
+                id: front_matter.id.clone(),
+                created,
+                modified: now_timestamp,
+                size: Some(full_content.len() as u64),
+                hash: None,
+            },
+        );
+        cache.save()?;
+    }
+
+    if let Ok(manager_lock) = index_manager.read() {
+        if let Some(ref manager) = *manager_lock {
+            let cache = cache_manager
+                .read()
+                .map_err(|e| format!("获取 cache 锁失败: {}", e))?;
+            let _ = manager.update_entry(&target, workspace_root, &cache);
+        }
+    }
+    Ok((target, relative_path, title))
+}
+
+/// 取消正在进行的整篇翻译；当前批次完成后停止，已完成的批次保留
+#[tauri::command]
+pub fn cancel_note_translation(job_id: String) -> Result<bool, String> {
+    let jobs = ACTIVE_JOBS
+        .lock()
+        .map_err(|e| format!("获取任务状态锁失败: {}", e))?;
+    match jobs.get(&job_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 列出中断后可继续的整篇翻译任务
+#[tauri::command]
+pub fn list_note_translation_jobs(
+    app_handle: AppHandle,
+) -> Result<Vec<NoteTranslationJob>, String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    let conn = open_plugin_store()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT job_id, source_path, from_lang, to_lang, done_chunks, total_chunks, updated_at
+             FROM translation_note_jobs ORDER BY updated_at DESC",
+        )
+        .map_err(|e| format!("读取翻译任务失败: {}", e))?;
+    let active: Vec<String> = ACTIVE_JOBS
+        .lock()
+        .map(|jobs| jobs.keys().cloned().collect())
+        .unwrap_or_default();
+    let jobs = stmt
+        .query_map([], |row| {
+            Ok(NoteTranslationJob {
+                job_id: row.get(0)?,
+                source_path: row.get(1)?,
+                from: row.get(2)?,
+                to: row.get(3)?,
+                done: row.get::<_, i64>(4)?.max(0) as usize,
+                total: row.get::<_, i64>(5)?.max(0) as usize,
+                updated_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| format!("读取翻译任务失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取翻译任务失败: {}", e))?;
+    Ok(jobs
+        .into_iter()
+        .filter(|job| !active.contains(&job.job_id))
+        .collect())
+}
+
+/// 放弃中断的任务，删除已保存的批次
+#[tauri::command]
+pub fn discard_note_translation_job(app_handle: AppHandle, job_id: String) -> Result<(), String> {
+    crate::app_config::require_plugin_enabled(&app_handle, "translation")?;
+    discard_job(&job_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate_all(body: &str, translate: impl Fn(&str) -> String) -> String {
+        let units = extract_units(body);
+        let translations: Vec<String> = units.iter().map(|unit| translate(&unit.source)).collect();
+        apply_translations(body, &units, &translations)
+    }
+
+    #[test]
+    fn extracts_only_prose_and_protects_inline_markup() {
+        let body = "# Getting started\n\nRun `npm install` then see [the docs](https://example.com/docs) and [[Setup Guide]].\n\n```rust\nlet greeting = \"hello\";\n```\n\n- Visit https://example.com now\n- 12345\n";
+        let units = extract_units(body);
+        let sources: Vec<&str> = units.iter().map(|unit| unit.source.as_str()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                "Getting started",
+                "Run ⟦#0⟧ then see ⟦#1⟧the docs⟦#2⟧ and ⟦#3⟧.",
+                "Visit ⟦#0⟧ now",
+            ]
+        );
+
+        let translated = translate_all(body, |source| source.to_uppercase());
+        assert_eq!(
+            translated,
+            "# GETTING STARTED\n\nRUN `npm install` THEN SEE [THE DOCS](https://example.com/docs) AND [[Setup Guide]].\n\n```rust\nlet greeting = \"hello\";\n```\n\n- VISIT https://example.com NOW\n- 12345\n"
+        );
+    }
+
+    #[test]
+    fn joins_soft_breaks_inside_containers() {
+        let body = "> first line\n> second `code`\n\n| Name | Note |\n| --- | --- |\n| 42 | keep **this** |\n";
+        let translated = translate_all(body, |source| format!("<{}>", source));
+        assert_eq!(
+            translated,
+            "> <first line second> `code`\n\n| <Name> | <Note> |\n| --- | --- |\n| 42 | <keep **this>** |\n"
+        );
+    }
+
+    #[test]
+    fn restores_missing_placeholders_and_batches_units() {
+        let units = extract_units("Use `x` here and *there*.");
+        assert_eq!(units[0].source, "Use ⟦#0⟧ here and ⟦#1⟧there⟦#2⟧.");
+        assert_eq!(
+            units[0].render("在 ⟦ #1 ⟧那里⟦#2⟧使用。"),
+            "在 *那里*使用。 `x`"
+        );
+
+        let sources: Vec<String> = ["a".repeat(10), "b".repeat(10), "c".repeat(10)].to_vec();
+        assert_eq!(plan_chunks(&sources, 25), vec![0..2, 2..3]);
+        assert_eq!(
+            split_batch("A\n\nB\n", 2),
+            Some(vec!["A".to_string(), "B".to_string()])
+        );
+        assert!(split_batch("A B", 2).is_none());
+    }
+}
@@ -0,0 +1,1264 @@
+// 翻译服务提供方
+//
+// Bing、Google（网页接口）、LibreTranslate（可自建）、DeepL、任意 OpenAI 兼容聊天服务和本地 AI
+// 统一实现 TranslationProvider：应用语言代码到各服务代码的映射、支持语言的发现和翻译请求。
+// 失败按限流、认证、不支持的语言组合等分类；translate_with_fallback 先用选定的引擎，失败后按
+// 配置的回退顺序尝试其他已配置的引擎，限流或认证失败的引擎在冷却期内直接跳过。
+// 术语表对大模型引擎写入提示，对其余引擎用占位符保护（见 translation_memory）。
+
+use crate::plugins::local_ai::{strip_think, translation_prompt};
+use crate::plugins::local_ai_provider::normalize_base_url;
+use crate::plugins::translation_memory::{protect_terms, GlossaryTerm};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use log::{info, warn};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+// app.json 中保存各翻译服务配置的键
+pub const PROVIDERS_CONFIG_KEY: &str = "translation_providers";
+pub const PROVIDER_IDS: &[&str] = &[
+    "bing",
+    "google",
+    "libretranslate",
+    "deepl",
+    "openai-compatible",
+    "local-ai",
+];
+const DEFAULT_TIMEOUT_SECS: u64 = 15;
+const DEFAULT_MEMORY_TTL_DAYS: u32 = 30;
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+const AUTH_COOLDOWN: Duration = Duration::from_secs(600);
+const LANGUAGE_CACHE_TTL: Duration = Duration::from_secs(3600);
+const ERROR_DETAIL_MAX_CHARS: usize = 300;
+const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+// 应用内的语言代码（与翻译窗口的语言列表一致）
+const APP_LANGUAGES: &[(&str, &str)] = &[
+    ("zh", "简体中文"),
+    ("zh_tw", "繁體中文"),
+    ("en", "English"),
+    ("ja", "日本語"),
+    ("ko", "한국어"),
+    ("fr", "Français"),
+    ("de", "Deutsch"),
+    ("ru", "Русский"),
+    ("es", "Español"),
+    ("pt_pt", "Português (Portugal)"),
+    ("pt_br", "Português (Brasil)"),
+    ("vi", "Tiếng Việt"),
+    ("id", "Bahasa Indonesia"),
+    ("th", "ไทย"),
+    ("ar", "العربية"),
+];
+
+// 服务端语言列表（LibreTranslate /languages、DeepL /v2/languages）按请求地址缓存
+static LANGUAGE_CACHE: LazyLock<Mutex<HashMap<String, (Instant, Value)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+// 限流或认证失败的引擎在冷却结束前不再参与回退
+static COOLDOWNS: LazyLock<Mutex<HashMap<String, (Instant, TranslationErrorKind)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// ============= 配置 =============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationProvidersConfig {
+    // 选定的引擎失败后依次尝试的引擎
+    #[serde(default = "default_fallback_order")]
+    pub fallback_order: Vec<String>,
+    #[serde(default = "default_true")]
+    pub fallback_enabled: bool,
+    #[serde(default = "default_timeout_secs")]
+    pub request_timeout_secs: u64,
+    #[serde(default)]
+    pub libretranslate: LibreTranslateConfig,
+    #[serde(default)]
+    pub deepl: DeepLConfig,
+    #[serde(default)]
+    pub openai_compatible: OpenAiCompatibleTranslationConfig,
+    // 相同原文、语言对和引擎在有效期内复用翻译记忆中的译文
+    #[serde(default = "default_true")]
+    pub memory_enabled: bool,
+    #[serde(default = "default_memory_ttl_days")]
+    pub memory_ttl_days: u32,
+    // 未指定语言时按识别结果在这两种语言间互译：原文是首选语言则译为次选语言，否则译为首选语言
+    #[serde(default = "default_auto_primary_language")]
+    pub auto_primary_language: String,
+    #[serde(default = "default_auto_secondary_language")]
+    pub auto_secondary_language: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibreTranslateConfig {
+    // 如 http://127.0.0.1:5000，未填写时不参与翻译
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLConfig {
+    // 以 :fx 结尾的免费版密钥使用 api-free.deepl.com
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiCompatibleTranslationConfig {
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+fn default_fallback_order() -> Vec<String> {
+    // 本地 AI 可能需要先加载模型，默认不参与回退
+    [
+        "bing",
+        "google",
+        "deepl",
+        "libretranslate",
+        "openai-compatible",
+    ]
+    .iter()
+    .map(|id| id.to_string())
+    .collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+fn default_memory_ttl_days() -> u32 {
+    DEFAULT_MEMORY_TTL_DAYS
+}
+
+fn default_auto_primary_language() -> String {
+    "zh".to_string()
+}
+
+fn default_auto_secondary_language() -> String {
+    "en".to_string()
+}
+
+impl Default for TranslationProvidersConfig {
+    fn default() -> Self {
+        Self {
+            fallback_order: default_fallback_order(),
+            fallback_enabled: true,
+            request_timeout_secs: DEFAULT_TIMEOUT_SECS,
+            libretranslate: LibreTranslateConfig::default(),
+            deepl: DeepLConfig::default(),
+            openai_compatible: OpenAiCompatibleTranslationConfig::default(),
+            memory_enabled: true,
+            memory_ttl_days: DEFAULT_MEMORY_TTL_DAYS,
+            auto_primary_language: default_auto_primary_language(),
+            auto_secondary_language: default_auto_secondary_language(),
+        }
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+impl TranslationProvidersConfig {
+    /// 去掉空白字段、规范化服务地址，回退顺序只保留已知且不重复的引擎
+    pub fn normalized(mut self) -> Self {
+        let mut order: Vec<String> = Vec::new();
+        for id in &self.fallback_order {
+            let id = id.trim();
+            if PROVIDER_IDS.contains(&id) && !order.iter().any(|existing| existing == id) {
+                order.push(id.to_string());
+            }
+        }
+        self.fallback_order = order;
+        self.request_timeout_secs = self.request_timeout_secs.clamp(3, 120);
+        self.memory_ttl_days = self.memory_ttl_days.clamp(1, 365);
+        if !is_app_language(&self.auto_primary_language) {
+            self.auto_primary_language = default_auto_primary_language();
+        }
+        if !is_app_language(&self.auto_secondary_language)
+            || self.auto_secondary_language == self.auto_primary_language
+        {
+            self.auto_secondary_language = if self.auto_primary_language == "en" {
+                default_auto_primary_language()
+            } else {
+                default_auto_secondary_language()
+            };
+        }
+        self.libretranslate.base_url =
+            non_empty(&self.libretranslate.base_url).map(|url| normalize_base_url(&url));
+        self.libretranslate.api_key = non_empty(&self.libretranslate.api_key);
+        self.deepl.api_key = non_empty(&self.deepl.api_key);
+        self.openai_compatible.base_url =
+            non_empty(&self.openai_compatible.base_url).map(|url| normalize_base_url(&url));
+        self.openai_compatible.api_key = non_empty(&self.openai_compatible.api_key);
+        self.openai_compatible.model = non_empty(&self.openai_compatible.model);
+        self
+    }
+}
+
+fn is_app_language(code: &str) -> bool {
+    APP_LANGUAGES.iter().any(|(item, _)| *item == code)
+}
+
+pub fn read_providers_config(app_handle: &AppHandle) -> TranslationProvidersConfig {
+    crate::json_config::get_app_config_value::<TranslationProvidersConfig>(
+        app_handle,
+        PROVIDERS_CONFIG_KEY,
+    )
+    .unwrap_or_default()
+}
+
+// ============= 错误分类 =============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranslationErrorKind {
+    // 429 或配额用尽
+    RateLimited,
+    // 密钥缺失、无效或无权限
+    Auth,
+    // 服务不支持该语言组合
+    UnsupportedPair,
+    // 未填写服务地址或密钥
+    NotConfigured,
+    // 网络错误、超时或服务端 5xx
+    Unavailable,
+    // 返回内容无法解析或为空
+    InvalidResponse,
+}
+
+impl TranslationErrorKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::RateLimited => "请求过于频繁",
+            Self::Auth => "认证失败",
+            Self::UnsupportedPair => "不支持的语言组合",
+            Self::NotConfigured => "未配置",
+            Self::Unavailable => "服务不可用",
+            Self::InvalidResponse => "返回结果无效",
+        }
+    }
+
+    fn cooldown(self) -> Option<Duration> {
+        match self {
+            Self::RateLimited => Some(RATE_LIMIT_COOLDOWN),
+            Self::Auth => Some(AUTH_COOLDOWN),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TranslationError {
+    pub kind: TranslationErrorKind,
+    pub message: String,
+}
+
+impl TranslationError {
+    fn new(kind: TranslationErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::new(TranslationErrorKind::InvalidResponse, message)
+    }
+
+    fn unsupported(from: &str, to: &str) -> Self {
+        Self::new(
+            TranslationErrorKind::UnsupportedPair,
+            format!("{} -> {}", from, to),
+        )
+    }
+}
+
+/// 按 HTTP 状态码与错误正文分类
+pub(crate) fn classify_status(status: u16, body: &str) -> TranslationErrorKind {
+    let body = body.to_lowercase();
+    match status {
+        // DeepL 用 456 表示额度用尽
+        429 | 456 => TranslationErrorKind::RateLimited,
+        401 | 403 => TranslationErrorKind::Auth,
+        400 | 404 | 422
+            if body.contains("not supported")
+                || body.contains("unsupported")
+                || body.contains("target_lang")
+                || body.contains("source_lang") =>
+        {
+            TranslationErrorKind::UnsupportedPair
+        }
+        500..=599 => TranslationErrorKind::Unavailable,
+        _ => TranslationErrorKind::InvalidResponse,
+    }
+}
+
+async fn error_from_response(response: Response) -> TranslationError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let detail: String = body.trim().chars().take(ERROR_DETAIL_MAX_CHARS).collect();
+    TranslationError::new(
+        classify_status(status.as_u16(), &body),
+        format!("状态码: {}，详情: {}", status, detail),
+    )
+}
+
+fn request_error(error: reqwest::Error) -> TranslationError {
+    let message = if error.is_timeout() {
+        format!("请求超时（timeout）: {}", error)
+    } else {
+        format!("网络请求失败（network）: {}", error)
+    };
+    TranslationError::new(TranslationErrorKind::Unavailable, message)
+}
+
+async fn send_json(request: RequestBuilder) -> Result<Value, TranslationError> {
+    let response = request.send().await.map_err(request_error)?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    response
+        .json::<Value>()
+        .await
+        .map_err(|error| TranslationError::invalid(format!("解析响应失败: {}", error)))
+}
+
+// 语言列表变化很少，缓存一小时
+async fn cached_languages(key: String, request: RequestBuilder) -> Result<Value, TranslationError> {
+    if let Some(value) = LANGUAGE_CACHE.lock().ok().and_then(|cache| {
+        cache
+            .get(&key)
+            .filter(|(fetched, _)| fetched.elapsed() < LANGUAGE_CACHE_TTL)
+            .map(|(_, value)| value.clone())
+    }) {
+        return Ok(value);
+    }
+    let value = send_json(request).await?;
+    if let Ok(mut cache) = LANGUAGE_CACHE.lock() {
+        cache.insert(key, (Instant::now(), value.clone()));
+    }
+    Ok(value)
+}
+
+// ============= 提供方 =============
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationLanguage {
+    // 应用内的语言代码
+    pub code: String,
+    pub name: String,
+    // 可作为源语言 / 目标语言
+    pub source: bool,
+    pub target: bool,
+}
+
+fn all_languages() -> Vec<TranslationLanguage> {
+    APP_LANGUAGES
+        .iter()
+        .map(|(code, name)| TranslationLanguage {
+            code: code.to_string(),
+            name: name.to_string(),
+            source: true,
+            target: true,
+        })
+        .collect()
+}
+
+pub(crate) trait TranslationProvider: Send + Sync {
+    fn id(&self) -> &'static str;
+
+    fn label(&self) -> &'static str;
+
+    /// 是否已填写必需的地址或密钥
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    /// 支持的语言（应用语言代码）；默认全部应用语言均可互译
+    fn languages(&self) -> BoxFuture<'_, Result<Vec<TranslationLanguage>, TranslationError>> {
+        async { Ok(all_languages()) }.boxed()
+    }
+
+    /// 能否按提示中的术语表翻译；否则由调用方用占位符保护术语
+    fn follows_glossary(&self) -> bool {
+        false
+    }
+
+    /// glossary 仅在 follows_glossary 为 true 时传入原文命中的术语
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>>;
+}
+
+struct Bing {
+    client: Client,
+}
+
+fn bing_code(code: &str) -> &str {
+    match code {
+        "auto" => "",
+        "zh" => "zh-Hans",
+        "zh_tw" => "zh-Hant",
+        "pt_pt" => "pt-pt",
+        "pt_br" => "pt",
+        _ => code,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BingTranslation {
+    translations: Vec<BingTranslationText>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BingTranslationText {
+    text: String,
+}
+
+impl TranslationProvider for Bing {
+    fn id(&self) -> &'static str {
+        "bing"
+    }
+
+    fn label(&self) -> &'static str {
+        "Bing"
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        _glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>> {
+        async move {
+            // Bing 不能自动选择目标语言
+            if to == "auto" {
+                return Err(TranslationError::unsupported(from, to));
+            }
+            let token_response = self
+                .client
+                .get("https://edge.microsoft.com/translate/auth")
+                .header("User-Agent", BROWSER_USER_AGENT)
+                .send()
+                .await
+                .map_err(request_error)?;
+            if !token_response.status().is_success() {
+                return Err(error_from_response(token_response).await);
+            }
+            let token = token_response.text().await.map_err(request_error)?;
+            if token.trim().is_empty() {
+                return Err(TranslationError::new(
+                    TranslationErrorKind::Auth,
+                    "获取Bing令牌为空",
+                ));
+            }
+
+            let url = format!(
+                "https://api.cognitive.microsofttranslator.com/translate?api-version=3.0&from={}&to={}",
+                bing_code(from),
+                bing_code(to)
+            );
+            let response = self
+                .client
+                .post(url)
+                .header("Authorization", format!("Bearer {}", token.trim()))
+                .json(&json!([{ "text": text }]))
+                .send()
+                .await
+                .map_err(request_error)?;
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
+            let result: Vec<BingTranslation> = response
+                .json()
+                .await
+                .map_err(|e| TranslationError::invalid(format!("解析Bing翻译结果失败: {}", e)))?;
+            result
+                .first()
+                .and_then(|first| first.translations.first())
+                .map(|translation| translation.text.trim().to_string())
+                .ok_or_else(|| TranslationError::invalid("Bing翻译结果解析错误"))
+        }
+        .boxed()
+    }
+}
+
+struct Google {
+    client: Client,
+}
+
+fn google_code(code: &str) -> &str {
+    match code {
+        "zh" => "zh-CN",
+        "zh_tw" => "zh-TW",
+        "pt_pt" => "pt-PT",
+        "pt_br" => "pt-BR",
+        _ => code,
+    }
+}
+
+impl TranslationProvider for Google {
+    fn id(&self) -> &'static str {
+        "google"
+    }
+
+    fn label(&self) -> &'static str {
+        "Google"
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        _glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>> {
+        async move {
+            let (from_code, to_code) = (google_code(from), google_code(to));
+            let result = send_json(
+                self.client
+                    .get("https://translate.google.com/translate_a/single")
+                    .query(&[
+                        ("client", "gtx"),
+                        ("sl", from_code),
+                        ("tl", to_code),
+                        ("hl", to_code),
+                        ("dt", "t"),
+                        ("ie", "UTF-8"),
+                        ("oe", "UTF-8"),
+                        ("otf", "1"),
+                        ("q", text),
+                    ])
+                    .header("User-Agent", BROWSER_USER_AGENT),
+            )
+            .await?;
+
+            let translated: String = result
+                .get(0)
+                .and_then(Value::as_array)
+                .map(|segments| {
+                    segments
+                        .iter()
+                        .filter_map(|segment| segment.get(0).and_then(Value::as_str))
+                        .collect()
+                })
+                .unwrap_or_default();
+            if translated.trim().is_empty() {
+                return Err(TranslationError::invalid("Google翻译结果解析错误"));
+            }
+            Ok(translated.trim().to_string())
+        }
+        .boxed()
+    }
+}
+
+pub(crate) struct LibreTranslate {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+// 不同版本的 LibreTranslate 对中文和葡萄牙语使用不同代码，按服务端列表选用第一个存在的
+fn libre_candidates(code: &str) -> Vec<&str> {
+    match code {
+        "zh" => vec!["zh-Hans", "zh"],
+        "zh_tw" => vec!["zh-Hant", "zt"],
+        "pt_pt" => vec!["pt"],
+        "pt_br" => vec!["pt-BR", "pb", "pt"],
+        _ => vec![code],
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LibreLanguage {
+    code: String,
+    #[serde(default)]
+    targets: Vec<String>,
+}
+
+fn resolve_libre_code(code: &str, languages: &[LibreLanguage]) -> Option<String> {
+    libre_candidates(code)
+        .into_iter()
+        .find(|candidate| {
+            languages
+                .iter()
+                .any(|language| language.code.eq_ignore_ascii_case(candidate))
+        })
+        .map(str::to_string)
+}
+
+impl LibreTranslate {
+    async fn server_languages(&self) -> Result<Vec<LibreLanguage>, TranslationError> {
+        let url = format!("{}/languages", self.base_url);
+        let value = cached_languages(url.clone(), self.client.get(&url)).await?;
+        serde_json::from_value(value)
+            .map_err(|e| TranslationError::invalid(format!("解析语言列表失败: {}", e)))
+    }
+}
+
+impl TranslationProvider for LibreTranslate {
+    fn id(&self) -> &'static str {
+        "libretranslate"
+    }
+
+    fn label(&self) -> &'static str {
+        "LibreTranslate"
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.base_url.is_empty()
+    }
+
+    fn follows_glossary(&self) -> bool {
+        true
+    }
+
+    fn languages(&self) -> BoxFuture<'_, Result<Vec<TranslationLanguage>, TranslationError>> {
+        async move {
+            let languages = self.server_languages().await?;
+            Ok(APP_LANGUAGES
+                .iter()
+                .filter_map(|(code, name)| {
+                    let native = resolve_libre_code(code, &languages)?;
+                    let target = languages
+                        .iter()
+                        .any(|language| language.targets.contains(&native));
+                    Some(TranslationLanguage {
+                        code: code.to_string(),
+                        name: name.to_string(),
+                        source: true,
+                        target,
+                    })
+                })
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        _glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>> {
+        async move {
+            let languages = self.server_languages().await?;
+            let target = resolve_libre_code(to, &languages)
+                .ok_or_else(|| TranslationError::unsupported(from, to))?;
+            let source = if from == "auto" {
+                "auto".to_string()
+            } else {
+                let source = resolve_libre_code(from, &languages)
+                    .ok_or_else(|| TranslationError::unsupported(from, to))?;
+                let supported = languages
+                    .iter()
+                    .find(|language| language.code == source)
+                    .is_some_and(|language| {
+                        language.targets.is_empty() || language.targets.contains(&target)
+                    });
+                if !supported {
+                    return Err(TranslationError::unsupported(from, to));
+                }
+                source
+            };
+
+            let mut body = json!({
+                "q": text,
+                "source": source,
+                "target": target,
+                "format": "text",
+            });
+            if let Some(api_key) = &self.api_key {
+                body["api_key"] = json!(api_key);
+            }
+            let result = send_json(
+                self.client
+                    .post(format!("{}/translate", self.base_url))
+                    .json(&body),
+            )
+            .await?;
+            result
+                .get("translatedText")
+                .and_then(Value::as_str)
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+                .ok_or_else(|| TranslationError::invalid("LibreTranslate 返回结果为空"))
+        }
+        .boxed()
+    }
+}
+
+struct DeepL {
+    client: Client,
+    api_key: String,
+}
+
+fn deepl_source_code(code: &str) -> Option<String> {
+    match code {
+        "auto" => None,
+        "zh" | "zh_tw" => Some("ZH".to_string()),
+        "pt_pt" | "pt_br" => Some("PT".to_string()),
+        _ => Some(code.to_uppercase()),
+    }
+}
+
+fn deepl_target_code(code: &str) -> String {
+    match code {
+        "zh" => "ZH-HANS".to_string(),
+        "zh_tw" => "ZH-HANT".to_string(),
+        "en" => "EN-US".to_string(),
+        "pt_pt" => "PT-PT".to_string(),
+        "pt_br" => "PT-BR".to_string(),
+        _ => code.to_uppercase(),
+    }
+}
+
+impl DeepL {
+    fn endpoint(&self, path: &str) -> String {
+        let host = if self.api_key.ends_with(":fx") {
+            "https://api-free.deepl.com"
+        } else {
+            "https://api.deepl.com"
+        };
+        format!("{}{}", host, path)
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+    }
+
+    async fn language_codes(&self, kind: &str) -> Result<Vec<String>, TranslationError> {
+        let url = self.endpoint(&format!("/v2/languages?type={}", kind));
+        let value = cached_languages(url.clone(), self.authorized(self.client.get(&url))).await?;
+        Ok(value
+            .as_array()
+            .map(|languages| {
+                languages
+                    .iter()
+                    .filter_map(|language| language.get("language").and_then(Value::as_str))
+                    .map(str::to_uppercase)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+impl TranslationProvider for DeepL {
+    fn id(&self) -> &'static str {
+        "deepl"
+    }
+
+    fn label(&self) -> &'static str {
+        "DeepL"
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn languages(&self) -> BoxFuture<'_, Result<Vec<TranslationLanguage>, TranslationError>> {
+        async move {
+            let sources = self.language_codes("source").await?;
+            let targets = self.language_codes("target").await?;
+            Ok(APP_LANGUAGES
+                .iter()
+                .map(|(code, name)| TranslationLanguage {
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    source: deepl_source_code(code).is_some_and(|code| sources.contains(&code)),
+                    target: targets.contains(&deepl_target_code(code)),
+                })
+                .filter(|language| language.source || language.target)
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        _glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>> {
+        async move {
+            if to == "auto" {
+                return Err(TranslationError::unsupported(from, to));
+            }
+            let mut body = json!({
+                "text": [text],
+                "target_lang": deepl_target_code(to),
+            });
+            if let Some(source) = deepl_source_code(from) {
+                body["source_lang"] = json!(source);
+            }
+            let result = send_json(
+                self.authorized(self.client.post(self.endpoint("/v2/translate")))
+                    .json(&body),
+            )
+            .await?;
+            result
+                .get("translations")
+                .and_then(Value::as_array)
+                .and_then(|translations| translations.first())
+                .and_then(|translation| translation.get("text"))
+                .and_then(Value::as_str)
+                .map(|text| text.trim().to_string())
+                .ok_or_else(|| TranslationError::invalid("DeepL 翻译结果解析错误"))
+        }
+        .boxed()
+    }
+}
+
+struct OpenAiCompatible {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl TranslationProvider for OpenAiCompatible {
+    fn id(&self) -> &'static str {
+        "openai-compatible"
+    }
+
+    fn label(&self) -> &'static str {
+        "OpenAI 兼容服务"
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.base_url.is_empty()
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>> {
+        async move {
+            let mut request = self
+                .client
+                .post(format!("{}/v1/chat/completions", self.base_url))
+                .json(&json!({
+                    "model": self.model,
+                    "temperature": 0.2,
+                    "stream": false,
+                    "messages": [
+                        { "role": "system", "content": translation_prompt(from, to, glossary) },
+                        { "role": "user", "content": text },
+                    ],
+                }));
+            if let Some(api_key) = &self.api_key {
+                request = request.bearer_auth(api_key);
+            }
+            let result = send_json(request).await?;
+            result
+                .get("choices")
+                .and_then(Value::as_array)
+                .and_then(|choices| choices.first())
+                .and_then(|choice| choice.pointer("/message/content"))
+                .and_then(Value::as_str)
+                .map(strip_think)
+                .filter(|text| !text.is_empty())
+                .ok_or_else(|| TranslationError::invalid("模型未返回译文"))
+        }
+        .boxed()
+    }
+}
+
+struct LocalAi {
+    app_handle: AppHandle,
+}
+
+impl TranslationProvider for LocalAi {
+    fn id(&self) -> &'static str {
+        "local-ai"
+    }
+
+    fn label(&self) -> &'static str {
+        "本地 AI"
+    }
+
+    fn is_configured(&self) -> bool {
+        crate::app_config::require_plugin_enabled(&self.app_handle, "local-ai").is_ok()
+    }
+
+    fn follows_glossary(&self) -> bool {
+        true
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        from: &'a str,
+        to: &'a str,
+        glossary: &'a [GlossaryTerm],
+    ) -> BoxFuture<'a, Result<String, TranslationError>> {
+        async move {
+            crate::plugins::local_ai::translate_text(
+                self.app_handle.clone(),
+                text.to_string(),
+                from.to_string(),
+                to.to_string(),
+                glossary,
+            )
+            .await
+            .map(|text| strip_think(&text))
+            .map_err(|error| TranslationError::new(TranslationErrorKind::Unavailable, error))
+        }
+        .boxed()
+    }
+}
+
+// ============= 注册表 =============
+
+fn http_client(config: &TranslationProvidersConfig) -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .build()
+        .unwrap_or_default()
+}
+
+/// 按 id 创建翻译提供方；未知 id 返回 None
+pub(crate) fn provider_for(
+    app_handle: &AppHandle,
+    config: &TranslationProvidersConfig,
+    id: &str,
+) -> Option<Box<dyn TranslationProvider>> {
+    let client = http_client(config);
+    let provider: Box<dyn TranslationProvider> = match id {
+        "bing" => Box::new(Bing { client }),
+        "google" => Box::new(Google { client }),
+        "libretranslate" => Box::new(LibreTranslate {
+            client,
+            base_url: config.libretranslate.base_url.clone().unwrap_or_default(),
+            api_key: config.libretranslate.api_key.clone(),
+        }),
+        "deepl" => Box::new(DeepL {
+            client,
+            api_key: config.deepl.api_key.clone().unwrap_or_default(),
+        }),
+        "openai-compatible" => Box::new(OpenAiCompatible {
+            client,
+            base_url: config
+                .openai_compatible
+                .base_url
+                .clone()
+                .unwrap_or_default(),
+            api_key: config.openai_compatible.api_key.clone(),
+            model: config.openai_compatible.model.clone().unwrap_or_default(),
+        }),
+        "local-ai" => Box::new(LocalAi {
+            app_handle: app_handle.clone(),
+        }),
+        _ => return None,
+    };
+    Some(provider)
+}
+
+fn cooling_down(id: &str) -> Option<TranslationErrorKind> {
+    let mut cooldowns = COOLDOWNS.lock().ok()?;
+    match cooldowns.get(id) {
+        Some((until, kind)) if Instant::now() < *until => Some(*kind),
+        Some(_) => {
+            cooldowns.remove(id);
+            None
+        }
+        None => None,
+    }
+}
+
+fn record_failure(id: &str, kind: TranslationErrorKind) {
+    if let (Some(cooldown), Ok(mut cooldowns)) = (kind.cooldown(), COOLDOWNS.lock()) {
+        cooldowns.insert(id.to_string(), (Instant::now() + cooldown, kind));
+    }
+}
+
+/// 配置变化后清除冷却状态与语言列表缓存
+pub fn reset_provider_state() {
+    if let Ok(mut cooldowns) = COOLDOWNS.lock() {
+        cooldowns.clear();
+    }
+    if let Ok(mut cache) = LANGUAGE_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationProviderInfo {
+    pub id: String,
+    pub label: String,
+    pub configured: bool,
+    // 限流或认证失败后处于冷却期
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused: Option<TranslationErrorKind>,
+}
+
+pub fn list_providers(
+    app_handle: &AppHandle,
+    config: &TranslationProvidersConfig,
+) -> Vec<TranslationProviderInfo> {
+    PROVIDER_IDS
+        .iter()
+        .filter_map(|id| provider_for(app_handle, config, id))
+        .map(|provider| TranslationProviderInfo {
+            id: provider.id().to_string(),
+            label: provider.label().to_string(),
+            configured: provider.is_configured(),
+            paused: cooling_down(provider.id()),
+        })
+        .collect()
+}
+
+fn describe(label: &str, error: &TranslationError) -> String {
+    format!(
+        "{}翻译失败（{}）: {}",
+        label,
+        error.kind.label(),
+        error.message
+    )
+}
+
+pub async fn provider_languages(
+    app_handle: &AppHandle,
+    config: &TranslationProvidersConfig,
+    engine: &str,
+) -> Result<Vec<TranslationLanguage>, String> {
+    let provider = provider_for(app_handle, config, engine)
+        .ok_or_else(|| format!("不支持的翻译引擎: {}", engine))?;
+    if !provider.is_configured() {
+        return Err(format!("{} 未配置", provider.label()));
+    }
+    provider
+        .languages()
+        .await
+        .map_err(|error| describe(provider.label(), &error))
+}
+
+/// 先用选定的引擎翻译，失败后按回退顺序尝试其他已配置且不在冷却期的引擎；
+/// 返回译文与实际完成翻译的引擎
+pub async fn translate_with_fallback(
+    app_handle: &AppHandle,
+    config: &TranslationProvidersConfig,
+    engine: &str,
+    text: &str,
+    from: &str,
+    to: &str,
+    glossary: &[GlossaryTerm],
+) -> Result<(String, &'static str), String> {
+    let primary = provider_for(app_handle, config, engine)
+        .ok_or_else(|| format!("不支持的翻译引擎: {}", engine))?;
+    let mut providers = vec![primary];
+    if config.fallback_enabled {
+        providers.extend(
+            config
+                .fallback_order
+                .iter()
+                .filter(|id| id.as_str() != engine)
+                .filter_map(|id| provider_for(app_handle, config, id))
+                .filter(|provider| provider.is_configured()),
+        );
+    }
+
+    let mut errors = Vec::new();
+    for provider in providers {
+        if !provider.is_configured() {
+            errors.push(describe(
+                provider.label(),
+                &TranslationError::new(TranslationErrorKind::NotConfigured, "请先在翻译设置中填写"),
+            ));
+            continue;
+        }
+        if let Some(kind) = cooling_down(provider.id()) {
+            errors.push(describe(
+                provider.label(),
+                &TranslationError::new(kind, "暂时跳过，稍后自动重试"),
+            ));
+            continue;
+        }
+        let result = if provider.follows_glossary() {
+            provider.translate(text, from, to, glossary).await
+        } else {
+            let protected = protect_terms(text, glossary);
+            provider
+                .translate(&protected.text, from, to, &[])
+                .await
+                .map(|translated| protected.restore(&translated))
+        };
+        match result {
+            Ok(translated) => {
+                if provider.id() != engine {
+                    info!(
+                        "🔁 [翻译] {} 不可用，已改用 {} 完成翻译",
+                        engine,
+                        provider.label()
+                    );
+                }
+                return Ok((translated, provider.id()));
+            }
+            Err(error) => {
+                warn!("⚠️ [翻译] {}", describe(provider.label(), &error));
+                record_failure(provider.id(), error.kind);
+                errors.push(describe(provider.label(), &error));
+            }
+        }
+    }
+    Err(errors.join("；"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_http::{spawn_stub_server, StubResponse};
+
+    // 最小的 LibreTranslate 桩服务：/languages 返回固定列表，/translate 按请求体返回译文或 429
+    fn spawn_libretranslate_stub() -> String {
+        spawn_stub_server(|request| {
+            if request.method == "GET" && request.path.starts_with("/languages") {
+                return StubResponse::json(
+                    200,
+                    &json!([
+                        { "code": "en", "name": "English", "targets": ["en", "zh-Hans"] },
+                        { "code": "zh-Hans", "name": "Chinese", "targets": ["en"] },
+                    ]),
+                );
+            }
+            let body = request.json();
+            if body["q"] == "busy" {
+                return StubResponse::json(429, &json!({ "error": "Slowdown" }));
+            }
+            StubResponse::json(
+                200,
+                &json!({
+                    "translatedText": format!(
+                        "{}:{}>{}",
+                        body["q"].as_str().unwrap(),
+                        body["source"].as_str().unwrap(),
+                        body["target"].as_str().unwrap()
+                    )
+                }),
+            )
+        })
+    }
+
+    #[test]
+    fn classifies_http_failures() {
+        assert_eq!(classify_status(429, ""), TranslationErrorKind::RateLimited);
+        assert_eq!(classify_status(456, ""), TranslationErrorKind::RateLimited);
+        assert_eq!(
+            classify_status(403, "Invalid API key"),
+            TranslationErrorKind::Auth
+        );
+        assert_eq!(
+            classify_status(
+                400,
+                "{\"message\":\"Value for 'target_lang' not supported.\"}"
+            ),
+            TranslationErrorKind::UnsupportedPair
+        );
+        assert_eq!(classify_status(503, ""), TranslationErrorKind::Unavailable);
+        assert_eq!(deepl_target_code("pt_br"), "PT-BR");
+        assert_eq!(deepl_source_code("zh_tw").as_deref(), Some("ZH"));
+        assert_eq!(deepl_source_code("auto"), None);
+    }
+
+    #[test]
+    fn libretranslate_discovers_languages_and_translates() {
+        let provider = LibreTranslate {
+            client: Client::new(),
+            base_url: spawn_libretranslate_stub(),
+            api_key: None,
+        };
+        tauri::async_runtime::block_on(async {
+            let languages = provider.languages().await.unwrap();
+            let codes: Vec<(&str, bool, bool)> = languages
+                .iter()
+                .map(|language| (language.code.as_str(), language.source, language.target))
+                .collect();
+            assert_eq!(codes, vec![("zh", true, true), ("en", true, true)]);
+
+            assert_eq!(
+                provider.translate("hello", "en", "zh", &[]).await.unwrap(),
+                "hello:en>zh-Hans"
+            );
+            assert_eq!(
+                provider.translate("hi", "auto", "en", &[]).await.unwrap(),
+                "hi:auto>en"
+            );
+            let unsupported = provider
+                .translate("hello", "en", "ja", &[])
+                .await
+                .unwrap_err();
+            assert_eq!(unsupported.kind, TranslationErrorKind::UnsupportedPair);
+            let limited = provider
+                .translate("busy", "en", "zh", &[])
+                .await
+                .unwrap_err();
+            assert_eq!(limited.kind, TranslationErrorKind::RateLimited);
+        });
+    }
+
+    #[test]
+    fn normalizes_config() {
+        let config = TranslationProvidersConfig {
+            fallback_order: vec![
+                "google".to_string(),
+                "unknown".to_string(),
+                " google ".to_string(),
+                "deepl".to_string(),
+            ],
+            request_timeout_secs: 0,
+            libretranslate: LibreTranslateConfig {
+                base_url: Some(" http://localhost:5000/ ".to_string()),
+                api_key: Some("  ".to_string()),
+            },
+            auto_primary_language: "en".to_string(),
+            auto_secondary_language: "en".to_string(),
+            ..TranslationProvidersConfig::default()
+        }
+        .normalized();
+        assert_eq!(config.fallback_order, vec!["google", "deepl"]);
+        assert_eq!(config.request_timeout_secs, 3);
+        assert_eq!(
+            config.libretranslate.base_url.as_deref(),
+            Some("http://localhost:5000")
+        );
+        assert_eq!(config.libretranslate.api_key, None);
+        assert_eq!(config.auto_primary_language, "en");
+        assert_eq!(config.auto_secondary_language, "zh");
+    }
+}
@@ -0,0 +1,448 @@
+// 端到端加密同步
+//
+// 笔记和附件在本机加密后才写入 Git 镜像：文件名换成用 HMAC-SHA256 计算的对象名（同一路径总是
+// 得到同一个对象名），内容连同原路径一起用 AES-256-GCM 加密，同步清单也按同样方式加密，托管服务
+// 只能看到不透明的对象。密钥由口令经 PBKDF2-HMAC-SHA256 派生，口令本身不保存；盐和迭代次数
+// 以明文写在远端 .snippets-crypt/key.json，其他设备输入同一口令即可得到相同密钥。派生出的密钥
+// 保存在本机数据目录。冲突检测仍由 file_sync 的清单三方比较完成，与加密层无关。
+
+use super::file_sync::RemoteStore;
+use crate::git_common::{decode_base64, decrypt_data, encode_base64, encrypt_data};
+use crate::text_utils::sha256_hex;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// 远端保存密钥参数的位置（明文，不含任何密钥材料）
+pub(crate) const KEY_INFO_PATH: &str = ".snippets-crypt/key.json";
+const KEY_INFO_VERSION: u32 = 1;
+const KDF_NAME: &str = "pbkdf2-sha256";
+const KDF_ITERATIONS: u32 = 600_000;
+// 远端参数可被篡改：次数过少会削弱口令强度，过多会让解锁长时间卡住
+const MIN_KDF_ITERATIONS: u32 = 100_000;
+const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+// 用派生密钥加密的固定内容，用于在不解密任何笔记的情况下校验口令
+const KEY_CHECK: &[u8] = b"snippets-code encrypted sync";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+const OBJECTS_DIR: &str = "objects";
+
+/// 远端的密钥参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyInfo {
+    pub version: u32,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub check: String,
+}
+
+impl KeyInfo {
+    /// 用新的随机盐派生密钥
+    pub(crate) fn create(passphrase: &str) -> Result<(Self, SyncKey), String> {
+        Self::create_with_iterations(passphrase, KDF_ITERATIONS)
+    }
+
+    /// 测试用允许范围内最少的迭代次数，避免每次派生都耗时数秒
+    #[cfg(test)]
+    pub(crate) fn create_for_test(passphrase: &str) -> Result<(Self, SyncKey), String> {
+        Self::create_with_iterations(passphrase, MIN_KDF_ITERATIONS)
+    }
+
+    fn create_with_iterations(
+        passphrase: &str,
+        iterations: u32,
+    ) -> Result<(Self, SyncKey), String> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+            return Err(format!("加密口令至少需要 {} 个字符", MIN_PASSPHRASE_CHARS));
+        }
+        let salt: Vec<u8> = uuid::Uuid::new_v4()
+            .as_bytes()
+            .iter()
+            .chain(uuid::Uuid::new_v4().as_bytes())
+            .copied()
+            .collect();
+        let key = SyncKey::derive(passphrase, &salt, iterations);
+        let info = Self {
+            version: KEY_INFO_VERSION,
+            kdf: KDF_NAME.to_string(),
+            iterations,
+            salt: encode_base64(&salt),
+            check: encode_base64(&encrypt_data(KEY_CHECK, &key.content_token)?),
+        };
+        Ok((info, key))
+    }
+
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let info: Self =
+            serde_json::from_slice(bytes).map_err(|e| format!("解析加密参数失败: {}", e))?;
+        if info.version > KEY_INFO_VERSION || info.kdf != KDF_NAME {
+            return Err("远端加密参数版本高于当前应用支持范围".to_string());
+        }
+        if !(MIN_KDF_ITERATIONS..=MAX_KDF_ITERATIONS).contains(&info.iterations) {
+            return Err(format!(
+                "远端加密参数的迭代次数 {} 不在允许范围内 ({}–{})",
+                info.iterations, MIN_KDF_ITERATIONS, MAX_KDF_ITERATIONS
+            ));
+        }
+        Ok(info)
+    }
+
+    /// 用口令重新派生密钥，口令错误时返回错误
+    pub(crate) fn unlock(&self, passphrase: &str) -> Result<SyncKey, String> {
+        let salt = decode_base64(&self.salt)?;
+        let key = SyncKey::derive(passphrase, &salt, self.iterations);
+        if !self.verify(&key) {
+            return Err("加密口令不正确".to_string());
+        }
+        Ok(key)
+    }
+
+    pub(crate) fn verify(&self, key: &SyncKey) -> bool {
+        decode_base64(&self.check)
+            .and_then(|check| decrypt_data(&check, &key.content_token))
+            .map(|plain| plain == KEY_CHECK)
+            .unwrap_or(false)
+    }
+}
+
+/// 同步密钥：由主密钥分出内容加密和对象名两把子密钥
+#[derive(Clone)]
+pub(crate) struct SyncKey {
+    master: [u8; 32],
+    content_token: String,
+    name_key: [u8; 32],
+}
+
+impl SyncKey {
+    fn derive(passphrase: &str, salt: &[u8], iterations: u32) -> Self {
+        let mut master = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut master);
+        Self::from_master(master)
+    }
+
+    fn from_master(master: [u8; 32]) -> Self {
+        Self {
+            master,
+            content_token: hex::encode(hmac_sha256(&master, b"snippets-sync/content")),
+            name_key: hmac_sha256(&master, b"snippets-sync/names"),
+        }
+    }
+
+    /// 同一路径总是映射到同一个对象，远端只能看到对象名
+    fn object_path(&self, path: &str) -> String {
+        let name = hex::encode(hmac_sha256(&self.name_key, path.as_bytes()));
+        format!("{}/{}/{}", OBJECTS_DIR, &name[..2], &name[2..])
+    }
+
+    // 明文前附带原路径，解密时核对，防止远端调换对象
+    fn seal(&self, path: &str, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let mut plain = Vec::with_capacity(4 + path.len() + bytes.len());
+        plain.extend_from_slice(&(path.len() as u32).to_be_bytes());
+        plain.extend_from_slice(path.as_bytes());
+        plain.extend_from_slice(bytes);
+        encrypt_data(&plain, &self.content_token)
+    }
+
+    fn open(&self, path: &str, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        let plain = decrypt_data(sealed, &self.content_token)
+            .map_err(|_| format!("解密 {} 失败，加密口令可能已在其他设备上更改", path))?;
+        let embedded = plain
+            .get(..4)
+            .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+            .and_then(|len| plain.get(4..4 + len));
+        if embedded != Some(path.as_bytes()) {
+            return Err(format!("加密对象与文件 {} 不匹配", path));
+        }
+        Ok(plain[4 + path.len()..].to_vec())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = <Hmac<Sha256> as KeyInit>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// 在任意远端存储之上加一层加密
+pub(crate) struct EncryptedStore<S: RemoteStore> {
+    inner: S,
+    key: SyncKey,
+    key_info: KeyInfo,
+}
+
+impl<S: RemoteStore> EncryptedStore<S> {
+    pub(crate) fn new(inner: S, key: SyncKey, key_info: KeyInfo) -> Self {
+        Self {
+            inner,
+            key,
+            key_info,
+        }
+    }
+}
+
+impl<S: RemoteStore> RemoteStore for EncryptedStore<S> {
+    fn location(&self) -> String {
+        format!("encrypted:{}", self.inner.location())
+    }
+
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+        async move {
+            match self.inner.read(&self.key.object_path(path)).await? {
+                Some(sealed) => self.key.open(path, &sealed).map(Some),
+                None => Ok(None),
+            }
+        }
+        .boxed()
+    }
+
+    fn write<'a>(&'a self, path: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            let sealed = self.key.seal(path, &bytes)?;
+            self.inner.write(&self.key.object_path(path), sealed).await
+        }
+        .boxed()
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        async move { self.inner.remove(&self.key.object_path(path)).await }.boxed()
+    }
+
+    // 明文比较后把密文交给内层做条件写入，密文每次加密都不同，不能直接比较
+    fn write_if_unchanged<'a>(
+        &'a self,
+        path: &'a str,
+        expected: Option<&'a [u8]>,
+        bytes: Vec<u8>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        async move {
+            let object_path = self.key.object_path(path);
+            let current_sealed = self.inner.read(&object_path).await?;
+            let current = match &current_sealed {
+                Some(sealed) => Some(self.key.open(path, sealed)?),
+                None => None,
+            };
+            if current.as_deref() != expected {
+                return Ok(false);
+            }
+            let sealed = self.key.seal(path, &bytes)?;
+            self.inner
+                .write_if_unchanged(&object_path, current_sealed.as_deref(), sealed)
+                .await
+        }
+        .boxed()
+    }
+
+    // 远端密钥参数缺失时补写；与本机密钥不符说明其他设备重新设置了口令，停止同步
+    fn refresh(&self) -> BoxFuture<'_, Result<(), String>> {
+        async move {
+            self.inner.refresh().await?;
+            match self.inner.read(KEY_INFO_PATH).await? {
+                Some(bytes) => {
+                    if !KeyInfo::parse(&bytes)?.verify(&self.key) {
+                        return Err("远端仓库的加密口令与本机不一致，请重新输入口令".to_string());
+                    }
+                }
+                None => {
+                    let bytes = serde_json::to_vec_pretty(&self.key_info)
+                        .map_err(|e| format!("序列化加密参数失败: {}", e))?;
+                    self.inner.write(KEY_INFO_PATH, bytes).await?;
+                }
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn commit<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        self.inner.commit(message)
+    }
+}
+
+// ============= 本机密钥 =============
+
+// 本机保存的密钥和镜像信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalKeyFile {
+    key: String,
+    branch: String,
+    key_info: KeyInfo,
+}
+
+/// 本机已保存的加密同步密钥
+pub(crate) struct LocalKey {
+    pub key: SyncKey,
+    pub key_info: KeyInfo,
+    pub branch: String,
+}
+
+// 每个工作区单独保存密钥和镜像仓库
+fn sync_dir(app_handle: &AppHandle, workspace_root: &Path) -> PathBuf {
+    let key = sha256_hex(workspace_root.display().to_string());
+    crate::json_config::get_data_dir(app_handle)
+        .join("state")
+        .join("encrypted-sync")
+        .join(&key[..16])
+}
+
+pub(crate) fn mirror_dir(app_handle: &AppHandle, workspace_root: &Path) -> PathBuf {
+    sync_dir(app_handle, workspace_root).join("repo")
+}
+
+fn key_path(app_handle: &AppHandle, workspace_root: &Path) -> PathBuf {
+    sync_dir(app_handle, workspace_root).join("key.json")
+}
+
+pub(crate) fn load_local_key(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+) -> Result<Option<LocalKey>, String> {
+    let path = key_path(app_handle, workspace_root);
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(format!("读取加密同步密钥失败: {}", error)),
+    };
+    let file: LocalKeyFile =
+        serde_json::from_slice(&content).map_err(|e| format!("解析加密同步密钥失败: {}", e))?;
+    let master: [u8; 32] = decode_base64(&file.key)?
+        .try_into()
+        .map_err(|_| "加密同步密钥长度无效".to_string())?;
+    Ok(Some(LocalKey {
+        key: SyncKey::from_master(master),
+        key_info: file.key_info,
+        branch: file.branch,
+    }))
+}
+
+pub(crate) fn save_local_key(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+    local_key: &LocalKey,
+) -> Result<(), String> {
+    let path = key_path(app_handle, workspace_root);
+    let file = LocalKeyFile {
+        key: encode_base64(&local_key.key.master),
+        branch: local_key.branch.clone(),
+        key_info: local_key.key_info.clone(),
+    };
+    let content = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("序列化加密同步密钥失败: {}", e))?;
+    crate::json_config::write_text_atomic(&path, &content)?;
+    // 密钥只允许当前用户读取
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("设置加密同步密钥权限失败: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::folder::FolderStore;
+    use super::*;
+    use std::fs;
+
+    // 测试用较少的迭代次数
+    fn test_key(passphrase: &str, salt: &[u8]) -> SyncKey {
+        SyncKey::derive(passphrase, salt, 1_000)
+    }
+
+    #[test]
+    fn same_passphrase_and_salt_derive_the_same_key() {
+        let a = test_key("correct horse battery", b"salt-1");
+        let b = test_key("correct horse battery", b"salt-1");
+        let other_salt = test_key("correct horse battery", b"salt-2");
+        assert_eq!(a.master, b.master);
+        assert_eq!(a.object_path("notes/a.md"), b.object_path("notes/a.md"));
+        assert_ne!(a.master, other_salt.master);
+        assert_ne!(
+            a.object_path("notes/a.md"),
+            other_salt.object_path("notes/a.md")
+        );
+    }
+
+    #[test]
+    fn store_hides_names_and_content() {
+        let root =
+            std::env::temp_dir().join(format!("encrypted-sync-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let key = test_key("correct horse battery", b"salt");
+        let info = KeyInfo {
+            version: KEY_INFO_VERSION,
+            kdf: KDF_NAME.to_string(),
+            // 密钥按较少次数直接派生；迭代次数只在 unlock 时使用，这里须能通过 parse 的范围检查
+            iterations: MIN_KDF_ITERATIONS,
+            salt: encode_base64(b"salt"),
+            check: encode_base64(&encrypt_data(KEY_CHECK, &key.content_token).unwrap()),
+        };
+        let store = EncryptedStore::new(FolderStore::new(root.clone()), key.clone(), info.clone());
+
+        futures::executor::block_on(store.refresh()).unwrap();
+        futures::executor::block_on(store.write("内部/机房.md", b"10.0.0.1 db".to_vec())).unwrap();
+        let files: Vec<_> = walkdir::WalkDir::new(&root)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.path().to_path_buf())
+            .collect();
+        assert_eq!(files.len(), 2);
+        for file in &files {
+            let relative = file
+                .strip_prefix(&root)
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            assert!(!relative.contains("机房"));
+            let bytes = fs::read(file).unwrap();
+            assert!(!bytes.windows(8).any(|window| window == b"10.0.0.1"));
+        }
+        assert_eq!(
+            futures::executor::block_on(store.read("内部/机房.md")).unwrap(),
+            Some(b"10.0.0.1 db".to_vec())
+        );
+
+        // 口令不同的设备无法读取，也不能通过远端密钥参数校验
+        let wrong = test_key("wrong passphrase", b"salt");
+        assert!(!info.verify(&wrong));
+        let other = EncryptedStore::new(FolderStore::new(root.clone()), wrong, info);
+        let error = futures::executor::block_on(other.refresh()).unwrap_err();
+        assert!(error.contains("口令与本机不一致"), "{}", error);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rejects_out_of_range_iterations() {
+        let key_info = |iterations: u32| {
+            serde_json::to_vec(&KeyInfo {
+                version: KEY_INFO_VERSION,
+                kdf: KDF_NAME.to_string(),
+                iterations,
+                salt: encode_base64(b"salt"),
+                check: String::new(),
+            })
+            .unwrap()
+        };
+        assert!(KeyInfo::parse(&key_info(0)).is_err());
+        assert!(KeyInfo::parse(&key_info(MIN_KDF_ITERATIONS - 1)).is_err());
+        assert!(KeyInfo::parse(&key_info(u32::MAX)).is_err());
+        assert!(KeyInfo::parse(&key_info(MIN_KDF_ITERATIONS)).is_ok());
+        assert!(KeyInfo::parse(&key_info(KDF_ITERATIONS)).is_ok());
+        assert!(KeyInfo::parse(&key_info(MAX_KDF_ITERATIONS)).is_ok());
+    }
+
+    #[test]
+    fn swapped_objects_are_rejected() {
+        let key = test_key("correct horse battery", b"salt");
+        let sealed = key.seal("a.md", b"secret").unwrap();
+        assert_eq!(key.open("a.md", &sealed).unwrap(), b"secret");
+        assert!(key.open("b.md", &sealed).is_err());
+    }
+}
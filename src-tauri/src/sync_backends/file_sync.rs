@@ -0,0 +1,936 @@
+// 基于清单的文件同步（WebDAV、共享文件夹、加密 Git 共用）
+//
+// 远端目录与工作区同构：笔记和附件按相对路径存放，另有 .snippets-sync/manifest.json 记录每个
+// 文件的 SHA-256、大小、修改时间和修改设备；删除记为墓碑而不是直接移除条目。本机在数据目录保存
+// 上次同步时两端一致的哈希，作为三方比较的基线：
+// - 只有远端变化：下载或删除本地文件
+// - 只有本地变化：上传文件，或在远端删除并写入墓碑
+// - 两端都改了：sync.json 按字段时钟合并，其余文件保留本机版本，远端版本另存为冲突副本
+// 推送时若远端还有未拉取的改动则拒绝，由调用方先拉取再重试；清单用条件写入，被其他设备
+// 抢先改写时重新读取合并，同时推送的设备不会互相覆盖条目。
+
+use crate::git_sync::{ChangedFilesByStatus, PullResult, PushResult};
+use crate::markdown::file_ops::is_within_workspace;
+use crate::sync_data::{is_allowed_sync_path, is_sync_protocol_path, managed_attachment_roots};
+use crate::text_utils::sha256_hex;
+use chrono::{DateTime, Duration as ChronoDuration, Local, SecondsFormat, Utc};
+use futures::future::BoxFuture;
+use futures::lock::Mutex as AsyncMutex;
+use futures::FutureExt;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+pub(crate) const MANIFEST_PATH: &str = ".snippets-sync/manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+// 墓碑保留期；离线超过这个时间的设备上未改动的已删文件会被重新上传
+const TOMBSTONE_RETENTION_DAYS: i64 = 180;
+// 写入过程中的临时文件后缀，扫描时跳过
+const TEMP_SUFFIX: &str = ".snippets-sync-tmp";
+// 清单被其他设备同时改写时重新合并的次数
+const MANIFEST_WRITE_ATTEMPTS: usize = 5;
+
+/// 远端存储：按相对路径整体读写文件
+pub(crate) trait RemoteStore: Send + Sync {
+    /// 远端位置描述，用于日志和区分本机同步基线
+    fn location(&self) -> String;
+
+    /// 文件不存在时返回 None
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>>;
+
+    fn write<'a>(&'a self, path: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>>;
+
+    /// 文件不存在时视为成功
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), String>>;
+
+    /// 仅当远端内容仍为 `expected`（None 表示文件不存在）时写入，返回 false 表示已被其他设备改写。
+    /// 默认实现先读后比，只能缩小并发窗口；能做条件写入的后端应覆盖它
+    fn write_if_unchanged<'a>(
+        &'a self,
+        path: &'a str,
+        expected: Option<&'a [u8]>,
+        bytes: Vec<u8>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        async move {
+            if self.read(path).await?.as_deref() != expected {
+                return Ok(false);
+            }
+            self.write(path, bytes).await?;
+            Ok(true)
+        }
+        .boxed()
+    }
+
+    /// 每次拉取或推送前调用，用于先获取远端最新状态（例如 Git 镜像的 fetch）
+    fn refresh(&self) -> BoxFuture<'_, Result<(), String>> {
+        async { Ok(()) }.boxed()
+    }
+
+    /// 推送写完清单后调用，用于一次性提交本次写入（例如 Git 镜像的 commit + push）；
+    /// 远端已有新提交时应返回以 `rejected` 开头的错误
+    fn commit<'a>(&'a self, _message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        async { Ok(()) }.boxed()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ManifestEntry {
+    #[serde(default)]
+    pub hash: String,
+    #[serde(default)]
+    pub size: u64,
+    pub modified_at: String,
+    #[serde(default)]
+    pub device: String,
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+impl ManifestEntry {
+    // 墓碑没有内容哈希
+    fn live_hash(&self) -> Option<&str> {
+        (!self.deleted).then_some(self.hash.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SyncManifest {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub entries: BTreeMap<String, ManifestEntry>,
+}
+
+// 本机保存的同步基线
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalSyncState {
+    // 路径 -> 上次同步时两端一致的内容哈希
+    #[serde(default)]
+    entries: BTreeMap<String, String>,
+    // 上次同步时的 sync.json，两端都修改时作为字段合并的基线
+    #[serde(default)]
+    sync_file_base: Option<String>,
+    #[serde(default)]
+    last_sync_time: Option<String>,
+}
+
+impl LocalSyncState {
+    fn set_base(&mut self, path: &str, hash: Option<&str>) {
+        match hash {
+            Some(hash) => {
+                self.entries.insert(path.to_string(), hash.to_string());
+            }
+            None => {
+                self.entries.remove(path);
+            }
+        }
+    }
+}
+
+struct LocalFile {
+    hash: String,
+}
+
+pub(crate) struct FileSyncEngine<S: RemoteStore> {
+    store: S,
+    workspace_root: PathBuf,
+    state_path: PathBuf,
+    device: String,
+    // 拉取与推送互斥，避免同时改写同一份基线
+    operation_lock: AsyncMutex<()>,
+    // 按大小和修改时间缓存文件哈希，轮询本地改动时不必反复读取附件
+    hash_cache: Mutex<HashMap<String, (u64, SystemTime, String)>>,
+    last_changes: Mutex<ChangedFilesByStatus>,
+}
+
+impl<S: RemoteStore> FileSyncEngine<S> {
+    pub(crate) fn new(
+        store: S,
+        workspace_root: PathBuf,
+        state_path: PathBuf,
+        device: String,
+    ) -> Self {
+        Self {
+            store,
+            workspace_root,
+            state_path,
+            device,
+            operation_lock: AsyncMutex::new(()),
+            hash_cache: Mutex::new(HashMap::new()),
+            last_changes: Mutex::new(ChangedFilesByStatus::default()),
+        }
+    }
+
+    pub(crate) fn location(&self) -> String {
+        self.store.location()
+    }
+
+    /// 取出最近一次拉取的变更分类
+    pub(crate) fn take_changes(&self) -> ChangedFilesByStatus {
+        self.last_changes
+            .lock()
+            .map(|mut changes| std::mem::take(&mut *changes))
+            .unwrap_or_default()
+    }
+
+    /// 本机是否有尚未推送的改动
+    pub(crate) fn has_local_changes(&self) -> Result<bool, String> {
+        let state = self.read_state()?;
+        let local = self.scan_local()?;
+        Ok(local.len() != state.entries.len()
+            || local
+                .iter()
+                .any(|(path, file)| state.entries.get(path) != Some(&file.hash)))
+    }
+
+    pub(crate) async fn pull(&self) -> Result<PullResult, String> {
+        let _guard = self.operation_lock.lock().await;
+        self.store.refresh().await?;
+        let remote = read_manifest(&self.store).await?;
+        let mut state = self.read_state()?;
+        let mut changes = ChangedFilesByStatus::default();
+        let mut conflict_copies = Vec::new();
+
+        // sync.json 决定附件目录，先处理它，再按更新后的白名单处理其余文件
+        let (protocol, others): (Vec<_>, Vec<_>) = remote
+            .entries
+            .iter()
+            .partition(|(path, _)| is_sync_protocol_path(path));
+        for group in [protocol, others] {
+            let roots = managed_attachment_roots(&self.workspace_root);
+            let local = self.scan_local()?;
+            for (path, entry) in group {
+                if !is_safe_relative_path(&self.workspace_root, path)
+                    || !is_allowed_sync_path(path, &roots)
+                {
+                    continue;
+                }
+                let base = state.entries.get(path).cloned();
+                let base = base.as_deref();
+                let remote_hash = entry.live_hash();
+                if remote_hash == base {
+                    continue;
+                }
+
+                let local_hash = local.get(path).map(|file| file.hash.as_str());
+                if local_hash == remote_hash {
+                    self.remember_base(&mut state, path, remote_hash)?;
+                    continue;
+                }
+
+                match (local_hash == base, local_hash, remote_hash) {
+                    // 只有远端删除
+                    (true, _, None) => {
+                        self.remove_local(path)?;
+                        changes.record_path(&self.workspace_root, "D", path.clone());
+                        state.set_base(path, None);
+                    }
+                    // 只有远端修改，或本机删除后远端又修改：以远端为准
+                    (true, _, Some(hash)) | (false, None, Some(hash)) => {
+                        let Some(bytes) = self.fetch(path, hash).await? else {
+                            continue;
+                        };
+                        self.write_local(path, &bytes)?;
+                        let status = if local_hash.is_none() { "A" } else { "M" };
+                        changes.record_path(&self.workspace_root, status, path.clone());
+                        self.remember_base(&mut state, path, remote_hash)?;
+                    }
+                    // 本机修改过的文件在远端被删：保留本机版本，推送时重新上传
+                    (false, Some(_), None) => {
+                        state.set_base(path, None);
+                    }
+                    // 两端都修改
+                    (false, Some(_), Some(hash)) => {
+                        let Some(bytes) = self.fetch(path, hash).await? else {
+                            continue;
+                        };
+                        if is_sync_protocol_path(path) {
+                            crate::sync_data::merge_remote_sync_file(
+                                &self.workspace_root,
+                                state.sync_file_base.as_deref().map(str::as_bytes),
+                                &bytes,
+                            )?;
+                            changes.record_path(&self.workspace_root, "M", path.clone());
+                            state.sync_file_base =
+                                Some(String::from_utf8_lossy(&bytes).into_owned());
+                        } else {
+                            let copy = conflict_copy_path(path, &entry.device, Local::now());
+                            self.write_local(&copy, &bytes)?;
+                            warn!(
+                                "⚠️ [FileSync] {} 两端都有修改，远端版本已另存为 {}",
+                                path, copy
+                            );
+                            changes.record_path(&self.workspace_root, "A", copy.clone());
+                            conflict_copies.push(copy);
+                        }
+                        // 基线记为远端版本，随后推送会把本机版本（或合并结果）传上去
+                        state.set_base(path, Some(hash));
+                    }
+                    (false, None, None) => {}
+                }
+            }
+        }
+
+        let last_sync_time = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        state.last_sync_time = Some(last_sync_time.clone());
+        self.save_state(&state)?;
+
+        let files_updated = changes.total_count();
+        let message = if !conflict_copies.is_empty() {
+            format!(
+                "已同步 {} 个文件，其中 {} 个文件两端都有修改，远端版本已另存为冲突副本",
+                files_updated,
+                conflict_copies.len()
+            )
+        } else if files_updated > 0 {
+            format!("已从 {} 同步 {} 个文件", self.location(), files_updated)
+        } else {
+            "已是最新版本".to_string()
+        };
+        if files_updated > 0 {
+            info!("✅ [FileSync] {}", message);
+        }
+        if let Ok(mut last_changes) = self.last_changes.lock() {
+            *last_changes = changes;
+        }
+
+        Ok(PullResult {
+            success: true,
+            files_updated,
+            has_conflicts: false,
+            conflict_files: vec![],
+            message,
+            pre_pull_head: None,
+            untracked_files: vec![],
+            last_sync_time: Some(last_sync_time),
+            branch_selection: None,
+            merge_reports: vec![],
+        })
+    }
+
+    pub(crate) async fn push(&self, message: &str) -> Result<PushResult, String> {
+        let _guard = self.operation_lock.lock().await;
+        self.store.refresh().await?;
+        let remote = read_manifest(&self.store).await?;
+        let mut state = self.read_state()?;
+        let local = self.scan_local()?;
+        let roots = managed_attachment_roots(&self.workspace_root);
+
+        let mut uploads = Vec::new();
+        let mut deletions = Vec::new();
+        let paths: BTreeSet<String> = local.keys().chain(state.entries.keys()).cloned().collect();
+        for path in &paths {
+            if !is_allowed_sync_path(path, &roots) {
+                // 已移出同步范围的文件不再跟踪
+                state.set_base(path, None);
+                continue;
+            }
+            let local_hash = local.get(path).map(|file| file.hash.as_str());
+            let base = state.entries.get(path).map(String::as_str);
+            let remote_entry = remote.entries.get(path);
+            let remote_hash = remote_entry.and_then(ManifestEntry::live_hash);
+
+            // 远端条目丢失（例如目标目录被清空）时重新上传
+            if local_hash == base && (local_hash.is_none() || remote_entry.is_some()) {
+                continue;
+            }
+            if local_hash == remote_hash {
+                let hash = remote_hash.map(str::to_string);
+                self.remember_base(&mut state, path, hash.as_deref())?;
+                continue;
+            }
+            if remote_entry.is_some() && remote_hash != base {
+                return Err(format!("rejected: 远端的 {} 有尚未拉取的改动", path));
+            }
+            if local_hash.is_some() {
+                uploads.push(path.clone());
+            } else {
+                deletions.push(path.clone());
+            }
+        }
+
+        if uploads.is_empty() && deletions.is_empty() {
+            self.save_state(&state)?;
+            return Ok(PushResult {
+                success: true,
+                files_pushed: 0,
+                commit_hash: String::new(),
+                message: "没有需要同步的改动".to_string(),
+            });
+        }
+
+        let modified_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut pushed = BTreeMap::new();
+        for path in &uploads {
+            let bytes = fs::read(local_path(&self.workspace_root, path))
+                .map_err(|e| format!("读取待同步文件 {} 失败: {}", path, e))?;
+            let entry = ManifestEntry {
+                hash: sha256_hex(&bytes),
+                size: bytes.len() as u64,
+                modified_at: modified_at.clone(),
+                device: self.device.clone(),
+                deleted: false,
+            };
+            if is_sync_protocol_path(path) {
+                state.sync_file_base = Some(String::from_utf8_lossy(&bytes).into_owned());
+            }
+            self.store.write(path, bytes).await?;
+            pushed.insert(path.clone(), entry);
+        }
+        for path in &deletions {
+            self.store.remove(path).await?;
+            pushed.insert(
+                path.clone(),
+                ManifestEntry {
+                    hash: String::new(),
+                    size: 0,
+                    modified_at: modified_at.clone(),
+                    device: self.device.clone(),
+                    deleted: true,
+                },
+            );
+        }
+
+        self.publish_manifest(&pushed).await?;
+        self.store.commit(message).await?;
+
+        for (path, entry) in &pushed {
+            state.set_base(path, entry.live_hash());
+        }
+        state.last_sync_time = Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        self.save_state(&state)?;
+
+        info!(
+            "✅ [FileSync] 已推送到 {}: 上传 {} 个文件，删除 {} 个文件",
+            self.location(),
+            uploads.len(),
+            deletions.len()
+        );
+        Ok(PushResult {
+            success: true,
+            files_pushed: pushed.len(),
+            commit_hash: String::new(),
+            message: format!("已推送 {} 个文件到 {}", pushed.len(), self.location()),
+        })
+    }
+
+    // 把本次推送的条目合并进远端最新清单；清单在读取后被其他设备改写时重新读取合并，
+    // 避免同时推送的设备互相覆盖对方的条目
+    async fn publish_manifest(
+        &self,
+        pushed: &BTreeMap<String, ManifestEntry>,
+    ) -> Result<(), String> {
+        for attempt in 1..=MANIFEST_WRITE_ATTEMPTS {
+            let current = self.store.read(MANIFEST_PATH).await?;
+            let mut latest = parse_manifest(current.as_deref())?;
+            latest.version = MANIFEST_VERSION;
+            latest.entries.extend(pushed.clone());
+            prune_tombstones(&mut latest, Utc::now());
+            let bytes = serde_json::to_vec_pretty(&latest)
+                .map_err(|e| format!("序列化同步清单失败: {}", e))?;
+            if self
+                .store
+                .write_if_unchanged(MANIFEST_PATH, current.as_deref(), bytes)
+                .await?
+            {
+                return Ok(());
+            }
+            warn!(
+                "⚠️ [FileSync] 同步清单已被其他设备更新，重新合并后写入（第 {} 次）",
+                attempt
+            );
+        }
+        Err("rejected: 同步清单正被其他设备频繁更新，请稍后重试".to_string())
+    }
+
+    // 下载远端文件并核对清单中的哈希；Syncthing 等工具可能先同步了清单、文件还在传输中，
+    // 此时跳过该文件，下次拉取再处理
+    async fn fetch(&self, path: &str, hash: &str) -> Result<Option<Vec<u8>>, String> {
+        let Some(bytes) = self.store.read(path).await? else {
+            warn!("⚠️ [FileSync] 远端缺少清单中的文件，稍后重试: {}", path);
+            return Ok(None);
+        };
+        if sha256_hex(&bytes) != hash {
+            warn!("⚠️ [FileSync] 远端文件与清单不一致，稍后重试: {}", path);
+            return Ok(None);
+        }
+        Ok(Some(bytes))
+    }
+
+    fn remember_base(
+        &self,
+        state: &mut LocalSyncState,
+        path: &str,
+        hash: Option<&str>,
+    ) -> Result<(), String> {
+        state.set_base(path, hash);
+        if hash.is_some() && is_sync_protocol_path(path) {
+            let content = fs::read(local_path(&self.workspace_root, path))
+                .map_err(|e| format!("读取 sync.json 失败: {}", e))?;
+            state.sync_file_base = Some(String::from_utf8_lossy(&content).into_owned());
+        }
+        Ok(())
+    }
+
+    fn write_local(&self, path: &str, bytes: &[u8]) -> Result<(), String> {
+        write_file_atomic(&local_path(&self.workspace_root, path), bytes)
+    }
+
+    fn remove_local(&self, path: &str) -> Result<(), String> {
+        match fs::remove_file(local_path(&self.workspace_root, path)) {
+            Ok(()) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(format!("删除本地文件 {} 失败: {}", path, error)),
+        }
+        if let Ok(mut cache) = self.hash_cache.lock() {
+            cache.remove(path);
+        }
+        Ok(())
+    }
+
+    fn read_state(&self) -> Result<LocalSyncState, String> {
+        crate::json_config::recover_atomic_file(&self.state_path)?;
+        match fs::read(&self.state_path) {
+            Ok(bytes) => {
+                serde_json::from_slice(&bytes).map_err(|e| format!("解析本机同步基线失败: {}", e))
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                Ok(LocalSyncState::default())
+            }
+            Err(error) => Err(format!("读取本机同步基线失败: {}", error)),
+        }
+    }
+
+    fn save_state(&self, state: &LocalSyncState) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(state)
+            .map_err(|e| format!("序列化本机同步基线失败: {}", e))?;
+        crate::json_config::write_text_atomic(&self.state_path, &content)
+    }
+
+    // 扫描白名单内的本地文件
+    fn scan_local(&self) -> Result<BTreeMap<String, LocalFile>, String> {
+        let roots = managed_attachment_roots(&self.workspace_root);
+        let mut cache = self
+            .hash_cache
+            .lock()
+            .map_err(|e| format!("获取文件哈希缓存锁失败: {}", e))?;
+        let mut files = BTreeMap::new();
+        for entry in walkdir::WalkDir::new(&self.workspace_root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || !matches!(
+                        entry.file_name().to_string_lossy().as_ref(),
+                        ".git" | "node_modules" | "target"
+                    )
+            })
+        {
+            let entry = entry.map_err(|e| format!("扫描同步文件失败: {}", e))?;
+            if !entry.file_type().is_file()
+                || entry.file_name().to_string_lossy().ends_with(TEMP_SUFFIX)
+            {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(&self.workspace_root)
+                .map_err(|e| format!("计算同步文件相对路径失败: {}", e))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !is_allowed_sync_path(&relative, &roots) {
+                continue;
+            }
+
+            let metadata = entry
+                .metadata()
+                .map_err(|e| format!("读取文件信息失败 {}: {}", relative, e))?;
+            let size = metadata.len();
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            let hash = match cache.get(&relative) {
+                Some((cached_size, cached_modified, hash))
+                    if *cached_size == size && *cached_modified == modified =>
+                {
+                    hash.clone()
+                }
+                _ => {
+                    let bytes = fs::read(entry.path())
+                        .map_err(|e| format!("读取同步文件 {} 失败: {}", relative, e))?;
+                    let hash = sha256_hex(&bytes);
+                    cache.insert(relative.clone(), (size, modified, hash.clone()));
+                    hash
+                }
+            };
+            files.insert(relative, LocalFile { hash });
+        }
+        Ok(files)
+    }
+}
+
+/// 读取远端清单；远端尚未同步过时返回空清单
+pub(crate) async fn read_manifest<S: RemoteStore>(store: &S) -> Result<SyncManifest, String> {
+    parse_manifest(store.read(MANIFEST_PATH).await?.as_deref())
+}
+
+fn parse_manifest(bytes: Option<&[u8]>) -> Result<SyncManifest, String> {
+    let Some(bytes) = bytes else {
+        return Ok(SyncManifest {
+            version: MANIFEST_VERSION,
+            entries: BTreeMap::new(),
+        });
+    };
+    let manifest: SyncManifest =
+        serde_json::from_slice(bytes).map_err(|e| format!("解析远端同步清单失败: {}", e))?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err("远端同步清单版本高于当前应用支持范围".to_string());
+    }
+    Ok(manifest)
+}
+
+pub(crate) fn local_path(root: &Path, relative: &str) -> PathBuf {
+    root.join(relative.replace('/', std::path::MAIN_SEPARATOR_STR))
+}
+
+/// 先写临时文件再替换，避免同步工具或编辑器读到写了一半的文件
+pub(crate) fn write_file_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("文件缺少父目录: {}", path.display()))?;
+    fs::create_dir_all(parent).map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = parent.join(format!(".{}{}", file_name, TEMP_SUFFIX));
+    fs::write(&temp_path, bytes)
+        .map_err(|e| format!("写入临时文件失败 {}: {}", temp_path.display(), e))?;
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("替换文件失败 {}: {}", path.display(), e)
+    })
+}
+
+// 清单里的路径来自远端，除白名单外还要求每段都是普通文件名：拒绝空段、`.`、`..` 和含 `:` 的段
+// （Windows 上 `C:` 会让 join 得到绝对路径），拼接后也必须仍在工作区内
+fn is_safe_relative_path(workspace_root: &Path, path: &str) -> bool {
+    !path.contains('\\')
+        && path.split('/').all(|segment| {
+            !segment.is_empty() && !segment.contains(':') && segment != "." && segment != ".."
+        })
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        && is_within_workspace(workspace_root, &local_path(workspace_root, path))
+}
+
+/// 冲突副本与原文件放在同一目录，例如 `笔记 (冲突副本 1a2b3c4d 2024-05-01 093000).md`
+fn conflict_copy_path(path: &str, device: &str, now: DateTime<Local>) -> String {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (Some(dir), name),
+        None => (None, path),
+    };
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (name, None),
+    };
+    let device: String = device.chars().take(8).collect();
+    let device = if device.is_empty() {
+        "远端".to_string()
+    } else {
+        device
+    };
+    let mut file_name = format!(
+        "{} (冲突副本 {} {})",
+        stem,
+        device,
+        now.format("%Y-%m-%d %H%M%S")
+    );
+    if let Some(extension) = extension {
+        file_name.push('.');
+        file_name.push_str(extension);
+    }
+    match dir {
+        Some(dir) => format!("{}/{}", dir, file_name),
+        None => file_name,
+    }
+}
+
+fn prune_tombstones(manifest: &mut SyncManifest, now: DateTime<Utc>) {
+    let cutoff = now - ChronoDuration::days(TOMBSTONE_RETENTION_DAYS);
+    manifest.entries.retain(|_, entry| {
+        !entry.deleted
+            || DateTime::parse_from_rfc3339(&entry.modified_at)
+                .map(|time| time.with_timezone(&Utc) >= cutoff)
+                .unwrap_or(true)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::folder::FolderStore;
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Barrier};
+
+    struct Fixture {
+        base: PathBuf,
+        remote: PathBuf,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let base =
+                std::env::temp_dir().join(format!("file-sync-test-{}", uuid::Uuid::new_v4()));
+            let remote = base.join("remote");
+            fs::create_dir_all(&remote).unwrap();
+            Self { base, remote }
+        }
+
+        fn device(&self, name: &str) -> FileSyncEngine<FolderStore> {
+            let workspace = self.base.join(name);
+            fs::create_dir_all(&workspace).unwrap();
+            FileSyncEngine::new(
+                FolderStore::new(self.remote.clone()),
+                workspace,
+                self.base.join(format!("{}-state.json", name)),
+                name.to_string(),
+            )
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.base);
+        }
+    }
+
+    fn write<S: RemoteStore>(engine: &FileSyncEngine<S>, path: &str, content: &str) {
+        write_file_atomic(
+            &local_path(&engine.workspace_root, path),
+            content.as_bytes(),
+        )
+        .unwrap();
+    }
+
+    fn read<S: RemoteStore>(engine: &FileSyncEngine<S>, path: &str) -> Option<String> {
+        fs::read_to_string(local_path(&engine.workspace_root, path)).ok()
+    }
+
+    #[test]
+    fn propagates_changes_between_devices() {
+        let fixture = Fixture::new();
+        let a = fixture.device("a");
+        let b = fixture.device("b");
+
+        write(&a, "notes/hello.md", "# Hello");
+        write(&a, "assets/image.png", "png");
+        write(&a, "build.log", "不在白名单内");
+        assert!(a.has_local_changes().unwrap());
+        let pushed = futures::executor::block_on(a.push("sync")).unwrap();
+        assert_eq!(pushed.files_pushed, 2);
+        assert!(!a.has_local_changes().unwrap());
+        assert!(!fixture.remote.join("build.log").exists());
+
+        let pulled = futures::executor::block_on(b.pull()).unwrap();
+        assert_eq!(pulled.files_updated, 2);
+        assert_eq!(read(&b, "notes/hello.md").as_deref(), Some("# Hello"));
+        let changes = b.take_changes();
+        assert_eq!(changes.created, vec!["notes/hello.md".to_string()]);
+        assert_eq!(
+            changes.attachment_files,
+            vec!["assets/image.png".to_string()]
+        );
+
+        write(&b, "notes/hello.md", "# Hello again");
+        futures::executor::block_on(b.push("sync")).unwrap();
+        futures::executor::block_on(a.pull()).unwrap();
+        assert_eq!(read(&a, "notes/hello.md").as_deref(), Some("# Hello again"));
+        assert_eq!(
+            a.take_changes().modified,
+            vec!["notes/hello.md".to_string()]
+        );
+    }
+
+    #[test]
+    fn ignores_hostile_manifest_paths() {
+        let fixture = Fixture::new();
+        let a = fixture.device("a");
+        let b = fixture.device("b");
+        write(&a, "notes/ok.md", "ok");
+        futures::executor::block_on(a.push("sync")).unwrap();
+
+        // 篡改远端清单：指向工作区外的条目引用同一内容，远端也放好对应文件
+        let mut manifest = futures::executor::block_on(read_manifest(&a.store)).unwrap();
+        let entry = manifest.entries["notes/ok.md"].clone();
+        let hostile = [
+            "C:/Users/x/evil.md",
+            "notes/C:evil.md",
+            "../evil.md",
+            "notes/../../evil.md",
+            "/tmp/evil.md",
+            "./evil.md",
+        ];
+        for path in hostile {
+            manifest.entries.insert(path.to_string(), entry.clone());
+            assert!(!is_safe_relative_path(&b.workspace_root, path), "{}", path);
+        }
+        let remote_file = fixture.remote.join("C:").join("Users").join("x");
+        fs::create_dir_all(&remote_file).unwrap();
+        fs::write(remote_file.join("evil.md"), "ok").unwrap();
+        futures::executor::block_on(
+            a.store
+                .write(MANIFEST_PATH, serde_json::to_vec(&manifest).unwrap()),
+        )
+        .unwrap();
+
+        let pulled = futures::executor::block_on(b.pull()).unwrap();
+        assert_eq!(pulled.files_updated, 1);
+        assert_eq!(read(&b, "notes/ok.md").as_deref(), Some("ok"));
+        assert!(!b.workspace_root.join("C:").exists());
+        assert!(!fixture.base.join("evil.md").exists());
+        assert!(is_safe_relative_path(&b.workspace_root, "notes/ok.md"));
+    }
+
+    #[test]
+    fn deletions_travel_as_tombstones() {
+        let fixture = Fixture::new();
+        let a = fixture.device("a");
+        let b = fixture.device("b");
+        write(&a, "todo.md", "- [ ] task");
+        write(&a, "keep.md", "v1");
+        futures::executor::block_on(a.push("sync")).unwrap();
+        futures::executor::block_on(b.pull()).unwrap();
+
+        fs::remove_file(local_path(&a.workspace_root, "todo.md")).unwrap();
+        fs::remove_file(local_path(&a.workspace_root, "keep.md")).unwrap();
+        futures::executor::block_on(a.push("sync")).unwrap();
+        let manifest = futures::executor::block_on(read_manifest(&a.store)).unwrap();
+        assert!(manifest.entries["todo.md"].deleted);
+        assert!(!fixture.remote.join("todo.md").exists());
+
+        // 未拉取墓碑就推送会被拒绝；拉取后未改动的文件被删除，改过的文件保留并重新上传
+        write(&b, "keep.md", "v2");
+        let rejected = futures::executor::block_on(b.push("sync")).unwrap_err();
+        assert!(rejected.starts_with("rejected"));
+        futures::executor::block_on(b.pull()).unwrap();
+        assert!(read(&b, "todo.md").is_none());
+        assert_eq!(b.take_changes().deleted, vec!["todo.md".to_string()]);
+        assert_eq!(read(&b, "keep.md").as_deref(), Some("v2"));
+
+        futures::executor::block_on(b.push("sync")).unwrap();
+        futures::executor::block_on(a.pull()).unwrap();
+        assert_eq!(read(&a, "keep.md").as_deref(), Some("v2"));
+        assert!(read(&a, "todo.md").is_none());
+    }
+
+    #[test]
+    fn concurrent_edits_keep_a_conflict_copy() {
+        let fixture = Fixture::new();
+        let a = fixture.device("a");
+        let b = fixture.device("b");
+        write(&a, "plan.md", "v1");
+        futures::executor::block_on(a.push("sync")).unwrap();
+        futures::executor::block_on(b.pull()).unwrap();
+
+        write(&a, "plan.md", "from a");
+        futures::executor::block_on(a.push("sync")).unwrap();
+        write(&b, "plan.md", "from b");
+        assert!(futures::executor::block_on(b.push("sync")).is_err());
+
+        let pulled = futures::executor::block_on(b.pull()).unwrap();
+        assert!(!pulled.has_conflicts);
+        assert_eq!(read(&b, "plan.md").as_deref(), Some("from b"));
+        let copies = b.take_changes().created;
+        assert_eq!(copies.len(), 1);
+        assert!(copies[0].starts_with("plan (冲突副本 a "));
+        assert_eq!(read(&b, &copies[0]).as_deref(), Some("from a"));
+
+        // 本机版本和冲突副本都推送上去，另一台设备也能看到
+        futures::executor::block_on(b.push("sync")).unwrap();
+        futures::executor::block_on(a.pull()).unwrap();
+        assert_eq!(read(&a, "plan.md").as_deref(), Some("from b"));
+        assert_eq!(read(&a, &copies[0]).as_deref(), Some("from a"));
+    }
+
+    // 第一次写清单前在屏障处等待：两台设备都读到同一份旧清单后才开始写入
+    struct RacingStore {
+        inner: FolderStore,
+        barrier: Arc<Barrier>,
+        waited: AtomicBool,
+    }
+
+    impl RemoteStore for RacingStore {
+        fn location(&self) -> String {
+            self.inner.location()
+        }
+
+        fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+            self.inner.read(path)
+        }
+
+        fn write<'a>(&'a self, path: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+            self.inner.write(path, bytes)
+        }
+
+        fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), String>> {
+            self.inner.remove(path)
+        }
+
+        fn write_if_unchanged<'a>(
+            &'a self,
+            path: &'a str,
+            expected: Option<&'a [u8]>,
+            bytes: Vec<u8>,
+        ) -> BoxFuture<'a, Result<bool, String>> {
+            if path == MANIFEST_PATH && !self.waited.swap(true, Ordering::SeqCst) {
+                self.barrier.wait();
+            }
+            self.inner.write_if_unchanged(path, expected, bytes)
+        }
+    }
+
+    #[test]
+    fn concurrent_pushes_keep_every_manifest_entry() {
+        let fixture = Fixture::new();
+        let barrier = Arc::new(Barrier::new(2));
+        let device = |name: &str| {
+            let workspace = fixture.base.join(name);
+            fs::create_dir_all(&workspace).unwrap();
+            FileSyncEngine::new(
+                RacingStore {
+                    inner: FolderStore::new(fixture.remote.clone()),
+                    barrier: barrier.clone(),
+                    waited: AtomicBool::new(false),
+                },
+                workspace,
+                fixture.base.join(format!("{}-state.json", name)),
+                name.to_string(),
+            )
+        };
+        let a = device("a");
+        let b = device("b");
+        write(&a, "from-a.md", "a");
+        write(&b, "from-b.md", "b");
+
+        std::thread::scope(|scope| {
+            for engine in [&a, &b] {
+                scope.spawn(move || tauri::async_runtime::block_on(engine.push("sync")).unwrap());
+            }
+        });
+
+        let manifest = futures::executor::block_on(read_manifest(&a.store)).unwrap();
+        assert!(manifest.entries.contains_key("from-a.md"));
+        assert!(manifest.entries.contains_key("from-b.md"));
+        futures::executor::block_on(a.pull()).unwrap();
+        futures::executor::block_on(b.pull()).unwrap();
+        assert_eq!(read(&a, "from-b.md").as_deref(), Some("b"));
+        assert_eq!(read(&b, "from-a.md").as_deref(), Some("a"));
+    }
+}
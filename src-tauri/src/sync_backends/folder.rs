@@ -0,0 +1,152 @@
+// 普通文件夹同步目标：Syncthing、Dropbox、OneDrive 或网络共享挂载的目录
+
+use super::file_sync::{local_path, write_file_atomic, RemoteStore};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// 条件写入时在目标旁创建的锁文件；超过时限仍未释放的视为崩溃残留
+const LOCK_SUFFIX: &str = ".lock";
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(30);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) struct FolderStore {
+    root: PathBuf,
+}
+
+impl FolderStore {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    // 网络共享未挂载时目标目录不存在，不能在挂载点上自行创建目录写入
+    fn ensure_root(&self) -> Result<(), String> {
+        if self.root.is_dir() {
+            Ok(())
+        } else {
+            Err(format!("同步文件夹不存在或未挂载: {}", self.root.display()))
+        }
+    }
+
+    fn read_file(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        match fs::read(local_path(&self.root, path)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(format!("读取同步文件夹中的 {} 失败: {}", path, error)),
+        }
+    }
+}
+
+// 独占创建的锁文件，离开作用域时删除
+struct FolderLock(PathBuf);
+
+impl FolderLock {
+    // 等待时让出运行时线程，其他设备持锁最长可能要等 LOCK_TIMEOUT
+    async fn acquire(target: &Path) -> Result<Self, String> {
+        let mut lock_name = target.file_name().unwrap_or_default().to_os_string();
+        lock_name.push(LOCK_SUFFIX);
+        let lock_path = target.with_file_name(lock_name);
+        let started = SystemTime::now();
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(_) => return Ok(Self(lock_path)),
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&lock_path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > LOCK_STALE_AFTER);
+                    if stale {
+                        let _ = fs::remove_file(&lock_path);
+                        continue;
+                    }
+                    if started.elapsed().unwrap_or_default() > LOCK_TIMEOUT {
+                        return Err(format!(
+                            "同步文件夹正被其他设备写入，请稍后重试: {}",
+                            lock_path.display()
+                        ));
+                    }
+                    tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+                }
+                Err(error) => {
+                    return Err(format!(
+                        "创建同步锁文件失败 {}: {}",
+                        lock_path.display(),
+                        error
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FolderLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl RemoteStore for FolderStore {
+    fn location(&self) -> String {
+        format!("folder:{}", self.root.display())
+    }
+
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+        async move {
+            self.ensure_root()?;
+            self.read_file(path)
+        }
+        .boxed()
+    }
+
+    fn write<'a>(&'a self, path: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            self.ensure_root()?;
+            write_file_atomic(&local_path(&self.root, path), &bytes)
+        }
+        .boxed()
+    }
+
+    // 共享目录没有条件写入，用锁文件把“比较 + 写入”变成独占操作
+    fn write_if_unchanged<'a>(
+        &'a self,
+        path: &'a str,
+        expected: Option<&'a [u8]>,
+        bytes: Vec<u8>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        async move {
+            self.ensure_root()?;
+            let target = local_path(&self.root, path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+            }
+            let _lock = FolderLock::acquire(&target).await?;
+            if self.read_file(path)?.as_deref() != expected {
+                return Ok(false);
+            }
+            write_file_atomic(&target, &bytes)?;
+            Ok(true)
+        }
+        .boxed()
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            self.ensure_root()?;
+            match fs::remove_file(local_path(&self.root, path)) {
+                Ok(()) => Ok(()),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(error) => Err(format!("删除同步文件夹中的 {} 失败: {}", path, error)),
+            }
+        }
+        .boxed()
+    }
+}
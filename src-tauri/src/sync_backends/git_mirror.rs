@@ -0,0 +1,388 @@
+// 加密同步使用的 Git 镜像
+//
+// 镜像是数据目录下单独的工作树，只存放加密对象和密钥参数；远端仓库就是 Git 设置中的远程仓库。
+// 每次拉取或推送前先 fetch 并强制对齐远端分支，推送时把本次写入一次性提交后 push。
+
+use super::file_sync::{local_path, write_file_atomic, RemoteStore};
+use crate::git_common::{get_git_stderr, get_git_stdout, is_git_success, run_git_command};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub(crate) struct GitMirrorStore {
+    root: PathBuf,
+    remote_url: String,
+    branch: String,
+    user_name: String,
+    user_email: String,
+    // 迁移时从空白历史开始：不对齐远端，提交后强制推送覆盖远端的明文历史
+    rewrite_history: AtomicBool,
+}
+
+impl GitMirrorStore {
+    pub(crate) fn open(
+        root: PathBuf,
+        remote_url: &str,
+        branch: &str,
+        user_name: &str,
+        user_email: &str,
+    ) -> Result<Self, String> {
+        init_repo(&root, remote_url)?;
+        Ok(Self {
+            root,
+            remote_url: remote_url.to_string(),
+            branch: branch.to_string(),
+            user_name: user_name.to_string(),
+            user_email: user_email.to_string(),
+            rewrite_history: AtomicBool::new(false),
+        })
+    }
+
+    /// 丢弃已有镜像，从没有历史的分支开始；第一次提交会强制推送并替换远端分支
+    pub(crate) fn for_migration(
+        root: PathBuf,
+        remote_url: &str,
+        branch: &str,
+        user_name: &str,
+        user_email: &str,
+    ) -> Result<Self, String> {
+        if root.exists() {
+            fs::remove_dir_all(&root).map_err(|e| format!("清理加密同步仓库失败: {}", e))?;
+        }
+        let store = Self::open(root, remote_url, branch, user_name, user_email)?;
+        store.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])?;
+        store.rewrite_history.store(true, Ordering::SeqCst);
+        Ok(store)
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, String> {
+        let output = run_git_command(&self.root, args)?;
+        if !is_git_success(&output) {
+            return Err(format!(
+                "加密同步仓库执行 git {} 失败: {}",
+                args[0],
+                get_git_stderr(&output)
+            ));
+        }
+        Ok(get_git_stdout(&output))
+    }
+
+    /// 工作树中除 .git 外是否还有文件（远端已有内容）
+    pub(crate) fn has_files(&self) -> bool {
+        fs::read_dir(&self.root)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .any(|entry| entry.file_name() != ".git")
+            })
+            .unwrap_or(false)
+    }
+
+    /// 远端除同步分支外的其他分支和标签；迁移只替换同步分支，这些引用里仍可能保存明文历史
+    pub(crate) fn other_remote_refs(&self) -> Result<Vec<String>, String> {
+        let own = format!("refs/heads/{}", self.branch);
+        let stdout = self.git(&["ls-remote", "--heads", "--tags", "origin"])?;
+        let mut refs: Vec<String> = stdout
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            // 附注标签会额外列出一行 ^{} 指向被标记的提交
+            .map(|name| name.trim_end_matches("^{}"))
+            .filter(|name| *name != own)
+            .map(str::to_string)
+            .collect();
+        refs.dedup();
+        Ok(refs)
+    }
+
+    /// 读取远端同步分支上的文件，不改动工作树；远端分支或文件不存在时返回 None
+    pub(crate) fn read_remote(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        self.git(&["fetch", "--prune", "--quiet", "origin"])?;
+        if !self.remote_branch_exists()? {
+            return Ok(None);
+        }
+        let object = format!("origin/{}:{}", self.branch, path);
+        let output = run_git_command(&self.root, &["cat-file", "blob", &object])?;
+        Ok(is_git_success(&output).then_some(output.stdout))
+    }
+
+    fn remote_branch_exists(&self) -> Result<bool, String> {
+        let remote_ref = format!("refs/remotes/origin/{}", self.branch);
+        let output = run_git_command(
+            &self.root,
+            &["rev-parse", "--verify", "--quiet", &remote_ref],
+        )?;
+        Ok(is_git_success(&output))
+    }
+}
+
+/// 创建镜像仓库（已存在时只更新远程地址）
+pub(crate) fn init_repo(root: &Path, remote_url: &str) -> Result<(), String> {
+    let run = |args: &[&str]| -> Result<(), String> {
+        let output = run_git_command(root, args)?;
+        if is_git_success(&output) {
+            Ok(())
+        } else {
+            Err(format!(
+                "初始化加密同步仓库失败 (git {}): {}",
+                args[0],
+                get_git_stderr(&output)
+            ))
+        }
+    };
+
+    if root.join(".git").is_dir() {
+        run(&["remote", "set-url", "origin", remote_url])?;
+    } else {
+        fs::create_dir_all(root).map_err(|e| format!("创建加密同步仓库目录失败: {}", e))?;
+        run(&["init", "--quiet"])?;
+        run(&["remote", "add", "origin", remote_url])?;
+    }
+    // 与工作区仓库一样按路径查找 credential helper 中的 token；加密对象按二进制原样提交
+    run(&["config", "credential.useHttpPath", "true"])?;
+    run(&["config", "core.autocrlf", "false"])
+}
+
+/// 远端默认分支；远端为空仓库时返回 None
+pub(crate) fn remote_default_branch(root: &Path) -> Option<String> {
+    let output = run_git_command(root, &["ls-remote", "--symref", "origin", "HEAD"]).ok()?;
+    if !is_git_success(&output) {
+        return None;
+    }
+    get_git_stdout(&output).lines().find_map(|line| {
+        line.strip_prefix("ref: refs/heads/")
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(str::to_string)
+    })
+}
+
+impl RemoteStore for GitMirrorStore {
+    fn location(&self) -> String {
+        format!("git:{}#{}", self.remote_url, self.branch)
+    }
+
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+        async move {
+            match fs::read(local_path(&self.root, path)) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(format!("读取加密同步仓库中的 {} 失败: {}", path, error)),
+            }
+        }
+        .boxed()
+    }
+
+    fn write<'a>(&'a self, path: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+        async move { write_file_atomic(&local_path(&self.root, path), &bytes) }.boxed()
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            match fs::remove_file(local_path(&self.root, path)) {
+                Ok(()) => Ok(()),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(error) => Err(format!("删除加密同步仓库中的 {} 失败: {}", path, error)),
+            }
+        }
+        .boxed()
+    }
+
+    fn refresh(&self) -> BoxFuture<'_, Result<(), String>> {
+        async move {
+            if self.rewrite_history.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            self.git(&["fetch", "--prune", "--quiet", "origin"])?;
+            // 远端还是空仓库时保留本地内容，第一次推送会创建分支
+            if self.remote_branch_exists()? {
+                let remote_ref = format!("origin/{}", self.branch);
+                self.git(&["reset", "--hard", "--quiet", &remote_ref])?;
+            }
+            self.git(&["clean", "-fdq"])?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn commit<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            self.git(&["add", "-A"])?;
+            let staged = run_git_command(&self.root, &["diff", "--cached", "--quiet"])?;
+            if is_git_success(&staged) {
+                return Ok(());
+            }
+
+            let mut args = Vec::new();
+            let user_name = format!("user.name={}", self.user_name);
+            let user_email = format!("user.email={}", self.user_email);
+            if !self.user_name.is_empty() {
+                args.extend(["-c", user_name.as_str()]);
+            }
+            if !self.user_email.is_empty() {
+                args.extend(["-c", user_email.as_str()]);
+            }
+            args.extend(["commit", "--quiet", "-m", message]);
+            self.git(&args)?;
+
+            let rewrite = self.rewrite_history.load(Ordering::SeqCst);
+            let target = format!("HEAD:refs/heads/{}", self.branch);
+            let mut push_args = vec!["push", "--quiet"];
+            if rewrite {
+                push_args.push("--force");
+            }
+            push_args.extend(["origin", target.as_str()]);
+            let output = run_git_command(&self.root, &push_args)?;
+            if !is_git_success(&output) {
+                let stderr = get_git_stderr(&output);
+                if stderr.contains("rejected")
+                    || stderr.contains("non-fast-forward")
+                    || stderr.contains("fetch first")
+                {
+                    return Err("rejected: 加密同步仓库的远端有尚未拉取的提交".to_string());
+                }
+                return Err(format!("推送加密同步仓库失败: {}", stderr));
+            }
+
+            if rewrite {
+                self.rewrite_history.store(false, Ordering::SeqCst);
+                info!(
+                    "✅ [EncryptedSync] 已用加密内容替换远端分支 {}",
+                    self.branch
+                );
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::encrypted::{EncryptedStore, KeyInfo, KEY_INFO_PATH};
+    use super::super::file_sync::FileSyncEngine;
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn open_store(root: PathBuf, url: &str) -> GitMirrorStore {
+        GitMirrorStore::open(root, url, "main", "test", "test@example.com").unwrap()
+    }
+
+    #[test]
+    fn migration_replaces_plain_history_with_sealed_objects() {
+        let base = std::env::temp_dir().join(format!("git-mirror-test-{}", uuid::Uuid::new_v4()));
+        let remote = base.join("remote.git");
+        fs::create_dir_all(&remote).unwrap();
+        git(&remote, &["init", "--bare", "--quiet", "-b", "main"]);
+        let url = remote.to_string_lossy().to_string();
+
+        // 明文仓库，远端另有一个分支和一个附注标签
+        let workspace = base.join("workspace");
+        fs::create_dir_all(workspace.join("内部")).unwrap();
+        fs::write(workspace.join("内部/机房.md"), "10.0.0.1 db").unwrap();
+        git(&workspace, &["init", "--quiet", "-b", "main"]);
+        git(&workspace, &["add", "-A"]);
+        git(&workspace, &["commit", "--quiet", "-m", "plain"]);
+        git(&workspace, &["tag", "-a", "v1", "-m", "v1"]);
+        git(&workspace, &["remote", "add", "origin", &url]);
+        git(
+            &workspace,
+            &["push", "--quiet", "origin", "main", "main:dev", "v1"],
+        );
+
+        let mirror = GitMirrorStore::for_migration(
+            base.join("mirror-a"),
+            &url,
+            "main",
+            "test",
+            "test@example.com",
+        )
+        .unwrap();
+        assert_eq!(
+            mirror.other_remote_refs().unwrap(),
+            vec!["refs/heads/dev", "refs/tags/v1"]
+        );
+        git(&remote, &["branch", "--quiet", "-D", "dev"]);
+        git(&remote, &["tag", "-d", "v1"]);
+        assert!(mirror.other_remote_refs().unwrap().is_empty());
+        assert_eq!(mirror.read_remote(KEY_INFO_PATH).unwrap(), None);
+
+        // 迁移：强制推送覆盖明文历史，远端只剩加密对象和密钥参数
+        let (key_info, key) = KeyInfo::create_for_test("correct horse battery").unwrap();
+        let engine = FileSyncEngine::new(
+            EncryptedStore::new(mirror, key, key_info),
+            workspace.clone(),
+            base.join("state-a.json"),
+            "device-a".to_string(),
+        );
+        let pushed = futures::executor::block_on(engine.push("Migrate")).unwrap();
+        assert_eq!(pushed.files_pushed, 1);
+        let log = git(&remote, &["log", "--format=%s", "main"]);
+        assert_eq!(log.trim(), "Migrate");
+        let tree = git(&remote, &["ls-tree", "-r", "--name-only", "main"]);
+        assert!(tree.lines().any(|path| path == KEY_INFO_PATH), "{}", tree);
+        for path in tree.lines().filter(|path| *path != KEY_INFO_PATH) {
+            assert!(path.starts_with("objects/"), "{}", tree);
+            let object = Command::new("git")
+                .args(["cat-file", "blob", &format!("main:{}", path)])
+                .current_dir(&remote)
+                .output()
+                .unwrap()
+                .stdout;
+            assert!(!object.windows(8).any(|window| window == b"10.0.0.1"));
+        }
+
+        // 另一台设备再次迁移时能发现远端的密钥参数，并按它校验口令
+        let again = GitMirrorStore::for_migration(
+            base.join("mirror-again"),
+            &url,
+            "main",
+            "test",
+            "test@example.com",
+        )
+        .unwrap();
+        let remote_key_info =
+            KeyInfo::parse(&again.read_remote(KEY_INFO_PATH).unwrap().unwrap()).unwrap();
+        assert!(remote_key_info.unlock("wrong passphrase").is_err());
+        assert!(remote_key_info.unlock("correct horse battery").is_ok());
+
+        // 普通镜像：refresh 对齐远端，commit 推送；基于旧提交的推送被拒绝
+        let b = open_store(base.join("mirror-b"), &url);
+        let c = open_store(base.join("mirror-c"), &url);
+        futures::executor::block_on(b.refresh()).unwrap();
+        futures::executor::block_on(c.refresh()).unwrap();
+        assert!(b.has_files());
+        futures::executor::block_on(b.write("objects/00/from-b", b"b".to_vec())).unwrap();
+        futures::executor::block_on(b.commit("from b")).unwrap();
+        futures::executor::block_on(c.write("objects/00/from-c", b"c".to_vec())).unwrap();
+        let error = futures::executor::block_on(c.commit("from c")).unwrap_err();
+        assert!(error.starts_with("rejected"), "{}", error);
+        futures::executor::block_on(c.refresh()).unwrap();
+        assert_eq!(
+            futures::executor::block_on(c.read("objects/00/from-b")).unwrap(),
+            Some(b"b".to_vec())
+        );
+        assert_eq!(
+            futures::executor::block_on(c.read("objects/00/from-c")).unwrap(),
+            None
+        );
+        let _ = fs::remove_dir_all(&base);
+    }
+}
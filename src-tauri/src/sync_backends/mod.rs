@@ -0,0 +1,688 @@
+// 同步后端
+//
+// AutoSyncManager 通过 SyncBackend 拉取和推送，不关心底层是 Git 仓库还是普通文件存储：
+// Git 后端沿用 git_sync 的提交流程；WebDAV 和普通文件夹（Syncthing、Dropbox、网络共享）
+// 使用 file_sync 中基于清单的三方同步，不需要安装 Git。两类后端的同步范围都由
+// is_allowed_sync_path 决定，sync.json 两端都修改时都按字段时钟合并。加密 Git 后端同样走
+// file_sync，只是远端换成加密后的 Git 镜像（见 encrypted、git_mirror）。
+
+mod encrypted;
+mod file_sync;
+mod folder;
+mod git_mirror;
+mod webdav;
+
+use crate::git_sync::{ChangedFilesByStatus, PullResult, PushResult};
+use crate::text_utils::sha256_hex;
+use encrypted::{EncryptedStore, KeyInfo, LocalKey, KEY_INFO_PATH};
+use file_sync::{read_manifest, FileSyncEngine, RemoteStore};
+use folder::FolderStore;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use git_mirror::GitMirrorStore;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use webdav::WebdavStore;
+
+// app.json 中保存同步后端配置的键
+pub const SYNC_BACKEND_CONFIG_KEY: &str = "sync_backend";
+
+// ============= 配置 =============
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackendKind {
+    #[default]
+    Git,
+    Webdav,
+    Folder,
+    // 端到端加密后推送到 Git 设置中的远程仓库
+    #[serde(rename = "encrypted-git")]
+    EncryptedGit,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncBackendConfig {
+    #[serde(default)]
+    pub kind: SyncBackendKind,
+    #[serde(default)]
+    pub webdav: WebdavConfig,
+    #[serde(default)]
+    pub folder: FolderConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebdavConfig {
+    // 同步根目录，例如 https://cloud.example.com/remote.php/dav/files/alice/notes/
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    // Nextcloud 等建议使用应用专用密码
+    #[serde(default)]
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderConfig {
+    #[serde(default)]
+    pub path: String,
+}
+
+impl SyncBackendConfig {
+    fn normalized(mut self) -> Self {
+        self.webdav.url = self.webdav.url.trim().to_string();
+        if !self.webdav.url.is_empty() && !self.webdav.url.ends_with('/') {
+            self.webdav.url.push('/');
+        }
+        self.webdav.username = self.webdav.username.trim().to_string();
+        self.folder.path = self.folder.path.trim().to_string();
+        self
+    }
+
+    fn validate(&self, workspace_root: &Path) -> Result<(), String> {
+        match self.kind {
+            SyncBackendKind::Git | SyncBackendKind::EncryptedGit => Ok(()),
+            SyncBackendKind::Webdav => {
+                if self.webdav.url.is_empty() {
+                    return Err("请填写 WebDAV 地址".to_string());
+                }
+                WebdavStore::new(
+                    &self.webdav.url,
+                    &self.webdav.username,
+                    &self.webdav.password,
+                )
+                .map(|_| ())
+            }
+            SyncBackendKind::Folder => {
+                if self.folder.path.is_empty() {
+                    return Err("请选择同步文件夹".to_string());
+                }
+                let folder = Path::new(&self.folder.path);
+                if !folder.is_absolute() {
+                    return Err("同步文件夹必须是绝对路径".to_string());
+                }
+                // 互相包含会把同步目标当成笔记再同步一遍
+                if folder.starts_with(workspace_root) || workspace_root.starts_with(folder) {
+                    return Err("同步文件夹不能位于工作区内，也不能包含工作区".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub fn read_backend_config(app_handle: &AppHandle) -> SyncBackendConfig {
+    crate::json_config::get_app_config_value::<SyncBackendConfig>(
+        app_handle,
+        SYNC_BACKEND_CONFIG_KEY,
+    )
+    .unwrap_or_default()
+    .normalized()
+}
+
+/// 当前配置是否通过 Git 远程仓库同步（Git 和加密 Git 后端才需要用户名、邮箱和远程仓库）
+pub fn uses_git_backend(app_handle: &AppHandle) -> bool {
+    matches!(
+        read_backend_config(app_handle).kind,
+        SyncBackendKind::Git | SyncBackendKind::EncryptedGit
+    )
+}
+
+/// 启用加密同步后，工作区仓库不能再直接拉取或推送明文内容
+pub fn ensure_plaintext_git_allowed(app_handle: &AppHandle) -> Result<(), String> {
+    if read_backend_config(app_handle).kind == SyncBackendKind::EncryptedGit {
+        return Err(
+            "已启用端到端加密同步，请使用“立即同步”，工作区仓库不再直接推送到远端".to_string(),
+        );
+    }
+    Ok(())
+}
+
+// ============= 后端 =============
+
+/// 自动同步使用的同步引擎
+pub(crate) trait SyncBackend: Send + Sync {
+    /// 同步目标标识，变化时重建自动同步
+    fn target(&self) -> String;
+
+    fn pull(&self) -> BoxFuture<'_, Result<PullResult, String>>;
+
+    /// 拉取带来的变更文件，用于增量更新 cache
+    fn changed_files(&self, result: &PullResult) -> Result<ChangedFilesByStatus, String>;
+
+    /// 本机是否有尚未推送的改动
+    fn has_local_changes(&self) -> Result<bool, String>;
+
+    /// 导出同步配置并推送本机改动
+    fn push<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<PushResult, String>>;
+
+    /// 推送被拒绝并拉取远端改动后重试推送
+    fn push_after_pull(&self) -> BoxFuture<'_, Result<PushResult, String>>;
+}
+
+struct GitBackend {
+    workspace_root: PathBuf,
+    app_handle: AppHandle,
+}
+
+impl SyncBackend for GitBackend {
+    fn target(&self) -> String {
+        "git".to_string()
+    }
+
+    fn pull(&self) -> BoxFuture<'_, Result<PullResult, String>> {
+        crate::git_sync::git_pull(&self.workspace_root).boxed()
+    }
+
+    fn changed_files(&self, result: &PullResult) -> Result<ChangedFilesByStatus, String> {
+        crate::git_sync::get_changed_files_with_status(
+            &self.workspace_root,
+            result.pre_pull_head.as_deref(),
+        )
+    }
+
+    fn has_local_changes(&self) -> Result<bool, String> {
+        crate::git_sync::get_git_status(&self.workspace_root).map(|status| status.has_changes)
+    }
+
+    fn push<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<PushResult, String>> {
+        async move {
+            crate::git_sync::ensure_gitignore(&self.workspace_root)?;
+            crate::sync_data::export_sync_bundle(&self.app_handle, &self.workspace_root)?;
+            crate::git_sync::git_push(&self.workspace_root, message).await
+        }
+        .boxed()
+    }
+
+    fn push_after_pull(&self) -> BoxFuture<'_, Result<PushResult, String>> {
+        async move {
+            // 本地提交已在被拒绝的那次推送中完成，这里只需推送当前分支
+            crate::git_sync::push_current_branch(&self.workspace_root)?;
+            Ok(PushResult {
+                success: true,
+                files_pushed: 1,
+                commit_hash: String::new(),
+                message: "Push 成功".to_string(),
+            })
+        }
+        .boxed()
+    }
+}
+
+struct FileBackend<S: RemoteStore> {
+    engine: FileSyncEngine<S>,
+    workspace_root: PathBuf,
+    app_handle: AppHandle,
+}
+
+impl<S: RemoteStore> SyncBackend for FileBackend<S> {
+    fn target(&self) -> String {
+        self.engine.location()
+    }
+
+    fn pull(&self) -> BoxFuture<'_, Result<PullResult, String>> {
+        self.engine.pull().boxed()
+    }
+
+    fn changed_files(&self, _result: &PullResult) -> Result<ChangedFilesByStatus, String> {
+        Ok(self.engine.take_changes())
+    }
+
+    fn has_local_changes(&self) -> Result<bool, String> {
+        self.engine.has_local_changes()
+    }
+
+    fn push<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<PushResult, String>> {
+        async move {
+            crate::sync_data::export_sync_bundle(&self.app_handle, &self.workspace_root)?;
+            self.engine.push(message).await
+        }
+        .boxed()
+    }
+
+    fn push_after_pull(&self) -> BoxFuture<'_, Result<PushResult, String>> {
+        // 与 Git 后端一致，重试只发生在自动同步中
+        self.engine.push("Auto sync").boxed()
+    }
+}
+
+fn file_backend<S: RemoteStore + 'static>(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+    store: S,
+) -> Result<Arc<dyn SyncBackend>, String> {
+    let device = crate::sync_data::ensure_device_id(app_handle)?;
+    let state_path = file_sync_state_path(app_handle, workspace_root, &store.location());
+    Ok(Arc::new(FileBackend {
+        engine: FileSyncEngine::new(store, workspace_root.to_path_buf(), state_path, device),
+        workspace_root: workspace_root.to_path_buf(),
+        app_handle: app_handle.clone(),
+    }))
+}
+
+// 同一工作区同步到不同目标时各自保存基线
+fn file_sync_state_path(app_handle: &AppHandle, workspace_root: &Path, location: &str) -> PathBuf {
+    let key = sha256_hex(format!("{}\n{}", workspace_root.display(), location).as_bytes());
+    crate::json_config::get_data_dir(app_handle)
+        .join("state")
+        .join("file-sync")
+        .join(format!("{}.json", &key[..16]))
+}
+
+fn git_settings(app_handle: &AppHandle) -> Result<crate::app_config::GitSettings, String> {
+    let config_state = app_handle
+        .try_state::<Arc<std::sync::RwLock<crate::app_config::AppConfigManager>>>()
+        .ok_or("AppConfigManager 未初始化".to_string())?;
+    let manager = config_state
+        .read()
+        .map_err(|e| format!("获取配置锁失败: {}", e))?;
+    let settings = manager.get_git_settings().clone();
+    if settings.remote_url.trim().is_empty() {
+        return Err("请先在 Git 设置中填写远程仓库地址".to_string());
+    }
+    Ok(settings)
+}
+
+fn open_mirror(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+    branch: &str,
+) -> Result<GitMirrorStore, String> {
+    let settings = git_settings(app_handle)?;
+    GitMirrorStore::open(
+        encrypted::mirror_dir(app_handle, workspace_root),
+        settings.remote_url.trim(),
+        branch,
+        &settings.user_name,
+        &settings.user_email,
+    )
+}
+
+fn encrypted_backend(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+) -> Result<Arc<dyn SyncBackend>, String> {
+    let local_key = encrypted::load_local_key(app_handle, workspace_root)?
+        .ok_or("尚未设置加密同步口令".to_string())?;
+    let mirror = open_mirror(app_handle, workspace_root, &local_key.branch)?;
+    file_backend(
+        app_handle,
+        workspace_root,
+        EncryptedStore::new(mirror, local_key.key, local_key.key_info),
+    )
+}
+
+fn build_backend(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+    config: &SyncBackendConfig,
+) -> Result<Arc<dyn SyncBackend>, String> {
+    config.validate(workspace_root)?;
+    match config.kind {
+        SyncBackendKind::Git => Ok(Arc::new(GitBackend {
+            workspace_root: workspace_root.to_path_buf(),
+            app_handle: app_handle.clone(),
+        })),
+        SyncBackendKind::Webdav => file_backend(
+            app_handle,
+            workspace_root,
+            WebdavStore::new(
+                &config.webdav.url,
+                &config.webdav.username,
+                &config.webdav.password,
+            )?,
+        ),
+        SyncBackendKind::Folder => file_backend(
+            app_handle,
+            workspace_root,
+            FolderStore::new(PathBuf::from(&config.folder.path)),
+        ),
+        SyncBackendKind::EncryptedGit => encrypted_backend(app_handle, workspace_root),
+    }
+}
+
+/// 按当前配置创建同步后端
+pub(crate) fn create_backend(
+    app_handle: &AppHandle,
+    workspace_root: &Path,
+) -> Result<Arc<dyn SyncBackend>, String> {
+    build_backend(app_handle, workspace_root, &read_backend_config(app_handle))
+}
+
+// ============= Tauri 命令 =============
+
+fn require_git_sync_plugin(app_handle: &AppHandle) -> Result<(), String> {
+    crate::app_config::require_plugin_enabled(app_handle, "git-sync")
+}
+
+/// 手动同步结果
+#[derive(Debug, Serialize)]
+pub struct SyncNowResult {
+    pub pull: PullResult,
+    pub push: PushResult,
+}
+
+/// 获取同步后端配置
+#[tauri::command]
+pub fn get_sync_backend_config(app_handle: AppHandle) -> Result<SyncBackendConfig, String> {
+    require_git_sync_plugin(&app_handle)?;
+    Ok(read_backend_config(&app_handle))
+}
+
+/// 保存同步后端配置；自动同步运行中时按新配置重启
+#[tauri::command]
+pub fn set_sync_backend_config(
+    app_handle: AppHandle,
+    config: SyncBackendConfig,
+) -> Result<SyncBackendConfig, String> {
+    require_git_sync_plugin(&app_handle)?;
+    let config = config.normalized();
+    if let Some(workspace_root) = crate::json_config::get_workspace_root(&app_handle)? {
+        config.validate(&workspace_root)?;
+        if config.kind == SyncBackendKind::EncryptedGit
+            && encrypted::load_local_key(&app_handle, &workspace_root)?.is_none()
+        {
+            return Err("请先设置加密同步口令".to_string());
+        }
+    }
+    crate::json_config::set_app_config_value(&app_handle, SYNC_BACKEND_CONFIG_KEY, &config)?;
+    info!("🔄 [SyncBackend] 同步后端已更新: {:?}", config.kind);
+
+    if crate::git_sync::get_auto_sync_status_command(app_handle.clone()).unwrap_or(false) {
+        crate::git_sync::start_auto_sync_command(app_handle)?;
+    }
+    Ok(config)
+}
+
+/// 测试 WebDAV 或同步文件夹能否访问，返回远端已有文件数的说明
+#[tauri::command]
+pub async fn test_sync_backend(
+    app_handle: AppHandle,
+    config: SyncBackendConfig,
+) -> Result<String, String> {
+    require_git_sync_plugin(&app_handle)?;
+    let config = config.normalized();
+    let workspace_root =
+        crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
+    config.validate(&workspace_root)?;
+
+    let manifest = match config.kind {
+        SyncBackendKind::Git | SyncBackendKind::EncryptedGit => {
+            return Err("Git 同步请在仓库设置中测试连接".to_string())
+        }
+        SyncBackendKind::Webdav => {
+            let store = WebdavStore::new(
+                &config.webdav.url,
+                &config.webdav.username,
+                &config.webdav.password,
+            )?;
+            read_manifest(&store).await?
+        }
+        SyncBackendKind::Folder => {
+            read_manifest(&FolderStore::new(PathBuf::from(&config.folder.path))).await?
+        }
+    };
+
+    let files = manifest
+        .entries
+        .values()
+        .filter(|entry| !entry.deleted)
+        .count();
+    Ok(if files == 0 {
+        "连接成功，远端还没有同步过的文件".to_string()
+    } else {
+        format!("连接成功，远端已有 {} 个文件", files)
+    })
+}
+
+/// 立即同步一次（WebDAV、文件夹、加密 Git 后端）：先拉取远端改动，再推送本机改动
+#[tauri::command]
+pub async fn sync_backend_now(app_handle: AppHandle) -> Result<SyncNowResult, String> {
+    require_git_sync_plugin(&app_handle)?;
+    let workspace_root =
+        crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
+    let config = read_backend_config(&app_handle);
+    if config.kind == SyncBackendKind::Git {
+        return Err("Git 同步请使用拉取和推送".to_string());
+    }
+
+    // 自动同步在运行时复用它的后端，拉取和推送与后台同步互斥
+    let running_backend = app_handle
+        .try_state::<Arc<Mutex<Option<crate::git_sync::AutoSyncManager>>>>()
+        .and_then(|state| {
+            state
+                .lock()
+                .ok()
+                .and_then(|manager| manager.as_ref().map(|manager| manager.backend()))
+        });
+    let backend = build_backend(&app_handle, &workspace_root, &config)?;
+    let backend = match running_backend {
+        Some(running) if running.target() == backend.target() => running,
+        _ => backend,
+    };
+
+    let pull = backend.pull().await?;
+    crate::git_sync::import_portable_sync_config(&app_handle, &workspace_root);
+    let changes = backend.changed_files(&pull)?;
+    crate::git_sync::refresh_pulled_files(&app_handle, &workspace_root, &changes);
+    let push = backend.push("Manual sync").await?;
+    Ok(SyncNowResult { pull, push })
+}
+
+// ============= 端到端加密同步 =============
+
+/// 加密同步状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedSyncStatus {
+    pub enabled: bool,
+    pub key_configured: bool,
+    pub branch: Option<String>,
+}
+
+fn default_branch(workspace_root: &Path) -> String {
+    crate::git_sync::get_current_branch(workspace_root).unwrap_or_else(|_| "main".to_string())
+}
+
+// 切换到加密后端；自动同步原本在运行时按新后端重启
+fn enable_encrypted_backend(app_handle: &AppHandle, restart_auto_sync: bool) -> Result<(), String> {
+    let mut config = read_backend_config(app_handle);
+    config.kind = SyncBackendKind::EncryptedGit;
+    crate::json_config::set_app_config_value(app_handle, SYNC_BACKEND_CONFIG_KEY, &config)?;
+    info!("🔐 [SyncBackend] 已启用端到端加密同步");
+    if restart_auto_sync {
+        crate::git_sync::start_auto_sync_command(app_handle.clone())?;
+    }
+    Ok(())
+}
+
+/// 获取加密同步状态
+#[tauri::command]
+pub fn get_encrypted_sync_status(app_handle: AppHandle) -> Result<EncryptedSyncStatus, String> {
+    require_git_sync_plugin(&app_handle)?;
+    let workspace_root =
+        crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
+    let local_key = encrypted::load_local_key(&app_handle, &workspace_root)?;
+    Ok(EncryptedSyncStatus {
+        enabled: read_backend_config(&app_handle).kind == SyncBackendKind::EncryptedGit,
+        key_configured: local_key.is_some(),
+        branch: local_key.map(|local_key| local_key.branch),
+    })
+}
+
+/// 用口令启用加密同步：远程仓库已加密时校验口令并加入，远程仓库为空时生成新的密钥参数。
+/// 远程仓库已有明文内容时拒绝，需改用迁移。
+#[tauri::command]
+pub async fn setup_encrypted_sync(
+    app_handle: AppHandle,
+    passphrase: String,
+) -> Result<String, String> {
+    require_git_sync_plugin(&app_handle)?;
+    let workspace_root =
+        crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
+    let settings = git_settings(&app_handle)?;
+    let mirror_root = encrypted::mirror_dir(&app_handle, &workspace_root);
+    git_mirror::init_repo(&mirror_root, settings.remote_url.trim())?;
+    let branch = git_mirror::remote_default_branch(&mirror_root)
+        .unwrap_or_else(|| default_branch(&workspace_root));
+
+    let mirror = open_mirror(&app_handle, &workspace_root, &branch)?;
+    mirror.refresh().await?;
+    let (key_info, key, message) = match mirror.read(KEY_INFO_PATH).await? {
+        Some(bytes) => {
+            let key_info = KeyInfo::parse(&bytes)?;
+            let key = key_info.unlock(&passphrase)?;
+            (key_info, key, "口令正确，已加入现有的加密同步仓库")
+        }
+        None => {
+            if mirror.has_files() {
+                return Err("远程仓库中已有未加密的内容，请使用“迁移到加密同步”转换".to_string());
+            }
+            let (key_info, key) = KeyInfo::create(&passphrase)?;
+            (
+                key_info,
+                key,
+                "已启用加密同步，首次同步时会上传加密后的笔记",
+            )
+        }
+    };
+
+    encrypted::save_local_key(
+        &app_handle,
+        &workspace_root,
+        &LocalKey {
+            key,
+            key_info,
+            branch,
+        },
+    )?;
+    let restart =
+        crate::git_sync::get_auto_sync_status_command(app_handle.clone()).unwrap_or(false);
+    enable_encrypted_backend(&app_handle, restart)?;
+    Ok(message.to_string())
+}
+
+/// 把现有明文仓库一次性迁移为加密同步：加密上传所有同步范围内的文件，强制推送替换远端当前分支
+/// 的明文历史，并移除工作区仓库的 origin，之后只通过加密镜像同步。远端还有其他分支或标签时中止；
+/// 其他设备已完成迁移时校验口令后加入，不再改写远端历史。
+#[tauri::command]
+pub async fn migrate_to_encrypted_sync(
+    app_handle: AppHandle,
+    passphrase: String,
+) -> Result<PushResult, String> {
+    require_git_sync_plugin(&app_handle)?;
+    let workspace_root =
+        crate::json_config::get_workspace_root(&app_handle)?.ok_or("工作区未设置".to_string())?;
+    let settings = git_settings(&app_handle)?;
+    let branch = default_branch(&workspace_root);
+    let mirror_root = encrypted::mirror_dir(&app_handle, &workspace_root);
+
+    // 迁移只替换同步分支，其他分支和标签里的明文历史会留在远端
+    let mirror = GitMirrorStore::for_migration(
+        mirror_root.clone(),
+        settings.remote_url.trim(),
+        &branch,
+        &settings.user_name,
+        &settings.user_email,
+    )?;
+    let leftover_refs = mirror.other_remote_refs()?;
+    if !leftover_refs.is_empty() {
+        return Err(format!(
+            "远程仓库中还有其他分支或标签，其中可能保存着明文历史，请先删除后再迁移: {}",
+            leftover_refs.join(", ")
+        ));
+    }
+    // 其他设备已经完成迁移时沿用远端的密钥参数，口令必须一致，且不能再覆盖远端的加密内容
+    let existing_key_info = match mirror.read_remote(KEY_INFO_PATH)? {
+        Some(bytes) => Some(KeyInfo::parse(&bytes)?),
+        None => None,
+    };
+    let joining = existing_key_info.is_some();
+    let (key_info, key) = match existing_key_info {
+        Some(key_info) => {
+            let key = key_info.unlock(&passphrase)?;
+            (key_info, key)
+        }
+        None => KeyInfo::create(&passphrase)?,
+    };
+    let mirror = if joining {
+        GitMirrorStore::open(
+            mirror_root,
+            settings.remote_url.trim(),
+            &branch,
+            &settings.user_name,
+            &settings.user_email,
+        )?
+    } else {
+        mirror
+    };
+
+    // 迁移期间停止自动同步，避免旧的 Git 后端继续推送明文
+    let auto_sync_running =
+        crate::git_sync::get_auto_sync_status_command(app_handle.clone()).unwrap_or(false);
+    if auto_sync_running {
+        crate::git_sync::stop_auto_sync_command(app_handle.clone())?;
+    }
+
+    let migrated = async {
+        let store = EncryptedStore::new(mirror, key.clone(), key_info.clone());
+        let backend = if joining {
+            let backend = file_backend(&app_handle, &workspace_root, store)?;
+            let pull = backend.pull().await?;
+            crate::git_sync::import_portable_sync_config(&app_handle, &workspace_root);
+            let changes = backend.changed_files(&pull)?;
+            crate::git_sync::refresh_pulled_files(&app_handle, &workspace_root, &changes);
+            backend
+        } else {
+            // 远端内容整体替换，旧的同步基线不再有效，所有文件重新上传
+            let state_path = file_sync_state_path(&app_handle, &workspace_root, &store.location());
+            if state_path.exists() {
+                std::fs::remove_file(&state_path)
+                    .map_err(|e| format!("清理同步基线失败: {}", e))?;
+            }
+            file_backend(&app_handle, &workspace_root, store)?
+        };
+        backend.push("Migrate to end-to-end encrypted sync").await
+    }
+    .await;
+    let result = match migrated {
+        Ok(result) => result,
+        Err(error) => {
+            if auto_sync_running {
+                let _ = crate::git_sync::start_auto_sync_command(app_handle.clone());
+            }
+            return Err(error);
+        }
+    };
+
+    encrypted::save_local_key(
+        &app_handle,
+        &workspace_root,
+        &LocalKey {
+            key,
+            key_info,
+            branch,
+        },
+    )?;
+    // 工作区仓库保留本地历史，但不再关联远端，避免之后误推送明文
+    let output =
+        crate::git_common::run_git_command(&workspace_root, &["remote", "remove", "origin"])?;
+    if !crate::git_common::is_git_success(&output) {
+        warn!(
+            "⚠️ [SyncBackend] 移除工作区仓库的 origin 失败: {}",
+            crate::git_common::get_git_stderr(&output)
+        );
+    }
+    enable_encrypted_backend(&app_handle, auto_sync_running)?;
+    if !joining {
+        warn!("⚠️ [SyncBackend] 远端分支已替换为加密内容；托管服务可能仍保留旧提交的缓存，敏感信息请另行轮换");
+    }
+    Ok(result)
+}
@@ -0,0 +1,436 @@
+// WebDAV 同步目标（Nextcloud、ownCloud、坚果云或任意 WebDAV 服务）
+//
+// 只用到 GET / PUT / DELETE / MKCOL：文件按相对路径整体读写，写入前逐级创建缺失的集合。
+// 同步清单用 If-Match / If-None-Match 条件写入，服务器不返回 ETag 时退化为先读后比。
+
+use super::file_sync::RemoteStore;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use reqwest::header::{HeaderName, ETAG, IF_MATCH, IF_NONE_MATCH};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+
+pub(crate) struct WebdavStore {
+    client: Client,
+    base_url: Url,
+    username: String,
+    password: String,
+    // 本次运行中已确认存在的目录，避免每次上传都重复 MKCOL
+    known_dirs: Mutex<HashSet<String>>,
+}
+
+impl WebdavStore {
+    pub(crate) fn new(url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let base_url = Url::parse(url).map_err(|e| format!("WebDAV 地址无效: {}", e))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err("WebDAV 地址必须以 http:// 或 https:// 开头".to_string());
+        }
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| format!("创建 WebDAV 客户端失败: {}", e))?;
+        Ok(Self {
+            client,
+            base_url,
+            username: username.to_string(),
+            password: password.to_string(),
+            known_dirs: Mutex::new(HashSet::new()),
+        })
+    }
+
+    // 按路径段拼接地址，中文和空格等由 Url 负责转义
+    fn url_for(&self, path: &str) -> Result<Url, String> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| "WebDAV 地址无效".to_string())?
+            .pop_if_empty()
+            .extend(path.split('/').filter(|segment| !segment.is_empty()));
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        if self.username.is_empty() {
+            request
+        } else {
+            request.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Vec<u8>>,
+    ) -> Result<Response, String> {
+        self.send_conditional(method, path, body, None).await
+    }
+
+    async fn send_conditional(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Vec<u8>>,
+        condition: Option<(HeaderName, String)>,
+    ) -> Result<Response, String> {
+        let mut request = self.request(method.clone(), self.url_for(path)?);
+        if let Some((name, value)) = condition {
+            request = request.header(name, value);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV {} {} 请求失败: {}", method, path, e))?;
+        if matches!(
+            response.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ) {
+            return Err(format!(
+                "WebDAV 认证失败（HTTP {}），请检查用户名和应用密码",
+                response.status().as_u16()
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn ensure_parent_dirs(&self, path: &str) -> Result<(), String> {
+        let Some((parent, _)) = path.rsplit_once('/') else {
+            return Ok(());
+        };
+        let mut current = String::new();
+        for segment in parent.split('/') {
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(segment);
+            if self
+                .known_dirs
+                .lock()
+                .map(|dirs| dirs.contains(&current))
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            let method = Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+            let response = self.send(method, &current, None).await?;
+            // 405 表示目录已存在
+            if !response.status().is_success()
+                && response.status() != StatusCode::METHOD_NOT_ALLOWED
+            {
+                return Err(format!(
+                    "WebDAV 创建目录 {} 失败: HTTP {}",
+                    current,
+                    response.status().as_u16()
+                ));
+            }
+            if let Ok(mut dirs) = self.known_dirs.lock() {
+                dirs.insert(current.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RemoteStore for WebdavStore {
+    fn location(&self) -> String {
+        let mut url = self.base_url.clone();
+        let _ = url.set_password(None);
+        if self.username.is_empty() {
+            format!("webdav:{}", url)
+        } else {
+            format!("webdav:{}@{}", self.username, url)
+        }
+    }
+
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+        async move {
+            let response = self.send(Method::GET, path, None).await?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            if !response.status().is_success() {
+                return Err(format!(
+                    "WebDAV 读取 {} 失败: HTTP {}",
+                    path,
+                    response.status().as_u16()
+                ));
+            }
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| format!("WebDAV 读取 {} 失败: {}", path, e))?;
+            Ok(Some(bytes.to_vec()))
+        }
+        .boxed()
+    }
+
+    fn write<'a>(&'a self, path: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            self.ensure_parent_dirs(path).await?;
+            let response = self.send(Method::PUT, path, Some(bytes)).await?;
+            if !response.status().is_success() {
+                return Err(format!(
+                    "WebDAV 上传 {} 失败: HTTP {}",
+                    path,
+                    response.status().as_u16()
+                ));
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn write_if_unchanged<'a>(
+        &'a self,
+        path: &'a str,
+        expected: Option<&'a [u8]>,
+        bytes: Vec<u8>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        async move {
+            let response = self.send(Method::GET, path, None).await?;
+            let (current, etag) = if response.status() == StatusCode::NOT_FOUND {
+                (None, None)
+            } else if response.status().is_success() {
+                let etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| format!("WebDAV 读取 {} 失败: {}", path, e))?;
+                (Some(bytes.to_vec()), etag)
+            } else {
+                return Err(format!(
+                    "WebDAV 读取 {} 失败: HTTP {}",
+                    path,
+                    response.status().as_u16()
+                ));
+            };
+            if current.as_deref() != expected {
+                return Ok(false);
+            }
+
+            let condition = match (&current, etag) {
+                (None, _) => Some((IF_NONE_MATCH, "*".to_string())),
+                (Some(_), Some(etag)) => Some((IF_MATCH, etag)),
+                (Some(_), None) => None,
+            };
+            self.ensure_parent_dirs(path).await?;
+            let response = self
+                .send_conditional(Method::PUT, path, Some(bytes), condition)
+                .await?;
+            if response.status() == StatusCode::PRECONDITION_FAILED {
+                return Ok(false);
+            }
+            if !response.status().is_success() {
+                return Err(format!(
+                    "WebDAV 上传 {} 失败: HTTP {}",
+                    path,
+                    response.status().as_u16()
+                ));
+            }
+            Ok(true)
+        }
+        .boxed()
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            let response = self.send(Method::DELETE, path, None).await?;
+            if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+                return Err(format!(
+                    "WebDAV 删除 {} 失败: HTTP {}",
+                    path,
+                    response.status().as_u16()
+                ));
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_http::{spawn_stub_server, StubRequest, StubResponse};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    // user:secret
+    const AUTHORIZATION: &str = "Basic dXNlcjpzZWNyZXQ=";
+
+    // 最小的 WebDAV 桩服务状态：文件和集合都以请求中的原始（已转义）路径为键
+    #[derive(Default)]
+    struct Dav {
+        files: HashMap<String, Vec<u8>>,
+        dirs: HashSet<String>,
+        requests: Vec<String>,
+        // 下一次 PUT 前模拟其他设备抢先写入
+        race_next_put: bool,
+    }
+
+    fn etag(bytes: &[u8]) -> String {
+        format!("\"{}\"", &crate::text_utils::sha256_hex(bytes)[..16])
+    }
+
+    fn handle(dav: &mut Dav, request: StubRequest) -> StubResponse {
+        let path = request.path.clone();
+        match request.method.as_str() {
+            "GET" => match dav.files.get(&path) {
+                Some(bytes) => StubResponse::bytes(200, "application/octet-stream", bytes.clone())
+                    .header("ETag", etag(bytes)),
+                None => StubResponse::status(404),
+            },
+            "PUT" => {
+                let parent = path
+                    .rsplit_once('/')
+                    .map(|(parent, _)| parent)
+                    .unwrap_or("");
+                if parent != "/dav" && !dav.dirs.contains(parent) {
+                    return StubResponse::status(409);
+                }
+                if std::mem::take(&mut dav.race_next_put) {
+                    dav.files.insert(path.clone(), b"other device".to_vec());
+                }
+                let current = dav.files.get(&path).map(|bytes| etag(bytes));
+                let precondition_failed =
+                    match (request.header("if-match"), request.header("if-none-match")) {
+                        (Some(expected), _) => current.as_deref() != Some(expected),
+                        (_, Some("*")) => current.is_some(),
+                        _ => false,
+                    };
+                if precondition_failed {
+                    return StubResponse::status(412);
+                }
+                dav.files.insert(path, request.body);
+                StubResponse::status(201)
+            }
+            "DELETE" => match dav.files.remove(&path) {
+                Some(_) => StubResponse::status(204),
+                None => StubResponse::status(404),
+            },
+            "MKCOL" => {
+                if dav.dirs.insert(path) {
+                    StubResponse::status(201)
+                } else {
+                    StubResponse::status(405)
+                }
+            }
+            _ => StubResponse::status(501),
+        }
+    }
+
+    fn spawn_dav() -> (String, Arc<Mutex<Dav>>) {
+        let dav = Arc::new(Mutex::new(Dav::default()));
+        let state = dav.clone();
+        let url = spawn_stub_server(move |request| {
+            let mut dav = state.lock().unwrap();
+            dav.requests
+                .push(format!("{} {}", request.method, request.path));
+            if request.header("authorization") != Some(AUTHORIZATION) {
+                return StubResponse::status(401);
+            }
+            handle(&mut dav, request)
+        });
+        (format!("{}/dav/", url), dav)
+    }
+
+    #[test]
+    fn reads_writes_and_removes_with_escaped_paths() {
+        let (url, dav) = spawn_dav();
+        let store = WebdavStore::new(&url, "user", "secret").unwrap();
+        // 目录已存在时 MKCOL 返回 405，视为成功
+        dav.lock().unwrap().dirs.insert("/dav/notes".to_string());
+
+        tauri::async_runtime::block_on(async {
+            assert_eq!(store.read("missing.md").await.unwrap(), None);
+
+            store.write("notes/a.md", b"a".to_vec()).await.unwrap();
+            store
+                .write("笔记/a b#1.md", "内容".as_bytes().to_vec())
+                .await
+                .unwrap();
+            assert_eq!(
+                store.read("笔记/a b#1.md").await.unwrap().as_deref(),
+                Some("内容".as_bytes())
+            );
+
+            store.remove("笔记/a b#1.md").await.unwrap();
+            // 文件不存在时删除视为成功
+            store.remove("笔记/a b#1.md").await.unwrap();
+            assert_eq!(store.read("笔记/a b#1.md").await.unwrap(), None);
+        });
+
+        let requests = dav.lock().unwrap().requests.clone();
+        assert_eq!(
+            requests,
+            vec![
+                "GET /dav/missing.md",
+                "MKCOL /dav/notes",
+                "PUT /dav/notes/a.md",
+                "MKCOL /dav/%E7%AC%94%E8%AE%B0",
+                "PUT /dav/%E7%AC%94%E8%AE%B0/a%20b%231.md",
+                "GET /dav/%E7%AC%94%E8%AE%B0/a%20b%231.md",
+                "DELETE /dav/%E7%AC%94%E8%AE%B0/a%20b%231.md",
+                "DELETE /dav/%E7%AC%94%E8%AE%B0/a%20b%231.md",
+                "GET /dav/%E7%AC%94%E8%AE%B0/a%20b%231.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn conditional_writes_detect_other_writers() {
+        let (url, dav) = spawn_dav();
+        let store = WebdavStore::new(&url, "user", "secret").unwrap();
+        tauri::async_runtime::block_on(async {
+            assert!(store
+                .write_if_unchanged("manifest.json", None, b"v1".to_vec())
+                .await
+                .unwrap());
+            // 远端已有内容，与期望的“不存在”不符
+            assert!(!store
+                .write_if_unchanged("manifest.json", None, b"v2".to_vec())
+                .await
+                .unwrap());
+            assert!(store
+                .write_if_unchanged("manifest.json", Some(b"v1"), b"v2".to_vec())
+                .await
+                .unwrap());
+
+            // 读取之后、写入之前被其他设备改写：If-Match 失败返回 412
+            dav.lock().unwrap().race_next_put = true;
+            assert!(!store
+                .write_if_unchanged("manifest.json", Some(b"v2"), b"v3".to_vec())
+                .await
+                .unwrap());
+            assert_eq!(
+                store.read("manifest.json").await.unwrap().as_deref(),
+                Some(&b"other device"[..])
+            );
+        });
+    }
+
+    #[test]
+    fn reports_authentication_failures() {
+        let (url, _dav) = spawn_dav();
+        let store = WebdavStore::new(&url, "user", "wrong").unwrap();
+        tauri::async_runtime::block_on(async {
+            let read = store.read("a.md").await.unwrap_err();
+            assert!(read.contains("认证失败"), "{}", read);
+            let write = store.write("a.md", b"a".to_vec()).await.unwrap_err();
+            assert!(write.contains("HTTP 401"), "{}", write);
+        });
+        assert!(WebdavStore::new("ftp://example.com/dav", "", "").is_err());
+    }
+}
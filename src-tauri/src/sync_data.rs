@@ -17,13 +17,16 @@ pub const CONTENT_SCHEMA_VERSION: u32 = 1;
 pub const PREFERENCE_SCHEMA_VERSION: u32 = 1;
 const MINIMUM_SYNC_APP_VERSION: &str = "2.1.43";
 
-/// 工作区内唯一允许进入 Git 的应用配置文件。
+/// 工作区内允许进入 Git 的可移植配置文件。
 ///
 /// `workspace.json` 同时保存界面布局和本机 Git 开关，不能直接提交；同步数据
 /// 因此使用独立、精简的投影文件，避免把本机状态混进远端仓库。
 const SYNC_FILE: &str = ".snippets-code/sync.json";
 
-const PROTOCOL_FILES: &[&str] = &[SYNC_FILE];
+/// 代码文件片段的 tags/favorite/kind 等元数据（代码文件没有 Frontmatter），与笔记一起同步。
+const SNIPPET_METADATA_FILE: &str = ".snippets-code/snippets.json";
+
+const PROTOCOL_FILES: &[&str] = &[SYNC_FILE, SNIPPET_METADATA_FILE];
 
 const PREFERENCE_KEYS: &[&str] = &[
     "appearance.theme",
@@ -202,7 +205,7 @@ fn read_sync_bundle(workspace_root: &Path) -> Result<Option<SyncBundle>, String>
     Ok(None)
 }
 
-fn ensure_device_id(app_handle: &AppHandle) -> Result<String, String> {
+pub(crate) fn ensure_device_id(app_handle: &AppHandle) -> Result<String, String> {
     let path = crate::json_config::get_data_dir(app_handle)
         .join("state")
         .join("sync")
@@ -571,10 +574,7 @@ pub fn export_sync_bundle(
     info!("✅ [SyncData] 已导出可移植配置，vault={}", vault_id);
     Ok(SyncExportReport {
         vault_id,
-        files_written: PROTOCOL_FILES
-            .iter()
-            .map(|path| (*path).to_string())
-            .collect(),
+        files_written: vec![SYNC_FILE.to_string()],
         managed_attachment_roots: managed_roots,
     })
 }
@@ -1364,7 +1364,11 @@ pub fn is_attachment_path(workspace_root: &Path, path: &str) -> bool {
     })
 }
 
-fn git_stage_file(workspace_root: &Path, stage: u8, path: &str) -> Result<Option<Vec<u8>>, String> {
+pub(crate) fn git_stage_file(
+    workspace_root: &Path,
+    stage: u8,
+    path: &str,
+) -> Result<Option<Vec<u8>>, String> {
     let output = crate::git_common::git_command()
         .args(["show", &format!(":{}:{}", stage, path)])
         .current_dir(workspace_root)
@@ -1642,3 +1646,26 @@ pub fn resolve_sync_protocol_conflicts(
     );
     Ok(true)
 }
+
+/// 非 Git 后端两端都修改了 sync.json 时按字段时钟合并，结果写回工作区。
+///
+/// `base` 为上次同步时的版本，缺失或无法解析时按无基线合并。
+pub(crate) fn merge_remote_sync_file(
+    workspace_root: &Path,
+    base: Option<&[u8]>,
+    theirs: &[u8],
+) -> Result<(), String> {
+    let path = path_for(workspace_root, SYNC_FILE);
+    let theirs = parse_sync_bundle_bytes(theirs, "远端")?;
+    let merged = match fs::read(&path) {
+        Ok(ours) => {
+            let ours = parse_sync_bundle_bytes(&ours, "本机")?;
+            let base = base.and_then(|bytes| parse_sync_bundle_bytes(bytes, "基线").ok());
+            merge_sync_bundles(base.as_ref(), &ours, &theirs)?
+        }
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => theirs,
+        Err(error) => return Err(format!("读取 sync.json 失败: {}", error)),
+    };
+    write_json_atomic(&path, &merged)?;
+    validate_protocol_file_content(workspace_root, SYNC_FILE)
+}
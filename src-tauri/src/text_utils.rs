@@ -0,0 +1,35 @@
+// 文本处理通用工具
+
+use sha2::{Digest, Sha256};
+
+/// 中日韩表意文字、假名和谚文
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // 平假名、片假名
+        | 0x3400..=0x4DBF    // CJK 扩展 A
+        | 0x4E00..=0x9FFF    // CJK 统一表意文字
+        | 0xAC00..=0xD7AF    // 谚文音节
+        | 0xF900..=0xFAFF    // CJK 兼容表意文字
+        | 0x20000..=0x2FA1F  // CJK 扩展 B 及以后
+    )
+}
+
+/// 按字符（而非字节）截断，返回不超过 `max_chars` 个字符的前缀
+pub(crate) fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// SHA-256 摘要的小写十六进制表示
+pub(crate) fn sha256_hex(bytes: impl AsRef<[u8]>) -> String {
+    hex::encode(Sha256::digest(bytes.as_ref()))
+}
+
+/// 转义 LIKE 模式中的通配符，配合 `ESCAPE '\'` 使用
+pub(crate) fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
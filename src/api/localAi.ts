@@ -1,7 +1,21 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import type { MarkdownFile } from '@/types/models';
+
+export type LocalAiProviderKind =
+  | 'llama-server'
+  | 'openai-compatible'
+  | 'ollama';
 
 export interface LocalAiConfig {
+  // 缺省为 0（旧版配置），后端读取时迁移并写回
+  configVersion?: number;
+  provider?: LocalAiProviderKind;
+  // 外部服务地址（OpenAI 兼容或 Ollama）
+  providerBaseUrl?: string | null;
+  apiKey?: string | null;
+  // 外部服务使用的模型名
+  providerModel?: string | null;
   modelDir: string;
   modelPath?: string | null;
   mmprojPath?: string | null;
@@ -28,8 +42,18 @@ export interface LocalAiConfig {
   repeatLastN: number;
   maxTokens: number;
   requestTimeoutSecs: number;
+  embeddings?: boolean;
+  embeddingBaseUrl?: string | null;
+  toolCalling?: boolean;
+  // 对话超出上下文窗口时的压缩策略
+  contextStrategy?: LocalAiContextStrategy;
 }
 
+export type LocalAiContextStrategy =
+  | 'drop-oldest'
+  | 'summarize'
+  | 'pin-important';
+
 export interface LocalAiRuntimeStatus {
   available: boolean;
   path?: string;
@@ -46,9 +70,31 @@ export interface LocalAiModelScan {
   selectedModelPath?: string;
   selectedMmprojPath?: string;
   message?: string;
+  models: LocalAiGgufInfo[];
+  recommendedCtxSize?: number;
+  mmprojWarning?: string;
+}
+
+export interface LocalAiGgufInfo {
+  path: string;
+  fileSize: number;
+  ggufVersion?: number;
+  architecture?: string;
+  name?: string;
+  sizeLabel?: string;
+  parameterCount?: number;
+  quantization?: string;
+  contextLength?: number;
+  embeddingLength?: number;
+  chatTemplate?: string;
+  isVisionProjector: boolean;
+  projectorType?: string;
+  projectionDim?: number;
+  error?: string;
 }
 
 export interface LocalAiServiceStatus {
+  provider: LocalAiProviderKind;
   running: boolean;
   healthy: boolean;
   pid?: number;
@@ -85,6 +131,29 @@ export interface LocalAiChatRequest {
   temperature?: number;
   enableThinking?: boolean;
   maxTokens?: number;
+  workspaceContext?: boolean;
+  enableTools?: boolean;
+}
+
+export interface LocalAiChatSource {
+  index: number;
+  noteId: string;
+  filePath: string;
+  title: string;
+  startLine: number;
+  endLine: number;
+  kind: 'text' | 'code';
+  excerpt: string;
+}
+
+export interface LocalAiToolInvocation {
+  id: string;
+  name: string;
+  arguments: unknown;
+  status: 'pending' | 'ok' | 'error' | 'rejected';
+  requiresConfirmation: boolean;
+  result?: string;
+  error?: string;
 }
 
 export interface LocalAiChatTurn {
@@ -92,6 +161,8 @@ export interface LocalAiChatTurn {
   role: string;
   content: string;
   createdAt: string;
+  sources?: LocalAiChatSource[];
+  toolCalls?: LocalAiToolInvocation[];
 }
 
 export interface LocalAiChatHistory {
@@ -100,6 +171,37 @@ export interface LocalAiChatHistory {
   createdAt: string;
   updatedAt: string;
   turns: LocalAiChatTurn[];
+  pinned?: boolean;
+  tags?: string[];
+  notePath?: string | null;
+}
+
+export interface LocalAiChatConversation {
+  id: string;
+  title: string;
+  createdAt: string;
+  updatedAt: string;
+  pinned: boolean;
+  tags: string[];
+  messageCount: number;
+  preview: string;
+  notePath?: string | null;
+}
+
+export interface LocalAiChatHistoryPage {
+  items: LocalAiChatConversation[];
+  total: number;
+  offset: number;
+  limit: number;
+}
+
+export interface LocalAiChatSearchHit {
+  conversationId: string;
+  title: string;
+  turnId: string;
+  role: string;
+  excerpt: string;
+  createdAt: string;
 }
 
 export interface LocalAiPickedAttachment {
@@ -121,6 +223,8 @@ export interface LocalAiPickedAttachment {
 
 export interface LocalAiChatResponse {
   content: string;
+  sources?: LocalAiChatSource[];
+  toolCalls?: LocalAiToolInvocation[];
 }
 
 export interface LocalAiChatStreamStats {
@@ -131,19 +235,57 @@ export interface LocalAiChatStreamStats {
   generationTimeMs?: number;
   tokensPerSecond?: number;
   finishReason?: string;
+  // 发送前统计的提示词 token 数（已计入历史压缩）
+  estimatedPromptTokens?: number;
+  promptBudget?: number;
+  compactedMessages?: number;
+  compactionStrategy?: LocalAiContextStrategy;
 }
 
 export interface LocalAiChatStreamEvent {
   requestId: string;
-  event: 'delta' | 'stats' | 'done' | 'error';
+  event: 'delta' | 'stats' | 'sources' | 'tool' | 'done' | 'error';
   content?: string;
   error?: string;
   stats?: LocalAiChatStreamStats;
+  sources?: LocalAiChatSource[];
+  toolCall?: LocalAiToolInvocation;
+}
+
+export interface LocalAiPromptTemplate {
+  command: string;
+  title: string;
+  description?: string;
+  template: string;
+  // 来自工作区 kind: prompt 笔记时的相对路径
+  path?: string;
+  builtin: boolean;
+  usesSelection: boolean;
+}
+
+export interface LocalAiSnippetActionRequest {
+  filePath: string;
+  command: string;
+  selection?: string;
+  input?: string;
+  temperature?: number;
+  maxTokens?: number;
+  enableThinking?: boolean;
+}
+
+export interface LocalAiSnippetActionSaveRequest {
+  filePath: string;
+  command: string;
+  content: string;
+  target: 'note' | 'version';
 }
 
 export interface LocalAiChatStreamOptions {
   requestId?: string;
   onStats?: (stats: LocalAiChatStreamStats) => void;
+  onSources?: (sources: LocalAiChatSource[]) => void;
+  // status 为 pending 时需调用 respondLocalAiToolCall 确认或拒绝
+  onToolCall?: (invocation: LocalAiToolInvocation) => void;
 }
 
 export async function getLocalAiConfig(): Promise<LocalAiConfig> {
@@ -164,6 +306,20 @@ export async function scanLocalAiModels(
   });
 }
 
+export interface LocalAiProviderModel {
+  id: string;
+  size?: number;
+  details?: string;
+}
+
+export async function listLocalAiModels(
+  config?: LocalAiConfig
+): Promise<LocalAiProviderModel[]> {
+  return await invoke<LocalAiProviderModel[]>('local_ai_list_models', {
+    config: config ?? null
+  });
+}
+
 export async function getLocalAiRuntimeStatus(): Promise<LocalAiRuntimeStatus> {
   return await invoke<LocalAiRuntimeStatus>('local_ai_get_runtime_status');
 }
@@ -200,24 +356,33 @@ export function createLocalAiStreamRequestId(): string {
     : `local-ai-${Date.now()}-${Math.random().toString(16).slice(2)}`;
 }
 
+function listenChatStream(
+  requestId: string,
+  onDelta: (content: string) => void,
+  options: LocalAiChatStreamOptions
+) {
+  return listen<LocalAiChatStreamEvent>('local-ai-chat-stream', (event) => {
+    const payload = event.payload;
+    if (payload.requestId !== requestId) return;
+    if (payload.event === 'delta' && payload.content) {
+      onDelta(payload.content);
+    } else if (payload.event === 'stats' && payload.stats) {
+      options.onStats?.(payload.stats);
+    } else if (payload.event === 'sources' && payload.sources) {
+      options.onSources?.(payload.sources);
+    } else if (payload.event === 'tool' && payload.toolCall) {
+      options.onToolCall?.(payload.toolCall);
+    }
+  });
+}
+
 export async function streamChatWithLocalAi(
   request: LocalAiChatRequest,
   onDelta: (content: string) => void,
   options: LocalAiChatStreamOptions = {}
 ): Promise<LocalAiChatResponse> {
   const requestId = options.requestId ?? createLocalAiStreamRequestId();
-  const unlisten = await listen<LocalAiChatStreamEvent>(
-    'local-ai-chat-stream',
-    (event) => {
-      const payload = event.payload;
-      if (payload.requestId !== requestId) return;
-      if (payload.event === 'delta' && payload.content) {
-        onDelta(payload.content);
-      } else if (payload.event === 'stats' && payload.stats) {
-        options.onStats?.(payload.stats);
-      }
-    }
-  );
+  const unlisten = await listenChatStream(requestId, onDelta, options);
 
   try {
     return await invoke<LocalAiChatResponse>('local_ai_chat_stream', {
@@ -229,6 +394,18 @@ export async function streamChatWithLocalAi(
   }
 }
 
+export async function respondLocalAiToolCall(
+  requestId: string,
+  callId: string,
+  approved: boolean
+): Promise<boolean> {
+  return await invoke<boolean>('local_ai_respond_tool_call', {
+    requestId,
+    callId,
+    approved
+  });
+}
+
 export async function cancelLocalAiChatStream(
   requestId: string
 ): Promise<boolean> {
@@ -250,18 +427,19 @@ export async function readLocalAiAttachmentFiles(
 
 export async function saveLocalAiChatHistory(
   history: LocalAiChatHistory
-): Promise<LocalAiChatHistory[]> {
-  return await invoke<LocalAiChatHistory[]>('local_ai_save_chat_history', {
+): Promise<LocalAiChatHistory> {
+  return await invoke<LocalAiChatHistory>('local_ai_save_chat_history', {
     history
   });
 }
 
 export async function deleteLocalAiChatHistory(
   historyId: string
-): Promise<LocalAiChatHistory[]> {
-  return await invoke<LocalAiChatHistory[]>('local_ai_delete_chat_history', {
-    historyId
-  });
+): Promise<LocalAiChatHistoryPage> {
+  return await invoke<LocalAiChatHistoryPage>(
+    'local_ai_delete_chat_history',
+    { historyId }
+  );
 }
 
 export async function clearLocalAiChatHistories(): Promise<
@@ -270,6 +448,113 @@ export async function clearLocalAiChatHistories(): Promise<
   return await invoke<LocalAiChatHistory[]>('local_ai_clear_chat_histories');
 }
 
+export async function listLocalAiChatConversations(
+  options: {
+    offset?: number;
+    limit?: number;
+    tag?: string;
+    pinnedOnly?: boolean;
+  } = {}
+): Promise<LocalAiChatHistoryPage> {
+  return await invoke<LocalAiChatHistoryPage>(
+    'local_ai_list_chat_conversations',
+    {
+      offset: options.offset ?? null,
+      limit: options.limit ?? null,
+      tag: options.tag ?? null,
+      pinnedOnly: options.pinnedOnly ?? null
+    }
+  );
+}
+
+export async function getLocalAiChatHistory(
+  historyId: string
+): Promise<LocalAiChatHistory | null> {
+  return await invoke<LocalAiChatHistory | null>(
+    'local_ai_get_chat_history',
+    { historyId }
+  );
+}
+
+export async function searchLocalAiChatHistories(
+  query: string,
+  limit?: number
+): Promise<LocalAiChatSearchHit[]> {
+  return await invoke<LocalAiChatSearchHit[]>(
+    'local_ai_search_chat_histories',
+    { query, limit: limit ?? null }
+  );
+}
+
+export async function setLocalAiChatHistoryPinned(
+  historyId: string,
+  pinned: boolean
+): Promise<void> {
+  await invoke('local_ai_set_chat_history_pinned', { historyId, pinned });
+}
+
+export async function setLocalAiChatHistoryTags(
+  historyId: string,
+  tags: string[]
+): Promise<string[]> {
+  return await invoke<string[]>('local_ai_set_chat_history_tags', {
+    historyId,
+    tags
+  });
+}
+
+export async function exportLocalAiChatHistory(
+  historyId: string,
+  category?: string
+): Promise<string> {
+  return await invoke<string>('local_ai_export_chat_history', {
+    historyId,
+    category: category ?? null
+  });
+}
+
+export async function listLocalAiPrompts(): Promise<LocalAiPromptTemplate[]> {
+  return await invoke<LocalAiPromptTemplate[]>('local_ai_list_prompts');
+}
+
+// 展开 /命令 对应的模板，变量如 selection、language、input
+export async function renderLocalAiPrompt(
+  command: string,
+  variables?: Record<string, string>
+): Promise<string> {
+  return await invoke<string>('local_ai_render_prompt', {
+    command,
+    variables: variables ?? null
+  });
+}
+
+export async function runLocalAiSnippetAction(
+  request: LocalAiSnippetActionRequest,
+  onDelta: (content: string) => void,
+  options: LocalAiChatStreamOptions = {}
+): Promise<LocalAiChatResponse> {
+  const requestId = options.requestId ?? createLocalAiStreamRequestId();
+  const unlisten = await listenChatStream(requestId, onDelta, options);
+
+  try {
+    return await invoke<LocalAiChatResponse>('local_ai_run_snippet_action', {
+      request,
+      requestId
+    });
+  } finally {
+    unlisten();
+  }
+}
+
+// 返回新建笔记或新版本片段的文件路径
+export async function saveLocalAiSnippetActionResult(
+  request: LocalAiSnippetActionSaveRequest
+): Promise<string> {
+  return await invoke<string>('local_ai_save_snippet_action_result', {
+    request
+  });
+}
+
 export async function translateWithLocalAi(
   text: string,
   from: string,
@@ -277,3 +562,124 @@ export async function translateWithLocalAi(
 ): Promise<string> {
   return await invoke<string>('local_ai_translate', { text, from, to });
 }
+
+export interface LocalAiEmbeddingSyncStats {
+  files: number;
+  chunks: number;
+  embedded: number;
+  reused: number;
+  removed: number;
+}
+
+export type LocalAiSemanticSearchMode = 'semantic' | 'hybrid';
+
+export async function refreshLocalAiEmbeddings(): Promise<
+  LocalAiEmbeddingSyncStats
+> {
+  return await invoke<LocalAiEmbeddingSyncStats>(
+    'local_ai_refresh_embeddings'
+  );
+}
+
+export async function semanticSearchWithLocalAi(
+  query: string,
+  mode: LocalAiSemanticSearchMode = 'hybrid',
+  limit?: number
+): Promise<MarkdownFile[]> {
+  return await invoke<MarkdownFile[]>('local_ai_semantic_search', {
+    query,
+    mode,
+    limit
+  });
+}
+
+export async function clearLocalAiEmbeddings(): Promise<number> {
+  return await invoke<number>('local_ai_clear_embeddings');
+}
+
+export type LocalAiTaggingStatus =
+  | 'idle'
+  | 'running'
+  | 'paused'
+  | 'completed'
+  | 'cancelled'
+  | 'failed';
+
+export interface LocalAiTaggingProgress {
+  status: LocalAiTaggingStatus;
+  done: number;
+  total: number;
+  queued: number;
+  failed: number;
+  current?: string;
+  message?: string;
+}
+
+export type LocalAiTagSuggestionStatus = 'pending' | 'accepted' | 'rejected';
+
+export interface LocalAiTagSuggestion {
+  filePath: string;
+  noteId: string;
+  title: string;
+  tags: string[];
+  newTags: string[];
+  summary?: string;
+  status: LocalAiTagSuggestionStatus;
+  createdAt: string;
+}
+
+export const LOCAL_AI_TAGGING_PROGRESS_EVENT = 'local-ai-tagging-progress';
+
+export async function startLocalAiTagging(options?: {
+  maxNewTags?: number;
+  intervalMs?: number;
+}): Promise<LocalAiTaggingProgress> {
+  return await invoke<LocalAiTaggingProgress>('local_ai_start_tagging', {
+    maxNewTags: options?.maxNewTags,
+    intervalMs: options?.intervalMs
+  });
+}
+
+export async function pauseLocalAiTagging(): Promise<LocalAiTaggingProgress> {
+  return await invoke<LocalAiTaggingProgress>('local_ai_pause_tagging');
+}
+
+export async function resumeLocalAiTagging(): Promise<LocalAiTaggingProgress> {
+  return await invoke<LocalAiTaggingProgress>('local_ai_resume_tagging');
+}
+
+export async function cancelLocalAiTagging(): Promise<void> {
+  await invoke('local_ai_cancel_tagging');
+}
+
+export async function getLocalAiTaggingStatus(): Promise<
+  LocalAiTaggingProgress
+> {
+  return await invoke<LocalAiTaggingProgress>('local_ai_get_tagging_status');
+}
+
+export async function listLocalAiTagSuggestions(
+  status: LocalAiTagSuggestionStatus = 'pending'
+): Promise<LocalAiTagSuggestion[]> {
+  return await invoke<LocalAiTagSuggestion[]>(
+    'local_ai_list_tag_suggestions',
+    { status }
+  );
+}
+
+export async function applyLocalAiTagSuggestion(
+  filePath: string,
+  edits?: { tags?: string[]; summary?: string }
+): Promise<void> {
+  await invoke('local_ai_apply_tag_suggestion', {
+    filePath,
+    tags: edits?.tags,
+    summary: edits?.summary
+  });
+}
+
+export async function rejectLocalAiTagSuggestion(
+  filePath: string
+): Promise<void> {
+  await invoke('local_ai_reject_tag_suggestion', { filePath });
+}
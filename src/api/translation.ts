@@ -0,0 +1,292 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+export type TranslationProviderId =
+  | 'bing'
+  | 'google'
+  | 'libretranslate'
+  | 'deepl'
+  | 'openai-compatible'
+  | 'local-ai';
+
+export type TranslationErrorKind =
+  | 'rate-limited'
+  | 'auth'
+  | 'unsupported-pair'
+  | 'not-configured'
+  | 'unavailable'
+  | 'invalid-response';
+
+export interface TranslationProvidersConfig {
+  // 选定的引擎失败后依次尝试的引擎
+  fallbackOrder: TranslationProviderId[];
+  fallbackEnabled: boolean;
+  requestTimeoutSecs: number;
+  libretranslate: {
+    baseUrl?: string | null;
+    apiKey?: string | null;
+  };
+  deepl: {
+    apiKey?: string | null;
+  };
+  openaiCompatible: {
+    baseUrl?: string | null;
+    apiKey?: string | null;
+    model?: string | null;
+  };
+  // 相同原文、语言对和引擎在有效期内复用翻译记忆中的译文
+  memoryEnabled: boolean;
+  memoryTtlDays: number;
+  // 未指定语言时在这两种语言间互译
+  autoPrimaryLanguage: string;
+  autoSecondaryLanguage: string;
+}
+
+export interface TranslationProviderInfo {
+  id: TranslationProviderId;
+  label: string;
+  configured: boolean;
+  // 限流或认证失败后处于冷却期
+  paused?: TranslationErrorKind;
+}
+
+export interface TranslationLanguage {
+  code: string;
+  name: string;
+  source: boolean;
+  target: boolean;
+}
+
+export interface GlossaryTerm {
+  id?: number | null;
+  term: string;
+  // 与原文相同表示保持不译
+  translation: string;
+  // 为空时适用于任意源语言 / 目标语言
+  fromLang: string;
+  toLang: string;
+  caseSensitive: boolean;
+}
+
+export interface TranslationHistoryEntry {
+  id: number;
+  sourceText: string;
+  translatedText: string;
+  fromLang: string;
+  toLang: string;
+  // 实际完成翻译的引擎
+  engine: string;
+  hitCount: number;
+  createdAt: number;
+  lastUsedAt: number;
+  // 超过有效期后不再复用
+  expired: boolean;
+}
+
+export interface TranslationHistoryPage {
+  items: TranslationHistoryEntry[];
+  total: number;
+  offset: number;
+  limit: number;
+}
+
+export type TranslationHistoryExportFormat = 'csv' | 'json' | 'tmx';
+
+export interface LanguageGuess {
+  code: string;
+  confidence: number;
+}
+
+export interface TranslationDirection {
+  // 识别不出时为 auto
+  from: string;
+  to: string;
+  confidence: number;
+  candidates: LanguageGuess[];
+}
+
+export interface NoteTranslationProgress {
+  jobId: string;
+  // 原文相对路径
+  sourcePath: string;
+  status: 'running' | 'completed' | 'failed' | 'cancelled';
+  done: number;
+  total: number;
+  // 从上次中断处直接复用的批次数
+  resumed: number;
+  targetPath?: string;
+  message?: string;
+}
+
+export interface NoteTranslationResult {
+  jobId: string;
+  // 译文笔记的绝对路径
+  targetPath: string;
+  relativePath: string;
+  title: string;
+  chunks: number;
+  resumed: number;
+}
+
+export interface NoteTranslationJob {
+  jobId: string;
+  sourcePath: string;
+  from: string;
+  to: string;
+  done: number;
+  total: number;
+  updatedAt: number;
+}
+
+export async function translateText(
+  text: string,
+  from: string,
+  to: string,
+  engine: TranslationProviderId
+): Promise<string> {
+  return await invoke<string>('translate_text', { text, from, to, engine });
+}
+
+// 离线识别文本语种，from / to 为 auto 或省略时给出自动翻译方向
+export async function detectTextLanguage(
+  text: string,
+  from?: string,
+  to?: string
+): Promise<TranslationDirection> {
+  return await invoke<TranslationDirection>('detect_text_language', {
+    text,
+    from,
+    to
+  });
+}
+
+export async function getTranslationProvidersConfig(): Promise<
+  TranslationProvidersConfig
+> {
+  return await invoke<TranslationProvidersConfig>(
+    'get_translation_providers_config'
+  );
+}
+
+export async function setTranslationProvidersConfig(
+  config: TranslationProvidersConfig
+): Promise<TranslationProvidersConfig> {
+  return await invoke<TranslationProvidersConfig>(
+    'set_translation_providers_config',
+    { config }
+  );
+}
+
+export async function listTranslationProviders(): Promise<
+  TranslationProviderInfo[]
+> {
+  return await invoke<TranslationProviderInfo[]>('list_translation_providers');
+}
+
+// LibreTranslate 与 DeepL 从服务端获取，其余引擎返回应用内的全部语言
+export async function getTranslationLanguages(
+  engine: TranslationProviderId
+): Promise<TranslationLanguage[]> {
+  return await invoke<TranslationLanguage[]>('get_translation_languages', {
+    engine
+  });
+}
+
+export async function listTranslationHistory(options?: {
+  query?: string;
+  engine?: string;
+  offset?: number;
+  limit?: number;
+}): Promise<TranslationHistoryPage> {
+  return await invoke<TranslationHistoryPage>('list_translation_history', {
+    query: options?.query,
+    engine: options?.engine,
+    offset: options?.offset,
+    limit: options?.limit
+  });
+}
+
+export async function deleteTranslationHistory(ids: number[]): Promise<number> {
+  return await invoke<number>('delete_translation_history', { ids });
+}
+
+export async function clearTranslationHistory(): Promise<void> {
+  await invoke('clear_translation_history');
+}
+
+// 未指定格式时按文件扩展名判断，返回导出的条目数
+export async function exportTranslationHistory(
+  path: string,
+  format?: TranslationHistoryExportFormat,
+  query?: string
+): Promise<number> {
+  return await invoke<number>('export_translation_history', {
+    path,
+    format,
+    query
+  });
+}
+
+export async function listTranslationGlossary(): Promise<GlossaryTerm[]> {
+  return await invoke<GlossaryTerm[]>('list_translation_glossary');
+}
+
+export async function saveTranslationGlossaryTerm(
+  term: GlossaryTerm
+): Promise<GlossaryTerm[]> {
+  return await invoke<GlossaryTerm[]>('save_translation_glossary_term', {
+    term
+  });
+}
+
+export async function deleteTranslationGlossaryTerm(
+  id: number
+): Promise<GlossaryTerm[]> {
+  return await invoke<GlossaryTerm[]>('delete_translation_glossary_term', {
+    id
+  });
+}
+
+// 翻译整篇笔记，写为同目录下的 `文件名.语言.md`；失败或取消后再次调用会从中断处继续
+export async function translateNote(
+  filePath: string,
+  from: string,
+  to: string,
+  engine: TranslationProviderId,
+  onProgress?: (progress: NoteTranslationProgress) => void
+): Promise<NoteTranslationResult> {
+  const normalizedPath = filePath.replace(/\\/g, '/');
+  const unlisten = await listen<NoteTranslationProgress>(
+    'note-translation-progress',
+    (event) => {
+      if (normalizedPath.endsWith(event.payload.sourcePath)) {
+        onProgress?.(event.payload);
+      }
+    }
+  );
+
+  try {
+    return await invoke<NoteTranslationResult>('translate_note', {
+      filePath,
+      from,
+      to,
+      engine
+    });
+  } finally {
+    unlisten();
+  }
+}
+
+export async function cancelNoteTranslation(jobId: string): Promise<boolean> {
+  return await invoke<boolean>('cancel_note_translation', { jobId });
+}
+
+export async function listNoteTranslationJobs(): Promise<
+  NoteTranslationJob[]
+> {
+  return await invoke<NoteTranslationJob[]>('list_note_translation_jobs');
+}
+
+export async function discardNoteTranslationJob(jobId: string): Promise<void> {
+  await invoke('discard_note_translation_job', { jobId });
+}
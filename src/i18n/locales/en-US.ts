@@ -310,6 +310,7 @@ export default {
       configFailed: 'Git configuration failed',
       pullSuccess: 'Pull successful, updated {count} files',
       alreadyUpToDate: 'Already up to date',
+      pullAutoMerged: 'Pull successful, auto-merged conflicts in {count} notes: {files}',
       pullFailed: 'Pull failed',
       pullConflicts: 'Pull successful, but conflicts exist',
       conflictError:
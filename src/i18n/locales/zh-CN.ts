@@ -296,6 +296,7 @@ export default {
       configFailed: 'Git 配置失败',
       pullSuccess: '拉取成功，更新了 {count} 个文件',
       alreadyUpToDate: '已是最新版本',
+      pullAutoMerged: '拉取成功，已自动合并 {count} 篇笔记的冲突：{files}',
       pullFailed: '拉取失败',
       pullConflicts: '拉取成功，但存在冲突文件',
       conflictError:
@@ -22,6 +22,8 @@ export interface PullResult {
   untracked_files?: string[];
   last_sync_time?: string;
   branch_selection?: BranchSelection;
+  // 冲突笔记的自动合并结果（含仍需手动解决的笔记）
+  merge_reports?: NoteMergeReport[];
 }
 
 export interface BranchSelection {
@@ -106,6 +108,21 @@ export enum ConflictStrategy {
   KeepRemote = 'KeepRemote',
 }
 
+/**
+ * 笔记三方合并结果
+ */
+export interface NoteMergeReport {
+  file_path: string;
+  // 自动合并的 frontmatter 字段
+  merged_fields: string[];
+  // 两端改成了不同的值，暂时保留本地值
+  conflict_fields: string[];
+  auto_merged_hunks: number;
+  // 两端改动重叠的段落，已用冲突标记包围
+  conflict_hunks: number;
+  merged_content: string;
+}
+
 /**
  * 冲突文件内容
  */
@@ -114,6 +131,8 @@ export interface ConflictFileContent {
   remote_content: string;
   local_content: string;
   base_content: string | null;
+  // 仅 Markdown 笔记在合并冲突中返回
+  merge_report?: NoteMergeReport;
 }
 
 /**
@@ -443,3 +462,118 @@ export async function ensureGitignore(): Promise<boolean> {
     throw new Error(`确保 .gitignore 失败: ${error}`);
   }
 }
+
+/**
+ * 同步后端：Git 仓库、WebDAV 或普通文件夹（Syncthing、Dropbox、网络共享）
+ */
+export type SyncBackendKind = 'git' | 'webdav' | 'folder' | 'encrypted-git';
+
+export interface SyncBackendConfig {
+  kind: SyncBackendKind;
+  webdav: {
+    url: string;
+    username: string;
+    password: string;
+  };
+  folder: {
+    path: string;
+  };
+}
+
+export interface SyncNowResult {
+  pull: PullResult;
+  push: PushResult;
+}
+
+/**
+ * 获取同步后端配置
+ */
+export async function getSyncBackendConfig(): Promise<SyncBackendConfig> {
+  try {
+    return await invoke<SyncBackendConfig>('get_sync_backend_config');
+  } catch (error) {
+    throw new Error(`获取同步后端配置失败: ${error}`);
+  }
+}
+
+/**
+ * 保存同步后端配置（自动同步运行中时按新配置重启）
+ */
+export async function setSyncBackendConfig(
+  config: SyncBackendConfig
+): Promise<SyncBackendConfig> {
+  try {
+    return await invoke<SyncBackendConfig>('set_sync_backend_config', {
+      config
+    });
+  } catch (error) {
+    throw new Error(`保存同步后端配置失败: ${error}`);
+  }
+}
+
+/**
+ * 测试 WebDAV 或同步文件夹能否访问
+ */
+export async function testSyncBackend(
+  config: SyncBackendConfig
+): Promise<string> {
+  try {
+    return await invoke<string>('test_sync_backend', { config });
+  } catch (error) {
+    throw new Error(`测试同步目标失败: ${error}`);
+  }
+}
+
+/**
+ * 立即同步一次（WebDAV、文件夹、加密 Git 后端）：先拉取再推送
+ */
+export async function syncBackendNow(): Promise<SyncNowResult> {
+  try {
+    return await invoke<SyncNowResult>('sync_backend_now');
+  } catch (error) {
+    throw new Error(`同步失败: ${error}`);
+  }
+}
+
+export interface EncryptedSyncStatus {
+  enabled: boolean;
+  keyConfigured: boolean;
+  branch: string | null;
+}
+
+/**
+ * 获取端到端加密同步状态
+ */
+export async function getEncryptedSyncStatus(): Promise<EncryptedSyncStatus> {
+  try {
+    return await invoke<EncryptedSyncStatus>('get_encrypted_sync_status');
+  } catch (error) {
+    throw new Error(`获取加密同步状态失败: ${error}`);
+  }
+}
+
+/**
+ * 用口令启用加密同步：远程仓库已加密时校验口令加入，为空时新建密钥参数
+ */
+export async function setupEncryptedSync(passphrase: string): Promise<string> {
+  try {
+    return await invoke<string>('setup_encrypted_sync', { passphrase });
+  } catch (error) {
+    throw new Error(`启用加密同步失败: ${error}`);
+  }
+}
+
+/**
+ * 把现有明文仓库迁移为加密同步（强制替换远端当前分支的历史）
+ */
+export async function migrateToEncryptedSync(
+  passphrase: string
+): Promise<PushResult> {
+  try {
+    return await invoke<PushResult>('migrate_to_encrypted_sync', {
+      passphrase
+    });
+  } catch (error) {
+    throw new Error(`迁移到加密同步失败: ${error}`);
+  }
+}